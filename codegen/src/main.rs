@@ -18,9 +18,12 @@ fn main() {
         &["proto/health.proto"],
         &["proto"],
         &PathBuf::from("src/generated"),
-        &PathBuf::from("src/generated/grpc_health_v1_fds.rs"),
-        true,
-        true,
+        CodegenOptions {
+            file_descriptor_set_path: Some(&PathBuf::from("src/generated/grpc_health_v1_fds.rs")),
+            extern_paths: &[],
+            build_client: true,
+            build_server: true,
+        },
     );
 
     // tonic-reflection
@@ -32,9 +35,12 @@ fn main() {
         &["proto/reflection_v1.proto"],
         &["proto"],
         &PathBuf::from("src/generated"),
-        &PathBuf::from("src/generated/reflection_v1_fds.rs"),
-        true,
-        true,
+        CodegenOptions {
+            file_descriptor_set_path: Some(&PathBuf::from("src/generated/reflection_v1_fds.rs")),
+            extern_paths: &[],
+            build_client: true,
+            build_server: true,
+        },
     );
     codegen(
         &PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
@@ -44,9 +50,14 @@ fn main() {
         &["proto/reflection_v1alpha.proto"],
         &["proto"],
         &PathBuf::from("src/generated"),
-        &PathBuf::from("src/generated/reflection_v1alpha1_fds.rs"),
-        true,
-        true,
+        CodegenOptions {
+            file_descriptor_set_path: Some(&PathBuf::from(
+                "src/generated/reflection_v1alpha1_fds.rs",
+            )),
+            extern_paths: &[],
+            build_client: true,
+            build_server: true,
+        },
     );
 
     // tonic-types
@@ -58,21 +69,54 @@ fn main() {
         &["proto/status.proto", "proto/error_details.proto"],
         &["proto"],
         &PathBuf::from("src/generated"),
-        &PathBuf::from("src/generated/types_fds.rs"),
-        false,
-        false,
+        CodegenOptions {
+            file_descriptor_set_path: Some(&PathBuf::from("src/generated/types_fds.rs")),
+            extern_paths: &[],
+            build_client: false,
+            build_server: false,
+        },
+    );
+
+    // tonic-lro
+    codegen(
+        &PathBuf::from(std::env!("CARGO_MANIFEST_DIR"))
+            .parent()
+            .unwrap()
+            .join("tonic-lro"),
+        &["proto/google/longrunning/operations.proto"],
+        &["proto", "../tonic-types/proto"],
+        &PathBuf::from("src/generated"),
+        CodegenOptions {
+            file_descriptor_set_path: None,
+            extern_paths: &[(".google.rpc", "::tonic_types::pb")],
+            build_client: true,
+            build_server: true,
+        },
     );
 }
 
+// What is generated for the interface files, besides the messages.
+struct CodegenOptions<'a> {
+    file_descriptor_set_path: Option<&'a Path>,
+    extern_paths: &'a [(&'a str, &'a str)],
+    build_client: bool,
+    build_server: bool,
+}
+
 fn codegen(
     root_dir: &Path,
     iface_files: &[&str],
     include_dirs: &[&str],
     out_dir: &Path,
-    file_descriptor_set_path: &Path,
-    build_client: bool,
-    build_server: bool,
+    options: CodegenOptions<'_>,
 ) {
+    let CodegenOptions {
+        file_descriptor_set_path,
+        extern_paths,
+        build_client,
+        build_server,
+    } = options;
+
     let tempdir = tempfile::Builder::new()
         .prefix("tonic-codegen-")
         .tempdir()
@@ -81,18 +125,21 @@ fn codegen(
     let iface_files = iface_files.iter().map(|&path| root_dir.join(path));
     let include_dirs = include_dirs.iter().map(|&path| root_dir.join(path));
     let out_dir = root_dir.join(out_dir);
-    let file_descriptor_set_path = root_dir.join(file_descriptor_set_path);
 
     let fds = protox::compile(iface_files, include_dirs).unwrap();
 
-    write_fds(&fds, &file_descriptor_set_path);
+    if let Some(file_descriptor_set_path) = file_descriptor_set_path {
+        write_fds(&fds, &root_dir.join(file_descriptor_set_path));
+    }
 
-    tonic_build::configure()
+    let mut builder = tonic_build::configure()
         .build_client(build_client)
         .build_server(build_server)
-        .out_dir(&tempdir)
-        .compile_fds(fds)
-        .unwrap();
+        .out_dir(&tempdir);
+    for (proto_path, rust_path) in extern_paths {
+        builder = builder.extern_path(*proto_path, *rust_path);
+    }
+    builder.compile_fds(fds).unwrap();
 
     for path in std::fs::read_dir(tempdir.path()).unwrap() {
         let path = path.unwrap().path();
@@ -26,6 +26,8 @@ sed -i -E "s/${TONIC_CRATE_MATCHER} = \"${VERSION_MATCHER}\"/\1 = \"${MINOR}\"/"
 CRATES=( \
   "tonic" \
   "tonic-build" \
+  "protoc-gen-tonic" \
+  "tonic-types-derive" \
   "tonic-types" \
   "tonic-reflection" \
   "tonic-health" \
@@ -0,0 +1,183 @@
+//! A `protoc` plugin generating `tonic` gRPC services.
+//!
+//! The plugin reads a `CodeGeneratorRequest` from stdin and writes a
+//! `CodeGeneratorResponse` to stdout, generating the same code as
+//! `tonic-build` does from a build script. See the README for the supported
+//! options.
+
+use std::io::{self, Read, Write};
+
+use prost::Message;
+use prost_types::compiler::{CodeGeneratorRequest, CodeGeneratorResponse};
+use tonic_build::Builder;
+
+fn main() -> io::Result<()> {
+    let mut buf = Vec::new();
+    io::stdin().read_to_end(&mut buf)?;
+
+    let request = CodeGeneratorRequest::decode(buf.as_slice())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+
+    let response = generate(request);
+
+    io::stdout().write_all(&response.encode_to_vec())
+}
+
+fn generate(request: CodeGeneratorRequest) -> CodeGeneratorResponse {
+    match parse_parameter(request.parameter()) {
+        Ok(builder) => builder.generate_plugin_response(request),
+        Err(error) => CodeGeneratorResponse {
+            error: Some(error),
+            ..Default::default()
+        },
+    }
+}
+
+fn parse_parameter(parameter: &str) -> Result<Builder, String> {
+    let mut builder = tonic_build::configure().emit_rerun_if_changed(false);
+
+    for option in parameter.split(',').map(str::trim) {
+        if option.is_empty() {
+            continue;
+        }
+
+        let (name, value) = match option.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (option, None),
+        };
+
+        builder = match name {
+            "build_client" => builder.build_client(parse_bool(name, value)?),
+            "build_server" => builder.build_server(parse_bool(name, value)?),
+            "build_transport" => builder.build_transport(parse_bool(name, value)?),
+            "build_mock" => builder.build_mock(parse_bool(name, value)?),
+            "build_client_trait" => builder.build_client_trait(parse_bool(name, value)?),
+            "client_trait_automock" => builder.client_trait_automock(parse_bool(name, value)?),
+            "build_web_client" => builder.build_web_client(parse_bool(name, value)?),
+            "build_reflection" => builder.build_reflection(parse_bool(name, value)?),
+            "compile_well_known_types" => {
+                builder.compile_well_known_types(parse_bool(name, value)?)
+            }
+            "generate_default_stubs" => builder.generate_default_stubs(parse_bool(name, value)?),
+            "use_arc_self" => builder.use_arc_self(parse_bool(name, value)?),
+            "use_local_futures" => builder.use_local_futures(parse_bool(name, value)?),
+            "serde" => builder.serde(parse_bool(name, value)?),
+            "split_by_file" => builder.split_by_file(parse_bool(name, value)?),
+            "disable_package_emission" => {
+                if parse_bool(name, value)? {
+                    builder.disable_package_emission()
+                } else {
+                    builder
+                }
+            }
+            "extern_path" => {
+                let (proto_path, rust_path) =
+                    parse_value(name, value)?.split_once('=').ok_or_else(|| {
+                        format!("`extern_path` expects `<proto path>=<rust path>`, got `{option}`")
+                    })?;
+                builder.extern_path(proto_path, rust_path)
+            }
+            "proto_path" => builder.proto_path(parse_value(name, value)?),
+            "codec_path" => builder.codec_path(parse_value(name, value)?),
+            _ => return Err(format!("unknown option `{name}`")),
+        };
+    }
+
+    Ok(builder)
+}
+
+fn parse_bool(name: &str, value: Option<&str>) -> Result<bool, String> {
+    match value {
+        None | Some("true") => Ok(true),
+        Some("false") => Ok(false),
+        Some(value) => Err(format!("`{name}` expects `true` or `false`, got `{value}`")),
+    }
+}
+
+fn parse_value<'a>(name: &str, value: Option<&'a str>) -> Result<&'a str, String> {
+    match value {
+        Some(value) if !value.is_empty() => Ok(value),
+        _ => Err(format!("`{name}` expects a value")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prost_types::{
+        DescriptorProto, FileDescriptorProto, MethodDescriptorProto, ServiceDescriptorProto,
+    };
+
+    use super::*;
+
+    fn request(parameter: &str) -> CodeGeneratorRequest {
+        let file = FileDescriptorProto {
+            name: Some("helloworld.proto".to_string()),
+            package: Some("helloworld".to_string()),
+            syntax: Some("proto3".to_string()),
+            message_type: vec![
+                DescriptorProto {
+                    name: Some("HelloRequest".to_string()),
+                    ..Default::default()
+                },
+                DescriptorProto {
+                    name: Some("HelloReply".to_string()),
+                    ..Default::default()
+                },
+            ],
+            service: vec![ServiceDescriptorProto {
+                name: Some("Greeter".to_string()),
+                method: vec![MethodDescriptorProto {
+                    name: Some("SayHello".to_string()),
+                    input_type: Some(".helloworld.HelloRequest".to_string()),
+                    output_type: Some(".helloworld.HelloReply".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        CodeGeneratorRequest {
+            file_to_generate: vec!["helloworld.proto".to_string()],
+            parameter: Some(parameter.to_string()),
+            proto_file: vec![file],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn generates_services() {
+        let response = generate(request("build_server=false, build_mock"));
+
+        assert_eq!(response.error, None);
+        assert_eq!(response.file.len(), 1);
+
+        let file = &response.file[0];
+        assert_eq!(file.name(), "helloworld.rs");
+        assert!(file.content().contains("pub struct HelloRequest"));
+        assert!(file.content().contains("pub struct GreeterClient<T>"));
+        assert!(file.content().contains("pub struct MockGreeterClient"));
+        assert!(!file.content().contains("greeter_server"));
+    }
+
+    #[test]
+    fn invalid_options() {
+        for (parameter, error) in [
+            ("build_foo", "unknown option `build_foo`"),
+            (
+                "build_client=yes",
+                "`build_client` expects `true` or `false`, got `yes`",
+            ),
+            ("proto_path", "`proto_path` expects a value"),
+            (
+                "extern_path=.foo",
+                "`extern_path` expects `<proto path>=<rust path>`, got `extern_path=.foo`",
+            ),
+        ] {
+            let response = generate(request(parameter));
+
+            assert_eq!(response.error.as_deref(), Some(error));
+            assert!(response.file.is_empty());
+        }
+    }
+}
@@ -15,6 +15,8 @@ DIR="$( cd "$( dirname "${BASH_SOURCE[0]}" )" >/dev/null 2>&1 && pwd )"
 CRATES=( \
   "tonic" \
   "tonic-build" \
+  "protoc-gen-tonic" \
+  "tonic-types-derive" \
   "tonic-types" \
   "tonic-reflection" \
   "tonic-health" \
@@ -0,0 +1,20 @@
+use std::{env, path::PathBuf};
+
+fn main() {
+    tonic_build::configure()
+        .build_server(false)
+        .build_client_trait(true)
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+
+    // Only inspected, since compiling it for tests requires `mockall`.
+    let automock_dir = PathBuf::from(env::var("OUT_DIR").unwrap()).join("automock");
+    std::fs::create_dir_all(&automock_dir).unwrap();
+    tonic_build::configure()
+        .build_server(false)
+        .build_client_trait(true)
+        .client_trait_automock(true)
+        .out_dir(automock_dir)
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
@@ -0,0 +1 @@
+tonic::include_proto!("test");
@@ -0,0 +1,77 @@
+use client_trait::{
+    test_client::{TestClient, TestClientApi},
+    Input, Output,
+};
+use tonic::{transport::Channel, Request, Response, Status};
+
+// Code under test only depends on the generated trait.
+async fn double<C: TestClientApi>(client: &mut C, value: i32) -> Result<i32, Status> {
+    let response = client.unary(Request::new(Input { value })).await?;
+    Ok(response.into_inner().value * 2)
+}
+
+struct Fake;
+
+#[tonic::async_trait]
+impl TestClientApi for Fake {
+    async fn unary(&mut self, request: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {
+            value: request.into_inner().value + 1,
+        }))
+    }
+
+    async fn server_stream(
+        &mut self,
+        _: Request<Input>,
+    ) -> Result<Response<tonic::codegen::BoxStream<Output>>, Status> {
+        Err(Status::unimplemented("server_stream"))
+    }
+
+    async fn client_stream(
+        &mut self,
+        _: Request<
+            std::pin::Pin<Box<dyn tonic::codegen::tokio_stream::Stream<Item = Input> + Send>>,
+        >,
+    ) -> Result<Response<Output>, Status> {
+        Err(Status::unimplemented("client_stream"))
+    }
+
+    async fn bidirectional_stream(
+        &mut self,
+        _: Request<
+            std::pin::Pin<Box<dyn tonic::codegen::tokio_stream::Stream<Item = Input> + Send>>,
+        >,
+    ) -> Result<Response<tonic::codegen::BoxStream<Output>>, Status> {
+        Err(Status::unimplemented("bidirectional_stream"))
+    }
+}
+
+#[test]
+fn client_implements_api() {
+    fn assert_api<C: TestClientApi>() {}
+
+    assert_api::<TestClient<Channel>>();
+}
+
+#[tokio::test]
+async fn other_implementation() {
+    assert_eq!(double(&mut Fake, 20).await.unwrap(), 42);
+}
+
+#[test]
+fn without_mock_client() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/test.rs"));
+
+    assert!(generated.contains("pub trait TestClientApi"));
+    assert!(!generated.contains("mockall"));
+    assert!(!generated.contains("MockTestClient"));
+}
+
+#[test]
+fn automock() {
+    let generated = include_str!(concat!(env!("OUT_DIR"), "/automock/test.rs"));
+    let generated = generated.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    assert!(generated
+        .contains("#[cfg_attr(test, mockall::automock)] #[async_trait] pub trait TestClientApi"));
+}
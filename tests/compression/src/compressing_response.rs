@@ -488,3 +488,105 @@ async fn disabling_compression_on_response_from_client_stream(encoding: Compress
     let bytes_sent = response_bytes_counter.load(SeqCst);
     assert!(bytes_sent > UNCOMPRESSED_MIN_BODY_SIZE);
 }
+
+#[allow(dead_code)]
+/// Compresses the messages of at least `.0` bytes, with the encoding the client prefers.
+#[derive(Debug)]
+struct MinMessageSize(usize);
+
+impl tonic::codec::CompressionPolicy for MinMessageSize {
+    fn compress_message(
+        &self,
+        method: &str,
+        _encoding: CompressionEncoding,
+        message_size: usize,
+    ) -> bool {
+        assert_eq!(method, "/test.Test/CompressOutputUnary");
+        message_size >= self.0
+    }
+}
+
+#[allow(dead_code)]
+async fn compress_output_unary_with_policy(
+    encoding: CompressionEncoding,
+    policy: MinMessageSize,
+) -> (Option<String>, usize) {
+    let (client, server) = tokio::io::duplex(UNCOMPRESSED_MIN_BODY_SIZE * 10);
+
+    let svc = test_server::TestServer::new(Svc::default()).send_compression_policy(policy);
+
+    let response_bytes_counter = Arc::new(AtomicUsize::new(0));
+
+    tokio::spawn({
+        let response_bytes_counter = response_bytes_counter.clone();
+        async move {
+            Server::builder()
+                .layer(
+                    ServiceBuilder::new()
+                        .layer(MapResponseBodyLayer::new(move |body| {
+                            util::CountBytesBody {
+                                inner: body,
+                                counter: response_bytes_counter.clone(),
+                            }
+                        }))
+                        .into_inner(),
+                )
+                .add_service(svc)
+                .serve_with_incoming(tokio_stream::once(Ok::<_, std::io::Error>(server)))
+                .await
+                .unwrap();
+        }
+    });
+
+    let mut client =
+        test_client::TestClient::new(mock_io_channel(client).await).accept_compressed(encoding);
+
+    let res = client.compress_output_unary(()).await.unwrap();
+
+    let encoding_header = res
+        .metadata()
+        .get("grpc-encoding")
+        .map(|value| value.to_str().unwrap().to_owned());
+    (encoding_header, response_bytes_counter.load(SeqCst))
+}
+
+util::parametrized_tests! {
+    compression_policy_compressing_large_message,
+    zstd: CompressionEncoding::Zstd,
+    gzip: CompressionEncoding::Gzip,
+    deflate: CompressionEncoding::Deflate,
+}
+
+#[allow(dead_code)]
+async fn compression_policy_compressing_large_message(encoding: CompressionEncoding) {
+    let (encoding_header, bytes_sent) =
+        compress_output_unary_with_policy(encoding, MinMessageSize(UNCOMPRESSED_MIN_BODY_SIZE))
+            .await;
+
+    assert_eq!(
+        encoding_header.as_deref(),
+        Some(encoding.to_string().as_str())
+    );
+    assert!(bytes_sent < UNCOMPRESSED_MIN_BODY_SIZE);
+}
+
+util::parametrized_tests! {
+    compression_policy_skipping_small_message,
+    zstd: CompressionEncoding::Zstd,
+    gzip: CompressionEncoding::Gzip,
+    deflate: CompressionEncoding::Deflate,
+}
+
+#[allow(dead_code)]
+async fn compression_policy_skipping_small_message(encoding: CompressionEncoding) {
+    let (encoding_header, bytes_sent) =
+        compress_output_unary_with_policy(encoding, MinMessageSize(UNCOMPRESSED_MIN_BODY_SIZE * 2))
+            .await;
+
+    // the response is still compressed, with its only message sent as is
+    assert_eq!(
+        encoding_header.as_deref(),
+        Some(encoding.to_string().as_str())
+    );
+    assert!(bytes_sent > UNCOMPRESSED_MIN_BODY_SIZE);
+}
@@ -10,4 +10,22 @@ fn test() {
         .join(" ");
     assert!(s.contains("#[deprecated] pub async fn deprecated("));
     assert!(!s.contains("#[deprecated] pub async fn not_deprecated("));
+
+    assert!(s.contains("#[deprecated] async fn deprecated("));
+    assert!(!s.contains("#[deprecated] async fn not_deprecated("));
+    assert!(s.contains("#[allow(deprecated)] \"/test.Service1/Deprecated\" =>"));
+}
+
+#[test]
+fn deprecated_service() {
+    let path = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("test.rs");
+    let s = fs::read_to_string(path)
+        .unwrap()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert!(s.contains("#[deprecated] #[derive(Debug, Clone)] pub struct Service2Client<T>"));
+    assert!(s.contains("#[deprecated] #[async_trait] pub trait Service2"));
+    assert!(s.contains("#[deprecated] #[derive(Debug)] pub struct Service2Server<T>"));
+    assert!(!s.contains("#[deprecated] #[derive(Debug, Clone)] pub struct Service1Client<T>"));
 }
@@ -0,0 +1,68 @@
+use editions::pb::{request::Nested, test_server, Request, Response};
+use prost::Message;
+
+#[test]
+fn field_presence() {
+    let request = Request {
+        explicit: Some(1),
+        implicit: 2,
+        required: "required".to_string(),
+        packed: vec![],
+        expanded: vec![],
+        nested: Some(Nested::default()),
+        delimited: None,
+    };
+    assert_eq!(request.explicit, Some(1));
+
+    let response = Response {
+        value: String::new(),
+        count: None,
+    };
+    assert_eq!(response.encoded_len(), 0);
+}
+
+#[test]
+fn encodings() {
+    let packed = Request {
+        packed: vec![1, 2],
+        ..Default::default()
+    };
+    // The required field is always encoded.
+    assert_eq!(packed.encode_to_vec(), [0x1a, 0x00, 0x22, 0x02, 0x01, 0x02]);
+
+    let expanded = Request {
+        expanded: vec![1, 2],
+        ..Default::default()
+    };
+    assert_eq!(
+        expanded.encode_to_vec(),
+        [0x1a, 0x00, 0x28, 0x01, 0x28, 0x02]
+    );
+
+    let delimited = Request {
+        delimited: Some(Nested::default()),
+        ..Default::default()
+    };
+    assert_eq!(delimited.encode_to_vec(), [0x1a, 0x00, 0x3b, 0x3c]);
+}
+
+#[test]
+fn server_trait() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn call(
+            &self,
+            request: tonic::Request<Request>,
+        ) -> Result<tonic::Response<Response>, tonic::Status> {
+            let request = request.into_inner();
+            Ok(tonic::Response::new(Response {
+                value: request.required,
+                count: request.explicit,
+            }))
+        }
+    }
+
+    let _ = test_server::TestServer::new(Svc);
+}
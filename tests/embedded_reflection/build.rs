@@ -0,0 +1,7 @@
+fn main() {
+    tonic_build::configure()
+        .build_client(false)
+        .build_reflection(true)
+        .compile_protos(&["proto/library.proto"], &["proto"])
+        .unwrap();
+}
@@ -0,0 +1,28 @@
+use embedded_reflection::pb::{catalog_server, library_server};
+use prost::Message;
+use prost_types::FileDescriptorSet;
+
+#[test]
+fn file_descriptor_set_includes_imports() {
+    let fds = FileDescriptorSet::decode(library_server::FILE_DESCRIPTOR_SET).unwrap();
+
+    let names = fds.file.iter().map(|file| file.name()).collect::<Vec<_>>();
+
+    assert_eq!(
+        names,
+        ["book.proto", "google/protobuf/empty.proto", "library.proto"]
+    );
+    assert_eq!(
+        catalog_server::FILE_DESCRIPTOR_SET,
+        library_server::FILE_DESCRIPTOR_SET
+    );
+}
+
+#[test]
+fn register_reflection() {
+    let builder = tonic_reflection::server::Builder::configure();
+    let builder = library_server::register_reflection(builder);
+    let builder = catalog_server::register_reflection(builder);
+
+    builder.build_v1().unwrap();
+}
@@ -0,0 +1,7 @@
+fn main() {
+    tonic_build::configure()
+        .field_mask_message(".test.Book")
+        .field_mask_message(".test.UpdateBookRequest")
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
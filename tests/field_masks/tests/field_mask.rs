@@ -0,0 +1,76 @@
+use field_masks::pb::{author::Address, book::Format, Author, Book, UpdateBookRequest};
+use tonic::{Code, FieldMaskMessage};
+
+fn book() -> Book {
+    Book {
+        title: "Dune".to_string(),
+        summary: "Spice".to_string(),
+        author: Some(Author {
+            name: "Frank Herbert".to_string(),
+            address: Some(Address {
+                street: "1 Main St".to_string(),
+                city: "Tacoma".to_string(),
+            }),
+        }),
+        tags: vec!["sci-fi".to_string()],
+        format: Some(Format::Pages(412)),
+    }
+}
+
+#[test]
+fn empty_masks_keep_all_fields() {
+    let mut masked = book();
+    masked.apply_field_mask::<&str>(&[]);
+    assert_eq!(masked, book());
+
+    masked.apply_field_mask(&["title", "*"]);
+    assert_eq!(masked, book());
+}
+
+#[test]
+fn masks_clear_unselected_fields() {
+    let mut masked = book();
+    masked.apply_field_mask(&["title", "author.address.city", "tags"]);
+
+    assert_eq!(masked.title, "Dune");
+    assert_eq!(masked.summary, "");
+    assert_eq!(masked.tags, ["sci-fi"]);
+    assert_eq!(masked.format, None);
+
+    let author = masked.author.unwrap();
+    assert_eq!(author.name, "");
+    let address = author.address.unwrap();
+    assert_eq!(address.street, "");
+    assert_eq!(address.city, "Tacoma");
+
+    let mut masked = book();
+    masked.apply_field_mask(&["author", "pages"]);
+    assert_eq!(masked.author, book().author);
+    assert_eq!(masked.format, Some(Format::Pages(412)));
+
+    masked.apply_field_mask(&["ebook_url"]);
+    assert_eq!(masked.author, None);
+    assert_eq!(masked.format, None);
+}
+
+#[test]
+fn checks_mask_paths() {
+    assert!(Book::check_field_mask(&["title", "author.address.city", "pages"]).is_ok());
+    assert!(Book::check_field_mask(&["*"]).is_ok());
+
+    let status = Book::check_field_mask(&["title", "author.birthday"]).unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(
+        status.message(),
+        "invalid field mask path `author.birthday`"
+    );
+
+    assert!(!Book::is_field_path("tags.name"));
+    assert!(!Book::is_field_path("title.length"));
+    assert!(!Book::is_field_path(""));
+
+    // Fields of extern types are kept or cleared as a whole.
+    assert!(UpdateBookRequest::is_field_path("update_mask"));
+    assert!(!UpdateBookRequest::is_field_path("update_mask.paths"));
+    assert!(UpdateBookRequest::is_field_path("book.author.name"));
+}
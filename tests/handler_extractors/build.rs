@@ -0,0 +1,15 @@
+fn main() {
+    tonic_build::configure()
+        .handler_extractor("test.Accounts", "crate::User")
+        .handler_extractor(
+            "test.Accounts.Whoami",
+            "tonic::server::Extension<crate::Tenant>",
+        )
+        .handler_extractor("test.Accounts.Whoami", "Option<crate::User>")
+        .handler_extractor(
+            "test.Accounts.ServerInfo",
+            "tonic::server::State<crate::AppState>",
+        )
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
@@ -0,0 +1,83 @@
+use std::pin::Pin;
+
+use tokio_stream::Stream;
+use tonic::{
+    server::{Extension, FromRequest, State},
+    Request, Response, Status,
+};
+
+pub mod pb {
+    tonic::include_proto!("test");
+}
+
+use pb::{Empty, Identity, Server};
+
+/// The user of a request, from its `x-user` metadata.
+#[derive(Debug, Clone)]
+pub struct User(pub String);
+
+impl FromRequest for User {
+    fn from_request<T>(request: &Request<T>) -> Result<Self, Status> {
+        request
+            .metadata()
+            .get("x-user")
+            .and_then(|user| user.to_str().ok())
+            .map(|user| User(user.to_string()))
+            .ok_or_else(|| Status::unauthenticated("missing user"))
+    }
+}
+
+/// The tenant of a request, inserted by an interceptor.
+#[derive(Debug, Clone)]
+pub struct Tenant(pub String);
+
+/// The state of the server.
+#[derive(Debug)]
+pub struct AppState {
+    pub name: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Svc;
+
+#[tonic::async_trait]
+impl pb::accounts_server::Accounts for Svc {
+    async fn whoami(
+        &self,
+        user: User,
+        Extension(tenant): Extension<Tenant>,
+        option: Option<User>,
+        _: Request<Empty>,
+    ) -> Result<Response<Identity>, Status> {
+        assert_eq!(option.map(|user| user.0), Some(user.0.clone()));
+        Ok(Response::new(Identity {
+            user: user.0,
+            tenant: tenant.0,
+        }))
+    }
+
+    type WatchStream = Pin<Box<dyn Stream<Item = Result<Identity, Status>> + Send + 'static>>;
+
+    async fn watch(
+        &self,
+        user: User,
+        _: Request<Empty>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let identity = Identity {
+            user: user.0,
+            tenant: String::new(),
+        };
+        Ok(Response::new(Box::pin(tokio_stream::once(Ok(identity)))))
+    }
+
+    async fn server_info(
+        &self,
+        _: User,
+        state: State<AppState>,
+        _: Request<Empty>,
+    ) -> Result<Response<Server>, Status> {
+        Ok(Response::new(Server {
+            name: state.name.clone(),
+        }))
+    }
+}
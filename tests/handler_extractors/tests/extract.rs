@@ -0,0 +1,79 @@
+use handler_extractors::{
+    pb::{accounts_client::AccountsClient, accounts_server::AccountsServer, Empty},
+    AppState, Svc, Tenant,
+};
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tonic::{
+    service::interceptor::InterceptedService,
+    transport::{server::TcpIncoming, Channel, Server},
+    Code, Request, Status,
+};
+
+async fn spawn() -> Channel {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let state = Arc::new(AppState {
+        name: "accounts-1".to_string(),
+    });
+    let svc = InterceptedService::new(
+        AccountsServer::with_state(Svc, state),
+        |mut req: Request<()>| {
+            req.extensions_mut().insert(Tenant("acme".to_string()));
+            Ok::<_, Status>(req)
+        },
+    );
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+fn request() -> Request<Empty> {
+    let mut req = Request::new(Empty {});
+    req.metadata_mut()
+        .insert("x-user", "alice".parse().unwrap());
+    req
+}
+
+#[tokio::test]
+async fn handlers_receive_extracted_arguments() {
+    let mut client = AccountsClient::new(spawn().await);
+
+    let identity = client.whoami(request()).await.unwrap().into_inner();
+    assert_eq!(identity.user, "alice");
+    assert_eq!(identity.tenant, "acme");
+
+    let mut stream = client.watch(request()).await.unwrap().into_inner();
+    let identity = stream.next().await.unwrap().unwrap();
+    assert_eq!(identity.user, "alice");
+}
+
+#[tokio::test]
+async fn handlers_receive_server_state() {
+    let mut client = AccountsClient::new(spawn().await);
+
+    let server = client.server_info(request()).await.unwrap().into_inner();
+    assert_eq!(server.name, "accounts-1");
+}
+
+#[tokio::test]
+async fn failed_extractions_are_answered_with_their_status() {
+    let mut client = AccountsClient::new(spawn().await);
+
+    let status = client.whoami(Empty {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    assert_eq!(status.message(), "missing user");
+
+    let status = client.watch(Empty {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
@@ -0,0 +1,6 @@
+fn main() {
+    tonic_build::configure()
+        .build_http_routes(true)
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
@@ -0,0 +1,43 @@
+use http_routes::pb::{shelves_methods, unannotated_methods};
+
+#[test]
+fn routes() {
+    let routes = shelves_methods::HTTP_ROUTES
+        .iter()
+        .map(|route| {
+            (
+                route.method().method(),
+                route.http_method(),
+                route.path_template(),
+                route.body(),
+                route.response_body(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        routes,
+        [
+            ("GetShelf", "GET", "/v1/{name=shelves/*}", None, None),
+            ("GetShelf", "GET", "/v1/shelves/{name}", None, None),
+            (
+                "CreateShelf",
+                "POST",
+                "/v1/shelves",
+                Some("shelf"),
+                Some("name")
+            ),
+            ("Describe", "HEAD", "/v1/{name=shelves/*}", None, None),
+        ]
+    );
+
+    assert_eq!(
+        *shelves_methods::HTTP_ROUTES[0].method(),
+        shelves_methods::get_shelf::DESCRIPTOR
+    );
+}
+
+#[test]
+fn no_routes() {
+    assert!(unannotated_methods::HTTP_ROUTES.is_empty());
+}
@@ -0,0 +1,41 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::transport::{
+    server::{AcceptRateLimit, TcpIncoming},
+    Endpoint, Server,
+};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn closes_connections_beyond_limit() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming =
+        TcpIncoming::from(listener).with_accept_rate_limit(Some(AcceptRateLimit::per_ip(2)));
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap();
+    });
+
+    let mut results = Vec::new();
+    for _ in 0..3 {
+        let channel = Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect_lazy();
+        let mut client = test_client::TestClient::new(channel);
+        results.push(client.unary_call(Input {}).await.is_ok());
+    }
+    assert_eq!(results, [true, true, false]);
+}
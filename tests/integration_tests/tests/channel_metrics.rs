@@ -0,0 +1,130 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use http::Uri;
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::net::TcpListener;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    transport::{channel::MetricsSink, server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1::default()))
+    }
+
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let stream = tokio_stream::iter([Ok(Output1::default()), Ok(Output1::default())]);
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[derive(Debug, Default)]
+struct Recorded {
+    queue_times: usize,
+    pick_latencies: usize,
+    connects: Vec<Uri>,
+    in_flight: Vec<usize>,
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Recorded>>);
+
+impl MetricsSink for Recorder {
+    fn queue_time(&self, _: &Uri, _: Duration) {
+        self.0.lock().unwrap().queue_times += 1;
+    }
+
+    fn pick_latency(&self, _: &Uri, _: Duration) {
+        self.0.lock().unwrap().pick_latencies += 1;
+    }
+
+    fn connect_latency(&self, endpoint: &Uri, _: Duration) {
+        self.0.lock().unwrap().connects.push(endpoint.clone());
+    }
+
+    fn in_flight(&self, _: &Uri, in_flight: usize) {
+        self.0.lock().unwrap().in_flight.push(in_flight);
+    }
+}
+
+async fn spawn() -> Endpoint {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    Endpoint::from_shared(format!("http://{addr}")).unwrap()
+}
+
+#[tokio::test]
+async fn requests_and_connections_are_measured() {
+    let endpoint = spawn().await;
+    let uri = endpoint.uri().clone();
+    let recorder = Recorder::default();
+    let channel = endpoint.metrics_sink(recorder.clone()).connect_lazy();
+    let mut client = Test1Client::new(channel);
+
+    client.unary_call(Input1::default()).await.unwrap();
+    {
+        let recorded = recorder.0.lock().unwrap();
+        assert_eq!(recorded.connects, [uri]);
+        assert_eq!(recorded.queue_times, 1);
+        assert_eq!(recorded.pick_latencies, 1);
+        assert_eq!(recorded.in_flight, [1, 0]);
+    }
+
+    let mut stream = client
+        .stream_call(Input1::default())
+        .await
+        .unwrap()
+        .into_inner();
+    stream.next().await.unwrap().unwrap();
+    // The stream is in flight until its body ends.
+    assert_eq!(recorder.0.lock().unwrap().in_flight, [1, 0, 1]);
+    while stream.next().await.is_some() {}
+
+    let recorded = recorder.0.lock().unwrap();
+    assert_eq!(recorded.connects.len(), 1);
+    assert_eq!(recorded.queue_times, 2);
+    assert_eq!(recorded.in_flight, [1, 0, 1, 0]);
+}
+
+#[tokio::test]
+async fn concurrent_requests_are_counted_in_flight() {
+    let endpoint = spawn().await;
+    let recorder = Recorder::default();
+    let channel = endpoint
+        .metrics_sink(recorder.clone())
+        .connect()
+        .await
+        .unwrap();
+    let client = Test1Client::new(channel);
+
+    let mut first = client.clone();
+    let mut second = client.clone();
+    let first = first.stream_call(Input1::default()).await.unwrap();
+    let second = second.stream_call(Input1::default()).await.unwrap();
+    assert_eq!(recorder.0.lock().unwrap().in_flight, [1, 2]);
+
+    drop(first);
+    drop(second);
+    assert_eq!(recorder.0.lock().unwrap().in_flight, [1, 2, 1, 0]);
+}
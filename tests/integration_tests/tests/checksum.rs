@@ -0,0 +1,126 @@
+use bytes::Bytes;
+use http_body_util::BodyExt;
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::net::TcpListener;
+use tokio_stream::Stream;
+use tonic::service::ChecksumLayer;
+use tonic::transport::{server::TcpIncoming, Channel, Server};
+use tonic::{body::Body, Code, Request, Response, Status};
+use tower::ServiceBuilder;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1 {
+            buf: req.into_inner().buf,
+        }))
+    }
+
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        Err(Status::unimplemented(""))
+    }
+}
+
+async fn spawn(checksum: bool) -> Channel {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let svc = test1_server::Test1Server::new(Svc);
+    let incoming = TcpIncoming::from(listener);
+    tokio::spawn(async move {
+        if checksum {
+            Server::builder()
+                .layer(ChecksumLayer::new())
+                .add_service(svc)
+                .serve_with_incoming(incoming)
+                .await
+                .unwrap();
+        } else {
+            Server::builder()
+                .add_service(svc)
+                .serve_with_incoming(incoming)
+                .await
+                .unwrap();
+        }
+    });
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+// Flips the last byte of each data frame of the responses, i.e. of the `buf` of the messages.
+fn corrupt(response: http::Response<Body>) -> http::Response<Body> {
+    response.map(|body| {
+        Body::new(body.map_frame(|frame| match frame.into_data() {
+            Ok(data) if !data.is_empty() => {
+                let mut data = data.to_vec();
+                *data.last_mut().unwrap() ^= 0xff;
+                http_body::Frame::data(Bytes::from(data))
+            }
+            Ok(data) => http_body::Frame::data(data),
+            Err(frame) => frame,
+        }))
+    })
+}
+
+fn input() -> Input1 {
+    Input1 { buf: vec![7; 1024] }
+}
+
+#[tokio::test]
+async fn verifies_checksums() {
+    let channel = ServiceBuilder::new()
+        .layer(ChecksumLayer::verify())
+        .service(spawn(true).await);
+    let mut client = Test1Client::new(channel);
+
+    let response = client.unary_call(input()).await.unwrap();
+    assert!(response.metadata().contains_key("checksum-crc32c-bin"));
+    assert_eq!(response.into_inner().buf, input().buf);
+}
+
+#[tokio::test]
+async fn detects_corruption() {
+    let channel = spawn(true).await;
+
+    // Without verification, the corrupted message is decoded as is.
+    let unverified = ServiceBuilder::new()
+        .map_response(corrupt)
+        .service(channel.clone());
+    let output = Test1Client::new(unverified)
+        .unary_call(input())
+        .await
+        .unwrap();
+    assert_ne!(output.into_inner().buf, input().buf);
+
+    let verified = ServiceBuilder::new()
+        .layer(ChecksumLayer::verify())
+        .map_response(corrupt)
+        .service(channel);
+    let status = Test1Client::new(verified)
+        .unary_call(input())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::DataLoss);
+}
+
+#[tokio::test]
+async fn skips_responses_without_checksum() {
+    let channel = ServiceBuilder::new()
+        .layer(ChecksumLayer::verify())
+        .map_response(corrupt)
+        .service(spawn(false).await);
+    let mut client = Test1Client::new(channel);
+
+    let response = client.unary_call(input()).await.unwrap();
+    assert!(!response.metadata().contains_key("checksum-crc32c-bin"));
+}
@@ -0,0 +1,87 @@
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use http::uri::PathAndQuery;
+use tokio::net::TcpListener;
+use tonic::codec::chunk::{self, Chunk, ChunkCodec};
+use tonic::codec::Streaming;
+use tonic::service::{method::ClientStreamingMethod, Routes};
+use tonic::transport::{server::TcpIncoming, Channel, Server};
+use tonic::{client::Grpc, Code, Request, Response, Status};
+
+const MAX_LEN: usize = 16 * 1024 * 1024;
+
+async fn spawn(received: Arc<Mutex<Option<Bytes>>>) -> Channel {
+    let upload = ClientStreamingMethod::new(
+        ChunkCodec,
+        tower::service_fn(move |request: Request<Streaming<Chunk>>| {
+            let received = received.clone();
+            async move {
+                let payload = chunk::reassemble(request.into_inner(), MAX_LEN).await?;
+                *received.lock().unwrap() = Some(payload);
+                Ok::<_, Status>(Response::new(Chunk::empty()))
+            }
+        }),
+    );
+    let routes = Routes::default().add_method("/files.Files/Upload", upload);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_routes(routes)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+async fn upload(channel: Channel, chunks: Vec<Chunk>) -> Result<Chunk, Status> {
+    let mut grpc = Grpc::new(channel);
+    grpc.ready().await.unwrap();
+    let request = Request::new(tokio_stream::iter(chunks));
+    let path = PathAndQuery::from_static("/files.Files/Upload");
+    let response = grpc.client_streaming(request, path, ChunkCodec).await?;
+    Ok(response.into_inner())
+}
+
+#[tokio::test]
+async fn transfers_payload_larger_than_max_message_size() {
+    let received = Arc::new(Mutex::new(None));
+    let channel = spawn(received.clone()).await;
+
+    // Larger than the default max decoding message size of 4MiB.
+    let payload = Bytes::from(
+        (0..=255)
+            .cycle()
+            .take(10 * 1024 * 1024)
+            .collect::<Vec<u8>>(),
+    );
+    let chunks = chunk::split(payload.clone(), 1024 * 1024).collect::<Vec<_>>();
+    assert_eq!(chunks.len(), 10);
+
+    assert_eq!(upload(channel, chunks).await.unwrap(), Chunk::empty());
+    assert_eq!(received.lock().unwrap().take(), Some(payload));
+}
+
+#[tokio::test]
+async fn rejects_invalid_payloads() {
+    let received = Arc::new(Mutex::new(None));
+    let channel = spawn(received.clone()).await;
+
+    let mut chunks = chunk::split(Bytes::from(vec![1; 4096]), 1024).collect::<Vec<_>>();
+    chunks.pop();
+    let status = upload(channel.clone(), chunks).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let too_large = Chunk::new(0, MAX_LEN as u64 + 1, Bytes::from(vec![1; 1024]));
+    let status = upload(channel, vec![too_large]).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+
+    assert!(received.lock().unwrap().is_none());
+}
@@ -0,0 +1,101 @@
+use std::net::SocketAddr;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::service::CircuitBreakerLayer;
+use tonic::transport::{server::TcpIncoming, Channel, Endpoint, Server};
+use tonic::{Code, Request, Response, Status};
+
+struct Svc {
+    fail: bool,
+    calls: Arc<AtomicUsize>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _req: Request<Input>) -> Result<Response<Output>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.fail {
+            return Err(Status::unavailable("failing"));
+        }
+        Ok(Response::new(Output {}))
+    }
+}
+
+fn breaker() -> CircuitBreakerLayer {
+    CircuitBreakerLayer::new()
+        .window_size(2)
+        .minimum_calls(2)
+        .open_duration(Duration::from_secs(60))
+}
+
+#[tokio::test]
+async fn fails_fast_while_open() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let addr = spawn(true, calls.clone()).await;
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let channel = tower::ServiceBuilder::new()
+        .layer(breaker())
+        .service(channel);
+    let mut client = test_client::TestClient::new(channel);
+
+    for _ in 0..2 {
+        let status = client.unary_call(Input {}).await.unwrap_err();
+        assert_eq!(status.message(), "failing");
+    }
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(status.message(), "circuit breaker is open");
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn balancer_avoids_open_endpoints() {
+    let failing_calls = Arc::new(AtomicUsize::new(0));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let endpoints = [
+        spawn(true, failing_calls.clone()).await,
+        spawn(false, calls.clone()).await,
+    ]
+    .map(|addr| {
+        Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .circuit_breaker(breaker())
+    });
+    let mut client = test_client::TestClient::new(Channel::balance_list(endpoints.into_iter()));
+
+    // Call until the breaker of the failing endpoint opens.
+    for _ in 0..50 {
+        let _ = client.unary_call(Input {}).await;
+    }
+    assert_eq!(failing_calls.load(Ordering::SeqCst), 2);
+
+    for _ in 0..20 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(failing_calls.load(Ordering::SeqCst), 2);
+    assert_eq!(calls.load(Ordering::SeqCst), 68);
+}
+
+async fn spawn(fail: bool, calls: Arc<AtomicUsize>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc { fail, calls }))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    addr
+}
@@ -0,0 +1,133 @@
+use std::sync::{Arc, Mutex};
+
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::net::TcpListener;
+use tokio_stream::{Stream, StreamExt};
+use tonic::service::{
+    completion::Completion, interceptor::InterceptedService, CompletionLayer, Interceptor,
+};
+use tonic::transport::{server::TcpIncoming, Channel, Server};
+use tonic::{Code, Request, Response, Status};
+use tower::Layer;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let buf = req.into_inner().buf;
+        if buf.is_empty() {
+            return Err(Status::invalid_argument("empty buf"));
+        }
+        Ok(Response::new(Output1 { buf }))
+    }
+
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let buf = req.into_inner().buf;
+        let messages = vec![Ok(Output1 { buf: buf.clone() }), Ok(Output1 { buf })];
+        Ok(Response::new(Box::pin(tokio_stream::iter(messages))))
+    }
+}
+
+async fn spawn() -> Channel {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let svc = test1_server::Test1Server::new(Svc);
+    let incoming = TcpIncoming::from(listener);
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap();
+    });
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+// The state stashed by the interceptor, the name of the call.
+#[derive(Clone)]
+struct CallName(&'static str);
+
+#[derive(Clone)]
+struct StashName(&'static str);
+
+impl Interceptor for StashName {
+    fn call(&mut self, mut req: Request<()>) -> Result<Request<()>, Status> {
+        req.extensions_mut().insert(CallName(self.0));
+        Ok(req)
+    }
+}
+
+type Completed = Arc<Mutex<Vec<(&'static str, Code)>>>;
+type Hook = Box<dyn Fn(CallName, &Status) + Send + Sync>;
+type Client = Test1Client<InterceptedService<Completion<Channel, CallName, Hook>, StashName>>;
+
+fn client(channel: Channel, completed: Completed, name: &'static str) -> Client {
+    let hook: Hook = Box::new(move |call, status| {
+        completed.lock().unwrap().push((call.0, status.code()));
+    });
+    let channel = CompletionLayer::new(hook).layer(channel);
+    Test1Client::with_interceptor(channel, StashName(name))
+}
+
+#[tokio::test]
+async fn hook_receives_state_and_status_of_unary_calls() {
+    let channel = spawn().await;
+    let completed = Completed::default();
+
+    let mut ok = client(channel.clone(), completed.clone(), "ok");
+    ok.unary_call(Input1 { buf: vec![1] }).await.unwrap();
+
+    let mut failing = client(channel, completed.clone(), "failing");
+    let status = failing
+        .unary_call(Input1 { buf: vec![] })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    assert_eq!(
+        *completed.lock().unwrap(),
+        [("ok", Code::Ok), ("failing", Code::InvalidArgument)]
+    );
+}
+
+#[tokio::test]
+async fn hook_is_called_once_streams_end() {
+    let channel = spawn().await;
+    let completed = Completed::default();
+    let mut client = client(channel, completed.clone(), "stream");
+
+    let mut stream = client
+        .stream_call(Input1 { buf: vec![1] })
+        .await
+        .unwrap()
+        .into_inner();
+    stream.next().await.unwrap().unwrap();
+    assert!(completed.lock().unwrap().is_empty());
+    stream.next().await.unwrap().unwrap();
+    assert!(stream.next().await.is_none());
+    assert_eq!(*completed.lock().unwrap(), [("stream", Code::Ok)]);
+
+    // Dropping a stream before its end cancels the call.
+    let mut stream = client
+        .stream_call(Input1 { buf: vec![1] })
+        .await
+        .unwrap()
+        .into_inner();
+    stream.next().await.unwrap().unwrap();
+    drop(stream);
+    assert_eq!(
+        *completed.lock().unwrap(),
+        [("stream", Code::Ok), ("stream", Code::Cancelled)]
+    );
+}
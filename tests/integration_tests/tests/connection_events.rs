@@ -0,0 +1,72 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::transport::{channel::ConnectionEvent, server::TcpIncoming, Endpoint, Server};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn emits_connection_events() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel::<()>();
+    let server = tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connection_events({
+            let events = events.clone();
+            move |event| events.lock().unwrap().push(event)
+        })
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(
+        *events.lock().unwrap(),
+        [ConnectionEvent::Connected {
+            addr: Some(addr),
+            alpn: None,
+            tls_version: None,
+        }]
+    );
+
+    // The server closes the connection when shutting down.
+    tx.send(()).unwrap();
+    server.await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    client.unary_call(Input {}).await.unwrap_err();
+
+    // Then the channel fails to reconnect, and schedules another reconnect.
+    let events = events.lock().unwrap();
+    assert_eq!(
+        events[1..],
+        [
+            ConnectionEvent::Disconnected { reason: None },
+            ConnectionEvent::ReconnectScheduled {
+                backoff: Duration::ZERO
+            },
+            ConnectionEvent::ReconnectScheduled {
+                backoff: Duration::ZERO
+            },
+        ]
+    );
+}
@@ -0,0 +1,93 @@
+use std::sync::{Arc, Mutex};
+
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::transport::{server::TcpIncoming, ConnectionId, Endpoint, PeerSettings, Server};
+use tonic::{Request, Response, Status};
+
+struct Svc(Arc<Mutex<Vec<ConnectionId>>>);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let id = *req.extensions().get::<ConnectionId>().unwrap();
+        self.0.lock().unwrap().push(id);
+        Ok(Response::new(Output {}))
+    }
+}
+
+struct SettingsSvc(Arc<Mutex<Option<PeerSettings>>>);
+
+#[tonic::async_trait]
+impl test_server::Test for SettingsSvc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        *self.0.lock().unwrap() = req.extensions().get::<PeerSettings>().copied();
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn identifies_connections() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server_ids = Arc::new(Mutex::new(Vec::new()));
+    let svc = test_server::TestServer::new(Svc(server_ids.clone()));
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client_ids = Vec::new();
+    for _ in 0..2 {
+        let channel = Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = TestClient::new(channel);
+        for _ in 0..2 {
+            let res = client.unary_call(Input {}).await.unwrap();
+            client_ids.push(*res.extensions().get::<ConnectionId>().unwrap());
+        }
+    }
+
+    for ids in [client_ids, server_ids.lock().unwrap().clone()] {
+        assert_eq!(ids[0], ids[1]);
+        assert_eq!(ids[2], ids[3]);
+        assert_ne!(ids[0], ids[2]);
+    }
+}
+
+#[tokio::test]
+async fn exposes_peer_settings() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server_settings = Arc::new(Mutex::new(None));
+    let svc = test_server::TestServer::new(SettingsSvc(server_settings.clone()));
+    tokio::spawn(async move {
+        Server::builder()
+            .max_concurrent_streams(7)
+            .add_service(svc)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .initial_stream_window_size(1024 * 1024)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+    let res = client.unary_call(Input {}).await.unwrap();
+
+    let client_settings = *res.extensions().get::<PeerSettings>().unwrap();
+    assert_eq!(client_settings.max_concurrent_streams(), Some(7));
+
+    let server_settings = server_settings.lock().unwrap().unwrap();
+    assert_eq!(server_settings.initial_window_size(), Some(1024 * 1024));
+}
@@ -0,0 +1,70 @@
+use std::{net::SocketAddr, time::Duration};
+
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Endpoint, Server},
+    Request, Response, Status,
+};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn serve() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap()
+    });
+    addr
+}
+
+#[tokio::test]
+async fn connection_tasks_complete_after_channel_drop() {
+    let addr = serve().await;
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let tasks = channel.connection_tasks();
+
+    let mut client = test_client::TestClient::new(channel);
+    client.unary_call(Input {}).await.unwrap();
+    assert_eq!(tasks.len(), 1);
+
+    drop(client);
+    tokio::time::timeout(Duration::from_secs(5), tasks.wait())
+        .await
+        .expect("connection tasks should complete once the channel is dropped");
+    assert!(tasks.is_empty());
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_connection_tasks() {
+    let addr = serve().await;
+    let endpoint = Endpoint::from_shared(format!("http://{addr}")).unwrap();
+    let channel = Channel::balance_list(std::iter::once(endpoint));
+    let tasks = channel.connection_tasks();
+
+    let mut client = test_client::TestClient::new(channel.clone());
+    client.unary_call(Input {}).await.unwrap();
+    drop(client);
+    assert_eq!(tasks.len(), 1);
+
+    tokio::time::timeout(Duration::from_secs(5), channel.shutdown())
+        .await
+        .expect("shutdown should complete once the channel is dropped");
+    assert!(tasks.is_empty());
+}
@@ -0,0 +1,130 @@
+use std::{pin::Pin, time::Duration};
+
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::{net::TcpListener, sync::oneshot, task::JoinHandle};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    codegen::{response_channel, ResponseStream},
+    transport::{
+        server::{Drain, TcpIncoming},
+        Channel, Server,
+    },
+    Request, Response, Status,
+};
+
+const FINAL: &[u8] = b"final";
+
+// Streams updates until the connection drains, then a final message, or forever if asked to
+// ignore the draining.
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Err(Status::unimplemented(""))
+    }
+
+    type StreamCallStream = ResponseStream<Output1>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let drain = req.extensions().get::<Drain>().cloned().unwrap();
+        let ignore_drain = !req.into_inner().buf.is_empty();
+        let (tx, rx) = response_channel(1);
+        tokio::spawn(async move {
+            assert!(!drain.is_draining());
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(Duration::from_millis(10)) => {
+                        if tx.send(Ok(Output1::default())).await.is_err() {
+                            return;
+                        }
+                    }
+                    _ = drain.draining(), if !ignore_drain => break,
+                }
+            }
+            assert!(drain.is_draining());
+            let _ = tx
+                .send(Ok(Output1 {
+                    buf: FINAL.to_vec(),
+                }))
+                .await;
+        });
+        Ok(Response::new(rx))
+    }
+}
+
+async fn spawn(mut server: Server) -> (Channel, oneshot::Sender<()>, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (signal_tx, signal_rx) = oneshot::channel::<()>();
+    let serve = tokio::spawn(async move {
+        server
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async {
+                let _ = signal_rx.await;
+            })
+            .await
+            .unwrap();
+    });
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    (channel, signal_tx, serve)
+}
+
+async fn open_stream(
+    channel: Channel,
+    ignore_drain: bool,
+) -> Pin<Box<dyn Stream<Item = Result<Output1, Status>>>> {
+    let req = Input1 {
+        buf: if ignore_drain { vec![1] } else { vec![] },
+    };
+    let mut stream = Test1Client::new(channel)
+        .stream_call(req)
+        .await
+        .unwrap()
+        .into_inner();
+    stream.next().await.unwrap().unwrap();
+    Box::pin(stream)
+}
+
+#[tokio::test]
+async fn handlers_end_their_streams_once_draining() {
+    let (channel, signal, serve) = spawn(Server::builder()).await;
+    let stream = open_stream(channel, false).await;
+
+    signal.send(()).unwrap();
+    let messages: Vec<_> = stream.map(|message| message.unwrap().buf).collect().await;
+    assert_eq!(messages.last().unwrap(), FINAL);
+
+    tokio::time::timeout(Duration::from_secs(5), serve)
+        .await
+        .unwrap()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn streams_are_reset_after_the_shutdown_timeout() {
+    let server = Server::builder().graceful_shutdown_timeout(Duration::from_millis(100));
+    let (channel, signal, serve) = spawn(server).await;
+    let mut stream = open_stream(channel, true).await;
+
+    signal.send(()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), serve)
+        .await
+        .unwrap()
+        .unwrap();
+    let failed = loop {
+        match stream.next().await {
+            Some(Ok(_)) => continue,
+            Some(Err(_)) => break true,
+            None => break false,
+        }
+    };
+    assert!(failed);
+}
@@ -0,0 +1,63 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status,
+};
+
+// An executor spawning its tasks on Tokio, counting them.
+#[derive(Clone, Default)]
+struct CountingExecutor(Arc<AtomicUsize>);
+
+impl<F> hyper::rt::Executor<F> for CountingExecutor
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    fn execute(&self, fut: F) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        tokio::spawn(fut);
+    }
+}
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn servers_and_channels_spawn_on_their_executors() {
+    let server_executor = CountingExecutor::default();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+    let server = Server::builder()
+        .executor(server_executor.clone())
+        .add_service(test_server::TestServer::new(Svc));
+    tokio::spawn(async move { server.serve_with_incoming(incoming).await.unwrap() });
+
+    let channel_executor = CountingExecutor::default();
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .executor(channel_executor.clone())
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+    client.unary_call(Input {}).await.unwrap();
+
+    // The server spawned the connection, and the channel its connection and buffer.
+    assert!(server_executor.0.load(Ordering::SeqCst) >= 1);
+    assert!(channel_executor.0.load(Ordering::SeqCst) >= 2);
+}
@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::service::fault::{Fault, FaultInjectionLayer};
+use tonic::transport::{server::TcpIncoming, Endpoint, Server};
+use tonic::{Code, Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _req: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn server_side_faults() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let faults = FaultInjectionLayer::new().method_fault(
+        "/test.Test/UnaryCall",
+        Fault::new()
+            .delay(Duration::from_millis(200), 100.0)
+            .abort(Status::resource_exhausted("injected"), 100.0),
+    );
+    tokio::spawn(async move {
+        Server::builder()
+            .layer(faults)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    assert_eq!(status.message(), "injected");
+
+    // The delay is injected before the abort.
+    let mut req = Request::new(Input {});
+    req.set_timeout(Duration::from_millis(50));
+    let status = client.unary_call(req).await.unwrap_err();
+    assert_eq!(status.code(), Code::Cancelled);
+}
+
+#[tokio::test]
+async fn client_side_faults() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let channel = tower::ServiceBuilder::new()
+        .layer(
+            FaultInjectionLayer::new()
+                .fault(Fault::new().abort(Status::unavailable("injected"), 100.0)),
+        )
+        .service(channel);
+    let mut client = test_client::TestClient::new(channel);
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+}
@@ -0,0 +1,106 @@
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_stream::Stream;
+use tonic::transport::{server::TcpIncoming, Endpoint, Server};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Ok(Response::new(Output1 {
+            buf: req.into_inner().buf,
+        }))
+    }
+
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        Err(Status::unimplemented("not used"))
+    }
+}
+
+#[tokio::test]
+async fn asymmetric_flow_control_settings_carry_large_messages() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        // Small windows to receive, large frames and buffers to send.
+        Server::builder()
+            .initial_stream_window_size(16 * 1024)
+            .initial_connection_window_size(32 * 1024)
+            .http2_max_send_buf_size(4 * 1024 * 1024)
+            .max_frame_size(64 * 1024)
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    // Large windows to receive, small frames and buffers to send.
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .initial_stream_window_size(4 * 1024 * 1024)
+        .initial_connection_window_size(8 * 1024 * 1024)
+        .http2_max_send_buf_size(8 * 1024)
+        .max_frame_size(16 * 1024)
+        .connect()
+        .await
+        .unwrap();
+    let mut client = Test1Client::new(channel);
+
+    let buf = vec![7; 1024 * 1024];
+    let res = client
+        .unary_call(Input1 { buf: buf.clone() })
+        .await
+        .unwrap();
+    assert_eq!(res.into_inner().buf, buf);
+}
+
+#[tokio::test]
+async fn advertises_header_table_size() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .http2_header_table_size(1024)
+            .max_concurrent_streams(10)
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n\0\0\0\x04\0\0\0\0\0")
+        .await
+        .unwrap();
+
+    // The first frame of the server is its SETTINGS frame.
+    let mut header = [0; 9];
+    stream.read_exact(&mut header).await.unwrap();
+    assert_eq!(header[3], 0x4);
+    let mut payload = vec![0; u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize];
+    stream.read_exact(&mut payload).await.unwrap();
+
+    let settings: Vec<_> = payload
+        .chunks(6)
+        .map(|s| {
+            (
+                u16::from_be_bytes([s[0], s[1]]),
+                u32::from_be_bytes([s[2], s[3], s[4], s[5]]),
+            )
+        })
+        .collect();
+    assert!(settings.contains(&(0x1, 1024)), "{settings:?}");
+    assert!(settings.contains(&(0x3, 10)), "{settings:?}");
+}
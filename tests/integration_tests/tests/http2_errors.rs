@@ -0,0 +1,119 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use integration_tests::pb::{test_client::TestClient, Input};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tonic::{
+    transport::{channel::ConnectionEvent, Endpoint},
+    Code,
+};
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const HEADERS: u8 = 0x1;
+const RST_STREAM: u8 = 0x3;
+const SETTINGS: u8 = 0x4;
+const GOAWAY: u8 = 0x7;
+
+fn frame(kind: u8, stream: u32, payload: &[u8]) -> Vec<u8> {
+    let mut frame = (payload.len() as u32).to_be_bytes()[1..].to_vec();
+    frame.extend([kind, 0]);
+    frame.extend(stream.to_be_bytes());
+    frame.extend(payload);
+    frame
+}
+
+// Serves a single HTTP/2 connection by hand, answering the first request with `answer`.
+async fn serve_once(answer: impl FnOnce(u32) -> Vec<u8> + Send + 'static) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut io, _) = listener.accept().await.unwrap();
+        let mut preface = [0; PREFACE.len()];
+        io.read_exact(&mut preface).await.unwrap();
+        io.write_all(&frame(SETTINGS, 0, &[])).await.unwrap();
+
+        let stream = read_until_headers(&mut io).await;
+        io.write_all(&answer(stream)).await.unwrap();
+        // Keep the connection open until the client closes it.
+        let _ = io.read_to_end(&mut Vec::new()).await;
+    });
+    format!("http://{addr}")
+}
+
+// Reads frames until the headers of a request, returning its stream.
+async fn read_until_headers(io: &mut TcpStream) -> u32 {
+    loop {
+        let mut header = [0; 9];
+        io.read_exact(&mut header).await.unwrap();
+        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+        io.read_exact(&mut vec![0; len]).await.unwrap();
+        if header[3] == HEADERS {
+            return u32::from_be_bytes([header[5], header[6], header[7], header[8]]) & 0x7fff_ffff;
+        }
+    }
+}
+
+#[tokio::test]
+async fn go_away_is_surfaced_on_statuses_and_events() {
+    let addr = serve_once(|_| {
+        // `ENHANCE_YOUR_CALM`, refusing all the streams.
+        let mut payload = vec![0, 0, 0, 0, 0, 0, 0, 0xb];
+        payload.extend(b"too many pings");
+        frame(GOAWAY, 0, &payload)
+    })
+    .await;
+
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorded = events.clone();
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connection_events(move |event| recorded.lock().unwrap().push(event))
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::ResourceExhausted);
+    let error = status.http2_error().unwrap();
+    assert_eq!(error.code(), 0xb);
+    assert!(error.is_go_away());
+    assert!(error.is_remote());
+
+    // The events are emitted once the connection task ends.
+    for _ in 0..50 {
+        if events.lock().unwrap().len() >= 3 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    let events = events.lock().unwrap();
+    assert!(matches!(events[0], ConnectionEvent::Connected { .. }));
+    assert_eq!(events[1], ConnectionEvent::GoAway { error });
+    assert!(matches!(events[2], ConnectionEvent::Disconnected { .. }));
+}
+
+#[tokio::test]
+async fn stream_resets_are_surfaced_on_statuses() {
+    // `REFUSED_STREAM`.
+    let addr = serve_once(|stream| frame(RST_STREAM, stream, &[0, 0, 0, 0x7])).await;
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = TestClient::new(channel);
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    let error = status.http2_error().unwrap();
+    assert_eq!(error.code(), 0x7);
+    assert!(!error.is_go_away());
+    assert!(error.is_remote());
+    assert_eq!(error.to_string(), "RST_STREAM received with REFUSED_STREAM");
+}
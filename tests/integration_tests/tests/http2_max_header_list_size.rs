@@ -68,3 +68,103 @@ async fn test_http_max_header_list_size_and_long_errors() {
 
     jh.await.unwrap();
 }
+
+/// This test checks that requests over the max header list size of the server fail with
+/// `Code::ResourceExhausted`, stating the size of their headers.
+#[tokio::test]
+async fn test_request_over_max_header_list_size() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .http2_max_header_list_size(1024)
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tonic::transport::server::TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr).unwrap().connect_lazy();
+    let mut client = test_client::TestClient::new(channel);
+
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    let mut request = Request::new(Input {});
+    request
+        .metadata_mut()
+        .insert("x-large", "a".repeat(2048).parse().unwrap());
+    let err = client.unary_call(request).await.unwrap_err();
+
+    assert_eq!(err.code(), tonic::Code::ResourceExhausted);
+    assert!(
+        err.message().starts_with("request headers of "),
+        "{}",
+        err.message()
+    );
+    assert!(err
+        .message()
+        .contains("exceed the max header list size of the server"));
+}
+
+/// This test checks that responses whose metadata exceeds the max header list size of the client
+/// fail with `Code::ResourceExhausted`.
+#[tokio::test]
+async fn test_response_over_max_header_list_size() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            let mut response = Response::new(Output {});
+            response
+                .metadata_mut()
+                .insert("x-large", "a".repeat(2048).parse().unwrap());
+            Ok(response)
+        }
+    }
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = format!("http://{}", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tonic::transport::server::TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(addr)
+        .unwrap()
+        .http2_max_header_list_size(1024)
+        .connect_lazy();
+    let mut client = test_client::TestClient::new(channel);
+
+    let err = tokio::time::timeout(
+        Duration::from_secs(5),
+        client.unary_call(Request::new(Input {})),
+    )
+    .await
+    .expect("the response should be rejected")
+    .unwrap_err();
+
+    assert_eq!(err.code(), tonic::Code::ResourceExhausted, "{err:?}");
+    assert!(
+        err.message().starts_with("response headers of "),
+        "{}",
+        err.message()
+    );
+    assert!(err
+        .message()
+        .contains("exceed the max header list size of the client"));
+}
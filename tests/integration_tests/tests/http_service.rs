@@ -0,0 +1,81 @@
+use std::time::Duration;
+
+use axum::routing::get;
+use http::{header, Request, StatusCode};
+use http_body_util::{BodyExt as _, Empty};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::transport::{server::TcpIncoming, Endpoint, Server};
+use tonic::{Response, Status};
+
+#[tokio::test]
+async fn grpc_and_http_on_one_listener() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(
+            &self,
+            _req: tonic::Request<Input>,
+        ) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let rest = axum::Router::new().route("/health", get(|| async { "ok" }));
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener);
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .add_service(test_server::TestServer::new(Svc))
+            .http_service(rest)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+    client.unary_call(Input {}).await.unwrap();
+
+    let http = Client::builder(TokioExecutor::new()).build_http::<Empty<bytes::Bytes>>();
+    let res = http
+        .get(format!("http://{addr}/health").parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], b"ok");
+
+    let res = http
+        .get(format!("http://{addr}/missing").parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+
+    // gRPC requests are not routed to the HTTP service, even on unknown paths.
+    let req = Request::post(format!("http://{addr}/test.Unknown/Call"))
+        .header(header::CONTENT_TYPE, "application/grpc")
+        .body(Empty::new())
+        .unwrap();
+    let res = http.request(req).await.unwrap();
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()["grpc-status"], "12");
+
+    drop(http);
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
@@ -0,0 +1,98 @@
+use std::{
+    convert::Infallible,
+    future::{ready, Ready},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::{
+    body::Body,
+    client::HttpTransport,
+    transport::{server::TcpIncoming, Server},
+    Code, Request, Response, Status,
+};
+use tower_service::Service;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        match req.metadata().get("x-fail") {
+            Some(_) => Err(Status::failed_precondition("asked to fail")),
+            None => Ok(Response::new(Output {})),
+        }
+    }
+}
+
+#[tokio::test]
+async fn hyper_client_backs_generated_clients() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let client = Client::builder(TokioExecutor::new())
+        .http2_only(true)
+        .build_http::<Body>();
+    let transport = HttpTransport::new(client, format!("http://{addr}").parse().unwrap());
+    let mut client = TestClient::new(transport);
+
+    client.unary_call(Input {}).await.unwrap();
+
+    // The statuses of failed calls are read from the trailers of their responses.
+    let mut req = Request::new(Input {});
+    req.metadata_mut().insert("x-fail", "1".parse().unwrap());
+    let status = client.unary_call(req).await.unwrap_err();
+    assert_eq!(status.code(), Code::FailedPrecondition);
+    assert_eq!(status.message(), "asked to fail");
+}
+
+// Test double answering every call with `Unavailable`, recording the requests it receives.
+#[derive(Clone, Default)]
+struct Unavailable {
+    requests: Arc<Mutex<Vec<(http::Uri, http::Version)>>>,
+}
+
+impl Service<http::Request<Body>> for Unavailable {
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((req.uri().clone(), req.version()));
+        ready(Ok(Status::unavailable("down").into_http()))
+    }
+}
+
+#[tokio::test]
+async fn requests_are_sent_to_the_origin() {
+    let double = Unavailable::default();
+    let transport = HttpTransport::new(double.clone(), "https://example.com".parse().unwrap());
+    let mut client = TestClient::new(transport);
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert_eq!(
+        *double.requests.lock().unwrap(),
+        [(
+            "https://example.com/test.Test/UnaryCall".parse().unwrap(),
+            http::Version::HTTP_2
+        )]
+    );
+}
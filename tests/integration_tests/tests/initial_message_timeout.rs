@@ -0,0 +1,83 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body::Frame;
+use http_body_util::StreamBody;
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::body::Body;
+use tonic::transport::{server::TcpIncoming, Endpoint, Server};
+use tonic::{Code, Request, Response, Status};
+use tower::ServiceExt;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn spawn(server: Server) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut server = server;
+    tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    addr
+}
+
+#[tokio::test]
+async fn fails_streams_without_messages() {
+    let addr = spawn(Server::builder().initial_message_timeout(Duration::from_millis(100))).await;
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let mut client = test_client::TestClient::new(channel.clone());
+    client.unary_call(Input {}).await.unwrap();
+
+    // The request stream stays open without a message.
+    let (_tx, rx) = mpsc::channel::<Result<Frame<Bytes>, Status>>(1);
+    let request = http::Request::post(format!("http://{addr}/test.Test/UnaryCall"))
+        .header("content-type", "application/grpc")
+        .header("te", "trailers")
+        .body(Body::new(StreamBody::new(ReceiverStream::new(rx))))
+        .unwrap();
+    let response = channel.oneshot(request).await.unwrap();
+    let status = Status::from_header_map(response.headers()).unwrap();
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+}
+
+#[tokio::test]
+async fn closes_connections_without_request_headers() {
+    let addr = spawn(
+        Server::builder()
+            .accept_http1(true)
+            .request_header_timeout(Duration::from_millis(100)),
+    )
+    .await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"POST /test.Test/UnaryCall HTTP/1.1\r\n")
+        .await
+        .unwrap();
+    let mut buf = Vec::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut buf))
+        .await
+        .expect("the connection is closed")
+        .unwrap();
+}
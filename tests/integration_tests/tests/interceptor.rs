@@ -57,3 +57,73 @@ async fn interceptor_retrieves_grpc_method() {
     tx.send(()).unwrap();
     jh.await.unwrap();
 }
+
+#[tokio::test]
+async fn composed_interceptors_run_in_order() {
+    use test_server::Test;
+    use tonic::service::InterceptorExt;
+
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            assert_eq!(req.extensions().get::<&str>(), Some(&"tenant-a"));
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    fn authenticate(req: Request<()>) -> Result<Request<()>, Status> {
+        match req.metadata().get("authorization") {
+            Some(token) if token == "secret" => Ok(req),
+            _ => Err(Status::unauthenticated("invalid token")),
+        }
+    }
+
+    fn extract_tenant(mut req: Request<()>) -> Result<Request<()>, Status> {
+        assert_eq!(req.metadata().get("tenant").unwrap(), "a");
+        req.extensions_mut().insert("tenant-a");
+        Ok(req)
+    }
+
+    let svc = test_server::TestServer::with_interceptor(Svc, authenticate.and_then(extract_tenant));
+
+    let (tx, rx) = oneshot::channel();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect_lazy();
+
+    let add_tenant = |mut req: Request<()>| {
+        req.metadata_mut().insert("tenant", "a".parse().unwrap());
+        Ok(req)
+    };
+    let authorize = |mut req: Request<()>| {
+        req.metadata_mut()
+            .insert("authorization", "secret".parse().unwrap());
+        Ok(req)
+    };
+    let mut client = TestClient::with_interceptor(channel.clone(), add_tenant.and_then(authorize));
+    client.unary_call(Request::new(Input {})).await.unwrap();
+
+    let mut unauthorized = TestClient::with_interceptor(channel, add_tenant);
+    let status = unauthorized
+        .unary_call(Request::new(Input {}))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+    tx.send(()).unwrap();
+    jh.await.unwrap();
+}
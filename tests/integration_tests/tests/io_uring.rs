@@ -0,0 +1,59 @@
+#![cfg(target_os = "linux")]
+
+use std::net::{SocketAddr, TcpListener};
+
+use integration_tests::pb::{test_client::TestClient, test_server, Input, Output};
+use tokio::sync::oneshot;
+use tonic::transport::{server::TcpConnectInfo, Endpoint, Server};
+use tonic::{Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+        let info = req.extensions().get::<TcpConnectInfo>().unwrap();
+        assert!(info.local_addr().is_some());
+        assert!(info.remote_addr().is_some());
+        Ok(Response::new(Output {}))
+    }
+}
+
+fn free_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+#[test]
+fn serves_with_io_uring() {
+    tokio_uring::start(async {
+        let addr = free_addr();
+        let (tx, rx) = oneshot::channel::<()>();
+        let server = tokio_uring::spawn(async move {
+            Server::builder()
+                .io_uring(true)
+                .add_service(test_server::TestServer::new(Svc))
+                .serve_with_shutdown(addr, async { drop(rx.await) })
+                .await
+                .unwrap();
+        });
+
+        // The server binds its address once it is first polled.
+        let endpoint = Endpoint::from_shared(format!("http://{addr}")).unwrap();
+        let channel = loop {
+            match endpoint.connect().await {
+                Ok(channel) => break channel,
+                Err(_) => tokio::task::yield_now().await,
+            }
+        };
+        let mut client = TestClient::new(channel);
+        for _ in 0..3 {
+            client.unary_call(Input {}).await.unwrap();
+        }
+
+        tx.send(()).unwrap();
+        server.await.unwrap();
+    });
+}
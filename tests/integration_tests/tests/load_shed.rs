@@ -59,3 +59,49 @@ async fn run_service_in_background(concurrency_limit: usize) -> SocketAddr {
 
     addr
 }
+
+#[tokio::test]
+async fn overloaded_requests_are_pushed_back() {
+    use std::{sync::Arc, time::Duration};
+    use tokio::sync::Notify;
+    use tonic_types::StatusExt;
+
+    struct Svc(Arc<Notify>);
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _req: Request<Input>) -> Result<Response<Output>, Status> {
+            self.0.notify_one();
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let started = Arc::new(Notify::new());
+    let svc = test_server::TestServer::new(Svc(started.clone()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .concurrency_limit_per_connection(1)
+            .load_shed(true)
+            .overload_pushback(Duration::from_millis(100))
+            .add_service(svc)
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    let mut busy = client.clone();
+    tokio::spawn(async move { busy.unary_call(Input {}).await });
+    started.notified().await;
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    // Two connections' worth of requests were in flight: the busy one and the rejected one.
+    let retry_info = status.get_details_retry_info().unwrap();
+    assert_eq!(retry_info.retry_delay, Some(Duration::from_millis(200)));
+}
@@ -0,0 +1,124 @@
+use std::net::SocketAddr;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::service::CircuitBreakerLayer;
+use tonic::transport::channel::{Change, Locality};
+use tonic::transport::{server::TcpIncoming, Channel, Endpoint, Server};
+use tonic::{Request, Response, Status};
+
+struct Svc {
+    fail: bool,
+    calls: Arc<AtomicUsize>,
+}
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _req: Request<Input>) -> Result<Response<Output>, Status> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.fail {
+            return Err(Status::unavailable("failing"));
+        }
+        Ok(Response::new(Output {}))
+    }
+}
+
+struct Backend {
+    endpoint: Endpoint,
+    calls: Arc<AtomicUsize>,
+}
+
+impl Backend {
+    async fn spawn(fail: bool, locality: Locality) -> Self {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let addr = spawn(fail, calls.clone()).await;
+        let endpoint = Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .locality(locality)
+            .circuit_breaker(
+                CircuitBreakerLayer::new()
+                    .window_size(2)
+                    .minimum_calls(2)
+                    .open_duration(Duration::from_secs(60)),
+            );
+        Self { endpoint, calls }
+    }
+
+    fn calls(&self) -> usize {
+        self.calls.swap(0, Ordering::SeqCst)
+    }
+}
+
+fn local() -> Locality {
+    Locality::new("region-1", "zone-a")
+}
+
+#[tokio::test]
+async fn prefers_local_zone() {
+    let backends = [
+        Backend::spawn(false, local()).await,
+        Backend::spawn(false, Locality::new("region-1", "zone-b")).await,
+        Backend::spawn(false, Locality::new("region-2", "zone-a")).await,
+    ];
+    let channel = Channel::balance_list_with_locality(
+        backends.iter().map(|backend| backend.endpoint.clone()),
+        local(),
+    );
+    let mut client = test_client::TestClient::new(channel);
+
+    for _ in 0..20 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(backends.map(|backend| backend.calls()), [20, 0, 0]);
+}
+
+#[tokio::test]
+async fn spills_over_to_other_localities() {
+    let backends = [
+        Backend::spawn(true, local()).await,
+        Backend::spawn(false, Locality::new("region-1", "zone-b")).await,
+        Backend::spawn(false, Locality::new("region-2", "zone-a")).await,
+    ];
+    let (channel, tx) = Channel::balance_channel_with_locality(16, local());
+    for (i, backend) in backends.iter().enumerate() {
+        tx.send(Change::Insert(i, backend.endpoint.clone()))
+            .await
+            .unwrap();
+    }
+    let mut client = test_client::TestClient::new(channel);
+
+    // The breaker of the local endpoint opens.
+    for _ in 0..2 {
+        client.unary_call(Input {}).await.unwrap_err();
+    }
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    // Then the endpoint of the local region is removed.
+    tx.send(Change::Remove(1)).await.unwrap();
+    for _ in 0..10 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+
+    let calls = backends.map(|backend| backend.calls.load(Ordering::SeqCst));
+    assert_eq!(calls, [2, 10, 10]);
+}
+
+async fn spawn(fail: bool, calls: Arc<AtomicUsize>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc { fail, calls }))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    addr
+}
@@ -0,0 +1,120 @@
+use integration_tests::pb::{
+    test1_client, test1_methods, test_client, test_methods, test_server, Input, Input1, Output,
+};
+use std::time::Duration;
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    server::NamedService,
+    transport::{server::TcpIncoming, Endpoint, Server},
+    GrpcMethod, IdempotencyLevel, Request, Response, Status,
+};
+
+#[test]
+fn method_constants() {
+    assert_eq!(test_methods::SERVICE_NAME, "test.Test");
+    assert_eq!(
+        test_methods::SERVICE_NAME,
+        <test_server::TestServer<()> as NamedService>::NAME
+    );
+    assert_eq!(test_methods::unary_call::NAME, "UnaryCall");
+    assert_eq!(test_methods::unary_call::PATH, "/test.Test/UnaryCall");
+
+    let paths = test1_methods::METHODS
+        .iter()
+        .map(|method| method.path())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["/test.Test1/UnaryCall", "/test.Test1/StreamCall"]);
+
+    let stream_call = test1_methods::stream_call::DESCRIPTOR;
+    assert_eq!(stream_call.service(), "test.Test1");
+    assert_eq!(stream_call.method(), "StreamCall");
+    assert!(!stream_call.client_streaming());
+    assert!(stream_call.server_streaming());
+}
+
+#[test]
+fn idempotency_levels() {
+    assert_eq!(
+        test_methods::unary_call::DESCRIPTOR.idempotency_level(),
+        IdempotencyLevel::Unknown
+    );
+    assert_eq!(
+        test1_methods::unary_call::DESCRIPTOR.idempotency_level(),
+        IdempotencyLevel::Idempotent
+    );
+    assert_eq!(
+        test1_methods::stream_call::DESCRIPTOR.idempotency_level(),
+        IdempotencyLevel::NoSideEffects
+    );
+}
+
+#[tokio::test]
+async fn requests_carry_idempotency_level() {
+    let channel = Endpoint::from_static("http://[::1]:1").connect_lazy();
+    let mut client = test1_client::Test1Client::with_interceptor(channel, |req: Request<()>| {
+        let level = req.extensions().get::<IdempotencyLevel>().copied();
+        Err(Status::cancelled(format!("{level:?}")))
+    });
+
+    let status = client.unary_call(Input1::default()).await.unwrap_err();
+    assert_eq!(status.message(), "Some(Idempotent)");
+    let status = client.stream_call(Input1::default()).await.unwrap_err();
+    assert_eq!(status.message(), "Some(NoSideEffects)");
+
+    let channel = Endpoint::from_static("http://[::1]:1").connect_lazy();
+    let mut client = test_client::TestClient::with_interceptor(channel, |req: Request<()>| {
+        assert!(req.extensions().get::<IdempotencyLevel>().is_none());
+        Err(Status::cancelled(""))
+    });
+    client.unary_call(Input {}).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn descriptor_matches_requests() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+            Ok(Response::new(Output {}))
+        }
+    }
+
+    let svc = test_server::TestServer::new(Svc);
+
+    let (tx, rx) = oneshot::channel::<()>();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from(listener).with_nodelay(Some(true));
+
+    let jh = tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming_shutdown(incoming, async { drop(rx.await) })
+            .await
+            .unwrap();
+    });
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let channel = Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+
+    let mut client = test_client::TestClient::with_interceptor(channel, |req: Request<()>| {
+        let method = req.extensions().get::<GrpcMethod>().unwrap();
+        let descriptor = test_methods::unary_call::DESCRIPTOR.grpc_method();
+        assert_eq!(method.service(), descriptor.service());
+        assert_eq!(method.method(), descriptor.method());
+        Ok(req)
+    });
+
+    client.unary_call(Input {}).await.unwrap();
+
+    tx.send(()).unwrap();
+
+    jh.await.unwrap();
+}
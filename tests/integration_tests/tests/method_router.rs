@@ -0,0 +1,61 @@
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::codec::ProstCodec;
+use tonic::service::{method::UnaryMethod, Routes};
+use tonic::transport::{server::TcpIncoming, Channel, Server};
+use tonic::{Code, Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _: Request<Input>) -> Result<Response<Output>, Status> {
+        Ok(Response::new(Output {}))
+    }
+}
+
+async fn spawn(routes: Routes) -> Channel {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_routes(routes)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+fn add_failing_method(routes: Routes) -> Routes {
+    let method = UnaryMethod::new(
+        ProstCodec::<Output, Input>::default(),
+        tower::service_fn(|_: Request<Input>| async {
+            Err::<Response<Output>, _>(Status::permission_denied("overridden"))
+        }),
+    );
+    routes.add_method("/test.Test/UnaryCall", method)
+}
+
+#[tokio::test]
+async fn overrides_generated_method() {
+    let routes = add_failing_method(Routes::new(test_server::TestServer::new(Svc)));
+    let mut client = test_client::TestClient::new(spawn(routes).await);
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+    assert_eq!(status.message(), "overridden");
+}
+
+#[tokio::test]
+async fn serves_method_without_service() {
+    let routes = add_failing_method(Routes::default());
+    let mut client = test_client::TestClient::new(spawn(routes).await);
+
+    let status = client.unary_call(Input {}).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+}
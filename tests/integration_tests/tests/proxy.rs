@@ -0,0 +1,113 @@
+use std::net::SocketAddr;
+
+use integration_tests::pb::{test1_client, test1_server, Input1, Output1};
+use tokio::net::TcpListener;
+use tokio_stream::{Stream, StreamExt};
+use tonic::metadata::MetadataValue;
+use tonic::service::{AxumRouter, GrpcProxy, Routes};
+use tonic::transport::{server::TcpIncoming, Channel, Endpoint, Server};
+use tonic::{Code, Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let tenant = req.metadata().get("x-tenant").cloned();
+        let buf = req.into_inner().buf;
+        if buf.is_empty() {
+            let mut status = Status::invalid_argument("empty buf");
+            status
+                .metadata_mut()
+                .insert("x-detail", MetadataValue::from_static("detail"));
+            return Err(status);
+        }
+        let mut response = Response::new(Output1 { buf });
+        if let Some(tenant) = tenant {
+            response.metadata_mut().insert("x-tenant", tenant);
+        }
+        Ok(response)
+    }
+
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let buf = req.into_inner().buf;
+        let outputs = (0..3).map(move |_| Ok(Output1 { buf: buf.clone() }));
+        Ok(Response::new(Box::pin(tokio_stream::iter(outputs))))
+    }
+}
+
+#[tokio::test]
+async fn forwards_calls() {
+    let upstream = spawn(Routes::new(test1_server::Test1Server::new(Svc))).await;
+    let proxy = GrpcProxy::new(connect(upstream).await);
+    let addr = spawn(Routes::from(AxumRouter::new().fallback_service(proxy))).await;
+    let mut client = test1_client::Test1Client::new(connect(addr).await);
+
+    let mut request = Request::new(Input1 { buf: vec![1] });
+    request
+        .metadata_mut()
+        .insert("x-tenant", MetadataValue::from_static("a"));
+    let response = client.unary_call(request).await.unwrap();
+    assert_eq!(response.metadata().get("x-tenant").unwrap(), "a");
+    assert_eq!(response.into_inner().buf, [1]);
+
+    let status = client.unary_call(Input1 { buf: vec![] }).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "empty buf");
+    assert_eq!(status.metadata().get("x-detail").unwrap(), "detail");
+
+    let stream = client
+        .stream_call(Input1 { buf: vec![2] })
+        .await
+        .unwrap()
+        .into_inner();
+    let outputs: Vec<_> = stream.map(|output| output.unwrap().buf).collect().await;
+    assert_eq!(outputs, [[2], [2], [2]]);
+}
+
+#[tokio::test]
+async fn fails_calls_without_upstream() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let upstream = listener.local_addr().unwrap();
+    drop(listener);
+    let proxy = GrpcProxy::new(
+        Endpoint::from_shared(format!("http://{upstream}"))
+            .unwrap()
+            .connect_lazy(),
+    );
+    let addr = spawn(Routes::from(AxumRouter::new().fallback_service(proxy))).await;
+    let mut client = test1_client::Test1Client::new(connect(addr).await);
+
+    let status = client
+        .unary_call(Input1 { buf: vec![1] })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+}
+
+async fn spawn(routes: Routes) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_routes(routes)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    addr
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
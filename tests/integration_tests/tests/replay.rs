@@ -0,0 +1,129 @@
+use std::net::SocketAddr;
+
+use integration_tests::pb::{test1_client, test1_server, Input1, Output1};
+use tokio::net::TcpListener;
+use tokio_stream::{Stream, StreamExt};
+use tonic::service::replay::{RecordLayer, Recording};
+use tonic::service::Routes;
+use tonic::transport::{server::TcpIncoming, Channel, Endpoint, Server};
+use tonic::{Code, Request, Response, Status};
+
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let buf = req.into_inner().buf;
+        if buf.is_empty() {
+            return Err(Status::invalid_argument("empty buf"));
+        }
+        Ok(Response::new(Output1 { buf }))
+    }
+
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let buf = req.into_inner().buf;
+        let outputs = [Ok(Output1 { buf: buf.clone() }), Ok(Output1 { buf })];
+        Ok(Response::new(Box::pin(tokio_stream::iter(outputs))))
+    }
+}
+
+#[tokio::test]
+async fn record_and_replay() {
+    let addr = spawn(Routes::new(test1_server::Test1Server::new(Svc))).await;
+    let recorder = RecordLayer::new();
+    let channel = tower::ServiceBuilder::new()
+        .layer(recorder.clone())
+        .service(connect(addr).await);
+    let mut client = test1_client::Test1Client::new(channel);
+
+    calls(&mut client).await;
+    let recording = recorder.recording();
+    assert_eq!(recording.calls().len(), 3);
+
+    let path = std::env::temp_dir().join(format!("tonic-replay-{}.rec", std::process::id()));
+    recording.save(&path).unwrap();
+    let loaded = Recording::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, recording);
+
+    // Replayed as client load, the server answers as recorded.
+    assert_eq!(
+        recording.replay(connect(addr).await).await.unwrap(),
+        recording
+    );
+
+    // Replayed as a mock server, the recording answers as the server.
+    let mock = axum::Router::new().fallback_service(recording.clone().into_service());
+    let addr = spawn(Routes::from(mock)).await;
+    let mut client = test1_client::Test1Client::new(connect(addr).await);
+    calls(&mut client).await;
+
+    let status = client
+        .unary_call(Input1 {
+            buf: b"not recorded".to_vec(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+// Make the calls recorded, checking their responses.
+async fn calls<T>(client: &mut test1_client::Test1Client<T>)
+where
+    T: tonic::client::GrpcService<tonic::body::Body>,
+    T::Error: Into<tonic::codegen::StdError>,
+    T::ResponseBody: http_body::Body<Data = bytes::Bytes> + Send + 'static,
+    <T::ResponseBody as http_body::Body>::Error: Into<tonic::codegen::StdError> + Send,
+{
+    let output = client
+        .unary_call(Input1 {
+            buf: b"hello".to_vec(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(output.into_inner().buf, b"hello");
+
+    let status = client.unary_call(Input1 { buf: vec![] }).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "empty buf");
+
+    let outputs = client
+        .stream_call(Input1 {
+            buf: b"stream".to_vec(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .unwrap();
+    assert_eq!(outputs.len(), 2);
+    assert_eq!(outputs[1].buf, b"stream");
+}
+
+async fn spawn(routes: Routes) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_routes(routes)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    addr
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
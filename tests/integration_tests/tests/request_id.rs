@@ -0,0 +1,105 @@
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::net::TcpListener;
+use tokio_stream::Stream;
+use tonic::{
+    service::request_id::{ClientRequestIdLayer, RequestId, ServerRequestIdLayer},
+    transport::{server::TcpIncoming, Channel, Server},
+    Request, Response, Status,
+};
+use tower::ServiceBuilder;
+
+// Answers with the request ID of its extension, checking it matches the metadata.
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let id = req.extensions().get::<RequestId>().unwrap();
+        assert_eq!(req.metadata().get("x-request-id").unwrap(), id.as_str());
+        Ok(Response::new(Output1 {
+            buf: id.as_str().into(),
+        }))
+    }
+
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        Err(Status::unimplemented(""))
+    }
+}
+
+async fn spawn() -> Channel {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .layer(ServerRequestIdLayer::new())
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+// The ID seen by the server, and the one sent back in the metadata of the response.
+fn ids(res: Response<Output1>) -> (String, String) {
+    let echoed = res
+        .metadata()
+        .get("x-request-id")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    let echoed = echoed.to_owned();
+    (String::from_utf8(res.into_inner().buf).unwrap(), echoed)
+}
+
+#[tokio::test]
+async fn servers_generate_missing_ids() {
+    let mut client = Test1Client::new(spawn().await);
+
+    let (first, echoed) = ids(client.unary_call(Input1::default()).await.unwrap());
+    assert_eq!(first.len(), 36);
+    assert_eq!(echoed, first);
+    let (second, _) = ids(client.unary_call(Input1::default()).await.unwrap());
+    assert_ne!(first, second);
+}
+
+#[tokio::test]
+async fn servers_keep_the_ids_of_requests() {
+    let mut client = Test1Client::new(spawn().await);
+
+    let mut req = Request::new(Input1::default());
+    req.metadata_mut()
+        .insert("x-request-id", "abc-123".parse().unwrap());
+    assert_eq!(
+        ids(client.unary_call(req).await.unwrap()),
+        ("abc-123".to_owned(), "abc-123".to_owned())
+    );
+}
+
+#[tokio::test]
+async fn clients_propagate_the_id_extension() {
+    let channel = ServiceBuilder::new()
+        .layer(ClientRequestIdLayer::new())
+        .service(spawn().await);
+    let mut client = Test1Client::new(channel);
+
+    let mut req = Request::new(Input1::default());
+    req.extensions_mut()
+        .insert("propagated".parse::<RequestId>().unwrap());
+    let (id, _) = ids(client.unary_call(req).await.unwrap());
+    assert_eq!(id, "propagated");
+
+    let res = client.unary_call(Input1::default()).await.unwrap();
+    let generated = res.extensions().get::<RequestId>().unwrap().clone();
+    assert_eq!(res.into_inner().buf, generated.as_str().as_bytes());
+}
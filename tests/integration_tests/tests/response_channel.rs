@@ -0,0 +1,103 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::{net::TcpListener, sync::oneshot};
+use tokio_stream::StreamExt;
+use tonic::{
+    codegen::{response_channel, ResponseStream},
+    transport::{server::TcpIncoming, Channel, Server},
+    Request, Response, Status,
+};
+
+const MESSAGES: usize = 100;
+const MESSAGE_SIZE: usize = 64 * 1024;
+
+struct Svc {
+    sent: Arc<AtomicUsize>,
+    closed: std::sync::Mutex<Option<oneshot::Sender<()>>>,
+}
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Err(Status::unimplemented(""))
+    }
+
+    type StreamCallStream = ResponseStream<Output1>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let (tx, rx) = response_channel(1);
+        let sent = self.sent.clone();
+        let closed = self.closed.lock().unwrap().take().unwrap();
+        tokio::spawn(async move {
+            for _ in 0..MESSAGES {
+                let message = Output1 {
+                    buf: vec![0; MESSAGE_SIZE],
+                };
+                if tx.send(Ok(message)).await.is_err() {
+                    break;
+                }
+                sent.fetch_add(1, Ordering::SeqCst);
+            }
+            tx.closed().await;
+            closed.send(()).unwrap();
+        });
+        Ok(Response::new(rx))
+    }
+}
+
+#[tokio::test]
+async fn senders_wait_for_slow_clients_and_see_them_leave() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let sent = Arc::new(AtomicUsize::new(0));
+    let (closed_tx, closed_rx) = oneshot::channel();
+    let svc = test1_server::Test1Server::new(Svc {
+        sent: sent.clone(),
+        closed: std::sync::Mutex::new(Some(closed_tx)),
+    });
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = Test1Client::new(channel);
+    let mut stream = client
+        .stream_call(Input1::default())
+        .await
+        .unwrap()
+        .into_inner();
+    stream.next().await.unwrap().unwrap();
+
+    // Without reading, the sender is stopped by flow control once the windows are full.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let stalled = sent.load(Ordering::SeqCst);
+    assert!(stalled < MESSAGES, "{stalled} messages sent");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(sent.load(Ordering::SeqCst), stalled);
+
+    // Dropping the call wakes the sender, which sees the client gone.
+    drop(stream);
+    tokio::time::timeout(Duration::from_secs(5), closed_rx)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(sent.load(Ordering::SeqCst) < MESSAGES);
+}
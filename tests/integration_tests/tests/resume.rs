@@ -0,0 +1,144 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::net::TcpListener;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    client::ResumableStream,
+    transport::{server::TcpIncoming, Channel, Server},
+    Code, Request, Response, Status,
+};
+
+const ITEMS: u8 = 6;
+
+// Streams the items after the one of the request, failing with `failure` every two items.
+struct Svc {
+    failure: Code,
+    calls: Arc<Mutex<Vec<Vec<u8>>>>,
+}
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Err(Status::unimplemented(""))
+    }
+
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        req: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let after = req.into_inner().buf;
+        self.calls.lock().unwrap().push(after.clone());
+        let first = after.first().map_or(0, |last| last + 1);
+        let last = (first + 2).min(ITEMS);
+
+        let items = (first..last).map(|item| Ok(Output1 { buf: vec![item] }));
+        let failure = (last < ITEMS).then(|| Err(Status::new(self.failure, "stream lost")));
+        let stream = tokio_stream::iter(items.chain(failure));
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+async fn spawn(failure: Code) -> (Test1Client<Channel>, Arc<Mutex<Vec<Vec<u8>>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let svc = test1_server::Test1Server::new(Svc {
+        failure,
+        calls: calls.clone(),
+    });
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(svc)
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    (Test1Client::new(channel), calls)
+}
+
+fn resumable(client: Test1Client<Channel>) -> impl Stream<Item = Result<Output1, Status>> + Unpin {
+    ResumableStream::new(
+        move |token: Option<u8>| {
+            let mut client = client.clone();
+            async move {
+                let req = Input1 {
+                    buf: token.into_iter().collect(),
+                };
+                client.stream_call(req).await.map(Response::into_inner)
+            }
+        },
+        |item: &Output1| item.buf.first().copied(),
+    )
+    .backoff(Duration::from_millis(1), Duration::from_millis(10))
+}
+
+#[tokio::test]
+async fn failed_streams_are_resumed_after_their_last_item() {
+    let (client, calls) = spawn(Code::Unavailable).await;
+
+    let items: Vec<_> = resumable(client)
+        .map(|item| item.unwrap().buf[0])
+        .collect()
+        .await;
+    assert_eq!(items, [0, 1, 2, 3, 4, 5]);
+    assert_eq!(*calls.lock().unwrap(), [vec![], vec![1], vec![3]]);
+}
+
+#[tokio::test]
+async fn other_failures_end_the_stream() {
+    let (client, calls) = spawn(Code::Internal).await;
+
+    let mut stream = resumable(client);
+    assert_eq!(stream.next().await.unwrap().unwrap().buf, [0]);
+    assert_eq!(stream.next().await.unwrap().unwrap().buf, [1]);
+    let status = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(status.code(), Code::Internal);
+    assert!(stream.next().await.is_none());
+    assert_eq!(calls.lock().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn attempts_without_progress_are_bounded() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect_lazy();
+
+    let attempts = Arc::new(Mutex::new(0));
+    let counted = attempts.clone();
+    let mut stream = ResumableStream::new(
+        move |_: Option<u8>| {
+            *counted.lock().unwrap() += 1;
+            let mut client = Test1Client::new(channel.clone());
+            async move {
+                client
+                    .stream_call(Input1::default())
+                    .await
+                    .map(Response::into_inner)
+            }
+        },
+        |item: &Output1| item.buf.first().copied(),
+    )
+    .backoff(Duration::from_millis(1), Duration::from_millis(10))
+    .max_attempts(3);
+
+    let status = stream.next().await.unwrap().unwrap_err();
+    assert_eq!(status.code(), Code::Unavailable);
+    assert!(stream.next().await.is_none());
+    assert_eq!(*attempts.lock().unwrap(), 3);
+}
@@ -0,0 +1,133 @@
+use std::{net::SocketAddr, pin::Pin, time::Duration};
+
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_stream::{Stream, StreamExt};
+use tonic::{
+    transport::{server::TcpIncoming, Server},
+    Request, Response, Status,
+};
+
+// Streams two messages, quiet for a while in between.
+struct Svc;
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, _: Request<Input1>) -> Result<Response<Output1>, Status> {
+        Err(Status::unimplemented(""))
+    }
+
+    type StreamCallStream = Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        let first = tokio_stream::once(Ok(Output1 { buf: vec![1] }));
+        let second = tokio_stream::once(()).then(|()| async {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            Ok(Output1 { buf: vec![2] })
+        });
+        Ok(Response::new(Box::pin(first.chain(second))))
+    }
+}
+
+async fn spawn() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .stream_keepalive_interval(Duration::from_millis(50))
+            .add_service(test1_server::Test1Server::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    addr
+}
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const DATA: u8 = 0x0;
+const HEADERS: u8 = 0x1;
+const SETTINGS: u8 = 0x4;
+const END_STREAM: u8 = 0x1;
+const END_HEADERS: u8 = 0x4;
+const ACK: u8 = 0x1;
+
+fn frame(kind: u8, flags: u8, stream: u32, payload: &[u8]) -> Vec<u8> {
+    let mut frame = (payload.len() as u32).to_be_bytes()[1..].to_vec();
+    frame.extend([kind, flags]);
+    frame.extend(stream.to_be_bytes());
+    frame.extend(payload);
+    frame
+}
+
+// A header field encoded by HPACK as a literal without indexing, with a new name.
+fn literal(block: &mut Vec<u8>, name: &str, value: &str) {
+    block.push(0);
+    for string in [name, value] {
+        block.push(string.len() as u8);
+        block.extend(string.as_bytes());
+    }
+}
+
+// Empty `DATA` frames are dropped by HTTP/2 clients, so the call is made by hand to see them.
+#[tokio::test]
+async fn quiet_streams_get_empty_frames() {
+    let addr = spawn().await;
+    let mut io = TcpStream::connect(addr).await.unwrap();
+    io.write_all(PREFACE).await.unwrap();
+    io.write_all(&frame(SETTINGS, 0, 0, &[])).await.unwrap();
+
+    // `:method: POST` and `:scheme: http` are in the static table.
+    let mut block = vec![0x83, 0x86];
+    literal(&mut block, ":path", "/test.Test1/StreamCall");
+    literal(&mut block, ":authority", &addr.to_string());
+    literal(&mut block, "content-type", "application/grpc");
+    literal(&mut block, "te", "trailers");
+    io.write_all(&frame(HEADERS, END_HEADERS, 1, &block))
+        .await
+        .unwrap();
+    io.write_all(&frame(DATA, END_STREAM, 1, &[0; 5]))
+        .await
+        .unwrap();
+
+    let mut empty = 0;
+    loop {
+        let mut header = [0; 9];
+        io.read_exact(&mut header).await.unwrap();
+        let len = u32::from_be_bytes([0, header[0], header[1], header[2]]) as usize;
+        io.read_exact(&mut vec![0; len]).await.unwrap();
+        match (header[3], header[4]) {
+            (SETTINGS, flags) if flags & ACK == 0 => {
+                io.write_all(&frame(SETTINGS, ACK, 0, &[])).await.unwrap();
+            }
+            (DATA, _) if len == 0 => empty += 1,
+            // The trailers.
+            (HEADERS, flags) if flags & END_STREAM != 0 => break,
+            _ => {}
+        }
+    }
+    assert!(empty >= 3, "{empty} empty frames");
+}
+
+#[tokio::test]
+async fn clients_only_see_the_messages() {
+    let addr = spawn().await;
+    let mut client = Test1Client::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let messages: Vec<_> = client
+        .stream_call(Input1::default())
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|message| message.unwrap().buf)
+        .collect()
+        .await;
+    assert_eq!(messages, [vec![1], vec![2]]);
+}
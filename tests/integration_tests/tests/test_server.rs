@@ -0,0 +1,99 @@
+use std::time::{Duration, Instant};
+
+use tonic::{Code, Request, Status};
+use tonic_health::pb::{health_client::HealthClient, HealthCheckRequest};
+use tonic_mock::{Injection, TestServer};
+
+const CHECK: &str = "/grpc.health.v1.Health/Check";
+
+async fn start() -> TestServer {
+    let (_, health) = tonic_health::server::health_reporter();
+    TestServer::start(health).await
+}
+
+fn check(service: &str) -> Request<HealthCheckRequest> {
+    let mut request = Request::new(HealthCheckRequest {
+        service: service.to_string(),
+    });
+    request
+        .metadata_mut()
+        .insert("x-caller", "test".parse().unwrap());
+    request
+}
+
+#[tokio::test]
+async fn records_received_requests() {
+    let server = start().await;
+    let mut client = HealthClient::new(server.channel());
+
+    client.check(check("")).await.unwrap();
+    let status = client.check(check("unknown")).await.unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    server.assert_calls(CHECK, 2);
+    server.assert_calls("/grpc.health.v1.Health/Watch", 0);
+    let services: Vec<_> = server
+        .requests::<HealthCheckRequest>(CHECK)
+        .into_iter()
+        .map(|request| request.service)
+        .collect();
+    assert_eq!(services, ["", "unknown"]);
+
+    let recording = server.recording();
+    let call = &recording.calls()[0];
+    assert!(call
+        .metadata()
+        .contains(&("x-caller".to_string(), "test".to_string())));
+    assert_eq!(recording.calls()[1].code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn injects_responses_in_turn() {
+    let server = start().await;
+    let mut client = HealthClient::new(server.channel());
+
+    server.inject(
+        CHECK,
+        Injection::new()
+            .status(Status::unavailable("injected"))
+            .times(2),
+    );
+    server.inject(
+        CHECK,
+        Injection::new().delay(Duration::from_millis(100)).times(1),
+    );
+
+    for _ in 0..2 {
+        let status = client.check(check("")).await.unwrap_err();
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(status.message(), "injected");
+    }
+    let started = Instant::now();
+    client.check(check("")).await.unwrap();
+    assert!(started.elapsed() >= Duration::from_millis(100));
+    client.check(check("")).await.unwrap();
+
+    server.assert_calls(CHECK, 4);
+    // Only the requests answered by the service were read.
+    assert_eq!(server.requests::<HealthCheckRequest>(CHECK).len(), 2);
+}
+
+#[tokio::test]
+async fn clears_injections() {
+    let server = start().await;
+    let mut client = HealthClient::new(server.channel());
+
+    server.inject(CHECK, Injection::new().status(Status::internal("injected")));
+    client.check(check("")).await.unwrap_err();
+    client.check(check("")).await.unwrap_err();
+
+    server.clear_injections();
+    client.check(check("")).await.unwrap();
+    server.assert_calls(CHECK, 3);
+}
+
+#[tokio::test]
+#[should_panic(expected = "expected 1 calls to /grpc.health.v1.Health/Check, received 0")]
+async fn asserts_call_counts() {
+    start().await.assert_calls(CHECK, 1);
+}
@@ -1,5 +1,13 @@
 use integration_tests::pb::{test_client, test_server, Input, Output};
-use std::{net::SocketAddr, time::Duration};
+use std::{
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 use tokio::net::TcpListener;
 use tonic::{transport::Server, Code, Request, Response, Status};
 
@@ -90,3 +98,232 @@ async fn run_service_in_background(latency: Duration, server_timeout: Duration)
 
     addr
 }
+
+#[tokio::test]
+async fn exposes_client_deadline() {
+    let addr = run_deadline_service_in_background(Server::builder()).await;
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let remaining = remaining_millis(&mut client, Some("3S")).await.unwrap();
+    assert!((2_000..=3_000).contains(&remaining), "{remaining}ms");
+    assert_eq!(remaining_millis(&mut client, None).await, None);
+}
+
+#[tokio::test]
+async fn applies_default_grpc_timeout() {
+    let server = Server::builder().default_grpc_timeout(Duration::from_secs(10));
+    let addr = run_deadline_service_in_background(server).await;
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let remaining = remaining_millis(&mut client, None).await.unwrap();
+    assert!((9_000..=10_000).contains(&remaining), "{remaining}ms");
+    // The default doesn't override the timeout of the client.
+    let remaining = remaining_millis(&mut client, Some("3S")).await.unwrap();
+    assert!((2_000..=3_000).contains(&remaining), "{remaining}ms");
+}
+
+#[tokio::test]
+async fn clamps_to_max_grpc_timeout() {
+    let server = Server::builder().max_grpc_timeout(Duration::from_secs(5));
+    let addr = run_deadline_service_in_background(server).await;
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    for timeout in [Some("10H"), None] {
+        let remaining = remaining_millis(&mut client, timeout).await.unwrap();
+        assert!((4_000..=5_000).contains(&remaining), "{remaining}ms");
+    }
+    let remaining = remaining_millis(&mut client, Some("3S")).await.unwrap();
+    assert!((2_000..=3_000).contains(&remaining), "{remaining}ms");
+}
+
+#[tokio::test]
+async fn max_grpc_timeout_cancels_requests() {
+    let server = Server::builder().max_grpc_timeout(Duration::from_millis(100));
+    let addr = run_deadline_service_in_background(server).await;
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut().insert("x-sleep", "1".parse().unwrap());
+    req.metadata_mut()
+        .insert("grpc-timeout", "10H".parse().unwrap());
+    let err = client.unary_call(req).await.unwrap_err();
+    assert!(err.message().contains("Timeout expired"));
+    assert_eq!(err.code(), Code::Cancelled);
+}
+
+// Returns the milliseconds left before the deadline of a request, as seen by the handler.
+async fn remaining_millis(
+    client: &mut test_client::TestClient<tonic::transport::Channel>,
+    timeout: Option<&'static str>,
+) -> Option<u128> {
+    let mut req = Request::new(Input {});
+    if let Some(timeout) = timeout {
+        req.metadata_mut()
+            .insert("grpc-timeout", timeout.parse().unwrap());
+    }
+    let res = client.unary_call(req).await.unwrap();
+    res.metadata()
+        .get("x-remaining-ms")
+        .map(|remaining| remaining.to_str().unwrap().parse().unwrap())
+}
+
+async fn run_deadline_service_in_background(mut server: Server) -> SocketAddr {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            if req.metadata().contains_key("x-sleep") {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            let mut res = Response::new(Output {});
+            if let Some(deadline) = req.deadline() {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                res.metadata_mut().insert(
+                    "x-remaining-ms",
+                    remaining.as_millis().to_string().parse().unwrap(),
+                );
+            }
+            Ok(res)
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        server
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+#[tokio::test]
+async fn with_deadline_fails_with_deadline_exceeded() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            let remaining = req.remaining_time().unwrap();
+            assert!(remaining <= Duration::from_millis(100), "{remaining:?}");
+            req.with_deadline(async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok(Response::new(Output {}))
+            })
+            .await
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .max_grpc_timeout(Duration::from_millis(100))
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+    // The client enforces its own timeout too, so the deadline comes from the server.
+    let mut req = Request::new(Input {});
+    req.set_timeout(Duration::from_secs(10));
+    let err = client.unary_call(req).await.unwrap_err();
+    assert_eq!(err.code(), Code::DeadlineExceeded);
+}
+
+#[tokio::test(start_paused = true)]
+async fn deadlines_follow_paused_time() {
+    struct Svc;
+
+    #[tonic::async_trait]
+    impl test_server::Test for Svc {
+        async fn unary_call(&self, req: Request<Input>) -> Result<Response<Output>, Status> {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            let remaining = req.remaining_time().unwrap();
+            let mut res = Response::new(Output {});
+            res.metadata_mut().insert(
+                "x-remaining-ms",
+                remaining.as_millis().to_string().parse().unwrap(),
+            );
+            Ok(res)
+        }
+    }
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .default_grpc_timeout(Duration::from_secs(30))
+            .add_service(test_server::TestServer::new(Svc))
+            .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+    let mut client = test_client::TestClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    // The handler slept for 10s of the paused time of Tokio.
+    assert_eq!(remaining_millis(&mut client, None).await, Some(20_000));
+}
+
+// A timer of Tokio counting the sleeps it creates.
+#[derive(Clone, Default)]
+struct CountingTimer(Arc<AtomicUsize>);
+
+impl hyper::rt::Timer for CountingTimer {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn hyper::rt::Sleep>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        hyper_util::rt::TokioTimer::new().sleep(duration)
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn hyper::rt::Sleep>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        hyper_util::rt::TokioTimer::new().sleep_until(deadline)
+    }
+}
+
+#[tokio::test]
+async fn timeouts_use_configured_timers() {
+    let server_timer = CountingTimer::default();
+    let server = Server::builder()
+        .timer(server_timer.clone())
+        .timeout(Duration::from_millis(100));
+    let addr = run_deadline_service_in_background(server).await;
+
+    let client_timer = CountingTimer::default();
+    let channel = tonic::transport::Endpoint::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .timer(client_timer.clone())
+        .timeout(Duration::from_secs(10))
+        .connect()
+        .await
+        .unwrap();
+    let mut client = test_client::TestClient::new(channel);
+
+    let mut req = Request::new(Input {});
+    req.metadata_mut().insert("x-sleep", "1".parse().unwrap());
+    let err = client.unary_call(req).await.unwrap_err();
+    assert!(err.message().contains("Timeout expired"));
+    assert_eq!(err.code(), Code::Cancelled);
+
+    assert!(server_timer.0.load(Ordering::SeqCst) > 0);
+    assert!(client_timer.0.load(Ordering::SeqCst) > 0);
+}
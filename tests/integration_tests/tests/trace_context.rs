@@ -0,0 +1,117 @@
+use integration_tests::pb::{test1_client::Test1Client, test1_server, Input1, Output1};
+use tokio::net::TcpListener;
+use tokio_stream::Stream;
+use tonic::{
+    service::trace_context::{
+        ClientTraceContext, ClientTraceContextLayer, ServerTraceContextLayer, TraceContext,
+    },
+    transport::{server::TcpIncoming, Channel, Server},
+    Request, Response, Status,
+};
+use tower::ServiceBuilder;
+
+const PARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+type Client = Test1Client<ClientTraceContext<Channel>>;
+
+// Answers with the trace context it got, or with the answer of the backend when it has one.
+struct Svc {
+    backend: Option<Client>,
+}
+
+#[tonic::async_trait]
+impl test1_server::Test1 for Svc {
+    async fn unary_call(&self, req: Request<Input1>) -> Result<Response<Output1>, Status> {
+        let context = req.extensions().get::<TraceContext>().unwrap();
+        assert_eq!(Some(context), TraceContext::current().as_ref());
+        match &self.backend {
+            Some(backend) => backend.clone().unary_call(Input1::default()).await,
+            None => {
+                let received = [
+                    req.metadata().get("traceparent"),
+                    req.metadata().get("baggage"),
+                ];
+                let buf = received
+                    .iter()
+                    .map(|value| value.map_or("", |value| value.to_str().unwrap()))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                Ok(Response::new(Output1 { buf: buf.into() }))
+            }
+        }
+    }
+
+    type StreamCallStream =
+        std::pin::Pin<Box<dyn Stream<Item = Result<Output1, Status>> + Send + 'static>>;
+
+    async fn stream_call(
+        &self,
+        _: Request<Input1>,
+    ) -> Result<Response<Self::StreamCallStream>, Status> {
+        Err(Status::unimplemented(""))
+    }
+}
+
+async fn spawn(backend: Option<Client>) -> Client {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .layer(ServerTraceContextLayer::new())
+            .add_service(test1_server::Test1Server::new(Svc { backend }))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let channel = ServiceBuilder::new()
+        .layer(ClientTraceContextLayer::new())
+        .service(channel);
+    Test1Client::new(channel)
+}
+
+async fn call(client: &mut Client, metadata: &[(&'static str, &'static str)]) -> String {
+    let mut req = Request::new(Input1::default());
+    for (key, value) in metadata {
+        req.metadata_mut().insert(*key, value.parse().unwrap());
+    }
+    let res = client.unary_call(req).await.unwrap();
+    String::from_utf8(res.into_inner().buf).unwrap()
+}
+
+#[tokio::test]
+async fn handlers_propagate_the_trace_context_of_their_request() {
+    let backend = spawn(None).await;
+    let mut frontend = spawn(Some(backend)).await;
+
+    let received = call(
+        &mut frontend,
+        &[("traceparent", PARENT), ("baggage", "user=alice")],
+    )
+    .await;
+    assert_eq!(received, format!("{PARENT} user=alice"));
+
+    let received = call(&mut frontend, &[]).await;
+    assert_eq!(received, " ");
+}
+
+#[tokio::test]
+async fn requests_keep_their_own_trace_context() {
+    let backend = spawn(None).await;
+    let mut backend = backend.clone();
+
+    let other = "00-11111111111111111111111111111111-2222222222222222-00";
+    let context = TraceContext::extract(&{
+        let mut metadata = tonic::metadata::MetadataMap::new();
+        metadata.insert("traceparent", PARENT.parse().unwrap());
+        metadata
+    });
+    let received = context
+        .scope(call(&mut backend, &[("traceparent", other)]))
+        .await;
+    assert_eq!(received, format!("{other} "));
+}
@@ -0,0 +1,114 @@
+use std::net::SocketAddr;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use integration_tests::pb::{test_client, test_server, Input, Output};
+use tokio::net::TcpListener;
+use tonic::transport::channel::{Change, TrafficSplit};
+use tonic::transport::{server::TcpIncoming, Channel, Endpoint, Server};
+use tonic::{Request, Response, Status};
+
+struct Svc(Arc<AtomicUsize>);
+
+#[tonic::async_trait]
+impl test_server::Test for Svc {
+    async fn unary_call(&self, _req: Request<Input>) -> Result<Response<Output>, Status> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(Response::new(Output {}))
+    }
+}
+
+#[tokio::test]
+async fn splits_traffic_across_groups() {
+    let stable = Arc::new(AtomicUsize::new(0));
+    let canary = Arc::new(AtomicUsize::new(0));
+    let (channel, tx) = Channel::balance_channel_with_groups(16);
+    for (key, calls, group) in [
+        (1, &stable, "stable"),
+        (2, &canary, "canary"),
+        (3, &stable, "stable"),
+    ] {
+        let addr = spawn(calls.clone()).await;
+        let endpoint = Endpoint::from_shared(format!("http://{addr}"))
+            .unwrap()
+            .group(group);
+        tx.send(Change::Insert(key, endpoint)).await.unwrap();
+    }
+    let mut client = test_client::TestClient::new(channel);
+    let counters = [stable, canary];
+
+    let split = |stable, canary| {
+        TrafficSplit::new()
+            .group("stable", stable)
+            .group("canary", canary)
+    };
+    tx.send(Change::Split(split(100.0, 0.0))).await.unwrap();
+    assert_eq!(calls(&mut client, &counters).await, [200, 0]);
+    tx.send(Change::Split(split(0.0, 100.0))).await.unwrap();
+    assert_eq!(calls(&mut client, &counters).await, [0, 200]);
+
+    tx.send(Change::Split(split(80.0, 20.0))).await.unwrap();
+    let [stable, canary] = calls(&mut client, &counters).await;
+    assert_eq!(stable + canary, 200);
+    assert!((10..=80).contains(&canary), "{canary} canary calls");
+}
+
+// Make 200 calls, returning the calls of each counter.
+async fn calls(
+    client: &mut test_client::TestClient<Channel>,
+    counters: &[Arc<AtomicUsize>; 2],
+) -> [usize; 2] {
+    for _ in 0..200 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    [0, 1].map(|i| counters[i].swap(0, Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn spills_over_without_ready_endpoints() {
+    let stable = Arc::new(AtomicUsize::new(0));
+    let (channel, tx) = Channel::balance_channel_with_groups(16);
+    let addr = spawn(stable.clone()).await;
+    tx.send(Change::Insert(
+        1,
+        Endpoint::from_shared(format!("http://{addr}")).unwrap(),
+    ))
+    .await
+    .unwrap();
+    // The canary endpoint is removed, so the canary group has no endpoint.
+    tx.send(Change::Insert(
+        2,
+        Endpoint::from_static("http://canary").group("canary"),
+    ))
+    .await
+    .unwrap();
+    tx.send(Change::Remove(2)).await.unwrap();
+    tx.send(Change::Split(
+        TrafficSplit::new()
+            .group("default", 50.0)
+            .group("canary", 50.0),
+    ))
+    .await
+    .unwrap();
+
+    let mut client = test_client::TestClient::new(channel);
+    for _ in 0..20 {
+        client.unary_call(Input {}).await.unwrap();
+    }
+    assert_eq!(stable.load(Ordering::SeqCst), 20);
+}
+
+async fn spawn(calls: Arc<AtomicUsize>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(test_server::TestServer::new(Svc(calls)))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    addr
+}
@@ -0,0 +1,7 @@
+fn main() {
+    tonic_build::configure()
+        .build_mock(true)
+        .use_local_futures(true)
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
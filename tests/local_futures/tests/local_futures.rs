@@ -0,0 +1,108 @@
+use std::{cell::Cell, rc::Rc};
+
+use local_futures::{
+    test_client::{MockTestClient, TestClient, TestClientApi},
+    test_server::{Test, TestServer},
+    Input, Output,
+};
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status, Streaming};
+
+// Handlers hold non-`Send` state across `.await` points.
+#[derive(Default)]
+struct Svc {
+    calls: Rc<Cell<i32>>,
+}
+
+#[tonic::async_trait(?Send)]
+impl Test for Svc {
+    async fn unary(&self, request: Request<Input>) -> Result<Response<Output>, Status> {
+        let calls = self.calls.clone();
+        tokio::task::yield_now().await;
+        calls.set(calls.get() + 1);
+
+        Ok(Response::new(Output {
+            value: request.into_inner().value + calls.get(),
+        }))
+    }
+
+    async fn client_stream(
+        &self,
+        request: Request<Streaming<Input>>,
+    ) -> Result<Response<Output>, Status> {
+        let calls = self.calls.clone();
+        let mut stream = request.into_inner();
+
+        let mut value = 0;
+        while let Some(input) = stream.next().await {
+            value += input?.value;
+        }
+        calls.set(calls.get() + 1);
+
+        Ok(Response::new(Output { value }))
+    }
+
+    type ServerStreamStream = tokio_stream::Iter<std::vec::IntoIter<Result<Output, Status>>>;
+
+    async fn server_stream(
+        &self,
+        request: Request<Input>,
+    ) -> Result<Response<Self::ServerStreamStream>, Status> {
+        let calls = self.calls.clone();
+        tokio::task::yield_now().await;
+        calls.set(calls.get() + 1);
+
+        let value = request.into_inner().value;
+        let outputs = (0..value)
+            .map(|value| Ok(Output { value }))
+            .collect::<Vec<_>>();
+        Ok(Response::new(tokio_stream::iter(outputs)))
+    }
+}
+
+#[tokio::test]
+async fn local_server() {
+    let svc = Svc::default();
+    let calls = svc.calls.clone();
+    // The server is used as the transport of the client, on the current thread.
+    let mut client = TestClient::new(TestServer::new(svc));
+
+    let response = client.unary(Input { value: 41 }).await.unwrap();
+    assert_eq!(response.into_inner().value, 42);
+
+    let inputs = tokio_stream::iter([1, 2, 3].map(|value| Input { value }));
+    let response = client.client_stream(inputs).await.unwrap();
+    assert_eq!(response.into_inner().value, 6);
+
+    let response = client.server_stream(Input { value: 3 }).await.unwrap();
+    let values = response
+        .into_inner()
+        .map(|output| output.unwrap().value)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(values, [0, 1, 2]);
+
+    assert_eq!(calls.get(), 3);
+}
+
+#[tokio::test]
+async fn local_mock() {
+    let calls = Rc::new(Cell::new(0));
+
+    // Code under test only depends on the generated trait, which is not `Send`.
+    async fn unary<C: TestClientApi>(client: &mut C, calls: Rc<Cell<i32>>) -> i32 {
+        let response = client.unary(Request::new(Input { value: 1 })).await;
+        calls.set(calls.get() + 1);
+        response.unwrap().into_inner().value
+    }
+
+    let mut mock = MockTestClient::new();
+    mock.on_unary(|request| {
+        Ok(Response::new(Output {
+            value: request.into_inner().value * 2,
+        }))
+    });
+
+    assert_eq!(unary(&mut mock, calls.clone()).await, 2);
+    assert_eq!(calls.get(), 1);
+}
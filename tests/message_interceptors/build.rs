@@ -0,0 +1,11 @@
+fn main() {
+    tonic_build::configure()
+        .intercept_messages("test.Library.GetBook")
+        .intercept_messages("test.Library.ListBooks")
+        .handler_extractor(
+            "test.Library.GetBook",
+            "tonic::server::Extension<crate::Shelf>",
+        )
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
@@ -0,0 +1,84 @@
+use std::pin::Pin;
+
+use tokio_stream::Stream;
+use tonic::{
+    server::{Extension, MessageInterceptors},
+    Request, Response, Status,
+};
+
+pub mod pb {
+    tonic::include_proto!("test");
+}
+
+use pb::{Book, GetBookRequest, ListBooksRequest};
+
+/// The shelf of the book named by a request, recorded by the interceptors.
+#[derive(Debug, Clone)]
+pub struct Shelf(pub String);
+
+/// Records the shelves of the books named by the requests, rejecting the
+/// names of other resources, and the requests listing hidden shelves.
+pub fn interceptors() -> MessageInterceptors {
+    MessageInterceptors::new()
+        .on(|method, request: &GetBookRequest, extensions| {
+            match request.name.split_once("/books/") {
+                Some((shelf, _)) => {
+                    extensions.insert(Shelf(shelf.to_string()));
+                    Ok(())
+                }
+                None => Err(Status::invalid_argument(format!(
+                    "{} takes the name of a book",
+                    method.method()
+                ))),
+            }
+        })
+        .on(|_, request: &ListBooksRequest, _| {
+            if request.parent == "shelves/hidden" {
+                return Err(Status::permission_denied("hidden shelf"));
+            }
+            Ok(())
+        })
+}
+
+#[derive(Debug, Default)]
+pub struct Svc;
+
+#[tonic::async_trait]
+impl pb::library_server::Library for Svc {
+    async fn get_book(
+        &self,
+        Extension(Shelf(shelf)): Extension<Shelf>,
+        request: Request<GetBookRequest>,
+    ) -> Result<Response<Book>, Status> {
+        Ok(Response::new(Book {
+            name: request.into_inner().name,
+            shelf,
+        }))
+    }
+
+    type ListBooksStream = Pin<Box<dyn Stream<Item = Result<Book, Status>> + Send + 'static>>;
+
+    async fn list_books(
+        &self,
+        request: Request<ListBooksRequest>,
+    ) -> Result<Response<Self::ListBooksStream>, Status> {
+        let parent = request.into_inner().parent;
+        let book = Book {
+            name: format!("{parent}/books/1"),
+            shelf: parent,
+        };
+        Ok(Response::new(Box::pin(tokio_stream::once(Ok(book)))))
+    }
+
+    async fn delete_book(
+        &self,
+        request: Request<GetBookRequest>,
+    ) -> Result<Response<Book>, Status> {
+        // Not intercepted, so nothing is recorded.
+        assert!(request.extensions().get::<Shelf>().is_none());
+        Ok(Response::new(Book {
+            name: request.into_inner().name,
+            shelf: String::new(),
+        }))
+    }
+}
@@ -0,0 +1,82 @@
+use message_interceptors::{
+    interceptors,
+    pb::{
+        library_client::LibraryClient, library_server::LibraryServer, GetBookRequest,
+        ListBooksRequest,
+    },
+    Svc,
+};
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Server},
+    Code,
+};
+
+async fn spawn() -> Channel {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(LibraryServer::with_interceptor(Svc, interceptors()))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+fn get_book(name: &str) -> GetBookRequest {
+    GetBookRequest {
+        name: name.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn interceptors_enrich_requests() {
+    let mut client = LibraryClient::new(spawn().await);
+
+    let book = client
+        .get_book(get_book("shelves/1/books/2"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(book.shelf, "shelves/1");
+
+    let request = ListBooksRequest {
+        parent: "shelves/1".to_string(),
+    };
+    let mut stream = client.list_books(request).await.unwrap().into_inner();
+    assert_eq!(stream.next().await.unwrap().unwrap().shelf, "shelves/1");
+}
+
+#[tokio::test]
+async fn failing_interceptors_answer_requests() {
+    let mut client = LibraryClient::new(spawn().await);
+
+    let status = client.get_book(get_book("shelves/1")).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "GetBook takes the name of a book");
+
+    let request = ListBooksRequest {
+        parent: "shelves/hidden".to_string(),
+    };
+    let status = client.list_books(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+}
+
+#[tokio::test]
+async fn unmatched_methods_are_not_intercepted() {
+    let mut client = LibraryClient::new(spawn().await);
+
+    let book = client
+        .delete_book(get_book("shelves/1"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(book.name, "shelves/1");
+}
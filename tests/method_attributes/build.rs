@@ -0,0 +1,7 @@
+fn main() {
+    tonic_build::configure()
+        .client_method_attribute("test.Service1.Traced", "#[doc(alias = \"client_traced\")]")
+        .server_method_attribute("Service1.Traced", "#[doc(alias = \"server_traced\")]")
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
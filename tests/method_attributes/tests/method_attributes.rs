@@ -0,0 +1,15 @@
+use std::{fs, path::PathBuf};
+
+#[test]
+fn test() {
+    let path = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("test.rs");
+    let s = fs::read_to_string(path)
+        .unwrap()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert!(s.contains("#[doc(alias = \"client_traced\")] pub async fn traced("));
+    assert!(s.contains("#[doc(alias = \"server_traced\")] async fn traced("));
+    assert_eq!(s.matches("client_traced").count(), 1);
+    assert_eq!(s.matches("server_traced").count(), 1);
+}
@@ -0,0 +1,7 @@
+fn main() {
+    tonic_build::configure()
+        .build_server(false)
+        .build_mock(true)
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
@@ -0,0 +1,95 @@
+use mock_client::{
+    test_client::{MockTestClient, TestClient, TestClientApi},
+    Input, Output,
+};
+use tokio_stream::StreamExt;
+use tonic::{transport::Channel, Code, Request, Response, Status};
+
+// Code under test only depends on the generated trait.
+async fn double<C: TestClientApi>(client: &mut C, value: i32) -> Result<i32, Status> {
+    let response = client.unary(Request::new(Input { value })).await?;
+    Ok(response.into_inner().value * 2)
+}
+
+#[test]
+fn client_implements_api() {
+    fn assert_api<C: TestClientApi>() {}
+
+    assert_api::<TestClient<Channel>>();
+}
+
+#[tokio::test]
+async fn unary() {
+    let mut mock = MockTestClient::new();
+    mock.on_unary(|request| {
+        Ok(Response::new(Output {
+            value: request.into_inner().value + 1,
+        }))
+    });
+
+    assert_eq!(double(&mut mock, 20).await.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn not_mocked() {
+    let mut mock = MockTestClient::new();
+
+    let status = double(&mut mock, 1).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+#[tokio::test]
+async fn streaming() {
+    let mut mock = MockTestClient::new();
+    mock.on_server_stream(|request| {
+        let value = request.into_inner().value;
+        let stream = tokio_stream::iter((0..value).map(|value| Ok(Output { value })));
+        Ok(Response::new(Box::pin(stream)))
+    })
+    .on_client_stream(|request| {
+        let value = request.into_inner().iter().map(|input| input.value).sum();
+        Ok(Response::new(Output { value }))
+    })
+    .on_bidirectional_stream(|request| {
+        let outputs: Vec<_> = request
+            .into_inner()
+            .into_iter()
+            .map(|input| {
+                Ok(Output {
+                    value: -input.value,
+                })
+            })
+            .collect();
+        Ok(Response::new(Box::pin(tokio_stream::iter(outputs))))
+    });
+
+    let mut client = mock.clone();
+
+    let values: Vec<i32> = client
+        .server_stream(Request::new(Input { value: 3 }))
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|output| output.unwrap().value)
+        .collect()
+        .await;
+    assert_eq!(values, [0, 1, 2]);
+
+    let inputs = || tokio_stream::iter([1, 2, 3].map(|value| Input { value }));
+
+    let response = client
+        .client_stream(Request::new(Box::pin(inputs())))
+        .await
+        .unwrap();
+    assert_eq!(response.into_inner().value, 6);
+
+    let values: Vec<i32> = client
+        .bidirectional_stream(Request::new(Box::pin(inputs())))
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|output| output.unwrap().value)
+        .collect()
+        .await;
+    assert_eq!(values, [-1, -2, -3]);
+}
@@ -0,0 +1,9 @@
+fn main() {
+    tonic_build::configure()
+        .redact_field(".test.User.email")
+        .redact_field(".test.User.Address.street")
+        .redact_field("password")
+        .redact_field(".test.Credentials.token")
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
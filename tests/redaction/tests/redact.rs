@@ -0,0 +1,104 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use http_body_util::{BodyExt, Full};
+use prost::Message;
+use redaction::pb::{credentials::Secret, user::Address, Credentials, SignUpRequest, User};
+use tonic::{body::Body, service::RecordLayer, RedactMessage, Redacted};
+use tower::{Layer, Service};
+
+fn request() -> SignUpRequest {
+    let address = Address {
+        street: "1 Main St".to_string(),
+        city: "Springfield".to_string(),
+    };
+    SignUpRequest {
+        user: Some(User {
+            name: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            addresses: vec![address.clone()],
+            named_addresses: [("home".to_string(), address)].into(),
+        }),
+        credentials: Some(Credentials {
+            secret: Some(Secret::Password("hunter2".to_string())),
+        }),
+        referrer: "bob".to_string(),
+    }
+}
+
+fn frame(message: &impl Message) -> Bytes {
+    let mut buf = BytesMut::new();
+    buf.put_u8(0);
+    buf.put_u32(message.encoded_len() as u32);
+    message.encode(&mut buf).unwrap();
+    buf.freeze()
+}
+
+#[test]
+fn redacts_fields() {
+    let mut redacted = request();
+    redacted.redact();
+
+    let user = redacted.user.as_ref().unwrap();
+    assert_eq!(user.name, "alice");
+    assert_eq!(user.email, "");
+    assert_eq!(user.addresses[0].street, "");
+    assert_eq!(user.addresses[0].city, "Springfield");
+    // Map values aren't redacted.
+    assert_eq!(user.named_addresses["home"].street, "1 Main St");
+    assert_eq!(redacted.credentials.as_ref().unwrap().secret, None);
+    assert_eq!(redacted.referrer, "bob");
+
+    let mut credentials = Credentials {
+        secret: Some(Secret::KeyId("key-1".to_string())),
+    };
+    credentials.redact();
+    assert_eq!(credentials.secret, Some(Secret::KeyId("key-1".to_string())));
+}
+
+#[test]
+fn formats_redacted_messages() {
+    let request = request();
+    let formatted = format!("{:?}", Redacted(&request));
+    assert!(formatted.contains("alice"));
+    assert!(!formatted.contains("alice@example.com"));
+    assert!(!formatted.contains("hunter2"));
+    assert_eq!(
+        request.credentials.unwrap().secret,
+        Some(Secret::Password("hunter2".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn records_redacted_requests() {
+    const PATH: &str = "/test.Accounts/SignUp";
+
+    let recorder = RecordLayer::new().redact_requests::<SignUpRequest>(PATH);
+    let mut svc =
+        recorder
+            .clone()
+            .layer(tower::service_fn(|req: http::Request<Body>| async move {
+                let body = req.into_body().collect().await?.to_bytes();
+                let res = http::Response::builder()
+                    .header("content-type", "application/grpc")
+                    .header("grpc-status", "0")
+                    .body(Full::new(body))
+                    .unwrap();
+                Ok::<_, tonic::codegen::StdError>(res)
+            }));
+
+    let req = http::Request::builder()
+        .uri(PATH)
+        .body(Body::new(Full::new(frame(&request()))))
+        .unwrap();
+    let res = svc.call(req).await.unwrap();
+    let echoed = res.into_body().collect().await.unwrap().to_bytes();
+    // Only the recording is redacted.
+    assert_eq!(echoed, frame(&request()));
+
+    let recording = recorder.recording();
+    let call = &recording.calls()[0];
+    let mut expected = request();
+    expected.redact();
+    let recorded = SignUpRequest::decode(call.requests()[0].clone()).unwrap();
+    assert_eq!(recorded, expected);
+    assert_eq!(call.responses()[0], frame(&request()).slice(5..));
+}
@@ -0,0 +1,7 @@
+fn main() {
+    tonic_build::configure()
+        .request_validator("test.Accounts", "crate::validate")
+        .request_validator("test.Accounts.Rename", "crate::reject_reserved_names")
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
@@ -0,0 +1,106 @@
+use std::pin::Pin;
+
+use tokio_stream::Stream;
+use tonic::{Code, Request, Response, Status, Streaming};
+use tonic_types::{ErrorDetails, StatusExt};
+
+pub mod pb {
+    tonic::include_proto!("test");
+}
+
+use pb::{Account, RenameRequest, WatchRequest};
+
+/// The constraints of the request messages.
+pub trait Validate {
+    /// The violations of the constraints, as `(field, description)` pairs.
+    fn violations(&self) -> Vec<(&'static str, &'static str)>;
+}
+
+impl Validate for RenameRequest {
+    fn violations(&self) -> Vec<(&'static str, &'static str)> {
+        let mut violations = Vec::new();
+        if self.id.is_empty() {
+            violations.push(("id", "must not be empty"));
+        }
+        if self.name.is_empty() {
+            violations.push(("name", "must not be empty"));
+        }
+        violations
+    }
+}
+
+impl Validate for WatchRequest {
+    fn violations(&self) -> Vec<(&'static str, &'static str)> {
+        if self.id.is_empty() {
+            vec![("id", "must not be empty")]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Rejects the messages breaking their constraints with `InvalidArgument`.
+pub fn validate<M: Validate>(message: &M) -> Result<(), Status> {
+    let violations = message.violations();
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    let mut details = ErrorDetails::new();
+    for (field, description) in violations {
+        details.add_bad_request_violation(field, description);
+    }
+    Err(Status::with_error_details(
+        Code::InvalidArgument,
+        "invalid request",
+        details,
+    ))
+}
+
+/// Rejects the renames to reserved names.
+pub fn reject_reserved_names(request: &RenameRequest) -> Result<(), Status> {
+    if request.name == "root" {
+        return Err(Status::permission_denied("reserved name"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Default)]
+pub struct Svc;
+
+#[tonic::async_trait]
+impl pb::accounts_server::Accounts for Svc {
+    async fn rename(&self, request: Request<RenameRequest>) -> Result<Response<Account>, Status> {
+        let RenameRequest { id, name } = request.into_inner();
+        assert!(!id.is_empty() && !name.is_empty());
+        Ok(Response::new(Account { id, name }))
+    }
+
+    type WatchStream = Pin<Box<dyn Stream<Item = Result<Account, Status>> + Send + 'static>>;
+
+    async fn watch(
+        &self,
+        request: Request<WatchRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let account = Account {
+            id: request.into_inner().id,
+            name: String::new(),
+        };
+        Ok(Response::new(Box::pin(tokio_stream::once(Ok(account)))))
+    }
+
+    async fn upload(
+        &self,
+        request: Request<Streaming<RenameRequest>>,
+    ) -> Result<Response<Account>, Status> {
+        let mut stream = request.into_inner();
+        let mut account = Account::default();
+        while let Some(rename) = stream.message().await? {
+            account = Account {
+                id: rename.id,
+                name: rename.name,
+            };
+        }
+        Ok(Response::new(account))
+    }
+}
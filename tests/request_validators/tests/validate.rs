@@ -0,0 +1,94 @@
+use request_validators::{
+    pb::{
+        accounts_client::AccountsClient, accounts_server::AccountsServer, RenameRequest,
+        WatchRequest,
+    },
+    Svc,
+};
+use tokio::net::TcpListener;
+use tokio_stream::StreamExt;
+use tonic::{
+    transport::{server::TcpIncoming, Channel, Server},
+    Code,
+};
+use tonic_types::StatusExt;
+
+async fn spawn() -> Channel {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(AccountsServer::new(Svc))
+            .serve_with_incoming(TcpIncoming::from(listener))
+            .await
+            .unwrap();
+    });
+    Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap()
+}
+
+fn rename(id: &str, name: &str) -> RenameRequest {
+    RenameRequest {
+        id: id.to_string(),
+        name: name.to_string(),
+    }
+}
+
+#[tokio::test]
+async fn valid_requests_reach_handlers() {
+    let mut client = AccountsClient::new(spawn().await);
+
+    let account = client
+        .rename(rename("1", "alice"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(account.name, "alice");
+
+    let request = WatchRequest {
+        id: "1".to_string(),
+    };
+    let mut stream = client.watch(request).await.unwrap().into_inner();
+    assert_eq!(stream.next().await.unwrap().unwrap().id, "1");
+}
+
+#[tokio::test]
+async fn invalid_requests_are_answered_with_bad_request() {
+    let mut client = AccountsClient::new(spawn().await);
+
+    let status = client.rename(rename("", "")).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let bad_request = status.get_details_bad_request().unwrap();
+    let fields: Vec<_> = bad_request
+        .field_violations
+        .iter()
+        .map(|violation| violation.field.as_str())
+        .collect();
+    assert_eq!(fields, ["id", "name"]);
+
+    let status = client.watch(WatchRequest::default()).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn validators_are_called_in_order() {
+    let mut client = AccountsClient::new(spawn().await);
+
+    let status = client.rename(rename("1", "root")).await.unwrap_err();
+    assert_eq!(status.code(), Code::PermissionDenied);
+
+    let status = client.rename(rename("", "root")).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn request_streams_are_not_validated() {
+    let mut client = AccountsClient::new(spawn().await);
+
+    let renames = tokio_stream::iter([rename("", "")]);
+    let account = client.upload(renames).await.unwrap().into_inner();
+    assert_eq!(account.id, "");
+}
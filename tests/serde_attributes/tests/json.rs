@@ -0,0 +1,73 @@
+use serde_attributes::pb::{
+    book::{cover::Kind, Cover, Location},
+    Book, Genre,
+};
+use serde_json::json;
+
+fn book() -> Book {
+    Book {
+        book_id: "978-0".to_string(),
+        genre: Genre::Fiction.into(),
+        previous_genres: vec![Genre::Poetry.into(), 42],
+        next_genre: Some(Genre::Poetry.into()),
+        page_count: 320,
+        location: Some(Location::Section(Genre::Fiction.into())),
+        cover: Some(Cover {
+            kind: Kind::Hardcover.into(),
+            genre: Genre::Unspecified.into(),
+        }),
+    }
+}
+
+fn book_json() -> serde_json::Value {
+    json!({
+        "bookId": "978-0",
+        "genre": "GENRE_FICTION",
+        "previousGenres": ["GENRE_POETRY", 42],
+        "nextGenre": "GENRE_POETRY",
+        "pageCount": 320,
+        "section": "GENRE_FICTION",
+        "cover": {
+            "kind": "KIND_HARDCOVER",
+            "genre": "GENRE_UNSPECIFIED",
+        },
+    })
+}
+
+#[test]
+fn serialize() {
+    assert_eq!(serde_json::to_value(book()).unwrap(), book_json());
+}
+
+#[test]
+fn deserialize() {
+    let book: Book = serde_json::from_value(book_json()).unwrap();
+    assert_eq!(book, self::book());
+}
+
+#[test]
+fn deserialize_defaults_and_numbers() {
+    let book: Book = serde_json::from_value(json!({
+        "genre": 2,
+        "shelfName": "A3",
+    }))
+    .unwrap();
+
+    assert_eq!(
+        book,
+        Book {
+            genre: Genre::Poetry.into(),
+            location: Some(Location::ShelfName("A3".to_string())),
+            ..Default::default()
+        }
+    );
+
+    let book: Book = serde_json::from_value(json!({})).unwrap();
+    assert_eq!(book, Book::default());
+}
+
+#[test]
+fn deserialize_unknown_name() {
+    let err = serde_json::from_value::<Book>(json!({ "genre": "GENRE_DRAMA" })).unwrap_err();
+    assert!(err.to_string().contains("GENRE_DRAMA"), "{err}");
+}
@@ -0,0 +1,8 @@
+fn main() {
+    tonic_build::configure()
+        .include_file("mod.rs")
+        .service_registry("services.rs")
+        .service_registry_features(true)
+        .compile_protos(&["proto/greeter.proto", "proto/echo.proto"], &["proto"])
+        .unwrap();
+}
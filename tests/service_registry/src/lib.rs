@@ -0,0 +1,2 @@
+include!(concat!(env!("OUT_DIR"), "/mod.rs"));
+include!(concat!(env!("OUT_DIR"), "/services.rs"));
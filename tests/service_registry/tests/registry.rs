@@ -0,0 +1,60 @@
+use service_registry::{
+    registry::greeter::{greeter_client::GreeterClient, greeter_server, HelloReply, HelloRequest},
+    ServiceId, Services,
+};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::{transport::Server, Request, Response, Status};
+
+struct Greeter;
+
+#[tonic::async_trait]
+impl greeter_server::Greeter for Greeter {
+    async fn say_hello(
+        &self,
+        request: Request<HelloRequest>,
+    ) -> Result<Response<HelloReply>, Status> {
+        Ok(Response::new(HelloReply {
+            message: format!("Hello {}!", request.into_inner().name),
+        }))
+    }
+}
+
+#[test]
+fn lists_enabled_services() {
+    assert_eq!(ServiceId::ALL, [ServiceId::RegistryGreeterGreeter]);
+    assert_eq!(
+        ServiceId::RegistryGreeterGreeter.name(),
+        "registry.greeter.Greeter"
+    );
+}
+
+#[tokio::test]
+async fn add_all() {
+    let services = Services::new().registry_greeter_greeter(Greeter);
+    assert_eq!(services.registered(), [ServiceId::RegistryGreeterGreeter]);
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        services
+            .add_all(&mut Server::builder())
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    let mut client = GreeterClient::connect(format!("http://{addr}"))
+        .await
+        .unwrap();
+
+    let response = client
+        .say_hello(HelloRequest {
+            name: "registry".to_string(),
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(response.into_inner().message, "Hello registry!");
+}
@@ -0,0 +1,10 @@
+fn main() {
+    tonic_build::configure()
+        .split_by_file(true)
+        .include_file("mod.rs")
+        .compile_protos(
+            &["proto/service.proto", "proto/other/item.proto"],
+            &["proto"],
+        )
+        .unwrap();
+}
@@ -0,0 +1,11 @@
+pub mod split {
+    tonic::include_proto!("split");
+
+    pub mod other {
+        tonic::include_proto!("split.other");
+    }
+}
+
+pub mod included {
+    include!(concat!(env!("OUT_DIR"), "/mod.rs"));
+}
@@ -0,0 +1,43 @@
+use split_by_file::split::{other::Item, pinger_client::PingerClient, Batch, Ping, Pong};
+
+#[test]
+fn messages_from_all_files() {
+    let batch = Batch {
+        pings: vec![Ping { value: 1 }],
+        item: Some(Item {
+            pong: Some(Pong { value: 2 }),
+        }),
+    };
+
+    assert_eq!(batch.item.unwrap().pong.unwrap().value, 2);
+
+    let included = split_by_file::included::split::other::Item {
+        pong: Some(split_by_file::included::split::Pong { value: 2 }),
+    };
+
+    assert_eq!(included.pong.unwrap().value, 2);
+}
+
+#[test]
+fn services_are_generated() {
+    fn assert_client<T>(_: Option<PingerClient<T>>) {}
+    assert_client::<tonic::transport::Channel>(None);
+}
+
+#[test]
+fn one_file_per_proto() {
+    let package = include_str!(concat!(env!("OUT_DIR"), "/split.rs"));
+
+    assert!(package.contains(r#"include!("split/messages.rs");"#));
+    assert!(package.contains(r#"include!("split/service.rs");"#));
+
+    let service = include_str!(concat!(env!("OUT_DIR"), "/split/service.rs"));
+
+    assert!(service.contains("pub struct Batch"));
+    assert!(service.contains("pub mod pinger_client"));
+    assert!(!service.contains("pub struct Ping "));
+
+    let other = include_str!(concat!(env!("OUT_DIR"), "/split.other.rs"));
+
+    assert!(other.contains(r#"include!("split.other/other/item.rs");"#));
+}
@@ -1,8 +1,18 @@
+use std::{env, path::PathBuf};
+
 fn main() {
-    let protos = &["proto/test.proto"];
+    let protos = &["proto/test.proto", "proto/library.proto"];
+
+    tonic_build::configure()
+        .build_web_client(true)
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
 
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
     tonic_build::configure()
-        .compile_protos(protos, &["proto"])
+        .build_http_routes(true)
+        .file_descriptor_set_path(out_dir.join("library_descriptor.bin"))
+        .compile_protos(&["proto/library.proto"], &["proto"])
         .unwrap();
 
     protos
@@ -9,6 +9,13 @@ pub mod pb {
     tonic::include_proto!("test");
 }
 
+pub mod library {
+    tonic::include_proto!("library");
+
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("library_descriptor");
+}
+
 type BoxStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + 'static>>;
 
 pub struct Svc;
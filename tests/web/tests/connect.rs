@@ -0,0 +1,198 @@
+use std::net::SocketAddr;
+
+use base64::Engine as _;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http_body_util::{BodyExt as _, Full};
+use hyper::http::{header, StatusCode};
+use hyper::{Method, Request, Response};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use prost::Message;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::StreamExt;
+use tonic::body::Body;
+use tonic::transport::Server;
+use tower_layer::Layer;
+
+use test_web::pb::{test_client::TestClient, test_server::TestServer, Input, Output};
+use test_web::Svc;
+use tonic_web::{ConnectClientLayer, ConnectLayer, GrpcWebLayer};
+
+#[tokio::test]
+async fn unary() {
+    let server_url = spawn().await;
+    let res = send(
+        Request::builder()
+            .method(Method::POST)
+            .uri(format!("{server_url}/test.Test/UnaryCall"))
+            .header(header::CONTENT_TYPE, "application/proto")
+            .header("connect-protocol-version", "1")
+            .body(Full::new(input(1, "one").encode_to_vec().into()))
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/proto");
+    assert!(!res.headers().contains_key("grpc-status"));
+
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(Output::decode(body).unwrap(), output(1, "one"));
+}
+
+#[tokio::test]
+async fn unary_error() {
+    let server_url = spawn().await;
+    let res = send(
+        Request::builder()
+            .method(Method::POST)
+            .uri(format!("{server_url}/test.Test/UnaryCall"))
+            .header(header::CONTENT_TYPE, "application/proto")
+            .header("connect-protocol-version", "1")
+            .body(Full::new(input(1, "boom").encode_to_vec().into()))
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(
+        &body[..],
+        br#"{"code":"invalid_argument","message":"invalid boom"}"#
+    );
+}
+
+#[tokio::test]
+async fn unary_get() {
+    let server_url = spawn().await;
+    let message =
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(input(2, "two").encode_to_vec());
+    let res = send(
+        Request::builder()
+            .method(Method::GET)
+            .uri(format!(
+                "{server_url}/test.Test/UnaryCall?connect=v1&encoding=proto&base64=1&message={message}"
+            ))
+            .body(Full::default())
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(Output::decode(body).unwrap(), output(2, "two"));
+}
+
+#[tokio::test]
+async fn server_stream() {
+    let server_url = spawn().await;
+    let mut body = BytesMut::new();
+    body.put_u8(0);
+    body.put_u32(input(3, "three").encoded_len() as u32);
+    input(3, "three").encode(&mut body).unwrap();
+
+    let res = send(
+        Request::builder()
+            .method(Method::POST)
+            .uri(format!("{server_url}/test.Test/ServerStream"))
+            .header(header::CONTENT_TYPE, "application/connect+proto")
+            .body(Full::new(body.freeze()))
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        res.headers()[header::CONTENT_TYPE],
+        "application/connect+proto"
+    );
+
+    let mut body = res.into_body().collect().await.unwrap().to_bytes();
+    for n in 1..=2 {
+        assert_eq!(body.get_u8(), 0);
+        let len = body.get_u32() as usize;
+        let message = Output::decode(body.split_to(len)).unwrap();
+        assert_eq!(message, output(3, &format!("{n}-three")));
+    }
+
+    // The end-of-stream message, without error
+    assert_eq!(body.get_u8(), 0b10);
+    let len = body.get_u32() as usize;
+    assert_eq!(&body.split_to(len)[..], b"{}");
+    assert!(body.is_empty());
+}
+
+#[tokio::test]
+async fn connect_client() {
+    let server_url = spawn().await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Body>();
+    let svc = ConnectClientLayer::new()
+        .get_methods(["/test.Test/UnaryCall"])
+        .layer(client);
+    let mut client = TestClient::with_origin(svc, server_url.parse().unwrap());
+
+    let response = client.unary_call(input(1, "one")).await.unwrap();
+    assert_eq!(response.into_inner(), output(1, "one"));
+
+    let status = client.unary_call(input(1, "boom")).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    assert_eq!(status.message(), "invalid boom");
+
+    let outputs = client
+        .server_stream(input(2, "two"))
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|output| output.unwrap())
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(outputs, [output(2, "1-two"), output(2, "2-two")]);
+
+    let response = client
+        .client_stream(tokio_stream::iter([input(1, "a"), input(2, "b")]))
+        .await
+        .unwrap();
+    assert_eq!(response.into_inner(), output(3, "ab"));
+}
+
+async fn spawn() -> String {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let listener_stream = TcpListenerStream::new(listener);
+
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .layer(ConnectLayer::new())
+            .layer(GrpcWebLayer::new())
+            .add_service(TestServer::new(Svc))
+            .serve_with_incoming(listener_stream)
+            .await
+            .unwrap()
+    }));
+
+    url
+}
+
+async fn send(req: Request<Full<Bytes>>) -> Response<hyper::body::Incoming> {
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    client.request(req).await.unwrap()
+}
+
+fn input(id: i32, desc: &str) -> Input {
+    Input {
+        id,
+        desc: desc.to_owned(),
+    }
+}
+
+fn output(id: i32, desc: &str) -> Output {
+    Output {
+        id,
+        desc: desc.to_owned(),
+    }
+}
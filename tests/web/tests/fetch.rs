@@ -0,0 +1,16 @@
+use test_web::pb::{test_client::TestClient, Input};
+use tonic_web::FetchChannel;
+
+fn assert_send<T: Send>(_: &T) {}
+
+// `fetch` only runs in browsers, so this only checks that generated clients are backed by fetch
+// channels, with calls that can be sent across threads like the ones of other channels.
+#[test]
+fn generated_clients_use_fetch_channels() {
+    let mut client = TestClient::new(FetchChannel::new("https://example.com/"));
+    let call = client.unary_call(Input {
+        id: 1,
+        desc: "one".into(),
+    });
+    assert_send(&call);
+}
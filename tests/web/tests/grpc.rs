@@ -47,6 +47,20 @@ async fn smoke_client_stream() {
     assert!(data(&r1) == data(&r2) && data(&r2) == data(&r3) && data(&r3) == data(&r4));
 }
 
+#[tokio::test]
+async fn client_stream_from_iter() {
+    let (mut c1, mut c2, _, _) = spawn().await.expect("clients");
+
+    let (r1, r2) = try_join!(
+        c1.client_stream(stream::iter(vec![input(), input()])),
+        c2.client_stream_from_iter(vec![input(), input()]),
+    )
+    .expect("responses");
+
+    assert!(meta(&r1) == meta(&r2));
+    assert!(data(&r1) == data(&r2));
+}
+
 #[tokio::test]
 async fn smoke_server_stream() {
     let (mut c1, mut c2, mut c3, mut c4) = spawn().await.expect("clients");
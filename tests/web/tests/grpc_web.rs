@@ -1,23 +1,36 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::task::{Context, Poll};
 
 use base64::Engine as _;
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use http_body_util::{BodyExt as _, Full};
 use hyper::body::Incoming;
 use hyper::http::{header, StatusCode};
-use hyper::{Method, Request, Uri};
+use hyper::{Method, Request, Uri, Version};
 use hyper_util::client::legacy::Client;
 use hyper_util::rt::TokioExecutor;
 use prost::Message;
-use tokio::net::TcpListener;
-use tokio_stream::wrappers::TcpListenerStream;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::{
+    crypto::ring,
+    pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    ServerConfig,
+};
+use tokio_rustls::TlsAcceptor;
+use tokio_stream::{
+    wrappers::{TcpListenerStream, UnboundedReceiverStream},
+    StreamExt,
+};
 use tonic::body::Body;
-use tonic::transport::Server;
+use tonic::codegen::{BoxFuture, Service, StdError as BoxError};
+use tonic::transport::{Certificate, ClientTlsConfig, Endpoint, Server};
 
-use test_web::pb::{test_server::TestServer, Input, Output};
+use test_web::pb::{test_client::TestClient, test_server::TestServer, Input, Output};
 use test_web::Svc;
 use tonic::Status;
-use tonic_web::GrpcWebLayer;
+use tonic_web::{FallbackChannel, GrpcWebChannel, GrpcWebLayer};
+use tower_layer::layer_fn;
 
 #[tokio::test]
 async fn binary_request() {
@@ -65,6 +78,171 @@ async fn text_request() {
     assert_eq!(&trailers[..], b"grpc-status:0\r\n");
 }
 
+#[tokio::test]
+async fn generated_client() {
+    let server_url = spawn().await;
+    let client = Client::builder(TokioExecutor::new()).build_http();
+
+    let mut client = TestClient::with_grpc_web(client, server_url.parse().unwrap());
+
+    let output = client
+        .unary_call(Input {
+            id: 1,
+            desc: "one".to_owned(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        output,
+        Output {
+            id: 1,
+            desc: "one".to_owned(),
+        }
+    );
+
+    let status = client
+        .unary_call(Input {
+            id: 1,
+            desc: "boom".to_owned(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn channel_client() {
+    let server_url = spawn().await;
+    let endpoint = Endpoint::from_shared(server_url).unwrap();
+    let channel = GrpcWebChannel::connect(endpoint).await.unwrap();
+
+    let mut client = TestClient::new(channel);
+
+    let output = client
+        .unary_call(Input {
+            id: 1,
+            desc: "one".to_owned(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(
+        output,
+        Output {
+            id: 1,
+            desc: "one".to_owned(),
+        }
+    );
+
+    let outputs = client
+        .server_stream(Input {
+            id: 2,
+            desc: "two".to_owned(),
+        })
+        .await
+        .unwrap()
+        .into_inner()
+        .map(|output| output.unwrap().desc)
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(outputs, ["1-two", "2-two"]);
+
+    let status = client
+        .unary_call(Input {
+            id: 1,
+            desc: "boom".to_owned(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn fallback_channel_client() {
+    let server_url = spawn_tls_http1_only().await;
+    let ca = std::fs::read(format!("{TLS_DATA}/ca.pem")).unwrap();
+    let tls = ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(ca))
+        .domain_name("example.com");
+    let endpoint = Endpoint::from_shared(server_url)
+        .unwrap()
+        .tls_config(tls)
+        .unwrap();
+    let channel = FallbackChannel::connect_lazy(endpoint);
+    assert!(!channel.is_fallback());
+
+    let mut client = TestClient::new(channel.clone());
+
+    let output = client
+        .unary_call(Input {
+            id: 1,
+            desc: "one".to_owned(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(output.desc, "one");
+    assert!(channel.is_fallback());
+
+    let status = client
+        .unary_call(Input {
+            id: 1,
+            desc: "boom".to_owned(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    let status = client
+        .server_stream(Input {
+            id: 2,
+            desc: "two".to_owned(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unimplemented);
+}
+
+#[tokio::test]
+async fn fallback_channel_falls_back_when_closed_before_response() {
+    let server_url = spawn_http1_only().await;
+    let endpoint = Endpoint::from_shared(server_url).unwrap();
+    let channel = FallbackChannel::connect_lazy(endpoint);
+
+    let mut client = TestClient::new(channel.clone());
+
+    let output = client
+        .unary_call(Input {
+            id: 1,
+            desc: "one".to_owned(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(output.desc, "one");
+    assert!(channel.is_fallback());
+}
+
+#[tokio::test]
+async fn fallback_channel_keeps_errors_once_accepted() {
+    let server_url = spawn_resetting_http2().await;
+    let endpoint = Endpoint::from_shared(server_url).unwrap();
+    let channel = FallbackChannel::connect_lazy(endpoint);
+
+    let mut client = TestClient::new(channel.clone());
+
+    let status = client
+        .unary_call(Input {
+            id: 1,
+            desc: "one".to_owned(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Internal);
+    assert!(!channel.is_fallback());
+}
+
 async fn spawn() -> String {
     let addr = SocketAddr::from(([127, 0, 0, 1], 0));
     let listener = TcpListener::bind(addr).await.expect("listener");
@@ -84,6 +262,130 @@ async fn spawn() -> String {
     url
 }
 
+const TLS_DATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../examples/data/tls");
+
+// Serves the test service behind a TLS proxy only negotiating HTTP/1.1, like proxies blocking
+// HTTP/2.
+async fn spawn_tls_http1_only() -> String {
+    let backend = spawn().await.trim_start_matches("http://").to_owned();
+
+    let certs = CertificateDer::pem_file_iter(format!("{TLS_DATA}/server.pem"))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let key = PrivateKeyDer::from_pem_file(format!("{TLS_DATA}/server.key")).unwrap();
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .unwrap();
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let url = format!("https://{}", listener.local_addr().unwrap());
+
+    drop(tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let acceptor = acceptor.clone();
+            let backend = backend.clone();
+            tokio::spawn(async move {
+                // The handshakes offering only HTTP/2 fail.
+                let Ok(mut stream) = acceptor.accept(stream).await else {
+                    return;
+                };
+                let mut upstream = TcpStream::connect(backend).await.unwrap();
+                let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+            });
+        }
+    }));
+
+    url
+}
+
+// Serves the test service over HTTP/1.1 only, closing the connections opening with the HTTP/2
+// preface once connected.
+async fn spawn_http1_only() -> String {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+
+    drop(tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let mut preface = [0; 3];
+                if stream.peek(&mut preface).await.is_ok() && &preface != b"PRI" {
+                    let _ = tx.send(Ok::<_, std::io::Error>(stream));
+                }
+            });
+        }
+    }));
+
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .layer(GrpcWebLayer::new())
+            .add_service(TestServer::new(Svc))
+            .serve_with_incoming(UnboundedReceiverStream::new(rx))
+            .await
+            .unwrap()
+    }));
+
+    url
+}
+
+// Serves the test service over HTTP/1.1, resetting the HTTP/2 streams once accepted.
+async fn spawn_resetting_http2() -> String {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let listener_stream = TcpListenerStream::new(listener);
+
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .layer(layer_fn(ResetHttp2))
+            .layer(GrpcWebLayer::new())
+            .add_service(TestServer::new(Svc))
+            .serve_with_incoming(listener_stream)
+            .await
+            .unwrap()
+    }));
+
+    url
+}
+
+// Fails the HTTP/2 requests, which resets their streams with `INTERNAL_ERROR`.
+#[derive(Clone)]
+struct ResetHttp2<S>(S);
+
+impl<S> Service<Request<Body>> for ResetHttp2<S>
+where
+    S: Service<Request<Body>>,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = BoxFuture<S::Response, BoxError>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.0.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        if req.version() == Version::HTTP_2 {
+            return Box::pin(async { Err("stream reset".into()) });
+        }
+        let fut = self.0.call(req);
+        Box::pin(async move { fut.await.map_err(Into::into) })
+    }
+}
+
 fn encode_body() -> Bytes {
     let input = Input {
         id: 1,
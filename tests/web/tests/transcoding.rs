@@ -0,0 +1,373 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt as _, Full};
+use hyper::http::{header, StatusCode};
+use hyper::{Method, Request, Response};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use prost_types::Timestamp;
+use serde_json::{json, Value};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::{self as stream, Stream, StreamExt};
+use tonic::transport::Server;
+use tonic::{Request as GrpcRequest, Response as GrpcResponse, Status, Streaming};
+
+use test_web::library::{
+    book::Genre,
+    library_client::LibraryClient,
+    library_methods,
+    library_server::{Library, LibraryServer},
+    Book, CreateBookRequest, GetBookRequest, ImportBooksResponse, ListBooksRequest,
+    FILE_DESCRIPTOR_SET,
+};
+use tonic_web::transcoding::{Builder, Error};
+
+#[tokio::test]
+async fn get() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::GET,
+        format!("{server_url}/v1/shelves/1/books/2"),
+        "",
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+    assert_eq!(
+        json_body(res).await,
+        json!({
+            "name": "shelves/1/books/2",
+            "title": "Title of shelves/1/books/2",
+            "pageCount": "300",
+            "published": "1972-01-01T10:00:20.021Z",
+            "tags": ["classic"],
+            "genre": "FICTION",
+        })
+    );
+}
+
+#[tokio::test]
+async fn get_response_body() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::GET,
+        format!("{server_url}/v1/shelves/1/books/2/title"),
+        "",
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(json_body(res).await, json!("Title of shelves/1/books/2"));
+}
+
+#[tokio::test]
+async fn error() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::GET,
+        format!("{server_url}/v1/shelves/1/books/missing"),
+        "",
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    assert_eq!(res.headers()[header::CONTENT_TYPE], "application/json");
+    assert!(!res.headers().contains_key("grpc-status"));
+    assert_eq!(
+        json_body(res).await,
+        json!({ "code": 5, "message": "no book shelves/1/books/missing" })
+    );
+}
+
+#[tokio::test]
+async fn body_field() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::POST,
+        format!("{server_url}/v1/shelves/1/books"),
+        r#"{"title": "New", "page_count": 12, "published": "2000-01-01T00:00:00+01:00", "genre": 2}"#,
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        json_body(res).await,
+        json!({
+            "name": "shelves/1/books/new",
+            "title": "New",
+            "pageCount": "12",
+            "published": "1999-12-31T23:00:00Z",
+            "genre": "HISTORY",
+        })
+    );
+}
+
+#[tokio::test]
+async fn whole_body() {
+    let server_url = spawn().await;
+    // The path variables take precedence over the body.
+    let res = send(
+        Method::PATCH,
+        format!("{server_url}/v1/shelves/1/books/2"),
+        r#"{"name": "ignored", "title": "Renamed", "tags": ["a", "b"]}"#,
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        json_body(res).await,
+        json!({ "name": "shelves/1/books/2", "title": "Renamed", "tags": ["a", "b"] })
+    );
+}
+
+#[tokio::test]
+async fn invalid_body() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::POST,
+        format!("{server_url}/v1/shelves/1/books"),
+        r#"{"pageCount": "many"}"#,
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    let body = json_body(res).await;
+    assert_eq!(body["code"], 3);
+    assert!(body["message"]
+        .as_str()
+        .unwrap()
+        .contains("invalid integer \"many\""));
+}
+
+#[tokio::test]
+async fn server_stream_query() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::GET,
+        format!("{server_url}/v1/shelves/1/books?pageSize=2&filter.author=me&filter.tags=a&filter.tags=b"),
+        "",
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    let lines = json_lines(&body);
+    assert_eq!(
+        lines,
+        [
+            json!({ "result": { "name": "shelves/1/books/0", "title": "me", "tags": ["a", "b"] } }),
+            json!({ "result": { "name": "shelves/1/books/1", "title": "me", "tags": ["a", "b"] } }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn server_stream_error() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::GET,
+        format!("{server_url}/v1/shelves/1/books?page_size=1&filter.author=fail"),
+        "",
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(
+        json_lines(&body),
+        [
+            json!({ "result": { "name": "shelves/1/books/0", "title": "fail" } }),
+            json!({ "error": { "code": 14, "message": "shelf unavailable" } }),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn unknown_query_parameter() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::GET,
+        format!("{server_url}/v1/shelves/1/books?color=red"),
+        "",
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn client_stream() {
+    let server_url = spawn().await;
+    let res = send(
+        Method::POST,
+        format!("{server_url}/v1/books:import"),
+        "{\"title\": \"One\"}\n{\"title\": \"Two\"}\n",
+    )
+    .await;
+
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        json_body(res).await,
+        json!({ "count": 2, "titles": ["One", "Two"] })
+    );
+}
+
+#[tokio::test]
+async fn grpc() {
+    let server_url = spawn().await;
+    let mut client = LibraryClient::connect(server_url).await.unwrap();
+
+    let book = client
+        .get_book(GetBookRequest {
+            name: "shelves/1/books/2".into(),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    assert_eq!(book.title, "Title of shelves/1/books/2");
+}
+
+#[test]
+fn missing_descriptors() {
+    let err = Builder::configure()
+        .add_routes(library_methods::HTTP_ROUTES)
+        .build()
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidRoute(_)), "{err}");
+}
+
+type BoxStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + 'static>>;
+
+struct Svc;
+
+#[tonic::async_trait]
+impl Library for Svc {
+    async fn get_book(
+        &self,
+        req: GrpcRequest<GetBookRequest>,
+    ) -> Result<GrpcResponse<Book>, Status> {
+        let name = req.into_inner().name;
+        if name.ends_with("missing") {
+            return Err(Status::not_found(format!("no book {name}")));
+        }
+
+        Ok(GrpcResponse::new(Book {
+            title: format!("Title of {name}"),
+            name,
+            page_count: 300,
+            published: Some(Timestamp {
+                seconds: 63_108_020,
+                nanos: 21_000_000,
+            }),
+            tags: vec!["classic".into()],
+            genre: Genre::Fiction.into(),
+        }))
+    }
+
+    type ListBooksStream = BoxStream<Book>;
+
+    async fn list_books(
+        &self,
+        req: GrpcRequest<ListBooksRequest>,
+    ) -> Result<GrpcResponse<Self::ListBooksStream>, Status> {
+        let req = req.into_inner();
+        let filter = req.filter.unwrap_or_default();
+
+        let end = (filter.author == "fail").then(|| Err(Status::unavailable("shelf unavailable")));
+        let books = (0..req.page_size).map(move |i| {
+            Ok(Book {
+                name: format!("{}/books/{i}", req.parent),
+                title: filter.author.clone(),
+                tags: filter.tags.clone(),
+                ..Default::default()
+            })
+        });
+
+        Ok(GrpcResponse::new(Box::pin(
+            stream::iter(books).chain(stream::iter(end)),
+        )))
+    }
+
+    async fn create_book(
+        &self,
+        req: GrpcRequest<CreateBookRequest>,
+    ) -> Result<GrpcResponse<Book>, Status> {
+        let req = req.into_inner();
+        Ok(GrpcResponse::new(Book {
+            name: format!("{}/books/new", req.parent),
+            ..req.book.unwrap_or_default()
+        }))
+    }
+
+    async fn update_book(&self, req: GrpcRequest<Book>) -> Result<GrpcResponse<Book>, Status> {
+        Ok(GrpcResponse::new(req.into_inner()))
+    }
+
+    async fn import_books(
+        &self,
+        req: GrpcRequest<Streaming<Book>>,
+    ) -> Result<GrpcResponse<ImportBooksResponse>, Status> {
+        let titles = req
+            .into_inner()
+            .map(|book| book.map(|book| book.title))
+            .collect::<Result<Vec<_>, _>>()
+            .await?;
+
+        Ok(GrpcResponse::new(ImportBooksResponse {
+            count: titles.len() as i32,
+            titles,
+        }))
+    }
+}
+
+async fn spawn() -> String {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let listener_stream = TcpListenerStream::new(listener);
+
+    let transcoding = Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .add_routes(library_methods::HTTP_ROUTES)
+        .build()
+        .unwrap();
+
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .layer(transcoding)
+            .add_service(LibraryServer::new(Svc))
+            .serve_with_incoming(listener_stream)
+            .await
+            .unwrap()
+    }));
+
+    url
+}
+
+async fn send(method: Method, uri: String, body: &'static str) -> Response<hyper::body::Incoming> {
+    let client = Client::builder(TokioExecutor::new()).build_http();
+    let req = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Full::new(Bytes::from_static(body.as_bytes())))
+        .unwrap();
+    client.request(req).await.unwrap()
+}
+
+async fn json_body(res: Response<hyper::body::Incoming>) -> Value {
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn json_lines(body: &[u8]) -> Vec<Value> {
+    body.split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| serde_json::from_slice(line).unwrap())
+        .collect()
+}
@@ -0,0 +1,126 @@
+use std::net::SocketAddr;
+
+use bytes::Bytes;
+use http_body_util::Empty;
+use hyper::http::{header, StatusCode};
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::{self as stream, StreamExt};
+use tonic::service::Routes;
+use tonic::transport::{Channel, Endpoint, Server};
+
+use test_web::pb::{test_client::TestClient, test_server::TestServer, Input, Output};
+use test_web::Svc;
+use tonic_web::websocket::tunnel::{self, TunnelConnector};
+
+#[tokio::test]
+async fn unary() {
+    let addr = spawn().await;
+    let mut client = TestClient::new(connect(addr).await);
+
+    let output = client.unary_call(input(1, "one")).await.unwrap();
+    assert_eq!(output.into_inner(), output_of(1, "one"));
+
+    let status = client.unary_call(input(1, "boom")).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn streaming() {
+    let addr = spawn().await;
+    let mut client = TestClient::new(connect(addr).await);
+
+    let outputs = client
+        .server_stream(input(2, "two"))
+        .await
+        .unwrap()
+        .into_inner()
+        .collect::<Result<Vec<_>, _>>()
+        .await
+        .unwrap();
+    assert_eq!(outputs, [output_of(2, "1-two"), output_of(2, "2-two")]);
+
+    // Large enough to span several WebSocket frames.
+    let desc = "x".repeat(200 * 1024);
+    let inputs = stream::iter([input(1, &desc), input(2, "end")]);
+    let output = client.client_stream(inputs).await.unwrap().into_inner();
+    assert_eq!(output, output_of(3, &format!("{desc}end")));
+}
+
+#[tokio::test]
+async fn not_upgrade_request() {
+    let addr = spawn().await;
+    let client = Client::builder(TokioExecutor::new()).build_http::<Empty<Bytes>>();
+
+    let res = client
+        .get(format!("http://{addr}/tunnel").parse().unwrap())
+        .await
+        .unwrap();
+    assert_eq!(res.status(), StatusCode::UPGRADE_REQUIRED);
+    assert_eq!(res.headers()[header::UPGRADE], "websocket");
+}
+
+#[tokio::test]
+async fn invalid_uri() {
+    let connector = TunnelConnector::new("http://127.0.0.1:1/tunnel".parse().unwrap());
+    let err = Endpoint::from_static("http://tunnelled")
+        .connect_with_connector(connector)
+        .await
+        .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("invalid websocket uri"),
+        "{err:?}"
+    );
+}
+
+async fn spawn() -> SocketAddr {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let addr = listener.local_addr().unwrap();
+    let listener_stream = TcpListenerStream::new(listener);
+
+    let (acceptor, incoming) = tunnel::acceptor();
+
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .accept_http1_upgrades(true)
+            .add_routes(Routes::default().http_service(acceptor))
+            .serve_with_incoming(listener_stream)
+            .await
+            .unwrap()
+    }));
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .add_service(TestServer::new(Svc))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap()
+    }));
+
+    addr
+}
+
+async fn connect(addr: SocketAddr) -> Channel {
+    let connector = TunnelConnector::new(format!("ws://{addr}/tunnel").parse().unwrap());
+    Endpoint::from_static("http://tunnelled")
+        .connect_with_connector(connector)
+        .await
+        .unwrap()
+}
+
+fn input(id: i32, desc: &str) -> Input {
+    Input {
+        id,
+        desc: desc.to_owned(),
+    }
+}
+
+fn output_of(id: i32, desc: &str) -> Output {
+    Output {
+        id,
+        desc: desc.to_owned(),
+    }
+}
@@ -0,0 +1,186 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use prost::Message;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+
+use test_web::pb::{test_server::TestServer, Input, Output};
+use test_web::Svc;
+use tonic_web::{GrpcWebLayer, GrpcWebSocketLayer};
+
+#[tokio::test]
+async fn client_stream() {
+    let addr = spawn().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(
+            format!(
+                "GET /test.Test/ClientStream HTTP/1.1\r\n\
+                 host: {addr}\r\n\
+                 connection: Upgrade\r\n\
+                 upgrade: websocket\r\n\
+                 sec-websocket-version: 13\r\n\
+                 sec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                 sec-websocket-protocol: grpc-websockets\r\n\r\n"
+            )
+            .as_bytes(),
+        )
+        .await
+        .unwrap();
+
+    let handshake = read_handshake(&mut stream).await;
+    assert!(handshake.starts_with("HTTP/1.1 101"), "{handshake}");
+    assert!(handshake.contains("sec-websocket-accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n"));
+    assert!(handshake.contains("sec-websocket-protocol: grpc-websockets"));
+
+    write_message(&mut stream, b"x-grpc-web: 1\r\n").await;
+    for (id, desc) in [(1, "one"), (2, "two")] {
+        let mut message = vec![0x00];
+        message.extend_from_slice(&encode_input(id, desc));
+        write_message(&mut stream, &message).await;
+    }
+    write_message(&mut stream, &[0x01]).await;
+
+    let mut body = read_messages(&mut stream).await;
+
+    // The headers frame
+    assert_eq!(body.get_u8(), 0x80);
+    let len = body.get_u32() as usize;
+    let headers = body.split_to(len);
+    assert!(std::str::from_utf8(&headers)
+        .unwrap()
+        .contains("content-type:application/grpc-web+proto"));
+
+    assert_eq!(body.get_u8(), 0x00);
+    let len = body.get_u32() as usize;
+    let output = Output::decode(body.split_to(len)).unwrap();
+    assert_eq!(
+        output,
+        Output {
+            id: 3,
+            desc: "onetwo".to_owned(),
+        }
+    );
+
+    assert_eq!(body.get_u8(), 0x80);
+    body.advance(4);
+    assert_eq!(&body[..], b"grpc-status:0\r\n");
+}
+
+#[tokio::test]
+async fn upgrades_not_accepted() {
+    let addr = spawn_with_upgrades(false).await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    stream
+        .write_all(
+            format!(
+                "GET /test.Test/ClientStream HTTP/1.1\r\n\
+                 host: {addr}\r\n\
+                 connection: Upgrade\r\n\
+                 upgrade: websocket\r\n\
+                 sec-websocket-version: 13\r\n\
+                 sec-websocket-key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+                 sec-websocket-protocol: grpc-websockets\r\n\r\n"
+            )
+            .as_bytes(),
+        )
+        .await
+        .unwrap();
+    write_message(&mut stream, b"x-grpc-web: 1\r\n").await;
+
+    // The connection is closed instead of being handed over to the WebSocket.
+    let mut rest = Vec::new();
+    let _ = tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut rest))
+        .await
+        .expect("the connection should be closed");
+    let rest = String::from_utf8_lossy(&rest);
+    assert!(rest.ends_with("\r\n\r\n"), "{rest}");
+}
+
+async fn spawn() -> SocketAddr {
+    spawn_with_upgrades(true).await
+}
+
+async fn spawn_with_upgrades(accept_http1_upgrades: bool) -> SocketAddr {
+    let addr = SocketAddr::from(([127, 0, 0, 1], 0));
+    let listener = TcpListener::bind(addr).await.expect("listener");
+    let addr = listener.local_addr().unwrap();
+    let listener_stream = TcpListenerStream::new(listener);
+
+    drop(tokio::spawn(async move {
+        Server::builder()
+            .accept_http1(true)
+            .accept_http1_upgrades(accept_http1_upgrades)
+            .layer(GrpcWebSocketLayer::new())
+            .layer(GrpcWebLayer::new())
+            .add_service(TestServer::new(Svc))
+            .serve_with_incoming(listener_stream)
+            .await
+            .unwrap()
+    }));
+
+    addr
+}
+
+fn encode_input(id: i32, desc: &str) -> Bytes {
+    let input = Input {
+        id,
+        desc: desc.to_owned(),
+    };
+
+    let mut buf = BytesMut::new();
+    buf.put_u8(0);
+    buf.put_u32(input.encoded_len() as u32);
+    input.encode(&mut buf).unwrap();
+    buf.freeze()
+}
+
+async fn read_handshake(stream: &mut TcpStream) -> String {
+    let mut handshake = Vec::new();
+    while !handshake.ends_with(b"\r\n\r\n") {
+        handshake.push(stream.read_u8().await.unwrap());
+    }
+    String::from_utf8(handshake).unwrap()
+}
+
+// Writes `payload` as a single masked binary frame.
+async fn write_message(stream: &mut TcpStream, payload: &[u8]) {
+    let mask = [0x12, 0x34, 0x56, 0x78];
+    assert!(payload.len() < 126);
+
+    let mut frame = vec![0x82, 0x80 | payload.len() as u8];
+    frame.extend_from_slice(&mask);
+    frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+    stream.write_all(&frame).await.unwrap();
+}
+
+// Reads the payloads of the binary frames sent by the server, until it closes the WebSocket.
+async fn read_messages(stream: &mut TcpStream) -> Bytes {
+    let mut body = BytesMut::new();
+
+    loop {
+        let opcode = stream.read_u8().await.unwrap() & 0x0F;
+        let len = match stream.read_u8().await.unwrap() {
+            126 => stream.read_u16().await.unwrap() as usize,
+            127 => stream.read_u64().await.unwrap() as usize,
+            len => len as usize,
+        };
+        let mut payload = vec![0; len];
+        stream.read_exact(&mut payload).await.unwrap();
+
+        match opcode {
+            0x2 => body.put_slice(&payload),
+            0x8 => {
+                assert_eq!(payload, 1000u16.to_be_bytes());
+                return body.freeze();
+            }
+            opcode => panic!("unexpected opcode {opcode}"),
+        }
+    }
+}
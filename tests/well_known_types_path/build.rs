@@ -0,0 +1,6 @@
+fn main() {
+    tonic_build::configure()
+        .well_known_types_path("crate::wkt")
+        .compile_protos(&["proto/test.proto"], &["proto"])
+        .unwrap();
+}
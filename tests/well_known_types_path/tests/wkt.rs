@@ -0,0 +1,32 @@
+use tonic::{Request, Response, Status};
+use well_known_types_path::{
+    pb::{clock_server, Event},
+    wkt::{Duration, Timestamp},
+};
+
+struct Clock;
+
+#[tonic::async_trait]
+impl clock_server::Clock for Clock {
+    async fn now(&self, _: Request<()>) -> Result<Response<Timestamp>, Status> {
+        Ok(Response::new(Timestamp {
+            seconds: 1,
+            nanos: 0,
+        }))
+    }
+
+    async fn sleep(&self, request: Request<Duration>) -> Result<Response<Event>, Status> {
+        let duration = request.into_inner();
+        Ok(Response::new(Event {
+            at: Some(Timestamp {
+                seconds: duration.seconds,
+                nanos: duration.nanos,
+            }),
+        }))
+    }
+}
+
+#[test]
+fn uses_well_known_types_path() {
+    let _ = clock_server::ClockServer::new(Clock);
+}
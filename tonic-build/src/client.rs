@@ -3,11 +3,13 @@ use std::collections::HashSet;
 use super::{Attributes, Method, Service};
 use crate::{
     format_method_name, format_method_path, format_service_name, generate_deprecated,
-    generate_doc_comments, naive_snake_case,
+    generate_doc_comments, generate_idempotency_level, generate_service_deprecated,
+    naive_snake_case,
 };
 use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn generate_internal<T: Service>(
     service: &T,
     emit_package: bool,
@@ -16,6 +18,11 @@ pub(crate) fn generate_internal<T: Service>(
     build_transport: bool,
     attributes: &Attributes,
     disable_comments: &HashSet<String>,
+    build_mock: bool,
+    build_client_trait: bool,
+    client_trait_automock: bool,
+    build_web_client: bool,
+    use_local_futures: bool,
 ) -> TokenStream {
     let service_ident = quote::format_ident!("{}Client", service.name());
     let client_mod = quote::format_ident!("{}_client", naive_snake_case(service.name()));
@@ -24,10 +31,25 @@ pub(crate) fn generate_internal<T: Service>(
         emit_package,
         proto_path,
         compile_well_known_types,
+        attributes,
         disable_comments,
     );
 
-    let connect = generate_connect(&service_ident, build_transport);
+    let connect = generate_connect(&service_ident, build_transport, build_web_client);
+    let web_client = generate_web_client(&service_ident, build_web_client);
+
+    let mock = if build_mock || build_client_trait {
+        crate::mock::generate(
+            service,
+            proto_path,
+            compile_well_known_types,
+            use_local_futures,
+            build_mock,
+            client_trait_automock,
+        )
+    } else {
+        TokenStream::new()
+    };
 
     let package = if emit_package { service.package() } else { "" };
     let service_name = format_service_name(service, emit_package);
@@ -40,6 +62,7 @@ pub(crate) fn generate_internal<T: Service>(
 
     let mod_attributes = attributes.for_mod(package);
     let struct_attributes = attributes.for_struct(&service_name);
+    let (deprecated, allow_deprecated) = generate_service_deprecated(service);
 
     quote! {
         /// Generated client implementations.
@@ -52,11 +75,13 @@ pub(crate) fn generate_internal<T: Service>(
                 clippy::wildcard_imports,
                 // will trigger if compression is disabled
                 clippy::let_unit_value,
+                #allow_deprecated
             )]
             use tonic::codegen::*;
             use tonic::codegen::http::Uri;
 
             #service_doc
+            #deprecated
             #(#struct_attributes)*
             #[derive(Debug, Clone)]
             pub struct #service_ident<T> {
@@ -65,6 +90,8 @@ pub(crate) fn generate_internal<T: Service>(
 
             #connect
 
+            #web_client
+
             impl<T> #service_ident<T>
             where
                 T: tonic::client::GrpcService<tonic::body::Body>,
@@ -132,13 +159,23 @@ pub(crate) fn generate_internal<T: Service>(
 
                 #methods
             }
+
+            #mock
         }
     }
 }
 
 #[cfg(feature = "transport")]
-fn generate_connect(service_ident: &syn::Ident, enabled: bool) -> TokenStream {
+fn generate_connect(
+    service_ident: &syn::Ident,
+    enabled: bool,
+    build_web_client: bool,
+) -> TokenStream {
+    // Browser clients are built without the transport of `tonic`.
+    let cfg = build_web_client.then(|| quote!(#[cfg(not(target_arch = "wasm32"))]));
+
     let connect_impl = quote! {
+        #cfg
         impl #service_ident<tonic::transport::Channel> {
             /// Attempt to create a new client by connecting to a given endpoint.
             pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
@@ -160,28 +197,55 @@ fn generate_connect(service_ident: &syn::Ident, enabled: bool) -> TokenStream {
 }
 
 #[cfg(not(feature = "transport"))]
-fn generate_connect(_service_ident: &syn::Ident, _enabled: bool) -> TokenStream {
+fn generate_connect(
+    _service_ident: &syn::Ident,
+    _enabled: bool,
+    _build_web_client: bool,
+) -> TokenStream {
     TokenStream::new()
 }
 
+fn generate_web_client(service_ident: &syn::Ident, enabled: bool) -> TokenStream {
+    if !enabled {
+        return TokenStream::new();
+    }
+
+    quote! {
+        impl<T> #service_ident<tonic_web::GrpcWebClientService<T>> {
+            /// Create a new client speaking the gRPC-web protocol to `origin`
+            /// through `inner`, an HTTP/1.1 service such as a `fetch` based
+            /// one on `wasm32-unknown-unknown`.
+            pub fn with_grpc_web(inner: T, origin: Uri) -> Self {
+                let inner = tonic_web::GrpcWebClientService::new(inner);
+                let inner = tonic::client::Grpc::with_origin(inner, origin);
+                Self { inner }
+            }
+        }
+    }
+}
+
 fn generate_methods<T: Service>(
     service: &T,
     emit_package: bool,
     proto_path: &str,
     compile_well_known_types: bool,
+    attributes: &Attributes,
     disable_comments: &HashSet<String>,
 ) -> TokenStream {
     let mut stream = TokenStream::new();
 
     for method in service.methods() {
-        if !disable_comments.contains(&format_method_name(service, method, emit_package)) {
+        let method_name = format_method_name(service, method, emit_package);
+        if !disable_comments.contains(&method_name) {
             stream.extend(generate_doc_comments(method.comment()));
         }
         if method.deprecated() {
             stream.extend(generate_deprecated());
         }
+        let method_attributes = attributes.for_method(&method_name);
+        stream.extend(quote! { #(#method_attributes)* });
 
-        let method = match (method.client_streaming(), method.server_streaming()) {
+        let method_stream = match (method.client_streaming(), method.server_streaming()) {
             (false, false) => generate_unary(
                 service,
                 method,
@@ -212,12 +276,64 @@ fn generate_methods<T: Service>(
             ),
         };
 
-        stream.extend(method);
+        stream.extend(method_stream);
+
+        if method.client_streaming() {
+            stream.extend(generate_from_iter(
+                method,
+                proto_path,
+                compile_well_known_types,
+            ));
+        }
     }
 
     stream
 }
 
+// Generates the variant of a client streaming method taking the messages of
+// the request stream as an iterator.
+fn generate_from_iter<T: Method>(
+    method: &T,
+    proto_path: &str,
+    compile_well_known_types: bool,
+) -> TokenStream {
+    let ident = format_ident!("{}", method.name());
+    let from_iter = format_ident!("{}_from_iter", method.name().trim_start_matches("r#"));
+    let (request, response) = method.request_response_name(proto_path, compile_well_known_types);
+    let response = if method.server_streaming() {
+        quote!(tonic::codec::Streaming<#response>)
+    } else {
+        response
+    };
+
+    let doc = format!(
+        " Calls `{}` with a request stream of the messages of `messages`.",
+        method.name().trim_start_matches("r#")
+    );
+    let deprecated = method.deprecated().then(|| {
+        let deprecated = generate_deprecated();
+        quote! {
+            #deprecated
+            #[allow(deprecated)]
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #deprecated
+        pub async fn #from_iter<I>(
+            &mut self,
+            messages: I,
+        ) -> std::result::Result<tonic::Response<#response>, tonic::Status>
+        where
+            I: IntoIterator<Item = #request>,
+            I::IntoIter: std::marker::Send + 'static,
+        {
+            self.#ident(tokio_stream::iter(messages)).await
+        }
+    }
+}
+
 fn generate_unary<T: Service>(
     service: &T,
     method: &T::Method,
@@ -231,6 +347,8 @@ fn generate_unary<T: Service>(
     let service_name = format_service_name(service, emit_package);
     let path = format_method_path(service, method, emit_package);
     let method_name = method.identifier();
+    let idempotency_level = generate_idempotency_level(method)
+        .map(|level| quote!(req.extensions_mut().insert(#level);));
 
     quote! {
         pub async fn #ident(
@@ -244,6 +362,7 @@ fn generate_unary<T: Service>(
            let path = http::uri::PathAndQuery::from_static(#path);
            let mut req = request.into_request();
            req.extensions_mut().insert(GrpcMethod::new(#service_name, #method_name));
+            #idempotency_level
            self.inner.unary(req, path, codec).await
         }
     }
@@ -262,6 +381,8 @@ fn generate_server_streaming<T: Service>(
     let service_name = format_service_name(service, emit_package);
     let path = format_method_path(service, method, emit_package);
     let method_name = method.identifier();
+    let idempotency_level = generate_idempotency_level(method)
+        .map(|level| quote!(req.extensions_mut().insert(#level);));
 
     quote! {
         pub async fn #ident(
@@ -275,6 +396,7 @@ fn generate_server_streaming<T: Service>(
             let path = http::uri::PathAndQuery::from_static(#path);
             let mut req = request.into_request();
             req.extensions_mut().insert(GrpcMethod::new(#service_name, #method_name));
+            #idempotency_level
             self.inner.server_streaming(req, path, codec).await
         }
     }
@@ -293,6 +415,8 @@ fn generate_client_streaming<T: Service>(
     let service_name = format_service_name(service, emit_package);
     let path = format_method_path(service, method, emit_package);
     let method_name = method.identifier();
+    let idempotency_level = generate_idempotency_level(method)
+        .map(|level| quote!(req.extensions_mut().insert(#level);));
 
     quote! {
         pub async fn #ident(
@@ -306,6 +430,7 @@ fn generate_client_streaming<T: Service>(
             let path = http::uri::PathAndQuery::from_static(#path);
             let mut req = request.into_streaming_request();
             req.extensions_mut().insert(GrpcMethod::new(#service_name, #method_name));
+            #idempotency_level
             self.inner.client_streaming(req, path, codec).await
         }
     }
@@ -324,6 +449,8 @@ fn generate_streaming<T: Service>(
     let service_name = format_service_name(service, emit_package);
     let path = format_method_path(service, method, emit_package);
     let method_name = method.identifier();
+    let idempotency_level = generate_idempotency_level(method)
+        .map(|level| quote!(req.extensions_mut().insert(#level);));
 
     quote! {
         pub async fn #ident(
@@ -337,6 +464,7 @@ fn generate_streaming<T: Service>(
             let path = http::uri::PathAndQuery::from_static(#path);
             let mut req = request.into_streaming_request();
             req.extensions_mut().insert(GrpcMethod::new(#service_name,#method_name));
+            #idempotency_level
             self.inner.streaming(req, path, codec).await
         }
     }
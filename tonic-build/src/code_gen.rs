@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use proc_macro2::TokenStream;
 
-use crate::{Attributes, Service};
+use crate::{Attributes, HttpRule, Service};
 
 /// Builder for the generic code generation of server and clients.
 #[derive(Debug)]
@@ -11,9 +11,19 @@ pub struct CodeGenBuilder {
     compile_well_known_types: bool,
     attributes: Attributes,
     build_transport: bool,
+    build_mock: bool,
+    build_client_trait: bool,
+    client_trait_automock: bool,
+    build_web_client: bool,
     disable_comments: HashSet<String>,
     use_arc_self: bool,
+    use_local_futures: bool,
     generate_default_stubs: bool,
+    file_descriptor_set: Option<Vec<u8>>,
+    http_rules: Option<Vec<HttpRule>>,
+    handler_extractors: Vec<(String, String)>,
+    request_validators: Vec<(String, String)>,
+    intercepted_messages: Vec<String>,
 }
 
 impl CodeGenBuilder {
@@ -28,7 +38,7 @@ impl CodeGenBuilder {
         self
     }
 
-    /// Attributes that will be added to `mod` and `struct` items.
+    /// Attributes that will be added to `mod`, `struct` and method items.
     ///
     /// Reference [`Attributes`] for more information.
     pub fn attributes(&mut self, attributes: Attributes) -> &mut Self {
@@ -46,6 +56,40 @@ impl CodeGenBuilder {
         self
     }
 
+    /// Enable generation of a `{Service}ClientApi` trait, implemented by the
+    /// generated client, and of a `Mock{Service}Client` implementation of it,
+    /// whose responses can be programmed method by method.
+    pub fn build_mock(&mut self, build_mock: bool) -> &mut Self {
+        self.build_mock = build_mock;
+        self
+    }
+
+    /// Enable generation of a `{Service}ClientApi` trait, implemented by the
+    /// generated client, without the mock client.
+    pub fn build_client_trait(&mut self, build_client_trait: bool) -> &mut Self {
+        self.build_client_trait = build_client_trait;
+        self
+    }
+
+    /// Annotate the `{Service}ClientApi` trait with
+    /// `#[cfg_attr(test, mockall::automock)]`, this requires `mockall` in
+    /// tests.
+    pub fn client_trait_automock(&mut self, enable: bool) -> &mut Self {
+        self.client_trait_automock = enable;
+        self
+    }
+
+    /// Enable generation of a `with_grpc_web` constructor of the client,
+    /// speaking the gRPC-web protocol through an HTTP/1.1 service, this
+    /// requires `tonic-web`.
+    ///
+    /// The `connect` constructor is then only generated outside of `wasm32`,
+    /// so that the same code can be used by native and browser clients.
+    pub fn build_web_client(&mut self, build_web_client: bool) -> &mut Self {
+        self.build_web_client = build_web_client;
+        self
+    }
+
     /// Enable compiling well known types, this will force codegen to not
     /// use the well known types from `prost-types`.
     pub fn compile_well_known_types(&mut self, enable: bool) -> &mut Self {
@@ -65,12 +109,75 @@ impl CodeGenBuilder {
         self
     }
 
+    /// Emit server traits and mock client traits whose futures are not
+    /// required to be `Send`.
+    pub fn use_local_futures(&mut self, enable: bool) -> &mut Self {
+        self.use_local_futures = enable;
+        self
+    }
+
     /// Enable or disable returning automatic unimplemented gRPC error code for generated traits.
     pub fn generate_default_stubs(&mut self, generate_default_stubs: bool) -> &mut Self {
         self.generate_default_stubs = generate_default_stubs;
         self
     }
 
+    /// Embed the encoded `prost_types::FileDescriptorSet` of the service in
+    /// the generated server, as a `FILE_DESCRIPTOR_SET` constant, along with a
+    /// `register_reflection` function registering it with a
+    /// `tonic_reflection::server::Builder`.
+    pub fn file_descriptor_set(&mut self, file_descriptor_set: Option<Vec<u8>>) -> &mut Self {
+        self.file_descriptor_set = file_descriptor_set;
+        self
+    }
+
+    /// Emit the given HTTP bindings of the methods of the service, as an
+    /// `HTTP_ROUTES` table of `tonic::HttpRoute`s in the module generated by
+    /// [`CodeGenBuilder::generate_methods`].
+    pub fn http_rules(&mut self, http_rules: Option<Vec<HttpRule>>) -> &mut Self {
+        self.http_rules = http_rules;
+        self
+    }
+
+    /// Add arguments to the handlers of the server trait, extracted from
+    /// their requests with `tonic::server::FromRequest`.
+    ///
+    /// Each extractor is a pattern matching the full names of methods or of
+    /// their services, e.g. `my.proto.package.EchoService.Echo` or
+    /// `my.proto.package.EchoService`, and the path of the type of the
+    /// argument. The arguments of the matched extractors are taken in order,
+    /// ahead of the request.
+    pub fn handler_extractors(&mut self, handler_extractors: Vec<(String, String)>) -> &mut Self {
+        self.handler_extractors = handler_extractors;
+        self
+    }
+
+    /// Validate the requests of the unary and server streaming methods with
+    /// functions taking a reference to the decoded request message and
+    /// returning a `Result<(), tonic::Status>`, before calling the handlers.
+    ///
+    /// Each validator is a pattern matching the full names of methods or of
+    /// their services, e.g. `my.proto.package.EchoService.Echo` or
+    /// `my.proto.package.EchoService`, and the path of the function. The
+    /// matched validators are called in order.
+    pub fn request_validators(&mut self, request_validators: Vec<(String, String)>) -> &mut Self {
+        self.request_validators = request_validators;
+        self
+    }
+
+    /// Run the `tonic::server::MessageInterceptors` inserted in the extensions
+    /// of the requests of the unary and server streaming methods on their
+    /// decoded request messages, after the validators and before calling the
+    /// handlers.
+    ///
+    /// Each pattern matches the full names of methods or of their services,
+    /// e.g. `my.proto.package.EchoService.Echo` or
+    /// `my.proto.package.EchoService`.
+    pub fn intercepted_messages(&mut self, intercepted_messages: Vec<String>) -> &mut Self {
+        self.intercepted_messages = intercepted_messages;
+        self
+    }
+
     /// Generate client code based on `Service`.
     ///
     /// This takes some `Service` and will generate a `TokenStream` that contains
@@ -84,9 +191,23 @@ impl CodeGenBuilder {
             self.build_transport,
             &self.attributes,
             &self.disable_comments,
+            self.build_mock,
+            self.build_client_trait,
+            self.client_trait_automock,
+            self.build_web_client,
+            self.use_local_futures,
         )
     }
 
+    /// Generate method constants based on `Service`.
+    ///
+    /// This takes some `Service` and will generate a `TokenStream` that contains
+    /// a public module with the name of the service and the name, path and
+    /// `tonic::MethodDescriptor` of each of its methods.
+    pub fn generate_methods(&self, service: &impl Service) -> TokenStream {
+        crate::methods::generate(service, self.emit_package, self.http_rules.as_deref())
+    }
+
     /// Generate server code based on `Service`.
     ///
     /// This takes some `Service` and will generate a `TokenStream` that contains
@@ -100,7 +221,12 @@ impl CodeGenBuilder {
             &self.attributes,
             &self.disable_comments,
             self.use_arc_self,
+            self.use_local_futures,
             self.generate_default_stubs,
+            self.file_descriptor_set.as_deref(),
+            &self.handler_extractors,
+            &self.request_validators,
+            &self.intercepted_messages,
         )
     }
 }
@@ -112,9 +238,19 @@ impl Default for CodeGenBuilder {
             compile_well_known_types: false,
             attributes: Attributes::default(),
             build_transport: true,
+            build_mock: false,
+            build_client_trait: false,
+            client_trait_automock: false,
+            build_web_client: false,
             disable_comments: HashSet::default(),
             use_arc_self: false,
+            use_local_futures: false,
             generate_default_stubs: false,
+            file_descriptor_set: None,
+            http_rules: None,
+            handler_extractors: Vec::new(),
+            request_validators: Vec::new(),
+            intercepted_messages: Vec::new(),
         }
     }
 }
@@ -0,0 +1,244 @@
+use std::{collections::HashMap, io};
+
+use prost::Message as _;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+};
+
+// The subset of `descriptor.proto` leading to the features of the files,
+// messages and fields, which `prost-types` drops.
+#[derive(Clone, PartialEq, prost::Message)]
+struct EncodedFileDescriptorSet {
+    #[prost(message, repeated, tag = "1")]
+    file: Vec<EncodedFile>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EncodedFile {
+    #[prost(string, optional, tag = "1")]
+    name: Option<String>,
+    #[prost(message, repeated, tag = "4")]
+    message_type: Vec<EncodedMessage>,
+    #[prost(message, optional, tag = "8")]
+    options: Option<FileOptions>,
+    #[prost(int32, optional, tag = "14")]
+    edition: Option<i32>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EncodedMessage {
+    #[prost(message, repeated, tag = "2")]
+    field: Vec<EncodedField>,
+    #[prost(message, repeated, tag = "3")]
+    nested_type: Vec<EncodedMessage>,
+    #[prost(message, optional, tag = "7")]
+    options: Option<MessageOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct EncodedField {
+    #[prost(message, optional, tag = "8")]
+    options: Option<FieldOptions>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FileOptions {
+    #[prost(message, optional, tag = "50")]
+    features: Option<FeatureSet>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct MessageOptions {
+    #[prost(message, optional, tag = "12")]
+    features: Option<FeatureSet>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FieldOptions {
+    #[prost(message, optional, tag = "21")]
+    features: Option<FeatureSet>,
+}
+
+// `google.protobuf.FeatureSet`.
+#[derive(Clone, PartialEq, prost::Message)]
+struct FeatureSet {
+    #[prost(int32, optional, tag = "1")]
+    field_presence: Option<i32>,
+    #[prost(int32, optional, tag = "3")]
+    repeated_field_encoding: Option<i32>,
+    #[prost(int32, optional, tag = "5")]
+    message_encoding: Option<i32>,
+}
+
+const EDITION_2023: i32 = 1000;
+const EDITION_2024: i32 = 1001;
+
+const FIELD_PRESENCE_EXPLICIT: i32 = 1;
+const FIELD_PRESENCE_LEGACY_REQUIRED: i32 = 3;
+const REPEATED_FIELD_ENCODING_PACKED: i32 = 1;
+const REPEATED_FIELD_ENCODING_EXPANDED: i32 = 2;
+const MESSAGE_ENCODING_LENGTH_PREFIXED: i32 = 1;
+const MESSAGE_ENCODING_DELIMITED: i32 = 2;
+
+/// The resolved features of a descriptor.
+#[derive(Clone, Copy)]
+struct Features {
+    field_presence: i32,
+    repeated_field_encoding: i32,
+    message_encoding: i32,
+}
+
+impl Features {
+    /// The defaults of the 2023 and 2024 editions.
+    const DEFAULTS: Features = Features {
+        field_presence: FIELD_PRESENCE_EXPLICIT,
+        repeated_field_encoding: REPEATED_FIELD_ENCODING_PACKED,
+        message_encoding: MESSAGE_ENCODING_LENGTH_PREFIXED,
+    };
+
+    fn merge(self, set: Option<&FeatureSet>) -> Self {
+        let Some(set) = set else {
+            return self;
+        };
+        Features {
+            field_presence: set.field_presence.unwrap_or(self.field_presence),
+            repeated_field_encoding: set
+                .repeated_field_encoding
+                .unwrap_or(self.repeated_field_encoding),
+            message_encoding: set.message_encoding.unwrap_or(self.message_encoding),
+        }
+    }
+}
+
+/// Whether `fds` holds files using protobuf editions.
+pub(crate) fn has_editions(fds: &FileDescriptorSet) -> bool {
+    fds.file.iter().any(is_editions)
+}
+
+fn is_editions(file: &FileDescriptorProto) -> bool {
+    file.syntax() == "editions"
+}
+
+/// Lowers the files of `fds` using protobuf editions to `proto3` files with
+/// the same field presence and encodings, which `prost-build` supports, from
+/// the features read from `encoded`, the output of `protoc`.
+///
+/// Scalar fields with explicit presence become `proto3` optional fields, and
+/// fields with legacy required presence become required fields.
+pub(crate) fn lower_editions(
+    fds: &mut FileDescriptorSet,
+    encoded: Option<&[u8]>,
+) -> io::Result<()> {
+    if !has_editions(fds) {
+        return Ok(());
+    }
+
+    let encoded = encoded.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "files using protobuf editions can only be compiled from `.proto` files",
+        )
+    })?;
+    let encoded = EncodedFileDescriptorSet::decode(encoded)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    let encoded_files = encoded
+        .file
+        .iter()
+        .map(|file| (file.name.as_deref().unwrap_or_default(), file))
+        .collect::<HashMap<_, _>>();
+
+    for file in fds.file.iter_mut().filter(|file| is_editions(file)) {
+        let encoded_file = encoded_files.get(file.name()).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("missing encoded descriptor of `{}`", file.name()),
+            )
+        })?;
+
+        match encoded_file.edition {
+            Some(EDITION_2023 | EDITION_2024) => {}
+            edition => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "unsupported edition {edition:?} of `{}`, only the 2023 and 2024 editions are supported",
+                        file.name()
+                    ),
+                ))
+            }
+        }
+
+        let features = Features::DEFAULTS.merge(
+            encoded_file
+                .options
+                .as_ref()
+                .and_then(|options| options.features.as_ref()),
+        );
+
+        file.syntax = Some("proto3".to_string());
+        for (message, encoded) in file.message_type.iter_mut().zip(&encoded_file.message_type) {
+            lower_message(message, encoded, features);
+        }
+    }
+
+    Ok(())
+}
+
+fn lower_message(message: &mut DescriptorProto, encoded: &EncodedMessage, features: Features) {
+    let features = features.merge(
+        encoded
+            .options
+            .as_ref()
+            .and_then(|options| options.features.as_ref()),
+    );
+
+    for (field, encoded) in message.field.iter_mut().zip(&encoded.field) {
+        let features = features.merge(
+            encoded
+                .options
+                .as_ref()
+                .and_then(|options| options.features.as_ref()),
+        );
+        lower_field(field, features);
+    }
+
+    for (nested, encoded) in message.nested_type.iter_mut().zip(&encoded.nested_type) {
+        lower_message(nested, encoded, features);
+    }
+}
+
+fn lower_field(field: &mut FieldDescriptorProto, features: Features) {
+    if field.r#type() == Type::Message && features.message_encoding == MESSAGE_ENCODING_DELIMITED {
+        field.set_type(Type::Group);
+    }
+
+    if field.label() == Label::Repeated {
+        if features.repeated_field_encoding == REPEATED_FIELD_ENCODING_EXPANDED
+            && is_packable(field.r#type())
+        {
+            field.options.get_or_insert_with(Default::default).packed = Some(false);
+        }
+        return;
+    }
+
+    if features.field_presence == FIELD_PRESENCE_LEGACY_REQUIRED {
+        field.set_label(Label::Required);
+        return;
+    }
+
+    // The presence of the fields of oneofs and of messages is always tracked.
+    if field.oneof_index.is_some() || field.r#type() == Type::Message {
+        return;
+    }
+
+    // Unlike messages, groups are only optional in `proto2` files for
+    // `prost-build`.
+    if field.r#type() == Type::Group || features.field_presence == FIELD_PRESENCE_EXPLICIT {
+        field.proto3_optional = Some(true);
+    }
+}
+
+fn is_packable(ty: Type) -> bool {
+    !matches!(ty, Type::String | Type::Bytes | Type::Message | Type::Group)
+}
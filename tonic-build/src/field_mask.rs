@@ -0,0 +1,210 @@
+use std::collections::{HashMap, HashSet};
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::TokenStream;
+use prost_build::Module;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    FieldDescriptorProto, FileDescriptorProto,
+};
+use quote::quote;
+
+use crate::{
+    match_name,
+    redact::collect_messages,
+    serde::{resolve_ident, sanitize_identifier},
+};
+
+/// Generates the implementations of `tonic::FieldMaskMessage` of the
+/// messages of `files`, by package.
+///
+/// Messages matching `patterns` implement the trait, and so do the messages
+/// of their singular message fields, outside of oneofs, so that masks can
+/// select the fields of these. Messages whose type is an extern path don't
+/// implement the trait, their fields are kept or cleared as a whole.
+pub(crate) fn generate(
+    files: &[FileDescriptorProto],
+    patterns: &[String],
+    extern_paths: &[(String, String)],
+    compile_well_known_types: bool,
+) -> HashMap<Module, String> {
+    if patterns.is_empty() {
+        return HashMap::new();
+    }
+
+    let is_extern = |fq_name: &str| {
+        (!compile_well_known_types && match_name(".google.protobuf", fq_name))
+            || extern_paths
+                .iter()
+                .any(|(proto_path, _)| match_name(proto_path, fq_name))
+    };
+
+    let mut messages = Vec::new();
+    for file in files {
+        let package = file.package();
+        let fq_package = if package.is_empty() {
+            String::new()
+        } else {
+            format!(".{package}")
+        };
+        for message in &file.message_type {
+            collect_messages(package, &fq_package, message, &mut messages);
+        }
+    }
+    messages.retain(|message| !is_extern(&message.fq_name));
+
+    // The messages implementing the trait, those matching the patterns
+    // first, then those they hold until none is added.
+    let mut masked = messages
+        .iter()
+        .filter(|message| {
+            patterns
+                .iter()
+                .any(|pattern| match_name(pattern, &message.fq_name))
+        })
+        .map(|message| message.fq_name.clone())
+        .collect::<HashSet<_>>();
+    let known = messages
+        .iter()
+        .map(|message| message.fq_name.as_str())
+        .collect::<HashSet<_>>();
+    loop {
+        let held = messages
+            .iter()
+            .filter(|message| masked.contains(&message.fq_name))
+            .flat_map(|message| &message.descriptor.field)
+            .filter(|field| is_singular_message(field))
+            .map(FieldDescriptorProto::type_name)
+            .filter(|type_name| known.contains(type_name) && !masked.contains(*type_name))
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        if held.is_empty() {
+            break;
+        }
+        masked.extend(held);
+    }
+
+    let mut impls = HashMap::<Module, Vec<TokenStream>>::new();
+    for message in &messages {
+        if !masked.contains(&message.fq_name) {
+            continue;
+        }
+
+        let type_path = message
+            .package
+            .split('.')
+            .filter(|part| !part.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let path = parse_path(&resolve_ident(&type_path, &message.fq_name));
+
+        let mut leaves = Vec::new();
+        let mut nested = Vec::new();
+        let mut statements = Vec::new();
+        for field in &message.descriptor.field {
+            let field_name = field.name();
+            let oneof = field
+                .oneof_index
+                .filter(|_| !field.proto3_optional())
+                .and_then(|index| message.descriptor.oneof_decl.get(index as usize));
+
+            if let Some(oneof) = oneof {
+                let name = parse_ident(oneof.name().to_snake_case());
+                let fq_oneof = format!("{}.{}", message.fq_name, oneof.name());
+                let oneof_path = parse_path(&resolve_ident(&type_path, &fq_oneof));
+                let variant = parse_ident(field_name.to_upper_camel_case());
+                statements.push(quote! {
+                    if matches!(self.#name, ::core::option::Option::Some(#oneof_path::#variant(_)))
+                        && !tonic::codegen::field_mask::selects(paths, #field_name)
+                    {
+                        self.#name = ::core::option::Option::None;
+                    }
+                });
+                leaves.push(field_name);
+                continue;
+            }
+
+            let name = parse_ident(field_name.to_snake_case());
+            if is_singular_message(field) && masked.contains(field.type_name()) {
+                let field_type = parse_path(&resolve_ident(&type_path, field.type_name()));
+                statements.push(quote! {
+                    match tonic::codegen::field_mask::select(paths, #field_name) {
+                        tonic::codegen::field_mask::Selection::Field => {}
+                        tonic::codegen::field_mask::Selection::Fields(paths) => {
+                            tonic::FieldMaskMessage::apply_field_mask(&mut self.#name, &paths);
+                        }
+                        tonic::codegen::field_mask::Selection::None => {
+                            self.#name = ::core::default::Default::default();
+                        }
+                    }
+                });
+                nested.push(quote! {
+                    ::core::option::Option::Some((#field_name, path)) => {
+                        <#field_type as tonic::FieldMaskMessage>::is_field_path(path)
+                    }
+                });
+            } else {
+                statements.push(quote! {
+                    if !tonic::codegen::field_mask::selects(paths, #field_name) {
+                        self.#name = ::core::default::Default::default();
+                    }
+                });
+            }
+            leaves.push(field_name);
+        }
+
+        let is_leaf = if leaves.is_empty() {
+            quote!(false)
+        } else {
+            quote!(matches!(path, #(#leaves)|*))
+        };
+
+        impls
+            .entry(Module::from_protobuf_package_name(message.package))
+            .or_default()
+            .push(quote! {
+                impl tonic::FieldMaskMessage for #path {
+                    fn is_field_path(path: &str) -> bool {
+                        match path.split_once('.') {
+                            ::core::option::Option::None => #is_leaf,
+                            #(#nested)*
+                            ::core::option::Option::Some(_) => false,
+                        }
+                    }
+
+                    fn apply_field_mask<S: AsRef<str>>(&mut self, paths: &[S]) {
+                        if tonic::codegen::field_mask::keeps_all(paths) {
+                            return;
+                        }
+                        #(#statements)*
+                    }
+                }
+            });
+    }
+
+    impls
+        .into_iter()
+        .map(|(module, impls)| {
+            let ast: syn::File =
+                syn::parse2(quote! { #(#impls)* }).expect("not a valid tokenstream");
+            (module, prettyplease::unparse(&ast))
+        })
+        .collect()
+}
+
+// Whether `field` is a message field holding at most one message, outside of
+// oneofs, whose fields masks may select.
+fn is_singular_message(field: &FieldDescriptorProto) -> bool {
+    let in_oneof = field.oneof_index.is_some() && !field.proto3_optional();
+    !in_oneof
+        && field.label() != Label::Repeated
+        && matches!(field.r#type(), Type::Message | Type::Group)
+}
+
+fn parse_path(path: &str) -> syn::Path {
+    syn::parse_str(path).expect("valid type path")
+}
+
+fn parse_ident(name: String) -> syn::Ident {
+    syn::parse_str(&sanitize_identifier(name)).expect("valid identifier")
+}
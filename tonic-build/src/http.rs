@@ -0,0 +1,170 @@
+/// An HTTP binding of a method, as declared by a `google.api.http`
+/// annotation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRule {
+    /// The name of the method, as in the `.proto` file, e.g. `GetShelf`.
+    pub method: String,
+    /// The HTTP method, e.g. `GET`, or the kind of a custom pattern.
+    pub http_method: String,
+    /// The path template, e.g. `/v1/{name=shelves/*}`.
+    pub path_template: String,
+    /// The field of the request mapped to the request body, `*` for the
+    /// whole request, or `None` if the request has no body.
+    pub body: Option<String>,
+    /// The field of the response mapped to the response body, or `None` for
+    /// the whole response.
+    pub response_body: Option<String>,
+}
+
+#[cfg(feature = "prost")]
+pub(crate) use self::decode::decode_http_rules;
+
+#[cfg(feature = "prost")]
+mod decode {
+    use std::collections::HashMap;
+
+    use prost::Message as _;
+
+    use super::HttpRule;
+
+    // The subset of `descriptor.proto` leading to the method options, which
+    // `prost-types` drops the extensions of.
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct FileDescriptorSet {
+        #[prost(message, repeated, tag = "1")]
+        file: Vec<FileDescriptorProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct FileDescriptorProto {
+        #[prost(string, optional, tag = "2")]
+        package: Option<String>,
+        #[prost(message, repeated, tag = "6")]
+        service: Vec<ServiceDescriptorProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct ServiceDescriptorProto {
+        #[prost(string, optional, tag = "1")]
+        name: Option<String>,
+        #[prost(message, repeated, tag = "2")]
+        method: Vec<MethodDescriptorProto>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct MethodDescriptorProto {
+        #[prost(string, optional, tag = "1")]
+        name: Option<String>,
+        #[prost(message, optional, tag = "4")]
+        options: Option<MethodOptions>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct MethodOptions {
+        // The `google.api.http` extension.
+        #[prost(message, optional, tag = "72295728")]
+        http: Option<GoogleHttpRule>,
+    }
+
+    // `google.api.HttpRule`.
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct GoogleHttpRule {
+        #[prost(oneof = "Pattern", tags = "2, 3, 4, 5, 6, 8")]
+        pattern: Option<Pattern>,
+        #[prost(string, tag = "7")]
+        body: String,
+        #[prost(string, tag = "12")]
+        response_body: String,
+        #[prost(message, repeated, tag = "11")]
+        additional_bindings: Vec<GoogleHttpRule>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Oneof)]
+    enum Pattern {
+        #[prost(string, tag = "2")]
+        Get(String),
+        #[prost(string, tag = "3")]
+        Put(String),
+        #[prost(string, tag = "4")]
+        Post(String),
+        #[prost(string, tag = "5")]
+        Delete(String),
+        #[prost(string, tag = "6")]
+        Patch(String),
+        #[prost(message, tag = "8")]
+        Custom(CustomHttpPattern),
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct CustomHttpPattern {
+        #[prost(string, tag = "1")]
+        kind: String,
+        #[prost(string, tag = "2")]
+        path: String,
+    }
+
+    /// Decodes the `google.api.http` annotations of the methods of an encoded
+    /// `FileDescriptorSet`, keyed by the fully qualified service name.
+    ///
+    /// Additional bindings follow the binding they are declared in.
+    pub(crate) fn decode_http_rules(
+        encoded: &[u8],
+    ) -> Result<HashMap<String, Vec<HttpRule>>, prost::DecodeError> {
+        let fds = FileDescriptorSet::decode(encoded)?;
+
+        let mut rules = HashMap::new();
+
+        for file in fds.file {
+            let package = file.package.unwrap_or_default();
+
+            for service in file.service {
+                let service_name = service.name.unwrap_or_default();
+                let full_name = if package.is_empty() {
+                    service_name
+                } else {
+                    format!("{package}.{service_name}")
+                };
+
+                let mut service_rules = Vec::new();
+                for method in service.method {
+                    let name = method.name.unwrap_or_default();
+                    if let Some(http) = method.options.and_then(|options| options.http) {
+                        add_rules(&name, http, &mut service_rules);
+                    }
+                }
+
+                if !service_rules.is_empty() {
+                    rules.insert(full_name, service_rules);
+                }
+            }
+        }
+
+        Ok(rules)
+    }
+
+    fn add_rules(method: &str, rule: GoogleHttpRule, rules: &mut Vec<HttpRule>) {
+        let pattern = match rule.pattern {
+            Some(Pattern::Get(path)) => Some(("GET".to_string(), path)),
+            Some(Pattern::Put(path)) => Some(("PUT".to_string(), path)),
+            Some(Pattern::Post(path)) => Some(("POST".to_string(), path)),
+            Some(Pattern::Delete(path)) => Some(("DELETE".to_string(), path)),
+            Some(Pattern::Patch(path)) => Some(("PATCH".to_string(), path)),
+            Some(Pattern::Custom(custom)) => Some((custom.kind, custom.path)),
+            None => None,
+        };
+
+        if let Some((http_method, path_template)) = pattern {
+            rules.push(HttpRule {
+                method: method.to_string(),
+                http_method,
+                path_template,
+                body: Some(rule.body).filter(|body| !body.is_empty()),
+                response_body: Some(rule.response_body).filter(|body| !body.is_empty()),
+            });
+        }
+
+        for binding in rule.additional_bindings {
+            add_rules(method, binding, rules);
+        }
+    }
+}
@@ -25,12 +25,35 @@ pub mod manual;
 
 /// Service code generation for client
 mod client;
+/// Protobuf editions support
+#[cfg(feature = "prost")]
+mod editions;
+/// `tonic::FieldMaskMessage` implementations of generated messages
+#[cfg(feature = "prost")]
+mod field_mask;
+/// Method name and path constants generation
+mod methods;
+/// Client trait and mock client code generation
+mod mock;
+/// `tonic::RedactMessage` implementations of generated messages
+#[cfg(feature = "prost")]
+mod redact;
+/// Service registry generation
+#[cfg(feature = "prost")]
+mod registry;
+/// `serde` attributes for generated messages
+#[cfg(feature = "prost")]
+mod serde;
 /// Service code generation for Server
 mod server;
 
 mod code_gen;
 pub use code_gen::CodeGenBuilder;
 
+/// `google.api.http` annotations
+mod http;
+pub use http::HttpRule;
+
 mod compile_settings;
 
 /// Service generation trait.
@@ -56,6 +79,10 @@ pub trait Service {
     fn methods(&self) -> &[Self::Method];
     /// Get comments about this item.
     fn comment(&self) -> &[Self::Comment];
+    /// Service is deprecated.
+    fn deprecated(&self) -> bool {
+        false
+    }
 }
 
 /// Method generation trait.
@@ -84,6 +111,10 @@ pub trait Method {
     fn deprecated(&self) -> bool {
         false
     }
+    /// Idempotency level of the method.
+    fn idempotency_level(&self) -> IdempotencyLevel {
+        IdempotencyLevel::Unknown
+    }
     /// Type name of request and response.
     fn request_response_name(
         &self,
@@ -92,13 +123,45 @@ pub trait Method {
     ) -> (TokenStream, TokenStream);
 }
 
-/// Attributes that will be added to `mod` and `struct` items.
+/// Idempotency level of a method, as declared by its `idempotency_level`
+/// option.
+///
+/// The level of the methods declaring one is set on their
+/// `tonic::MethodDescriptor` and inserted into the extensions of the requests
+/// of the generated clients as a `tonic::IdempotencyLevel`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IdempotencyLevel {
+    /// The method may have side effects, the default.
+    #[default]
+    Unknown,
+    /// The method has no side effects.
+    NoSideEffects,
+    /// Calling the method more than once has the same effects as calling it
+    /// once.
+    Idempotent,
+}
+
+// Generates the path of the `tonic::IdempotencyLevel` of a method declaring
+// one.
+fn generate_idempotency_level<T: Method>(method: &T) -> Option<TokenStream> {
+    match method.idempotency_level() {
+        IdempotencyLevel::Unknown => None,
+        IdempotencyLevel::NoSideEffects => {
+            Some(quote::quote!(tonic::IdempotencyLevel::NoSideEffects))
+        }
+        IdempotencyLevel::Idempotent => Some(quote::quote!(tonic::IdempotencyLevel::Idempotent)),
+    }
+}
+
+/// Attributes that will be added to `mod`, `struct` and method items.
 #[derive(Debug, Default, Clone)]
 pub struct Attributes {
     /// `mod` attributes.
     module: Vec<(String, String)>,
     /// `struct` attributes.
     structure: Vec<(String, String)>,
+    /// Method attributes.
+    method: Vec<(String, String)>,
 }
 
 impl Attributes {
@@ -110,6 +173,10 @@ impl Attributes {
         generate_attributes(name, &self.structure)
     }
 
+    fn for_method(&self, name: &str) -> Vec<syn::Attribute> {
+        generate_attributes(name, &self.method)
+    }
+
     /// Add an attribute that will be added to `mod` items matching the given pattern.
     ///
     /// # Examples
@@ -135,6 +202,21 @@ impl Attributes {
     pub fn push_struct(&mut self, pattern: impl Into<String>, attr: impl Into<String>) {
         self.structure.push((pattern.into(), attr.into()));
     }
+
+    /// Add an attribute that will be added to method items matching the given pattern.
+    ///
+    /// Methods are matched on their full name, e.g. `my.proto.package.EchoService.Echo`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tonic_build::*;
+    /// let mut attributes = Attributes::default();
+    /// attributes.push_method("EchoService.Echo", "#[deprecated]");
+    /// ```
+    pub fn push_method(&mut self, pattern: impl Into<String>, attr: impl Into<String>) {
+        self.method.push((pattern.into(), attr.into()));
+    }
 }
 
 fn format_service_name<T: Service>(service: &T, emit_package: bool) -> String {
@@ -193,6 +275,16 @@ fn generate_deprecated() -> TokenStream {
     stream
 }
 
+// Generates the `#[deprecated]` attribute of the items of a deprecated service,
+// along with the lint to allow in the module generated for the service.
+fn generate_service_deprecated<T: Service>(service: &T) -> (TokenStream, TokenStream) {
+    if service.deprecated() {
+        (generate_deprecated(), quote::quote!(deprecated,))
+    } else {
+        (TokenStream::new(), TokenStream::new())
+    }
+}
+
 // Generate a singular line of a doc comment
 fn generate_doc_comment<S: AsRef<str>>(comment: S) -> TokenStream {
     let comment = comment.as_ref();
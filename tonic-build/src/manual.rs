@@ -28,7 +28,7 @@
 //! }
 //! ```
 
-use crate::code_gen::CodeGenBuilder;
+use crate::{code_gen::CodeGenBuilder, IdempotencyLevel};
 
 use proc_macro2::TokenStream;
 use quote::ToTokens;
@@ -175,6 +175,8 @@ pub struct Method {
     server_streaming: bool,
     /// Identifies if the method is deprecated.
     deprecated: bool,
+    /// The idempotency level of the method.
+    idempotency_level: IdempotencyLevel,
     /// The path to the codec to use for this method
     codec_path: String,
 }
@@ -217,6 +219,10 @@ impl crate::Method for Method {
         self.deprecated
     }
 
+    fn idempotency_level(&self) -> IdempotencyLevel {
+        self.idempotency_level
+    }
+
     fn request_response_name(
         &self,
         _proto_path: &str,
@@ -270,6 +276,8 @@ pub struct MethodBuilder {
     server_streaming: bool,
     /// Identifies if the method is deprecated.
     deprecated: bool,
+    /// The idempotency level of the method.
+    idempotency_level: IdempotencyLevel,
     /// The path to the codec to use for this method
     codec_path: Option<String>,
 }
@@ -334,6 +342,12 @@ impl MethodBuilder {
         self
     }
 
+    /// Sets the idempotency level of the Method.
+    pub fn idempotency_level(mut self, idempotency_level: IdempotencyLevel) -> Self {
+        self.idempotency_level = idempotency_level;
+        self
+    }
+
     /// Build a Method
     ///
     /// Panics if `name`, `route_name`, `input_type`, `output_type`, or `codec_path` weren't set.
@@ -347,6 +361,7 @@ impl MethodBuilder {
             client_streaming: self.client_streaming,
             server_streaming: self.server_streaming,
             deprecated: self.deprecated,
+            idempotency_level: self.idempotency_level,
             codec_path: self.codec_path.unwrap(),
         }
     }
@@ -354,12 +369,21 @@ impl MethodBuilder {
 
 struct ServiceGenerator {
     builder: Builder,
+    methods: TokenStream,
     clients: TokenStream,
     servers: TokenStream,
 }
 
 impl ServiceGenerator {
     fn generate(&mut self, service: &Service) {
+        if self.builder.build_client || self.builder.build_server {
+            let methods = CodeGenBuilder::new()
+                .emit_package(true)
+                .generate_methods(service);
+
+            self.methods.extend(methods);
+        }
+
         if self.builder.build_server {
             let server = CodeGenBuilder::new()
                 .emit_package(true)
@@ -374,6 +398,7 @@ impl ServiceGenerator {
                 .emit_package(true)
                 .compile_well_known_types(false)
                 .build_transport(self.builder.build_transport)
+                .build_mock(self.builder.build_mock)
                 .generate_client(service, "");
 
             self.clients.extend(client);
@@ -381,6 +406,20 @@ impl ServiceGenerator {
     }
 
     fn finalize(&mut self, buf: &mut String) {
+        if !self.methods.is_empty() {
+            let methods = &self.methods;
+
+            let method_constants = quote::quote! {
+                #methods
+            };
+
+            let ast: syn::File = syn::parse2(method_constants).expect("not a valid tokenstream");
+            let code = prettyplease::unparse(&ast);
+            buf.push_str(&code);
+
+            self.methods = TokenStream::default();
+        }
+
         if self.builder.build_client && !self.clients.is_empty() {
             let clients = &self.clients;
 
@@ -417,6 +456,7 @@ pub struct Builder {
     build_server: bool,
     build_client: bool,
     build_transport: bool,
+    build_mock: bool,
 
     out_dir: Option<PathBuf>,
 }
@@ -427,6 +467,7 @@ impl Default for Builder {
             build_server: true,
             build_client: true,
             build_transport: true,
+            build_mock: false,
             out_dir: None,
         }
     }
@@ -463,6 +504,16 @@ impl Builder {
         self
     }
 
+    /// Enable or disable generation of a `{Service}ClientApi` trait and of a
+    /// `Mock{Service}Client` implementing it, whose responses can be
+    /// programmed method by method.
+    ///
+    /// Defaults to disabling mock client code generation.
+    pub fn build_mock(mut self, enable: bool) -> Self {
+        self.build_mock = enable;
+        self
+    }
+
     /// Set the output directory to generate code to.
     ///
     /// Defaults to the `OUT_DIR` environment variable.
@@ -484,6 +535,7 @@ impl Builder {
 
         let mut generator = ServiceGenerator {
             builder: self,
+            methods: TokenStream::default(),
             clients: TokenStream::default(),
             servers: TokenStream::default(),
         };
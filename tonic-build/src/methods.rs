@@ -0,0 +1,121 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use super::{Method, Service};
+use crate::{
+    format_method_path, format_service_name, generate_idempotency_level, naive_snake_case, HttpRule,
+};
+
+/// Generates the `{service}_methods` module, holding the name of the service
+/// along with the name, path and `tonic::MethodDescriptor` of each of its
+/// methods, and the `METHODS` table of the descriptors, along with the
+/// `HTTP_ROUTES` table of `http_rules` if set.
+pub(crate) fn generate<T: Service>(
+    service: &T,
+    emit_package: bool,
+    http_rules: Option<&[HttpRule]>,
+) -> TokenStream {
+    let methods_mod = format_ident!("{}_methods", naive_snake_case(service.name()));
+    let service_name = format_service_name(service, emit_package);
+
+    let mut method_mods = TokenStream::new();
+    let mut descriptors = TokenStream::new();
+
+    for method in service.methods() {
+        let method_mod = format_ident!("{}", method.name());
+        let method_name = method.identifier();
+        let path = format_method_path(service, method, emit_package);
+        let client_streaming = method.client_streaming();
+        let server_streaming = method.server_streaming();
+        let idempotency_level =
+            generate_idempotency_level(method).map(|level| quote!(.with_idempotency_level(#level)));
+
+        let mod_doc = format!(" Constants of the `{method_name}` method.");
+
+        method_mods.extend(quote! {
+            #[doc = #mod_doc]
+            pub mod #method_mod {
+                /// The name of the method.
+                pub const NAME: &str = #method_name;
+                /// The path of the method in requests.
+                pub const PATH: &str = #path;
+                /// The descriptor of the method.
+                pub const DESCRIPTOR: tonic::MethodDescriptor = tonic::MethodDescriptor::new(
+                    super::SERVICE_NAME,
+                    NAME,
+                    PATH,
+                    #client_streaming,
+                    #server_streaming,
+                )#idempotency_level;
+            }
+        });
+
+        descriptors.extend(quote! {
+            #method_mod::DESCRIPTOR,
+        });
+    }
+
+    let http_routes = http_rules.map(|http_rules| generate_http_routes(service, http_rules));
+
+    let mod_doc = format!(" Names and paths of the methods of the `{service_name}` service.");
+
+    quote! {
+        #[doc = #mod_doc]
+        pub mod #methods_mod {
+            #![allow(dead_code)]
+
+            /// The fully qualified name of the service.
+            pub const SERVICE_NAME: &str = #service_name;
+
+            #method_mods
+
+            /// The descriptors of the methods of the service, in declaration
+            /// order.
+            pub const METHODS: &[tonic::MethodDescriptor] = &[#descriptors];
+
+            #http_routes
+        }
+    }
+}
+
+fn generate_http_routes<T: Service>(service: &T, http_rules: &[HttpRule]) -> TokenStream {
+    let mut routes = TokenStream::new();
+
+    // Routes are listed in the order of the methods.
+    for method in service.methods() {
+        let method_mod = format_ident!("{}", method.name());
+
+        for rule in http_rules
+            .iter()
+            .filter(|rule| rule.method == method.identifier())
+        {
+            let http_method = &rule.http_method;
+            let path_template = &rule.path_template;
+            let body = optional_str(rule.body.as_deref());
+            let response_body = optional_str(rule.response_body.as_deref());
+
+            routes.extend(quote! {
+                tonic::HttpRoute::new(
+                    #method_mod::DESCRIPTOR,
+                    #http_method,
+                    #path_template,
+                    #body,
+                    #response_body,
+                ),
+            });
+        }
+    }
+
+    quote! {
+        /// The HTTP routes of the methods of the service, as declared by
+        /// their `google.api.http` annotations.
+        pub const HTTP_ROUTES: &[tonic::HttpRoute] = &[#routes];
+    }
+}
+
+fn optional_str(value: Option<&str>) -> TokenStream {
+    match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None),
+    }
+}
@@ -0,0 +1,224 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+
+use super::{Method, Service};
+use crate::generate_deprecated;
+
+/// Generates the `{Service}ClientApi` trait, implemented by the generated
+/// client, along with its `Mock{Service}Client` implementation when
+/// `build_mock` is set. With `automock`, the trait is annotated to be mocked
+/// by `mockall` in tests.
+///
+/// The generated items are expected to be placed inside the client module.
+pub(crate) fn generate<T: Service>(
+    service: &T,
+    proto_path: &str,
+    compile_well_known_types: bool,
+    use_local_futures: bool,
+    build_mock: bool,
+    automock: bool,
+) -> TokenStream {
+    let client_ident = format_ident!("{}Client", service.name());
+    let trait_ident = format_ident!("{}ClientApi", service.name());
+    let mock_ident = format_ident!("Mock{}Client", service.name());
+
+    let (async_trait, send_bound, future_bound, client_bounds) = if use_local_futures {
+        (
+            quote!(#[async_trait(?Send)]),
+            TokenStream::new(),
+            TokenStream::new(),
+            TokenStream::new(),
+        )
+    } else {
+        (
+            quote!(#[async_trait]),
+            quote!(: std::marker::Send),
+            quote!(+ std::marker::Send),
+            quote! {
+                T: std::marker::Send,
+                T::Future: std::marker::Send,
+            },
+        )
+    };
+
+    let mut trait_methods = TokenStream::new();
+    let mut client_methods = TokenStream::new();
+    let mut mock_fields = TokenStream::new();
+    let mut mock_setters = TokenStream::new();
+    let mut mock_methods = TokenStream::new();
+
+    for method in service.methods() {
+        let ident = format_ident!("{}", method.name());
+        let setter = format_ident!("on_{}", method.name().trim_start_matches("r#"));
+        let (request, response) =
+            method.request_response_name(proto_path, compile_well_known_types);
+
+        let (request_message, handler_message, collect_request) = if method.client_streaming() {
+            (
+                quote! {
+                    Pin<Box<dyn tokio_stream::Stream<Item = #request> + std::marker::Send + 'static>>
+                },
+                quote!(Vec<#request>),
+                quote! {
+                    let (metadata, extensions, stream) = request.into_parts();
+                    let messages = tokio_stream::StreamExt::collect::<Vec<_>>(stream).await;
+                    let request = tonic::Request::from_parts(metadata, extensions, messages);
+                },
+            )
+        } else {
+            (request.clone(), request, TokenStream::new())
+        };
+
+        let (response_message, into_response) = if method.server_streaming() {
+            (
+                quote!(BoxStream<#response>),
+                quote! {
+                    .map(|response| {
+                        response.map(|stream| Box::pin(stream) as BoxStream<#response>)
+                    })
+                },
+            )
+        } else {
+            (response, TokenStream::new())
+        };
+
+        let request_ty = quote!(tonic::Request<#request_message>);
+        let handler_request_ty = quote!(tonic::Request<#handler_message>);
+        let response_ty = quote!(tonic::Response<#response_message>);
+        let handler_ty = quote!(MockHandler<#handler_message, #response_message>);
+
+        let not_mocked = format!("{} is not mocked", method.identifier());
+
+        if method.deprecated() {
+            trait_methods.extend(generate_deprecated());
+        }
+
+        trait_methods.extend(quote! {
+            async fn #ident(
+                &mut self,
+                request: #request_ty,
+            ) -> std::result::Result<#response_ty, tonic::Status>;
+        });
+
+        client_methods.extend(quote! {
+            async fn #ident(
+                &mut self,
+                request: #request_ty,
+            ) -> std::result::Result<#response_ty, tonic::Status> {
+                // Boxed to help the compiler prove that the future is `Send`.
+                let future: Pin<Box<dyn Future<Output = _> #future_bound + '_>> =
+                    Box::pin(#client_ident::#ident(self, request));
+                future.await #into_response
+            }
+        });
+
+        mock_fields.extend(quote! {
+            #ident: #handler_ty,
+        });
+
+        mock_setters.extend(quote! {
+            /// Programs the response of the method. Requests to methods that
+            /// were not programmed fail with `Code::Unimplemented`.
+            pub fn #setter<F>(&mut self, handler: F) -> &mut Self
+            where
+                F: Fn(#handler_request_ty) -> std::result::Result<#response_ty, tonic::Status>
+                    + std::marker::Send
+                    + std::marker::Sync
+                    + 'static,
+            {
+                self.#ident = Some(Arc::new(handler));
+                self
+            }
+        });
+
+        mock_methods.extend(quote! {
+            async fn #ident(
+                &mut self,
+                request: #request_ty,
+            ) -> std::result::Result<#response_ty, tonic::Status> {
+                #collect_request
+                match &self.#ident {
+                    Some(handler) => handler(request),
+                    None => Err(tonic::Status::unimplemented(#not_mocked)),
+                }
+            }
+        });
+    }
+
+    let trait_doc = if build_mock {
+        format!(
+            " Abstraction over the methods of [`{client_ident}`], allowing code that depends on it to be tested with [`{mock_ident}`]."
+        )
+    } else {
+        format!(" Abstraction over the methods of [`{client_ident}`].")
+    };
+    let automock = automock.then(|| quote!(#[cfg_attr(test, mockall::automock)]));
+
+    let client_trait = quote! {
+        #[doc = #trait_doc]
+        #automock
+        #async_trait
+        pub trait #trait_ident #send_bound {
+            #trait_methods
+        }
+
+        #async_trait
+        #[allow(deprecated)]
+        impl<T> #trait_ident for #client_ident<T>
+        where
+            T: tonic::client::GrpcService<tonic::body::Body>,
+            #client_bounds
+            T::Error: Into<StdError>,
+            T::ResponseBody: Body<Data = Bytes> + std::marker::Send + 'static,
+            <T::ResponseBody as Body>::Error: Into<StdError> + std::marker::Send,
+        {
+            #client_methods
+        }
+    };
+
+    if !build_mock {
+        return client_trait;
+    }
+
+    let mock_doc = format!(
+        " Mock implementation of [`{trait_ident}`], whose responses are programmed method by method."
+    );
+
+    quote! {
+        #client_trait
+
+        type MockHandler<Req, Res> = Option<
+            Arc<
+                dyn Fn(tonic::Request<Req>) -> std::result::Result<tonic::Response<Res>, tonic::Status>
+                    + std::marker::Send
+                    + std::marker::Sync,
+            >,
+        >;
+
+        #[doc = #mock_doc]
+        #[derive(Clone, Default)]
+        pub struct #mock_ident {
+            #mock_fields
+        }
+
+        impl #mock_ident {
+            /// Creates a mock in which no method is programmed.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            #mock_setters
+        }
+
+        impl std::fmt::Debug for #mock_ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!(#mock_ident)).finish_non_exhaustive()
+            }
+        }
+
+        #async_trait
+        impl #trait_ident for #mock_ident {
+            #mock_methods
+        }
+    }
+}
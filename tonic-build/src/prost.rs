@@ -1,13 +1,16 @@
 use crate::{code_gen::CodeGenBuilder, compile_settings::CompileSettings};
 
-use super::Attributes;
+use super::{Attributes, HttpRule, IdempotencyLevel};
 use proc_macro2::TokenStream;
-use prost_build::{Config, Method, Service};
+use prost::Message as _;
+use prost_build::{Config, Method, Module, Service};
+use prost_types::compiler::{code_generator_response, CodeGeneratorRequest, CodeGeneratorResponse};
+use prost_types::FileDescriptorProto;
 use quote::ToTokens;
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashMap, HashSet},
     ffi::OsString,
-    io,
+    fs, io,
     path::{Path, PathBuf},
 };
 
@@ -19,6 +22,10 @@ pub fn configure() -> Builder {
         build_client: true,
         build_server: true,
         build_transport: true,
+        build_mock: false,
+        build_client_trait: false,
+        client_trait_automock: false,
+        build_web_client: false,
         file_descriptor_set_path: None,
         skip_protoc_run: false,
         out_dir: None,
@@ -40,9 +47,24 @@ pub fn configure() -> Builder {
         emit_rerun_if_changed: std::env::var_os("CARGO").is_some(),
         disable_comments: HashSet::default(),
         use_arc_self: false,
+        use_local_futures: false,
         generate_default_stubs: false,
+        handler_extractors: Vec::new(),
+        request_validators: Vec::new(),
+        intercepted_messages: Vec::new(),
         compile_settings: CompileSettings::default(),
         skip_debug: HashSet::default(),
+        split_by_file: false,
+        serde: false,
+        redact_fields: Vec::new(),
+        field_mask_messages: Vec::new(),
+        build_reflection: false,
+        file_descriptor_sets: HashMap::new(),
+        build_http_routes: false,
+        http_rules: HashMap::new(),
+        service_registry: None,
+        service_registry_features: false,
+        well_known_types_path: None,
     }
 }
 
@@ -66,6 +88,9 @@ pub fn compile_fds(fds: prost_types::FileDescriptorSet) -> io::Result<()> {
     self::configure().compile_fds(fds)
 }
 
+/// The file name `prost-build` uses for code outside of any package.
+const DEFAULT_PACKAGE_FILENAME: &str = "_";
+
 /// Non-path Rust types allowed for request/response types.
 const NON_PATH_TYPE_ALLOWLIST: &[&str] = &["()"];
 
@@ -119,6 +144,10 @@ impl crate::Service for TonicBuildService {
         &self.prost_service.comments.leading[..]
     }
 
+    fn deprecated(&self) -> bool {
+        self.prost_service.options.deprecated.unwrap_or_default()
+    }
+
     fn methods(&self) -> &[Self::Method] {
         &self.methods
     }
@@ -164,6 +193,16 @@ impl crate::Method for TonicBuildMethod {
         self.prost_method.options.deprecated.unwrap_or_default()
     }
 
+    fn idempotency_level(&self) -> IdempotencyLevel {
+        use prost_types::method_options::IdempotencyLevel as Level;
+
+        match self.prost_method.options.idempotency_level() {
+            Level::IdempotencyUnknown => IdempotencyLevel::Unknown,
+            Level::NoSideEffects => IdempotencyLevel::NoSideEffects,
+            Level::Idempotent => IdempotencyLevel::Idempotent,
+        }
+    }
+
     fn request_response_name(
         &self,
         proto_path: &str,
@@ -198,12 +237,21 @@ impl crate::Method for TonicBuildMethod {
     }
 }
 
+fn service_full_name(service: &Service) -> String {
+    if service.package.is_empty() {
+        service.proto_name.clone()
+    } else {
+        format!("{}.{}", service.package, service.proto_name)
+    }
+}
+
 fn is_google_type(ty: &str) -> bool {
     ty.starts_with(".google.protobuf")
 }
 
 struct ServiceGenerator {
     builder: Builder,
+    methods: TokenStream,
     clients: TokenStream,
     servers: TokenStream,
 }
@@ -212,6 +260,7 @@ impl ServiceGenerator {
     fn new(builder: Builder) -> Self {
         ServiceGenerator {
             builder,
+            methods: TokenStream::default(),
             clients: TokenStream::default(),
             servers: TokenStream::default(),
         }
@@ -220,6 +269,24 @@ impl ServiceGenerator {
 
 impl prost_build::ServiceGenerator for ServiceGenerator {
     fn generate(&mut self, service: prost_build::Service, _buf: &mut String) {
+        if self.builder.build_client || self.builder.build_server {
+            let methods = CodeGenBuilder::new()
+                .emit_package(self.builder.emit_package)
+                .http_rules(self.builder.build_http_routes.then(|| {
+                    self.builder
+                        .http_rules
+                        .get(&service_full_name(&service))
+                        .cloned()
+                        .unwrap_or_default()
+                }))
+                .generate_methods(&TonicBuildService::new(
+                    service.clone(),
+                    self.builder.compile_settings.clone(),
+                ));
+
+            self.methods.extend(methods);
+        }
+
         if self.builder.build_server {
             let server = CodeGenBuilder::new()
                 .emit_package(self.builder.emit_package)
@@ -227,7 +294,17 @@ impl prost_build::ServiceGenerator for ServiceGenerator {
                 .attributes(self.builder.server_attributes.clone())
                 .disable_comments(self.builder.disable_comments.clone())
                 .use_arc_self(self.builder.use_arc_self)
+                .use_local_futures(self.builder.use_local_futures)
                 .generate_default_stubs(self.builder.generate_default_stubs)
+                .handler_extractors(self.builder.handler_extractors.clone())
+                .request_validators(self.builder.request_validators.clone())
+                .intercepted_messages(self.builder.intercepted_messages.clone())
+                .file_descriptor_set(
+                    self.builder
+                        .file_descriptor_sets
+                        .get(&service_full_name(&service))
+                        .cloned(),
+                )
                 .generate_server(
                     &TonicBuildService::new(service.clone(), self.builder.compile_settings.clone()),
                     &self.builder.proto_path,
@@ -243,6 +320,11 @@ impl prost_build::ServiceGenerator for ServiceGenerator {
                 .attributes(self.builder.client_attributes.clone())
                 .disable_comments(self.builder.disable_comments.clone())
                 .build_transport(self.builder.build_transport)
+                .build_mock(self.builder.build_mock)
+                .build_client_trait(self.builder.build_client_trait)
+                .client_trait_automock(self.builder.client_trait_automock)
+                .build_web_client(self.builder.build_web_client)
+                .use_local_futures(self.builder.use_local_futures)
                 .generate_client(
                     &TonicBuildService::new(service, self.builder.compile_settings.clone()),
                     &self.builder.proto_path,
@@ -253,6 +335,20 @@ impl prost_build::ServiceGenerator for ServiceGenerator {
     }
 
     fn finalize(&mut self, buf: &mut String) {
+        if !self.methods.is_empty() {
+            let methods = &self.methods;
+
+            let method_constants = quote::quote! {
+                #methods
+            };
+
+            let ast: syn::File = syn::parse2(method_constants).expect("not a valid tokenstream");
+            let code = prettyplease::unparse(&ast);
+            buf.push_str(&code);
+
+            self.methods = TokenStream::default();
+        }
+
         if self.builder.build_client && !self.clients.is_empty() {
             let clients = &self.clients;
 
@@ -289,6 +385,10 @@ pub struct Builder {
     pub(crate) build_client: bool,
     pub(crate) build_server: bool,
     pub(crate) build_transport: bool,
+    pub(crate) build_mock: bool,
+    pub(crate) build_client_trait: bool,
+    pub(crate) client_trait_automock: bool,
+    pub(crate) build_web_client: bool,
     pub(crate) file_descriptor_set_path: Option<PathBuf>,
     pub(crate) skip_protoc_run: bool,
     pub(crate) extern_path: Vec<(String, String)>,
@@ -309,9 +409,24 @@ pub struct Builder {
     pub(crate) emit_rerun_if_changed: bool,
     pub(crate) disable_comments: HashSet<String>,
     pub(crate) use_arc_self: bool,
+    pub(crate) use_local_futures: bool,
     pub(crate) generate_default_stubs: bool,
+    pub(crate) handler_extractors: Vec<(String, String)>,
+    pub(crate) request_validators: Vec<(String, String)>,
+    pub(crate) intercepted_messages: Vec<String>,
     pub(crate) compile_settings: CompileSettings,
     pub(crate) skip_debug: HashSet<String>,
+    pub(crate) split_by_file: bool,
+    pub(crate) serde: bool,
+    pub(crate) redact_fields: Vec<String>,
+    pub(crate) field_mask_messages: Vec<String>,
+    pub(crate) build_reflection: bool,
+    pub(crate) file_descriptor_sets: HashMap<String, Vec<u8>>,
+    pub(crate) build_http_routes: bool,
+    pub(crate) http_rules: HashMap<String, Vec<HttpRule>>,
+    pub(crate) service_registry: Option<PathBuf>,
+    pub(crate) service_registry_features: bool,
+    pub(crate) well_known_types_path: Option<String>,
 
     out_dir: Option<PathBuf>,
 }
@@ -338,6 +453,62 @@ impl Builder {
         self
     }
 
+    /// Enable or disable generation of mock clients.
+    ///
+    /// When enabled, a `{Service}ClientApi` trait is generated in the client
+    /// module, implemented by the generated client and by a
+    /// `Mock{Service}Client`, whose responses can be programmed method by
+    /// method. Code that depends on the trait can then be unit tested without
+    /// spinning up a server.
+    ///
+    /// This defaults to `false`.
+    pub fn build_mock(mut self, enable: bool) -> Self {
+        self.build_mock = enable;
+        self
+    }
+
+    /// Enable or disable generation of client traits.
+    ///
+    /// When enabled, a `{Service}ClientApi` trait is generated in the client
+    /// module, implemented by the generated client, without the mock client
+    /// of [`Builder::build_mock`]. Code can then depend on the trait to be
+    /// handed other implementations.
+    ///
+    /// This defaults to `false`.
+    pub fn build_client_trait(mut self, enable: bool) -> Self {
+        self.build_client_trait = enable;
+        self
+    }
+
+    /// Enable or disable annotating the client traits with
+    /// `#[cfg_attr(test, mockall::automock)]`.
+    ///
+    /// When enabled, `mockall` generates a `Mock{Service}ClientApi` of each
+    /// `{Service}ClientApi` trait in tests, which requires `mockall` in the
+    /// dev-dependencies. The traits are generated with either
+    /// [`Builder::build_client_trait`] or [`Builder::build_mock`].
+    ///
+    /// This defaults to `false`.
+    pub fn client_trait_automock(mut self, enable: bool) -> Self {
+        self.client_trait_automock = enable;
+        self
+    }
+
+    /// Enable or disable generation of browser compatible clients.
+    ///
+    /// When enabled, the generated clients get a `with_grpc_web` constructor
+    /// speaking the gRPC-web protocol through an HTTP/1.1 service, such as a
+    /// `fetch` based one on `wasm32-unknown-unknown`, and their `connect`
+    /// constructor is only generated outside of `wasm32`. The same generated
+    /// code can then be used by native and browser clients. The generated
+    /// code depends on `tonic-web`.
+    ///
+    /// This defaults to `false`.
+    pub fn build_web_client(mut self, enable: bool) -> Self {
+        self.build_web_client = enable;
+        self
+    }
+
     /// Generate a file containing the encoded `prost_types::FileDescriptorSet` for protocol buffers
     /// modules. This is required for implementing gRPC Server Reflection.
     pub fn file_descriptor_set_path(mut self, path: impl AsRef<Path>) -> Self {
@@ -480,6 +651,117 @@ impl Builder {
         self
     }
 
+    /// Add additional attribute to matched methods of the service servers' traits. Matches on
+    /// the full name of the method, e.g. `my.proto.package.EchoService.Echo`.
+    pub fn server_method_attribute<P: AsRef<str>, A: AsRef<str>>(
+        mut self,
+        path: P,
+        attribute: A,
+    ) -> Self {
+        self.server_attributes
+            .push_method(path.as_ref().to_string(), attribute.as_ref().to_string());
+        self
+    }
+
+    /// Add an argument of type `extractor` to the handlers of the matched
+    /// methods of the service servers' traits. Matches on the full name of the
+    /// method, e.g. `my.proto.package.EchoService.Echo`, or of its service,
+    /// e.g. `my.proto.package.EchoService`.
+    ///
+    /// The arguments are extracted from the requests with
+    /// `tonic::server::FromRequest` before calling the handlers, which take
+    /// them ahead of the request in the order they're added, named after their
+    /// types. Requests failing to be extracted are answered with the status
+    /// returned by the extractor, without calling the handler:
+    ///
+    /// ```rust,no_run
+    /// tonic_build::configure()
+    ///     .handler_extractor("helloworld.Greeter", "crate::auth::User")
+    ///     .handler_extractor(
+    ///         "helloworld.Greeter.SayHello",
+    ///         "tonic::server::Extension<crate::Tenant>",
+    ///     )
+    ///     .compile_protos(&["helloworld.proto"], &["."])
+    ///     .unwrap();
+    /// ```
+    ///
+    /// generates the handler:
+    ///
+    /// ```rust,ignore
+    /// async fn say_hello(
+    ///     &self,
+    ///     user: crate::auth::User,
+    ///     extension: tonic::server::Extension<crate::Tenant>,
+    ///     request: tonic::Request<HelloRequest>,
+    /// ) -> Result<tonic::Response<HelloReply>, tonic::Status>;
+    /// ```
+    pub fn handler_extractor<P: AsRef<str>, E: AsRef<str>>(
+        mut self,
+        path: P,
+        extractor: E,
+    ) -> Self {
+        self.handler_extractors
+            .push((path.as_ref().to_string(), extractor.as_ref().to_string()));
+        self
+    }
+
+    /// Validate the requests of the matched methods with `validator` before
+    /// calling their handlers. Matches on the full name of the method, e.g.
+    /// `my.proto.package.EchoService.Echo`, or of its service, e.g.
+    /// `my.proto.package.EchoService`.
+    ///
+    /// The validator is the path of a function taking a reference to the
+    /// decoded request message and returning a `Result<(), tonic::Status>`.
+    /// Requests failing to be validated are answered with the status returned
+    /// by the validator, without calling the handler. With `tonic-types`'
+    /// `prost-validate` feature, `tonic_types::validate_request` answers the
+    /// messages breaking their protoc-gen-validate constraints with
+    /// `INVALID_ARGUMENT` and `BadRequest` details:
+    ///
+    /// ```rust,no_run
+    /// tonic_build::configure()
+    ///     .request_validator("helloworld.Greeter", "tonic_types::validate_request")
+    ///     .compile_protos(&["helloworld.proto"], &["."])
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Only the requests of unary and server streaming methods are validated,
+    /// the messages of request streams being decoded by the handlers.
+    pub fn request_validator<P: AsRef<str>, V: AsRef<str>>(
+        mut self,
+        path: P,
+        validator: V,
+    ) -> Self {
+        self.request_validators
+            .push((path.as_ref().to_string(), validator.as_ref().to_string()));
+        self
+    }
+
+    /// Run the `tonic::server::MessageInterceptors` of the requests of the
+    /// matched methods on their decoded request messages, before calling their
+    /// handlers. Matches on the full name of the method, e.g.
+    /// `my.proto.package.EchoService.Echo`, or of its service, e.g.
+    /// `my.proto.package.EchoService`.
+    ///
+    /// The interceptors are served as an interceptor of the server, which
+    /// inserts them in the extensions of the requests, and run after the
+    /// validators of the method:
+    ///
+    /// ```rust,no_run
+    /// tonic_build::configure()
+    ///     .intercept_messages("helloworld.Greeter")
+    ///     .compile_protos(&["helloworld.proto"], &["."])
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Only the requests of unary and server streaming methods are
+    /// intercepted, the messages of request streams being decoded by the
+    /// handlers.
+    pub fn intercept_messages<P: AsRef<str>>(mut self, path: P) -> Self {
+        self.intercepted_messages.push(path.as_ref().to_string());
+        self
+    }
+
     /// Add additional attribute to matched client `mod`s. Matches on the package name.
     pub fn client_mod_attribute<P: AsRef<str>, A: AsRef<str>>(
         mut self,
@@ -498,6 +780,18 @@ impl Builder {
         self
     }
 
+    /// Add additional attribute to matched methods of the service clients. Matches on the full
+    /// name of the method, e.g. `my.proto.package.EchoService.Echo`.
+    pub fn client_method_attribute<P: AsRef<str>, A: AsRef<str>>(
+        mut self,
+        path: P,
+        attribute: A,
+    ) -> Self {
+        self.client_attributes
+            .push_method(path.as_ref().to_string(), attribute.as_ref().to_string());
+        self
+    }
+
     /// Set the path to where tonic will search for the Request/Response proto structs
     /// live relative to the module where you call `include_proto!`.
     ///
@@ -527,6 +821,17 @@ impl Builder {
         self
     }
 
+    /// Emit server traits and mock client traits whose futures are not required to be `Send`.
+    ///
+    /// This allows handlers to hold non-`Send` state, such as `Rc` based caches, across
+    /// `.await` points, in which case the generated servers have to be driven on a single
+    /// thread, e.g. inside a `tokio::task::LocalSet`. Response streams, and the generated
+    /// clients, are still `Send` when their inputs are.
+    pub fn use_local_futures(mut self, enable: bool) -> Self {
+        self.use_local_futures = enable;
+        self
+    }
+
     /// Emits GRPC endpoints with no attached package. Effectively ignores protofile package declaration from grpc context.
     ///
     /// This effectively sets prost's exported package to an empty string.
@@ -544,6 +849,22 @@ impl Builder {
         self
     }
 
+    /// Configures the path of the crate the well-known protobuf types are
+    /// used from, instead of `::prost_types`, e.g. `::prost_wkt_types`.
+    ///
+    /// The types of the fields of the messages, as well as the requests and
+    /// responses of the generated clients and servers, refer to the types of
+    /// this crate, which have to be compatible with the ones of
+    /// `prost-types`. The wrapper types, such as `google.protobuf.Int32Value`,
+    /// and `google.protobuf.Empty` are still mapped to Rust primitives and
+    /// `()`.
+    ///
+    /// This has no effect when the well-known types are compiled.
+    pub fn well_known_types_path(mut self, path: impl Into<String>) -> Self {
+        self.well_known_types_path = Some(path.into());
+        self
+    }
+
     /// Configures the optional module filename for easy inclusion of all generated Rust files
     ///
     /// If set, generates a file (inside the `OUT_DIR` or `out_dir()` as appropriate) which contains
@@ -600,7 +921,156 @@ impl Builder {
         self
     }
 
+    /// Enable or disable generating one file per input `.proto` file.
+    ///
+    /// By default, a single file is generated per protobuf package. When
+    /// enabled, the code generated from each `.proto` file is written to its
+    /// own file, in a directory named after the package, and the package file
+    /// `include!`s them. The package file can still be included with
+    /// `tonic::include_proto!`. Splitting large packages can improve
+    /// incremental compile times and code navigation.
+    ///
+    /// This defaults to `false`.
+    pub fn split_by_file(mut self, enable: bool) -> Self {
+        self.split_by_file = enable;
+        self
+    }
+
+    /// Enable or disable deriving `serde::Serialize` and `serde::Deserialize`
+    /// on the generated messages and enums, following the naming of the
+    /// protobuf JSON mapping.
+    ///
+    /// Fields use their camel case names, missing fields take their default
+    /// values, oneofs are flattened into their messages and enumeration
+    /// values, including the ones of enumeration fields, are serialized as
+    /// their names. Enumeration fields whose type is an extern path, like the
+    /// well-known types of `prost-types`, are serialized as numbers. The other
+    /// rules of the mapping, like the string encoding of 64-bit integers and
+    /// of the well-known types, aren't applied.
+    ///
+    /// The generated code depends on `serde` and on the `serde` feature of
+    /// `tonic`.
+    ///
+    /// This defaults to `false`.
+    pub fn serde(mut self, enable: bool) -> Self {
+        self.serde = enable;
+        self
+    }
+
+    /// Redact the fields matching `path` when their messages are logged or
+    /// recorded, e.g. `.users.SignUpRequest.password`.
+    ///
+    /// Fields are matched by their fully qualified names, like with
+    /// [`Builder::field_attribute`], the fields of oneofs by the name of their
+    /// message. `tonic::RedactMessage` is implemented for the messages with
+    /// redacted fields, clearing these fields or the oneofs set to them, and
+    /// for the messages with fields of these messages, outside of maps and
+    /// oneofs, redacting them in turn. Messages whose type is an extern path
+    /// aren't redacted.
+    ///
+    /// The generated code depends on `tonic::RedactMessage`.
+    pub fn redact_field<P: AsRef<str>>(mut self, path: P) -> Self {
+        self.redact_fields.push(path.as_ref().to_string());
+        self
+    }
+
+    /// Implement `tonic::FieldMaskMessage` for the messages matching `path`,
+    /// e.g. `.library.Book` or `.library`, to apply `google.protobuf.FieldMask`
+    /// paths to them, e.g. the `read_mask` of AIP-157 partial responses, and
+    /// check the paths of masks, e.g. the `update_mask` of AIP-134 updates.
+    ///
+    /// Messages are matched by their fully qualified names, like with
+    /// [`Builder::message_attribute`]. The messages of the singular message
+    /// fields of matched messages, outside of oneofs, implement the trait in
+    /// turn, so that masks may select their fields, e.g. `author.name`. The
+    /// other fields, and the fields whose type is an extern path, are kept or
+    /// cleared as a whole.
+    ///
+    /// The generated code depends on `tonic::FieldMaskMessage` and on the
+    /// `codegen` feature of `tonic`.
+    pub fn field_mask_message<P: AsRef<str>>(mut self, path: P) -> Self {
+        self.field_mask_messages.push(path.as_ref().to_string());
+        self
+    }
+
+    /// Enable or disable embedding the file descriptors of the services in the
+    /// generated servers, for `tonic-reflection`.
+    ///
+    /// When enabled, each server module contains a `FILE_DESCRIPTOR_SET`
+    /// constant, with the encoded `prost_types::FileDescriptorSet` of the
+    /// file of the service and of the files it imports, and a
+    /// `register_reflection` function, which registers it with a
+    /// `tonic_reflection::server::Builder`:
+    ///
+    /// ```rust,ignore
+    /// let reflection = tonic_reflection::server::Builder::configure();
+    /// let reflection = greeter_server::register_reflection(reflection).build_v1()?;
+    /// ```
+    ///
+    /// The generated code depends on `tonic-reflection`.
+    ///
+    /// This defaults to `false`.
+    pub fn build_reflection(mut self, enable: bool) -> Self {
+        self.build_reflection = enable;
+        self
+    }
+
+    /// Enable or disable generation of the HTTP routes of the services.
+    ///
+    /// When enabled, the `{service}_methods` modules contain an `HTTP_ROUTES`
+    /// table of `tonic::HttpRoute`s, with the path templates and body
+    /// mappings declared by the `google.api.http` annotations of the
+    /// methods, so that a transcoding layer can be set up from them. The
+    /// `google/api/annotations.proto` file has to be found in the includes.
+    ///
+    /// The annotations are read from the output of `protoc`, so the routes
+    /// are only generated by [`Builder::compile_protos`], since the well
+    /// known types of `prost-types` drop the extensions of the descriptors
+    /// passed to [`Builder::compile_fds`].
+    ///
+    /// This defaults to `false`.
+    pub fn build_http_routes(mut self, enable: bool) -> Self {
+        self.build_http_routes = enable;
+        self
+    }
+
+    /// Configures the file name of the service registry of the generated
+    /// services, relative to the `OUT_DIR` or `out_dir()` as appropriate.
+    ///
+    /// If set, generates a file with a `ServiceId` enum listing the services
+    /// of all the compiled packages, along with a `Services` registry with a
+    /// method adding the implementation of each service, and an `add_all`
+    /// method adding the registered services to a `Server` at once. The
+    /// registry refers to the servers relative to the module it is included
+    /// in, so it has to be included next to the modules of the packages, as
+    /// declared by the [`Builder::include_file`].
+    ///
+    /// The registry needs the servers to be built, and the `router` feature of
+    /// `tonic`.
+    pub fn service_registry(mut self, path: impl AsRef<Path>) -> Self {
+        self.service_registry = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Enable or disable gating each service of the service registry by a
+    /// feature.
+    ///
+    /// When enabled, each service is only listed in the registry when the
+    /// feature named after the snake case of its fully qualified name, e.g.
+    /// `helloworld_greeter` for `helloworld.Greeter`, is enabled on the
+    /// crate including the registry, which has to declare the features.
+    ///
+    /// This defaults to `false`.
+    pub fn service_registry_features(mut self, enable: bool) -> Self {
+        self.service_registry_features = enable;
+        self
+    }
+
     /// Compile the .proto files and execute code generation.
+    ///
+    /// Files using the 2023 and 2024 protobuf editions are supported, with
+    /// scalar fields with explicit presence generated as `Option`s, as for
+    /// `proto3` optional fields.
     pub fn compile_protos(
         self,
         protos: &[impl AsRef<Path>],
@@ -613,7 +1083,7 @@ impl Builder {
     /// `prost_build::Config`. The provided config will be updated with this builder's config.
     pub fn compile_protos_with_config(
         self,
-        mut config: Config,
+        config: Config,
         protos: &[impl AsRef<Path>],
         includes: &[impl AsRef<Path>],
     ) -> io::Result<()> {
@@ -630,11 +1100,21 @@ impl Builder {
             }
         }
 
-        self.setup_config(&mut config);
-        config.compile_protos(protos, includes)
+        // The files are always loaded before generating code, since whether
+        // they use protobuf editions is only known from their descriptors.
+        let encoded_path = self.encoded_file_descriptor_set_path()?;
+        self.compile_loaded_fds(config, |config| {
+            config.file_descriptor_set_path(&encoded_path);
+            let fds = config.load_fds(protos, includes)?;
+            Ok((fds, Some(fs::read(encoded_path)?)))
+        })
     }
 
     /// Execute code generation from a file descriptor set.
+    ///
+    /// Files using protobuf editions are rejected, since the features they
+    /// declare are dropped from the descriptors of `prost-types`; they are
+    /// supported by [`Builder::compile_protos`].
     pub fn compile_fds(self, fds: prost_types::FileDescriptorSet) -> io::Result<()> {
         self.compile_fds_with_config(Config::new(), fds)
     }
@@ -645,11 +1125,237 @@ impl Builder {
         mut config: Config,
         fds: prost_types::FileDescriptorSet,
     ) -> io::Result<()> {
+        if self.needs_descriptors() || crate::editions::has_editions(&fds) {
+            return self.compile_loaded_fds(config, |_| Ok((fds, None)));
+        }
+
         self.setup_config(&mut config);
         config.compile_fds(fds)
     }
 
+    /// Execute code generation for a `protoc` plugin request, as received by
+    /// `protoc-gen-tonic` from `protoc` or `buf`.
+    ///
+    /// Code is only generated for the files listed in `file_to_generate`, one
+    /// output file per package, named as with [`Builder::compile_protos`].
+    /// Failures are reported through the `error` field of the response.
+    pub fn generate_plugin_response(self, request: CodeGeneratorRequest) -> CodeGeneratorResponse {
+        self.generate_plugin_response_with_config(Config::new(), request)
+    }
+
+    /// Execute code generation for a `protoc` plugin request using a custom
+    /// `prost_build::Config`.
+    pub fn generate_plugin_response_with_config(
+        mut self,
+        mut config: Config,
+        request: CodeGeneratorRequest,
+    ) -> CodeGeneratorResponse {
+        let mut response = CodeGeneratorResponse {
+            supported_features: Some(code_generator_response::Feature::Proto3Optional as u64),
+            ..Default::default()
+        };
+
+        let files = request
+            .proto_file
+            .iter()
+            .filter(|file| request.file_to_generate.contains(&file.name().to_string()))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let split_by_file = self.split_by_file;
+        let registry = self.registry_file(&files);
+        let message_impls = self.message_impls(&files);
+        self.setup_prost_config(&mut config);
+        if let Err(error) = self.setup_descriptor_config(&mut config, &request.proto_file, None) {
+            response.error = Some(error.to_string());
+            return response;
+        }
+        config.service_generator(self.service_generator());
+
+        match generate_files(&mut config, files, split_by_file, &message_impls) {
+            Ok(files) => {
+                response.file = files
+                    .into_iter()
+                    .map(|file| code_generator_response::File {
+                        name: Some(file.name),
+                        content: Some(file.content),
+                        ..Default::default()
+                    })
+                    .collect();
+                response.file.extend(registry.map(|(name, content)| {
+                    code_generator_response::File {
+                        name: Some(name),
+                        content: Some(content),
+                        ..Default::default()
+                    }
+                }));
+            }
+            Err(error) => response.error = Some(error.to_string()),
+        }
+
+        response
+    }
+
+    // Applies the options that need the descriptors of the compiled files,
+    // before the service generator is set up. `encoded` is the output of
+    // `protoc`, when available.
+    fn setup_descriptor_config(
+        &mut self,
+        config: &mut Config,
+        files: &[FileDescriptorProto],
+        encoded: Option<&[u8]>,
+    ) -> io::Result<()> {
+        if self.serde {
+            crate::serde::add_attributes(
+                config,
+                files,
+                &self.extern_path,
+                self.compile_well_known_types,
+            );
+        }
+
+        if self.build_reflection {
+            self.file_descriptor_sets = encode_service_file_descriptor_sets(files);
+        }
+
+        if self.build_http_routes {
+            if let Some(encoded) = encoded {
+                self.http_rules = crate::http::decode_http_rules(encoded)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // The path `protoc` writes the encoded file descriptor set to.
+    fn encoded_file_descriptor_set_path(&self) -> io::Result<PathBuf> {
+        if let Some(path) = &self.file_descriptor_set_path {
+            return Ok(path.clone());
+        }
+
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or_else(|| io::Error::other("OUT_DIR environment variable is not set"))?,
+        };
+        Ok(out_dir.join("tonic_build_file_descriptor_set.bin"))
+    }
+
+    // Generates code from a file descriptor set loaded after the configuration
+    // of `prost-build`, for the options that need the descriptors.
+    fn compile_loaded_fds(
+        mut self,
+        mut config: Config,
+        load_fds: impl FnOnce(
+            &mut Config,
+        ) -> io::Result<(prost_types::FileDescriptorSet, Option<Vec<u8>>)>,
+    ) -> io::Result<()> {
+        self.setup_prost_config(&mut config);
+        let (mut fds, encoded) = load_fds(&mut config)?;
+        crate::editions::lower_editions(&mut fds, encoded.as_deref())?;
+        self.setup_descriptor_config(&mut config, &fds.file, encoded.as_deref())?;
+
+        let split_by_file = self.split_by_file;
+        let out_dir = self.out_dir.clone();
+        let include_file = self.include_file.clone();
+        let registry = self.registry_file(&fds.file);
+        let message_impls = self.message_impls(&fds.file);
+        config.service_generator(self.service_generator());
+
+        // As in `prost-build`, the include file refers to the package files
+        // relatively only when the output directory is set explicitly.
+        let relative_includes = out_dir.is_some();
+        let out_dir = match out_dir {
+            Some(out_dir) => out_dir,
+            None => std::env::var_os("OUT_DIR")
+                .map(PathBuf::from)
+                .ok_or_else(|| io::Error::other("OUT_DIR environment variable is not set"))?,
+        };
+
+        if let Some((name, content)) = registry {
+            write_file_if_changed(&out_dir.join(name), &content)?;
+        }
+
+        if !split_by_file && message_impls.is_empty() {
+            return config.compile_fds(fds);
+        }
+
+        let files = generate_files(&mut config, fds.file, split_by_file, &message_impls)?;
+
+        for file in &files {
+            write_file_if_changed(&out_dir.join(&file.name), &file.content)?;
+        }
+
+        if let Some(include_file) = include_file {
+            let content = include_file_content(&files, relative_includes);
+            write_file_if_changed(&out_dir.join(include_file), &content)?;
+        }
+
+        Ok(())
+    }
+
+    // Whether code generation needs the descriptors of the compiled files.
+    fn needs_descriptors(&self) -> bool {
+        self.split_by_file
+            || self.serde
+            || !self.redact_fields.is_empty()
+            || !self.field_mask_messages.is_empty()
+            || self.build_reflection
+            || self.build_http_routes
+            || self.service_registry.is_some()
+    }
+
+    // The `tonic::RedactMessage` and `tonic::FieldMaskMessage`
+    // implementations of the messages of `files`, by package.
+    fn message_impls(&self, files: &[FileDescriptorProto]) -> HashMap<Module, String> {
+        let mut impls = crate::redact::generate(
+            files,
+            &self.redact_fields,
+            &self.extern_path,
+            self.compile_well_known_types,
+        );
+        let field_masks = crate::field_mask::generate(
+            files,
+            &self.field_mask_messages,
+            &self.extern_path,
+            self.compile_well_known_types,
+        );
+        for (module, code) in field_masks {
+            let content = impls.entry(module).or_default();
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            content.push_str(&code);
+        }
+        impls
+    }
+
+    // The name and content of the service registry of the services of
+    // `files`, when enabled.
+    fn registry_file(&self, files: &[FileDescriptorProto]) -> Option<(String, String)> {
+        let path = self
+            .service_registry
+            .as_ref()
+            .filter(|_| self.build_server)?;
+        let content = crate::registry::generate(
+            files,
+            self.emit_package,
+            self.service_registry_features,
+            self.build_transport,
+        );
+        Some((path.to_string_lossy().into_owned(), content))
+    }
+
     fn setup_config(self, config: &mut Config) {
+        self.setup_prost_config(config);
+        config.service_generator(self.service_generator());
+    }
+
+    // Applies the options of the builder to `prost-build`, apart from the
+    // service generator.
+    fn setup_prost_config(&self, config: &mut Config) {
         if let Some(out_dir) = self.out_dir.as_ref() {
             config.out_dir(out_dir);
         }
@@ -686,6 +1392,9 @@ impl Builder {
         if self.compile_well_known_types {
             config.compile_well_known_types();
         }
+        if let Some(path) = self.well_known_types_path.as_ref() {
+            config.prost_types_path(path);
+        }
         if let Some(path) = self.include_file.as_ref() {
             config.include_file(path);
         }
@@ -696,8 +1405,6 @@ impl Builder {
         for arg in self.protoc_args.iter() {
             config.protoc_arg(arg);
         }
-
-        config.service_generator(self.service_generator());
     }
 
     /// Turn the builder into a `ServiceGenerator` ready to be passed to `prost-build`s
@@ -706,3 +1413,205 @@ impl Builder {
         Box::new(ServiceGenerator::new(self))
     }
 }
+
+/// Encodes, for each service of `files`, the file descriptor set of its file
+/// and of the files it imports, keyed by the fully qualified service name.
+fn encode_service_file_descriptor_sets(files: &[FileDescriptorProto]) -> HashMap<String, Vec<u8>> {
+    fn add_file<'a>(
+        name: &str,
+        files: &HashMap<&str, &'a FileDescriptorProto>,
+        fds: &mut Vec<&'a FileDescriptorProto>,
+    ) {
+        let Some(&file) = files.get(name) else {
+            return;
+        };
+        if fds.iter().any(|added| added.name() == name) {
+            return;
+        }
+
+        // Dependencies come first, as in the output of `protoc`.
+        for dependency in &file.dependency {
+            add_file(dependency, files, fds);
+        }
+        fds.push(file);
+    }
+
+    let by_name = files
+        .iter()
+        .map(|file| (file.name(), file))
+        .collect::<HashMap<_, _>>();
+
+    let mut encoded = HashMap::new();
+
+    for file in files.iter().filter(|file| !file.service.is_empty()) {
+        let mut fds = Vec::new();
+        add_file(file.name(), &by_name, &mut fds);
+
+        let bytes = prost_types::FileDescriptorSet {
+            file: fds.into_iter().cloned().collect(),
+        }
+        .encode_to_vec();
+
+        for service in &file.service {
+            let name = if file.package().is_empty() {
+                service.name().to_string()
+            } else {
+                format!("{}.{}", file.package(), service.name())
+            };
+            encoded.insert(name, bytes.clone());
+        }
+    }
+
+    encoded
+}
+
+/// A generated Rust file, relative to the output directory.
+struct GeneratedFile {
+    /// The module of the package, for the files `tonic::include_proto!` expects.
+    package: Option<Module>,
+    name: String,
+    content: String,
+}
+
+/// Generates the code of `files`, either one file per package, or, when
+/// `split_by_file` is set, one file per `.proto` file along with a package
+/// file including them. The `message_impls` of each package are appended to
+/// its package file.
+fn generate_files(
+    config: &mut Config,
+    files: Vec<FileDescriptorProto>,
+    split_by_file: bool,
+    message_impls: &HashMap<Module, String>,
+) -> io::Result<Vec<GeneratedFile>> {
+    let mut generated = Vec::new();
+
+    if !split_by_file {
+        let requests = files
+            .into_iter()
+            .map(|file| (Module::from_protobuf_package_name(file.package()), file))
+            .collect();
+
+        for (module, content) in config.generate(requests)? {
+            generated.push(GeneratedFile {
+                name: module.to_file_name_or(DEFAULT_PACKAGE_FILENAME),
+                package: Some(module),
+                content,
+            });
+        }
+    } else {
+        // `prost-build` resolves type paths from the package of each file,
+        // the modules are only used to keep the code of each file apart.
+        let mut packages = HashMap::new();
+        let requests = files
+            .into_iter()
+            .map(|file| {
+                let package = Module::from_protobuf_package_name(file.package());
+                let package_dir = package
+                    .to_file_name_or(DEFAULT_PACKAGE_FILENAME)
+                    .trim_end_matches(".rs")
+                    .to_string();
+                let proto_name = file.name().trim_end_matches(".proto");
+                let name = format!("{package_dir}/{proto_name}.rs");
+                let module = Module::from_parts(package.parts().chain([name.as_str()]));
+
+                packages.insert(module.clone(), (package, name));
+                (module, file)
+            })
+            .collect();
+
+        let mut includes = BTreeMap::<Module, Vec<String>>::new();
+
+        for (module, content) in config.generate(requests)? {
+            let (package, name) = packages
+                .remove(&module)
+                .expect("module should be requested");
+            includes.entry(package).or_default().push(name.clone());
+            generated.push(GeneratedFile {
+                package: None,
+                name,
+                content,
+            });
+        }
+
+        for (package, mut names) in includes {
+            names.sort();
+
+            let mut content = String::from("// This file is @generated by tonic-build.\n");
+            for name in names {
+                content.push_str(&format!("include!(\"{name}\");\n"));
+            }
+
+            generated.push(GeneratedFile {
+                name: package.to_file_name_or(DEFAULT_PACKAGE_FILENAME),
+                package: Some(package),
+                content,
+            });
+        }
+    }
+
+    for file in &mut generated {
+        if let Some(impls) = file.package.as_ref().and_then(|p| message_impls.get(p)) {
+            file.content.push('\n');
+            file.content.push_str(impls);
+        }
+    }
+
+    generated.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(generated)
+}
+
+/// Generates an include file declaring the module of each package, as
+/// `prost-build` does.
+fn include_file_content(files: &[GeneratedFile], relative: bool) -> String {
+    let mut packages = files
+        .iter()
+        .filter_map(|file| Some((file.package.as_ref()?, &file.name)))
+        .collect::<Vec<_>>();
+    packages.sort();
+
+    let mut content = String::from("// This file is @generated by tonic-build.\n");
+    let mut stack = Vec::<&str>::new();
+
+    for (module, name) in packages {
+        let parts = module.parts().collect::<Vec<_>>();
+
+        while !parts.starts_with(&stack) {
+            stack.pop();
+            content.push_str(&format!("{}}}\n", "    ".repeat(stack.len())));
+        }
+        while stack.len() < parts.len() {
+            let part = parts[stack.len()];
+            content.push_str(&format!(
+                "{}pub mod {part} {{\n",
+                "    ".repeat(stack.len())
+            ));
+            stack.push(part);
+        }
+
+        let indent = "    ".repeat(stack.len());
+        if relative {
+            content.push_str(&format!("{indent}include!(\"{name}\");\n"));
+        } else {
+            content.push_str(&format!(
+                "{indent}include!(concat!(env!(\"OUT_DIR\"), \"/{name}\"));\n"
+            ));
+        }
+    }
+
+    for depth in (0..stack.len()).rev() {
+        content.push_str(&format!("{}}}\n", "    ".repeat(depth)));
+    }
+
+    content
+}
+
+fn write_file_if_changed(path: &Path, content: &str) -> io::Result<()> {
+    if fs::read(path).is_ok_and(|previous| previous == content.as_bytes()) {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)
+}
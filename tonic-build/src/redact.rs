@@ -0,0 +1,209 @@
+use std::collections::{HashMap, HashSet};
+
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::TokenStream;
+use prost_build::Module;
+use prost_types::{
+    field_descriptor_proto::Type, DescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+};
+use quote::quote;
+
+use crate::{
+    match_name,
+    serde::{resolve_ident, sanitize_identifier},
+};
+
+/// Generates the implementations of `tonic::RedactMessage` of the messages
+/// of `files`, by package.
+///
+/// Messages with fields matching `patterns` clear them, and messages with
+/// fields of such messages redact them in turn. Other messages, and messages
+/// whose type is an extern path, don't implement the trait.
+pub(crate) fn generate(
+    files: &[FileDescriptorProto],
+    patterns: &[String],
+    extern_paths: &[(String, String)],
+    compile_well_known_types: bool,
+) -> HashMap<Module, String> {
+    if patterns.is_empty() {
+        return HashMap::new();
+    }
+
+    let is_extern = |fq_name: &str| {
+        (!compile_well_known_types && match_name(".google.protobuf", fq_name))
+            || extern_paths
+                .iter()
+                .any(|(proto_path, _)| match_name(proto_path, fq_name))
+    };
+
+    let mut messages = Vec::new();
+    for file in files {
+        let package = file.package();
+        let fq_package = if package.is_empty() {
+            String::new()
+        } else {
+            format!(".{package}")
+        };
+        for message in &file.message_type {
+            collect_messages(package, &fq_package, message, &mut messages);
+        }
+    }
+    messages.retain(|message| !is_extern(&message.fq_name));
+
+    let is_redacted = |message: &Message<'_>, field: &str| {
+        let fq_field = format!("{}.{field}", message.fq_name);
+        patterns
+            .iter()
+            .any(|pattern| match_name(pattern, &fq_field))
+    };
+
+    // The messages implementing the trait, those with redacted fields first,
+    // then those holding them until none is added.
+    let mut redacted = messages
+        .iter()
+        .filter(|message| {
+            message
+                .descriptor
+                .field
+                .iter()
+                .any(|field| is_redacted(message, field.name()))
+        })
+        .map(|message| message.fq_name.clone())
+        .collect::<HashSet<_>>();
+    loop {
+        let holding = messages
+            .iter()
+            .filter(|message| !redacted.contains(&message.fq_name))
+            .filter(|message| {
+                message
+                    .descriptor
+                    .field
+                    .iter()
+                    .any(|field| holds_redacted(field, &redacted))
+            })
+            .map(|message| message.fq_name.clone())
+            .collect::<Vec<_>>();
+        if holding.is_empty() {
+            break;
+        }
+        redacted.extend(holding);
+    }
+
+    let mut impls = HashMap::<Module, Vec<TokenStream>>::new();
+    for message in &messages {
+        if !redacted.contains(&message.fq_name) {
+            continue;
+        }
+
+        let type_path = message
+            .package
+            .split('.')
+            .filter(|part| !part.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let path = parse_path(&resolve_ident(&type_path, &message.fq_name));
+
+        let mut statements = Vec::new();
+        for field in &message.descriptor.field {
+            let oneof = field
+                .oneof_index
+                .filter(|_| !field.proto3_optional())
+                .and_then(|index| message.descriptor.oneof_decl.get(index as usize));
+
+            if let Some(oneof) = oneof {
+                if !is_redacted(message, field.name()) {
+                    continue;
+                }
+                let name = parse_ident(oneof.name().to_snake_case());
+                let fq_oneof = format!("{}.{}", message.fq_name, oneof.name());
+                let oneof_path = parse_path(&resolve_ident(&type_path, &fq_oneof));
+                let variant = parse_ident(field.name().to_upper_camel_case());
+                statements.push(quote! {
+                    if matches!(self.#name, ::core::option::Option::Some(#oneof_path::#variant(_))) {
+                        self.#name = ::core::option::Option::None;
+                    }
+                });
+            } else {
+                let name = parse_ident(field.name().to_snake_case());
+                if is_redacted(message, field.name()) {
+                    statements.push(quote! {
+                        self.#name = ::core::default::Default::default();
+                    });
+                } else if holds_redacted(field, &redacted) {
+                    statements.push(quote! {
+                        tonic::RedactMessage::redact(&mut self.#name);
+                    });
+                }
+            }
+        }
+
+        impls
+            .entry(Module::from_protobuf_package_name(message.package))
+            .or_default()
+            .push(quote! {
+                impl tonic::RedactMessage for #path {
+                    fn redact(&mut self) {
+                        #(#statements)*
+                    }
+                }
+            });
+    }
+
+    impls
+        .into_iter()
+        .map(|(module, impls)| {
+            let ast: syn::File =
+                syn::parse2(quote! { #(#impls)* }).expect("not a valid tokenstream");
+            (module, prettyplease::unparse(&ast))
+        })
+        .collect()
+}
+
+pub(crate) struct Message<'a> {
+    pub(crate) package: &'a str,
+    pub(crate) fq_name: String,
+    pub(crate) descriptor: &'a DescriptorProto,
+}
+
+pub(crate) fn collect_messages<'a>(
+    package: &'a str,
+    fq_parent: &str,
+    descriptor: &'a DescriptorProto,
+    messages: &mut Vec<Message<'a>>,
+) {
+    if descriptor
+        .options
+        .as_ref()
+        .is_some_and(|options| options.map_entry())
+    {
+        return;
+    }
+
+    let fq_name = format!("{fq_parent}.{}", descriptor.name());
+    for nested in &descriptor.nested_type {
+        collect_messages(package, &fq_name, nested, messages);
+    }
+    messages.push(Message {
+        package,
+        fq_name,
+        descriptor,
+    });
+}
+
+// Whether `field` is a message field, outside of oneofs, whose type
+// implements the trait. Map fields are left out, since map entries don't
+// implement it.
+fn holds_redacted(field: &FieldDescriptorProto, redacted: &HashSet<String>) -> bool {
+    let in_oneof = field.oneof_index.is_some() && !field.proto3_optional();
+    !in_oneof
+        && matches!(field.r#type(), Type::Message | Type::Group)
+        && redacted.contains(field.type_name())
+}
+
+fn parse_path(path: &str) -> syn::Path {
+    syn::parse_str(path).expect("valid type path")
+}
+
+fn parse_ident(name: String) -> syn::Ident {
+    syn::parse_str(&sanitize_identifier(name)).expect("valid identifier")
+}
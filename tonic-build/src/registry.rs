@@ -0,0 +1,162 @@
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use proc_macro2::TokenStream;
+use prost_build::Module;
+use prost_types::FileDescriptorProto;
+use quote::{format_ident, quote};
+
+use crate::naive_snake_case;
+
+/// Generates the service registry of the services of `files`: the
+/// `ServiceId` enum listing the services, and the `Services` registry adding
+/// implementations of the services to a server at once.
+///
+/// The paths of the servers are relative to the module the registry is
+/// included in, next to the modules of the packages. When `features` is set,
+/// each service is gated by the feature named after the snake case of its
+/// fully qualified name.
+pub(crate) fn generate(
+    files: &[FileDescriptorProto],
+    emit_package: bool,
+    features: bool,
+    build_transport: bool,
+) -> String {
+    let mut variants = TokenStream::new();
+    let mut all = TokenStream::new();
+    let mut names = TokenStream::new();
+    let mut setters = TokenStream::new();
+
+    for file in files {
+        let package = file.package();
+        let module = Module::from_protobuf_package_name(package);
+
+        for service in &file.service {
+            let full_name = if package.is_empty() {
+                service.name().to_string()
+            } else {
+                format!("{package}.{}", service.name())
+            };
+            let service_name = if emit_package {
+                full_name.clone()
+            } else {
+                service.name().to_string()
+            };
+
+            let rust_name = service.name().to_upper_camel_case();
+            let server_mod = format!("{}_server", naive_snake_case(&rust_name));
+            let server_path = module
+                .parts()
+                .chain([server_mod.as_str()])
+                .collect::<Vec<_>>()
+                .join("::");
+            let server_path = syn::parse_str::<syn::Path>(&format!("self::{server_path}"))
+                .expect("invalid server path");
+            let trait_name = format_ident!("{}", rust_name);
+            let server = format_ident!("{}Server", rust_name);
+
+            let snake_name = full_name.replace('.', "_").to_snake_case();
+            let variant = format_ident!("{}", snake_name.to_upper_camel_case());
+            let setter = format_ident!("{}", snake_name);
+
+            let cfg = if features {
+                quote!(#[cfg(feature = #snake_name)])
+            } else {
+                TokenStream::new()
+            };
+
+            let variant_doc = format!(" The `{full_name}` service.");
+            let setter_doc = format!(" Registers the implementation of the `{full_name}` service.");
+
+            variants.extend(quote! {
+                #[doc = #variant_doc]
+                #cfg
+                #variant,
+            });
+            all.extend(quote! {
+                #cfg
+                ServiceId::#variant,
+            });
+            names.extend(quote! {
+                #cfg
+                ServiceId::#variant => #service_name,
+            });
+            setters.extend(quote! {
+                #[doc = #setter_doc]
+                #cfg
+                pub fn #setter<T: #server_path::#trait_name>(mut self, service: T) -> Self {
+                    self.routes.add_service(#server_path::#server::new(service));
+                    self.registered.push(ServiceId::#variant);
+                    self
+                }
+            });
+        }
+    }
+
+    let add_all = if build_transport {
+        quote! {
+            /// Adds the registered services to `server`, returning the router
+            /// serving them.
+            pub fn add_all<L: Clone>(
+                self,
+                server: &mut tonic::transport::Server<L>,
+            ) -> tonic::transport::server::Router<L> {
+                server.add_routes(self.into_routes())
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let registry = quote! {
+        /// The services of the registry.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum ServiceId {
+            #variants
+        }
+
+        impl ServiceId {
+            /// All the services, in declaration order.
+            pub const ALL: &'static [ServiceId] = &[#all];
+
+            /// The fully qualified name of the service.
+            pub const fn name(self) -> &'static str {
+                match self {
+                    #names
+                }
+            }
+        }
+
+        /// A registry of implementations of the services, added to a server
+        /// at once.
+        #[derive(Debug, Clone, Default)]
+        pub struct Services {
+            routes: tonic::service::RoutesBuilder,
+            registered: Vec<ServiceId>,
+        }
+
+        impl Services {
+            /// Creates an empty registry.
+            pub fn new() -> Self {
+                Self::default()
+            }
+
+            #setters
+
+            /// The registered services, in registration order.
+            pub fn registered(&self) -> &[ServiceId] {
+                &self.registered
+            }
+
+            /// Turns the registry into the routes of the registered services.
+            pub fn into_routes(self) -> tonic::service::Routes {
+                self.routes.routes()
+            }
+
+            #add_all
+        }
+    };
+
+    let ast: syn::File = syn::parse2(registry).expect("not a valid tokenstream");
+    let mut content = String::from("// This file is @generated by tonic-build.\n");
+    content.push_str(&prettyplease::unparse(&ast));
+    content
+}
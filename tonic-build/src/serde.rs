@@ -0,0 +1,216 @@
+use heck::{ToSnakeCase, ToUpperCamelCase};
+use prost_build::Config;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+};
+
+use crate::match_name;
+
+const DERIVE: &str = "#[derive(::serde::Serialize, ::serde::Deserialize)]";
+
+/// Adds the attributes deriving `serde` traits with the protobuf JSON names
+/// to the messages and enums of `files`.
+///
+/// Messages use the camel case names of their fields and the defaults of
+/// missing fields, oneofs are flattened into their messages, and enumeration
+/// values, including the ones of enumeration fields, use their names.
+pub(crate) fn add_attributes(
+    config: &mut Config,
+    files: &[FileDescriptorProto],
+    extern_paths: &[(String, String)],
+    compile_well_known_types: bool,
+) {
+    config.message_attribute(".", DERIVE);
+    config.message_attribute(".", "#[serde(rename_all = \"camelCase\", default)]");
+    // Matches both the enums and the oneofs.
+    config.enum_attribute(".", DERIVE);
+
+    let is_extern = |fq_name: &str| {
+        (!compile_well_known_types && match_name(".google.protobuf", fq_name))
+            || extern_paths
+                .iter()
+                .any(|(proto_path, _)| match_name(proto_path, fq_name))
+    };
+
+    for file in files {
+        let package = file.package();
+        let fq_package = if package.is_empty() {
+            String::new()
+        } else {
+            format!(".{package}")
+        };
+        let type_path = package
+            .split('.')
+            .filter(|part| !part.is_empty())
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        let mut generator = Generator {
+            config: &mut *config,
+            is_extern: &is_extern,
+            proto2: file.syntax() != "proto3",
+        };
+
+        for enum_type in &file.enum_type {
+            generator.config_enum(&fq_package, enum_type);
+        }
+
+        for message in &file.message_type {
+            generator.config_message(&fq_package, &type_path, message);
+        }
+    }
+}
+
+struct Generator<'a> {
+    config: &'a mut Config,
+    is_extern: &'a dyn Fn(&str) -> bool,
+    proto2: bool,
+}
+
+impl Generator<'_> {
+    fn config_enum(&mut self, fq_parent: &str, enum_type: &EnumDescriptorProto) {
+        let fq_name = format!("{fq_parent}.{}", enum_type.name());
+
+        for value in &enum_type.value {
+            // Values are matched as the fields of their enum.
+            let attribute = format!("#[serde(rename = \"{}\")]", value.name());
+            self.config_field_attribute(&fq_name, value.name(), &attribute);
+        }
+    }
+
+    fn config_message(&mut self, fq_parent: &str, type_path: &[String], message: &DescriptorProto) {
+        if message
+            .options
+            .as_ref()
+            .is_some_and(|options| options.map_entry())
+        {
+            return;
+        }
+
+        let fq_name = format!("{fq_parent}.{}", message.name());
+        // The types nested in the message, among which its oneofs, are
+        // generated in a module named after it.
+        let mut nested_type_path = type_path.to_vec();
+        nested_type_path.push(message.name().to_string());
+
+        for field in &message.field {
+            let oneof = field
+                .oneof_index
+                .filter(|_| !field.proto3_optional())
+                .and_then(|index| message.oneof_decl.get(index as usize));
+
+            match oneof {
+                Some(oneof) => {
+                    let fq_oneof = format!("{fq_name}.{}", oneof.name());
+                    self.config_enum_field(&fq_oneof, &nested_type_path, field, "enumeration");
+                }
+                None => {
+                    let helper = if field.label() == Label::Repeated {
+                        "enumeration_repeated"
+                    } else if field.label() == Label::Optional
+                        && (self.proto2 || field.proto3_optional())
+                    {
+                        "enumeration_option"
+                    } else {
+                        "enumeration"
+                    };
+                    self.config_enum_field(&fq_name, type_path, field, helper);
+                }
+            }
+        }
+
+        for (index, oneof) in message.oneof_decl.iter().enumerate() {
+            let synthetic = message
+                .field
+                .iter()
+                .any(|field| field.oneof_index == Some(index as i32) && field.proto3_optional());
+            if synthetic {
+                continue;
+            }
+
+            // The field holding the oneof is matched by suffix, since a
+            // fully qualified path would also match the fields of the oneof.
+            self.config.field_attribute(
+                format!("{}.{}", &fq_name[1..], oneof.name()),
+                "#[serde(flatten)]",
+            );
+            self.config.enum_attribute(
+                format!("{fq_name}.{}", oneof.name()),
+                "#[serde(rename_all = \"camelCase\")]",
+            );
+        }
+
+        for enum_type in &message.enum_type {
+            self.config_enum(&fq_name, enum_type);
+        }
+
+        for nested in &message.nested_type {
+            self.config_message(&fq_name, &nested_type_path, nested);
+        }
+    }
+
+    fn config_enum_field(
+        &mut self,
+        fq_parent: &str,
+        type_path: &[String],
+        field: &FieldDescriptorProto,
+        helper: &str,
+    ) {
+        if field.r#type() != Type::Enum || (self.is_extern)(field.type_name()) {
+            return;
+        }
+
+        let enum_path = resolve_ident(type_path, field.type_name());
+        let attribute = format!(
+            "#[serde(serialize_with = \"tonic::codegen::serde::{helper}::serialize::<{enum_path}, _>\", \
+             deserialize_with = \"tonic::codegen::serde::{helper}::deserialize::<{enum_path}, _>\")]"
+        );
+        self.config_field_attribute(fq_parent, field.name(), &attribute);
+    }
+
+    fn config_field_attribute(&mut self, fq_parent: &str, name: &str, attribute: &str) {
+        self.config
+            .field_attribute(format!("{fq_parent}.{name}"), attribute);
+    }
+}
+
+/// Resolves the Rust path of the type `fq_name` from the module of
+/// `type_path`, the package and message names in which the path is used, as
+/// `prost-build` does.
+pub(crate) fn resolve_ident(type_path: &[String], fq_name: &str) -> String {
+    let mut local_path = type_path.iter().map(String::as_str).peekable();
+
+    let mut ident_path = fq_name[1..].split('.');
+    let ident_type = ident_path.next_back().unwrap_or_default();
+    let mut ident_path = ident_path.peekable();
+
+    while local_path.peek().is_some() && local_path.peek() == ident_path.peek() {
+        local_path.next();
+        ident_path.next();
+    }
+
+    local_path
+        .map(|_| "super".to_string())
+        .chain(ident_path.map(|part| sanitize_identifier(part.to_snake_case())))
+        .chain(std::iter::once(sanitize_identifier(
+            ident_type.to_upper_camel_case(),
+        )))
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+// Mirrors the identifier sanitization of `prost-build`.
+pub(crate) fn sanitize_identifier(ident: String) -> String {
+    match ident.as_str() {
+        "as" | "break" | "const" | "continue" | "else" | "enum" | "false" | "fn" | "for" | "if"
+        | "impl" | "in" | "let" | "loop" | "match" | "mod" | "move" | "mut" | "pub" | "ref"
+        | "return" | "static" | "struct" | "trait" | "true" | "type" | "unsafe" | "use"
+        | "where" | "while" | "dyn" | "abstract" | "become" | "box" | "do" | "final" | "macro"
+        | "override" | "priv" | "typeof" | "unsized" | "virtual" | "yield" | "async" | "await"
+        | "try" | "gen" => format!("r#{ident}"),
+        "_" | "super" | "self" | "Self" | "extern" | "crate" => format!("{ident}_"),
+        s if s.starts_with(|c: char| c.is_numeric()) => format!("_{ident}"),
+        _ => ident,
+    }
+}
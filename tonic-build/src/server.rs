@@ -2,10 +2,11 @@ use std::collections::HashSet;
 
 use super::{Attributes, Method, Service};
 use crate::{
-    format_method_name, format_method_path, format_service_name, generate_doc_comment,
-    generate_doc_comments, naive_snake_case,
+    format_method_name, format_method_path, format_service_name, generate_deprecated,
+    generate_doc_comment, generate_doc_comments, generate_idempotency_level,
+    generate_service_deprecated, match_name, naive_snake_case,
 };
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Literal, Span, TokenStream};
 use quote::quote;
 use syn::{Ident, Lit, LitStr};
 
@@ -18,7 +19,12 @@ pub(crate) fn generate_internal<T: Service>(
     attributes: &Attributes,
     disable_comments: &HashSet<String>,
     use_arc_self: bool,
+    use_local_futures: bool,
     generate_default_stubs: bool,
+    file_descriptor_set: Option<&[u8]>,
+    handler_extractors: &[(String, String)],
+    request_validators: &[(String, String)],
+    intercepted_messages: &[String],
 ) -> TokenStream {
     let methods = generate_methods(
         service,
@@ -27,6 +33,9 @@ pub(crate) fn generate_internal<T: Service>(
         compile_well_known_types,
         use_arc_self,
         generate_default_stubs,
+        handler_extractors,
+        request_validators,
+        intercepted_messages,
     );
 
     let server_service = quote::format_ident!("{}Server", service.name());
@@ -38,9 +47,12 @@ pub(crate) fn generate_internal<T: Service>(
         proto_path,
         compile_well_known_types,
         server_trait.clone(),
+        attributes,
         disable_comments,
         use_arc_self,
+        use_local_futures,
         generate_default_stubs,
+        handler_extractors,
     );
     let package = if emit_package { service.package() } else { "" };
     // Transport based implementations
@@ -55,6 +67,7 @@ pub(crate) fn generate_internal<T: Service>(
     let named = generate_named(&server_service, &service_name);
     let mod_attributes = attributes.for_mod(package);
     let struct_attributes = attributes.for_struct(&service_name);
+    let (deprecated, allow_deprecated) = generate_service_deprecated(service);
 
     let configure_compression_methods = quote! {
         /// Enable decompressing requests with the given encoding.
@@ -70,6 +83,14 @@ pub(crate) fn generate_internal<T: Service>(
             self.send_compression_encodings.enable(encoding);
             self
         }
+
+        /// Decide whether and how responses are compressed with the given policy, in place of the
+        /// encodings enabled with `send_compressed`.
+        #[must_use]
+        pub fn send_compression_policy(mut self, policy: impl CompressionPolicy) -> Self {
+            self.send_compression_policy = Some(Arc::new(policy));
+            self
+        }
     };
 
     let configure_max_message_size_methods = quote! {
@@ -92,6 +113,29 @@ pub(crate) fn generate_internal<T: Service>(
         }
     };
 
+    // Shadows the `Send` future of `tonic::codegen` in the server module.
+    let local_futures = use_local_futures.then(|| {
+        quote! {
+            type BoxFuture<T, E> = LocalBoxFuture<T, E>;
+        }
+    });
+
+    let reflection = file_descriptor_set.map(|file_descriptor_set| {
+        let file_descriptor_set = Literal::byte_string(file_descriptor_set);
+
+        quote! {
+            /// The encoded `FileDescriptorSet` of the service, including the files it imports.
+            pub const FILE_DESCRIPTOR_SET: &[u8] = #file_descriptor_set;
+
+            /// Registers [`FILE_DESCRIPTOR_SET`] with a `tonic-reflection` server builder.
+            pub fn register_reflection(
+                builder: tonic_reflection::server::Builder<'_>,
+            ) -> tonic_reflection::server::Builder<'_> {
+                builder.register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+            }
+        }
+    });
+
     quote! {
         /// Generated server implementations.
         #(#mod_attributes)*
@@ -103,18 +147,23 @@ pub(crate) fn generate_internal<T: Service>(
                 clippy::wildcard_imports,
                 // will trigger if compression is disabled
                 clippy::let_unit_value,
+                #allow_deprecated
             )]
             use tonic::codegen::*;
 
+            #local_futures
+
             #generated_trait
 
             #service_doc
+            #deprecated
             #(#struct_attributes)*
             #[derive(Debug)]
             pub struct #server_service<T> {
                 inner: Arc<T>,
                 accept_compression_encodings: EnabledCompressionEncodings,
                 send_compression_encodings: EnabledCompressionEncodings,
+                send_compression_policy: Option<Arc<dyn CompressionPolicy>>,
                 max_decoding_message_size: Option<usize>,
                 max_encoding_message_size: Option<usize>,
             }
@@ -129,6 +178,7 @@ pub(crate) fn generate_internal<T: Service>(
                         inner,
                         accept_compression_encodings: Default::default(),
                         send_compression_encodings: Default::default(),
+                        send_compression_policy: None,
                         max_decoding_message_size: None,
                         max_encoding_message_size: None,
                     }
@@ -141,6 +191,13 @@ pub(crate) fn generate_internal<T: Service>(
                     InterceptedService::new(Self::new(inner), interceptor)
                 }
 
+                pub fn with_state<S>(inner: T, state: Arc<S>) -> InterceptedService<Self, tonic::server::State<S>>
+                where
+                    S: std::marker::Send + std::marker::Sync + 'static,
+                {
+                    Self::with_interceptor(inner, tonic::server::State(state))
+                }
+
                 #configure_compression_methods
 
                 #configure_max_message_size_methods
@@ -182,6 +239,7 @@ pub(crate) fn generate_internal<T: Service>(
                         inner,
                         accept_compression_encodings: self.accept_compression_encodings,
                         send_compression_encodings: self.send_compression_encodings,
+                        send_compression_policy: self.send_compression_policy.clone(),
                         max_decoding_message_size: self.max_decoding_message_size,
                         max_encoding_message_size: self.max_encoding_message_size,
                     }
@@ -189,6 +247,8 @@ pub(crate) fn generate_internal<T: Service>(
             }
 
             #named
+
+            #reflection
         }
     }
 }
@@ -200,41 +260,62 @@ fn generate_trait<T: Service>(
     proto_path: &str,
     compile_well_known_types: bool,
     server_trait: Ident,
+    attributes: &Attributes,
     disable_comments: &HashSet<String>,
     use_arc_self: bool,
+    use_local_futures: bool,
     generate_default_stubs: bool,
+    handler_extractors: &[(String, String)],
 ) -> TokenStream {
     let methods = generate_trait_methods(
         service,
         emit_package,
         proto_path,
         compile_well_known_types,
+        attributes,
         disable_comments,
         use_arc_self,
         generate_default_stubs,
+        handler_extractors,
     );
     let trait_doc = generate_doc_comment(format!(
         " Generated trait containing gRPC methods that should be implemented for use with {}Server.",
         service.name()
     ));
-
-    quote! {
-        #trait_doc
-        #[async_trait]
-        pub trait #server_trait : std::marker::Send + std::marker::Sync + 'static {
-            #methods
+    let (deprecated, _) = generate_service_deprecated(service);
+
+    if use_local_futures {
+        quote! {
+            #trait_doc
+            #deprecated
+            #[async_trait(?Send)]
+            pub trait #server_trait : 'static {
+                #methods
+            }
+        }
+    } else {
+        quote! {
+            #trait_doc
+            #deprecated
+            #[async_trait]
+            pub trait #server_trait : std::marker::Send + std::marker::Sync + 'static {
+                #methods
+            }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_trait_methods<T: Service>(
     service: &T,
     emit_package: bool,
     proto_path: &str,
     compile_well_known_types: bool,
+    attributes: &Attributes,
     disable_comments: &HashSet<String>,
     use_arc_self: bool,
     generate_default_stubs: bool,
+    handler_extractors: &[(String, String)],
 ) -> TokenStream {
     let mut stream = TokenStream::new();
 
@@ -244,12 +325,20 @@ fn generate_trait_methods<T: Service>(
         let (req_message, res_message) =
             method.request_response_name(proto_path, compile_well_known_types);
 
-        let method_doc =
-            if disable_comments.contains(&format_method_name(service, method, emit_package)) {
-                TokenStream::new()
-            } else {
-                generate_doc_comments(method.comment())
-            };
+        let method_name = format_method_name(service, method, emit_package);
+        let mut method_doc = if disable_comments.contains(&method_name) {
+            TokenStream::new()
+        } else {
+            generate_doc_comments(method.comment())
+        };
+        if method.deprecated() {
+            method_doc.extend(generate_deprecated());
+        }
+        let method_attributes = attributes.for_method(&method_name);
+        method_doc.extend(quote! { #(#method_attributes)* });
+        let service_name = format_service_name(service, emit_package);
+        let extractors =
+            Extractors::for_method(&service_name, &method_name, handler_extractors).params();
 
         let self_param = if use_arc_self {
             quote!(self: std::sync::Arc<Self>)
@@ -265,7 +354,7 @@ fn generate_trait_methods<T: Service>(
             (false, false, true) => {
                 quote! {
                     #method_doc
-                    async fn #name(#self_param, request: tonic::Request<#req_message>)
+                    async fn #name(#self_param, #extractors request: tonic::Request<#req_message>)
                         -> std::result::Result<tonic::Response<#res_message>, tonic::Status> {
                         Err(tonic::Status::unimplemented("Not yet implemented"))
                     }
@@ -274,14 +363,14 @@ fn generate_trait_methods<T: Service>(
             (false, false, false) => {
                 quote! {
                     #method_doc
-                    async fn #name(#self_param, request: tonic::Request<#req_message>)
+                    async fn #name(#self_param, #extractors request: tonic::Request<#req_message>)
                         -> std::result::Result<tonic::Response<#res_message>, tonic::Status>;
                 }
             }
             (true, false, true) => {
                 quote! {
                     #method_doc
-                    async fn #name(#self_param, request: tonic::Request<tonic::Streaming<#req_message>>)
+                    async fn #name(#self_param, #extractors request: tonic::Request<tonic::Streaming<#req_message>>)
                         -> std::result::Result<tonic::Response<#res_message>, tonic::Status> {
                         Err(tonic::Status::unimplemented("Not yet implemented"))
                     }
@@ -290,14 +379,14 @@ fn generate_trait_methods<T: Service>(
             (true, false, false) => {
                 quote! {
                     #method_doc
-                    async fn #name(#self_param, request: tonic::Request<tonic::Streaming<#req_message>>)
+                    async fn #name(#self_param, #extractors request: tonic::Request<tonic::Streaming<#req_message>>)
                         -> std::result::Result<tonic::Response<#res_message>, tonic::Status>;
                 }
             }
             (false, true, true) => {
                 quote! {
                     #method_doc
-                    async fn #name(#self_param, request: tonic::Request<#req_message>)
+                    async fn #name(#self_param, #extractors request: tonic::Request<#req_message>)
                         -> std::result::Result<tonic::Response<BoxStream<#res_message>>, tonic::Status> {
                         Err(tonic::Status::unimplemented("Not yet implemented"))
                     }
@@ -315,14 +404,14 @@ fn generate_trait_methods<T: Service>(
                     type #stream: tonic::codegen::tokio_stream::Stream<Item = std::result::Result<#res_message, tonic::Status>> + std::marker::Send + 'static;
 
                     #method_doc
-                    async fn #name(#self_param, request: tonic::Request<#req_message>)
+                    async fn #name(#self_param, #extractors request: tonic::Request<#req_message>)
                         -> std::result::Result<tonic::Response<Self::#stream>, tonic::Status>;
                 }
             }
             (true, true, true) => {
                 quote! {
                     #method_doc
-                    async fn #name(#self_param, request: tonic::Request<tonic::Streaming<#req_message>>)
+                    async fn #name(#self_param, #extractors request: tonic::Request<tonic::Streaming<#req_message>>)
                         -> std::result::Result<tonic::Response<BoxStream<#res_message>>, tonic::Status> {
                         Err(tonic::Status::unimplemented("Not yet implemented"))
                     }
@@ -340,7 +429,7 @@ fn generate_trait_methods<T: Service>(
                     type #stream: tonic::codegen::tokio_stream::Stream<Item = std::result::Result<#res_message, tonic::Status>> + std::marker::Send + 'static;
 
                     #method_doc
-                    async fn #name(#self_param, request: tonic::Request<tonic::Streaming<#req_message>>)
+                    async fn #name(#self_param, #extractors request: tonic::Request<tonic::Streaming<#req_message>>)
                         -> std::result::Result<tonic::Response<Self::#stream>, tonic::Status>;
                 }
             }
@@ -366,6 +455,7 @@ fn generate_named(server_service: &syn::Ident, service_name: &str) -> TokenStrea
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_methods<T: Service>(
     service: &T,
     emit_package: bool,
@@ -373,11 +463,24 @@ fn generate_methods<T: Service>(
     compile_well_known_types: bool,
     use_arc_self: bool,
     generate_default_stubs: bool,
+    handler_extractors: &[(String, String)],
+    request_validators: &[(String, String)],
+    intercepted_messages: &[String],
 ) -> TokenStream {
     let mut stream = TokenStream::new();
 
     for method in service.methods() {
         let path = format_method_path(service, method, emit_package);
+        let method_name = format_method_name(service, method, emit_package);
+        let service_name = format_service_name(service, emit_package);
+        let extractors = Extractors::for_method(&service_name, &method_name, handler_extractors);
+        let mut decoded = validate_request(&service_name, &method_name, request_validators);
+        decoded.extend(intercept_message(
+            service,
+            method,
+            emit_package,
+            intercepted_messages,
+        ));
         let method_path = Lit::Str(LitStr::new(&path, Span::call_site()));
         let ident = quote::format_ident!("{}", method.name());
         let server_trait = quote::format_ident!("{}", service.name());
@@ -390,6 +493,8 @@ fn generate_methods<T: Service>(
                 ident,
                 server_trait,
                 use_arc_self,
+                &extractors,
+                &decoded,
             ),
 
             (false, true) => generate_server_streaming(
@@ -399,6 +504,8 @@ fn generate_methods<T: Service>(
                 ident.clone(),
                 server_trait,
                 use_arc_self,
+                &extractors,
+                &decoded,
                 generate_default_stubs,
             ),
             (true, false) => generate_client_streaming(
@@ -408,6 +515,7 @@ fn generate_methods<T: Service>(
                 ident.clone(),
                 server_trait,
                 use_arc_self,
+                &extractors,
             ),
 
             (true, true) => generate_streaming(
@@ -417,11 +525,16 @@ fn generate_methods<T: Service>(
                 ident.clone(),
                 server_trait,
                 use_arc_self,
+                &extractors,
                 generate_default_stubs,
             ),
         };
 
+        // The handlers of deprecated methods are still called.
+        let allow_deprecated = method.deprecated().then(|| quote!(#[allow(deprecated)]));
+
         let method = quote! {
+            #allow_deprecated
             #method_path => {
                 #method_stream
             }
@@ -432,6 +545,7 @@ fn generate_methods<T: Service>(
     stream
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_unary<T: Method>(
     method: &T,
     proto_path: &str,
@@ -439,6 +553,8 @@ fn generate_unary<T: Method>(
     method_ident: Ident,
     server_trait: Ident,
     use_arc_self: bool,
+    extractors: &Extractors,
+    decoded: &TokenStream,
 ) -> TokenStream {
     let codec_name = syn::parse_str::<syn::Path>(method.codec_path()).unwrap();
 
@@ -451,6 +567,8 @@ fn generate_unary<T: Method>(
     } else {
         quote!(&inner)
     };
+    let extract = extractors.extract();
+    let args = extractors.args();
 
     quote! {
         #[allow(non_camel_case_types)]
@@ -463,7 +581,9 @@ fn generate_unary<T: Method>(
             fn call(&mut self, request: tonic::Request<#request>) -> Self::Future {
                 let inner = Arc::clone(&self.0);
                 let fut = async move {
-                    <T as #server_trait>::#method_ident(#inner_arg, request).await
+                    #decoded
+                    #extract
+                    <T as #server_trait>::#method_ident(#inner_arg, #args request).await
                 };
                 Box::pin(fut)
             }
@@ -471,6 +591,7 @@ fn generate_unary<T: Method>(
 
         let accept_compression_encodings = self.accept_compression_encodings;
         let send_compression_encodings = self.send_compression_encodings;
+        let send_compression_policy = self.send_compression_policy.clone();
         let max_decoding_message_size = self.max_decoding_message_size;
         let max_encoding_message_size = self.max_encoding_message_size;
         let inner = self.inner.clone();
@@ -480,6 +601,7 @@ fn generate_unary<T: Method>(
 
             let mut grpc = tonic::server::Grpc::new(codec)
                 .apply_compression_config(accept_compression_encodings, send_compression_encodings)
+                .apply_compression_policy(send_compression_policy)
                 .apply_max_message_size_config(max_decoding_message_size, max_encoding_message_size);
 
             let res = grpc.unary(method, req).await;
@@ -490,6 +612,7 @@ fn generate_unary<T: Method>(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_server_streaming<T: Method>(
     method: &T,
     proto_path: &str,
@@ -497,6 +620,8 @@ fn generate_server_streaming<T: Method>(
     method_ident: Ident,
     server_trait: Ident,
     use_arc_self: bool,
+    extractors: &Extractors,
+    decoded: &TokenStream,
     generate_default_stubs: bool,
 ) -> TokenStream {
     let codec_name = syn::parse_str::<syn::Path>(method.codec_path()).unwrap();
@@ -517,6 +642,8 @@ fn generate_server_streaming<T: Method>(
     } else {
         quote!(&inner)
     };
+    let extract = extractors.extract();
+    let args = extractors.args();
 
     quote! {
         #[allow(non_camel_case_types)]
@@ -530,7 +657,9 @@ fn generate_server_streaming<T: Method>(
             fn call(&mut self, request: tonic::Request<#request>) -> Self::Future {
                 let inner = Arc::clone(&self.0);
                 let fut = async move {
-                    <T as #server_trait>::#method_ident(#inner_arg, request).await
+                    #decoded
+                    #extract
+                    <T as #server_trait>::#method_ident(#inner_arg, #args request).await
                 };
                 Box::pin(fut)
             }
@@ -538,6 +667,7 @@ fn generate_server_streaming<T: Method>(
 
         let accept_compression_encodings = self.accept_compression_encodings;
         let send_compression_encodings = self.send_compression_encodings;
+        let send_compression_policy = self.send_compression_policy.clone();
         let max_decoding_message_size = self.max_decoding_message_size;
         let max_encoding_message_size = self.max_encoding_message_size;
         let inner = self.inner.clone();
@@ -547,6 +677,7 @@ fn generate_server_streaming<T: Method>(
 
             let mut grpc = tonic::server::Grpc::new(codec)
                 .apply_compression_config(accept_compression_encodings, send_compression_encodings)
+                .apply_compression_policy(send_compression_policy)
                 .apply_max_message_size_config(max_decoding_message_size, max_encoding_message_size);
 
             let res = grpc.server_streaming(method, req).await;
@@ -564,6 +695,7 @@ fn generate_client_streaming<T: Method>(
     method_ident: Ident,
     server_trait: Ident,
     use_arc_self: bool,
+    extractors: &Extractors,
 ) -> TokenStream {
     let service_ident = quote::format_ident!("{}Svc", method.identifier());
 
@@ -575,6 +707,8 @@ fn generate_client_streaming<T: Method>(
     } else {
         quote!(&inner)
     };
+    let extract = extractors.extract();
+    let args = extractors.args();
 
     quote! {
         #[allow(non_camel_case_types)]
@@ -588,7 +722,8 @@ fn generate_client_streaming<T: Method>(
             fn call(&mut self, request: tonic::Request<tonic::Streaming<#request>>) -> Self::Future {
                 let inner = Arc::clone(&self.0);
                 let fut = async move {
-                    <T as #server_trait>::#method_ident(#inner_arg, request).await
+                    #extract
+                    <T as #server_trait>::#method_ident(#inner_arg, #args request).await
                 };
                 Box::pin(fut)
             }
@@ -596,6 +731,7 @@ fn generate_client_streaming<T: Method>(
 
         let accept_compression_encodings = self.accept_compression_encodings;
         let send_compression_encodings = self.send_compression_encodings;
+        let send_compression_policy = self.send_compression_policy.clone();
         let max_decoding_message_size = self.max_decoding_message_size;
         let max_encoding_message_size = self.max_encoding_message_size;
         let inner = self.inner.clone();
@@ -605,6 +741,7 @@ fn generate_client_streaming<T: Method>(
 
             let mut grpc = tonic::server::Grpc::new(codec)
                 .apply_compression_config(accept_compression_encodings, send_compression_encodings)
+                .apply_compression_policy(send_compression_policy)
                 .apply_max_message_size_config(max_decoding_message_size, max_encoding_message_size);
 
             let res = grpc.client_streaming(method, req).await;
@@ -615,6 +752,7 @@ fn generate_client_streaming<T: Method>(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn generate_streaming<T: Method>(
     method: &T,
     proto_path: &str,
@@ -622,6 +760,7 @@ fn generate_streaming<T: Method>(
     method_ident: Ident,
     server_trait: Ident,
     use_arc_self: bool,
+    extractors: &Extractors,
     generate_default_stubs: bool,
 ) -> TokenStream {
     let codec_name = syn::parse_str::<syn::Path>(method.codec_path()).unwrap();
@@ -642,6 +781,8 @@ fn generate_streaming<T: Method>(
     } else {
         quote!(&inner)
     };
+    let extract = extractors.extract();
+    let args = extractors.args();
 
     quote! {
         #[allow(non_camel_case_types)]
@@ -656,7 +797,8 @@ fn generate_streaming<T: Method>(
             fn call(&mut self, request: tonic::Request<tonic::Streaming<#request>>) -> Self::Future {
                 let inner = Arc::clone(&self.0);
                 let fut = async move {
-                    <T as #server_trait>::#method_ident(#inner_arg, request).await
+                    #extract
+                    <T as #server_trait>::#method_ident(#inner_arg, #args request).await
                 };
                 Box::pin(fut)
             }
@@ -664,6 +806,7 @@ fn generate_streaming<T: Method>(
 
         let accept_compression_encodings = self.accept_compression_encodings;
         let send_compression_encodings = self.send_compression_encodings;
+        let send_compression_policy = self.send_compression_policy.clone();
         let max_decoding_message_size = self.max_decoding_message_size;
         let max_encoding_message_size = self.max_encoding_message_size;
         let inner = self.inner.clone();
@@ -673,6 +816,7 @@ fn generate_streaming<T: Method>(
 
             let mut grpc = tonic::server::Grpc::new(codec)
                 .apply_compression_config(accept_compression_encodings, send_compression_encodings)
+                .apply_compression_policy(send_compression_policy)
                 .apply_max_message_size_config(max_decoding_message_size, max_encoding_message_size);
 
             let res = grpc.streaming(method, req).await;
@@ -682,3 +826,127 @@ fn generate_streaming<T: Method>(
         Box::pin(fut)
     }
 }
+
+// Validates the request of a method with the matched validators, answering
+// with the status of the first failed validation, see
+// `CodeGenBuilder::request_validators`.
+fn validate_request(
+    service_name: &str,
+    method_name: &str,
+    request_validators: &[(String, String)],
+) -> TokenStream {
+    let validators = request_validators
+        .iter()
+        .filter(|(pattern, _)| {
+            match_name(pattern, service_name) || match_name(pattern, method_name)
+        })
+        .map(|(_, validator)| {
+            syn::parse_str::<syn::Path>(validator).expect("valid validator path")
+        });
+
+    quote! {
+        #(#validators(request.get_ref())?;)*
+    }
+}
+
+// Runs the `tonic::server::MessageInterceptors` of the request on its message,
+// if the method is matched, see `CodeGenBuilder::intercepted_messages`.
+fn intercept_message<T: Service>(
+    service: &T,
+    method: &T::Method,
+    emit_package: bool,
+    intercepted_messages: &[String],
+) -> TokenStream {
+    let service_name = format_service_name(service, emit_package);
+    let method_name = format_method_name(service, method, emit_package);
+    if !intercepted_messages
+        .iter()
+        .any(|pattern| match_name(pattern, &service_name) || match_name(pattern, &method_name))
+    {
+        return TokenStream::new();
+    }
+
+    let name = method.identifier();
+    let path = format_method_path(service, method, emit_package);
+    let client_streaming = method.client_streaming();
+    let server_streaming = method.server_streaming();
+    let idempotency_level =
+        generate_idempotency_level(method).map(|level| quote!(.with_idempotency_level(#level)));
+
+    quote! {
+        const DESCRIPTOR: tonic::MethodDescriptor = tonic::MethodDescriptor::new(
+            #service_name,
+            #name,
+            #path,
+            #client_streaming,
+            #server_streaming,
+        )#idempotency_level;
+        let request = tonic::server::intercept_message(&DESCRIPTOR, request)?;
+    }
+}
+
+// The extractor arguments of the handler of a method, taken ahead of its
+// request, see `CodeGenBuilder::handler_extractors`.
+struct Extractors {
+    names: Vec<Ident>,
+    types: Vec<syn::Type>,
+}
+
+impl Extractors {
+    fn for_method(
+        service_name: &str,
+        method_name: &str,
+        handler_extractors: &[(String, String)],
+    ) -> Self {
+        let mut names = Vec::<Ident>::new();
+        let mut types = Vec::new();
+
+        for (_, extractor) in handler_extractors.iter().filter(|(pattern, _)| {
+            match_name(pattern, service_name) || match_name(pattern, method_name)
+        }) {
+            let ty = syn::parse_str::<syn::Type>(extractor).expect("valid extractor type");
+            // The arguments are named after their types, e.g. `user` for
+            // `crate::auth::User`.
+            let name = match &ty {
+                syn::Type::Path(path) => path
+                    .path
+                    .segments
+                    .last()
+                    .map(|segment| naive_snake_case(&segment.ident.to_string())),
+                _ => None,
+            }
+            .unwrap_or_else(|| "extracted".to_string());
+            let name = if name == "request" || names.iter().any(|other| *other == name) {
+                format!("{name}_{}", names.len())
+            } else {
+                name
+            };
+
+            names.push(quote::format_ident!("{name}"));
+            types.push(ty);
+        }
+
+        Self { names, types }
+    }
+
+    // The parameters of the handler.
+    fn params(&self) -> TokenStream {
+        let Self { names, types } = self;
+        quote! { #(#names: #types,)* }
+    }
+
+    // Extracts the arguments from `request`, answering with the status of
+    // the first failed extraction.
+    fn extract(&self) -> TokenStream {
+        let Self { names, types } = self;
+        quote! {
+            #(let #names = <#types as tonic::server::FromRequest>::from_request(&request)?;)*
+        }
+    }
+
+    // The arguments of the call of the handler.
+    fn args(&self) -> TokenStream {
+        let names = &self.names;
+        quote! { #(#names,)* }
+    }
+}
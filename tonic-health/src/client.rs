@@ -0,0 +1,335 @@
+//! Contains all healthcheck based client utilities.
+
+use crate::pb::{self, HealthCheckRequest, HealthCheckResponse};
+use crate::server::HealthProbe;
+use crate::ServingStatus;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Sleep;
+use tokio_stream::Stream;
+use tonic::codec::Streaming;
+use tonic::codegen::{Body, BoxFuture, Bytes, StdError};
+use tonic::{Code, Status};
+
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(120);
+
+/// A client of the `grpc.health.v1.Health` service, wrapping the generated
+/// [`pb::health_client::HealthClient`] to work with [`ServingStatus`]es.
+#[derive(Debug, Clone)]
+pub struct HealthClient<T> {
+    inner: pb::health_client::HealthClient<T>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl<T> HealthClient<T>
+where
+    T: tonic::client::GrpcService<tonic::body::Body>,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    /// Create a new `HealthClient` sending requests through `inner`.
+    pub fn new(inner: T) -> Self {
+        pb::health_client::HealthClient::new(inner).into()
+    }
+
+    /// Set the delays between the attempts of [`HealthClient::watch`] to
+    /// watch the service again after a failure, starting at `initial` and
+    /// doubling up to `max`.
+    ///
+    /// Defaults to starting at 1 second, up to 120 seconds.
+    #[must_use]
+    pub fn watch_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Check the status of the service with `service_name`, the empty name
+    /// standing for the overall health of the server.
+    ///
+    /// Fails with `Code::NotFound` if the service is not registered by the
+    /// server.
+    pub async fn check(
+        &mut self,
+        service_name: impl Into<String>,
+    ) -> Result<ServingStatus, Status> {
+        let request = HealthCheckRequest {
+            service: service_name.into(),
+        };
+        let response = self.inner.check(request).await?;
+        Ok(response.into_inner().status().into())
+    }
+
+    /// Watch the status of the service with `service_name`, the empty name
+    /// standing for the overall health of the server.
+    ///
+    /// The returned stream yields the status of the service each time it
+    /// changes. When watching fails, as when the connection is lost or the
+    /// service is not registered, the stream yields `ServingStatus::Unknown`
+    /// and watches the service again after a backoff. The stream ends if the
+    /// server does not implement watching.
+    pub fn watch(&self, service_name: impl Into<String>) -> HealthWatch
+    where
+        T: Clone + Send + 'static,
+        T::Future: Send,
+    {
+        let client = self.inner.clone();
+        let service = service_name.into();
+
+        let connect = move || -> BoxFuture<Streaming<HealthCheckResponse>, Status> {
+            let mut client = client.clone();
+            let request = HealthCheckRequest {
+                service: service.clone(),
+            };
+            Box::pin(async move { Ok(client.watch(request).await?.into_inner()) })
+        };
+
+        HealthWatch::new(Box::new(connect), self.initial_backoff, self.max_backoff)
+    }
+}
+
+impl<T> HealthClient<T> {
+    /// Turn the client into a [`HealthProbe`] checking the service with
+    /// `service_name`, to make it a dependency of another server with
+    /// [`HealthReporter::probe_dependency`](crate::server::HealthReporter::probe_dependency).
+    ///
+    /// Failed checks are `NotServing`.
+    pub fn probe(self, service_name: impl Into<String>) -> ClientProbe<T> {
+        ClientProbe {
+            client: self,
+            service_name: service_name.into(),
+        }
+    }
+}
+
+impl<T> From<pb::health_client::HealthClient<T>> for HealthClient<T> {
+    fn from(inner: pb::health_client::HealthClient<T>) -> Self {
+        Self {
+            inner,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+}
+
+/// A [`HealthProbe`] checking a service with a [`HealthClient`], created by
+/// [`HealthClient::probe`].
+#[derive(Debug, Clone)]
+pub struct ClientProbe<T> {
+    client: HealthClient<T>,
+    service_name: String,
+}
+
+impl<T> HealthProbe for ClientProbe<T>
+where
+    T: tonic::client::GrpcService<tonic::body::Body> + Send + 'static,
+    T::Future: Send,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    fn probe(&mut self) -> Pin<Box<dyn Future<Output = ServingStatus> + Send + '_>> {
+        Box::pin(async move {
+            self.client
+                .check(self.service_name.clone())
+                .await
+                .unwrap_or(ServingStatus::NotServing)
+        })
+    }
+}
+
+type Connect = Box<dyn FnMut() -> BoxFuture<Streaming<HealthCheckResponse>, Status> + Send>;
+
+enum State {
+    Connecting(BoxFuture<Streaming<HealthCheckResponse>, Status>),
+    Watching(Box<Streaming<HealthCheckResponse>>),
+    Waiting(Pin<Box<Sleep>>),
+    Done,
+}
+
+/// A stream of the statuses of a service, created by
+/// [`HealthClient::watch`].
+pub struct HealthWatch {
+    connect: Connect,
+    state: State,
+    last: Option<ServingStatus>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff: Duration,
+}
+
+impl HealthWatch {
+    fn new(mut connect: Connect, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        let state = State::Connecting(connect());
+        Self {
+            connect,
+            state,
+            last: None,
+            initial_backoff,
+            max_backoff,
+            backoff: initial_backoff,
+        }
+    }
+
+    // Waits before watching the service again, returning the status to yield
+    // if it changed.
+    fn retry(&mut self, status: Option<Status>) -> Option<ServingStatus> {
+        if status.is_some_and(|status| status.code() == Code::Unimplemented) {
+            self.state = State::Done;
+            return None;
+        }
+
+        self.state = State::Waiting(Box::pin(tokio::time::sleep(self.backoff)));
+        self.backoff = (self.backoff * 2).min(self.max_backoff);
+        self.update(ServingStatus::Unknown)
+    }
+
+    fn update(&mut self, status: ServingStatus) -> Option<ServingStatus> {
+        if self.last == Some(status) {
+            return None;
+        }
+        self.last = Some(status);
+        Some(status)
+    }
+}
+
+impl Stream for HealthWatch {
+    type Item = ServingStatus;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+
+        loop {
+            let changed = match &mut this.state {
+                State::Connecting(future) => match future.as_mut().poll(cx) {
+                    Poll::Ready(Ok(stream)) => {
+                        this.state = State::Watching(Box::new(stream));
+                        None
+                    }
+                    Poll::Ready(Err(status)) => this.retry(Some(status)),
+                    Poll::Pending => return Poll::Pending,
+                },
+                State::Watching(stream) => match Pin::new(stream).poll_next(cx) {
+                    Poll::Ready(Some(Ok(response))) => {
+                        this.backoff = this.initial_backoff;
+                        this.update(response.status().into())
+                    }
+                    Poll::Ready(Some(Err(status))) => this.retry(Some(status)),
+                    Poll::Ready(None) => this.retry(None),
+                    Poll::Pending => return Poll::Pending,
+                },
+                State::Waiting(sleep) => match sleep.as_mut().poll(cx) {
+                    Poll::Ready(()) => {
+                        this.state = State::Connecting((this.connect)());
+                        None
+                    }
+                    Poll::Pending => return Poll::Pending,
+                },
+                State::Done => return Poll::Ready(None),
+            };
+
+            if let Some(status) = changed {
+                return Poll::Ready(Some(status));
+            }
+        }
+    }
+}
+
+impl fmt::Debug for HealthWatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HealthWatch")
+            .field("last", &self.last)
+            .field("backoff", &self.backoff)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::HealthClient;
+    use crate::server::health_reporter;
+    use crate::ServingStatus;
+    use std::time::Duration;
+    use tokio_stream::StreamExt;
+    use tonic::Code;
+
+    #[tokio::test]
+    async fn test_client_check() {
+        let (reporter, server) = health_reporter();
+        let mut client = HealthClient::new(server);
+
+        assert_eq!(client.check("").await.unwrap(), ServingStatus::Serving);
+
+        let status = client.check("TestService").await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+
+        reporter
+            .set_service_status("TestService", ServingStatus::NotServing)
+            .await;
+        assert_eq!(
+            client.check("TestService").await.unwrap(),
+            ServingStatus::NotServing
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_watch() {
+        let (mut reporter, server) = health_reporter();
+        let client = HealthClient::new(server)
+            .watch_backoff(Duration::from_millis(10), Duration::from_millis(20));
+
+        reporter
+            .set_service_status("TestService", ServingStatus::Serving)
+            .await;
+
+        let mut watch = client.watch("TestService");
+        assert_eq!(watch.next().await, Some(ServingStatus::Serving));
+
+        reporter
+            .set_service_status("TestService", ServingStatus::NotServing)
+            .await;
+        assert_eq!(watch.next().await, Some(ServingStatus::NotServing));
+
+        // The stream of the server ends, and watching the unregistered
+        // service fails until it is registered again.
+        reporter.clear_service_status("TestService").await;
+        assert_eq!(watch.next().await, Some(ServingStatus::Unknown));
+
+        reporter
+            .set_service_status("TestService", ServingStatus::Serving)
+            .await;
+        assert_eq!(watch.next().await, Some(ServingStatus::Serving));
+    }
+
+    #[tokio::test]
+    async fn test_client_probe() {
+        let (upstream, upstream_server) = health_reporter();
+        let (reporter, server) = health_reporter();
+        let mut client = HealthClient::new(server);
+
+        let probe = HealthClient::new(upstream_server).probe("Upstream");
+        let task = reporter
+            .probe_dependency("upstream", probe, Duration::from_millis(10))
+            .await;
+        let task = tokio::spawn(task);
+
+        let mut watch = client.watch("upstream");
+        // Failed checks, as of unregistered services, are `NotServing`.
+        assert_eq!(watch.next().await, Some(ServingStatus::Unknown));
+        assert_eq!(watch.next().await, Some(ServingStatus::NotServing));
+
+        upstream
+            .set_service_status("Upstream", ServingStatus::Serving)
+            .await;
+        assert_eq!(watch.next().await, Some(ServingStatus::Serving));
+        assert_eq!(client.check("").await.unwrap(), ServingStatus::Serving);
+
+        task.abort();
+    }
+}
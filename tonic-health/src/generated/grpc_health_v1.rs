@@ -55,6 +55,48 @@ pub mod health_check_response {
         }
     }
 }
+/// Names and paths of the methods of the `grpc.health.v1.Health` service.
+pub mod health_methods {
+    #![allow(dead_code)]
+    /// The fully qualified name of the service.
+    pub const SERVICE_NAME: &str = "grpc.health.v1.Health";
+    /// Constants of the `Check` method.
+    pub mod check {
+        /// The name of the method.
+        pub const NAME: &str = "Check";
+        /// The path of the method in requests.
+        pub const PATH: &str = "/grpc.health.v1.Health/Check";
+        /// The descriptor of the method.
+        pub const DESCRIPTOR: tonic::MethodDescriptor = tonic::MethodDescriptor::new(
+            super::SERVICE_NAME,
+            NAME,
+            PATH,
+            false,
+            false,
+        );
+    }
+    /// Constants of the `Watch` method.
+    pub mod watch {
+        /// The name of the method.
+        pub const NAME: &str = "Watch";
+        /// The path of the method in requests.
+        pub const PATH: &str = "/grpc.health.v1.Health/Watch";
+        /// The descriptor of the method.
+        pub const DESCRIPTOR: tonic::MethodDescriptor = tonic::MethodDescriptor::new(
+            super::SERVICE_NAME,
+            NAME,
+            PATH,
+            false,
+            true,
+        );
+    }
+    /// The descriptors of the methods of the service, in declaration
+    /// order.
+    pub const METHODS: &[tonic::MethodDescriptor] = &[
+        check::DESCRIPTOR,
+        watch::DESCRIPTOR,
+    ];
+}
 /// Generated client implementations.
 pub mod health_client {
     #![allow(
@@ -255,6 +297,7 @@ pub mod health_server {
         inner: Arc<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
+        send_compression_policy: Option<Arc<dyn CompressionPolicy>>,
         max_decoding_message_size: Option<usize>,
         max_encoding_message_size: Option<usize>,
     }
@@ -267,6 +310,7 @@ pub mod health_server {
                 inner,
                 accept_compression_encodings: Default::default(),
                 send_compression_encodings: Default::default(),
+                send_compression_policy: None,
                 max_decoding_message_size: None,
                 max_encoding_message_size: None,
             }
@@ -280,6 +324,15 @@ pub mod health_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        pub fn with_state<S>(
+            inner: T,
+            state: Arc<S>,
+        ) -> InterceptedService<Self, tonic::server::State<S>>
+        where
+            S: std::marker::Send + std::marker::Sync + 'static,
+        {
+            Self::with_interceptor(inner, tonic::server::State(state))
+        }
         /// Enable decompressing requests with the given encoding.
         #[must_use]
         pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
@@ -292,6 +345,16 @@ pub mod health_server {
             self.send_compression_encodings.enable(encoding);
             self
         }
+        /// Decide whether and how responses are compressed with the given policy, in place of the
+        /// encodings enabled with `send_compressed`.
+        #[must_use]
+        pub fn send_compression_policy(
+            mut self,
+            policy: impl CompressionPolicy,
+        ) -> Self {
+            self.send_compression_policy = Some(Arc::new(policy));
+            self
+        }
         /// Limits the maximum size of a decoded message.
         ///
         /// Default: `4MB`
@@ -351,6 +414,7 @@ pub mod health_server {
                     }
                     let accept_compression_encodings = self.accept_compression_encodings;
                     let send_compression_encodings = self.send_compression_encodings;
+                    let send_compression_policy = self.send_compression_policy.clone();
                     let max_decoding_message_size = self.max_decoding_message_size;
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
@@ -362,6 +426,7 @@ pub mod health_server {
                                 accept_compression_encodings,
                                 send_compression_encodings,
                             )
+                            .apply_compression_policy(send_compression_policy)
                             .apply_max_message_size_config(
                                 max_decoding_message_size,
                                 max_encoding_message_size,
@@ -397,6 +462,7 @@ pub mod health_server {
                     }
                     let accept_compression_encodings = self.accept_compression_encodings;
                     let send_compression_encodings = self.send_compression_encodings;
+                    let send_compression_policy = self.send_compression_policy.clone();
                     let max_decoding_message_size = self.max_decoding_message_size;
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
@@ -408,6 +474,7 @@ pub mod health_server {
                                 accept_compression_encodings,
                                 send_compression_encodings,
                             )
+                            .apply_compression_policy(send_compression_policy)
                             .apply_max_message_size_config(
                                 max_decoding_message_size,
                                 max_encoding_message_size,
@@ -446,6 +513,7 @@ pub mod health_server {
                 inner,
                 accept_compression_encodings: self.accept_compression_encodings,
                 send_compression_encodings: self.send_compression_encodings,
+                send_compression_policy: self.send_compression_policy.clone(),
                 max_decoding_message_size: self.max_decoding_message_size,
                 max_encoding_message_size: self.max_encoding_message_size,
             }
@@ -42,6 +42,7 @@ pub mod pb {
     pub use crate::generated::{grpc_health_v1::*, FILE_DESCRIPTOR_SET};
 }
 
+pub mod client;
 pub mod server;
 
 /// An enumeration of values representing gRPC service health.
@@ -74,3 +75,14 @@ impl From<ServingStatus> for pb::health_check_response::ServingStatus {
         }
     }
 }
+
+impl From<pb::health_check_response::ServingStatus> for ServingStatus {
+    fn from(s: pb::health_check_response::ServingStatus) -> Self {
+        match s {
+            pb::health_check_response::ServingStatus::Serving => ServingStatus::Serving,
+            pb::health_check_response::ServingStatus::NotServing => ServingStatus::NotServing,
+            pb::health_check_response::ServingStatus::Unknown
+            | pb::health_check_response::ServingStatus::ServiceUnknown => ServingStatus::Unknown,
+        }
+    }
+}
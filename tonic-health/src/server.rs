@@ -5,8 +5,12 @@ use crate::pb::{HealthCheckRequest, HealthCheckResponse};
 use crate::ServingStatus;
 use std::collections::HashMap;
 use std::fmt;
-use std::sync::Arc;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::{watch, RwLock};
+use tokio::time::{Instant, Sleep};
 use tokio_stream::Stream;
 use tonic::{server::NamedService, Request, Response, Status};
 
@@ -26,7 +30,79 @@ pub fn health_reporter() -> (HealthReporter, HealthServer<impl Health>) {
     (reporter, server)
 }
 
-type StatusPair = (watch::Sender<ServingStatus>, watch::Receiver<ServingStatus>);
+type StatusPair = (
+    watch::Sender<ReportedStatus>,
+    watch::Receiver<ReportedStatus>,
+);
+
+/// A status reported for a service, lapsing to `NotServing` at `expires` if
+/// set.
+#[derive(Clone, Copy, Debug)]
+struct ReportedStatus {
+    status: ServingStatus,
+    expires: Option<Instant>,
+}
+
+impl ReportedStatus {
+    fn current(&self) -> ServingStatus {
+        match self.expires {
+            Some(expires) if expires <= Instant::now() => ServingStatus::NotServing,
+            _ => self.status,
+        }
+    }
+}
+
+impl From<ServingStatus> for ReportedStatus {
+    fn from(status: ServingStatus) -> Self {
+        Self {
+            status,
+            expires: None,
+        }
+    }
+}
+
+/// The overall health of the server: the status reported for the empty
+/// service, combined with the statuses of the dependencies.
+#[derive(Debug)]
+struct Overall {
+    reported: ReportedStatus,
+    dependencies: HashMap<String, ServingStatus>,
+}
+
+impl Overall {
+    fn status(&self) -> ReportedStatus {
+        if self
+            .dependencies
+            .values()
+            .all(|status| *status == ServingStatus::Serving)
+        {
+            self.reported
+        } else {
+            ServingStatus::NotServing.into()
+        }
+    }
+}
+
+/// A check of the health of a dependency of the server, run periodically by
+/// [`HealthReporter::probe_dependency`].
+///
+/// This is implemented by closures returning a future of the status, and by
+/// the probes of services served by other servers made by
+/// [`HealthClient::probe`](crate::client::HealthClient::probe).
+pub trait HealthProbe: Send + 'static {
+    /// Checks the health of the dependency.
+    fn probe(&mut self) -> Pin<Box<dyn Future<Output = ServingStatus> + Send + '_>>;
+}
+
+impl<F, Fut> HealthProbe for F
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ServingStatus> + Send + 'static,
+{
+    fn probe(&mut self) -> Pin<Box<dyn Future<Output = ServingStatus> + Send + '_>> {
+        Box::pin(self())
+    }
+}
 
 /// A handle providing methods to update the health status of gRPC services. A
 /// `HealthReporter` is connected to a `HealthServer` which serves the statuses
@@ -34,17 +110,26 @@ type StatusPair = (watch::Sender<ServingStatus>, watch::Receiver<ServingStatus>)
 #[derive(Clone, Debug)]
 pub struct HealthReporter {
     statuses: Arc<RwLock<HashMap<String, StatusPair>>>,
+    overall: Arc<Mutex<Overall>>,
 }
 
 impl HealthReporter {
     /// Create a new HealthReporter with an initial service (named ""), corresponding to overall server health
     pub fn new() -> Self {
         // According to the gRPC Health Check specification, the empty service "" corresponds to the overall server health
-        let server_status = ("".to_string(), watch::channel(ServingStatus::Serving));
+        let server_status = (
+            "".to_string(),
+            watch::channel(ServingStatus::Serving.into()),
+        );
 
         let statuses = Arc::new(RwLock::new(HashMap::from([server_status])));
 
-        HealthReporter { statuses }
+        let overall = Arc::new(Mutex::new(Overall {
+            reported: ServingStatus::Serving.into(),
+            dependencies: HashMap::new(),
+        }));
+
+        HealthReporter { statuses, overall }
     }
 
     /// Sets the status of the service implemented by `S` to `Serving`. This notifies any watchers
@@ -75,8 +160,110 @@ impl HealthReporter {
     where
         S: AsRef<str>,
     {
-        let service_name = service_name.as_ref();
+        self.report(service_name.as_ref(), status.into()).await;
+    }
+
+    /// Sets the status of the service implemented by `S` to `Serving` for
+    /// `ttl`, after which it flips to `NotServing` unless refreshed by
+    /// another call. This notifies any watchers if there is a change in
+    /// status.
+    pub async fn set_serving_with_ttl<S>(&self, ttl: Duration)
+    where
+        S: NamedService,
+    {
+        let service_name = <S as NamedService>::NAME;
+        self.set_service_serving_with_ttl(service_name, ttl).await;
+    }
+
+    /// Sets the status of the service with `service_name` to `Serving` for
+    /// `ttl`, after which it flips to `NotServing` unless refreshed by
+    /// another call.
+    ///
+    /// Calling this periodically from the tasks doing the work of the service
+    /// ties its health to them, so that a task that is stuck without
+    /// reporting a failure shows as `NotServing`. This notifies any watchers
+    /// if there is a change in status.
+    pub async fn set_service_serving_with_ttl<S>(&self, service_name: S, ttl: Duration)
+    where
+        S: AsRef<str>,
+    {
+        let reported = ReportedStatus {
+            status: ServingStatus::Serving,
+            expires: Some(Instant::now() + ttl),
+        };
+        self.report(service_name.as_ref(), reported).await;
+    }
+
+    /// Sets the status of the dependency with `dependency_name` to `status`.
+    ///
+    /// The status of a dependency is served as the status of a service with
+    /// `dependency_name`, and the overall health of the server, served as
+    /// the status of the empty service, is only `Serving` while all the
+    /// dependencies are `Serving` too. This notifies any watchers if there is
+    /// a change in status.
+    pub async fn set_dependency_status<S>(&self, dependency_name: S, status: ServingStatus)
+    where
+        S: AsRef<str>,
+    {
+        let dependency_name = dependency_name.as_ref();
+        let mut writer = self.statuses.write().await;
+
+        let overall = {
+            let mut overall = self.overall.lock().expect("lock should not be poisoned");
+            overall
+                .dependencies
+                .insert(dependency_name.to_string(), status);
+            overall.status()
+        };
+
+        send_if_changed(&mut writer, dependency_name, status.into());
+        send_if_changed(&mut writer, "", overall);
+    }
+
+    /// Registers the dependency with `dependency_name`, whose status is set
+    /// by running `probe` every `interval`, as with
+    /// [`HealthReporter::set_dependency_status`].
+    ///
+    /// The dependency is `Unknown`, and so the server is not `Serving`, until
+    /// it is first probed. The probes are run by the returned future, which
+    /// has to be spawned.
+    pub async fn probe_dependency<P>(
+        &self,
+        dependency_name: impl Into<String>,
+        mut probe: P,
+        interval: Duration,
+    ) -> impl Future<Output = ()> + Send + 'static
+    where
+        P: HealthProbe,
+    {
+        let dependency_name = dependency_name.into();
+        self.set_dependency_status(&dependency_name, ServingStatus::Unknown)
+            .await;
+
+        let reporter = self.clone();
+        async move {
+            let mut interval = tokio::time::interval(interval);
+            loop {
+                interval.tick().await;
+                let status = probe.probe().await;
+                reporter
+                    .set_dependency_status(&dependency_name, status)
+                    .await;
+            }
+        }
+    }
+
+    async fn report(&self, service_name: &str, status: ReportedStatus) {
         let mut writer = self.statuses.write().await;
+
+        let status = if service_name.is_empty() {
+            let mut overall = self.overall.lock().expect("lock should not be poisoned");
+            overall.reported = status;
+            overall.status()
+        } else {
+            status
+        };
+
         match writer.get(service_name) {
             Some((tx, _)) => {
                 // We only ever hand out clones of the receiver, so the originally-created
@@ -98,6 +285,28 @@ impl HealthReporter {
     }
 }
 
+// Sets the status of the service with `service_name`, only notifying the
+// watchers if it changed, as the statuses of dependencies are set again on
+// each probe.
+fn send_if_changed(
+    statuses: &mut HashMap<String, StatusPair>,
+    service_name: &str,
+    status: ReportedStatus,
+) {
+    match statuses.get(service_name) {
+        Some((tx, _)) => {
+            tx.send_if_modified(|current| {
+                let changed = current.status != status.status || current.expires != status.expires;
+                *current = status;
+                changed
+            });
+        }
+        None => {
+            statuses.insert(service_name.to_string(), watch::channel(status));
+        }
+    }
+}
+
 impl Default for HealthReporter {
     fn default() -> Self {
         Self::new()
@@ -122,7 +331,7 @@ impl HealthService {
 
     async fn service_health(&self, service_name: &str) -> Option<ServingStatus> {
         let reader = self.statuses.read().await;
-        reader.get(service_name).map(|p| *p.1.borrow())
+        reader.get(service_name).map(|p| p.1.borrow().current())
     }
 }
 
@@ -158,13 +367,27 @@ impl Health for HealthService {
 
 /// A watch stream for the health service.
 pub struct WatchStream {
-    inner: tokio_stream::wrappers::WatchStream<ServingStatus>,
+    inner: tokio_stream::wrappers::WatchStream<ReportedStatus>,
+    last: Option<ServingStatus>,
+    expiry: Option<Pin<Box<Sleep>>>,
 }
 
 impl WatchStream {
-    fn new(status_rx: watch::Receiver<ServingStatus>) -> Self {
+    fn new(status_rx: watch::Receiver<ReportedStatus>) -> Self {
         let inner = tokio_stream::wrappers::WatchStream::new(status_rx);
-        Self { inner }
+        Self {
+            inner,
+            last: None,
+            expiry: None,
+        }
+    }
+
+    fn yield_status(
+        &mut self,
+        status: ServingStatus,
+    ) -> Option<Result<HealthCheckResponse, Status>> {
+        self.last = Some(status);
+        Some(Ok(HealthCheckResponse::new(status)))
     }
 }
 
@@ -175,9 +398,37 @@ impl Stream for WatchStream {
         mut self: std::pin::Pin<&mut Self>,
         cx: &mut std::task::Context<'_>,
     ) -> std::task::Poll<Option<Self::Item>> {
-        std::pin::Pin::new(&mut self.inner)
-            .poll_next(cx)
-            .map(|opt| opt.map(|status| Ok(HealthCheckResponse::new(status))))
+        use std::task::Poll;
+
+        loop {
+            if let Some(expiry) = self.expiry.as_mut() {
+                if expiry.as_mut().poll(cx).is_ready() {
+                    self.expiry = None;
+                    return Poll::Ready(self.yield_status(ServingStatus::NotServing));
+                }
+            }
+
+            let reported = match Pin::new(&mut self.inner).poll_next(cx) {
+                Poll::Ready(Some(reported)) => reported,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let status = reported.current();
+            self.expiry = match reported.expires {
+                Some(expires) if status == ServingStatus::Serving => {
+                    Some(Box::pin(tokio::time::sleep_until(expires)))
+                }
+                _ => None,
+            };
+
+            // Refreshing a status with a TTL is not a change in status.
+            if reported.expires.is_some() && self.last == Some(status) {
+                continue;
+            }
+
+            return Poll::Ready(self.yield_status(status));
+        }
     }
 }
 
@@ -200,6 +451,9 @@ mod tests {
     use crate::pb::HealthCheckRequest;
     use crate::server::{HealthReporter, HealthService};
     use crate::ServingStatus;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
     use tokio::sync::watch;
     use tokio_stream::StreamExt;
     use tonic::{Code, Request, Status};
@@ -222,7 +476,7 @@ mod tests {
             let mut statuses = health_reporter.statuses.write().await;
             statuses.insert(
                 "TestService".to_string(),
-                watch::channel(ServingStatus::Unknown),
+                watch::channel(ServingStatus::Unknown.into()),
             );
         }
 
@@ -350,4 +604,118 @@ mod tests {
         let item = resp.next().await;
         assert!(item.is_none());
     }
+
+    #[tokio::test]
+    async fn test_service_ttl() {
+        let (reporter, service) = make_test_service().await;
+        let ttl = Duration::from_millis(100);
+
+        async fn check(service: &HealthService) -> i32 {
+            let request = Request::new(HealthCheckRequest {
+                service: "TestService".to_string(),
+            });
+            service.check(request).await.unwrap().into_inner().status
+        }
+
+        reporter
+            .set_service_serving_with_ttl("TestService", ttl)
+            .await;
+        assert_serving_status(check(&service).await, ServingStatus::Serving);
+
+        let mut resp = service
+            .watch(Request::new(HealthCheckRequest {
+                service: "TestService".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::Serving);
+
+        // Refreshing the status is not a change.
+        reporter
+            .set_service_serving_with_ttl("TestService", ttl)
+            .await;
+
+        // Without refreshes, the status lapses.
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::NotServing);
+        assert_serving_status(check(&service).await, ServingStatus::NotServing);
+
+        reporter
+            .set_service_serving_with_ttl("TestService", ttl)
+            .await;
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::Serving);
+        assert_serving_status(check(&service).await, ServingStatus::Serving);
+    }
+
+    #[tokio::test]
+    async fn test_service_dependencies() {
+        let (reporter, service) = make_test_service().await;
+
+        let mut resp = service
+            .watch(Request::new(HealthCheckRequest {
+                service: "".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::Serving);
+
+        reporter
+            .set_dependency_status("database", ServingStatus::NotServing)
+            .await;
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::NotServing);
+
+        // The dependency is served as a service.
+        let resp_dependency = service
+            .check(Request::new(HealthCheckRequest {
+                service: "database".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_serving_status(resp_dependency.status, ServingStatus::NotServing);
+
+        let serving = Arc::new(AtomicBool::new(false));
+        let probe = {
+            let serving = serving.clone();
+            move || {
+                let serving = serving.load(Ordering::SeqCst);
+                async move {
+                    if serving {
+                        ServingStatus::Serving
+                    } else {
+                        ServingStatus::NotServing
+                    }
+                }
+            }
+        };
+        let task = reporter
+            .probe_dependency("cache", probe, Duration::from_millis(10))
+            .await;
+        let task = tokio::spawn(task);
+
+        serving.store(true, Ordering::SeqCst);
+        reporter
+            .set_dependency_status("database", ServingStatus::Serving)
+            .await;
+
+        // Only the changes of the overall status are sent.
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::Serving);
+
+        // The overall status set for the server is combined with the
+        // dependencies.
+        reporter
+            .set_service_status("", ServingStatus::NotServing)
+            .await;
+        let item = resp.next().await.unwrap().unwrap();
+        assert_serving_status(item.status, ServingStatus::NotServing);
+
+        task.abort();
+    }
 }
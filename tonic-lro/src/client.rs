@@ -0,0 +1,167 @@
+//! Contains all long-running operations based client utilities.
+
+use crate::pb::{self, CancelOperationRequest, DeleteOperationRequest, GetOperationRequest};
+use crate::OperationExt;
+use prost::Name;
+use std::time::Duration;
+use tonic::codegen::{Body, Bytes, StdError};
+use tonic::{Code, Status};
+
+const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// A client of the `google.longrunning.Operations` service, wrapping the
+/// generated [`pb::operations_client::OperationsClient`] to poll operations
+/// until they are done.
+#[derive(Debug, Clone)]
+pub struct OperationsClient<T> {
+    inner: pb::operations_client::OperationsClient<T>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl<T> OperationsClient<T>
+where
+    T: tonic::client::GrpcService<tonic::body::Body>,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    /// Create a new `OperationsClient` sending requests through `inner`.
+    pub fn new(inner: T) -> Self {
+        pb::operations_client::OperationsClient::new(inner).into()
+    }
+
+    /// Set the delays between the polls of [`OperationsClient::poll_until_done`],
+    /// starting at `initial` and doubling up to `max`.
+    ///
+    /// Defaults to starting at 500 milliseconds, up to 30 seconds.
+    #[must_use]
+    pub fn poll_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Get the latest state of the operation with `name`.
+    pub async fn get(&mut self, name: impl Into<String>) -> Result<pb::Operation, Status> {
+        let request = GetOperationRequest { name: name.into() };
+        Ok(self.inner.get_operation(request).await?.into_inner())
+    }
+
+    /// Start the cancellation of the operation with `name`. The server makes
+    /// a best effort to cancel it, which completes it with `Code::Cancelled`.
+    pub async fn cancel(&mut self, name: impl Into<String>) -> Result<(), Status> {
+        let request = CancelOperationRequest { name: name.into() };
+        self.inner.cancel_operation(request).await?;
+        Ok(())
+    }
+
+    /// Delete the operation with `name`, once its result is not needed
+    /// anymore.
+    pub async fn delete(&mut self, name: impl Into<String>) -> Result<(), Status> {
+        let request = DeleteOperationRequest { name: name.into() };
+        self.inner.delete_operation(request).await?;
+        Ok(())
+    }
+
+    /// Poll the operation with `name` until it is done, returning its final
+    /// state.
+    ///
+    /// The delay between polls starts at the initial backoff and doubles up
+    /// to the maximum backoff. Polls failing with `Code::Unavailable` are
+    /// retried the same way, while other failures are returned. Wrap the
+    /// call in `tokio::time::timeout` to stop polling after a while.
+    pub async fn poll_until_done(
+        &mut self,
+        name: impl Into<String>,
+    ) -> Result<pb::Operation, Status> {
+        let name = name.into();
+        let mut backoff = self.initial_backoff;
+        loop {
+            match self.get(name.clone()).await {
+                Ok(operation) if operation.done => return Ok(operation),
+                Ok(_) => {}
+                Err(status) if status.code() == Code::Unavailable => {}
+                Err(status) => return Err(status),
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = backoff.saturating_mul(2).min(self.max_backoff);
+        }
+    }
+
+    /// Poll the operation with `name` until it is done, with
+    /// [`OperationsClient::poll_until_done`], returning its response as an
+    /// `R` or its error.
+    ///
+    /// See [`OperationExt::result`] for the decoding of the result.
+    pub async fn wait_for_result<R: Name + Default>(
+        &mut self,
+        name: impl Into<String>,
+    ) -> Result<R, Status> {
+        let operation = self.poll_until_done(name).await?;
+        operation
+            .result()
+            .unwrap_or_else(|| Err(Status::internal("operation is not done")))
+    }
+}
+
+impl<T> From<pb::operations_client::OperationsClient<T>> for OperationsClient<T> {
+    fn from(inner: pb::operations_client::OperationsClient<T>) -> Self {
+        Self {
+            inner,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::client::OperationsClient;
+    use crate::server::operation_store;
+    use prost_types::Duration;
+    use tonic::Code;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_client_poll_until_done() {
+        let (store, server) = operation_store();
+        let mut client = OperationsClient::new(server);
+
+        let handle = store.start("operations/1").unwrap();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+            handle.complete(Ok(Duration {
+                seconds: 7,
+                nanos: 0,
+            }));
+        });
+
+        let response = client
+            .wait_for_result::<Duration>("operations/1")
+            .await
+            .unwrap();
+        assert_eq!(response.seconds, 7);
+
+        client.delete("operations/1").await.unwrap();
+        let status = client.poll_until_done("operations/1").await.unwrap_err();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_client_cancel() {
+        let (store, server) = operation_store();
+        let mut client = OperationsClient::new(server);
+
+        let handle = store.start("operations/1").unwrap();
+        client.cancel("operations/1").await.unwrap();
+        assert!(handle.is_cancelled());
+
+        let status = client
+            .wait_for_result::<Duration>("operations/1")
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Cancelled);
+    }
+}
@@ -0,0 +1,209 @@
+//! A `tonic` based implementation of the long-running operations pattern, as
+//! described by [AIP-151].
+//!
+//! Methods taking long to complete return a [`pb::Operation`] right away,
+//! which clients poll through the `google.longrunning.Operations` service
+//! until it is done, before reading its result.
+//!
+//! - [`server`] serves the `google.longrunning.Operations` service from an
+//!   [`OperationStore`](server::OperationStore), in which request handlers
+//!   start operations and later complete them.
+//! - [`client`] polls operations until they are done, with backoff.
+//! - [`OperationExt`] decodes the typed metadata and results of operations.
+//!
+//! # Example
+//!
+//! ```
+//! use prost_types::Duration;
+//! use tonic_lro::{server::operation_store, OperationExt};
+//!
+//! # fn main() -> Result<(), tonic::Status> {
+//! let (store, _server) = operation_store();
+//!
+//! // In the handler of the method, before returning the operation.
+//! let handle = store.start("operations/export-1")?;
+//! assert!(!handle.operation().done);
+//!
+//! // Later, in the task doing the work.
+//! handle.complete(Ok(Duration { seconds: 5, nanos: 0 }));
+//!
+//! let operation = store.get("operations/export-1")?;
+//! let result = operation.result::<Duration>().expect("operation is done");
+//! assert_eq!(result?.seconds, 5);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [AIP-151]: https://google.aip.dev/151
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/tokio-rs/website/master/public/img/icons/tonic.svg"
+)]
+#![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
+#![doc(test(no_crate_inject, attr(deny(rust_2018_idioms))))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+use prost::{Message, Name};
+use tonic::{Code, Status};
+
+mod generated {
+    #![allow(unreachable_pub)]
+    #![allow(missing_docs)]
+    #[rustfmt::skip]
+    pub mod google_longrunning;
+}
+
+/// Generated protobuf types from the `google.longrunning` package.
+pub mod pb {
+    pub use crate::generated::google_longrunning::*;
+}
+
+pub mod client;
+pub mod server;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Decodes the typed metadata and results of [`pb::Operation`]s.
+///
+/// This trait is sealed and implemented by [`pb::Operation`] only.
+pub trait OperationExt: sealed::Sealed {
+    /// Decodes the metadata of the operation as an `M`.
+    ///
+    /// Returns `Ok(None)` if the operation has no metadata, and fails with
+    /// `Code::Internal` if its metadata is not an `M`.
+    fn metadata<M: Name + Default>(&self) -> Result<Option<M>, Status>;
+
+    /// Decodes the result of the operation, the response as an `R` or the
+    /// error as a `tonic::Status` carrying the details of the error.
+    ///
+    /// Returns `None` while the operation is not done. Fails with
+    /// `Code::Internal` if the response is not an `R`, and with `Code::Unknown`
+    /// if the operation is done without any result.
+    fn result<R: Name + Default>(&self) -> Option<Result<R, Status>>;
+}
+
+impl sealed::Sealed for pb::Operation {}
+
+impl OperationExt for pb::Operation {
+    fn metadata<M: Name + Default>(&self) -> Result<Option<M>, Status> {
+        self.metadata.as_ref().map(decode_any).transpose()
+    }
+
+    fn result<R: Name + Default>(&self) -> Option<Result<R, Status>> {
+        if !self.done {
+            return None;
+        }
+
+        Some(match &self.result {
+            Some(pb::operation::Result::Response(response)) => decode_any(response),
+            Some(pb::operation::Result::Error(error)) => Err(status_from_pb(error)),
+            None => Err(Status::unknown(format!(
+                "operation {} is done without a result",
+                self.name
+            ))),
+        })
+    }
+}
+
+fn decode_any<M: Name + Default>(any: &prost_types::Any) -> Result<M, Status> {
+    any.to_msg()
+        .map_err(|err| Status::internal(format!("failed to decode {}: {err}", any.type_url)))
+}
+
+// Converts the error of an operation to a `tonic::Status`, keeping the whole
+// `google.rpc.Status` in the details so that its error details can be read.
+fn status_from_pb(status: &tonic_types::pb::Status) -> Status {
+    Status::with_details(
+        Code::from_i32(status.code),
+        status.message.clone(),
+        status.encode_to_vec().into(),
+    )
+}
+
+// Converts a `tonic::Status` to the error of an operation, keeping its error
+// details if it carries some.
+fn status_to_pb(status: &Status) -> tonic_types::pb::Status {
+    tonic_types::pb::Status::decode(status.details())
+        .ok()
+        .filter(|decoded| !status.details().is_empty() && decoded.code == status.code() as i32)
+        .unwrap_or_else(|| tonic_types::pb::Status {
+            code: status.code() as i32,
+            message: status.message().to_owned(),
+            details: Vec::new(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pb, status_from_pb, status_to_pb, OperationExt};
+    use prost_types::Duration;
+    use std::collections::HashMap;
+    use tonic::{Code, Status};
+    use tonic_types::{ErrorDetails, StatusExt};
+
+    #[test]
+    fn result_of_pending_operation() {
+        let operation = pb::Operation {
+            name: "operations/1".into(),
+            ..Default::default()
+        };
+
+        assert!(operation.result::<Duration>().is_none());
+        assert_eq!(operation.metadata::<Duration>().unwrap(), None);
+    }
+
+    #[test]
+    fn typed_metadata_and_response() {
+        let duration = Duration {
+            seconds: 3,
+            nanos: 0,
+        };
+        let operation = pb::Operation {
+            name: "operations/1".into(),
+            metadata: Some(prost_types::Any::from_msg(&duration).unwrap()),
+            done: true,
+            result: Some(pb::operation::Result::Response(
+                prost_types::Any::from_msg(&duration).unwrap(),
+            )),
+        };
+
+        assert_eq!(operation.metadata::<Duration>().unwrap(), Some(duration));
+        assert_eq!(operation.result::<Duration>().unwrap().unwrap(), duration);
+
+        let err = operation
+            .result::<prost_types::Timestamp>()
+            .unwrap()
+            .unwrap_err();
+        assert_eq!(err.code(), Code::Internal);
+    }
+
+    #[test]
+    fn error_keeps_details() {
+        let status = Status::with_error_details(
+            Code::FailedPrecondition,
+            "bucket is locked",
+            ErrorDetails::with_error_info("BUCKET_LOCKED", "example.com", HashMap::new()),
+        );
+
+        let operation = pb::Operation {
+            name: "operations/1".into(),
+            done: true,
+            result: Some(pb::operation::Result::Error(status_to_pb(&status))),
+            ..Default::default()
+        };
+
+        let err = operation.result::<Duration>().unwrap().unwrap_err();
+        assert_eq!(err.code(), Code::FailedPrecondition);
+        assert_eq!(err.message(), "bucket is locked");
+        assert_eq!(
+            err.get_details_error_info().unwrap().reason,
+            "BUCKET_LOCKED"
+        );
+
+        let plain = status_to_pb(&Status::not_found("missing"));
+        assert_eq!(plain.code, Code::NotFound as i32);
+        assert_eq!(status_from_pb(&plain).message(), "missing");
+    }
+}
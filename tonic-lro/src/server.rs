@@ -0,0 +1,515 @@
+//! Contains all long-running operations based server utilities.
+
+use crate::pb::operations_server::{Operations, OperationsServer};
+use crate::pb::{
+    self, CancelOperationRequest, DeleteOperationRequest, GetOperationRequest,
+    ListOperationsRequest, ListOperationsResponse, WaitOperationRequest,
+};
+use prost::Name;
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::watch;
+use tonic::{Request, Response, Status};
+
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+
+/// Creates an `OperationStore` and a linked `OperationsServer` pair. Together,
+/// these types can be used to serve the `google.longrunning.Operations`
+/// service.
+///
+/// An `OperationStore` is used by the handlers of the methods returning
+/// operations to start them, and by the tasks doing the work to complete them.
+///
+/// An `OperationsServer` is a Tonic gRPC server for the
+/// `google.longrunning.Operations` service, which can be added to a Tonic
+/// runtime using `add_service` on the runtime builder.
+pub fn operation_store() -> (OperationStore, OperationsServer<impl Operations>) {
+    let store = OperationStore::new();
+    let server = OperationsServer::new(OperationsService {
+        store: store.clone(),
+    });
+
+    (store, server)
+}
+
+/// The state of an operation, shared with the handles and the waiters of the
+/// operation.
+#[derive(Clone, Debug)]
+struct State {
+    operation: pb::Operation,
+    cancelled: bool,
+}
+
+/// The operations of a server, by name.
+///
+/// Operations stay in the store once done, until clients delete them through
+/// the `DeleteOperation` method or [`OperationStore::delete`] is called.
+#[derive(Clone, Debug, Default)]
+pub struct OperationStore {
+    operations: Arc<Mutex<BTreeMap<String, watch::Sender<State>>>>,
+}
+
+impl OperationStore {
+    /// Creates a new, empty, `OperationStore`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts the operation with `name`, returning the handle through which
+    /// it is updated and completed.
+    ///
+    /// Names should end with `operations/{unique_id}`. Fails with
+    /// `Code::AlreadyExists` if the store holds an operation with `name`.
+    pub fn start(&self, name: impl Into<String>) -> Result<OperationHandle, Status> {
+        let name = name.into();
+        let mut operations = self.operations.lock().unwrap();
+        if operations.contains_key(&name) {
+            return Err(Status::already_exists(format!(
+                "operation {name} already exists"
+            )));
+        }
+
+        let (sender, state) = watch::channel(State {
+            operation: pb::Operation {
+                name: name.clone(),
+                ..Default::default()
+            },
+            cancelled: false,
+        });
+        operations.insert(name.clone(), sender);
+
+        Ok(OperationHandle {
+            name,
+            store: self.clone(),
+            state,
+        })
+    }
+
+    /// Returns the latest state of the operation with `name`.
+    ///
+    /// Fails with `Code::NotFound` if the store holds no operation with
+    /// `name`.
+    pub fn get(&self, name: &str) -> Result<pb::Operation, Status> {
+        let operations = self.operations.lock().unwrap();
+        let sender = operations.get(name).ok_or_else(|| not_found(name))?;
+        let operation = sender.borrow().operation.clone();
+        Ok(operation)
+    }
+
+    /// Cancels the operation with `name`, which is then done, failed with
+    /// `Code::Cancelled`. Operations already done are left as is.
+    ///
+    /// The task doing the work learns about the cancellation through
+    /// [`OperationHandle::cancelled`], and its result is then ignored.
+    ///
+    /// Fails with `Code::NotFound` if the store holds no operation with
+    /// `name`.
+    pub fn cancel(&self, name: &str) -> Result<(), Status> {
+        let operations = self.operations.lock().unwrap();
+        let sender = operations.get(name).ok_or_else(|| not_found(name))?;
+        sender.send_if_modified(|state| {
+            if state.operation.done {
+                return false;
+            }
+            state.cancelled = true;
+            state.operation.done = true;
+            state.operation.result = Some(pb::operation::Result::Error(crate::status_to_pb(
+                &Status::cancelled(format!("operation {name} was cancelled")),
+            )));
+            true
+        });
+        Ok(())
+    }
+
+    /// Deletes the operation with `name`, without cancelling it.
+    ///
+    /// Fails with `Code::NotFound` if the store holds no operation with
+    /// `name`.
+    pub fn delete(&self, name: &str) -> Result<(), Status> {
+        self.operations
+            .lock()
+            .unwrap()
+            .remove(name)
+            .map(drop)
+            .ok_or_else(|| not_found(name))
+    }
+
+    /// Waits until the operation with `name` is done, at most for `timeout`
+    /// if set, returning its latest state.
+    ///
+    /// Fails with `Code::NotFound` if the store holds no operation with
+    /// `name`.
+    pub async fn wait(
+        &self,
+        name: &str,
+        timeout: Option<Duration>,
+    ) -> Result<pb::Operation, Status> {
+        let mut state = {
+            let operations = self.operations.lock().unwrap();
+            operations
+                .get(name)
+                .ok_or_else(|| not_found(name))?
+                .subscribe()
+        };
+
+        let done = state.wait_for(|state| state.operation.done);
+        match timeout {
+            Some(timeout) => {
+                let _ = tokio::time::timeout(timeout, done).await;
+            }
+            None => {
+                let _ = done.await;
+            }
+        }
+
+        let operation = state.borrow().operation.clone();
+        Ok(operation)
+    }
+
+    // Lists the operations whose name starts with `parent/`, after the page
+    // token, the name of the last operation of the previous page.
+    fn list(
+        &self,
+        parent: &str,
+        page_size: usize,
+        page_token: &str,
+    ) -> (Vec<pb::Operation>, String) {
+        let prefix = if parent.is_empty() {
+            String::new()
+        } else {
+            format!("{parent}/")
+        };
+        let start = if page_token.is_empty() {
+            Bound::Unbounded
+        } else {
+            Bound::Excluded(page_token.to_string())
+        };
+
+        let operations = self.operations.lock().unwrap();
+        let mut matching = operations
+            .range((start, Bound::Unbounded))
+            .filter(|(name, _)| name.starts_with(&prefix))
+            .map(|(_, sender)| sender.borrow().operation.clone());
+
+        let page = matching.by_ref().take(page_size).collect::<Vec<_>>();
+        let next_page_token = match (page.last(), matching.next()) {
+            (Some(last), Some(_)) => last.name.clone(),
+            _ => String::new(),
+        };
+
+        (page, next_page_token)
+    }
+
+    fn update(&self, name: &str, update: impl FnOnce(&mut pb::Operation)) {
+        if let Some(sender) = self.operations.lock().unwrap().get(name) {
+            sender.send_if_modified(|state| {
+                if state.operation.done {
+                    return false;
+                }
+                update(&mut state.operation);
+                true
+            });
+        }
+    }
+}
+
+/// A handle on a started operation, through which the task doing the work
+/// reports its progress and result.
+///
+/// Updates of operations that are done, because they were cancelled, or that
+/// were deleted are ignored.
+#[derive(Clone, Debug)]
+pub struct OperationHandle {
+    name: String,
+    store: OperationStore,
+    state: watch::Receiver<State>,
+}
+
+impl OperationHandle {
+    /// Returns the name of the operation.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the latest state of the operation, to be returned by the
+    /// method that started it.
+    pub fn operation(&self) -> pb::Operation {
+        self.state.borrow().operation.clone()
+    }
+
+    /// Sets the metadata of the operation, typically its progress.
+    pub fn set_metadata<M: Name>(&self, metadata: &M) {
+        let metadata = to_any(metadata);
+        self.store.update(&self.name, |operation| {
+            operation.metadata = Some(metadata);
+        });
+    }
+
+    /// Completes the operation with the response or the error of the work.
+    ///
+    /// The details of the error, set with `tonic_types::StatusExt`, are kept.
+    pub fn complete<R: Name>(self, result: Result<R, Status>) {
+        let result = match result {
+            Ok(response) => pb::operation::Result::Response(to_any(&response)),
+            Err(status) => pb::operation::Result::Error(crate::status_to_pb(&status)),
+        };
+        self.store.update(&self.name, |operation| {
+            operation.done = true;
+            operation.result = Some(result);
+        });
+    }
+
+    /// Returns `true` if the operation was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.borrow().cancelled
+    }
+
+    /// Waits until the operation is cancelled, or deleted as nobody can read
+    /// its result anymore.
+    pub async fn cancelled(&self) {
+        let mut state = self.state.clone();
+        let _ = state.wait_for(|state| state.cancelled).await;
+    }
+}
+
+/// A service providing implementations of the `google.longrunning.Operations`
+/// methods over an [`OperationStore`].
+#[derive(Debug)]
+struct OperationsService {
+    store: OperationStore,
+}
+
+#[tonic::async_trait]
+impl Operations for OperationsService {
+    async fn list_operations(
+        &self,
+        request: Request<ListOperationsRequest>,
+    ) -> Result<Response<ListOperationsResponse>, Status> {
+        let request = request.into_inner();
+        if !request.filter.is_empty() {
+            return Err(Status::invalid_argument(
+                "filtering operations is not supported",
+            ));
+        }
+        let page_size = match usize::try_from(request.page_size) {
+            Ok(0) => DEFAULT_PAGE_SIZE,
+            Ok(page_size) => page_size.min(MAX_PAGE_SIZE),
+            Err(_) => return Err(Status::invalid_argument("page_size must not be negative")),
+        };
+
+        let (operations, next_page_token) =
+            self.store
+                .list(&request.name, page_size, &request.page_token);
+
+        Ok(Response::new(ListOperationsResponse {
+            operations,
+            next_page_token,
+        }))
+    }
+
+    async fn get_operation(
+        &self,
+        request: Request<GetOperationRequest>,
+    ) -> Result<Response<pb::Operation>, Status> {
+        self.store.get(&request.get_ref().name).map(Response::new)
+    }
+
+    async fn delete_operation(
+        &self,
+        request: Request<DeleteOperationRequest>,
+    ) -> Result<Response<()>, Status> {
+        self.store
+            .delete(&request.get_ref().name)
+            .map(Response::new)
+    }
+
+    async fn cancel_operation(
+        &self,
+        request: Request<CancelOperationRequest>,
+    ) -> Result<Response<()>, Status> {
+        self.store
+            .cancel(&request.get_ref().name)
+            .map(Response::new)
+    }
+
+    async fn wait_operation(
+        &self,
+        request: Request<WaitOperationRequest>,
+    ) -> Result<Response<pb::Operation>, Status> {
+        let request = request.into_inner();
+        let timeout = request
+            .timeout
+            .map(Duration::try_from)
+            .transpose()
+            .map_err(|_| Status::invalid_argument("timeout must not be negative"))?;
+
+        self.store
+            .wait(&request.name, timeout)
+            .await
+            .map(Response::new)
+    }
+}
+
+fn not_found(name: &str) -> Status {
+    Status::not_found(format!("operation {name} not found"))
+}
+
+fn to_any<M: Name>(message: &M) -> prost_types::Any {
+    prost_types::Any {
+        type_url: M::type_url(),
+        value: message.encode_to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::pb::operations_server::Operations;
+    use crate::pb::{
+        CancelOperationRequest, DeleteOperationRequest, GetOperationRequest, ListOperationsRequest,
+        WaitOperationRequest,
+    };
+    use crate::server::{OperationStore, OperationsService};
+    use crate::OperationExt;
+    use prost_types::Duration;
+    use tonic::{Code, Request, Status};
+
+    fn make_test_service() -> (OperationStore, OperationsService) {
+        let store = OperationStore::new();
+        let service = OperationsService {
+            store: store.clone(),
+        };
+        (store, service)
+    }
+
+    fn seconds(seconds: i64) -> Duration {
+        Duration { seconds, nanos: 0 }
+    }
+
+    #[tokio::test]
+    async fn test_operation_lifecycle() {
+        let (store, service) = make_test_service();
+
+        let handle = store.start("operations/1").unwrap();
+        assert_eq!(
+            store.start("operations/1").unwrap_err().code(),
+            Code::AlreadyExists
+        );
+
+        handle.set_metadata(&seconds(1));
+        let operation = service
+            .get_operation(Request::new(GetOperationRequest {
+                name: "operations/1".into(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!operation.done);
+        assert_eq!(operation.metadata::<Duration>().unwrap(), Some(seconds(1)));
+
+        handle.complete(Ok(seconds(2)));
+        let operation = store.get("operations/1").unwrap();
+        assert_eq!(operation.result::<Duration>().unwrap().unwrap(), seconds(2));
+
+        service
+            .delete_operation(Request::new(DeleteOperationRequest {
+                name: "operations/1".into(),
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get("operations/1").unwrap_err().code(),
+            Code::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cancel_operation() {
+        let (store, service) = make_test_service();
+        let handle = store.start("operations/1").unwrap();
+
+        service
+            .cancel_operation(Request::new(CancelOperationRequest {
+                name: "operations/1".into(),
+            }))
+            .await
+            .unwrap();
+        handle.cancelled().await;
+        assert!(handle.is_cancelled());
+
+        // The result of the work is ignored once cancelled.
+        handle.complete(Ok(seconds(2)));
+        let operation = store.get("operations/1").unwrap();
+        let err = operation.result::<Duration>().unwrap().unwrap_err();
+        assert_eq!(err.code(), Code::Cancelled);
+    }
+
+    #[tokio::test]
+    async fn test_list_operations() {
+        let (store, service) = make_test_service();
+        for name in ["operations/1", "operations/2", "operations/3", "other/1"] {
+            store.start(name).unwrap();
+        }
+
+        let list = |page_token: String| {
+            service.list_operations(Request::new(ListOperationsRequest {
+                name: "operations".into(),
+                page_size: 2,
+                page_token,
+                ..Default::default()
+            }))
+        };
+
+        let first = list(String::new()).await.unwrap().into_inner();
+        let names = first.operations.iter().map(|op| op.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["operations/1", "operations/2"]);
+        assert!(!first.next_page_token.is_empty());
+
+        let second = list(first.next_page_token).await.unwrap().into_inner();
+        let names = second.operations.iter().map(|op| op.name.as_str());
+        assert_eq!(names.collect::<Vec<_>>(), ["operations/3"]);
+        assert!(second.next_page_token.is_empty());
+
+        let err = service
+            .list_operations(Request::new(ListOperationsRequest {
+                filter: "done = true".into(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_wait_operation() {
+        let (store, service) = make_test_service();
+        let handle = store.start("operations/1").unwrap();
+
+        let operation = service
+            .wait_operation(Request::new(WaitOperationRequest {
+                name: "operations/1".into(),
+                timeout: Some(seconds(1)),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!operation.done);
+
+        let waiter = tokio::spawn(async move {
+            service
+                .wait_operation(Request::new(WaitOperationRequest {
+                    name: "operations/1".into(),
+                    timeout: None,
+                }))
+                .await
+        });
+        handle.complete::<Duration>(Err(Status::internal("disk full")));
+
+        let operation = waiter.await.unwrap().unwrap().into_inner();
+        let err = operation.result::<Duration>().unwrap().unwrap_err();
+        assert_eq!(err.code(), Code::Internal);
+        assert_eq!(err.message(), "disk full");
+    }
+}
@@ -0,0 +1,335 @@
+//! An in-process test server for `tonic` services.
+//!
+//! [`TestServer::start`] serves a service on a local port, and returns a server connected to by
+//! its [`channel`](TestServer::channel). The server records the calls it receives, to inspect
+//! their requests and assert their counts, and answers them with the responses injected into
+//! their methods, delayed or failed, instead of the responses of the service:
+//!
+//! ```
+//! use tonic::{Code, Status};
+//! use tonic_health::pb::{health_client::HealthClient, HealthCheckRequest};
+//! use tonic_mock::{Injection, TestServer};
+//!
+//! const CHECK: &str = "/grpc.health.v1.Health/Check";
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let (_, health) = tonic_health::server::health_reporter();
+//! let server = TestServer::start(health).await;
+//! let mut client = HealthClient::new(server.channel());
+//!
+//! server.inject(
+//!     CHECK,
+//!     Injection::new().status(Status::unavailable("down")).times(1),
+//! );
+//! let status = client
+//!     .check(HealthCheckRequest::default())
+//!     .await
+//!     .unwrap_err();
+//! assert_eq!(status.code(), Code::Unavailable);
+//! client.check(HealthCheckRequest::default()).await.unwrap();
+//!
+//! server.assert_calls(CHECK, 2);
+//! # }
+//! ```
+//!
+//! Servers shut down once dropped.
+
+#![doc(
+    html_logo_url = "https://raw.githubusercontent.com/tokio-rs/website/master/public/img/icons/tonic.svg"
+)]
+#![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
+#![doc(test(no_crate_inject, attr(deny(rust_2018_idioms))))]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::{net::TcpListener, sync::oneshot};
+use tonic::{
+    body::Body,
+    server::NamedService,
+    service::{RecordLayer, Recording, Routes},
+    transport::{server::TcpIncoming, Channel, Endpoint, Server},
+    Status,
+};
+use tower_layer::layer_fn;
+use tower_service::Service;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+/// A response injected into the calls of a method, instead of the response of the service.
+///
+/// The calls are first delayed, then answered with the injected status, if any, or by the
+/// service.
+#[derive(Debug, Clone, Default)]
+pub struct Injection {
+    delay: Option<Duration>,
+    status: Option<Status>,
+    // The number of calls left to inject into, or `None` to inject into all of them.
+    remaining: Option<usize>,
+}
+
+impl Injection {
+    /// Create a new `Injection`, answering the calls with the response of the service.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Delay the calls by `delay`.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Answer the calls with `status`, without calling the service.
+    pub fn status(mut self, status: Status) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Inject into the next `times` calls only, instead of all the calls of the method.
+    pub fn times(mut self, times: usize) -> Self {
+        self.remaining = Some(times);
+        self
+    }
+}
+
+// The calls received by a server, and the responses injected into them.
+#[derive(Debug, Default)]
+struct Calls {
+    counts: HashMap<String, usize>,
+    injections: HashMap<String, VecDeque<Injection>>,
+}
+
+impl Calls {
+    // The injection into the next call to the method of `path`.
+    fn injection(&mut self, path: &str) -> Option<Injection> {
+        let injections = self.injections.get_mut(path)?;
+        loop {
+            let injection = injections.front_mut()?;
+            match &mut injection.remaining {
+                None => return Some(injection.clone()),
+                Some(0) => {
+                    injections.pop_front();
+                }
+                Some(remaining) => {
+                    *remaining -= 1;
+                    return Some(injection.clone());
+                }
+            }
+        }
+    }
+}
+
+/// A server serving a service in-process, for tests.
+///
+/// See the [crate docs](crate) for more details.
+#[derive(Debug)]
+pub struct TestServer {
+    channel: Channel,
+    local_addr: SocketAddr,
+    recorder: RecordLayer,
+    calls: Arc<Mutex<Calls>>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl TestServer {
+    /// Start a server serving `service` on a local port, and connect to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the server fails to bind its port, or to be connected to.
+    pub async fn start<S>(service: S) -> Self
+    where
+        S: Service<http::Request<Body>, Response = http::Response<Body>, Error = Infallible>
+            + NamedService
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        Self::start_routes(Routes::new(service)).await
+    }
+
+    /// Start a server serving `routes`, e.g. several services, on a local port, and connect to
+    /// it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the server fails to bind its port, or to be connected to.
+    pub async fn start_routes(routes: Routes) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("failed to bind the test server");
+        let local_addr = listener
+            .local_addr()
+            .expect("failed to bind the test server");
+
+        let recorder = RecordLayer::new();
+        let calls = Arc::new(Mutex::new(Calls::default()));
+        let mock_calls = calls.clone();
+        let (shutdown, signal) = oneshot::channel::<()>();
+        let server = Server::builder()
+            .layer(recorder.clone())
+            .layer(layer_fn(move |inner| Mock {
+                inner,
+                calls: mock_calls.clone(),
+            }))
+            .add_routes(routes)
+            .serve_with_incoming_shutdown(TcpIncoming::from(listener), async {
+                let _ = signal.await;
+            });
+        tokio::spawn(async move { server.await.expect("test server failed") });
+
+        let channel = Endpoint::from_shared(format!("http://{local_addr}"))
+            .expect("invalid test server address")
+            .connect()
+            .await
+            .expect("failed to connect to the test server");
+        Self {
+            channel,
+            local_addr,
+            recorder,
+            calls,
+            _shutdown: shutdown,
+        }
+    }
+
+    /// A channel connected to the server.
+    pub fn channel(&self) -> Channel {
+        self.channel.clone()
+    }
+
+    /// The local address the server is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Inject `injection` into the calls to the method of `path`, e.g.
+    /// `/helloworld.Greeter/SayHello`.
+    ///
+    /// Injections into the same method apply in turn: each one once the calls it injects into,
+    /// see [`Injection::times`], are over.
+    pub fn inject(&self, path: impl Into<String>, injection: Injection) {
+        self.calls
+            .lock()
+            .unwrap()
+            .injections
+            .entry(path.into())
+            .or_default()
+            .push_back(injection);
+    }
+
+    /// Remove the injections into all methods, answering their calls by the service again.
+    pub fn clear_injections(&self) {
+        self.calls.lock().unwrap().injections.clear();
+    }
+
+    /// The number of calls received to the method of `path`, including the ones answered by an
+    /// injection.
+    pub fn calls(&self, path: &str) -> usize {
+        self.calls
+            .lock()
+            .unwrap()
+            .counts
+            .get(path)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Assert that the server received `expected` calls to the method of `path`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of calls received differs.
+    #[track_caller]
+    pub fn assert_calls(&self, path: &str, expected: usize) {
+        let calls = self.calls(path);
+        assert_eq!(
+            calls, expected,
+            "expected {expected} calls to {path}, received {calls}"
+        );
+    }
+
+    /// The calls received, once their response ended, with their metadata, messages and status.
+    ///
+    /// The calls answered with an injected status are recorded without their messages, as their
+    /// requests aren't read.
+    pub fn recording(&self) -> Recording {
+        self.recorder.recording()
+    }
+
+    /// The request messages received by the method of `path`, decoded as `M`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a request fails to be decoded as `M`.
+    pub fn requests<M>(&self, path: &str) -> Vec<M>
+    where
+        M: prost::Message + Default,
+    {
+        self.recording()
+            .calls()
+            .iter()
+            .filter(|call| call.method() == path)
+            .flat_map(|call| call.requests())
+            .map(|request| {
+                M::decode(request.clone())
+                    .unwrap_or_else(|e| panic!("failed to decode a request to {path}: {e}"))
+            })
+            .collect()
+    }
+}
+
+// Middleware counting the calls of the routes, and injecting responses into them.
+#[derive(Debug, Clone)]
+struct Mock {
+    inner: Routes,
+    calls: Arc<Mutex<Calls>>,
+}
+
+impl Service<http::Request<Body>> for Mock {
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Service::<http::Request<Body>>::poll_ready(&mut self.inner, cx)
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let injection = {
+            let mut calls = self.calls.lock().unwrap();
+            let path = req.uri().path();
+            *calls.counts.entry(path.to_owned()).or_default() += 1;
+            calls.injection(path)
+        };
+        let Some(injection) = injection else {
+            return Box::pin(self.inner.call(req));
+        };
+
+        // The routes are called once the delay elapsed, so take the routes driven to readiness
+        // and leave a clone in their place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            if let Some(delay) = injection.delay {
+                tokio::time::sleep(delay).await;
+            }
+            match injection.status {
+                Some(status) => Ok(status.into_http()),
+                None => inner.call(req).await,
+            }
+        })
+    }
+}
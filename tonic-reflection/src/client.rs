@@ -0,0 +1,296 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+
+use prost::{DecodeError, Message};
+use prost_reflect::{DescriptorError, DescriptorPool};
+use prost_types::FileDescriptorProto;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+use tonic::codegen::{Body, Bytes, StdError};
+use tonic::{Code, Status, Streaming};
+
+use crate::pb::v1::server_reflection_client::ServerReflectionClient;
+use crate::pb::v1::server_reflection_request::MessageRequest;
+use crate::pb::v1::server_reflection_response::MessageResponse;
+use crate::pb::v1::{ServerReflectionRequest, ServerReflectionResponse};
+
+/// A client of the v1 gRPC Reflection Service, resolving the descriptors of the services of a
+/// server.
+#[derive(Debug, Clone)]
+pub struct ReflectionClient<T> {
+    inner: ServerReflectionClient<T>,
+    host: String,
+}
+
+impl<T> ReflectionClient<T>
+where
+    T: tonic::client::GrpcService<tonic::body::Body>,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    /// Create a new `ReflectionClient` sending requests through `inner`.
+    pub fn new(inner: T) -> Self {
+        ServerReflectionClient::new(inner).into()
+    }
+
+    /// Set the host of the requests, for servers serving several virtual hosts.
+    pub fn with_host(mut self, host: impl Into<String>) -> Self {
+        self.host = host.into();
+        self
+    }
+
+    /// List the fully-qualified names of the services advertised by the server.
+    pub async fn list_services(&mut self) -> Result<Vec<String>, Error> {
+        let mut session = self.session().await?;
+        match session
+            .request(MessageRequest::ListServices(String::new()))
+            .await?
+        {
+            MessageResponse::ListServicesResponse(response) => Ok(response
+                .service
+                .into_iter()
+                .map(|service| service.name)
+                .collect()),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    /// Fetch the file descriptor named `filename`, along with any of its dependencies sent by
+    /// the server.
+    pub async fn file_by_filename(
+        &mut self,
+        filename: impl Into<String>,
+    ) -> Result<Vec<FileDescriptorProto>, Error> {
+        let mut session = self.session().await?;
+        session
+            .files(MessageRequest::FileByFilename(filename.into()))
+            .await
+    }
+
+    /// Fetch the file descriptor defining the fully-qualified `symbol`, along with any of its
+    /// dependencies sent by the server.
+    pub async fn file_containing_symbol(
+        &mut self,
+        symbol: impl Into<String>,
+    ) -> Result<Vec<FileDescriptorProto>, Error> {
+        let mut session = self.session().await?;
+        session
+            .files(MessageRequest::FileContainingSymbol(symbol.into()))
+            .await
+    }
+
+    /// Build a [`DescriptorPool`] of the files defining the fully-qualified `symbols`, and of
+    /// their dependencies, resolved transitively.
+    pub async fn descriptor_pool_for_symbols<I>(
+        &mut self,
+        symbols: I,
+    ) -> Result<DescriptorPool, Error>
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let mut session = self.session().await?;
+        let mut files = HashMap::new();
+
+        for symbol in symbols {
+            let symbol = symbol.into();
+            // Symbols of the files already resolved are not fetched again.
+            if files
+                .values()
+                .any(|file: &FileDescriptorProto| defines(file, &symbol))
+            {
+                continue;
+            }
+
+            let fetched = session
+                .files(MessageRequest::FileContainingSymbol(symbol))
+                .await?;
+            session.resolve(&mut files, fetched).await?;
+        }
+
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_protos(files.into_values())?;
+        Ok(pool)
+    }
+
+    /// Build a [`DescriptorPool`] of the files defining the services advertised by the server,
+    /// and of their dependencies, resolved transitively.
+    pub async fn descriptor_pool(&mut self) -> Result<DescriptorPool, Error> {
+        let services = self.list_services().await?;
+        self.descriptor_pool_for_symbols(services).await
+    }
+
+    async fn session(&mut self) -> Result<Session, Error> {
+        let (tx, rx) = mpsc::channel(1);
+        let responses = self
+            .inner
+            .server_reflection_info(ReceiverStream::new(rx))
+            .await?
+            .into_inner();
+
+        Ok(Session {
+            host: self.host.clone(),
+            tx,
+            responses,
+        })
+    }
+}
+
+impl<T> From<ServerReflectionClient<T>> for ReflectionClient<T> {
+    fn from(inner: ServerReflectionClient<T>) -> Self {
+        Self {
+            inner,
+            host: String::new(),
+        }
+    }
+}
+
+// A `ServerReflectionInfo` stream sending the requests one at a time.
+struct Session {
+    host: String,
+    tx: mpsc::Sender<ServerReflectionRequest>,
+    responses: Streaming<ServerReflectionResponse>,
+}
+
+impl Session {
+    async fn request(&mut self, request: MessageRequest) -> Result<MessageResponse, Error> {
+        let request = ServerReflectionRequest {
+            host: self.host.clone(),
+            message_request: Some(request),
+        };
+        if self.tx.send(request).await.is_err() {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        match self.responses.next().await {
+            Some(Ok(response)) => match response.message_response {
+                Some(MessageResponse::ErrorResponse(error)) => Err(Error::Status(Status::new(
+                    Code::from_i32(error.error_code),
+                    error.error_message,
+                ))),
+                Some(response) => Ok(response),
+                None => Err(Error::UnexpectedResponse),
+            },
+            Some(Err(status)) => Err(Error::Status(status)),
+            None => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    async fn files(&mut self, request: MessageRequest) -> Result<Vec<FileDescriptorProto>, Error> {
+        match self.request(request).await? {
+            MessageResponse::FileDescriptorResponse(response) => response
+                .file_descriptor_proto
+                .iter()
+                .map(|encoded| FileDescriptorProto::decode(encoded.as_slice()))
+                .collect::<Result<_, _>>()
+                .map_err(Error::from),
+            _ => Err(Error::UnexpectedResponse),
+        }
+    }
+
+    // Adds `fetched` to `files`, fetching the dependencies missing from both.
+    async fn resolve(
+        &mut self,
+        files: &mut HashMap<String, FileDescriptorProto>,
+        fetched: Vec<FileDescriptorProto>,
+    ) -> Result<(), Error> {
+        let mut queue = VecDeque::from(fetched);
+        let mut requested = HashSet::new();
+
+        while let Some(file) = queue.pop_front() {
+            if files.contains_key(file.name()) {
+                continue;
+            }
+
+            for dependency in &file.dependency {
+                if !files.contains_key(dependency)
+                    && !queue.iter().any(|queued| queued.name() == dependency)
+                    && requested.insert(dependency.clone())
+                {
+                    let fetched = self
+                        .files(MessageRequest::FileByFilename(dependency.clone()))
+                        .await?;
+                    queue.extend(fetched);
+                }
+            }
+
+            files.insert(file.name().to_string(), file);
+        }
+
+        Ok(())
+    }
+}
+
+// Whether `file` defines the fully-qualified `symbol`, as a top-level declaration or a
+// member of one.
+fn defines(file: &FileDescriptorProto, symbol: &str) -> bool {
+    let relative = match file.package() {
+        "" => symbol,
+        package => match symbol
+            .strip_prefix(package)
+            .and_then(|rest| rest.strip_prefix('.'))
+        {
+            Some(relative) => relative,
+            None => return false,
+        },
+    };
+    let top_level = relative.split('.').next().unwrap_or_default();
+
+    file.message_type.iter().any(|m| m.name() == top_level)
+        || file.enum_type.iter().any(|e| e.name() == top_level)
+        || file.service.iter().any(|s| s.name() == top_level)
+}
+
+/// Represents an error of a gRPC Reflection Service client.
+#[derive(Debug)]
+pub enum Error {
+    /// The server failed a request.
+    Status(Status),
+    /// An error was encountered decoding a `prost_types::FileDescriptorProto` sent by the server.
+    DecodeError(DecodeError),
+    /// The file descriptors sent by the server do not form a valid `DescriptorPool`.
+    DescriptorError(DescriptorError),
+    /// The server sent a response not matching the request, or ended the stream.
+    UnexpectedResponse,
+}
+
+impl From<Status> for Error {
+    fn from(e: Status) -> Self {
+        Error::Status(e)
+    }
+}
+
+impl From<DecodeError> for Error {
+    fn from(e: DecodeError) -> Self {
+        Error::DecodeError(e)
+    }
+}
+
+impl From<DescriptorError> for Error {
+    fn from(e: DescriptorError) -> Self {
+        Error::DescriptorError(e)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Status(e) => Some(e),
+            Error::DecodeError(e) => Some(e),
+            Error::DescriptorError(e) => Some(e),
+            Error::UnexpectedResponse => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Status(status) => write!(f, "reflection request failed - {status}"),
+            Error::DecodeError(_) => f.write_str("error decoding FileDescriptorProto from buffer"),
+            Error::DescriptorError(e) => write!(f, "invalid file descriptors - {e}"),
+            Error::UnexpectedResponse => f.write_str("unexpected reflection response"),
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor, ReflectMessage,
+};
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::codegen::{Body, Bytes, StdError};
+use tonic::{IntoRequest, IntoStreamingRequest, Request, Response, Status, Streaming};
+
+/// A client invoking the methods of a [`DescriptorPool`] at runtime, with messages of the
+/// methods represented as [`DynamicMessage`]s.
+///
+/// The descriptor pool of a server may be resolved with the
+/// [`ReflectionClient`](crate::client::ReflectionClient). With the `serde` feature of
+/// `prost-reflect`, the messages may be read from and written to JSON.
+#[derive(Debug, Clone)]
+pub struct DynamicClient<T> {
+    inner: tonic::client::Grpc<T>,
+    pool: DescriptorPool,
+}
+
+impl<T> DynamicClient<T>
+where
+    T: tonic::client::GrpcService<tonic::body::Body>,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    /// Create a new `DynamicClient` invoking the methods of `pool` through `inner`.
+    pub fn new(inner: T, pool: DescriptorPool) -> Self {
+        Self {
+            inner: tonic::client::Grpc::new(inner),
+            pool,
+        }
+    }
+
+    /// The descriptor pool of the methods.
+    pub fn pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Find the method named `full_method`, either as a path such as
+    /// `/helloworld.Greeter/SayHello`, or as the fully-qualified name of the method such as
+    /// `helloworld.Greeter.SayHello`.
+    ///
+    /// Fails with `Code::NotFound` if the pool has no such method.
+    pub fn method(&self, full_method: &str) -> Result<MethodDescriptor, Status> {
+        let name = full_method.trim_start_matches('/');
+        let (service, method) = name
+            .split_once('/')
+            .or_else(|| name.rsplit_once('.'))
+            .ok_or_else(|| Status::invalid_argument(format!("invalid method '{full_method}'")))?;
+
+        self.pool
+            .get_service_by_name(service)
+            .and_then(|service| service.methods().find(|m| m.name() == method))
+            .ok_or_else(|| Status::not_found(format!("method '{full_method}' not found")))
+    }
+
+    /// Send a unary request to the method named `full_method`.
+    pub async fn unary(
+        &mut self,
+        full_method: &str,
+        request: impl IntoRequest<DynamicMessage>,
+    ) -> Result<Response<DynamicMessage>, Status> {
+        let method = self.method(full_method)?;
+        check_kind(&method, false, false)?;
+        let request = request.into_request();
+        check_message(&method, request.get_ref())?;
+
+        let (request, path, codec) = self.prepare(&method, request).await?;
+        self.inner.unary(request, path, codec).await
+    }
+
+    /// Send a client side streaming request to the method named `full_method`.
+    pub async fn client_streaming(
+        &mut self,
+        full_method: &str,
+        request: impl IntoStreamingRequest<Message = DynamicMessage>,
+    ) -> Result<Response<DynamicMessage>, Status> {
+        let method = self.method(full_method)?;
+        check_kind(&method, true, false)?;
+
+        let (request, path, codec) = self
+            .prepare(&method, request.into_streaming_request())
+            .await?;
+        self.inner.client_streaming(request, path, codec).await
+    }
+
+    /// Send a server side streaming request to the method named `full_method`.
+    pub async fn server_streaming(
+        &mut self,
+        full_method: &str,
+        request: impl IntoRequest<DynamicMessage>,
+    ) -> Result<Response<Streaming<DynamicMessage>>, Status> {
+        let method = self.method(full_method)?;
+        check_kind(&method, false, true)?;
+        let request = request.into_request();
+        check_message(&method, request.get_ref())?;
+
+        let (request, path, codec) = self.prepare(&method, request).await?;
+        self.inner.server_streaming(request, path, codec).await
+    }
+
+    /// Send a bi-directional streaming request to the method named `full_method`.
+    pub async fn streaming(
+        &mut self,
+        full_method: &str,
+        request: impl IntoStreamingRequest<Message = DynamicMessage>,
+    ) -> Result<Response<Streaming<DynamicMessage>>, Status> {
+        let method = self.method(full_method)?;
+        check_kind(&method, true, true)?;
+
+        let (request, path, codec) = self
+            .prepare(&method, request.into_streaming_request())
+            .await?;
+        self.inner.streaming(request, path, codec).await
+    }
+
+    async fn prepare<R>(
+        &mut self,
+        method: &MethodDescriptor,
+        request: Request<R>,
+    ) -> Result<(Request<R>, PathAndQuery, DynamicCodec), Status> {
+        self.inner.ready().await.map_err(|e| {
+            Status::unknown(format!(
+                "Service was not ready: {}",
+                Into::<StdError>::into(e)
+            ))
+        })?;
+
+        let service = method.parent_service();
+        let path = PathAndQuery::try_from(format!("/{}/{}", service.full_name(), method.name()))
+            .map_err(|_| Status::invalid_argument("invalid method path"))?;
+
+        Ok((request, path, DynamicCodec::new(method.output())))
+    }
+}
+
+fn check_kind(
+    method: &MethodDescriptor,
+    client_streaming: bool,
+    server_streaming: bool,
+) -> Result<(), Status> {
+    if method.is_client_streaming() != client_streaming
+        || method.is_server_streaming() != server_streaming
+    {
+        return Err(Status::invalid_argument(format!(
+            "method '{}' is {}",
+            method.full_name(),
+            match (method.is_client_streaming(), method.is_server_streaming()) {
+                (false, false) => "unary",
+                (true, false) => "client side streaming",
+                (false, true) => "server side streaming",
+                (true, true) => "bi-directional streaming",
+            }
+        )));
+    }
+    Ok(())
+}
+
+fn check_message(method: &MethodDescriptor, message: &DynamicMessage) -> Result<(), Status> {
+    if message.descriptor() != method.input() {
+        return Err(Status::invalid_argument(format!(
+            "method '{}' expects a '{}' request, not '{}'",
+            method.full_name(),
+            method.input().full_name(),
+            message.descriptor().full_name()
+        )));
+    }
+    Ok(())
+}
+
+/// A [`Codec`] encoding [`DynamicMessage`]s, and decoding them as messages of a
+/// [`MessageDescriptor`].
+#[derive(Debug, Clone)]
+pub struct DynamicCodec {
+    decode: MessageDescriptor,
+}
+
+impl DynamicCodec {
+    /// Create a new `DynamicCodec` decoding messages as `decode` messages.
+    pub fn new(decode: MessageDescriptor) -> Self {
+        Self { decode }
+    }
+}
+
+impl Codec for DynamicCodec {
+    type Encode = DynamicMessage;
+    type Decode = DynamicMessage;
+
+    type Encoder = DynamicEncoder;
+    type Decoder = DynamicDecoder;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        DynamicEncoder { _priv: () }
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        DynamicDecoder {
+            descriptor: self.decode.clone(),
+        }
+    }
+}
+
+/// A [`Encoder`] that knows how to encode [`DynamicMessage`]s.
+#[derive(Debug, Clone)]
+pub struct DynamicEncoder {
+    _priv: (),
+}
+
+impl Encoder for DynamicEncoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, buf: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        item.encode(buf)
+            .expect("Message only errors if not enough space");
+
+        Ok(())
+    }
+}
+
+/// A [`Decoder`] that knows how to decode [`DynamicMessage`]s of a [`MessageDescriptor`].
+#[derive(Debug, Clone)]
+pub struct DynamicDecoder {
+    descriptor: MessageDescriptor,
+}
+
+impl Decoder for DynamicDecoder {
+    type Item = DynamicMessage;
+    type Error = Status;
+
+    fn decode(&mut self, buf: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        DynamicMessage::decode(self.descriptor.clone(), buf)
+            .map(Some)
+            .map_err(|error| Status::internal(error.to_string()))
+    }
+}
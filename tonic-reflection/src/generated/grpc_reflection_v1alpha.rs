@@ -144,6 +144,32 @@ pub struct ErrorResponse {
     #[prost(string, tag = "2")]
     pub error_message: ::prost::alloc::string::String,
 }
+/// Names and paths of the methods of the `grpc.reflection.v1alpha.ServerReflection` service.
+pub mod server_reflection_methods {
+    #![allow(dead_code)]
+    /// The fully qualified name of the service.
+    pub const SERVICE_NAME: &str = "grpc.reflection.v1alpha.ServerReflection";
+    /// Constants of the `ServerReflectionInfo` method.
+    pub mod server_reflection_info {
+        /// The name of the method.
+        pub const NAME: &str = "ServerReflectionInfo";
+        /// The path of the method in requests.
+        pub const PATH: &str = "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo";
+        /// The descriptor of the method.
+        pub const DESCRIPTOR: tonic::MethodDescriptor = tonic::MethodDescriptor::new(
+            super::SERVICE_NAME,
+            NAME,
+            PATH,
+            true,
+            true,
+        );
+    }
+    /// The descriptors of the methods of the service, in declaration
+    /// order.
+    pub const METHODS: &[tonic::MethodDescriptor] = &[
+        server_reflection_info::DESCRIPTOR,
+    ];
+}
 /// Generated client implementations.
 pub mod server_reflection_client {
     #![allow(
@@ -257,6 +283,20 @@ pub mod server_reflection_client {
                 );
             self.inner.streaming(req, path, codec).await
         }
+        /// Calls `server_reflection_info` with a request stream of the messages of `messages`.
+        pub async fn server_reflection_info_from_iter<I>(
+            &mut self,
+            messages: I,
+        ) -> std::result::Result<
+            tonic::Response<tonic::codec::Streaming<super::ServerReflectionResponse>>,
+            tonic::Status,
+        >
+        where
+            I: IntoIterator<Item = super::ServerReflectionRequest>,
+            I::IntoIter: std::marker::Send + 'static,
+        {
+            self.server_reflection_info(tokio_stream::iter(messages)).await
+        }
     }
 }
 /// Generated server implementations.
@@ -296,6 +336,7 @@ pub mod server_reflection_server {
         inner: Arc<T>,
         accept_compression_encodings: EnabledCompressionEncodings,
         send_compression_encodings: EnabledCompressionEncodings,
+        send_compression_policy: Option<Arc<dyn CompressionPolicy>>,
         max_decoding_message_size: Option<usize>,
         max_encoding_message_size: Option<usize>,
     }
@@ -308,6 +349,7 @@ pub mod server_reflection_server {
                 inner,
                 accept_compression_encodings: Default::default(),
                 send_compression_encodings: Default::default(),
+                send_compression_policy: None,
                 max_decoding_message_size: None,
                 max_encoding_message_size: None,
             }
@@ -321,6 +363,15 @@ pub mod server_reflection_server {
         {
             InterceptedService::new(Self::new(inner), interceptor)
         }
+        pub fn with_state<S>(
+            inner: T,
+            state: Arc<S>,
+        ) -> InterceptedService<Self, tonic::server::State<S>>
+        where
+            S: std::marker::Send + std::marker::Sync + 'static,
+        {
+            Self::with_interceptor(inner, tonic::server::State(state))
+        }
         /// Enable decompressing requests with the given encoding.
         #[must_use]
         pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
@@ -333,6 +384,16 @@ pub mod server_reflection_server {
             self.send_compression_encodings.enable(encoding);
             self
         }
+        /// Decide whether and how responses are compressed with the given policy, in place of the
+        /// encodings enabled with `send_compressed`.
+        #[must_use]
+        pub fn send_compression_policy(
+            mut self,
+            policy: impl CompressionPolicy,
+        ) -> Self {
+            self.send_compression_policy = Some(Arc::new(policy));
+            self
+        }
         /// Limits the maximum size of a decoded message.
         ///
         /// Default: `4MB`
@@ -399,6 +460,7 @@ pub mod server_reflection_server {
                     }
                     let accept_compression_encodings = self.accept_compression_encodings;
                     let send_compression_encodings = self.send_compression_encodings;
+                    let send_compression_policy = self.send_compression_policy.clone();
                     let max_decoding_message_size = self.max_decoding_message_size;
                     let max_encoding_message_size = self.max_encoding_message_size;
                     let inner = self.inner.clone();
@@ -410,6 +472,7 @@ pub mod server_reflection_server {
                                 accept_compression_encodings,
                                 send_compression_encodings,
                             )
+                            .apply_compression_policy(send_compression_policy)
                             .apply_max_message_size_config(
                                 max_decoding_message_size,
                                 max_encoding_message_size,
@@ -448,6 +511,7 @@ pub mod server_reflection_server {
                 inner,
                 accept_compression_encodings: self.accept_compression_encodings,
                 send_compression_encodings: self.send_compression_encodings,
+                send_compression_policy: self.send_compression_policy.clone(),
                 max_decoding_message_size: self.max_decoding_message_size,
                 max_encoding_message_size: self.max_encoding_message_size,
             }
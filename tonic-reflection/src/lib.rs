@@ -64,3 +64,11 @@ pub mod pb {
 /// Implementation of the server component of gRPC Server Reflection.
 #[cfg(feature = "server")]
 pub mod server;
+
+/// Implementation of the client component of gRPC Server Reflection.
+#[cfg(feature = "client")]
+pub mod client;
+
+/// A client invoking methods at runtime, from their descriptors.
+#[cfg(feature = "client")]
+pub mod dynamic;
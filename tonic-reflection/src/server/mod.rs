@@ -1,5 +1,5 @@
-use std::collections::HashMap;
-use std::fmt::{Display, Formatter};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::{self, Display, Formatter};
 use std::sync::Arc;
 
 use prost::{DecodeError, Message};
@@ -7,6 +7,7 @@ use prost_types::{
     DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
     FileDescriptorSet,
 };
+use tonic::metadata::MetadataMap;
 use tonic::Status;
 
 /// v1 interface for the gRPC Reflection Service server.
@@ -14,8 +15,9 @@ pub mod v1;
 /// v1alpha interface for the gRPC Reflection Service server.
 pub mod v1alpha;
 
+type RequestFilter = Arc<dyn Fn(&MetadataMap) -> bool + Send + Sync>;
+
 /// A builder used to construct a gRPC Reflection Service.
-#[derive(Debug)]
 pub struct Builder<'b> {
     file_descriptor_sets: Vec<FileDescriptorSet>,
     encoded_file_descriptor_sets: Vec<&'b [u8]>,
@@ -23,6 +25,8 @@ pub struct Builder<'b> {
 
     service_names: Vec<String>,
     use_all_service_names: bool,
+    hide_unadvertised: bool,
+    request_filter: Option<RequestFilter>,
 }
 
 impl<'b> Builder<'b> {
@@ -35,6 +39,8 @@ impl<'b> Builder<'b> {
 
             service_names: Vec::new(),
             use_all_service_names: true,
+            hide_unadvertised: false,
+            request_filter: None,
         }
     }
 
@@ -73,6 +79,27 @@ impl<'b> Builder<'b> {
         self
     }
 
+    /// Only serve the files declaring the services advertised with
+    /// [`Builder::with_service_name`], and their dependencies. This is disabled by default - set
+    /// `hide` to true to enable.
+    ///
+    /// When enabled, the symbols and files of the services which are not advertised cannot be
+    /// resolved either, keeping internal services undiscoverable.
+    pub fn hide_unadvertised(mut self, hide: bool) -> Self {
+        self.hide_unadvertised = hide;
+        self
+    }
+
+    /// Only serve the reflection requests whose metadata satisfies `filter`, failing the others
+    /// with `Code::PermissionDenied`.
+    pub fn with_request_filter<F>(mut self, filter: F) -> Self
+    where
+        F: Fn(&MetadataMap) -> bool + Send + Sync + 'static,
+    {
+        self.request_filter = Some(Arc::new(filter));
+        self
+    }
+
     /// Build a v1 gRPC Reflection Service to be served via Tonic.
     pub fn build_v1(
         mut self,
@@ -87,6 +114,8 @@ impl<'b> Builder<'b> {
                 self.encoded_file_descriptor_sets,
                 self.file_descriptor_sets,
                 self.use_all_service_names,
+                self.hide_unadvertised,
+                self.request_filter,
             )?),
         ))
     }
@@ -106,16 +135,72 @@ impl<'b> Builder<'b> {
                 self.encoded_file_descriptor_sets,
                 self.file_descriptor_sets,
                 self.use_all_service_names,
+                self.hide_unadvertised,
+                self.request_filter,
             )?),
         ))
     }
+
+    /// Build both a v1 and a v1alpha gRPC Reflection Service to be served via Tonic, sharing
+    /// the registered file descriptor sets.
+    ///
+    /// Serving both versions supports the clients still probing the v1alpha service first.
+    pub fn build_v1_and_v1alpha(
+        mut self,
+    ) -> Result<
+        (
+            v1::ServerReflectionServer<impl v1::ServerReflection>,
+            v1alpha::ServerReflectionServer<impl v1alpha::ServerReflection>,
+        ),
+        Error,
+    > {
+        if self.include_reflection_service {
+            self = self
+                .register_encoded_file_descriptor_set(crate::pb::v1::FILE_DESCRIPTOR_SET)
+                .register_encoded_file_descriptor_set(crate::pb::v1alpha::FILE_DESCRIPTOR_SET);
+        }
+
+        let state = Arc::new(ReflectionServiceState::new(
+            self.service_names,
+            self.encoded_file_descriptor_sets,
+            self.file_descriptor_sets,
+            self.use_all_service_names,
+            self.hide_unadvertised,
+            self.request_filter,
+        )?);
+
+        Ok((
+            v1::ServerReflectionServer::new(v1::ReflectionService::with_state(state.clone())),
+            v1alpha::ServerReflectionServer::new(v1alpha::ReflectionService::with_state(state)),
+        ))
+    }
+}
+
+impl fmt::Debug for Builder<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Builder")
+            .field("file_descriptor_sets", &self.file_descriptor_sets)
+            .field(
+                "encoded_file_descriptor_sets",
+                &self.encoded_file_descriptor_sets,
+            )
+            .field(
+                "include_reflection_service",
+                &self.include_reflection_service,
+            )
+            .field("service_names", &self.service_names)
+            .field("use_all_service_names", &self.use_all_service_names)
+            .field("hide_unadvertised", &self.hide_unadvertised)
+            .field("request_filter", &self.request_filter.is_some())
+            .finish()
+    }
 }
 
-#[derive(Debug)]
 struct ReflectionServiceState {
     service_names: Vec<String>,
     files: HashMap<String, Arc<FileDescriptorProto>>,
     symbols: HashMap<String, Arc<FileDescriptorProto>>,
+    request_filter: Option<RequestFilter>,
 }
 
 impl ReflectionServiceState {
@@ -124,17 +209,15 @@ impl ReflectionServiceState {
         encoded_file_descriptor_sets: Vec<&[u8]>,
         mut file_descriptor_sets: Vec<FileDescriptorSet>,
         use_all_service_names: bool,
+        hide_unadvertised: bool,
+        request_filter: Option<RequestFilter>,
     ) -> Result<Self, Error> {
         for encoded in encoded_file_descriptor_sets {
             file_descriptor_sets.push(FileDescriptorSet::decode(encoded)?);
         }
 
-        let mut state = ReflectionServiceState {
-            service_names,
-            files: HashMap::new(),
-            symbols: HashMap::new(),
-        };
-
+        let mut files = Vec::new();
+        let mut names = HashSet::new();
         for fds in file_descriptor_sets {
             for fd in fds.file {
                 let name = match fd.name.clone() {
@@ -144,19 +227,41 @@ impl ReflectionServiceState {
                     Some(n) => n,
                 };
 
-                if state.files.contains_key(&name) {
-                    continue;
+                if names.insert(name) {
+                    files.push(fd);
                 }
-
-                let fd = Arc::new(fd);
-                state.files.insert(name, fd.clone());
-                state.process_file(fd, use_all_service_names)?;
             }
         }
 
+        if hide_unadvertised && !use_all_service_names {
+            files = advertised_files(files, &service_names);
+        }
+
+        let mut state = ReflectionServiceState {
+            service_names,
+            files: HashMap::new(),
+            symbols: HashMap::new(),
+            request_filter,
+        };
+
+        for fd in files {
+            let fd = Arc::new(fd);
+            state.files.insert(fd.name().to_string(), fd.clone());
+            state.process_file(fd, use_all_service_names)?;
+        }
+
         Ok(state)
     }
 
+    fn check_request(&self, metadata: &MetadataMap) -> Result<(), Status> {
+        match &self.request_filter {
+            Some(filter) if !filter(metadata) => {
+                Err(Status::permission_denied("reflection is not allowed"))
+            }
+            _ => Ok(()),
+        }
+    }
+
     fn process_file(
         &mut self,
         fd: Arc<FileDescriptorProto>,
@@ -278,6 +383,53 @@ impl ReflectionServiceState {
     }
 }
 
+impl fmt::Debug for ReflectionServiceState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReflectionServiceState")
+            .field("service_names", &self.service_names)
+            .field("files", &self.files)
+            .field("symbols", &self.symbols)
+            .field("request_filter", &self.request_filter.is_some())
+            .finish()
+    }
+}
+
+// Keeps the files declaring the services named `service_names`, and their dependencies.
+fn advertised_files(
+    files: Vec<FileDescriptorProto>,
+    service_names: &[String],
+) -> Vec<FileDescriptorProto> {
+    let declares = |fd: &FileDescriptorProto| {
+        fd.service.iter().any(|service| {
+            let name = match fd.package() {
+                "" => service.name().to_string(),
+                package => format!("{package}.{}", service.name()),
+            };
+            service_names.contains(&name)
+        })
+    };
+
+    let mut queue: VecDeque<_> = files
+        .iter()
+        .filter(|fd| declares(fd))
+        .map(|fd| fd.name().to_string())
+        .collect();
+    let mut kept = HashSet::new();
+    while let Some(name) = queue.pop_front() {
+        if !kept.insert(name.clone()) {
+            continue;
+        }
+        if let Some(fd) = files.iter().find(|fd| fd.name() == name) {
+            queue.extend(fd.dependency.iter().cloned());
+        }
+    }
+
+    files
+        .into_iter()
+        .filter(|fd| kept.contains(fd.name()))
+        .collect()
+}
+
 fn extract_name(
     prefix: &str,
     name_type: &str,
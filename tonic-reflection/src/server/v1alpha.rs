@@ -27,6 +27,8 @@ impl ServerReflection for ReflectionService {
         &self,
         req: Request<Streaming<ServerReflectionRequest>>,
     ) -> Result<Response<Self::ServerReflectionInfoStream>, Status> {
+        self.state.check_request(req.metadata())?;
+
         let mut req_rx = req.into_inner();
         let (resp_tx, resp_rx) = mpsc::channel::<Result<ServerReflectionResponse, Status>>(1);
 
@@ -96,11 +98,15 @@ impl ServerReflection for ReflectionService {
     }
 }
 
+impl ReflectionService {
+    pub(super) fn with_state(state: Arc<ReflectionServiceState>) -> Self {
+        Self { state }
+    }
+}
+
 impl From<ReflectionServiceState> for ReflectionService {
     fn from(state: ReflectionServiceState) -> Self {
-        Self {
-            state: Arc::new(state),
-        }
+        Self::with_state(Arc::new(state))
     }
 }
 
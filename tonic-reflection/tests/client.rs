@@ -0,0 +1,171 @@
+#![allow(missing_docs)]
+#![cfg(feature = "client")]
+
+use std::net::SocketAddr;
+
+use prost_reflect::{DynamicMessage, Value};
+use prost_types::{
+    DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    MethodDescriptorProto, ServiceDescriptorProto,
+};
+use tokio::sync::oneshot;
+use tokio_stream::wrappers::TcpListenerStream;
+use tokio_stream::StreamExt;
+use tonic::{transport::Server, Code};
+use tonic_reflection::client::{Error, ReflectionClient};
+use tonic_reflection::dynamic::DynamicClient;
+use tonic_reflection::server::Builder;
+
+// `b.proto`, declaring the `test.b.Echo` service, depends on `a.proto`, declaring the
+// messages of the service.
+fn file_descriptor_set() -> FileDescriptorSet {
+    let message = |name: &str| DescriptorProto {
+        name: Some(name.to_string()),
+        field: vec![FieldDescriptorProto {
+            name: Some("value".to_string()),
+            number: Some(1),
+            label: Some(1),
+            r#type: Some(9),
+            json_name: Some("value".to_string()),
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+
+    let a = FileDescriptorProto {
+        name: Some("a.proto".to_string()),
+        package: Some("test.a".to_string()),
+        message_type: vec![message("Request"), message("Response")],
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    };
+    let b = FileDescriptorProto {
+        name: Some("b.proto".to_string()),
+        package: Some("test.b".to_string()),
+        dependency: vec!["a.proto".to_string()],
+        service: vec![ServiceDescriptorProto {
+            name: Some("Echo".to_string()),
+            method: vec![MethodDescriptorProto {
+                name: Some("Echo".to_string()),
+                input_type: Some(".test.a.Request".to_string()),
+                output_type: Some(".test.a.Response".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        syntax: Some("proto3".to_string()),
+        ..Default::default()
+    };
+
+    FileDescriptorSet { file: vec![a, b] }
+}
+
+#[tokio::test]
+async fn test_client() {
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().expect("SocketAddr parse");
+    let listener = tokio::net::TcpListener::bind(addr).await.expect("bind");
+    let local_addr = format!("http://{}", listener.local_addr().expect("local address"));
+    let jh = tokio::spawn(async move {
+        let service = Builder::configure()
+            .register_file_descriptor_set(file_descriptor_set())
+            .build_v1()
+            .unwrap();
+
+        Server::builder()
+            .add_service(service)
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                drop(shutdown_rx.await)
+            })
+            .await
+            .unwrap();
+    });
+
+    // Give the test server a few ms to become available
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let conn = tonic::transport::Endpoint::new(local_addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = ReflectionClient::new(conn.clone());
+
+    let mut services = client.list_services().await.unwrap();
+    services.sort();
+    assert_eq!(
+        services,
+        ["grpc.reflection.v1.ServerReflection", "test.b.Echo"]
+    );
+
+    let files = client.file_containing_symbol("test.b.Echo").await.unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].name(), "b.proto");
+
+    // The dependencies of the files are resolved transitively.
+    let pool = client
+        .descriptor_pool_for_symbols(["test.b.Echo"])
+        .await
+        .unwrap();
+    let service = pool.get_service_by_name("test.b.Echo").expect("service");
+    let method = service.methods().next().expect("method");
+    assert_eq!(method.input().full_name(), "test.a.Request");
+    assert_eq!(pool.files().count(), 2);
+
+    let pool = client.descriptor_pool().await.unwrap();
+    assert!(pool
+        .get_service_by_name("grpc.reflection.v1.ServerReflection")
+        .is_some());
+    assert!(pool.get_message_by_name("test.a.Response").is_some());
+
+    match client.file_by_filename("missing.proto").await {
+        Err(Error::Status(status)) => assert_eq!(status.code(), Code::NotFound),
+        other => panic!("Expected a NotFound status, got {other:?}"),
+    }
+
+    // The reflection service is itself invoked dynamically.
+    let mut client = DynamicClient::new(conn, pool);
+    let method = client
+        .method("/grpc.reflection.v1.ServerReflection/ServerReflectionInfo")
+        .unwrap();
+    assert_eq!(
+        method.full_name(),
+        "grpc.reflection.v1.ServerReflection.ServerReflectionInfo"
+    );
+
+    let mut request = DynamicMessage::new(method.input());
+    request.set_field_by_name("list_services", Value::String(String::new()));
+
+    let status = client
+        .unary(method.full_name(), request.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    let status = client
+        .unary("test.b.Echo/Missing", request.clone())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+
+    let mut responses = client
+        .streaming(method.full_name(), tokio_stream::once(request))
+        .await
+        .unwrap()
+        .into_inner();
+    let response = responses.next().await.unwrap().unwrap();
+    let Some(Value::Message(list)) = response
+        .get_field_by_name("list_services_response")
+        .map(|value| value.into_owned())
+    else {
+        panic!("Expected a list_services_response field");
+    };
+    let Some(Value::List(services)) = list.get_field_by_name("service").map(|v| v.into_owned())
+    else {
+        panic!("Expected a service field");
+    };
+    assert_eq!(services.len(), 2);
+
+    shutdown_tx.send(()).expect("send shutdown");
+    jh.await.expect("server shutdown");
+}
@@ -0,0 +1,147 @@
+#![allow(missing_docs)]
+
+use std::net::SocketAddr;
+
+use tokio::sync::oneshot;
+use tokio_stream::{wrappers::TcpListenerStream, StreamExt};
+use tonic::{transport::Server, Code, Request, Status};
+use tonic_reflection::{
+    pb::{
+        v1::{
+            server_reflection_client::ServerReflectionClient,
+            server_reflection_request::MessageRequest, server_reflection_response::MessageResponse,
+            ServerReflectionRequest,
+        },
+        v1alpha,
+    },
+    server::Builder,
+};
+
+#[tokio::test]
+async fn test_hide_unadvertised() {
+    let builder = Builder::configure()
+        .register_encoded_file_descriptor_set(v1alpha::FILE_DESCRIPTOR_SET)
+        .with_service_name("grpc.reflection.v1.ServerReflection")
+        .hide_unadvertised(true);
+
+    let responses = make_test_reflection_requests(
+        builder,
+        Request::new(()),
+        vec![
+            MessageRequest::FileContainingSymbol(
+                "grpc.reflection.v1.ServerReflectionRequest".to_string(),
+            ),
+            MessageRequest::FileContainingSymbol(
+                "grpc.reflection.v1alpha.ServerReflection".to_string(),
+            ),
+        ],
+    )
+    .await;
+
+    assert!(matches!(
+        responses[0],
+        Ok(MessageResponse::FileDescriptorResponse(_))
+    ));
+    assert_eq!(responses[1].as_ref().unwrap_err().code(), Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_request_filter() {
+    let builder = || {
+        Builder::configure().with_request_filter(|metadata| {
+            metadata
+                .get("authorization")
+                .is_some_and(|value| value == "Bearer secret")
+        })
+    };
+    let list_services = || vec![MessageRequest::ListServices(String::new())];
+
+    let responses =
+        make_test_reflection_requests(builder(), Request::new(()), list_services()).await;
+    assert_eq!(
+        responses[0].as_ref().unwrap_err().code(),
+        Code::PermissionDenied
+    );
+
+    let mut request = Request::new(());
+    request
+        .metadata_mut()
+        .insert("authorization", "Bearer secret".parse().unwrap());
+    let responses = make_test_reflection_requests(builder(), request, list_services()).await;
+    assert!(matches!(
+        responses[0],
+        Ok(MessageResponse::ListServicesResponse(_))
+    ));
+}
+
+// Sends `requests` with the metadata of `request`, returning the responses until the first
+// failure.
+async fn make_test_reflection_requests(
+    builder: Builder<'static>,
+    request: Request<()>,
+    requests: Vec<MessageRequest>,
+) -> Vec<Result<MessageResponse, Status>> {
+    // Run a test server
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().expect("SocketAddr parse");
+    let listener = tokio::net::TcpListener::bind(addr).await.expect("bind");
+    let local_addr = format!("http://{}", listener.local_addr().expect("local address"));
+    let jh = tokio::spawn(async move {
+        let service = builder.build_v1().unwrap();
+
+        Server::builder()
+            .add_service(service)
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                drop(shutdown_rx.await)
+            })
+            .await
+            .unwrap();
+    });
+
+    // Give the test server a few ms to become available
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let conn = tonic::transport::Endpoint::new(local_addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let mut client = ServerReflectionClient::new(conn);
+
+    let messages =
+        tokio_stream::iter(
+            requests
+                .into_iter()
+                .map(|message_request| ServerReflectionRequest {
+                    host: "".to_string(),
+                    message_request: Some(message_request),
+                }),
+        );
+    let (metadata, extensions, ()) = request.into_parts();
+    let request = Request::from_parts(metadata, extensions, messages);
+
+    let mut responses = Vec::new();
+    match client.server_reflection_info(request).await {
+        Ok(response) => {
+            let mut inbound = response.into_inner();
+            while let Some(response) = inbound.next().await {
+                let failed = response.is_err();
+                responses.push(
+                    response
+                        .map(|response| response.message_response.expect("some MessageResponse")),
+                );
+                if failed {
+                    break;
+                }
+            }
+        }
+        Err(status) => responses.push(Err(status)),
+    }
+
+    // Shut down test server
+    shutdown_tx.send(()).expect("send shutdown");
+    jh.await.expect("server shutdown");
+
+    responses
+}
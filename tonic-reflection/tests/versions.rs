@@ -57,6 +57,99 @@ async fn test_v1alpha() {
     }
 }
 
+#[tokio::test]
+async fn test_v1_and_v1alpha() {
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+    let addr: SocketAddr = "127.0.0.1:0".parse().expect("SocketAddr parse");
+    let listener = tokio::net::TcpListener::bind(addr).await.expect("bind");
+    let local_addr = format!("http://{}", listener.local_addr().expect("local address"));
+    let jh = tokio::spawn(async move {
+        let (v1_service, v1alpha_service) = Builder::configure().build_v1_and_v1alpha().unwrap();
+
+        Server::builder()
+            .add_service(v1_service)
+            .add_service(v1alpha_service)
+            .serve_with_incoming_shutdown(TcpListenerStream::new(listener), async {
+                drop(shutdown_rx.await)
+            })
+            .await
+            .unwrap();
+    });
+
+    // Give the test server a few ms to become available
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    let conn = tonic::transport::Endpoint::new(local_addr)
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    let expected = [
+        "grpc.reflection.v1.ServerReflection",
+        "grpc.reflection.v1alpha.ServerReflection",
+    ];
+
+    // Both versions list the services from the same descriptor sets.
+    let mut client = v1::server_reflection_client::ServerReflectionClient::new(conn.clone());
+    let request = v1::ServerReflectionRequest {
+        host: "".to_string(),
+        message_request: Some(v1::server_reflection_request::MessageRequest::ListServices(
+            String::new(),
+        )),
+    };
+    let response = client
+        .server_reflection_info(Request::new(tokio_stream::once(request)))
+        .await
+        .expect("request")
+        .into_inner()
+        .next()
+        .await
+        .expect("streamed response")
+        .expect("successful response")
+        .message_response;
+    if let Some(v1::server_reflection_response::MessageResponse::ListServicesResponse(services)) =
+        response
+    {
+        let mut names: Vec<_> = services.service.into_iter().map(|s| s.name).collect();
+        names.sort();
+        assert_eq!(names, expected);
+    } else {
+        panic!("Expected a ListServicesResponse variant");
+    }
+
+    let mut client = v1alpha::server_reflection_client::ServerReflectionClient::new(conn);
+    let request = v1alpha::ServerReflectionRequest {
+        host: "".to_string(),
+        message_request: Some(
+            v1alpha::server_reflection_request::MessageRequest::FileContainingSymbol(
+                "grpc.reflection.v1.ServerReflection".to_string(),
+            ),
+        ),
+    };
+    let response = client
+        .server_reflection_info(Request::new(tokio_stream::once(request)))
+        .await
+        .expect("request")
+        .into_inner()
+        .next()
+        .await
+        .expect("streamed response")
+        .expect("successful response")
+        .message_response;
+    if let Some(v1alpha::server_reflection_response::MessageResponse::FileDescriptorResponse(
+        response,
+    )) = response
+    {
+        assert_eq!(response.file_descriptor_proto.len(), 1);
+    } else {
+        panic!("Expected a FileDescriptorResponse variant");
+    }
+
+    shutdown_tx.send(()).expect("send shutdown");
+    jh.await.expect("server shutdown");
+}
+
 async fn make_v1_request(
     request: v1::ServerReflectionRequest,
 ) -> v1::server_reflection_response::MessageResponse {
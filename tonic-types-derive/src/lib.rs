@@ -0,0 +1,173 @@
+//! Derive macros for `tonic-types`.
+//!
+//! This crate is not meant to be used directly, enable the `derive` feature of
+//! `tonic-types` instead.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Fields, Ident, Member,
+};
+
+/// Implements `From<T> for tonic::Status` for an application error type.
+///
+/// The message of the status is the `Display` output of the error, and its
+/// code is set through the `#[status(code = ...)]` attribute, which takes the
+/// name of a `tonic::Code` variant. On enums, the attribute can be set on the
+/// enum itself, as the code of the variants without one, and on each variant.
+/// Errors without a code are converted into `Code::Unknown`.
+///
+/// Variants and structs with a single field can be marked with
+/// `#[status(transparent)]` to delegate the conversion to the `Into<Status>`
+/// implementation of the field.
+///
+/// See the `tonic-types` documentation for examples.
+#[proc_macro_derive(IntoStatus, attributes(status))]
+pub fn derive_into_status(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct StatusAttr {
+    code: Option<Ident>,
+    transparent: bool,
+}
+
+fn parse_attrs(attrs: &[Attribute]) -> syn::Result<StatusAttr> {
+    let mut status_attr = StatusAttr::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("status")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("code") {
+                status_attr.code = Some(meta.value()?.parse()?);
+                Ok(())
+            } else if meta.path.is_ident("transparent") {
+                status_attr.transparent = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported status attribute, expected `code` or `transparent`"))
+            }
+        })?;
+
+        if status_attr.transparent && status_attr.code.is_some() {
+            return Err(Error::new(
+                attr.span(),
+                "`code` can't be used together with `transparent`",
+            ));
+        }
+    }
+
+    Ok(status_attr)
+}
+
+fn single_field(fields: &Fields, span: proc_macro2::Span) -> syn::Result<Member> {
+    if fields.len() != 1 {
+        return Err(Error::new(
+            span,
+            "`#[status(transparent)]` requires exactly one field",
+        ));
+    }
+
+    let field = fields.iter().next().unwrap();
+
+    Ok(match &field.ident {
+        Some(ident) => Member::Named(ident.clone()),
+        None => Member::Unnamed(0.into()),
+    })
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let attr = parse_attrs(&input.attrs)?;
+
+    let body = match &input.data {
+        Data::Struct(data) => {
+            if attr.transparent {
+                let member = single_field(&data.fields, input.ident.span())?;
+                quote! {
+                    ::core::convert::Into::<::tonic::Status>::into(err.#member)
+                }
+            } else {
+                let code = code_tokens(attr.code.as_ref());
+                quote! {
+                    ::tonic::Status::new(#code, ::std::string::ToString::to_string(&err))
+                }
+            }
+        }
+        Data::Enum(data) => {
+            if attr.transparent {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "`#[status(transparent)]` is only supported on variants of enums",
+                ));
+            }
+
+            let mut transparent_arms = Vec::new();
+            let mut code_arms = Vec::new();
+
+            for variant in &data.variants {
+                let variant_attr = parse_attrs(&variant.attrs)?;
+                let ident = &variant.ident;
+
+                if variant_attr.transparent {
+                    let member = single_field(&variant.fields, ident.span())?;
+                    transparent_arms.push(quote! {
+                        #name::#ident { #member: inner } => {
+                            ::core::convert::Into::<::tonic::Status>::into(inner)
+                        }
+                    });
+                } else if let Some(code) = &variant_attr.code {
+                    code_arms.push(quote! {
+                        #name::#ident { .. } => ::tonic::Code::#code
+                    });
+                }
+            }
+
+            let default_code = code_tokens(attr.code.as_ref());
+
+            quote! {
+                match err {
+                    #(#transparent_arms)*
+                    #[allow(unreachable_patterns)]
+                    err => {
+                        let code = match &err {
+                            #(#code_arms,)*
+                            #[allow(unreachable_patterns)]
+                            _ => #default_code,
+                        };
+                        ::tonic::Status::new(code, ::std::string::ToString::to_string(&err))
+                    }
+                }
+            }
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                input.ident.span(),
+                "`IntoStatus` can't be derived for unions",
+            ));
+        }
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::convert::From<#name #ty_generics> for ::tonic::Status
+        #where_clause
+        {
+            fn from(err: #name #ty_generics) -> Self {
+                #body
+            }
+        }
+    })
+}
+
+fn code_tokens(code: Option<&Ident>) -> TokenStream2 {
+    match code {
+        Some(code) => quote!(::tonic::Code::#code),
+        None => quote!(::tonic::Code::Unknown),
+    }
+}
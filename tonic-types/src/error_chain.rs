@@ -0,0 +1,279 @@
+//! Utilities that convert application errors, and their source chains, into
+//! `tonic::Status`.
+
+use std::{error::Error, fmt};
+
+use tonic::{Code, Status};
+
+/// Derives the conversion of an application error type into a
+/// `tonic::Status`.
+///
+/// The message of the status is the `Display` output of the error, and its
+/// code is set through the `#[status(code = ...)]` attribute, which takes the
+/// name of a `tonic::Code` variant. On enums, the attribute can be set on the
+/// enum itself, as the code of the variants without one, and on each variant.
+/// Errors without a code are converted into `Code::Unknown`. Variants with a
+/// single field can be marked with `#[status(transparent)]` to delegate the
+/// conversion to the field.
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt;
+/// use tonic::{Code, Status};
+/// use tonic_types::IntoStatus;
+///
+/// #[derive(Debug, IntoStatus)]
+/// #[status(code = Internal)]
+/// enum BookError {
+///     #[status(code = NotFound)]
+///     NotFound(String),
+///     Database,
+///     #[status(transparent)]
+///     Status(Status),
+/// }
+///
+/// impl fmt::Display for BookError {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         match self {
+///             BookError::NotFound(isbn) => write!(f, "book {isbn} not found"),
+///             BookError::Database => f.write_str("database error"),
+///             BookError::Status(status) => f.write_str(status.message()),
+///         }
+///     }
+/// }
+///
+/// let status = Status::from(BookError::NotFound("978-0".into()));
+///
+/// assert_eq!(status.code(), Code::NotFound);
+/// assert_eq!(status.message(), "book 978-0 not found");
+/// assert_eq!(Status::from(BookError::Database).code(), Code::Internal);
+/// ```
+#[cfg(feature = "derive")]
+pub use tonic_types_derive::IntoStatus;
+
+type Rule = Box<dyn Fn(&(dyn Error + 'static)) -> Option<Status> + Send + Sync>;
+
+/// Converts errors into `tonic::Status`, according to a list of downcast
+/// rules.
+///
+/// When converting an error, its source chain is walked from the outermost
+/// error to the innermost one. For each error of the chain, the mapper first
+/// checks whether it is a `tonic::Status`, which is then returned as is, and
+/// then tries each registered rule, in the order they were added. The first
+/// match is used. If no error of the chain matches, a status with the
+/// fallback code, `Code::Unknown` by default, and the message of the
+/// outermost error is returned.
+///
+/// # Examples
+///
+/// ```
+/// use std::io;
+/// use tonic::{Code, Status};
+/// use tonic_types::StatusMapper;
+///
+/// let mapper = StatusMapper::new()
+///     .map_code::<std::num::ParseIntError>(Code::InvalidArgument)
+///     .map(|err: &io::Error| match err.kind() {
+///         io::ErrorKind::NotFound => Status::not_found(err.to_string()),
+///         _ => Status::internal("internal I/O error"),
+///     })
+///     .fallback_code(Code::Internal);
+///
+/// let err = io::Error::new(io::ErrorKind::NotFound, "no such book");
+/// let status = mapper.map_error(&err);
+///
+/// assert_eq!(status.code(), Code::NotFound);
+/// assert_eq!(status.message(), "no such book");
+/// ```
+#[derive(Default)]
+pub struct StatusMapper {
+    rules: Vec<Rule>,
+    fallback_code: Option<Code>,
+}
+
+impl StatusMapper {
+    /// Creates a new [`StatusMapper`] with no rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule that converts errors of type `E` using `f`.
+    pub fn map<E, F>(mut self, f: F) -> Self
+    where
+        E: Error + 'static,
+        F: Fn(&E) -> Status + Send + Sync + 'static,
+    {
+        self.rules
+            .push(Box::new(move |err| err.downcast_ref::<E>().map(&f)));
+        self
+    }
+
+    /// Adds a rule that converts errors of type `E` into a status with the
+    /// given code, using the error's `Display` output as the message.
+    pub fn map_code<E>(self, code: Code) -> Self
+    where
+        E: Error + 'static,
+    {
+        self.map(move |err: &E| Status::new(code, err.to_string()))
+    }
+
+    /// Sets the code used when no error of the chain matches a rule.
+    pub fn fallback_code(self, code: Code) -> Self {
+        StatusMapper {
+            fallback_code: Some(code),
+            ..self
+        }
+    }
+
+    /// Converts `err` into a `tonic::Status`.
+    pub fn map_error(&self, err: &(dyn Error + 'static)) -> Status {
+        let mut source = Some(err);
+
+        while let Some(err) = source {
+            if let Some(status) = err.downcast_ref::<Status>() {
+                return status.clone();
+            }
+
+            if let Some(status) = self.rules.iter().find_map(|rule| rule(err)) {
+                return status;
+            }
+
+            source = err.source();
+        }
+
+        Status::new(self.fallback_code.unwrap_or(Code::Unknown), err.to_string())
+    }
+}
+
+impl fmt::Debug for StatusMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatusMapper")
+            .field("rules", &self.rules.len())
+            .field("fallback_code", &self.fallback_code)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, fmt, io, sync::Arc};
+
+    use tonic::{Code, Status};
+
+    use super::StatusMapper;
+
+    #[derive(Debug)]
+    struct Wrapper(Box<dyn Error + Send + Sync>);
+
+    impl fmt::Display for Wrapper {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "request failed: {}", self.0)
+        }
+    }
+
+    impl Error for Wrapper {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&*self.0)
+        }
+    }
+
+    #[test]
+    fn map_error_chain() {
+        let mapper = StatusMapper::new().map_code::<io::Error>(Code::Unavailable);
+
+        let err = Wrapper(Box::new(io::Error::other("connection reset")));
+        let status = mapper.map_error(&err);
+
+        assert_eq!(status.code(), Code::Unavailable);
+        assert_eq!(status.message(), "connection reset");
+
+        let mut not_found = Status::not_found("no such book");
+        not_found.set_source(Arc::new(io::Error::other("no such file")));
+        let err = Wrapper(Box::new(not_found));
+        let status = mapper.map_error(&err);
+
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "no such book");
+        assert_eq!(status.source().unwrap().to_string(), "no such file");
+
+        let err = Wrapper("something else".into());
+
+        let status = mapper.map_error(&err);
+        assert_eq!(status.code(), Code::Unknown);
+        assert_eq!(status.message(), "request failed: something else");
+
+        let status = mapper.fallback_code(Code::Internal).map_error(&err);
+        assert_eq!(status.code(), Code::Internal);
+    }
+
+    #[cfg(feature = "derive")]
+    mod derive {
+        use std::fmt;
+
+        use tonic::{Code, Status};
+
+        use super::super::IntoStatus;
+        use super::Wrapper;
+
+        #[derive(Debug, IntoStatus)]
+        enum AppError {
+            #[status(code = InvalidArgument)]
+            Invalid {
+                field: String,
+            },
+            Other,
+            #[status(transparent)]
+            Io {
+                source: Wrapper,
+            },
+        }
+
+        impl fmt::Display for AppError {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self {
+                    AppError::Invalid { field } => write!(f, "invalid {field}"),
+                    AppError::Other => f.write_str("other"),
+                    AppError::Io { source } => source.fmt(f),
+                }
+            }
+        }
+
+        impl From<Wrapper> for Status {
+            fn from(err: Wrapper) -> Self {
+                Status::unavailable(err.to_string())
+            }
+        }
+
+        #[derive(Debug, IntoStatus)]
+        #[status(code = DataLoss)]
+        struct Corrupted;
+
+        impl fmt::Display for Corrupted {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("corrupted")
+            }
+        }
+
+        #[test]
+        fn derive_into_status() {
+            let status = Status::from(AppError::Invalid {
+                field: "isbn".into(),
+            });
+            assert_eq!(status.code(), Code::InvalidArgument);
+            assert_eq!(status.message(), "invalid isbn");
+
+            assert_eq!(Status::from(AppError::Other).code(), Code::Unknown);
+
+            let status = Status::from(AppError::Io {
+                source: Wrapper("reset".into()),
+            });
+            assert_eq!(status.code(), Code::Unavailable);
+            assert_eq!(status.message(), "request failed: reset");
+
+            let status = Status::from(Corrupted);
+            assert_eq!(status.code(), Code::DataLoss);
+            assert_eq!(status.message(), "corrupted");
+        }
+    }
+}
@@ -23,6 +23,33 @@
 //! tonic-types = <tonic-types-version>
 //! ```
 //!
+//! # Feature Flags
+//!
+//! - `backtrace`: Adds [`DebugInfo::from_backtrace`], which captures a
+//!   backtrace of the current thread. Intended for development environments.
+//!   Not enabled by default.
+//! - `derive`: Adds the [`IntoStatus`] derive macro, which implements the
+//!   conversion of application error types into [`tonic::Status`]. Not
+//!   enabled by default.
+//! - `garde`: Implements conversions from [`garde`] validation reports into
+//!   [`BadRequest`] details. Not enabled by default.
+//! - `pagination`: Adds [`PageTokens`], [`PageSize`] and [`paginate`], which
+//!   help implement and call the `List` methods of APIs following AIP-158
+//!   pagination. Not enabled by default.
+//! - `prost-validate`: Implements conversions from [`prost-validate`]
+//!   validation errors into [`BadRequest`] details, and adds
+//!   [`validate_request`], which validates request messages against their
+//!   protoc-gen-validate constraints. Not enabled by default.
+//! - `serde`: Implements [`serde`] traits for the standard error message
+//!   structs and for [`ErrorDetail`], following the canonical protobuf JSON
+//!   mapping, and adds the [`JsonStatus`] struct. Not enabled by default.
+//! - `tower`: Adds [`RetryInfoPolicy`], a [`tower`] retry policy that honors
+//!   the retry delays recommended by servers. Not enabled by default.
+//! - `url`: Adds the [`Help`] and [`HelpLink`] methods validating links and
+//!   parsing them into [`url::Url`] values. Not enabled by default.
+//! - `validator`: Implements conversions from [`validator`] validation errors
+//!   into [`BadRequest`] details. Not enabled by default.
+//!
 //! # Examples
 //!
 //! The examples below cover a basic use case of the [gRPC Richer Error Model].
@@ -135,6 +162,19 @@
 //!
 //! [`tonic::Status`]: https://docs.rs/tonic/latest/tonic/struct.Status.html
 //! [`tonic`]: https://docs.rs/tonic/latest/tonic/
+//! [`DebugInfo::from_backtrace`]: https://docs.rs/tonic-types/latest/tonic_types/struct.DebugInfo.html#method.from_backtrace
+//! [`IntoStatus`]: https://docs.rs/tonic-types/latest/tonic_types/derive.IntoStatus.html
+//! [`garde`]: https://docs.rs/garde
+//! [`prost-validate`]: https://docs.rs/prost-validate
+//! [`PageTokens`]: https://docs.rs/tonic-types/latest/tonic_types/struct.PageTokens.html
+//! [`PageSize`]: https://docs.rs/tonic-types/latest/tonic_types/struct.PageSize.html
+//! [`paginate`]: https://docs.rs/tonic-types/latest/tonic_types/fn.paginate.html
+//! [`validate_request`]: https://docs.rs/tonic-types/latest/tonic_types/fn.validate_request.html
+//! [`serde`]: https://docs.rs/serde
+//! [`validator`]: https://docs.rs/validator
+//! [`tower`]: https://docs.rs/tower
+//! [`RetryInfoPolicy`]: https://docs.rs/tonic-types/latest/tonic_types/struct.RetryInfoPolicy.html
+//! [`JsonStatus`]: https://docs.rs/tonic-types/latest/tonic_types/struct.JsonStatus.html
 //! [gRPC Richer Error Model]: https://www.grpc.io/docs/guides/error/
 //! [examples]: https://github.com/hyperium/tonic/tree/master/examples
 //! [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
@@ -144,6 +184,7 @@
     html_logo_url = "https://raw.githubusercontent.com/tokio-rs/website/master/public/img/icons/tonic.svg"
 )]
 #![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
+#![cfg_attr(docsrs, feature(doc_auto_cfg))]
 
 mod generated {
     #![allow(unreachable_pub)]
@@ -177,11 +218,38 @@ pub use pb::Status;
 mod richer_error;
 
 pub use richer_error::{
-    BadRequest, DebugInfo, ErrorDetail, ErrorDetails, ErrorInfo, FieldViolation, Help, HelpLink,
-    LocalizedMessage, PreconditionFailure, PreconditionViolation, QuotaFailure, QuotaViolation,
-    RequestInfo, ResourceInfo, RetryInfo, RpcStatusExt, StatusExt,
+    is_valid_error_reason, BadRequest, BadRequestBuilder, CommonPreconditionType, DebugInfo,
+    Detail, DetailsIter, ErrorDetail, ErrorDetails, ErrorDetailsVec, ErrorInfo, ErrorInfoBuilder,
+    ErrorReason, FieldPath, FieldViolation, FieldViolationBuilder, Help, HelpBuilder, HelpLink,
+    LocalizedMessage, PreconditionFailure, PreconditionFailureBuilder, PreconditionType,
+    PreconditionViolation, PreconditionViolationBuilder, QuotaFailure, QuotaFailureBuilder,
+    QuotaViolation, QuotaViolationBuilder, RequestInfo, ResourceInfo, RetryInfo, RpcStatusExt,
+    StatusDetail, StatusExt,
 };
 
+#[cfg(feature = "prost-validate")]
+pub use richer_error::validate_request;
+#[cfg(feature = "serde")]
+pub use richer_error::JsonStatus;
+
+mod error_chain;
+
+#[cfg(feature = "derive")]
+pub use error_chain::IntoStatus;
+pub use error_chain::StatusMapper;
+
+#[cfg(feature = "pagination")]
+mod pagination;
+mod retry;
+
+pub mod test;
+
+#[cfg(feature = "pagination")]
+pub use pagination::{paginate, PageRequest, PageResponse, PageSize, PageTokens};
+pub use retry::RetryBackoff;
+#[cfg(feature = "tower")]
+pub use retry::RetryInfoPolicy;
+
 mod sealed {
     pub trait Sealed {}
 }
@@ -0,0 +1,428 @@
+//! Utilities that help implement and call the `List` methods of APIs following
+//! [AIP-158] pagination, through opaque page tokens and `page_size` bounds.
+//!
+//! [AIP-158]: https://google.aip.dev/158
+
+use std::{fmt, future::Future, vec};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use futures_util::{stream, Stream};
+use hmac::{Hmac, Mac};
+use prost::Message;
+use sha2::Sha256;
+use tonic::{Code, Status};
+
+use crate::{ErrorDetails, StatusExt};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const TAG_LEN: usize = 32;
+
+/// Encodes the position of a page in opaque page tokens, signed with
+/// HMAC-SHA256 so that clients can't forge or alter them, and decodes the
+/// page tokens of requests back into positions.
+///
+/// Positions are protobuf messages of the service, holding e.g. the key of
+/// the last item of the previous page. They should also hold the parameters
+/// of the request that must not change between pages, such as its `filter`,
+/// so that the service can reject tokens used with other parameters.
+///
+/// # Examples
+///
+/// ```
+/// use tonic::Code;
+/// use tonic_types::PageTokens;
+///
+/// #[derive(Clone, PartialEq, prost::Message)]
+/// struct Position {
+///     #[prost(string, tag = "1")]
+///     last_name: String,
+/// }
+///
+/// let tokens = PageTokens::new(b"server secret key".to_vec());
+///
+/// let token = tokens.encode(&Position { last_name: "shelves/42".into() });
+/// let position: Position = tokens.decode(&token).unwrap();
+/// assert_eq!(position.last_name, "shelves/42");
+///
+/// let forged = PageTokens::new(b"other key".to_vec()).encode(&position);
+/// let status = tokens.decode::<Position>(&forged).unwrap_err();
+/// assert_eq!(status.code(), Code::InvalidArgument);
+/// ```
+#[derive(Clone)]
+pub struct PageTokens {
+    key: Vec<u8>,
+}
+
+impl PageTokens {
+    /// Creates a new [`PageTokens`] signing tokens with `key`. The key should
+    /// be secret, and shared by all the replicas of the service.
+    pub fn new(key: impl Into<Vec<u8>>) -> Self {
+        PageTokens { key: key.into() }
+    }
+
+    /// Encodes `position` into an opaque page token, e.g. the
+    /// `next_page_token` of a response.
+    pub fn encode<M: Message>(&self, position: &M) -> String {
+        let mut token = position.encode_to_vec();
+        let tag = self.mac(&token).finalize().into_bytes();
+        token.extend_from_slice(&tag);
+
+        URL_SAFE_NO_PAD.encode(token)
+    }
+
+    /// Decodes the position encoded in `token`, e.g. the `page_token` of a
+    /// request. Tokens that weren't encoded with the same key, or that were
+    /// altered, are rejected with a `tonic::Status` with
+    /// `Code::InvalidArgument` and [`BadRequest`] details.
+    ///
+    /// [`BadRequest`]: crate::BadRequest
+    pub fn decode<M: Message + Default>(&self, token: &str) -> Result<M, Status> {
+        let token = URL_SAFE_NO_PAD
+            .decode(token)
+            .map_err(|_| invalid_page_token())?;
+        if token.len() < TAG_LEN {
+            return Err(invalid_page_token());
+        }
+
+        let (position, tag) = token.split_at(token.len() - TAG_LEN);
+        self.mac(position)
+            .verify_slice(tag)
+            .map_err(|_| invalid_page_token())?;
+
+        M::decode(position).map_err(|_| invalid_page_token())
+    }
+
+    fn mac(&self, data: &[u8]) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts keys of any size");
+        mac.update(data);
+        mac
+    }
+}
+
+impl fmt::Debug for PageTokens {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PageTokens").finish_non_exhaustive()
+    }
+}
+
+fn invalid_page_token() -> Status {
+    Status::with_error_details(
+        Code::InvalidArgument,
+        "invalid page token",
+        ErrorDetails::with_bad_request_violation(
+            "page_token",
+            "the page token was not returned by a previous list request",
+        ),
+    )
+}
+
+/// The bounds of the `page_size` of the requests of a `List` method.
+///
+/// As recommended by AIP-158, an unset page size is replaced by the default
+/// size, page sizes above the maximum are lowered to it, and negative page
+/// sizes are rejected.
+///
+/// # Examples
+///
+/// ```
+/// use tonic_types::PageSize;
+///
+/// let page_size = PageSize::new(50, 1000);
+///
+/// assert_eq!(page_size.resolve(0).unwrap(), 50);
+/// assert_eq!(page_size.resolve(20).unwrap(), 20);
+/// assert_eq!(page_size.resolve(5000).unwrap(), 1000);
+/// assert!(page_size.resolve(-1).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PageSize {
+    default: u32,
+    max: u32,
+}
+
+impl PageSize {
+    /// Creates a new [`PageSize`] with the given default and maximum sizes.
+    /// The default size is lowered to the maximum if above it.
+    pub const fn new(default: u32, max: u32) -> Self {
+        let default = if default > max { max } else { default };
+
+        PageSize { default, max }
+    }
+
+    /// Returns the default size of the pages.
+    pub const fn default_size(&self) -> u32 {
+        self.default
+    }
+
+    /// Returns the maximum size of the pages.
+    pub const fn max_size(&self) -> u32 {
+        self.max
+    }
+
+    /// Returns the number of items of the page requested with `page_size`.
+    /// Negative page sizes are rejected with a `tonic::Status` with
+    /// `Code::InvalidArgument` and [`BadRequest`] details.
+    ///
+    /// [`BadRequest`]: crate::BadRequest
+    pub fn resolve(&self, page_size: i32) -> Result<usize, Status> {
+        let size = match page_size {
+            0 => self.default,
+            size if size < 0 => {
+                return Err(Status::with_error_details(
+                    Code::InvalidArgument,
+                    "invalid page size",
+                    ErrorDetails::with_bad_request_violation(
+                        "page_size",
+                        "the page size must not be negative",
+                    ),
+                ));
+            }
+            size => (size as u32).min(self.max),
+        };
+
+        Ok(size as usize)
+    }
+}
+
+/// The request of a `List` method, whose pages are requested with page
+/// tokens. Used by [`paginate`].
+pub trait PageRequest {
+    /// Sets the `page_token` of the request, e.g. to the `next_page_token` of
+    /// the previous response.
+    fn set_page_token(&mut self, page_token: String);
+}
+
+/// The response of a `List` method, holding a page of items and the token of
+/// the next page. Used by [`paginate`].
+pub trait PageResponse {
+    /// The items of the pages.
+    type Item;
+
+    /// Returns the `next_page_token` of the response, empty for the last page.
+    fn next_page_token(&self) -> &str;
+
+    /// Returns the items of the page.
+    fn into_items(self) -> Vec<Self::Item>;
+}
+
+/// Turns the pages of a `List` method into a stream of their items.
+///
+/// `list` is called with `request` to get the first page, then with its page
+/// token set to the `next_page_token` of each response until one is empty,
+/// the next page being requested once the items of the previous one are
+/// consumed. The stream ends after the first error.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use tonic::{Response, Status};
+/// # #[derive(Clone, Default)]
+/// # struct ListBooksRequest { page_token: String }
+/// # struct ListBooksResponse { books: Vec<String>, next_page_token: String }
+/// # #[derive(Clone)]
+/// # struct LibraryClient;
+/// # impl LibraryClient {
+/// #     async fn list_books(&mut self, _: ListBooksRequest) -> Result<Response<ListBooksResponse>, Status> { todo!() }
+/// # }
+/// use futures_util::{pin_mut, TryStreamExt};
+/// use tonic_types::{paginate, PageRequest, PageResponse};
+///
+/// impl PageRequest for ListBooksRequest {
+///     fn set_page_token(&mut self, page_token: String) {
+///         self.page_token = page_token;
+///     }
+/// }
+///
+/// impl PageResponse for ListBooksResponse {
+///     type Item = String;
+///
+///     fn next_page_token(&self) -> &str {
+///         &self.next_page_token
+///     }
+///
+///     fn into_items(self) -> Vec<String> {
+///         self.books
+///     }
+/// }
+///
+/// # async fn run(client: LibraryClient) -> Result<(), Status> {
+/// let books = paginate(ListBooksRequest::default(), move |request| {
+///     let mut client = client.clone();
+///     async move { client.list_books(request).await }
+/// });
+/// pin_mut!(books);
+///
+/// while let Some(book) = books.try_next().await? {
+///     // Handle book
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn paginate<Req, Res, F, Fut>(
+    request: Req,
+    list: F,
+) -> impl Stream<Item = Result<Res::Item, Status>>
+where
+    Req: PageRequest + Clone,
+    Res: PageResponse,
+    F: FnMut(Req) -> Fut,
+    Fut: Future<Output = Result<tonic::Response<Res>, Status>>,
+{
+    let pages = Pages {
+        request: Some(request),
+        list,
+        items: Vec::new().into_iter(),
+    };
+
+    stream::unfold(Some(pages), |pages| async move {
+        let mut pages = pages?;
+
+        loop {
+            if let Some(item) = pages.items.next() {
+                return Some((Ok(item), Some(pages)));
+            }
+
+            let mut request = pages.request.take()?;
+            let response = match (pages.list)(request.clone()).await {
+                Ok(response) => response.into_inner(),
+                Err(status) => return Some((Err(status), None)),
+            };
+
+            if !response.next_page_token().is_empty() {
+                request.set_page_token(response.next_page_token().to_owned());
+                pages.request = Some(request);
+            }
+            pages.items = response.into_items().into_iter();
+        }
+    })
+}
+
+// The state of the stream of `paginate`: the request of the next page, if
+// any, and the items left of the current one.
+struct Pages<Req, F, Item> {
+    request: Option<Req>,
+    list: F,
+    items: vec::IntoIter<Item>,
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{StreamExt, TryStreamExt};
+    use tonic::{Code, Response, Status};
+
+    use super::{paginate, PageRequest, PageResponse, PageSize, PageTokens};
+    use crate::StatusExt;
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    struct Position {
+        #[prost(uint32, tag = "1")]
+        offset: u32,
+    }
+
+    #[test]
+    fn decode_page_tokens() {
+        let tokens = PageTokens::new(b"key".to_vec());
+
+        let token = tokens.encode(&Position { offset: 3 });
+        assert_eq!(tokens.decode::<Position>(&token).unwrap().offset, 3);
+
+        let mut altered = token.into_bytes();
+        altered[0] = if altered[0] == b'A' { b'B' } else { b'A' };
+        let altered = String::from_utf8(altered).unwrap();
+
+        for token in ["", "not base64!", "AAAA", altered.as_str()] {
+            let status = tokens.decode::<Position>(token).unwrap_err();
+            assert_eq!(status.code(), Code::InvalidArgument);
+            let bad_request = status.get_details_bad_request().unwrap();
+            assert_eq!(bad_request.field_violations[0].field, "page_token");
+        }
+
+        assert_eq!(format!("{tokens:?}"), "PageTokens { .. }");
+    }
+
+    #[test]
+    fn resolve_page_sizes() {
+        let page_size = PageSize::new(200, 100);
+
+        assert_eq!(page_size.default_size(), 100);
+        assert_eq!(page_size.resolve(0).unwrap(), 100);
+        assert_eq!(page_size.resolve(100).unwrap(), 100);
+        assert_eq!(page_size.resolve(i32::MAX).unwrap(), 100);
+        assert_eq!(
+            page_size.resolve(i32::MIN).unwrap_err().code(),
+            Code::InvalidArgument
+        );
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct ListRequest {
+        page_token: String,
+    }
+
+    impl PageRequest for ListRequest {
+        fn set_page_token(&mut self, page_token: String) {
+            self.page_token = page_token;
+        }
+    }
+
+    struct ListResponse {
+        items: Vec<u32>,
+        next_page_token: String,
+    }
+
+    impl PageResponse for ListResponse {
+        type Item = u32;
+
+        fn next_page_token(&self) -> &str {
+            &self.next_page_token
+        }
+
+        fn into_items(self) -> Vec<u32> {
+            self.items
+        }
+    }
+
+    fn list(request: &ListRequest) -> Result<Response<ListResponse>, Status> {
+        let (items, next_page_token) = match request.page_token.as_str() {
+            "" => (vec![1, 2], "b"),
+            "b" => (vec![], "c"),
+            "c" => (vec![3], ""),
+            _ => return Err(Status::invalid_argument("invalid page token")),
+        };
+
+        Ok(Response::new(ListResponse {
+            items,
+            next_page_token: next_page_token.to_owned(),
+        }))
+    }
+
+    #[tokio::test]
+    async fn paginate_items() {
+        let mut requests = Vec::new();
+        let items: Vec<u32> = paginate(ListRequest::default(), |request| {
+            requests.push(request.clone());
+            std::future::ready(list(&request))
+        })
+        .try_collect()
+        .await
+        .unwrap();
+
+        assert_eq!(items, [1, 2, 3]);
+        let tokens: Vec<_> = requests.iter().map(|r| r.page_token.as_str()).collect();
+        assert_eq!(tokens, ["", "b", "c"]);
+
+        let request = ListRequest {
+            page_token: "unknown".to_owned(),
+        };
+        let results: Vec<_> = paginate(request, |request| std::future::ready(list(&request)))
+            .collect::<Vec<_>>()
+            .await;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().code(),
+            Code::InvalidArgument
+        );
+    }
+}
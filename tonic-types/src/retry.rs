@@ -0,0 +1,281 @@
+//! Utilities that allow clients to honor the retry delays recommended by
+//! servers through the `RetryInfo` standard error message.
+
+use std::time::Duration;
+
+use tonic::{Code, Status};
+
+use crate::StatusExt;
+
+/// Computes the delay a client should wait before retrying a failed request.
+///
+/// If the failed `tonic::Status` carries a [`RetryInfo`] detail with a
+/// `retry_delay`, that delay is used, since the server knows best when it will
+/// be able to handle the request again. Otherwise, requests that failed with
+/// one of the retryable codes are retried using exponential backoff. Delays are
+/// always capped at the configured maximum backoff.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use tonic::{Code, Status};
+/// use tonic_types::{ErrorDetails, RetryBackoff, StatusExt};
+///
+/// let backoff = RetryBackoff::new(Duration::from_millis(100), Duration::from_secs(10));
+///
+/// let status = Status::with_error_details(
+///     Code::ResourceExhausted,
+///     "too many requests",
+///     ErrorDetails::with_retry_info(Some(Duration::from_secs(2))),
+/// );
+/// assert_eq!(backoff.delay(0, &status), Some(Duration::from_secs(2)));
+///
+/// let status = Status::unavailable("overloaded");
+/// assert_eq!(backoff.delay(2, &status), Some(Duration::from_millis(400)));
+///
+/// let status = Status::invalid_argument("bad request");
+/// assert_eq!(backoff.delay(0, &status), None);
+/// ```
+///
+/// [`RetryInfo`]: crate::RetryInfo
+#[derive(Clone, Debug)]
+pub struct RetryBackoff {
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: u32,
+    retryable_codes: Vec<Code>,
+}
+
+impl RetryBackoff {
+    /// Creates a new [`RetryBackoff`] with the given initial and maximum
+    /// backoff. The backoff doubles after each attempt and only
+    /// `Code::Unavailable` is considered retryable when the server did not
+    /// provide a [`RetryInfo`] detail.
+    ///
+    /// [`RetryInfo`]: crate::RetryInfo
+    pub fn new(initial_backoff: Duration, max_backoff: Duration) -> Self {
+        RetryBackoff {
+            initial_backoff,
+            max_backoff,
+            multiplier: 2,
+            retryable_codes: vec![Code::Unavailable],
+        }
+    }
+
+    /// Sets the factor by which the backoff is multiplied after each attempt.
+    pub fn multiplier(self, multiplier: u32) -> Self {
+        RetryBackoff { multiplier, ..self }
+    }
+
+    /// Sets the codes that are retried with exponential backoff when the
+    /// server did not provide a [`RetryInfo`] detail.
+    ///
+    /// [`RetryInfo`]: crate::RetryInfo
+    pub fn retryable_codes(self, codes: impl Into<Vec<Code>>) -> Self {
+        RetryBackoff {
+            retryable_codes: codes.into(),
+            ..self
+        }
+    }
+
+    /// Returns the delay to wait before performing the retry attempt number
+    /// `attempt` (starting at 0) of a request that failed with `status`, or
+    /// `None` if the request should not be retried.
+    pub fn delay(&self, attempt: u32, status: &Status) -> Option<Duration> {
+        if let Some(retry_delay) = status.get_retry_delay() {
+            return Some(retry_delay.min(self.max_backoff));
+        }
+
+        if !self.retryable_codes.contains(&status.code()) {
+            return None;
+        }
+
+        let backoff = self
+            .multiplier
+            .checked_pow(attempt)
+            .and_then(|factor| self.initial_backoff.checked_mul(factor))
+            .unwrap_or(self.max_backoff);
+
+        Some(backoff.min(self.max_backoff))
+    }
+}
+
+impl Default for RetryBackoff {
+    /// Creates a [`RetryBackoff`] with an initial backoff of 100 milliseconds
+    /// and a maximum backoff of 10 seconds.
+    fn default() -> Self {
+        RetryBackoff::new(Duration::from_millis(100), Duration::from_secs(10))
+    }
+}
+
+#[cfg(feature = "tower")]
+pub use self::policy::RetryInfoPolicy;
+
+#[cfg(feature = "tower")]
+mod policy {
+    use tonic::Status;
+
+    use super::RetryBackoff;
+
+    /// A [`tower::retry::Policy`] for services that fail with `tonic::Status`,
+    /// which waits for the delay computed by a [`RetryBackoff`] before each
+    /// retry.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tower::retry::RetryLayer;
+    /// use tonic_types::{RetryBackoff, RetryInfoPolicy};
+    ///
+    /// let layer = RetryLayer::new(RetryInfoPolicy::new(RetryBackoff::default(), 3));
+    /// ```
+    #[derive(Clone, Debug)]
+    pub struct RetryInfoPolicy {
+        backoff: RetryBackoff,
+        max_retries: u32,
+        attempt: u32,
+    }
+
+    impl RetryInfoPolicy {
+        /// Creates a new [`RetryInfoPolicy`] that retries each request at most
+        /// `max_retries` times.
+        pub fn new(backoff: RetryBackoff, max_retries: u32) -> Self {
+            RetryInfoPolicy {
+                backoff,
+                max_retries,
+                attempt: 0,
+            }
+        }
+    }
+
+    impl<Req: Clone, Res> tower::retry::Policy<Req, Res, Status> for RetryInfoPolicy {
+        type Future = tokio::time::Sleep;
+
+        fn retry(
+            &mut self,
+            _req: &mut Req,
+            result: &mut Result<Res, Status>,
+        ) -> Option<Self::Future> {
+            let status = result.as_ref().err()?;
+
+            if self.attempt >= self.max_retries {
+                return None;
+            }
+
+            let delay = self.backoff.delay(self.attempt, status)?;
+            self.attempt += 1;
+
+            Some(tokio::time::sleep(delay))
+        }
+
+        fn clone_request(&mut self, req: &Req) -> Option<Req> {
+            Some(req.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tonic::{Code, Status};
+
+    use super::RetryBackoff;
+    use crate::{ErrorDetails, StatusExt};
+
+    #[test]
+    fn delay_from_retry_info() {
+        let backoff = RetryBackoff::new(Duration::from_millis(100), Duration::from_secs(5));
+
+        let status = Status::with_error_details(
+            Code::Aborted,
+            "aborted",
+            ErrorDetails::with_retry_info(Some(Duration::from_secs(3))),
+        );
+
+        assert_eq!(backoff.delay(4, &status), Some(Duration::from_secs(3)));
+
+        let status = Status::with_error_details(
+            Code::Unavailable,
+            "unavailable",
+            ErrorDetails::with_retry_info(Some(Duration::from_secs(60))),
+        );
+
+        assert_eq!(
+            backoff.delay(0, &status),
+            Some(Duration::from_secs(5)),
+            "server provided delay should be capped at max backoff"
+        );
+    }
+
+    #[test]
+    fn exponential_delay() {
+        let backoff = RetryBackoff::new(Duration::from_millis(100), Duration::from_secs(1))
+            .multiplier(3)
+            .retryable_codes([Code::Unavailable, Code::Aborted]);
+
+        let status = Status::aborted("aborted");
+
+        assert_eq!(backoff.delay(0, &status), Some(Duration::from_millis(100)));
+        assert_eq!(backoff.delay(2, &status), Some(Duration::from_millis(900)));
+        assert_eq!(backoff.delay(3, &status), Some(Duration::from_secs(1)));
+        assert_eq!(
+            backoff.delay(u32::MAX, &status),
+            Some(Duration::from_secs(1))
+        );
+
+        assert_eq!(backoff.delay(0, &Status::internal("internal")), None);
+    }
+
+    #[cfg(feature = "tower")]
+    #[tokio::test]
+    async fn retry_policy() {
+        use std::sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        };
+
+        use tower::{retry::RetryLayer, service_fn, Layer, ServiceExt};
+
+        use super::RetryInfoPolicy;
+
+        let calls = Arc::new(AtomicU32::new(0));
+
+        let svc = {
+            let calls = calls.clone();
+            service_fn(move |req: u32| {
+                let calls = calls.clone();
+                async move {
+                    match calls.fetch_add(1, Ordering::SeqCst) {
+                        0 => Err(Status::with_error_details(
+                            Code::ResourceExhausted,
+                            "too many requests",
+                            ErrorDetails::with_retry_info(Some(Duration::from_millis(1))),
+                        )),
+                        1 => Err(Status::unavailable("unavailable")),
+                        _ => Ok::<_, Status>(req),
+                    }
+                }
+            })
+        };
+
+        let backoff = RetryBackoff::new(Duration::from_millis(1), Duration::from_millis(1));
+        let policy = RetryInfoPolicy::new(backoff, 2);
+
+        let res = RetryLayer::new(policy.clone())
+            .layer(svc.clone())
+            .oneshot(7)
+            .await;
+
+        assert_eq!(res.unwrap(), 7);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        calls.store(1, Ordering::SeqCst);
+
+        let policy = RetryInfoPolicy::new(RetryBackoff::default(), 0);
+        let res = RetryLayer::new(policy).layer(svc).oneshot(7).await;
+
+        assert_eq!(res.unwrap_err().code(), Code::Unavailable);
+    }
+}
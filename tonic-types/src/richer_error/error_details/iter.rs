@@ -0,0 +1,115 @@
+use prost_types::Any;
+
+use super::super::std_messages::{
+    BadRequest, DebugInfo, ErrorInfo, Help, LocalizedMessage, PreconditionFailure, QuotaFailure,
+    RequestInfo, ResourceInfo, RetryInfo,
+};
+use super::super::FromAnyRef;
+use super::vec::ErrorDetail;
+
+/// A single detail attached to a `tonic::Status` or to a `pb::Status`.
+/// Details that correspond to one of the standard error messages are
+/// decoded into an [`ErrorDetail`], while any other detail is kept as the
+/// original `prost_types::Any`.
+#[derive(Clone, Debug)]
+pub enum StatusDetail {
+    /// A detail that was decoded into one of the standard error messages.
+    Known(ErrorDetail),
+
+    /// A detail whose type is not one of the standard error messages, or
+    /// that could not be decoded.
+    Unknown(Any),
+}
+
+impl StatusDetail {
+    /// Returns the type URL of the detail.
+    pub fn type_url(&self) -> &str {
+        match self {
+            StatusDetail::Known(detail) => match detail {
+                ErrorDetail::RetryInfo(_) => RetryInfo::TYPE_URL,
+                ErrorDetail::DebugInfo(_) => DebugInfo::TYPE_URL,
+                ErrorDetail::QuotaFailure(_) => QuotaFailure::TYPE_URL,
+                ErrorDetail::ErrorInfo(_) => ErrorInfo::TYPE_URL,
+                ErrorDetail::PreconditionFailure(_) => PreconditionFailure::TYPE_URL,
+                ErrorDetail::BadRequest(_) => BadRequest::TYPE_URL,
+                ErrorDetail::RequestInfo(_) => RequestInfo::TYPE_URL,
+                ErrorDetail::ResourceInfo(_) => ResourceInfo::TYPE_URL,
+                ErrorDetail::Help(_) => Help::TYPE_URL,
+                ErrorDetail::LocalizedMessage(_) => LocalizedMessage::TYPE_URL,
+            },
+            StatusDetail::Unknown(any) => any.type_url.as_str(),
+        }
+    }
+
+    fn from_any(any: Any) -> Self {
+        let detail = match any.type_url.as_str() {
+            RetryInfo::TYPE_URL => RetryInfo::from_any_ref(&any).map(ErrorDetail::from),
+            DebugInfo::TYPE_URL => DebugInfo::from_any_ref(&any).map(ErrorDetail::from),
+            QuotaFailure::TYPE_URL => QuotaFailure::from_any_ref(&any).map(ErrorDetail::from),
+            ErrorInfo::TYPE_URL => ErrorInfo::from_any_ref(&any).map(ErrorDetail::from),
+            PreconditionFailure::TYPE_URL => {
+                PreconditionFailure::from_any_ref(&any).map(ErrorDetail::from)
+            }
+            BadRequest::TYPE_URL => BadRequest::from_any_ref(&any).map(ErrorDetail::from),
+            RequestInfo::TYPE_URL => RequestInfo::from_any_ref(&any).map(ErrorDetail::from),
+            ResourceInfo::TYPE_URL => ResourceInfo::from_any_ref(&any).map(ErrorDetail::from),
+            Help::TYPE_URL => Help::from_any_ref(&any).map(ErrorDetail::from),
+            LocalizedMessage::TYPE_URL => {
+                LocalizedMessage::from_any_ref(&any).map(ErrorDetail::from)
+            }
+            _ => return StatusDetail::Unknown(any),
+        };
+
+        match detail {
+            Ok(detail) => StatusDetail::Known(detail),
+            Err(_) => StatusDetail::Unknown(any),
+        }
+    }
+}
+
+impl From<ErrorDetail> for StatusDetail {
+    fn from(detail: ErrorDetail) -> Self {
+        StatusDetail::Known(detail)
+    }
+}
+
+/// Iterator over all the details attached to a `tonic::Status` or to a
+/// `pb::Status`, in the order they were added. Created by
+/// [`StatusExt::iter_details`] and [`RpcStatusExt::iter_details`].
+///
+/// [`StatusExt::iter_details`]: crate::StatusExt::iter_details
+/// [`RpcStatusExt::iter_details`]: crate::RpcStatusExt::iter_details
+#[derive(Clone, Debug)]
+pub struct DetailsIter {
+    details: std::vec::IntoIter<Any>,
+}
+
+impl DetailsIter {
+    pub(crate) fn new(details: Vec<Any>) -> Self {
+        DetailsIter {
+            details: details.into_iter(),
+        }
+    }
+}
+
+impl Iterator for DetailsIter {
+    type Item = StatusDetail;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.details.next().map(StatusDetail::from_any)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.details.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for DetailsIter {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.details.next_back().map(StatusDetail::from_any)
+    }
+}
+
+impl ExactSizeIterator for DetailsIter {}
+
+impl std::iter::FusedIterator for DetailsIter {}
@@ -6,6 +6,7 @@ use super::std_messages::{
     ResourceInfo, RetryInfo,
 };
 
+pub(crate) mod iter;
 pub(crate) mod vec;
 
 /// Groups the standard error messages structs. Provides associated
@@ -42,8 +43,8 @@ pub struct ErrorDetails {
     /// This field stores [`Help`] data, if any.
     pub(crate) help: Option<Help>,
 
-    /// This field stores [`LocalizedMessage`] data, if any.
-    pub(crate) localized_message: Option<LocalizedMessage>,
+    /// This field stores [`LocalizedMessage`] data, one per locale.
+    pub(crate) localized_messages: Vec<LocalizedMessage>,
 }
 
 impl ErrorDetails {
@@ -376,7 +377,7 @@ impl ErrorDetails {
     /// ```
     pub fn with_localized_message(locale: impl Into<String>, message: impl Into<String>) -> Self {
         ErrorDetails {
-            localized_message: Some(LocalizedMessage::new(locale, message)),
+            localized_messages: vec![LocalizedMessage::new(locale, message)],
             ..ErrorDetails::new()
         }
     }
@@ -426,9 +427,38 @@ impl ErrorDetails {
         self.help.as_ref()
     }
 
-    /// Get [`LocalizedMessage`] details, if any.
+    /// Get first [`LocalizedMessage`] details, if any.
     pub fn localized_message(&self) -> Option<&LocalizedMessage> {
-        self.localized_message.as_ref()
+        self.localized_messages.first()
+    }
+
+    /// Get all [`LocalizedMessage`] details.
+    pub fn localized_messages(&self) -> &[LocalizedMessage] {
+        &self.localized_messages
+    }
+
+    /// Get the [`LocalizedMessage`] details best matching
+    /// `preferred_locales`, if any. See [`LocalizedMessage::lookup`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::ErrorDetails;
+    ///
+    /// let mut err_details = ErrorDetails::new();
+    ///
+    /// err_details
+    ///     .add_localized_message("en-US", "message for the user")
+    ///     .add_localized_message("fr-FR", "message pour l'utilisateur");
+    ///
+    /// let message = err_details.localized_message_for(&["fr", "en"]);
+    /// assert!(message.is_none());
+    ///
+    /// let message = err_details.localized_message_for(&["fr-FR", "en"]).unwrap();
+    /// assert_eq!(message.locale, "fr-FR");
+    /// ```
+    pub fn localized_message_for(&self, preferred_locales: &[&str]) -> Option<&LocalizedMessage> {
+        LocalizedMessage::lookup(&self.localized_messages, preferred_locales)
     }
 
     /// Set [`RetryInfo`] details. Can be chained with other `.set_` and
@@ -876,7 +906,31 @@ impl ErrorDetails {
         locale: impl Into<String>,
         message: impl Into<String>,
     ) -> &mut Self {
-        self.localized_message = Some(LocalizedMessage::new(locale, message));
+        self.localized_messages = vec![LocalizedMessage::new(locale, message)];
+        self
+    }
+
+    /// Adds [`LocalizedMessage`] details for another locale. Can be chained
+    /// with other `.set_` and `.add_` [`ErrorDetails`] methods.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::ErrorDetails;
+    ///
+    /// let mut err_details = ErrorDetails::new();
+    ///
+    /// err_details
+    ///     .add_localized_message("en-US", "message for the user")
+    ///     .add_localized_message("fr-FR", "message pour l'utilisateur");
+    /// ```
+    pub fn add_localized_message(
+        &mut self,
+        locale: impl Into<String>,
+        message: impl Into<String>,
+    ) -> &mut Self {
+        self.localized_messages
+            .push(LocalizedMessage::new(locale, message));
         self
     }
 }
@@ -2,40 +2,87 @@ use super::super::std_messages::{
     BadRequest, DebugInfo, ErrorInfo, Help, LocalizedMessage, PreconditionFailure, QuotaFailure,
     RequestInfo, ResourceInfo, RetryInfo,
 };
+use super::ErrorDetails;
 
 /// Wraps the structs corresponding to the standard error messages, allowing
 /// the implementation and handling of vectors containing any of them.
+///
+/// With the `serde` feature enabled, each variant is (de)serialized using the
+/// canonical protobuf JSON mapping of `google.protobuf.Any`, where the `@type`
+/// field holds the type URL of the wrapped standard error message.
 #[non_exhaustive]
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "@type"))]
 pub enum ErrorDetail {
     /// Wraps the [`RetryInfo`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.RetryInfo")
+    )]
     RetryInfo(RetryInfo),
 
     /// Wraps the [`DebugInfo`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.DebugInfo")
+    )]
     DebugInfo(DebugInfo),
 
     /// Wraps the [`QuotaFailure`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.QuotaFailure")
+    )]
     QuotaFailure(QuotaFailure),
 
     /// Wraps the [`ErrorInfo`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.ErrorInfo")
+    )]
     ErrorInfo(ErrorInfo),
 
     /// Wraps the [`PreconditionFailure`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.PreconditionFailure")
+    )]
     PreconditionFailure(PreconditionFailure),
 
     /// Wraps the [`BadRequest`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.BadRequest")
+    )]
     BadRequest(BadRequest),
 
     /// Wraps the [`RequestInfo`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.RequestInfo")
+    )]
     RequestInfo(RequestInfo),
 
     /// Wraps the [`ResourceInfo`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.ResourceInfo")
+    )]
     ResourceInfo(ResourceInfo),
 
     /// Wraps the [`Help`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.Help")
+    )]
     Help(Help),
 
     /// Wraps the [`LocalizedMessage`] struct.
+    #[cfg_attr(
+        feature = "serde",
+        serde(rename = "type.googleapis.com/google.rpc.LocalizedMessage")
+    )]
     LocalizedMessage(LocalizedMessage),
 }
 
@@ -98,3 +145,131 @@ impl From<LocalizedMessage> for ErrorDetail {
         ErrorDetail::LocalizedMessage(err_detail)
     }
 }
+
+impl From<ErrorDetails> for Vec<ErrorDetail> {
+    fn from(details: ErrorDetails) -> Self {
+        let ErrorDetails {
+            retry_info,
+            debug_info,
+            quota_failure,
+            error_info,
+            precondition_failure,
+            bad_request,
+            request_info,
+            resource_info,
+            help,
+            localized_messages,
+        } = details;
+
+        let mut details: Vec<ErrorDetail> = Vec::with_capacity(10);
+
+        details.extend(retry_info.map(Into::into));
+        details.extend(debug_info.map(Into::into));
+        details.extend(quota_failure.map(Into::into));
+        details.extend(error_info.map(Into::into));
+        details.extend(precondition_failure.map(Into::into));
+        details.extend(bad_request.map(Into::into));
+        details.extend(request_info.map(Into::into));
+        details.extend(resource_info.map(Into::into));
+        details.extend(help.map(Into::into));
+        details.extend(localized_messages.into_iter().map(Into::into));
+
+        details
+    }
+}
+
+/// A standard error message, held by the details of statuses.
+pub trait Detail: crate::sealed::Sealed + Sized {
+    /// The message held by `detail`, if of this type.
+    fn from_detail(detail: &ErrorDetail) -> Option<&Self>;
+}
+
+macro_rules! impl_detail {
+    ($($message:ident),* $(,)?) => {
+        $(
+            impl crate::sealed::Sealed for $message {}
+
+            impl Detail for $message {
+                fn from_detail(detail: &ErrorDetail) -> Option<&Self> {
+                    match detail {
+                        ErrorDetail::$message(message) => Some(message),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_detail!(
+    RetryInfo,
+    DebugInfo,
+    QuotaFailure,
+    ErrorInfo,
+    PreconditionFailure,
+    BadRequest,
+    RequestInfo,
+    ResourceInfo,
+    Help,
+    LocalizedMessage,
+);
+
+/// Adds methods querying the details of a given type to vectors of
+/// [`ErrorDetail`] enums, such as the ones returned by
+/// [`StatusExt::get_error_details_vec`]. The details are looked up in the
+/// order they were attached to the status. This trait is sealed and not meant
+/// to be implemented outside of `tonic-types`.
+///
+/// # Examples
+///
+/// ```
+/// use tonic::{Code, Status};
+/// use tonic_types::{ErrorDetailsVec, Help, RetryInfo, StatusExt};
+///
+/// let status = Status::with_error_details_vec(
+///     Code::Unavailable,
+///     "unavailable",
+///     vec![
+///         Help::with_link("status page", "https://status.example.local").into(),
+///         RetryInfo::new(None).into(),
+///         Help::with_link("support", "https://support.example.local").into(),
+///     ],
+/// );
+///
+/// let details = status.get_error_details_vec();
+///
+/// assert_eq!(details.count_details::<Help>(), 2);
+/// assert_eq!(
+///     details.nth_detail::<Help>(1).unwrap().links[0].description,
+///     "support"
+/// );
+/// assert!(details.nth_detail::<Help>(2).is_none());
+/// ```
+///
+/// [`StatusExt::get_error_details_vec`]: crate::StatusExt::get_error_details_vec
+pub trait ErrorDetailsVec: crate::sealed::Sealed {
+    /// Returns an iterator over the details of type `D`, in order.
+    fn details_of<'a, D: Detail + 'a>(&'a self) -> impl Iterator<Item = &'a D>;
+
+    /// Returns the `n`th detail of type `D`, counting from zero, if any.
+    fn nth_detail<D: Detail>(&self, n: usize) -> Option<&D>;
+
+    /// Returns the number of details of type `D`.
+    fn count_details<D: Detail>(&self) -> usize;
+}
+
+impl crate::sealed::Sealed for [ErrorDetail] {}
+
+impl ErrorDetailsVec for [ErrorDetail] {
+    fn details_of<'a, D: Detail + 'a>(&'a self) -> impl Iterator<Item = &'a D> {
+        self.iter().filter_map(D::from_detail)
+    }
+
+    fn nth_detail<D: Detail>(&self, n: usize) -> Option<&D> {
+        self.details_of().nth(n)
+    }
+
+    fn count_details<D: Detail>(&self) -> usize {
+        self.details_of::<D>().count()
+    }
+}
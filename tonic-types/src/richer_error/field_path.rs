@@ -0,0 +1,135 @@
+use std::fmt;
+
+/// Path leading to a field in a request message, in the format expected by the
+/// `field` of [`FieldViolation`]: a sequence of dot-separated field names, in
+/// which elements of repeated fields are selected by their index.
+///
+/// The [`field_path!`] macro provides a more concise way of creating paths
+/// that are known at compile time.
+///
+/// # Examples
+///
+/// ```
+/// use tonic_types::FieldPath;
+///
+/// let path = FieldPath::new()
+///     .field("email_addresses")
+///     .index(1)
+///     .field("email");
+///
+/// assert_eq!(path.to_string(), "email_addresses[1].email");
+/// ```
+///
+/// [`FieldViolation`]: crate::FieldViolation
+/// [`field_path!`]: crate::field_path
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FieldPath {
+    path: String,
+}
+
+impl FieldPath {
+    /// Creates a new, empty [`FieldPath`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a field name to the path.
+    pub fn field(mut self, name: impl AsRef<str>) -> Self {
+        if !self.path.is_empty() {
+            self.path.push('.');
+        }
+        self.path.push_str(name.as_ref());
+        self
+    }
+
+    /// Appends the index of an element of a repeated field to the path.
+    pub fn index(mut self, index: usize) -> Self {
+        self.path.push('[');
+        self.path.push_str(&index.to_string());
+        self.path.push(']');
+        self
+    }
+
+    /// Returns the path as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns `true` if no field was appended to the path.
+    pub fn is_empty(&self) -> bool {
+        self.path.is_empty()
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+impl From<FieldPath> for String {
+    fn from(path: FieldPath) -> Self {
+        path.path
+    }
+}
+
+/// Creates a [`FieldPath`] from a sequence of dot-separated field names, in
+/// which elements of repeated fields can be selected with an index expression.
+///
+/// # Examples
+///
+/// ```
+/// use tonic_types::{field_path, BadRequest};
+///
+/// let i = 3;
+///
+/// assert_eq!(field_path!(full_name).to_string(), "full_name");
+/// assert_eq!(
+///     field_path!(email_addresses[i].r#type[2]).as_str(),
+///     "email_addresses[3].type[2]"
+/// );
+///
+/// let bad_request = BadRequest::with_violation(
+///     field_path!(email_addresses[1].email),
+///     "invalid email address",
+/// );
+/// ```
+///
+/// [`FieldPath`]: crate::FieldPath
+#[macro_export]
+macro_rules! field_path {
+    ($($field:ident $([$index:expr])*).+) => {{
+        let path = $crate::FieldPath::new();
+        $(
+            let path = path.field(
+                ::core::stringify!($field).trim_start_matches("r#"),
+            );
+            $( let path = path.index($index); )*
+        )+
+        path
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldPath;
+
+    #[test]
+    fn gen_field_path() {
+        assert!(FieldPath::new().is_empty());
+
+        let path = FieldPath::new()
+            .field("email_addresses")
+            .index(3)
+            .field("type")
+            .index(2);
+
+        assert_eq!(path.as_str(), "email_addresses[3].type[2]");
+
+        let index = 3;
+        let from_macro = crate::field_path!(email_addresses[index].r#type[1 + 1]);
+
+        assert_eq!(from_macro, path);
+        assert_eq!(String::from(from_macro), "email_addresses[3].type[2]");
+    }
+}
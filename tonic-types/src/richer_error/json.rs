@@ -0,0 +1,363 @@
+use serde::{Deserialize, Serialize};
+use tonic::Code;
+
+use super::{ErrorDetail, ErrorDetails, StatusExt};
+
+/// Canonical JSON representation of the `google.rpc.Status` message, as
+/// described by the [protobuf JSON mapping]. Can be used to emit the same
+/// error payloads both over gRPC and over REST/JSON gateways.
+///
+/// Error details that are not one of the standard error messages are not
+/// represented, in the same way as [`StatusExt::get_error_details_vec`]
+/// ignores them.
+///
+/// # Examples
+///
+/// ```
+/// use tonic::{Code, Status};
+/// use tonic_types::{ErrorDetails, JsonStatus};
+///
+/// let mut err_details = ErrorDetails::new();
+/// err_details.add_bad_request_violation("field", "description");
+///
+/// let json_status = JsonStatus::with_error_details(
+///     Code::InvalidArgument,
+///     "bad request",
+///     err_details,
+/// );
+///
+/// // Serialize `json_status` with any serde serializer, or convert it into a
+/// // `tonic::Status` carrying the same error details.
+/// let status: Status = json_status.into();
+/// ```
+///
+/// [protobuf JSON mapping]: https://protobuf.dev/programming-guides/proto3/#json
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JsonStatus {
+    /// The status code, which should be an enum value of `google.rpc.Code`.
+    pub code: i32,
+
+    /// A developer-facing error message.
+    pub message: String,
+
+    /// Standard error messages that carry the error details.
+    pub details: Vec<ErrorDetail>,
+}
+
+impl JsonStatus {
+    /// Creates a new [`JsonStatus`] struct from a vector of standard error
+    /// messages wrapped with the [`ErrorDetail`] enum.
+    pub fn new(
+        code: Code,
+        message: impl Into<String>,
+        details: impl IntoIterator<Item = ErrorDetail>,
+    ) -> Self {
+        JsonStatus {
+            code: code as i32,
+            message: message.into(),
+            details: details.into_iter().collect(),
+        }
+    }
+
+    /// Creates a new [`JsonStatus`] struct with error details obtained from an
+    /// [`ErrorDetails`] struct. Details are ordered in the same way as in
+    /// [`StatusExt::with_error_details`].
+    pub fn with_error_details(
+        code: Code,
+        message: impl Into<String>,
+        details: ErrorDetails,
+    ) -> Self {
+        JsonStatus::new(code, message, Vec::from(details))
+    }
+}
+
+impl From<&tonic::Status> for JsonStatus {
+    fn from(status: &tonic::Status) -> Self {
+        JsonStatus::new(
+            status.code(),
+            status.message(),
+            status.get_error_details_vec(),
+        )
+    }
+}
+
+impl From<tonic::Status> for JsonStatus {
+    fn from(status: tonic::Status) -> Self {
+        JsonStatus::from(&status)
+    }
+}
+
+impl From<JsonStatus> for tonic::Status {
+    fn from(status: JsonStatus) -> Self {
+        tonic::Status::with_error_details_vec(
+            Code::from_i32(status.code),
+            status.message,
+            status.details,
+        )
+    }
+}
+
+/// (De)serializes `Option<Duration>` values as JSON strings in the format used
+/// by `google.protobuf.Duration`, such as `"1.5s"`. Negative durations become
+/// 0, matching the behavior of the protobuf conversions.
+pub(crate) mod opt_duration {
+    use std::time::Duration;
+
+    use serde::{de::Error as _, Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S>(value: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let duration = match value {
+            Some(duration) => duration,
+            None => return serializer.serialize_none(),
+        };
+
+        let secs = duration.as_secs();
+        let nanos = duration.subsec_nanos();
+
+        if nanos == 0 {
+            serializer.collect_str(&format_args!("{secs}s"))
+        } else if nanos % 1_000_000 == 0 {
+            serializer.collect_str(&format_args!("{secs}.{:03}s", nanos / 1_000_000))
+        } else if nanos % 1_000 == 0 {
+            serializer.collect_str(&format_args!("{secs}.{:06}s", nanos / 1_000))
+        } else {
+            serializer.collect_str(&format_args!("{secs}.{nanos:09}s"))
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = match Option::<String>::deserialize(deserializer)? {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        parse(&value)
+            .map(Some)
+            .ok_or_else(|| D::Error::custom(format!("invalid duration: {value:?}")))
+    }
+
+    fn parse(value: &str) -> Option<Duration> {
+        let value = value.strip_suffix('s')?;
+
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+
+        let (secs, frac) = match value.split_once('.') {
+            Some((secs, frac)) => (secs, frac),
+            None => (value, ""),
+        };
+
+        if secs.is_empty() || frac.len() > 9 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        let secs: u64 = secs.parse().ok()?;
+        let nanos: u32 = if frac.is_empty() {
+            0
+        } else {
+            format!("{frac:0<9}").parse().ok()?
+        };
+
+        if negative {
+            return Some(Duration::ZERO);
+        }
+
+        Some(Duration::new(secs, nanos))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Int64 {
+    String(String),
+    Number(i64),
+}
+
+impl Int64 {
+    fn into_i64<E: serde::de::Error>(self) -> Result<i64, E> {
+        match self {
+            Int64::String(value) => value
+                .parse()
+                .map_err(|_| E::custom(format!("invalid int64: {value:?}"))),
+            Int64::Number(value) => Ok(value),
+        }
+    }
+}
+
+/// (De)serializes `i64` values as JSON strings, as required by the protobuf
+/// JSON mapping. Both strings and numbers are accepted when deserializing.
+pub(crate) mod int64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S>(value: &i64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<i64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        super::Int64::deserialize(deserializer)?.into_i64()
+    }
+}
+
+/// Same as [`int64`], for `Option<i64>` values.
+pub(crate) mod opt_int64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S>(value: &Option<i64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match value {
+            Some(value) => serializer.collect_str(value),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D>(deserializer: D) -> Result<Option<i64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<super::Int64>::deserialize(deserializer)?
+            .map(super::Int64::into_i64)
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+    use tonic::{Code, Status};
+
+    use super::super::{ErrorDetails, QuotaViolation, StatusExt};
+    use super::JsonStatus;
+
+    #[test]
+    fn gen_json_status() {
+        let mut err_details = ErrorDetails::new();
+
+        err_details
+            .set_retry_info(Some(Duration::from_millis(1500)))
+            .set_quota_failure(vec![QuotaViolation {
+                quota_value: 100,
+                ..QuotaViolation::new("clientip:<ip address>", "description")
+            }])
+            .add_precondition_failure_violation("TOS", "example.local", "description")
+            .add_bad_request_violation("field", "description");
+
+        let json_status =
+            JsonStatus::with_error_details(Code::InvalidArgument, "bad request", err_details);
+
+        let value = serde_json::to_value(&json_status).unwrap();
+
+        let expected = json!({
+            "code": 3,
+            "message": "bad request",
+            "details": [
+                {
+                    "@type": "type.googleapis.com/google.rpc.RetryInfo",
+                    "retryDelay": "1.500s"
+                },
+                {
+                    "@type": "type.googleapis.com/google.rpc.QuotaFailure",
+                    "violations": [{
+                        "subject": "clientip:<ip address>",
+                        "description": "description",
+                        "apiService": "",
+                        "quotaMetric": "",
+                        "quotaId": "",
+                        "quotaDimensions": {},
+                        "quotaValue": "100"
+                    }]
+                },
+                {
+                    "@type": "type.googleapis.com/google.rpc.PreconditionFailure",
+                    "violations": [{
+                        "type": "TOS",
+                        "subject": "example.local",
+                        "description": "description"
+                    }]
+                },
+                {
+                    "@type": "type.googleapis.com/google.rpc.BadRequest",
+                    "fieldViolations": [{
+                        "field": "field",
+                        "description": "description",
+                        "reason": ""
+                    }]
+                }
+            ]
+        });
+
+        assert_eq!(value, expected, "JsonStatus differs from expected JSON");
+
+        let status = Status::from(json_status);
+        let from_status = JsonStatus::from(&status);
+
+        assert_eq!(
+            serde_json::to_value(&from_status).unwrap(),
+            expected,
+            "JsonStatus extracted from Status differs from expected JSON"
+        );
+    }
+
+    #[test]
+    fn parse_json_status() {
+        let value = json!({
+            "code": 8,
+            "details": [
+                {
+                    "@type": "type.googleapis.com/google.rpc.RetryInfo",
+                    "retryDelay": "2.000000001s"
+                },
+                {
+                    "@type": "type.googleapis.com/google.rpc.QuotaFailure",
+                    "violations": [{ "subject": "project", "futureQuotaValue": 7 }]
+                }
+            ]
+        });
+
+        let json_status: JsonStatus = serde_json::from_value(value).unwrap();
+        let status = Status::from(json_status);
+
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.message(), "");
+
+        let err_details = status.get_error_details();
+
+        assert_eq!(
+            err_details.retry_info().unwrap().retry_delay,
+            Some(Duration::new(2, 1))
+        );
+
+        let violation = &err_details.quota_failure().unwrap().violations[0];
+
+        assert_eq!(violation.subject, "project");
+        assert_eq!(violation.futura_quota_value, Some(7));
+    }
+
+    #[test]
+    fn reject_invalid_duration() {
+        let value = json!({
+            "@type": "type.googleapis.com/google.rpc.RetryInfo",
+            "retryDelay": "5 seconds"
+        });
+
+        assert!(serde_json::from_value::<super::ErrorDetail>(value).is_err());
+    }
+}
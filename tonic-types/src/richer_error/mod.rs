@@ -1,3 +1,5 @@
+use std::{error::Error, time::Duration};
+
 use prost::{
     bytes::{Bytes, BytesMut},
     DecodeError, Message,
@@ -6,16 +8,33 @@ use prost_types::Any;
 use tonic::{metadata::MetadataMap, Code};
 
 mod error_details;
+mod field_path;
+#[cfg(feature = "serde")]
+pub(crate) mod json;
 mod std_messages;
+#[cfg(any(feature = "validator", feature = "garde", feature = "prost-validate"))]
+mod validation;
 
 use super::pb;
 
-pub use error_details::{vec::ErrorDetail, ErrorDetails};
+pub use error_details::{
+    iter::{DetailsIter, StatusDetail},
+    vec::{Detail, ErrorDetail, ErrorDetailsVec},
+    ErrorDetails,
+};
+pub use field_path::FieldPath;
+#[cfg(feature = "serde")]
+pub use json::JsonStatus;
 pub use std_messages::{
-    BadRequest, DebugInfo, ErrorInfo, FieldViolation, Help, HelpLink, LocalizedMessage,
-    PreconditionFailure, PreconditionViolation, QuotaFailure, QuotaViolation, RequestInfo,
-    ResourceInfo, RetryInfo,
+    is_valid_error_reason, BadRequest, BadRequestBuilder, CommonPreconditionType, DebugInfo,
+    ErrorInfo, ErrorInfoBuilder, ErrorReason, FieldViolation, FieldViolationBuilder, Help,
+    HelpBuilder, HelpLink, LocalizedMessage, PreconditionFailure, PreconditionFailureBuilder,
+    PreconditionType, PreconditionViolation, PreconditionViolationBuilder, QuotaFailure,
+    QuotaFailureBuilder, QuotaViolation, QuotaViolationBuilder, RequestInfo, ResourceInfo,
+    RetryInfo,
 };
+#[cfg(feature = "prost-validate")]
+pub use validation::validate_request;
 
 trait IntoAny {
     fn into_any(self) -> Any;
@@ -123,7 +142,8 @@ pub trait StatusExt: crate::sealed::Sealed {
     ) -> tonic::Status;
 
     /// Generates a `tonic::Status` with error details provided in a vector of
-    /// [`ErrorDetail`] enums.
+    /// [`ErrorDetail`] enums. The details are attached in the order they are
+    /// provided, including multiple details of the same type.
     ///
     /// # Examples
     ///
@@ -145,6 +165,29 @@ pub trait StatusExt: crate::sealed::Sealed {
         details: impl IntoIterator<Item = ErrorDetail>,
     ) -> tonic::Status;
 
+    /// Creates a `tonic::Status` from an error and its source chain. If a
+    /// `tonic::Status` is found in the chain, it is returned. Otherwise, a
+    /// `tonic::Status` with `Code::Unknown` and the message of `err` is
+    /// returned. Use a [`StatusMapper`] to configure how other error types
+    /// are converted.
+    ///
+    /// `anyhow::Error` can be converted through `err.as_ref()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic::{Code, Status};
+    /// use tonic_types::StatusExt;
+    ///
+    /// let err: Box<dyn std::error::Error> = Status::not_found("no such book").into();
+    /// let status = Status::from_error_chain(&*err);
+    ///
+    /// assert_eq!(status.code(), Code::NotFound);
+    /// ```
+    ///
+    /// [`StatusMapper`]: crate::StatusMapper
+    fn from_error_chain(err: &(dyn Error + 'static)) -> tonic::Status;
+
     /// Can be used to check if the error details contained in `tonic::Status`
     /// are malformed or not. Tries to get an [`ErrorDetails`] struct from a
     /// `tonic::Status`. If some `prost::DecodeError` occurs, it will be
@@ -231,6 +274,10 @@ pub trait StatusExt: crate::sealed::Sealed {
     /// Get a vector of [`ErrorDetail`] enums from `tonic::Status`. If some
     /// `prost::DecodeError` occurs, an empty vector will be returned.
     ///
+    /// The details keep the order in which they were attached, and multiple
+    /// details of the same type are all returned. See [`ErrorDetailsVec`] to
+    /// query them by type.
+    ///
     /// # Examples
     ///
     /// ```
@@ -257,6 +304,34 @@ pub trait StatusExt: crate::sealed::Sealed {
     /// ```
     fn get_error_details_vec(&self) -> Vec<ErrorDetail>;
 
+    /// Get an iterator over all the details found on `tonic::Status`,
+    /// including the ones that are not standard error messages, which are
+    /// yielded as [`StatusDetail::Unknown`]. Standard error messages that
+    /// can't be decoded are also yielded as [`StatusDetail::Unknown`]. If
+    /// the details of the `tonic::Status` are malformed, the iterator will be
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic::Status;
+    /// use tonic_types::{StatusDetail, StatusExt};
+    ///
+    /// fn log_details(status: &Status) {
+    ///     for detail in status.iter_details() {
+    ///         match detail {
+    ///             StatusDetail::Known(err_detail) => {
+    ///                 println!("{err_detail:?}");
+    ///             }
+    ///             StatusDetail::Unknown(any) => {
+    ///                 println!("unknown detail of type {}", any.type_url);
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    fn iter_details(&self) -> DetailsIter;
+
     /// Get first [`RetryInfo`] details found on `tonic::Status`, if any. If
     /// some `prost::DecodeError` occurs, returns `None`.
     ///
@@ -279,6 +354,32 @@ pub trait StatusExt: crate::sealed::Sealed {
     /// ```
     fn get_details_retry_info(&self) -> Option<RetryInfo>;
 
+    /// Get the `retry_delay` of the first [`RetryInfo`] details found on
+    /// `tonic::Status`, if any. If some `prost::DecodeError` occurs, returns
+    /// `None`. See [`RetryBackoff`] for a way of combining it with a fallback
+    /// backoff strategy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic::{Status, Response};
+    /// use tonic_types::StatusExt;
+    ///
+    /// fn handle_request_result<T>(req_result: Result<Response<T>, Status>) {
+    ///     match req_result {
+    ///         Ok(_) => {},
+    ///         Err(status) => {
+    ///             if let Some(retry_delay) = status.get_retry_delay() {
+    ///                 // Wait for retry_delay before retrying the request
+    ///             }
+    ///         }
+    ///     };
+    /// }
+    /// ```
+    ///
+    /// [`RetryBackoff`]: crate::RetryBackoff
+    fn get_retry_delay(&self) -> Option<Duration>;
+
     /// Get first [`DebugInfo`] details found on `tonic::Status`, if any. If
     /// some `prost::DecodeError` occurs, returns `None`.
     ///
@@ -476,6 +577,30 @@ pub trait StatusExt: crate::sealed::Sealed {
     /// }
     /// ```
     fn get_details_localized_message(&self) -> Option<LocalizedMessage>;
+
+    /// Get the [`LocalizedMessage`] details found on `tonic::Status` best
+    /// matching `preferred_locales`, ordered from the most to the least
+    /// preferred, if any. See [`LocalizedMessage::lookup`]. If some
+    /// `prost::DecodeError` occurs, returns `None`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic::{Status, Response};
+    /// use tonic_types::StatusExt;
+    ///
+    /// fn handle_request_result<T>(req_result: Result<Response<T>, Status>) {
+    ///     match req_result {
+    ///         Ok(_) => {},
+    ///         Err(status) => {
+    ///             if let Some(localized_message) = status.get_localized_message(&["fr-CH", "en"]) {
+    ///                 // Show localized_message to the user
+    ///             }
+    ///         }
+    ///     };
+    /// }
+    /// ```
+    fn get_localized_message(&self, preferred_locales: &[&str]) -> Option<LocalizedMessage>;
 }
 
 impl crate::sealed::Sealed for tonic::Status {}
@@ -527,7 +652,7 @@ impl StatusExt for tonic::Status {
             conv_details.push(help.into_any());
         }
 
-        if let Some(localized_message) = details.localized_message {
+        for localized_message in details.localized_messages {
             conv_details.push(localized_message.into_any());
         }
 
@@ -603,6 +728,10 @@ impl StatusExt for tonic::Status {
         )
     }
 
+    fn from_error_chain(err: &(dyn Error + 'static)) -> Self {
+        crate::StatusMapper::new().map_error(err)
+    }
+
     fn check_error_details(&self) -> Result<ErrorDetails, DecodeError> {
         let status = pb::Status::decode(self.details())?;
 
@@ -623,12 +752,25 @@ impl StatusExt for tonic::Status {
         self.check_error_details_vec().unwrap_or_default()
     }
 
+    fn iter_details(&self) -> DetailsIter {
+        match pb::Status::decode(self.details()) {
+            Ok(status) => DetailsIter::new(status.details),
+            Err(_) => DetailsIter::new(Vec::new()),
+        }
+    }
+
     fn get_details_retry_info(&self) -> Option<RetryInfo> {
         let status = pb::Status::decode(self.details()).ok()?;
 
         status.get_details_retry_info()
     }
 
+    fn get_retry_delay(&self) -> Option<Duration> {
+        let status = pb::Status::decode(self.details()).ok()?;
+
+        status.get_retry_delay()
+    }
+
     fn get_details_debug_info(&self) -> Option<DebugInfo> {
         let status = pb::Status::decode(self.details()).ok()?;
 
@@ -682,6 +824,12 @@ impl StatusExt for tonic::Status {
 
         status.get_details_localized_message()
     }
+
+    fn get_localized_message(&self, preferred_locales: &[&str]) -> Option<LocalizedMessage> {
+        let status = pb::Status::decode(self.details()).ok()?;
+
+        status.get_localized_message(preferred_locales)
+    }
 }
 
 impl crate::sealed::Sealed for pb::Status {}
@@ -712,13 +860,25 @@ pub trait RpcStatusExt: crate::sealed::Sealed {
     fn check_error_details_vec(&self) -> Result<Vec<ErrorDetail>, DecodeError>;
 
     /// Get a vector of [`ErrorDetail`] enums from `pb::Status`. If some
-    /// `prost::DecodeError` occurs, an empty vector will be returned.
+    /// `prost::DecodeError` occurs, an empty vector will be returned. The
+    /// details keep the order in which they were attached.
     fn get_error_details_vec(&self) -> Vec<ErrorDetail>;
 
+    /// Get an iterator over all the details found on `pb::Status`,
+    /// including the ones that are not standard error messages, which are
+    /// yielded as [`StatusDetail::Unknown`]. Standard error messages that
+    /// can't be decoded are also yielded as [`StatusDetail::Unknown`].
+    fn iter_details(&self) -> DetailsIter;
+
     /// Get first [`RetryInfo`] details found on `pb::Status`, if any. If
     /// some `prost::DecodeError` occurs, returns `None`.
     fn get_details_retry_info(&self) -> Option<RetryInfo>;
 
+    /// Get the `retry_delay` of the first [`RetryInfo`] details found on
+    /// `pb::Status`, if any. If some `prost::DecodeError` occurs, returns
+    /// `None`.
+    fn get_retry_delay(&self) -> Option<Duration>;
+
     /// Get first [`DebugInfo`] details found on `pb::Status`, if any. If
     /// some `prost::DecodeError` occurs, returns `None`.
     fn get_details_debug_info(&self) -> Option<DebugInfo>;
@@ -754,6 +914,12 @@ pub trait RpcStatusExt: crate::sealed::Sealed {
     /// Get first [`LocalizedMessage`] details found on `pb::Status`, if
     /// any. If some `prost::DecodeError` occurs, returns `None`.
     fn get_details_localized_message(&self) -> Option<LocalizedMessage>;
+
+    /// Get the [`LocalizedMessage`] details found on `pb::Status` best
+    /// matching `preferred_locales`, ordered from the most to the least
+    /// preferred, if any. See [`LocalizedMessage::lookup`]. If some
+    /// `prost::DecodeError` occurs, returns `None`.
+    fn get_localized_message(&self, preferred_locales: &[&str]) -> Option<LocalizedMessage>;
 }
 
 impl RpcStatusExt for pb::Status {
@@ -790,7 +956,9 @@ impl RpcStatusExt for pb::Status {
                     details.help = Some(Help::from_any_ref(any)?);
                 }
                 LocalizedMessage::TYPE_URL => {
-                    details.localized_message = Some(LocalizedMessage::from_any_ref(any)?);
+                    details
+                        .localized_messages
+                        .push(LocalizedMessage::from_any_ref(any)?);
                 }
                 _ => {}
             }
@@ -849,6 +1017,10 @@ impl RpcStatusExt for pb::Status {
         self.check_error_details_vec().unwrap_or_default()
     }
 
+    fn iter_details(&self) -> DetailsIter {
+        DetailsIter::new(self.details.clone())
+    }
+
     fn get_details_retry_info(&self) -> Option<RetryInfo> {
         for any in self.details.iter() {
             if any.type_url.as_str() == RetryInfo::TYPE_URL {
@@ -861,6 +1033,10 @@ impl RpcStatusExt for pb::Status {
         None
     }
 
+    fn get_retry_delay(&self) -> Option<Duration> {
+        self.get_details_retry_info()?.retry_delay
+    }
+
     fn get_details_debug_info(&self) -> Option<DebugInfo> {
         for any in self.details.iter() {
             if any.type_url.as_str() == DebugInfo::TYPE_URL {
@@ -968,6 +1144,17 @@ impl RpcStatusExt for pb::Status {
 
         None
     }
+
+    fn get_localized_message(&self, preferred_locales: &[&str]) -> Option<LocalizedMessage> {
+        let messages: Vec<LocalizedMessage> = self
+            .details
+            .iter()
+            .filter(|any| any.type_url.as_str() == LocalizedMessage::TYPE_URL)
+            .filter_map(|any| LocalizedMessage::from_any_ref(any).ok())
+            .collect();
+
+        LocalizedMessage::lookup(&messages, preferred_locales).cloned()
+    }
 }
 
 #[cfg(test)]
@@ -975,9 +1162,12 @@ mod tests {
     use std::{collections::HashMap, time::Duration};
     use tonic::{Code, Status};
 
+    use prost_types::Any;
+
     use super::{
-        BadRequest, DebugInfo, ErrorDetails, ErrorInfo, Help, LocalizedMessage,
-        PreconditionFailure, QuotaFailure, RequestInfo, ResourceInfo, RetryInfo, StatusExt,
+        gen_details_bytes, BadRequest, DebugInfo, ErrorDetail, ErrorDetails, ErrorDetailsVec,
+        ErrorInfo, Help, IntoAny, LocalizedMessage, PreconditionFailure, QuotaFailure, RequestInfo,
+        ResourceInfo, RetryInfo, StatusExt,
     };
 
     #[test]
@@ -1059,4 +1249,128 @@ mod tests {
             "Extracted details vec differs from original details vec"
         );
     }
+
+    #[test]
+    fn round_trip_details_vec_in_order() {
+        let details: Vec<ErrorDetail> = vec![
+            Help::with_link("link to resource a", "https://a.example.local").into(),
+            BadRequest::with_violation("field_a", "description").into(),
+            LocalizedMessage::new("fr", "message pour l'utilisateur").into(),
+            Help::with_link("link to resource b", "https://b.example.local").into(),
+            RetryInfo::new(Some(Duration::from_secs(1))).into(),
+            BadRequest::with_violation("field_b", "description").into(),
+            LocalizedMessage::new("en-US", "message for the user").into(),
+        ];
+
+        let fmt_details = format!("{details:?}");
+
+        let status = Status::with_error_details_vec(Code::InvalidArgument, "bad request", details);
+
+        let ext_details = status.check_error_details_vec().unwrap();
+
+        assert_eq!(format!("{ext_details:?}"), fmt_details);
+
+        assert_eq!(ext_details.count_details::<Help>(), 2);
+        assert_eq!(ext_details.count_details::<DebugInfo>(), 0);
+        assert_eq!(
+            ext_details
+                .nth_detail::<BadRequest>(1)
+                .unwrap()
+                .field_violations[0]
+                .field,
+            "field_b"
+        );
+        assert_eq!(
+            ext_details
+                .nth_detail::<LocalizedMessage>(0)
+                .unwrap()
+                .locale,
+            "fr"
+        );
+        assert!(ext_details.nth_detail::<RetryInfo>(1).is_none());
+
+        let locales: Vec<_> = ext_details
+            .details_of::<LocalizedMessage>()
+            .map(|message| message.locale.as_str())
+            .collect();
+        assert_eq!(locales, ["fr", "en-US"]);
+
+        let again =
+            Status::with_error_details_vec(Code::InvalidArgument, "bad request", ext_details);
+
+        assert_eq!(format!("{:?}", again.get_error_details_vec()), fmt_details);
+    }
+
+    #[test]
+    fn iter_status_details() {
+        let unknown = Any {
+            type_url: "type.example.local/Unknown".into(),
+            value: vec![1, 2, 3],
+        };
+        let malformed = Any {
+            type_url: BadRequest::TYPE_URL.into(),
+            value: vec![0xff],
+        };
+
+        let details = vec![
+            RetryInfo::new(Some(Duration::from_secs(5))).into_any(),
+            unknown,
+            malformed,
+            LocalizedMessage::new("en-US", "message for the user").into_any(),
+        ];
+
+        let status = Status::with_details(
+            Code::Unavailable,
+            "unavailable",
+            gen_details_bytes(Code::Unavailable, "unavailable", details),
+        );
+
+        let iter = status.iter_details();
+
+        assert_eq!(iter.len(), 4);
+
+        let type_urls: Vec<String> = iter.clone().map(|d| d.type_url().to_owned()).collect();
+
+        assert_eq!(
+            type_urls,
+            [
+                RetryInfo::TYPE_URL,
+                "type.example.local/Unknown",
+                BadRequest::TYPE_URL,
+                LocalizedMessage::TYPE_URL,
+            ]
+        );
+
+        let fmt_details = format!("{:?}", iter.collect::<Vec<_>>());
+
+        let expected = "[Known(RetryInfo(RetryInfo { retry_delay: Some(5s) })), Unknown(Any { type_url: \"type.example.local/Unknown\", value: [1, 2, 3] }), Unknown(Any { type_url: \"type.googleapis.com/google.rpc.BadRequest\", value: [255] }), Known(LocalizedMessage(LocalizedMessage { locale: \"en-US\", message: \"message for the user\" }))]";
+
+        assert!(
+            fmt_details.eq(expected),
+            "Iterated details differ from expected result"
+        );
+
+        assert_eq!(Status::internal("no details").iter_details().len(), 0);
+    }
+
+    #[test]
+    fn get_preferred_localized_message() {
+        let mut err_details = ErrorDetails::new();
+
+        err_details
+            .add_localized_message("en-US", "message for the user")
+            .add_localized_message("fr", "message pour l'utilisateur");
+
+        let status = Status::with_error_details(Code::NotFound, "not found", err_details);
+
+        assert_eq!(status.get_error_details().localized_messages().len(), 2);
+
+        let message = status.get_localized_message(&["fr-CH", "en"]).unwrap();
+        assert_eq!(message.message, "message pour l'utilisateur");
+
+        let message = status.get_localized_message(&["de", "en-us"]).unwrap();
+        assert_eq!(message.locale, "en-US");
+
+        assert!(status.get_localized_message(&["de"]).is_none());
+    }
 }
@@ -8,6 +8,8 @@ use super::super::{pb, FromAny, IntoAny};
 /// Used at the `field_violations` field of the [`BadRequest`] struct.
 /// Describes a single bad request field.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct FieldViolation {
     /// Path leading to a field in the request body. Value should be a
     /// sequence of dot-separated identifiers that identify a protocol buffer
@@ -23,6 +25,7 @@ pub struct FieldViolation {
     pub reason: String,
 
     /// A localized version of the field-level error.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
     pub localized_message: Option<LocalizedMessage>,
 }
 
@@ -35,6 +38,73 @@ impl FieldViolation {
             ..Default::default()
         }
     }
+
+    /// Creates a new [`FieldViolationBuilder`], allowing every field of a
+    /// [`FieldViolation`] to be set fluently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::FieldViolation;
+    ///
+    /// let violation = FieldViolation::builder()
+    ///     .field("user.email")
+    ///     .description("invalid email address")
+    ///     .reason("INVALID_EMAIL")
+    ///     .localized_message("en-US", "Please provide a valid email address")
+    ///     .build();
+    /// ```
+    pub fn builder() -> FieldViolationBuilder {
+        FieldViolationBuilder::default()
+    }
+}
+
+/// Builder for [`FieldViolation`] structs. Created by
+/// [`FieldViolation::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct FieldViolationBuilder {
+    violation: FieldViolation,
+}
+
+impl FieldViolationBuilder {
+    /// Sets the path leading to a field in the request body.
+    pub fn field(mut self, field: impl Into<String>) -> Self {
+        self.violation.field = field.into();
+        self
+    }
+
+    /// Sets the description of why the field is bad.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.violation.description = description.into();
+        self
+    }
+
+    /// Sets the reason of the field-level error.
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.violation.reason = reason.into();
+        self
+    }
+
+    /// Sets a localized version of the field-level error.
+    pub fn localized_message(
+        mut self,
+        locale: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Self {
+        self.violation.localized_message = Some(LocalizedMessage::new(locale, message));
+        self
+    }
+
+    /// Builds the [`FieldViolation`].
+    pub fn build(self) -> FieldViolation {
+        self.violation
+    }
+}
+
+impl From<FieldViolationBuilder> for FieldViolation {
+    fn from(builder: FieldViolationBuilder) -> Self {
+        builder.build()
+    }
 }
 
 impl From<pb::bad_request::FieldViolation> for FieldViolation {
@@ -53,7 +123,8 @@ impl From<FieldViolation> for pb::bad_request::FieldViolation {
         pb::bad_request::FieldViolation {
             field: value.field,
             description: value.description,
-            ..Default::default()
+            reason: value.reason,
+            localized_message: value.localized_message.map(Into::into),
         }
     }
 }
@@ -63,7 +134,9 @@ impl From<FieldViolation> for pb::bad_request::FieldViolation {
 /// on the syntactic aspects of the request.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct BadRequest {
     /// Describes all field violations of the request.
     pub field_violations: Vec<FieldViolation>,
@@ -111,6 +184,42 @@ impl BadRequest {
     pub fn is_empty(&self) -> bool {
         self.field_violations.is_empty()
     }
+
+    /// Creates a new [`BadRequestBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::{BadRequest, FieldViolation};
+    ///
+    /// let bad_request = BadRequest::builder()
+    ///     .violation(FieldViolation::builder().field("name").reason("REQUIRED"))
+    ///     .violation(FieldViolation::new("email", "invalid email address"))
+    ///     .build();
+    /// ```
+    pub fn builder() -> BadRequestBuilder {
+        BadRequestBuilder::default()
+    }
+}
+
+/// Builder for [`BadRequest`] structs. Created by [`BadRequest::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct BadRequestBuilder {
+    field_violations: Vec<FieldViolation>,
+}
+
+impl BadRequestBuilder {
+    /// Adds a [`FieldViolation`], or a [`FieldViolationBuilder`], to the
+    /// `field_violations` vector.
+    pub fn violation(mut self, violation: impl Into<FieldViolation>) -> Self {
+        self.field_violations.push(violation.into());
+        self
+    }
+
+    /// Builds the [`BadRequest`].
+    pub fn build(self) -> BadRequest {
+        BadRequest::new(self.field_violations)
+    }
 }
 
 impl IntoAny for BadRequest {
@@ -159,7 +268,41 @@ impl From<BadRequest> for pb::BadRequest {
 #[cfg(test)]
 mod tests {
     use super::super::super::{FromAny, IntoAny};
-    use super::BadRequest;
+    use super::{BadRequest, FieldViolation};
+
+    #[test]
+    fn gen_bad_request_with_builder() {
+        let br_details = BadRequest::builder()
+            .violation(
+                FieldViolation::builder()
+                    .field("field")
+                    .description("description")
+                    .reason("REQUIRED")
+                    .localized_message("en-US", "message for the user"),
+            )
+            .build();
+
+        let formatted = format!("{br_details:?}");
+
+        let expected = "BadRequest { field_violations: [FieldViolation { field: \"field\", description: \"description\", reason: \"REQUIRED\", localized_message: Some(LocalizedMessage { locale: \"en-US\", message: \"message for the user\" }) }] }";
+
+        assert!(
+            formatted.eq(expected),
+            "BadRequest from builder differs from expected result"
+        );
+
+        let br_details = match BadRequest::from_any(br_details.into_any()) {
+            Err(error) => panic!("Error generating BadRequest from Any: {error:?}"),
+            Ok(from_any) => from_any,
+        };
+
+        let formatted = format!("{br_details:?}");
+
+        assert!(
+            formatted.eq(expected),
+            "BadRequest from Any differs from expected result"
+        );
+    }
 
     #[test]
     fn gen_bad_request() {
@@ -9,7 +9,9 @@ use super::super::{pb, FromAny, IntoAny};
 /// [error_details.proto]. Describes additional debugging info.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct DebugInfo {
     /// Stack trace entries indicating where the error occurred.
     pub stack_entries: Vec<String>,
@@ -30,6 +32,36 @@ impl DebugInfo {
         }
     }
 
+    /// Creates a new [`DebugInfo`] struct with `stack_entries` filled with the
+    /// frames of a backtrace of the current thread, captured regardless of
+    /// the `RUST_BACKTRACE` and `RUST_LIB_BACKTRACE` environment variables.
+    /// Each entry contains the symbol name of the frame, followed by its
+    /// source location when available.
+    ///
+    /// Backtraces can expose internal details of the server, so they should
+    /// typically only be attached to statuses in development environments.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic::{Code, Status};
+    /// use tonic_types::{DebugInfo, StatusExt};
+    ///
+    /// let mut debug_info = DebugInfo::from_backtrace();
+    /// debug_info.detail = "unexpected state".into();
+    ///
+    /// let status = Status::with_error_details_vec(Code::Internal, "internal error", [debug_info.into()]);
+    /// ```
+    #[cfg(feature = "backtrace")]
+    pub fn from_backtrace() -> Self {
+        let backtrace = std::backtrace::Backtrace::force_capture();
+
+        DebugInfo {
+            stack_entries: backtrace_entries(&backtrace.to_string()),
+            detail: String::new(),
+        }
+    }
+
     /// Returns `true` if [`DebugInfo`] fields are empty, and `false` if they
     /// are not.
     pub fn is_empty(&self) -> bool {
@@ -37,6 +69,34 @@ impl DebugInfo {
     }
 }
 
+/// Splits the `Display` representation of a backtrace into one entry per frame,
+/// joining each symbol name with its source location.
+#[cfg(feature = "backtrace")]
+fn backtrace_entries(backtrace: &str) -> Vec<String> {
+    let mut entries: Vec<String> = Vec::new();
+
+    for line in backtrace.lines() {
+        let line = line.trim();
+
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(entry) = entries.last_mut() {
+                entry.push_str(" at ");
+                entry.push_str(location);
+            }
+            continue;
+        }
+
+        match line.split_once(": ") {
+            Some((index, symbol)) if index.bytes().all(|b| b.is_ascii_digit()) => {
+                entries.push(symbol.to_owned());
+            }
+            _ => {}
+        }
+    }
+
+    entries
+}
+
 impl IntoAny for DebugInfo {
     fn into_any(self) -> Any {
         let detail_data: pb::DebugInfo = self.into();
@@ -126,4 +186,31 @@ mod tests {
             "DebugInfo from Any differs from expected result"
         );
     }
+
+    #[cfg(feature = "backtrace")]
+    #[test]
+    fn gen_debug_info_from_backtrace() {
+        let debug_info = DebugInfo::from_backtrace();
+
+        assert!(
+            !debug_info.stack_entries.is_empty(),
+            "DebugInfo from backtrace has no stack entries"
+        );
+
+        let backtrace = "   0: app::handler
+             at ./src/main.rs:10:5
+   1: core::ops::function::FnOnce::call_once
+   2: main";
+
+        let entries = super::backtrace_entries(backtrace);
+
+        assert_eq!(
+            entries,
+            [
+                "app::handler at ./src/main.rs:10:5",
+                "core::ops::function::FnOnce::call_once",
+                "main",
+            ]
+        );
+    }
 }
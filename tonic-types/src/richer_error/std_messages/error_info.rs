@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
 use prost::{DecodeError, Message};
 use prost_types::Any;
 
-use crate::richer_error::FromAnyRef;
+use crate::{richer_error::FromAnyRef, ErrorReason};
 
 use super::super::{pb, FromAny, IntoAny};
 
@@ -12,7 +12,9 @@ use super::super::{pb, FromAny, IntoAny};
 /// details.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct ErrorInfo {
     /// Reason of the error. Should be a constant value that identifies the
     /// proximate cause of the error. Error reasons should be unique within a
@@ -33,6 +35,15 @@ impl ErrorInfo {
     /// Type URL of the `ErrorInfo` standard error message type.
     pub const TYPE_URL: &'static str = "type.googleapis.com/google.rpc.ErrorInfo";
 
+    /// Metadata key for the name of the service that generated the error.
+    pub const SERVICE_KEY: &'static str = "service";
+
+    /// Metadata key for the consumer of the API, such as `projects/123`.
+    pub const CONSUMER_KEY: &'static str = "consumer";
+
+    /// Metadata key for the name of the quota limit that was exceeded.
+    pub const QUOTA_LIMIT_KEY: &'static str = "quota_limit";
+
     /// Creates a new [`ErrorInfo`] struct.
     pub fn new(
         reason: impl Into<String>,
@@ -51,6 +62,160 @@ impl ErrorInfo {
     pub fn is_empty(&self) -> bool {
         self.reason.is_empty() && self.domain.is_empty() && self.metadata.is_empty()
     }
+
+    /// Creates a new [`ErrorInfo`] struct from a reason registered with the
+    /// [`ErrorReason`] trait, using the domain associated with the reason.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use tonic_types::{error_reasons, ErrorInfo};
+    ///
+    /// error_reasons! {
+    ///     /// Reasons of the errors generated by the example service.
+    ///     pub enum ExampleReason: "example.local" {
+    ///         /// The API is not enabled.
+    ///         ApiDisabled = "API_DISABLED",
+    ///     }
+    /// }
+    ///
+    /// let error_info = ErrorInfo::with_reason(ExampleReason::ApiDisabled, HashMap::new());
+    ///
+    /// assert_eq!(error_info.reason, "API_DISABLED");
+    /// assert_eq!(error_info.domain, "example.local");
+    /// assert_eq!(error_info.reason_as::<ExampleReason>(), Some(ExampleReason::ApiDisabled));
+    /// ```
+    pub fn with_reason<R: ErrorReason>(
+        reason: R,
+        metadata: impl Into<HashMap<String, String>>,
+    ) -> Self {
+        ErrorInfo::new(reason.as_reason(), R::DOMAIN, metadata)
+    }
+
+    /// Returns the reason as an [`ErrorReason`], if the domain of the
+    /// [`ErrorInfo`] matches the domain of the reasons, and the reason is one
+    /// of the registered reasons.
+    pub fn reason_as<R: ErrorReason>(&self) -> Option<R> {
+        if self.domain != R::DOMAIN {
+            return None;
+        }
+        R::from_reason(&self.reason)
+    }
+
+    /// Returns the value associated with `key` in `metadata`, if any.
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(String::as_str)
+    }
+
+    /// Returns the value associated with `key` in `metadata` parsed as `T`.
+    /// Returns `None` if `key` is not set or if its value can't be parsed.
+    pub fn get_metadata_as<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get_metadata(key)?.parse().ok()
+    }
+
+    /// Sets the value associated with `key` in `metadata`, converting it to a
+    /// string. Can be chained with other `.set_` [`ErrorInfo`] methods.
+    pub fn set_metadata(&mut self, key: impl Into<String>, value: impl ToString) -> &mut Self {
+        self.metadata.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Returns the name of the service that generated the error, stored at
+    /// the [`ErrorInfo::SERVICE_KEY`] metadata key.
+    pub fn service(&self) -> Option<&str> {
+        self.get_metadata(Self::SERVICE_KEY)
+    }
+
+    /// Sets the name of the service that generated the error.
+    pub fn set_service(&mut self, service: impl Into<String>) -> &mut Self {
+        self.metadata
+            .insert(Self::SERVICE_KEY.into(), service.into());
+        self
+    }
+
+    /// Returns the consumer of the API, stored at the
+    /// [`ErrorInfo::CONSUMER_KEY`] metadata key.
+    pub fn consumer(&self) -> Option<&str> {
+        self.get_metadata(Self::CONSUMER_KEY)
+    }
+
+    /// Sets the consumer of the API.
+    pub fn set_consumer(&mut self, consumer: impl Into<String>) -> &mut Self {
+        self.metadata
+            .insert(Self::CONSUMER_KEY.into(), consumer.into());
+        self
+    }
+
+    /// Returns the name of the quota limit that was exceeded, stored at the
+    /// [`ErrorInfo::QUOTA_LIMIT_KEY`] metadata key.
+    pub fn quota_limit(&self) -> Option<&str> {
+        self.get_metadata(Self::QUOTA_LIMIT_KEY)
+    }
+
+    /// Sets the name of the quota limit that was exceeded.
+    pub fn set_quota_limit(&mut self, quota_limit: impl Into<String>) -> &mut Self {
+        self.metadata
+            .insert(Self::QUOTA_LIMIT_KEY.into(), quota_limit.into());
+        self
+    }
+
+    /// Creates a new [`ErrorInfoBuilder`], allowing the fields of an
+    /// [`ErrorInfo`] to be set fluently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::ErrorInfo;
+    ///
+    /// let error_info = ErrorInfo::builder()
+    ///     .reason("API_DISABLED")
+    ///     .domain("googleapis.com")
+    ///     .metadata("service", "pubsub.googleapis.com")
+    ///     .build();
+    /// ```
+    pub fn builder() -> ErrorInfoBuilder {
+        ErrorInfoBuilder::default()
+    }
+}
+
+/// Builder for [`ErrorInfo`] structs. Created by [`ErrorInfo::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct ErrorInfoBuilder {
+    error_info: ErrorInfo,
+}
+
+impl ErrorInfoBuilder {
+    /// Sets the reason of the error.
+    pub fn reason(mut self, reason: impl Into<String>) -> Self {
+        self.error_info.reason = reason.into();
+        self
+    }
+
+    /// Sets the logical grouping to which the reason belongs.
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.error_info.domain = domain.into();
+        self
+    }
+
+    /// Adds an entry to the structured details about the error.
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.error_info.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the reason and the domain from a reason registered with the
+    /// [`ErrorReason`] trait.
+    pub fn error_reason<R: ErrorReason>(mut self, reason: R) -> Self {
+        self.error_info.reason = reason.as_reason().into();
+        self.error_info.domain = R::DOMAIN.into();
+        self
+    }
+
+    /// Builds the [`ErrorInfo`].
+    pub fn build(self) -> ErrorInfo {
+        self.error_info
+    }
 }
 
 impl IntoAny for ErrorInfo {
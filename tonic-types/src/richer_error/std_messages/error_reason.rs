@@ -0,0 +1,212 @@
+/// Registry of the reasons of a domain of errors, to be used in the `reason`
+/// field of [`ErrorInfo`]. Allows teams to centrally define their error
+/// reasons, instead of spreading string constants across services.
+///
+/// Usually implemented through the [`error_reasons!`] macro, which also checks
+/// at compile time that every reason is valid according to
+/// [`is_valid_error_reason`].
+///
+/// [`ErrorInfo`]: crate::ErrorInfo
+/// [`error_reasons!`]: crate::error_reasons
+pub trait ErrorReason: Sized {
+    /// Domain to which the reasons belong. Normally is the registered name of
+    /// the service that generates the errors.
+    const DOMAIN: &'static str;
+
+    /// Returns the string representation of the reason.
+    fn as_reason(&self) -> &'static str;
+
+    /// Parses a reason from its string representation. Returns `None` if
+    /// `reason` is not registered.
+    fn from_reason(reason: &str) -> Option<Self>;
+}
+
+/// Returns `true` if `reason` is a valid `ErrorInfo` reason. Valid reasons are
+/// at most 63 characters long and match `[A-Z][A-Z0-9_]+[A-Z0-9]`, which
+/// represents UPPER_SNAKE_CASE.
+///
+/// # Examples
+///
+/// ```
+/// use tonic_types::is_valid_error_reason;
+///
+/// assert!(is_valid_error_reason("API_DISABLED"));
+/// assert!(!is_valid_error_reason("api_disabled"));
+/// assert!(!is_valid_error_reason("API_DISABLED_"));
+/// ```
+pub const fn is_valid_error_reason(reason: &str) -> bool {
+    let bytes = reason.as_bytes();
+    let len = bytes.len();
+
+    if len < 3 || len > 63 {
+        return false;
+    }
+
+    if !bytes[0].is_ascii_uppercase() {
+        return false;
+    }
+
+    if !(bytes[len - 1].is_ascii_uppercase() || bytes[len - 1].is_ascii_digit()) {
+        return false;
+    }
+
+    let mut i = 1;
+    while i < len - 1 {
+        let b = bytes[i];
+        if !(b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_') {
+            return false;
+        }
+        i += 1;
+    }
+
+    true
+}
+
+/// Defines an enum of error reasons belonging to a domain, implementing the
+/// [`ErrorReason`] trait for it. Fails to compile if any of the reasons is
+/// not valid according to [`is_valid_error_reason`].
+///
+/// # Examples
+///
+/// ```
+/// use tonic_types::{error_reasons, ErrorInfo, ErrorReason};
+///
+/// error_reasons! {
+///     /// Reasons of the errors generated by the example service.
+///     pub enum ExampleReason: "example.local" {
+///         /// The API is not enabled for the consumer.
+///         ApiDisabled = "API_DISABLED",
+///         /// The requested region is out of stock.
+///         Stockout = "STOCKOUT",
+///     }
+/// }
+///
+/// assert_eq!(ExampleReason::Stockout.as_reason(), "STOCKOUT");
+/// assert_eq!(ExampleReason::from_reason("API_DISABLED"), Some(ExampleReason::ApiDisabled));
+///
+/// let error_info = ErrorInfo::builder()
+///     .error_reason(ExampleReason::Stockout)
+///     .build();
+/// ```
+///
+/// Reasons that are not in UPPER_SNAKE_CASE are rejected at compile time:
+///
+/// ```compile_fail
+/// tonic_types::error_reasons! {
+///     enum InvalidReason: "example.local" {
+///         ApiDisabled = "api-disabled",
+///     }
+/// }
+/// ```
+///
+/// [`ErrorReason`]: crate::ErrorReason
+/// [`is_valid_error_reason`]: crate::is_valid_error_reason
+#[macro_export]
+macro_rules! error_reasons {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident : $domain:literal {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $reason:literal
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )+
+        }
+
+        impl $crate::ErrorReason for $name {
+            const DOMAIN: &'static str = $domain;
+
+            fn as_reason(&self) -> &'static str {
+                match self {
+                    $( $name::$variant => $reason, )+
+                }
+            }
+
+            fn from_reason(reason: &str) -> ::core::option::Option<Self> {
+                match reason {
+                    $( $reason => ::core::option::Option::Some($name::$variant), )+
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str($crate::ErrorReason::as_reason(self))
+            }
+        }
+
+        const _: () = {
+            $(
+                ::core::assert!(
+                    $crate::is_valid_error_reason($reason),
+                    ::core::concat!("invalid error reason: ", $reason),
+                );
+            )+
+        };
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{is_valid_error_reason, ErrorReason};
+    use crate::ErrorInfo;
+
+    crate::error_reasons! {
+        enum TestReason: "example.local" {
+            ApiDisabled = "API_DISABLED",
+            Stockout = "STOCKOUT",
+        }
+    }
+
+    #[test]
+    fn validate_error_reasons() {
+        assert!(is_valid_error_reason("API_DISABLED"));
+        assert!(is_valid_error_reason("V2_ERROR_1"));
+        assert!(!is_valid_error_reason(""));
+        assert!(!is_valid_error_reason("AB"));
+        assert!(!is_valid_error_reason("_API"));
+        assert!(!is_valid_error_reason("1API"));
+        assert!(!is_valid_error_reason("API-DISABLED"));
+        assert!(!is_valid_error_reason(&"A".repeat(64)));
+    }
+
+    #[test]
+    fn error_info_with_reason() {
+        assert_eq!(TestReason::DOMAIN, "example.local");
+        assert_eq!(TestReason::ApiDisabled.to_string(), "API_DISABLED");
+        assert_eq!(TestReason::from_reason("UNKNOWN"), None);
+
+        let mut error_info = ErrorInfo::with_reason(TestReason::Stockout, HashMap::new());
+
+        error_info
+            .set_service("compute.example.local")
+            .set_consumer("projects/123")
+            .set_quota_limit("CPUS-PER-REGION")
+            .set_metadata("availableCpus", 16);
+
+        assert_eq!(
+            error_info.reason_as::<TestReason>(),
+            Some(TestReason::Stockout)
+        );
+        assert_eq!(error_info.service(), Some("compute.example.local"));
+        assert_eq!(error_info.consumer(), Some("projects/123"));
+        assert_eq!(error_info.quota_limit(), Some("CPUS-PER-REGION"));
+        assert_eq!(error_info.get_metadata_as::<u32>("availableCpus"), Some(16));
+        assert_eq!(error_info.get_metadata_as::<u32>("service"), None);
+
+        error_info.domain = "other.local".into();
+
+        assert_eq!(error_info.reason_as::<TestReason>(), None);
+    }
+}
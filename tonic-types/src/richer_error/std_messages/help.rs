@@ -1,12 +1,16 @@
 use prost::{DecodeError, Message};
 use prost_types::Any;
+#[cfg(feature = "url")]
+use url::{ParseError, Url};
 
 use crate::richer_error::FromAnyRef;
 
 use super::super::{pb, FromAny, IntoAny};
 
 /// Used at the `links` field of the [`Help`] struct. Describes a URL link.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct HelpLink {
     /// Description of what the link offers.
     pub description: String,
@@ -23,6 +27,20 @@ impl HelpLink {
             url: url.into(),
         }
     }
+
+    /// Creates a new [`HelpLink`] struct, checking that `url` is a valid
+    /// absolute URL.
+    #[cfg(feature = "url")]
+    pub fn try_new(description: impl Into<String>, url: &str) -> Result<Self, ParseError> {
+        Url::parse(url)?;
+        Ok(HelpLink::new(description, url))
+    }
+
+    /// Parses the URL of the link.
+    #[cfg(feature = "url")]
+    pub fn parse_url(&self) -> Result<Url, ParseError> {
+        Url::parse(&self.url)
+    }
 }
 
 impl From<pb::help::Link> for HelpLink {
@@ -48,7 +66,9 @@ impl From<HelpLink> for pb::help::Link {
 /// an out-of-band action.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct Help {
     /// Links pointing to additional information on how to handle the error.
     pub links: Vec<HelpLink>,
@@ -75,6 +95,28 @@ impl Help {
         }
     }
 
+    /// Creates a new [`Help`] struct with a single [`HelpLink`] in `links`,
+    /// checking that `url` is a valid absolute URL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::Help;
+    ///
+    /// # fn main() -> Result<(), url::ParseError> {
+    /// let help = Help::try_with_link("terms of service", "https://example.local/tos")?;
+    ///
+    /// assert!(Help::try_with_link("terms of service", "example.local/tos").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "url")]
+    pub fn try_with_link(description: impl Into<String>, url: &str) -> Result<Self, ParseError> {
+        Ok(Help {
+            links: vec![HelpLink::try_new(description, url)?],
+        })
+    }
+
     /// Adds a [`HelpLink`] to [`Help`]'s `links` vector.
     pub fn add_link(
         &mut self,
@@ -88,11 +130,104 @@ impl Help {
         self
     }
 
+    /// Adds a [`HelpLink`] to [`Help`]'s `links` vector, checking that `url`
+    /// is a valid absolute URL. The links are left unchanged if it is not.
+    #[cfg(feature = "url")]
+    pub fn try_add_link(
+        &mut self,
+        description: impl Into<String>,
+        url: &str,
+    ) -> Result<&mut Self, ParseError> {
+        self.links.push(HelpLink::try_new(description, url)?);
+        Ok(self)
+    }
+
+    /// Returns the parsed URLs of the links, skipping the malformed ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::Help;
+    ///
+    /// let help = Help::builder()
+    ///     .link("malformed link", "resource-a.example.local")
+    ///     .link("description of link b", "https://resource-b.example.local")
+    ///     .build();
+    ///
+    /// let urls: Vec<_> = help.urls().map(|url| url.to_string()).collect();
+    /// assert_eq!(urls, ["https://resource-b.example.local/"]);
+    /// ```
+    #[cfg(feature = "url")]
+    pub fn urls(&self) -> impl Iterator<Item = Url> + '_ {
+        self.links.iter().filter_map(|link| link.parse_url().ok())
+    }
+
     /// Returns `true` if [`Help`]'s `links` vector is empty, and `false` if it
     /// is not.
     pub fn is_empty(&self) -> bool {
         self.links.is_empty()
     }
+
+    /// Creates a new [`HelpBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::Help;
+    ///
+    /// let help = Help::builder()
+    ///     .link("description of link a", "resource-a.example.local")
+    ///     .link("description of link b", "resource-b.example.local")
+    ///     .build();
+    /// ```
+    pub fn builder() -> HelpBuilder {
+        HelpBuilder::default()
+    }
+}
+
+/// Builder for [`Help`] structs. Created by [`Help::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct HelpBuilder {
+    links: Vec<HelpLink>,
+}
+
+impl HelpBuilder {
+    /// Adds a [`HelpLink`] to the `links` vector.
+    pub fn link(mut self, description: impl Into<String>, url: impl Into<String>) -> Self {
+        self.links.push(HelpLink::new(description, url));
+        self
+    }
+
+    /// Adds a [`HelpLink`] to the `links` vector, checking that `url` is a
+    /// valid absolute URL.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::Help;
+    ///
+    /// # fn main() -> Result<(), url::ParseError> {
+    /// let help = Help::builder()
+    ///     .try_link("description of link a", "https://resource-a.example.local")?
+    ///     .try_link("description of link b", "https://resource-b.example.local")?
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "url")]
+    pub fn try_link(
+        mut self,
+        description: impl Into<String>,
+        url: &str,
+    ) -> Result<Self, ParseError> {
+        self.links.push(HelpLink::try_new(description, url)?);
+        Ok(self)
+    }
+
+    /// Builds the [`Help`].
+    pub fn build(self) -> Help {
+        Help::new(self.links)
+    }
 }
 
 impl IntoAny for Help {
@@ -143,6 +278,31 @@ mod tests {
     use super::super::super::{FromAny, IntoAny};
     use super::Help;
 
+    #[cfg(feature = "url")]
+    #[test]
+    fn validate_help_links() {
+        let mut help =
+            Help::try_with_link("link to resource a", "https://a.example.local").unwrap();
+
+        assert!(help
+            .try_add_link("link to resource b", "b.example.local")
+            .is_err());
+        assert_eq!(help.links.len(), 1);
+
+        help.add_link("link to resource c", "c.example.local");
+        help.try_add_link("link to resource d", "https://d.example.local/docs?q=1")
+            .unwrap();
+
+        assert_eq!(help.links[0].url, "https://a.example.local");
+        assert!(help.links[1].parse_url().is_err());
+
+        let hosts: Vec<_> = help
+            .urls()
+            .map(|url| url.host_str().unwrap().to_owned())
+            .collect();
+        assert_eq!(hosts, ["a.example.local", "d.example.local"]);
+    }
+
     #[test]
     fn gen_help() {
         let mut help = Help::new(Vec::new());
@@ -11,6 +11,8 @@ use super::super::{pb, FromAny, IntoAny};
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct LocalizedMessage {
     /// Locale used, following the specification defined in [BCP 47]. For
     /// example: "en-US", "fr-CH" or "es-MX".
@@ -39,6 +41,67 @@ impl LocalizedMessage {
     pub fn is_empty(&self) -> bool {
         self.locale.is_empty() && self.message.is_empty()
     }
+
+    /// Picks the message best matching `preferred_locales`, ordered from the
+    /// most to the least preferred, like the languages of an
+    /// `Accept-Language` header sorted by weight.
+    ///
+    /// Follows the lookup scheme of [RFC 4647]: each locale is compared to
+    /// the locales of the messages, ignoring case, removing its last subtag
+    /// until a message matches. For example, "de-CH-1996" falls back to
+    /// "de-CH", then to "de". The "*" wildcard is skipped. Returns `None` if
+    /// no locale matches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::LocalizedMessage;
+    ///
+    /// let messages = [
+    ///     LocalizedMessage::new("en", "Not found"),
+    ///     LocalizedMessage::new("fr", "Introuvable"),
+    /// ];
+    ///
+    /// let best = LocalizedMessage::lookup(&messages, &["fr-CA", "en"]).unwrap();
+    /// assert_eq!(best.message, "Introuvable");
+    /// ```
+    ///
+    /// [RFC 4647]: https://www.rfc-editor.org/rfc/rfc4647#section-3.4
+    pub fn lookup<'a>(
+        messages: &'a [LocalizedMessage],
+        preferred_locales: &[&str],
+    ) -> Option<&'a LocalizedMessage> {
+        for locale in preferred_locales {
+            let mut range = locale.trim();
+            if range == "*" {
+                continue;
+            }
+            while !range.is_empty() {
+                let found = messages
+                    .iter()
+                    .find(|message| message.locale.eq_ignore_ascii_case(range));
+                if found.is_some() {
+                    return found;
+                }
+                range = truncate_range(range);
+            }
+        }
+
+        None
+    }
+}
+
+// Removes the last subtag of a language range, along with the singleton
+// introducing an extension if it is left at the end.
+fn truncate_range(range: &str) -> &str {
+    let range = match range.rfind('-') {
+        Some(end) => &range[..end],
+        None => return "",
+    };
+    match range.rfind('-') {
+        Some(end) if range.len() - end == 2 => &range[..end],
+        _ => range,
+    }
 }
 
 impl IntoAny for LocalizedMessage {
@@ -89,7 +152,34 @@ impl From<LocalizedMessage> for pb::LocalizedMessage {
 #[cfg(test)]
 mod tests {
     use super::super::super::{FromAny, IntoAny};
-    use super::LocalizedMessage;
+    use super::{truncate_range, LocalizedMessage};
+
+    #[test]
+    fn truncates_language_ranges() {
+        assert_eq!(truncate_range("zh-Hant-CN-x-private1"), "zh-Hant-CN");
+        assert_eq!(truncate_range("zh-Hant-CN"), "zh-Hant");
+        assert_eq!(truncate_range("zh"), "");
+    }
+
+    #[test]
+    fn lookup_localized_messages() {
+        let messages = [
+            LocalizedMessage::new("en-US", "color"),
+            LocalizedMessage::new("en", "colour"),
+            LocalizedMessage::new("de", "Farbe"),
+        ];
+
+        let lookup = |locales: &[&str]| {
+            LocalizedMessage::lookup(&messages, locales).map(|found| found.message.as_str())
+        };
+
+        assert_eq!(lookup(&["EN-us"]), Some("color"));
+        assert_eq!(lookup(&["en-GB"]), Some("colour"));
+        assert_eq!(lookup(&["fr", "*", "de-CH-1996"]), Some("Farbe"));
+        assert_eq!(lookup(&["fr", "de"]), Some("Farbe"));
+        assert_eq!(lookup(&["fr"]), None);
+        assert_eq!(lookup(&[]), None);
+    }
 
     #[test]
     fn gen_localized_message() {
@@ -8,19 +8,30 @@ pub use debug_info::DebugInfo;
 
 mod quota_failure;
 
-pub use quota_failure::{QuotaFailure, QuotaViolation};
+pub use quota_failure::{QuotaFailure, QuotaFailureBuilder, QuotaViolation, QuotaViolationBuilder};
 
 mod error_info;
 
-pub use error_info::ErrorInfo;
+pub use error_info::{ErrorInfo, ErrorInfoBuilder};
+
+mod error_reason;
+
+pub use error_reason::{is_valid_error_reason, ErrorReason};
+
+mod prec_type;
+
+pub use prec_type::{CommonPreconditionType, PreconditionType};
 
 mod prec_failure;
 
-pub use prec_failure::{PreconditionFailure, PreconditionViolation};
+pub use prec_failure::{
+    PreconditionFailure, PreconditionFailureBuilder, PreconditionViolation,
+    PreconditionViolationBuilder,
+};
 
 mod bad_request;
 
-pub use bad_request::{BadRequest, FieldViolation};
+pub use bad_request::{BadRequest, BadRequestBuilder, FieldViolation, FieldViolationBuilder};
 
 mod request_info;
 
@@ -32,7 +43,7 @@ pub use resource_info::ResourceInfo;
 
 mod help;
 
-pub use help::{Help, HelpLink};
+pub use help::{Help, HelpBuilder, HelpLink};
 
 mod loc_message;
 
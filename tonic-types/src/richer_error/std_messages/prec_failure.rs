@@ -1,13 +1,15 @@
 use prost::{DecodeError, Message};
 use prost_types::Any;
 
-use crate::richer_error::FromAnyRef;
+use crate::{richer_error::FromAnyRef, PreconditionType};
 
 use super::super::{pb, FromAny, IntoAny};
 
 /// Used at the `violations` field of the [`PreconditionFailure`] struct.
 /// Describes a single precondition failure.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct PreconditionViolation {
     /// Type of the PreconditionFailure. At [error_details.proto], the usage
     /// of a service-specific enum type is recommended. For example, "TOS" for
@@ -36,6 +38,90 @@ impl PreconditionViolation {
             description: description.into(),
         }
     }
+
+    /// Creates a new [`PreconditionViolation`] struct from a type registered
+    /// with the [`PreconditionType`] trait.
+    pub fn with_type<T: PreconditionType>(
+        violation_type: T,
+        subject: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        PreconditionViolation::new(violation_type.as_type(), subject, description)
+    }
+
+    /// Returns the type as a [`PreconditionType`], if it is one of the
+    /// registered types.
+    pub fn type_as<T: PreconditionType>(&self) -> Option<T> {
+        T::from_type(&self.r#type)
+    }
+
+    /// Returns `true` if the type of the violation is `violation_type`.
+    pub fn is_type<T: PreconditionType>(&self, violation_type: T) -> bool {
+        self.r#type == violation_type.as_type()
+    }
+
+    /// Creates a new [`PreconditionViolationBuilder`], allowing the fields of
+    /// a [`PreconditionViolation`] to be set fluently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::PreconditionViolation;
+    ///
+    /// let violation = PreconditionViolation::builder()
+    ///     .violation_type("TOS")
+    ///     .subject("google.com/cloud")
+    ///     .description("Terms of service not accepted")
+    ///     .build();
+    /// ```
+    pub fn builder() -> PreconditionViolationBuilder {
+        PreconditionViolationBuilder::default()
+    }
+}
+
+/// Builder for [`PreconditionViolation`] structs. Created by
+/// [`PreconditionViolation::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct PreconditionViolationBuilder {
+    violation: PreconditionViolation,
+}
+
+impl PreconditionViolationBuilder {
+    /// Sets the type of the precondition failure.
+    pub fn violation_type(mut self, violation_type: impl Into<String>) -> Self {
+        self.violation.r#type = violation_type.into();
+        self
+    }
+
+    /// Sets the type of the precondition failure from a type registered with
+    /// the [`PreconditionType`] trait.
+    pub fn precondition_type<T: PreconditionType>(mut self, violation_type: T) -> Self {
+        self.violation.r#type = violation_type.as_type().into();
+        self
+    }
+
+    /// Sets the subject, relative to the type, that failed.
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.violation.subject = subject.into();
+        self
+    }
+
+    /// Sets the description of how the precondition failed.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.violation.description = description.into();
+        self
+    }
+
+    /// Builds the [`PreconditionViolation`].
+    pub fn build(self) -> PreconditionViolation {
+        self.violation
+    }
+}
+
+impl From<PreconditionViolationBuilder> for PreconditionViolation {
+    fn from(builder: PreconditionViolationBuilder) -> Self {
+        builder.build()
+    }
 }
 
 impl From<pb::precondition_failure::Violation> for PreconditionViolation {
@@ -63,7 +149,9 @@ impl From<PreconditionViolation> for pb::precondition_failure::Violation {
 /// failed.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct PreconditionFailure {
     /// Describes all precondition violations of the request.
     pub violations: Vec<PreconditionViolation>,
@@ -112,11 +200,80 @@ impl PreconditionFailure {
         self
     }
 
+    /// Adds a [`PreconditionViolation`] of a type registered with the
+    /// [`PreconditionType`] trait to [`PreconditionFailure`]'s `violations`
+    /// vector.
+    pub fn add_typed_violation<T: PreconditionType>(
+        &mut self,
+        violation_type: T,
+        subject: impl Into<String>,
+        description: impl Into<String>,
+    ) -> &mut Self {
+        self.violations.push(PreconditionViolation::with_type(
+            violation_type,
+            subject,
+            description,
+        ));
+        self
+    }
+
+    /// Returns the violations of type `violation_type`.
+    pub fn violations_of<T: PreconditionType>(
+        &self,
+        violation_type: T,
+    ) -> impl Iterator<Item = &PreconditionViolation> {
+        let violation_type = violation_type.as_type();
+        self.violations
+            .iter()
+            .filter(move |violation| violation.r#type == violation_type)
+    }
+
+    /// Returns `true` if one of the violations is of type `violation_type`.
+    pub fn has_violation<T: PreconditionType>(&self, violation_type: T) -> bool {
+        self.violations_of(violation_type).next().is_some()
+    }
+
     /// Returns `true` if [`PreconditionFailure`]'s `violations` vector is
     /// empty, and `false` if it is not.
     pub fn is_empty(&self) -> bool {
         self.violations.is_empty()
     }
+
+    /// Creates a new [`PreconditionFailureBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::{PreconditionFailure, PreconditionViolation};
+    ///
+    /// let prec_failure = PreconditionFailure::builder()
+    ///     .violation(PreconditionViolation::builder().violation_type("TOS").subject("example.local"))
+    ///     .build();
+    /// ```
+    pub fn builder() -> PreconditionFailureBuilder {
+        PreconditionFailureBuilder::default()
+    }
+}
+
+/// Builder for [`PreconditionFailure`] structs. Created by
+/// [`PreconditionFailure::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct PreconditionFailureBuilder {
+    violations: Vec<PreconditionViolation>,
+}
+
+impl PreconditionFailureBuilder {
+    /// Adds a [`PreconditionViolation`], or a [`PreconditionViolationBuilder`],
+    /// to the `violations` vector.
+    pub fn violation(mut self, violation: impl Into<PreconditionViolation>) -> Self {
+        self.violations.push(violation.into());
+        self
+    }
+
+    /// Builds the [`PreconditionFailure`].
+    pub fn build(self) -> PreconditionFailure {
+        PreconditionFailure::new(self.violations)
+    }
 }
 
 impl IntoAny for PreconditionFailure {
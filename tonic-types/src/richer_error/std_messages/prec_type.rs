@@ -0,0 +1,166 @@
+/// Registry of the types of precondition failures, to be used in the `type`
+/// field of [`PreconditionViolation`]. Allows services to define their
+/// precondition types as an enum, as recommended by [error_details.proto],
+/// instead of spreading string constants across services.
+///
+/// Usually implemented through the [`precondition_types!`] macro. Common types
+/// are registered by [`CommonPreconditionType`].
+///
+/// [`PreconditionViolation`]: crate::PreconditionViolation
+/// [`precondition_types!`]: crate::precondition_types
+/// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
+pub trait PreconditionType: Sized {
+    /// Returns the string representation of the type.
+    fn as_type(&self) -> &'static str;
+
+    /// Parses a type from its string representation. Returns `None` if
+    /// `violation_type` is not registered.
+    fn from_type(violation_type: &str) -> Option<Self>;
+}
+
+/// Defines an enum of precondition failure types, implementing the
+/// [`PreconditionType`] trait for it.
+///
+/// # Examples
+///
+/// ```
+/// use tonic_types::{precondition_types, PreconditionFailure, PreconditionType};
+///
+/// precondition_types! {
+///     /// Types of the precondition failures of the example service.
+///     pub enum ExamplePrecondition {
+///         /// The account has no payment method.
+///         NoPaymentMethod = "NO_PAYMENT_METHOD",
+///         /// The bucket is locked by a retention policy.
+///         RetentionLocked = "RETENTION_LOCKED",
+///     }
+/// }
+///
+/// let mut prec_failure = PreconditionFailure::new(Vec::new());
+/// prec_failure.add_typed_violation(
+///     ExamplePrecondition::RetentionLocked,
+///     "buckets/logs",
+///     "The retention policy of the bucket is locked",
+/// );
+///
+/// assert!(prec_failure.has_violation(ExamplePrecondition::RetentionLocked));
+/// assert_eq!(
+///     prec_failure.violations[0].type_as::<ExamplePrecondition>(),
+///     Some(ExamplePrecondition::RetentionLocked)
+/// );
+/// ```
+///
+/// [`PreconditionType`]: crate::PreconditionType
+#[macro_export]
+macro_rules! precondition_types {
+    (
+        $(#[$meta:meta])*
+        $vis:vis enum $name:ident {
+            $(
+                $(#[$variant_meta:meta])*
+                $variant:ident = $type:literal
+            ),+ $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        $vis enum $name {
+            $(
+                $(#[$variant_meta])*
+                $variant,
+            )+
+        }
+
+        impl $crate::PreconditionType for $name {
+            fn as_type(&self) -> &'static str {
+                match self {
+                    $( $name::$variant => $type, )+
+                }
+            }
+
+            fn from_type(violation_type: &str) -> ::core::option::Option<Self> {
+                match violation_type {
+                    $( $type => ::core::option::Option::Some($name::$variant), )+
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+
+        impl ::core::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.write_str($crate::PreconditionType::as_type(self))
+            }
+        }
+    };
+}
+
+crate::precondition_types! {
+    /// Common types of precondition failures.
+    ///
+    /// Services with types of their own define them with the
+    /// [`precondition_types!`](crate::precondition_types) macro.
+    pub enum CommonPreconditionType {
+        /// The terms of service have not been accepted.
+        TermsOfService = "TOS",
+        /// A backup of the resource is in progress.
+        BackupInProgress = "BACKUP_IN_PROGRESS",
+        /// The resource does not match the etag of the request.
+        EtagMismatch = "ETAG_MISMATCH",
+        /// The resource is not empty, e.g. a directory to be deleted.
+        ResourceNotEmpty = "RESOURCE_NOT_EMPTY",
+        /// The resource is used by another resource.
+        ResourceInUse = "RESOURCE_IN_USE",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CommonPreconditionType, PreconditionType};
+    use crate::{PreconditionFailure, PreconditionViolation};
+
+    #[test]
+    fn common_precondition_types() {
+        assert_eq!(CommonPreconditionType::TermsOfService.as_type(), "TOS");
+        assert_eq!(
+            CommonPreconditionType::from_type("BACKUP_IN_PROGRESS"),
+            Some(CommonPreconditionType::BackupInProgress)
+        );
+        assert_eq!(CommonPreconditionType::from_type("tos"), None);
+        assert_eq!(
+            CommonPreconditionType::EtagMismatch.to_string(),
+            "ETAG_MISMATCH"
+        );
+    }
+
+    #[test]
+    fn typed_violations() {
+        let violation = PreconditionViolation::builder()
+            .precondition_type(CommonPreconditionType::TermsOfService)
+            .subject("example.local")
+            .build();
+
+        assert_eq!(violation.r#type, "TOS");
+        assert!(violation.is_type(CommonPreconditionType::TermsOfService));
+        assert!(!violation.is_type(CommonPreconditionType::BackupInProgress));
+
+        let mut prec_failure = PreconditionFailure::new(vec![violation]);
+        prec_failure
+            .add_violation("FNF", "example.local", "File not found")
+            .add_typed_violation(
+                CommonPreconditionType::TermsOfService,
+                "other.local",
+                "Terms of service not accepted",
+            );
+
+        let subjects: Vec<_> = prec_failure
+            .violations_of(CommonPreconditionType::TermsOfService)
+            .map(|violation| violation.subject.as_str())
+            .collect();
+        assert_eq!(subjects, ["example.local", "other.local"]);
+        assert!(!prec_failure.has_violation(CommonPreconditionType::ResourceInUse));
+        assert_eq!(
+            prec_failure.violations[1].type_as::<CommonPreconditionType>(),
+            None
+        );
+    }
+}
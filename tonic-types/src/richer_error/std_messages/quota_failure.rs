@@ -10,6 +10,8 @@ use super::super::{pb, FromAny, IntoAny};
 /// Used at the `violations` field of the [`QuotaFailure`] struct. Describes a
 /// single quota violation.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct QuotaViolation {
     /// Subject on which the quota check failed.
     pub subject: String,
@@ -30,10 +32,19 @@ pub struct QuotaViolation {
     pub quota_dimensions: HashMap<String, String>,
 
     /// The quota check value at the time of violation.
+    #[cfg_attr(feature = "serde", serde(with = "crate::richer_error::json::int64"))]
     pub quota_value: i64,
 
     /// The future value of the quota check value when a quota check rollout is
     /// in progress.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "futureQuotaValue",
+            with = "crate::richer_error::json::opt_int64",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
     pub futura_quota_value: Option<i64>,
 }
 
@@ -46,6 +57,97 @@ impl QuotaViolation {
             ..Default::default()
         }
     }
+
+    /// Creates a new [`QuotaViolationBuilder`], allowing every field of a
+    /// [`QuotaViolation`] to be set fluently.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::QuotaViolation;
+    ///
+    /// let violation = QuotaViolation::builder()
+    ///     .subject("project:<project id>")
+    ///     .description("daily limit exceeded")
+    ///     .quota_id("RequestsPerDayPerProject")
+    ///     .dimension("region", "us-central1")
+    ///     .quota_value(1_000)
+    ///     .build();
+    /// ```
+    pub fn builder() -> QuotaViolationBuilder {
+        QuotaViolationBuilder::default()
+    }
+}
+
+/// Builder for [`QuotaViolation`] structs. Created by
+/// [`QuotaViolation::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct QuotaViolationBuilder {
+    violation: QuotaViolation,
+}
+
+impl QuotaViolationBuilder {
+    /// Sets the subject on which the quota check failed.
+    pub fn subject(mut self, subject: impl Into<String>) -> Self {
+        self.violation.subject = subject.into();
+        self
+    }
+
+    /// Sets the description of why the quota check failed.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.violation.description = description.into();
+        self
+    }
+
+    /// Sets the API service from which the quota check originates.
+    pub fn api_service(mut self, api_service: impl Into<String>) -> Self {
+        self.violation.api_service = api_service.into();
+        self
+    }
+
+    /// Sets the quota check that was violated.
+    pub fn quota_metric(mut self, quota_metric: impl Into<String>) -> Self {
+        self.violation.quota_metric = quota_metric.into();
+        self
+    }
+
+    /// Sets the ID of the violated quota check.
+    pub fn quota_id(mut self, quota_id: impl Into<String>) -> Self {
+        self.violation.quota_id = quota_id.into();
+        self
+    }
+
+    /// Adds a dimension of the violated quota check.
+    pub fn dimension(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.violation
+            .quota_dimensions
+            .insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the quota check value at the time of violation.
+    pub fn quota_value(mut self, quota_value: i64) -> Self {
+        self.violation.quota_value = quota_value;
+        self
+    }
+
+    /// Sets the future value of the quota check value, when a quota check
+    /// rollout is in progress.
+    pub fn future_quota_value(mut self, future_quota_value: i64) -> Self {
+        self.violation.futura_quota_value = Some(future_quota_value);
+        self
+    }
+
+    /// Builds the [`QuotaViolation`].
+    pub fn build(self) -> QuotaViolation {
+        self.violation
+    }
+}
+
+impl From<QuotaViolationBuilder> for QuotaViolation {
+    fn from(builder: QuotaViolationBuilder) -> Self {
+        builder.build()
+    }
 }
 
 impl From<pb::quota_failure::Violation> for QuotaViolation {
@@ -82,7 +184,9 @@ impl From<QuotaViolation> for pb::quota_failure::Violation {
 /// in [error_details.proto]. Describes how a quota check failed.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct QuotaFailure {
     /// Describes all quota violations.
     pub violations: Vec<QuotaViolation>,
@@ -130,6 +234,42 @@ impl QuotaFailure {
     pub fn is_empty(&self) -> bool {
         self.violations.is_empty()
     }
+
+    /// Creates a new [`QuotaFailureBuilder`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use tonic_types::{QuotaFailure, QuotaViolation};
+    ///
+    /// let quota_failure = QuotaFailure::builder()
+    ///     .violation(QuotaViolation::builder().subject("clientip:<ip address>").quota_value(10))
+    ///     .violation(QuotaViolation::new("project:<project id>", "description"))
+    ///     .build();
+    /// ```
+    pub fn builder() -> QuotaFailureBuilder {
+        QuotaFailureBuilder::default()
+    }
+}
+
+/// Builder for [`QuotaFailure`] structs. Created by [`QuotaFailure::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct QuotaFailureBuilder {
+    violations: Vec<QuotaViolation>,
+}
+
+impl QuotaFailureBuilder {
+    /// Adds a [`QuotaViolation`], or a [`QuotaViolationBuilder`], to the
+    /// `violations` vector.
+    pub fn violation(mut self, violation: impl Into<QuotaViolation>) -> Self {
+        self.violations.push(violation.into());
+        self
+    }
+
+    /// Builds the [`QuotaFailure`].
+    pub fn build(self) -> QuotaFailure {
+        QuotaFailure::new(self.violations)
+    }
 }
 
 impl IntoAny for QuotaFailure {
@@ -178,7 +318,33 @@ impl From<QuotaFailure> for pb::QuotaFailure {
 #[cfg(test)]
 mod tests {
     use super::super::super::{FromAny, IntoAny};
-    use super::QuotaFailure;
+    use super::{QuotaFailure, QuotaViolation};
+
+    #[test]
+    fn gen_quota_failure_with_builder() {
+        let quota_failure = QuotaFailure::builder()
+            .violation(
+                QuotaViolation::builder()
+                    .subject("project:<project id>")
+                    .description("description")
+                    .api_service("example.local")
+                    .quota_metric("example.local/requests")
+                    .quota_id("RequestsPerDay")
+                    .dimension("region", "us-central1")
+                    .quota_value(100)
+                    .future_quota_value(200),
+            )
+            .build();
+
+        let formatted = format!("{quota_failure:?}");
+
+        let expected = "QuotaFailure { violations: [QuotaViolation { subject: \"project:<project id>\", description: \"description\", api_service: \"example.local\", quota_metric: \"example.local/requests\", quota_id: \"RequestsPerDay\", quota_dimensions: {\"region\": \"us-central1\"}, quota_value: 100, futura_quota_value: Some(200) }] }";
+
+        assert!(
+            formatted.eq(expected),
+            "QuotaFailure from builder differs from expected result"
+        );
+    }
 
     #[test]
     fn gen_quota_failure() {
@@ -10,7 +10,9 @@ use super::super::{pb, FromAny, IntoAny};
 /// clients can attach when providing feedback.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct RequestInfo {
     /// An opaque string that should only be interpreted by the service that
     /// generated it. For example, an id used to identify requests in the logs.
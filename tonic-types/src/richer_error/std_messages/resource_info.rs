@@ -9,7 +9,9 @@ use super::super::{pb, FromAny, IntoAny};
 /// in [error_details.proto]. Describes the resource that is being accessed.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct ResourceInfo {
     /// Type of resource being accessed.
     pub resource_type: String,
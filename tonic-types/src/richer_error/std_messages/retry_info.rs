@@ -14,9 +14,18 @@ use super::super::{pb, FromAny, IntoAny};
 /// `retry_delay`'s become 0.
 ///
 /// [error_details.proto]: https://github.com/googleapis/googleapis/blob/master/google/rpc/error_details.proto
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, rename_all = "camelCase"))]
 pub struct RetryInfo {
     /// Informs the amount of time that clients should wait before retrying.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            with = "crate::richer_error::json::opt_duration",
+            skip_serializing_if = "Option::is_none"
+        )
+    )]
     pub retry_delay: Option<time::Duration>,
 }
 
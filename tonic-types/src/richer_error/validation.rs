@@ -0,0 +1,202 @@
+#[cfg(feature = "validator")]
+use super::FieldPath;
+use super::{BadRequest, FieldViolation};
+
+/// Converts `validator` errors into a [`BadRequest`]. The `code` of each
+/// validation error is used as the `reason` of the violation, converted to
+/// upper case, and its `message`, if any, as the description. Errors on
+/// nested structs and on elements of lists are reported with their full field
+/// path, such as `email_addresses[1].email`.
+#[cfg(feature = "validator")]
+impl From<&validator::ValidationErrors> for BadRequest {
+    fn from(errors: &validator::ValidationErrors) -> Self {
+        let mut violations = Vec::new();
+
+        push_validator_errors(&mut violations, &FieldPath::new(), errors);
+
+        BadRequest::new(violations)
+    }
+}
+
+#[cfg(feature = "validator")]
+impl From<validator::ValidationErrors> for BadRequest {
+    fn from(errors: validator::ValidationErrors) -> Self {
+        BadRequest::from(&errors)
+    }
+}
+
+#[cfg(feature = "validator")]
+fn push_validator_errors(
+    violations: &mut Vec<FieldViolation>,
+    path: &FieldPath,
+    errors: &validator::ValidationErrors,
+) {
+    use validator::ValidationErrorsKind;
+
+    // Sorted by field name, since `ValidationErrors` is backed by a `HashMap`
+    let mut fields: Vec<_> = errors.errors().iter().collect();
+    fields.sort_by_key(|(field, _)| *field);
+
+    for (field, kind) in fields {
+        // Struct level validation errors are reported at the `__all__` field
+        let path = if field == "__all__" {
+            path.clone()
+        } else {
+            path.clone().field(field)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for error in errors {
+                    let description = match &error.message {
+                        Some(message) => message.to_string(),
+                        None => error.code.to_string(),
+                    };
+
+                    violations.push(FieldViolation {
+                        field: path.to_string(),
+                        description,
+                        reason: error.code.to_uppercase(),
+                        localized_message: None,
+                    });
+                }
+            }
+            ValidationErrorsKind::Struct(errors) => {
+                push_validator_errors(violations, &path, errors);
+            }
+            ValidationErrorsKind::List(list) => {
+                for (index, errors) in list {
+                    push_validator_errors(violations, &path.clone().index(*index), errors);
+                }
+            }
+        }
+    }
+}
+
+/// Converts a `garde` report into a [`BadRequest`], using the path of each
+/// validation error as the `field` of the violation and its message as the
+/// description.
+#[cfg(feature = "garde")]
+impl From<&garde::Report> for BadRequest {
+    fn from(report: &garde::Report) -> Self {
+        let violations: Vec<FieldViolation> = report
+            .iter()
+            .map(|(path, error)| FieldViolation::new(path.to_string(), error.message()))
+            .collect();
+
+        BadRequest::new(violations)
+    }
+}
+
+#[cfg(feature = "garde")]
+impl From<garde::Report> for BadRequest {
+    fn from(report: garde::Report) -> Self {
+        BadRequest::from(&report)
+    }
+}
+
+/// Converts a `prost-validate` error into a [`BadRequest`] with a single
+/// violation, using the path of the invalid field as the `field` of the
+/// violation and the broken constraint as its description.
+#[cfg(feature = "prost-validate")]
+impl From<&prost_validate::Error> for BadRequest {
+    fn from(error: &prost_validate::Error) -> Self {
+        BadRequest::with_violation(error.field.clone(), error.details.to_string())
+    }
+}
+
+#[cfg(feature = "prost-validate")]
+impl From<prost_validate::Error> for BadRequest {
+    fn from(error: prost_validate::Error) -> Self {
+        BadRequest::from(&error)
+    }
+}
+
+/// Validates a request message against its protoc-gen-validate constraints.
+/// Invalid messages are rejected with a `tonic::Status` with
+/// `Code::InvalidArgument` and [`BadRequest`] details listing the violated
+/// constraints.
+///
+/// Meant to be configured as the request validator of the generated servers,
+/// with `tonic-build`'s `Builder::request_validator`:
+///
+/// ```rust,ignore
+/// tonic_build::configure()
+///     .request_validator("helloworld.Greeter", "tonic_types::validate_request")
+///     .compile_protos(&["helloworld.proto"], &["."])
+///     .unwrap();
+/// ```
+#[cfg(feature = "prost-validate")]
+pub fn validate_request<M>(message: &M) -> Result<(), tonic::Status>
+where
+    M: prost_validate::Validator,
+{
+    use super::{ErrorDetails, StatusExt};
+
+    message.validate().map_err(|error| {
+        let details = ErrorDetails {
+            bad_request: Some(BadRequest::from(&error)),
+            ..ErrorDetails::new()
+        };
+
+        tonic::Status::with_error_details(tonic::Code::InvalidArgument, "invalid request", details)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "validator")]
+    #[test]
+    fn bad_request_from_validator() {
+        use std::{borrow::Cow, collections::BTreeMap};
+
+        use validator::{ValidationError, ValidationErrors, ValidationErrorsKind};
+
+        let mut email_errors = ValidationErrors::new();
+        email_errors.add(
+            "email",
+            ValidationError::new("email").with_message(Cow::Borrowed("invalid email address")),
+        );
+
+        let mut errors = ValidationErrors::new();
+        errors.add("full_name", ValidationError::new("required"));
+        errors.errors_mut().insert(
+            Cow::Borrowed("email_addresses"),
+            ValidationErrorsKind::List(BTreeMap::from([(1, Box::new(email_errors))])),
+        );
+
+        let bad_request = super::BadRequest::from(errors);
+
+        let formatted = format!("{bad_request:?}");
+
+        let expected = "BadRequest { field_violations: [FieldViolation { field: \"email_addresses[1].email\", description: \"invalid email address\", reason: \"EMAIL\", localized_message: None }, FieldViolation { field: \"full_name\", description: \"required\", reason: \"REQUIRED\", localized_message: None }] }";
+
+        assert!(
+            formatted.eq(expected),
+            "BadRequest from validator errors differs from expected result"
+        );
+    }
+
+    #[cfg(feature = "garde")]
+    #[test]
+    fn bad_request_from_garde() {
+        use garde::{Error, Path, Report};
+
+        let mut report = Report::new();
+        report.append(
+            Path::new("email_addresses").join(1).join("email"),
+            Error::new("not a valid email"),
+        );
+
+        let bad_request = super::BadRequest::from(report);
+
+        let formatted = format!("{bad_request:?}");
+
+        let expected = "BadRequest { field_violations: [FieldViolation { field: \"email_addresses[1].email\", description: \"not a valid email\", reason: \"\", localized_message: None }] }";
+
+        assert!(
+            formatted.eq(expected),
+            "BadRequest from garde report differs from expected result"
+        );
+    }
+}
@@ -0,0 +1,112 @@
+//! Matchers of the details of statuses, for the assertions of [`tonic::assert_status!`].
+//!
+//! ```
+//! use tonic::{assert_status, Code, Status};
+//! use tonic_types::{test::contains_detail, ErrorDetails, ErrorInfo, StatusExt};
+//!
+//! let details = ErrorDetails::with_error_info("NO_QUOTA", "example.com", []);
+//! let result: Result<(), Status> = Err(Status::with_error_details(
+//!     Code::ResourceExhausted,
+//!     "quota exhausted",
+//!     details,
+//! ));
+//!
+//! assert_status!(
+//!     result,
+//!     Code::ResourceExhausted,
+//!     contains_detail(|info: &ErrorInfo| info.reason == "NO_QUOTA"),
+//! );
+//! ```
+//!
+//! Mismatches list the details of the status, decoded.
+
+use std::fmt::Write as _;
+
+use tonic::{test::StatusMatcher, Status};
+
+use crate::{StatusDetail, StatusExt};
+
+pub use crate::Detail;
+
+/// Matches the statuses with a detail of type `D` for which `predicate` returns `true`.
+pub fn contains_detail<D, F>(predicate: F) -> impl StatusMatcher
+where
+    D: Detail,
+    F: Fn(&D) -> bool,
+{
+    move |status: &Status| {
+        let mut details = String::new();
+        for detail in status.iter_details() {
+            match &detail {
+                StatusDetail::Known(known) => {
+                    if D::from_detail(known).is_some_and(&predicate) {
+                        return Ok(());
+                    }
+                    let _ = write!(details, "\n  {known:?}");
+                }
+                StatusDetail::Unknown(any) => {
+                    let _ = write!(details, "\n  unknown detail of type {}", any.type_url);
+                }
+            }
+        }
+
+        let name = std::any::type_name::<D>()
+            .rsplit("::")
+            .next()
+            .unwrap_or_default();
+        if details.is_empty() {
+            Err(format!(
+                "no {name} detail matches, the status has no details"
+            ))
+        } else {
+            Err(format!(
+                "no {name} detail matches, the details are:{details}"
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tonic::{test::check_status, Code};
+
+    use super::*;
+    use crate::{BadRequest, ErrorDetails, ErrorInfo};
+
+    #[test]
+    fn matches_details() {
+        let details = ErrorDetails::with_error_info("NO_QUOTA", "example.com", []);
+        let status = Status::with_error_details(Code::ResourceExhausted, "", details);
+
+        let matches = contains_detail(|info: &ErrorInfo| info.reason == "NO_QUOTA");
+        assert_eq!(matches.check(&status), Ok(()));
+
+        let report = check_status(
+            &status,
+            Code::ResourceExhausted,
+            &[
+                &contains_detail(|info: &ErrorInfo| info.reason == "OTHER"),
+                &contains_detail(|_: &BadRequest| true),
+            ],
+        )
+        .unwrap_err();
+        let mismatches = report.split_once("mismatches:").unwrap().1;
+        assert!(
+            mismatches.starts_with(
+                "\n    - no ErrorInfo detail matches, the details are:\
+                 \n        ErrorInfo(ErrorInfo { reason: \"NO_QUOTA\""
+            ),
+            "{report}"
+        );
+        assert!(
+            mismatches.contains("\n    - no BadRequest detail matches"),
+            "{report}"
+        );
+
+        let status = Status::internal("");
+        assert_eq!(
+            contains_detail(|_: &ErrorInfo| true).check(&status),
+            Err("no ErrorInfo detail matches, the status has no details".to_string())
+        );
+    }
+}
@@ -141,15 +141,36 @@ impl<B> GrpcWebCall<B> {
         (self.buf.len() / 4) * 4
     }
 
+    // Clients may encode each frame separately, sending base64 chunks ending
+    // with their own padding. This returns the end of the first padded chunk,
+    // or `None` if `buf` holds no padding.
+    fn padded_chunk_end(&self) -> Option<usize> {
+        let padding = self.buf.iter().position(|&byte| byte == b'=')?;
+        let end = (padding / 4 + 1) * 4;
+
+        // The padded chunk is incomplete, only decode what precedes it.
+        Some(if end > self.buf.len() {
+            (padding / 4) * 4
+        } else {
+            end
+        })
+    }
+
     fn decode_chunk(mut self: Pin<&mut Self>) -> Result<Option<Bytes>, Status> {
         // not enough bytes to decode
         if self.buf.is_empty() || self.buf.len() < 4 {
             return Ok(None);
         }
 
-        // Split `buf` at the largest index that is multiple of 4. Decode the
-        // returned `Bytes`, keeping the rest for the next attempt to decode.
-        let index = self.max_decodable();
+        // Split `buf` at the largest index that is multiple of 4, or at the
+        // end of the first padded chunk. Decode the returned `Bytes`, keeping
+        // the rest for the next attempt to decode.
+        let index = self
+            .padded_chunk_end()
+            .unwrap_or_else(|| self.max_decodable());
+        if index == 0 {
+            return Ok(None);
+        }
 
         crate::util::base64::STANDARD
             .decode(self.as_mut().project().buf.split_to(index))
@@ -353,8 +374,24 @@ impl Encoding {
         Self::from_header(headers.get(header::CONTENT_TYPE))
     }
 
+    /// The encoding of the responses accepted by the client, as the first
+    /// grpc-web content type of the `accept` header, falling back to the
+    /// encoding of the request.
     pub(crate) fn from_accept(headers: &HeaderMap) -> Encoding {
-        Self::from_header(headers.get(header::ACCEPT))
+        headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .find_map(|media_range| {
+                let media_type = media_range.split(';').next().unwrap_or_default().trim();
+                match media_type {
+                    GRPC_WEB_TEXT | GRPC_WEB_TEXT_PROTO => Some(Encoding::Base64),
+                    GRPC_WEB | GRPC_WEB_PROTO => Some(Encoding::None),
+                    _ => None,
+                }
+            })
+            .unwrap_or_else(|| Self::from_content_type(headers))
     }
 
     pub(crate) fn to_content_type(self) -> &'static str {
@@ -443,7 +480,7 @@ fn decode_trailers_frame(mut buf: Bytes) -> Result<Option<HeaderMap>, Status> {
     Ok(Some(map))
 }
 
-fn make_trailers_frame(trailers: HeaderMap) -> Bytes {
+pub(crate) fn make_trailers_frame(trailers: HeaderMap) -> Bytes {
     let trailers = encode_trailers(trailers);
     let len = trailers.len();
     assert!(len <= u32::MAX as usize);
@@ -531,6 +568,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn accept_negotiation() {
+        let cases = &[
+            (GRPC_WEB_TEXT, "*/*", Encoding::Base64),
+            (GRPC_WEB_TEXT, "", Encoding::Base64),
+            (GRPC_WEB_TEXT, "application/grpc-web", Encoding::None),
+            (
+                GRPC_WEB,
+                "text/html, application/grpc-web-text",
+                Encoding::Base64,
+            ),
+            (
+                GRPC_WEB,
+                "application/grpc-web-text+proto; q=0.9",
+                Encoding::Base64,
+            ),
+            (GRPC_WEB, "*/*", Encoding::None),
+        ];
+
+        for case in cases {
+            let mut headers = HeaderMap::new();
+            headers.insert(header::CONTENT_TYPE, case.0.parse().unwrap());
+            if !case.1.is_empty() {
+                headers.insert(header::ACCEPT, case.1.parse().unwrap());
+            }
+
+            assert_eq!(Encoding::from_accept(&headers), case.2, "{case:?}");
+        }
+    }
+
+    #[tokio::test]
+    async fn decode_base64_chunks() {
+        use http_body_util::{BodyExt as _, StreamBody};
+
+        let first = b"\0\0\0\0\x02ab";
+        let second = b"\0\0\0\0\x01c";
+        let encoded = [
+            crate::util::base64::STANDARD.encode(first),
+            crate::util::base64::STANDARD.encode(second),
+        ]
+        .concat();
+        assert_eq!(encoded, "AAAAAAJhYg==AAAAAAFj");
+
+        // Split the chunks anywhere, including within the padding.
+        let frames = ["AAAAAAJh", "Yg=", "=AA", "AAAAFj"]
+            .into_iter()
+            .map(|chunk| Ok::<_, Status>(Frame::data(Bytes::from(chunk))));
+        let body = StreamBody::new(tokio_stream::iter(frames));
+
+        let decoded = GrpcWebCall::request(body, Encoding::Base64)
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes();
+
+        assert_eq!(decoded, [&first[..], &second[..]].concat());
+    }
+
     #[test]
     fn decode_trailers() {
         let mut headers = HeaderMap::new();
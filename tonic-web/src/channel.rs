@@ -0,0 +1,305 @@
+//! A grpc-web [`Channel`](tonic::transport::Channel).
+
+use http::{Request, Response};
+use http_body_util::{BodyExt, Full};
+use pin_project::pin_project;
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tonic::body::Body;
+use tonic::transport::{channel, Channel, Endpoint, Error};
+use tonic::{ConnectError, GrpcMethodKind, Status};
+use tower_service::Service;
+
+use crate::call::GrpcWebCall;
+use crate::client::client_request;
+
+/// A [`Channel`] speaking the grpc-web protocol over HTTP/1.1.
+///
+/// This allows tonic clients to reach servers only exposed through grpc-web proxies, or from
+/// networks only letting HTTP/1.1 through. Like the browser clients, only `unary` and
+/// `server-streaming` calls are supported.
+///
+/// ```ignore
+/// let endpoint = Endpoint::from_static("https://example.com");
+/// let channel = GrpcWebChannel::connect(endpoint).await?;
+/// let mut client = GreeterClient::new(channel);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GrpcWebChannel {
+    inner: Channel,
+}
+
+impl GrpcWebChannel {
+    /// Connect to `endpoint` over HTTP/1.1, immediately.
+    pub async fn connect(endpoint: Endpoint) -> Result<Self, Error> {
+        let inner = endpoint.http1_only(true).connect().await?;
+        Ok(Self { inner })
+    }
+
+    /// Connect to `endpoint` over HTTP/1.1, lazily on the first request.
+    pub fn connect_lazy(endpoint: Endpoint) -> Self {
+        let inner = endpoint.http1_only(true).connect_lazy();
+        Self { inner }
+    }
+}
+
+impl Service<Request<Body>> for GrpcWebChannel {
+    type Response = Response<Body>;
+    type Error = Error;
+    type Future = ResponseFuture;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let req = client_request(req).map(Body::new);
+
+        ResponseFuture {
+            inner: self.inner.call(req),
+        }
+    }
+}
+
+/// Response future for the [`GrpcWebChannel`].
+#[pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture {
+    #[pin]
+    inner: channel::ResponseFuture,
+}
+
+impl Future for ResponseFuture {
+    type Output = Result<Response<Body>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().inner.poll(cx));
+
+        Poll::Ready(res.map(|r| r.map(|body| Body::new(GrpcWebCall::client_response(body)))))
+    }
+}
+
+impl fmt::Debug for ResponseFuture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+/// A [`Channel`] speaking gRPC over HTTP/2, falling back to grpc-web over HTTP/1.1 for unary calls
+/// when HTTP/2 doesn't get through, such as behind proxies only letting HTTP/1.1 through.
+///
+/// Unary calls failing over HTTP/2 before the server accepted them are sent again with grpc-web
+/// over HTTP/1.1, their request being buffered: when HTTP/2 fails to connect, e.g. when the TLS
+/// handshake doesn't negotiate it, when the connection is closed before any response frame, or
+/// when the server refuses the stream with `REFUSED_STREAM`. Calls failing once accepted are not
+/// sent again, as the server may have run them. Once a call succeeded over HTTP/1.1, the channel
+/// sends the next unary calls over HTTP/1.1 right away, while the streaming calls, which the
+/// fallback can't carry, fail right away with `Code::Unimplemented`. This is transparent to the
+/// generated clients.
+///
+/// ```ignore
+/// let endpoint = Endpoint::from_static("https://example.com");
+/// let channel = FallbackChannel::connect(endpoint).await?;
+/// let mut client = GreeterClient::new(channel);
+/// ```
+#[derive(Debug, Clone)]
+pub struct FallbackChannel {
+    http2: Channel,
+    grpc_web: GrpcWebChannel,
+    fell_back: Arc<AtomicBool>,
+    // Whether the channel made ready is the fallback one, so that calls go to it.
+    ready_fallback: bool,
+}
+
+impl FallbackChannel {
+    /// Connect to `endpoint` over HTTP/2, immediately, or over HTTP/1.1 if HTTP/2 fails.
+    ///
+    /// Fails with the error of HTTP/2 when both fail.
+    pub async fn connect(endpoint: Endpoint) -> Result<Self, Error> {
+        match endpoint.connect().await {
+            Ok(http2) => Ok(Self::new(
+                http2,
+                GrpcWebChannel::connect_lazy(endpoint),
+                false,
+            )),
+            Err(err) => match GrpcWebChannel::connect(endpoint.clone()).await {
+                Ok(grpc_web) => Ok(Self::new(endpoint.connect_lazy(), grpc_web, true)),
+                Err(_) => Err(err),
+            },
+        }
+    }
+
+    /// Connect to `endpoint` lazily, over HTTP/2 on the first request.
+    pub fn connect_lazy(endpoint: Endpoint) -> Self {
+        Self::new(
+            endpoint.connect_lazy(),
+            GrpcWebChannel::connect_lazy(endpoint),
+            false,
+        )
+    }
+
+    fn new(http2: Channel, grpc_web: GrpcWebChannel, fell_back: bool) -> Self {
+        Self {
+            http2,
+            grpc_web,
+            fell_back: Arc::new(AtomicBool::new(fell_back)),
+            ready_fallback: fell_back,
+        }
+    }
+
+    /// Whether the channel fell back to grpc-web over HTTP/1.1.
+    pub fn is_fallback(&self) -> bool {
+        self.fell_back.load(Ordering::Relaxed)
+    }
+}
+
+impl Service<Request<Body>> for FallbackChannel {
+    type Response = Response<Body>;
+    type Error = Error;
+    type Future = FallbackResponseFuture;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.ready_fallback = self.is_fallback();
+        if self.ready_fallback {
+            self.grpc_web.poll_ready(cx)
+        } else {
+            self.http2.poll_ready(cx)
+        }
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        // Requests not sent by `tonic::client::Grpc` are assumed to be unary.
+        let kind = req
+            .extensions()
+            .get::<GrpcMethodKind>()
+            .copied()
+            .unwrap_or(GrpcMethodKind::Unary);
+        let unary = kind == GrpcMethodKind::Unary;
+
+        let inner: Pin<Box<dyn Future<Output = _> + Send>> = match (self.ready_fallback, unary) {
+            (true, true) => Box::pin(self.grpc_web.call(req)),
+            (true, false) => {
+                let status = Status::unimplemented(format!(
+                    "{kind:?} calls are not supported by the grpc-web over HTTP/1.1 fallback of \
+                     the channel, which only carries unary calls"
+                ));
+                Box::pin(std::future::ready(Ok(status.into_http())))
+            }
+            (false, false) => Box::pin(self.http2.call(req)),
+            (false, true) => {
+                // The request is sent once buffered, by the channel made ready.
+                let clone = self.http2.clone();
+                let http2 = std::mem::replace(&mut self.http2, clone);
+                Box::pin(fallback_unary(
+                    http2,
+                    self.grpc_web.clone(),
+                    self.fell_back.clone(),
+                    req,
+                ))
+            }
+        };
+
+        FallbackResponseFuture { inner }
+    }
+}
+
+// Sends a unary call over HTTP/2, sending it again over HTTP/1.1 if HTTP/2 fails before the
+// server accepted it.
+async fn fallback_unary(
+    mut http2: Channel,
+    mut grpc_web: GrpcWebChannel,
+    fell_back: Arc<AtomicBool>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Error> {
+    let mut fallback_req = Request::new(());
+    *fallback_req.method_mut() = req.method().clone();
+    *fallback_req.uri_mut() = req.uri().clone();
+    *fallback_req.headers_mut() = req.headers().clone();
+    *fallback_req.extensions_mut() = req.extensions().clone();
+
+    let (parts, body) = req.into_parts();
+    let message = match body.collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(status) => return Ok(status.into_http()),
+    };
+
+    let http2_req = Request::from_parts(parts, Body::new(Full::new(message.clone())));
+    let err = match http2.call(http2_req).await {
+        Ok(res) => return Ok(res),
+        Err(err) if can_resend(&err) => err,
+        Err(err) => return Err(err),
+    };
+
+    if poll_fn(|cx| grpc_web.poll_ready(cx)).await.is_err() {
+        return Err(err);
+    }
+    match grpc_web
+        .call(fallback_req.map(|()| Body::new(Full::new(message))))
+        .await
+    {
+        Ok(res) => {
+            tracing::debug!("HTTP/2 call failed, falling back to grpc-web over HTTP/1.1: {err}");
+            fell_back.store(true, Ordering::Relaxed);
+            Ok(res)
+        }
+        Err(_) => Err(err),
+    }
+}
+
+// Whether the call failed before the server accepted it, so that sending it again can't run it
+// twice: while connecting, when the connection was closed before any response frame, or when the
+// server refused its stream.
+fn can_resend(err: &Error) -> bool {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(err) = source {
+        if err.is::<ConnectError>() {
+            return true;
+        }
+        if let Some(err) = err.downcast_ref::<h2::Error>() {
+            return match err.get_io() {
+                Some(err) => is_closed(err),
+                None => err.reason() == Some(h2::Reason::REFUSED_STREAM),
+            };
+        }
+        if let Some(err) = err.downcast_ref::<io::Error>() {
+            return is_closed(err);
+        }
+        source = err.source();
+    }
+    false
+}
+
+fn is_closed(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+/// Response future for the [`FallbackChannel`].
+#[must_use = "futures do nothing unless polled"]
+pub struct FallbackResponseFuture {
+    inner: Pin<Box<dyn Future<Output = Result<Response<Body>, Error>> + Send>>,
+}
+
+impl Future for FallbackResponseFuture {
+    type Output = Result<Response<Body>, Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+impl fmt::Debug for FallbackResponseFuture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FallbackResponseFuture").finish()
+    }
+}
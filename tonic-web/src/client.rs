@@ -60,22 +60,25 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, mut req: Request<B1>) -> Self::Future {
-        if req.version() == Version::HTTP_2 {
-            debug!("coercing HTTP2 request to HTTP1.1");
+    fn call(&mut self, req: Request<B1>) -> Self::Future {
+        let fut = self.inner.call(client_request(req));
 
-            *req.version_mut() = Version::HTTP_11;
-        }
+        ResponseFuture { inner: fut }
+    }
+}
 
-        req.headers_mut()
-            .insert(CONTENT_TYPE, GRPC_WEB.try_into().unwrap());
+// Coerces a request coming from `tonic::client::Grpc` into a grpc-web request.
+pub(crate) fn client_request<B>(mut req: Request<B>) -> Request<GrpcWebCall<B>> {
+    if req.version() == Version::HTTP_2 {
+        debug!("coercing HTTP2 request to HTTP1.1");
 
-        let req = req.map(GrpcWebCall::client_request);
+        *req.version_mut() = Version::HTTP_11;
+    }
 
-        let fut = self.inner.call(req);
+    req.headers_mut()
+        .insert(CONTENT_TYPE, GRPC_WEB.try_into().unwrap());
 
-        ResponseFuture { inner: fut }
-    }
+    req.map(GrpcWebCall::client_request)
 }
 
 /// Response future for the [`GrpcWebService`](crate::GrpcWebService).
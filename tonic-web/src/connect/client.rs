@@ -0,0 +1,581 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::future::{ready, Future};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use base64::Engine as _;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::{header, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri, Version};
+use http_body::Frame;
+use http_body_util::{BodyExt, Full};
+use pin_project::pin_project;
+use tonic::body::Body;
+use tonic::metadata::GRPC_CONTENT_TYPE;
+use tonic::{GrpcMethodKind, Status};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use super::{
+    code_from_http_status, status_from_error_json, trailers_from_end_stream, Codec,
+    ACCEPT_ENCODING, CONTENT_ENCODING, END_STREAM_FLAG, GRPC_ACCEPT_ENCODING, GRPC_ENCODING,
+    GRPC_TIMEOUT, PROTOCOL_VERSION, TIMEOUT_MS,
+};
+use crate::BoxError;
+
+const GRPC_HEADER_SIZE: usize = 5;
+const TRAILER_PREFIX: &str = "trailer-";
+
+/// Layer speaking the [Connect] protocol for clients.
+///
+/// The requests of [`tonic::client::Grpc`] are translated to Connect requests, and the Connect
+/// responses back to gRPC responses, so generated clients can call services served by
+/// `connect-go`, or by the [`ConnectLayer`](super::ConnectLayer):
+///
+/// ```ignore
+/// let client = Client::builder(TokioExecutor::new()).build_http();
+/// let svc = ConnectClientLayer::new()
+///     .get_methods(["/helloworld.Greeter/SayHello"])
+///     .layer(client);
+/// let mut client = GreeterClient::with_origin(svc, "http://example.com".try_into()?);
+/// ```
+///
+/// Unary calls use the Connect unary protocol, and all other calls the streaming protocol. The
+/// kind of a call is learned from the [`GrpcMethodKind`] extension of its request; requests
+/// without it use the streaming protocol.
+///
+/// [Connect]: https://connectrpc.com/docs/protocol
+#[derive(Debug, Default, Clone)]
+pub struct ConnectClientLayer {
+    get_methods: Arc<HashSet<String>>,
+}
+
+impl ConnectClientLayer {
+    /// Create a new Connect for clients layer.
+    pub fn new() -> ConnectClientLayer {
+        Self::default()
+    }
+
+    /// Send the unary calls of the methods at `paths`, such as `/helloworld.Greeter/SayHello`,
+    /// as GET requests.
+    ///
+    /// Only methods free of side effects should be called with GET requests, whose responses
+    /// HTTP caches may store.
+    pub fn get_methods<I>(mut self, paths: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        Arc::make_mut(&mut self.get_methods).extend(paths.into_iter().map(Into::into));
+        self
+    }
+}
+
+impl<S> Layer<S> for ConnectClientLayer {
+    type Service = ConnectClientService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectClientService {
+            inner,
+            get_methods: self.get_methods.clone(),
+        }
+    }
+}
+
+/// A [`Service`] that wraps some inner http service that will coerce requests coming from
+/// [`tonic::client::Grpc`] into [Connect] requests.
+///
+/// [Connect]: https://connectrpc.com/docs/protocol
+#[derive(Debug, Clone)]
+pub struct ConnectClientService<S> {
+    inner: S,
+    get_methods: Arc<HashSet<String>>,
+}
+
+impl<S> ConnectClientService<S> {
+    /// Create a new Connect for clients service.
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            get_methods: Arc::default(),
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for ConnectClientService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Send,
+    ReqBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ReqBody::Error: Into<BoxError>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let codec = Codec::from_grpc_content_type(req.headers());
+
+        let future: Pin<Box<dyn Future<Output = _> + Send>> =
+            match req.extensions().get::<GrpcMethodKind>() {
+                Some(GrpcMethodKind::Unary) => {
+                    let get = self.get_methods.contains(req.uri().path());
+                    // The request is sent once buffered, by the service made ready.
+                    let clone = self.inner.clone();
+                    let mut inner = std::mem::replace(&mut self.inner, clone);
+
+                    Box::pin(async move {
+                        let req = match unary_request(req, codec, get).await {
+                            Ok(req) => req,
+                            Err(status) => return Ok(status_response(status, HeaderMap::new())),
+                        };
+                        let res = inner.call(req).await?;
+                        Ok(unary_response(res).await)
+                    })
+                }
+                _ => {
+                    let future = self.inner.call(streaming_request(req, codec));
+                    Box::pin(async move { Ok(streaming_response(future.await?)) })
+                }
+            };
+
+        ResponseFuture { inner: future }
+    }
+}
+
+/// Response future for the [`ConnectClientService`].
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture<E> {
+    inner: Pin<Box<dyn Future<Output = Result<Response<Body>, E>> + Send>>,
+}
+
+impl<E> Future for ResponseFuture<E> {
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.inner.as_mut().poll(cx)
+    }
+}
+
+impl<E> fmt::Debug for ResponseFuture<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+// Moves the gRPC headers of a request to their Connect equivalents, returning the encoding of
+// its messages.
+fn coerce_headers(
+    parts: &mut http::request::Parts,
+    encoding: http::HeaderName,
+    accept_encoding: http::HeaderName,
+) -> Option<HeaderValue> {
+    if parts.version == Version::HTTP_2 {
+        parts.version = Version::HTTP_11;
+    }
+
+    let headers = &mut parts.headers;
+    headers.remove(header::TE);
+    headers.remove(header::CONTENT_LENGTH);
+    headers.insert(PROTOCOL_VERSION, HeaderValue::from_static("1"));
+
+    if let Some(timeout) = headers.remove(GRPC_TIMEOUT).and_then(|t| timeout_ms(&t)) {
+        headers.insert(TIMEOUT_MS, timeout);
+    }
+    if let Some(accept) = headers.remove(GRPC_ACCEPT_ENCODING) {
+        headers.insert(accept_encoding, accept);
+    }
+    let message_encoding = headers.remove(GRPC_ENCODING);
+    if let Some(message_encoding) = &message_encoding {
+        headers.insert(encoding, message_encoding.clone());
+    }
+    message_encoding
+}
+
+// The `connect-timeout-ms` of a `grpc-timeout` timeout.
+fn timeout_ms(timeout: &HeaderValue) -> Option<HeaderValue> {
+    let timeout = timeout.to_str().ok()?;
+    let (value, unit) = timeout.split_at(timeout.len().checked_sub(1)?);
+    let value = value.parse::<u64>().ok()?;
+
+    let ms = match unit {
+        "H" => value.saturating_mul(60 * 60 * 1000),
+        "M" => value.saturating_mul(60 * 1000),
+        "S" => value.saturating_mul(1000),
+        "m" => value,
+        // Round sub-millisecond timeouts up, as a zero timeout has already expired.
+        "u" => value.div_ceil(1000),
+        "n" => value.div_ceil(1_000_000),
+        _ => return None,
+    };
+    // Connect timeouts have at most 10 digits.
+    HeaderValue::try_from(ms.min(9_999_999_999).to_string()).ok()
+}
+
+// Translates the request of a unary call, buffering its single message.
+async fn unary_request<B>(req: Request<B>, codec: Codec, get: bool) -> Result<Request<Body>, Status>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    let (mut parts, body) = req.into_parts();
+    let mut message = body
+        .collect()
+        .await
+        .map_err(|e| Status::from_error(e.into()))?
+        .to_bytes();
+
+    if message.len() < GRPC_HEADER_SIZE {
+        return Err(Status::internal("missing request message"));
+    }
+    let compressed = message.get_u8() != 0;
+    let len = message.get_u32() as usize;
+    if message.len() != len {
+        return Err(Status::internal("expected a single request message"));
+    }
+
+    // Unary calls compress messages with the HTTP `content-encoding`.
+    let encoding = coerce_headers(
+        &mut parts,
+        header::CONTENT_ENCODING,
+        header::ACCEPT_ENCODING,
+    )
+    .filter(|_| compressed);
+    if !compressed {
+        parts.headers.remove(header::CONTENT_ENCODING);
+    }
+
+    if get {
+        let mut query = format!(
+            "connect=v1&encoding={}&base64=1&message={}",
+            codec.name(),
+            crate::util::base64::URL_SAFE.encode(&message)
+        );
+        if let Some(encoding) = encoding.as_ref().and_then(|e| e.to_str().ok()) {
+            query.push_str("&compression=");
+            query.push_str(encoding);
+        }
+
+        let mut uri = parts.uri.into_parts();
+        uri.path_and_query = Some(
+            format!(
+                "{}?{query}",
+                uri.path_and_query.as_ref().map_or("/", |p| p.path())
+            )
+            .parse()
+            .map_err(|_| Status::internal("invalid GET request uri"))?,
+        );
+        parts.uri =
+            Uri::from_parts(uri).map_err(|_| Status::internal("invalid GET request uri"))?;
+        parts.method = Method::GET;
+        parts.headers.remove(header::CONTENT_TYPE);
+        parts.headers.remove(header::CONTENT_ENCODING);
+        parts.headers.remove(PROTOCOL_VERSION);
+
+        return Ok(Request::from_parts(parts, Body::empty()));
+    }
+
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, codec.unary_content_type());
+    Ok(Request::from_parts(parts, Body::new(Full::new(message))))
+}
+
+fn streaming_request<B>(req: Request<B>, codec: Codec) -> Request<Body>
+where
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let (mut parts, body) = req.into_parts();
+    coerce_headers(&mut parts, CONTENT_ENCODING, ACCEPT_ENCODING);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, codec.streaming_content_type());
+
+    Request::from_parts(parts, Body::new(body))
+}
+
+// Translates the response of a unary call, a bare message or a Connect error, to a gRPC
+// response.
+async fn unary_response<B>(res: Response<B>) -> Response<Body>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    let (parts, body) = res.into_parts();
+    let (mut headers, trailers) = split_trailers(parts.headers);
+
+    let body = match body.collect().await {
+        Ok(body) => body.to_bytes(),
+        Err(e) => return status_response(Status::from_error(e.into()), headers),
+    };
+
+    if parts.status != StatusCode::OK {
+        let status = serde_json::from_slice(&body)
+            .ok()
+            .and_then(|error| status_from_error_json(&error))
+            .unwrap_or_else(|| {
+                Status::new(
+                    code_from_http_status(parts.status),
+                    format!("unexpected HTTP status {}", parts.status),
+                )
+            });
+        headers.extend(trailers);
+        return status_response(status, headers);
+    }
+
+    let encoding = headers
+        .remove(header::CONTENT_ENCODING)
+        .filter(|encoding| encoding != "identity");
+    let compressed = encoding.is_some();
+    if let Some(encoding) = encoding {
+        headers.insert(GRPC_ENCODING, encoding);
+    }
+    headers.remove(header::CONTENT_LENGTH);
+    headers.insert(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+
+    let mut message = BytesMut::with_capacity(GRPC_HEADER_SIZE + body.len());
+    message.put_u8(u8::from(compressed));
+    message.put_u32(body.len() as u32);
+    message.put(body);
+
+    let mut trailers = trailers;
+    if let Err(status) = Status::ok("").add_header(&mut trailers) {
+        return status_response(status, headers);
+    }
+
+    let body = Full::new(message.freeze())
+        .map_err(|never| match never {})
+        .with_trailers(ready(Some(Ok::<_, Status>(trailers))));
+    let mut res = Response::new(Body::new(body));
+    *res.headers_mut() = headers;
+    res
+}
+
+// Splits the `trailer-` prefixed headers of unary responses from the other headers.
+fn split_trailers(headers: HeaderMap) -> (HeaderMap, HeaderMap) {
+    let mut trailers = HeaderMap::new();
+    let mut rest = HeaderMap::new();
+
+    let mut name = None;
+    for (next, value) in headers {
+        // Following values of the same header have no name.
+        if next.is_some() {
+            name = next;
+        }
+        let Some(name) = name.clone() else { continue };
+
+        match name
+            .as_str()
+            .strip_prefix(TRAILER_PREFIX)
+            .and_then(|trailer| http::HeaderName::try_from(trailer).ok())
+        {
+            Some(trailer) => trailers.append(trailer, value),
+            None => rest.append(name, value),
+        };
+    }
+
+    (rest, trailers)
+}
+
+fn streaming_response<B>(res: Response<B>) -> Response<Body>
+where
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let (mut parts, body) = res.into_parts();
+
+    if parts.status != StatusCode::OK {
+        let status = Status::new(
+            code_from_http_status(parts.status),
+            format!("unexpected HTTP status {}", parts.status),
+        );
+        return status_response(status, HeaderMap::new());
+    }
+
+    let headers = &mut parts.headers;
+    headers.remove(header::CONTENT_LENGTH);
+    headers.insert(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+    if let Some(encoding) = headers.remove(CONTENT_ENCODING) {
+        headers.insert(GRPC_ENCODING, encoding);
+    }
+
+    let body = StreamingResponseBody {
+        inner: body,
+        buf: BytesMut::new(),
+        done: false,
+    };
+    Response::from_parts(parts, Body::new(body))
+}
+
+// A trailers-only gRPC response failing with `status`.
+fn status_response(status: Status, mut headers: HeaderMap) -> Response<Body> {
+    headers.insert(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+    if let Err(status) = status.add_header(&mut headers) {
+        headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+        let _ = status.add_header(&mut headers);
+    }
+
+    let mut res = Response::new(Body::empty());
+    *res.headers_mut() = headers;
+    res
+}
+
+// The body of a streaming response, ending with trailers instead of an end-of-stream message.
+#[pin_project]
+struct StreamingResponseBody<B> {
+    #[pin]
+    inner: B,
+    buf: BytesMut,
+    done: bool,
+}
+
+impl<B> http_body::Body for StreamingResponseBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.done {
+                return Poll::Ready(None);
+            }
+
+            // Forward the complete envelopes buffered, up to the end-of-stream message.
+            if this.buf.len() >= GRPC_HEADER_SIZE {
+                let flags = this.buf[0];
+                let len = u32::from_be_bytes([this.buf[1], this.buf[2], this.buf[3], this.buf[4]])
+                    as usize;
+
+                if this.buf.len() >= GRPC_HEADER_SIZE + len {
+                    let envelope = this.buf.split_to(GRPC_HEADER_SIZE + len).freeze();
+                    if flags & END_STREAM_FLAG == 0 {
+                        return Poll::Ready(Some(Ok(Frame::data(envelope))));
+                    }
+
+                    *this.done = true;
+                    let trailers = serde_json::from_slice(&envelope[GRPC_HEADER_SIZE..])
+                        .ok()
+                        .and_then(|end| trailers_from_end_stream(&end))
+                        .ok_or_else(|| Status::internal("invalid end-of-stream message"));
+                    return Poll::Ready(Some(trailers.map(Frame::trailers)));
+                }
+            }
+
+            match std::task::ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        this.buf.put(data);
+                    }
+                }
+                Some(Err(e)) => {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(Status::from_error(e.into()))));
+                }
+                None => {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(Status::internal(
+                        "missing end-of-stream message",
+                    ))));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grpc_request(message: &'static [u8]) -> Request<Full<Bytes>> {
+        let mut body = BytesMut::new();
+        body.put_u8(0);
+        body.put_u32(message.len() as u32);
+        body.put_slice(message);
+
+        let mut req = Request::new(Full::new(body.freeze()));
+        *req.method_mut() = Method::POST;
+        *req.uri_mut() = Uri::from_static("http://example.com/a.B/C");
+        *req.version_mut() = Version::HTTP_2;
+        req.headers_mut()
+            .insert(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+        req.headers_mut()
+            .insert(GRPC_TIMEOUT, HeaderValue::from_static("2S"));
+        req
+    }
+
+    #[tokio::test]
+    async fn unary_requests() {
+        let req = unary_request(grpc_request(b"\x08\x01"), Codec::Proto, false)
+            .await
+            .unwrap();
+        assert_eq!(req.method(), Method::POST);
+        assert_eq!(req.version(), Version::HTTP_11);
+        assert_eq!(req.headers()[header::CONTENT_TYPE], "application/proto");
+        assert_eq!(req.headers()[PROTOCOL_VERSION], "1");
+        assert_eq!(req.headers()[TIMEOUT_MS], "2000");
+        let body = req.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"\x08\x01");
+
+        let req = unary_request(grpc_request(b"\x08\x01"), Codec::Proto, true)
+            .await
+            .unwrap();
+        assert_eq!(req.method(), Method::GET);
+        assert_eq!(
+            req.uri(),
+            "http://example.com/a.B/C?connect=v1&encoding=proto&base64=1&message=CAE"
+        );
+    }
+
+    #[tokio::test]
+    async fn unary_error_responses() {
+        let res = Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header("trailer-x-value", "a")
+            .body(Full::new(Bytes::from_static(
+                br#"{"code":"not_found","message":"missing"}"#,
+            )))
+            .unwrap();
+
+        let res = unary_response(res).await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()["grpc-status"], "5");
+        assert_eq!(res.headers()["grpc-message"], "missing");
+        assert_eq!(res.headers()["x-value"], "a");
+
+        // Without a Connect error, the code is inferred from the HTTP status.
+        let res = Response::builder()
+            .status(StatusCode::SERVICE_UNAVAILABLE)
+            .body(Full::new(Bytes::from_static(b"<html>")))
+            .unwrap();
+        let res = unary_response(res).await;
+        assert_eq!(res.headers()["grpc-status"], "14");
+    }
+
+    #[test]
+    fn timeouts() {
+        let timeout = |t| timeout_ms(&HeaderValue::from_static(t)).unwrap();
+
+        assert_eq!(timeout("1H"), "3600000");
+        assert_eq!(timeout("250m"), "250");
+        assert_eq!(timeout("1500u"), "2");
+        assert!(timeout_ms(&HeaderValue::from_static("1x")).is_none());
+    }
+}
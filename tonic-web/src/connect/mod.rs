@@ -0,0 +1,478 @@
+//! The [Connect] protocol.
+//!
+//! Connect carries RPCs over plain HTTP semantics: unary calls send and receive bare messages,
+//! and report errors as JSON bodies with a matching HTTP status, while streaming calls use the
+//! same enveloped messages as gRPC, ending with a JSON end-of-stream message instead of HTTP
+//! trailers. Both work over HTTP/1.1, and both carry `proto` or `json` encoded messages.
+//!
+//! Messages are not transcoded: `json` requests reach the inner service as
+//! `application/grpc+json` requests, which only services generated with a JSON [`Codec`] can
+//! decode.
+//!
+//! [Connect]: https://connectrpc.com/docs/protocol
+//! [`Codec`]: tonic::codec::Codec
+
+use base64::Engine as _;
+use bytes::Bytes;
+use http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use http::StatusCode;
+use prost::Message as _;
+use serde_json::{json, Map, Value};
+use tonic::{Code, Status};
+
+use crate::util::base64::STANDARD_NO_PAD;
+pub(crate) use crate::util::status::{http_status, status_from_headers};
+
+mod client;
+mod server;
+
+pub use client::{ConnectClientLayer, ConnectClientService};
+pub use server::{ConnectLayer, ConnectService, ResponseFuture};
+
+pub(crate) const PROTOCOL_VERSION: HeaderName = HeaderName::from_static("connect-protocol-version");
+pub(crate) const TIMEOUT_MS: HeaderName = HeaderName::from_static("connect-timeout-ms");
+pub(crate) const CONTENT_ENCODING: HeaderName = HeaderName::from_static("connect-content-encoding");
+pub(crate) const ACCEPT_ENCODING: HeaderName = HeaderName::from_static("connect-accept-encoding");
+
+pub(crate) const GRPC_ENCODING: HeaderName = HeaderName::from_static("grpc-encoding");
+pub(crate) const GRPC_ACCEPT_ENCODING: HeaderName = HeaderName::from_static("grpc-accept-encoding");
+pub(crate) const GRPC_TIMEOUT: HeaderName = HeaderName::from_static("grpc-timeout");
+
+// The flag of the envelope holding the end-of-stream message.
+pub(crate) const END_STREAM_FLAG: u8 = 0b10;
+
+const TRAILER_PREFIX: &str = "trailer-";
+
+/// The encoding of the messages of a Connect call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Codec {
+    Proto,
+    Json,
+}
+
+impl Codec {
+    pub(crate) fn from_name(name: &str) -> Option<Codec> {
+        match name {
+            "proto" => Some(Codec::Proto),
+            "json" => Some(Codec::Json),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Codec::Proto => "proto",
+            Codec::Json => "json",
+        }
+    }
+
+    /// The codec of a gRPC request, from its content-type.
+    pub(crate) fn from_grpc_content_type(headers: &HeaderMap) -> Codec {
+        match headers.get(header::CONTENT_TYPE) {
+            Some(content_type) if content_type == "application/grpc+json" => Codec::Json,
+            _ => Codec::Proto,
+        }
+    }
+
+    pub(crate) fn unary_content_type(self) -> HeaderValue {
+        match self {
+            Codec::Proto => HeaderValue::from_static("application/proto"),
+            Codec::Json => HeaderValue::from_static("application/json"),
+        }
+    }
+
+    pub(crate) fn streaming_content_type(self) -> HeaderValue {
+        match self {
+            Codec::Proto => HeaderValue::from_static("application/connect+proto"),
+            Codec::Json => HeaderValue::from_static("application/connect+json"),
+        }
+    }
+
+    pub(crate) fn grpc_content_type(self) -> HeaderValue {
+        match self {
+            Codec::Proto => HeaderValue::from_static("application/grpc+proto"),
+            Codec::Json => HeaderValue::from_static("application/grpc+json"),
+        }
+    }
+}
+
+/// The name of `code` in the Connect protocol.
+pub(crate) fn code_name(code: Code) -> &'static str {
+    match code {
+        Code::Ok => "ok",
+        Code::Cancelled => "canceled",
+        Code::Unknown => "unknown",
+        Code::InvalidArgument => "invalid_argument",
+        Code::DeadlineExceeded => "deadline_exceeded",
+        Code::NotFound => "not_found",
+        Code::AlreadyExists => "already_exists",
+        Code::PermissionDenied => "permission_denied",
+        Code::ResourceExhausted => "resource_exhausted",
+        Code::FailedPrecondition => "failed_precondition",
+        Code::Aborted => "aborted",
+        Code::OutOfRange => "out_of_range",
+        Code::Unimplemented => "unimplemented",
+        Code::Internal => "internal",
+        Code::Unavailable => "unavailable",
+        Code::DataLoss => "data_loss",
+        Code::Unauthenticated => "unauthenticated",
+    }
+}
+
+/// The code named `name` in the Connect protocol.
+pub(crate) fn code_from_name(name: &str) -> Option<Code> {
+    let code = match name {
+        "canceled" => Code::Cancelled,
+        "unknown" => Code::Unknown,
+        "invalid_argument" => Code::InvalidArgument,
+        "deadline_exceeded" => Code::DeadlineExceeded,
+        "not_found" => Code::NotFound,
+        "already_exists" => Code::AlreadyExists,
+        "permission_denied" => Code::PermissionDenied,
+        "resource_exhausted" => Code::ResourceExhausted,
+        "failed_precondition" => Code::FailedPrecondition,
+        "aborted" => Code::Aborted,
+        "out_of_range" => Code::OutOfRange,
+        "unimplemented" => Code::Unimplemented,
+        "internal" => Code::Internal,
+        "unavailable" => Code::Unavailable,
+        "data_loss" => Code::DataLoss,
+        "unauthenticated" => Code::Unauthenticated,
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// The code of responses failing with the HTTP `status`, without a Connect error.
+pub(crate) fn code_from_http_status(status: StatusCode) -> Code {
+    match status.as_u16() {
+        400 => Code::Internal,
+        401 => Code::Unauthenticated,
+        403 => Code::PermissionDenied,
+        404 => Code::Unimplemented,
+        429 | 502 | 503 | 504 => Code::Unavailable,
+        _ => Code::Unknown,
+    }
+}
+
+// `google.rpc.Status`, the encoding of the details of a `Status`.
+#[derive(Clone, PartialEq, prost::Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<Any>,
+}
+
+// `google.protobuf.Any`.
+#[derive(Clone, PartialEq, prost::Message)]
+struct Any {
+    #[prost(string, tag = "1")]
+    type_url: String,
+    #[prost(bytes = "bytes", tag = "2")]
+    value: Bytes,
+}
+
+/// The Connect error of `status`, as its JSON representation.
+pub(crate) fn error_json(status: &Status) -> Value {
+    let mut error = Map::new();
+    error.insert("code".into(), code_name(status.code()).into());
+    if !status.message().is_empty() {
+        error.insert("message".into(), status.message().into());
+    }
+
+    let details = RpcStatus::decode(status.details())
+        .map(|details| details.details)
+        .unwrap_or_default();
+    if !details.is_empty() {
+        let details = details
+            .iter()
+            .map(|any| {
+                // Connect identifies details by the fully-qualified name of their message.
+                let name = any.type_url.rsplit('/').next().unwrap_or_default();
+                json!({ "type": name, "value": STANDARD_NO_PAD.encode(&any.value) })
+            })
+            .collect();
+        error.insert("details".into(), Value::Array(details));
+    }
+
+    Value::Object(error)
+}
+
+/// The status of the Connect `error`, from its JSON representation.
+pub(crate) fn status_from_error_json(error: &Value) -> Option<Status> {
+    let code = code_from_name(error.get("code")?.as_str()?)?;
+    let message = error
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+
+    let details = error
+        .get("details")
+        .and_then(Value::as_array)
+        .map(|details| {
+            details
+                .iter()
+                .filter_map(|detail| {
+                    let name = detail.get("type")?.as_str()?;
+                    let value = STANDARD_NO_PAD
+                        .decode(detail.get("value")?.as_str()?)
+                        .ok()?;
+                    Some(Any {
+                        type_url: format!("type.googleapis.com/{name}"),
+                        value: value.into(),
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    if details.is_empty() {
+        return Some(Status::new(code, message));
+    }
+    let details = RpcStatus {
+        code: code as i32,
+        message: message.to_string(),
+        details,
+    };
+    Some(Status::with_details(
+        code,
+        message,
+        details.encode_to_vec().into(),
+    ))
+}
+
+/// The end-of-stream message of a streaming call ending with `status` and `trailers`.
+pub(crate) fn end_stream_json(status: &Status, trailers: &HeaderMap) -> Value {
+    let mut end = Map::new();
+    if status.code() != Code::Ok {
+        end.insert("error".into(), error_json(status));
+    }
+
+    let mut metadata = Map::new();
+    for (name, value) in trailers {
+        if is_reserved(name) {
+            continue;
+        }
+        if let Ok(value) = value.to_str() {
+            let values = metadata
+                .entry(name.as_str())
+                .or_insert_with(|| Value::Array(Vec::new()));
+            if let Value::Array(values) = values {
+                values.push(value.into());
+            }
+        }
+    }
+    if !metadata.is_empty() {
+        end.insert("metadata".into(), Value::Object(metadata));
+    }
+
+    Value::Object(end)
+}
+
+/// The trailers of a gRPC response, from the end-of-stream message `end` of a streaming call.
+pub(crate) fn trailers_from_end_stream(end: &Value) -> Option<HeaderMap> {
+    let mut trailers = HeaderMap::new();
+
+    if let Some(metadata) = end.get("metadata") {
+        for (name, values) in metadata.as_object()? {
+            let name = HeaderName::try_from(name.as_str()).ok()?;
+            for value in values.as_array()? {
+                let value = HeaderValue::try_from(value.as_str()?).ok()?;
+                trailers.append(name.clone(), value);
+            }
+        }
+    }
+
+    let status = match end.get("error") {
+        Some(error) => status_from_error_json(error)?,
+        None => Status::ok(""),
+    };
+    status.add_header(&mut trailers).ok()?;
+
+    Some(trailers)
+}
+
+/// `headers` without the headers reserved to the gRPC protocol, with `trailers` as
+/// `trailer-` prefixed headers.
+pub(crate) fn unary_headers(mut headers: HeaderMap, trailers: &HeaderMap) -> HeaderMap {
+    remove_reserved(&mut headers);
+
+    for (name, value) in trailers {
+        if is_reserved(name) {
+            continue;
+        }
+        if let Ok(name) = HeaderName::try_from(format!("{TRAILER_PREFIX}{name}")) {
+            headers.append(name, value.clone());
+        }
+    }
+
+    headers
+}
+
+// Whether `name` is a header of the gRPC protocol, not to be forwarded to Connect clients.
+pub(crate) fn is_reserved(name: &HeaderName) -> bool {
+    name == header::CONTENT_TYPE
+        || name == header::CONTENT_LENGTH
+        || name == header::TE
+        || name.as_str().starts_with("grpc-")
+}
+
+// Drops the reserved headers of `headers`.
+pub(crate) fn remove_reserved(headers: &mut HeaderMap) {
+    let names = headers
+        .keys()
+        .filter(|name| is_reserved(name))
+        .cloned()
+        .collect::<Vec<_>>();
+    for name in names {
+        headers.remove(name);
+    }
+}
+
+/// The `grpc-timeout` of a `connect-timeout-ms` timeout.
+pub(crate) fn grpc_timeout(timeout_ms: &HeaderValue) -> Option<HeaderValue> {
+    let ms = timeout_ms.to_str().ok()?.parse::<u64>().ok()?;
+
+    // gRPC timeouts have at most 8 digits.
+    let timeout = if ms < 100_000_000 {
+        format!("{ms}m")
+    } else {
+        format!("{}S", (ms / 1000).min(99_999_999))
+    };
+    HeaderValue::try_from(timeout).ok()
+}
+
+/// Decode the message of a unary GET request, the `message` query parameter.
+pub(crate) fn decode_query_message(value: &str, base64: bool) -> Result<Bytes, Status> {
+    let value = percent_decode(value)
+        .ok_or_else(|| Status::invalid_argument("invalid percent-encoding of message"))?;
+
+    if base64 {
+        crate::util::base64::URL_SAFE
+            .decode(value)
+            .map(Bytes::from)
+            .map_err(|e| Status::invalid_argument(format!("invalid base64 message: {e}")))
+    } else {
+        Ok(value.into())
+    }
+}
+
+// Decodes the percent-encoded `value` of a query parameter.
+fn percent_decode(value: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(value.len());
+    let mut bytes = value.bytes();
+
+    while let Some(b) = bytes.next() {
+        match b {
+            b'%' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            b'+' => decoded.push(b' '),
+            b => decoded.push(b),
+        }
+    }
+
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_details() {
+        let details = RpcStatus {
+            code: 3,
+            message: "bad".into(),
+            details: vec![Any {
+                type_url: "type.googleapis.com/google.rpc.BadRequest".into(),
+                value: Bytes::from_static(b"\x01\x02"),
+            }],
+        };
+        let status =
+            Status::with_details(Code::InvalidArgument, "bad", details.encode_to_vec().into());
+
+        assert_eq!(
+            error_json(&status),
+            json!({
+                "code": "invalid_argument",
+                "message": "bad",
+                "details": [{ "type": "google.rpc.BadRequest", "value": "AQI" }],
+            })
+        );
+    }
+
+    #[test]
+    fn error_round_trip() {
+        let error = json!({
+            "code": "already_exists",
+            "message": "duplicate",
+            "details": [{ "type": "google.rpc.ErrorInfo", "value": "AQI" }],
+        });
+
+        let status = status_from_error_json(&error).unwrap();
+        assert_eq!(status.code(), Code::AlreadyExists);
+        assert_eq!(status.message(), "duplicate");
+        assert_eq!(error_json(&status), error);
+
+        assert!(status_from_error_json(&json!({ "code": "bogus" })).is_none());
+    }
+
+    #[test]
+    fn end_stream_trailers() {
+        let end = json!({
+            "error": { "code": "unavailable", "message": "later" },
+            "metadata": { "x-value": ["a", "b"] },
+        });
+
+        let trailers = trailers_from_end_stream(&end).unwrap();
+        assert_eq!(trailers["grpc-status"], "14");
+        assert_eq!(trailers["grpc-message"], "later");
+        let values = trailers.get_all("x-value").iter().collect::<Vec<_>>();
+        assert_eq!(values, ["a", "b"]);
+
+        let trailers = trailers_from_end_stream(&json!({})).unwrap();
+        assert_eq!(trailers["grpc-status"], "0");
+    }
+
+    #[test]
+    fn end_stream() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert("grpc-status", HeaderValue::from_static("0"));
+        trailers.append("x-value", HeaderValue::from_static("a"));
+        trailers.append("x-value", HeaderValue::from_static("b"));
+
+        assert_eq!(
+            end_stream_json(&Status::ok(""), &trailers),
+            json!({ "metadata": { "x-value": ["a", "b"] } })
+        );
+        assert_eq!(
+            end_stream_json(&Status::not_found("missing"), &HeaderMap::new()),
+            json!({ "error": { "code": "not_found", "message": "missing" } })
+        );
+    }
+
+    #[test]
+    fn timeouts() {
+        let timeout = |ms| grpc_timeout(&HeaderValue::from_static(ms));
+
+        assert_eq!(timeout("1500").unwrap(), "1500m");
+        assert_eq!(timeout("123456789").unwrap(), "123456S");
+        assert!(timeout("soon").is_none());
+    }
+
+    #[test]
+    fn query_messages() {
+        assert_eq!(
+            decode_query_message("%7B%22id%22%3A+1%7D", false).unwrap(),
+            r#"{"id": 1}"#
+        );
+        assert_eq!(decode_query_message("CAE", true).unwrap(), &b"\x08\x01"[..]);
+        assert!(decode_query_message("%7", false).is_err());
+    }
+}
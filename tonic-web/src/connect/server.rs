@@ -0,0 +1,697 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::{header, HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri, Version};
+use http_body::Frame;
+use http_body_util::{BodyExt, Full};
+use pin_project::pin_project;
+use tonic::body::Body;
+use tonic::Status;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::{debug, trace};
+
+use super::{
+    decode_query_message, end_stream_json, error_json, grpc_timeout, http_status, remove_reserved,
+    status_from_headers, unary_headers, Codec, ACCEPT_ENCODING, CONTENT_ENCODING, END_STREAM_FLAG,
+    GRPC_ACCEPT_ENCODING, GRPC_ENCODING, GRPC_TIMEOUT, PROTOCOL_VERSION, TIMEOUT_MS,
+};
+use crate::BoxError;
+
+const GRPC_HEADER_SIZE: usize = 5;
+
+/// Layer accepting [Connect] requests alongside gRPC requests.
+///
+/// Connect unary and streaming requests are translated to gRPC requests for the inner service,
+/// and its responses back to Connect responses. All other requests are passed through, so the
+/// layer may be stacked with the [`GrpcWebLayer`] to serve gRPC, grpc-web and Connect clients
+/// on the same port:
+///
+/// ```ignore
+/// Server::builder()
+///    .accept_http1(true)
+///    .layer(ConnectLayer::new())
+///    .layer(GrpcWebLayer::new())
+///    .add_service(greeter)
+///    .serve(addr)
+///    .await?;
+/// ```
+///
+/// Unary POST requests are only recognized with a `connect-protocol-version` header, and unary
+/// GET requests with the `connect=v1` query parameter, as sent by the Connect clients.
+///
+/// [Connect]: https://connectrpc.com/docs/protocol
+/// [`GrpcWebLayer`]: crate::GrpcWebLayer
+#[derive(Debug, Default, Clone)]
+pub struct ConnectLayer {
+    _priv: (),
+}
+
+impl ConnectLayer {
+    /// Create a new Connect layer.
+    pub fn new() -> ConnectLayer {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for ConnectLayer {
+    type Service = ConnectService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ConnectService { inner }
+    }
+}
+
+/// Service translating [Connect] requests to gRPC requests.
+///
+/// [Connect]: https://connectrpc.com/docs/protocol
+#[derive(Debug, Clone)]
+pub struct ConnectService<S> {
+    inner: S,
+}
+
+#[derive(Debug, PartialEq)]
+enum RequestKind {
+    // A POST request with a `application/proto` or `application/json` content-type, and a
+    // `connect-protocol-version` header.
+    Unary(Codec),
+    // A GET request with a `connect=v1` query parameter.
+    UnaryGet,
+    // A POST request with a `application/connect+proto` or `application/connect+json`
+    // content-type.
+    Streaming(Codec),
+    // A Connect request with an unsupported codec.
+    Unsupported,
+    // All other requests.
+    Other,
+}
+
+impl RequestKind {
+    fn new<B>(req: &Request<B>) -> Self {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.split(';').next().unwrap_or_default().trim());
+
+        match (req.method(), content_type) {
+            (&Method::POST, Some(content_type)) => {
+                if let Some(codec) = content_type.strip_prefix("application/connect+") {
+                    return Codec::from_name(codec).map_or(Self::Unsupported, Self::Streaming);
+                }
+                if !req.headers().contains_key(PROTOCOL_VERSION) {
+                    return Self::Other;
+                }
+                content_type
+                    .strip_prefix("application/")
+                    .and_then(Codec::from_name)
+                    .map_or(Self::Unsupported, Self::Unary)
+            }
+            (&Method::GET, _) if query_param(req.uri(), "connect") == Some("v1") => Self::UnaryGet,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for ConnectService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    ReqBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ReqBody::Error: Into<BoxError>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let case = match RequestKind::new(&req) {
+            RequestKind::Unary(codec) => {
+                trace!(kind = "connect unary", path = ?req.uri().path(), ?codec);
+
+                let (mut parts, body) = req.into_parts();
+                let compressed = coerce_unary_headers(&mut parts.headers, codec);
+                let body = UnaryRequestBody::new(body, compressed, BytesMut::new());
+                Case::Unary {
+                    future: self.inner.call(coerce_request(parts, body)),
+                    codec,
+                }
+            }
+
+            RequestKind::UnaryGet => match get_request(req) {
+                Ok((req, codec)) => {
+                    trace!(kind = "connect get", path = ?req.uri().path(), ?codec);
+                    Case::Unary {
+                        future: self.inner.call(req),
+                        codec,
+                    }
+                }
+                Err(status) => {
+                    debug!(kind = "connect get", error = %status.message());
+                    Case::Immediate {
+                        res: Some(error_response(&status, HeaderMap::new())),
+                    }
+                }
+            },
+
+            RequestKind::Streaming(codec) => {
+                trace!(kind = "connect streaming", path = ?req.uri().path(), ?codec);
+
+                let (mut parts, body) = req.into_parts();
+                coerce_streaming_headers(&mut parts.headers, codec);
+                let body = body.map_err(|e| Status::from_error(e.into()));
+                Case::Streaming {
+                    future: self.inner.call(coerce_request(parts, body)),
+                    codec,
+                }
+            }
+
+            RequestKind::Unsupported => {
+                debug!(kind = "connect", error = "unsupported media type", content_type = ?req.headers().get(header::CONTENT_TYPE));
+                Case::Immediate {
+                    res: Some(immediate(StatusCode::UNSUPPORTED_MEDIA_TYPE)),
+                }
+            }
+
+            RequestKind::Other => Case::Other {
+                future: self.inner.call(req.map(Body::new)),
+            },
+        };
+
+        ResponseFuture { case }
+    }
+}
+
+/// Response future for the [`ConnectService`].
+#[pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture<F> {
+    #[pin]
+    case: Case<F>,
+}
+
+#[pin_project(project = CaseProj)]
+enum Case<F> {
+    Unary {
+        #[pin]
+        future: F,
+        codec: Codec,
+    },
+    // The response of a unary call, buffered to learn its status.
+    Buffering {
+        future: Pin<Box<dyn Future<Output = Response<Body>> + Send>>,
+    },
+    Streaming {
+        #[pin]
+        future: F,
+        codec: Codec,
+    },
+    Other {
+        #[pin]
+        future: F,
+    },
+    Immediate {
+        res: Option<Response<Body>>,
+    },
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            match self.as_mut().project().case.project() {
+                CaseProj::Unary { future, codec } => {
+                    let res = ready!(future.poll(cx))?;
+                    let future = Box::pin(unary_response(res, *codec));
+                    self.as_mut().project().case.set(Case::Buffering { future });
+                }
+                CaseProj::Buffering { future } => return future.as_mut().poll(cx).map(Ok),
+                CaseProj::Streaming { future, codec } => {
+                    let res = ready!(future.poll(cx))?;
+                    return Poll::Ready(Ok(streaming_response(res, *codec)));
+                }
+                CaseProj::Other { future } => {
+                    let res = ready!(future.poll(cx))?;
+                    return Poll::Ready(Ok(res.map(Body::new)));
+                }
+                CaseProj::Immediate { res } => {
+                    return Poll::Ready(Ok(res.take().expect("polled after completion")));
+                }
+            }
+        }
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+fn immediate(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn query_param<'a>(uri: &'a Uri, name: &str) -> Option<&'a str> {
+    uri.query()?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn coerce_request<B>(mut parts: http::request::Parts, body: B) -> Request<Body>
+where
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    parts.method = Method::POST;
+    parts.version = Version::HTTP_2;
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.remove(PROTOCOL_VERSION);
+    parts
+        .headers
+        .insert(header::TE, HeaderValue::from_static("trailers"));
+
+    if let Some(timeout) = parts.headers.remove(TIMEOUT_MS) {
+        if let Some(timeout) = grpc_timeout(&timeout) {
+            parts.headers.insert(GRPC_TIMEOUT, timeout);
+        }
+    }
+
+    Request::from_parts(parts, Body::new(body))
+}
+
+// Returns whether the message of the request is compressed.
+fn coerce_unary_headers(headers: &mut HeaderMap, codec: Codec) -> bool {
+    headers.insert(header::CONTENT_TYPE, codec.grpc_content_type());
+
+    // Unary calls compress messages with the HTTP `content-encoding`.
+    let compressed = match headers.remove(header::CONTENT_ENCODING) {
+        Some(encoding) if encoding != "identity" => {
+            headers.insert(GRPC_ENCODING, encoding);
+            true
+        }
+        _ => false,
+    };
+    if let Some(accept) = headers.remove(header::ACCEPT_ENCODING) {
+        headers.insert(GRPC_ACCEPT_ENCODING, accept);
+    }
+
+    compressed
+}
+
+fn coerce_streaming_headers(headers: &mut HeaderMap, codec: Codec) {
+    headers.insert(header::CONTENT_TYPE, codec.grpc_content_type());
+
+    if let Some(encoding) = headers.remove(CONTENT_ENCODING) {
+        headers.insert(GRPC_ENCODING, encoding);
+    }
+    if let Some(accept) = headers.remove(ACCEPT_ENCODING) {
+        headers.insert(GRPC_ACCEPT_ENCODING, accept);
+    }
+}
+
+// Translates a unary GET request, carrying its message in its query parameters.
+fn get_request<B>(req: Request<B>) -> Result<(Request<Body>, Codec), Status> {
+    let uri = req.uri();
+    let codec = query_param(uri, "encoding")
+        .and_then(Codec::from_name)
+        .ok_or_else(|| Status::invalid_argument("missing or unsupported encoding"))?;
+    let base64 = query_param(uri, "base64") == Some("1");
+    let message = decode_query_message(query_param(uri, "message").unwrap_or_default(), base64)?;
+    let compression = query_param(uri, "compression")
+        .filter(|compression| *compression != "identity")
+        .map(HeaderValue::from_str)
+        .transpose()
+        .map_err(|_| Status::invalid_argument("invalid compression"))?;
+
+    let (mut parts, _) = req.into_parts();
+    parts.uri = Uri::builder()
+        .path_and_query(parts.uri.path())
+        .build()
+        .map_err(|_| Status::invalid_argument("invalid path"))?;
+
+    coerce_unary_headers(&mut parts.headers, codec);
+    let compressed = compression.is_some();
+    if let Some(compression) = compression {
+        parts.headers.insert(GRPC_ENCODING, compression);
+    }
+
+    let body = UnaryRequestBody::new(Body::empty(), compressed, message[..].into());
+    Ok((coerce_request(parts, body), codec))
+}
+
+// Buffers the response of a unary call, to translate it to a bare message, or to an error and
+// its HTTP status.
+async fn unary_response<B>(res: Response<B>, codec: Codec) -> Response<Body>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    let (mut parts, body) = res.into_parts();
+
+    // A trailers-only response.
+    if let Some(status) = status_from_headers(&parts.headers) {
+        if status.code() != tonic::Code::Ok {
+            return error_response(&status, unary_headers(parts.headers, &HeaderMap::new()));
+        }
+    }
+
+    let collected = match body.collect().await {
+        Ok(collected) => collected,
+        Err(e) => {
+            let status = Status::from_error(e.into());
+            return error_response(&status, HeaderMap::new());
+        }
+    };
+    let trailers = collected.trailers().cloned().unwrap_or_default();
+    let mut message = collected.to_bytes();
+
+    let status = status_from_headers(&trailers)
+        .or_else(|| status_from_headers(&parts.headers))
+        .unwrap_or_else(|| Status::internal("missing grpc-status"));
+    let encoding = parts.headers.remove(GRPC_ENCODING);
+    let headers = unary_headers(parts.headers, &trailers);
+    if status.code() != tonic::Code::Ok {
+        return error_response(&status, headers);
+    }
+
+    if message.len() < GRPC_HEADER_SIZE {
+        return error_response(&Status::internal("missing response message"), headers);
+    }
+    let compressed = message.get_u8() != 0;
+    let len = message.get_u32() as usize;
+    if message.len() != len {
+        return error_response(
+            &Status::internal("expected a single response message"),
+            headers,
+        );
+    }
+
+    let mut res = Response::new(Body::new(Full::new(message)));
+    *res.headers_mut() = headers;
+    res.headers_mut()
+        .insert(header::CONTENT_TYPE, codec.unary_content_type());
+    if compressed {
+        match encoding {
+            Some(encoding) => {
+                res.headers_mut().insert(header::CONTENT_ENCODING, encoding);
+            }
+            None => {
+                return error_response(
+                    &Status::internal("compressed message without grpc-encoding"),
+                    HeaderMap::new(),
+                )
+            }
+        }
+    }
+    res
+}
+
+// The Connect error response of unary calls.
+fn error_response(status: &Status, headers: HeaderMap) -> Response<Body> {
+    let body = serde_json::to_vec(&error_json(status)).expect("JSON values are serializable");
+
+    let mut res = Response::new(Body::new(Full::new(Bytes::from(body))));
+    *res.status_mut() = http_status(status.code());
+    *res.headers_mut() = headers;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+fn streaming_response<B>(res: Response<B>, codec: Codec) -> Response<Body>
+where
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let (mut parts, body) = res.into_parts();
+    let encoding = parts.headers.remove(GRPC_ENCODING);
+
+    // The status and metadata of trailers-only responses go to the end-of-stream message.
+    let trailers_only = status_from_headers(&parts.headers).map(|status| {
+        let mut headers = std::mem::take(&mut parts.headers);
+        remove_reserved(&mut headers);
+        (status, headers)
+    });
+
+    remove_reserved(&mut parts.headers);
+    parts
+        .headers
+        .insert(header::CONTENT_TYPE, codec.streaming_content_type());
+    if let Some(encoding) = encoding {
+        parts.headers.insert(CONTENT_ENCODING, encoding);
+    }
+    parts.status = StatusCode::OK;
+
+    let body = StreamingResponseBody {
+        inner: body,
+        trailers_only,
+        done: false,
+    };
+    Response::from_parts(parts, Body::new(body))
+}
+
+fn envelope(flags: u8, message: &[u8]) -> Bytes {
+    let mut buf = BytesMut::with_capacity(GRPC_HEADER_SIZE + message.len());
+    buf.put_u8(flags);
+    buf.put_u32(message.len() as u32);
+    buf.put_slice(message);
+    buf.freeze()
+}
+
+fn end_stream(status: &Status, trailers: &HeaderMap) -> Bytes {
+    let end = serde_json::to_vec(&end_stream_json(status, trailers))
+        .expect("JSON values are serializable");
+    envelope(END_STREAM_FLAG, &end)
+}
+
+// The body of a unary request, enveloping the message of the Connect request as a single gRPC
+// message.
+#[pin_project]
+struct UnaryRequestBody<B> {
+    #[pin]
+    inner: B,
+    compressed: bool,
+    message: BytesMut,
+    done: bool,
+}
+
+impl<B> UnaryRequestBody<B> {
+    fn new(inner: B, compressed: bool, message: BytesMut) -> Self {
+        Self {
+            inner,
+            compressed,
+            message,
+            done: false,
+        }
+    }
+}
+
+impl<B> http_body::Body for UnaryRequestBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            match ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        this.message.put(data);
+                    }
+                }
+                Some(Err(e)) => {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(Status::from_error(e.into()))));
+                }
+                None => {
+                    *this.done = true;
+                    let message = envelope(u8::from(*this.compressed), this.message);
+                    return Poll::Ready(Some(Ok(Frame::data(message))));
+                }
+            }
+        }
+    }
+}
+
+// The body of a streaming response, ending with an end-of-stream message instead of trailers.
+#[pin_project]
+struct StreamingResponseBody<B> {
+    #[pin]
+    inner: B,
+    trailers_only: Option<(Status, HeaderMap)>,
+    done: bool,
+}
+
+impl<B> http_body::Body for StreamingResponseBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            let end = match ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => match frame.into_data() {
+                    Ok(data) => return Poll::Ready(Some(Ok(Frame::data(data)))),
+                    Err(frame) => match frame.into_trailers() {
+                        Ok(trailers) => {
+                            let status = status_from_headers(&trailers)
+                                .unwrap_or_else(|| Status::internal("missing grpc-status"));
+                            end_stream(&status, &trailers)
+                        }
+                        Err(_) => continue,
+                    },
+                },
+                Some(Err(e)) => end_stream(&Status::from_error(e.into()), &HeaderMap::new()),
+                None => match this.trailers_only.take() {
+                    Some((status, headers)) => end_stream(&status, &headers),
+                    None => end_stream(&Status::internal("missing trailers"), &HeaderMap::new()),
+                },
+            };
+
+            *this.done = true;
+            return Poll::Ready(Some(Ok(Frame::data(end))));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Method, uri: &str, headers: &[(&'static str, &'static str)]) -> Request<()> {
+        let mut req = Request::builder().method(method).uri(uri);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        req.body(()).unwrap()
+    }
+
+    #[test]
+    fn request_kinds() {
+        let kind = |method, uri, headers| RequestKind::new(&request(method, uri, headers));
+
+        assert_eq!(
+            kind(
+                Method::POST,
+                "/a.B/C",
+                &[
+                    ("content-type", "application/json; charset=utf-8"),
+                    ("connect-protocol-version", "1"),
+                ],
+            ),
+            RequestKind::Unary(Codec::Json)
+        );
+        // Plain JSON requests are not Connect requests.
+        assert_eq!(
+            kind(
+                Method::POST,
+                "/a.B/C",
+                &[("content-type", "application/json")]
+            ),
+            RequestKind::Other
+        );
+        assert_eq!(
+            kind(
+                Method::POST,
+                "/a.B/C",
+                &[("content-type", "application/connect+proto")],
+            ),
+            RequestKind::Streaming(Codec::Proto)
+        );
+        assert_eq!(
+            kind(
+                Method::POST,
+                "/a.B/C",
+                &[("content-type", "application/connect+thrift")],
+            ),
+            RequestKind::Unsupported
+        );
+        assert_eq!(
+            kind(Method::GET, "/a.B/C?connect=v1&encoding=proto", &[]),
+            RequestKind::UnaryGet
+        );
+        assert_eq!(
+            kind(
+                Method::POST,
+                "/a.B/C",
+                &[("content-type", "application/grpc")]
+            ),
+            RequestKind::Other
+        );
+    }
+
+    #[tokio::test]
+    async fn unary_request_body() {
+        let body = UnaryRequestBody::new(
+            Full::new(Bytes::from_static(b"message")),
+            false,
+            BytesMut::new(),
+        );
+
+        let body = body.collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], b"\x00\x00\x00\x00\x07message");
+    }
+
+    #[tokio::test]
+    async fn unary_errors() {
+        let mut headers = HeaderMap::new();
+        headers.insert("grpc-status", HeaderValue::from_static("5"));
+        headers.insert("grpc-message", HeaderValue::from_static("missing"));
+        headers.insert("x-meta", HeaderValue::from_static("value"));
+        let mut res = Response::new(Body::empty());
+        *res.headers_mut() = headers;
+
+        let res = unary_response(res, Codec::Proto).await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.headers()["content-type"], "application/json");
+        assert_eq!(res.headers()["x-meta"], "value");
+        assert!(!res.headers().contains_key("grpc-status"));
+
+        let body = res.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], br#"{"code":"not_found","message":"missing"}"#);
+    }
+}
@@ -0,0 +1,245 @@
+//! A grpc-web channel for browsers, over the fetch API.
+
+use bytes::Bytes;
+use http::{header::TE, Request, Response};
+use http_body::Frame;
+use http_body_util::BodyExt;
+use js_sys::{Array, Reflect, Uint8Array};
+use pin_project::pin_project;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::sync::{mpsc, oneshot};
+use tonic::body::Body;
+use tower_service::Service;
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsValue};
+use wasm_bindgen_futures::{spawn_local, JsFuture};
+
+use crate::call::GrpcWebCall;
+use crate::client::client_request;
+
+#[wasm_bindgen]
+extern "C" {
+    // The global `fetch`, of both windows and workers.
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_with_request(request: &web_sys::Request) -> js_sys::Promise;
+}
+
+/// A channel speaking the grpc-web protocol through the `fetch` API of browsers, for clients
+/// compiled to `wasm32-unknown-unknown`.
+///
+/// The calls run on the browser's event loop, so the channel needs no runtime:
+///
+/// ```ignore
+/// let channel = FetchChannel::new("https://example.com");
+/// let mut client = GreeterClient::new(channel);
+/// let reply = client.say_hello(HelloRequest::default()).await?;
+/// ```
+///
+/// Like the other browser clients, only `unary` and `server-streaming` calls are supported, as
+/// `fetch` sends the bodies of requests once complete. The responses are streamed as they are
+/// received, and stop being read once dropped.
+///
+/// Browsers only expose the headers of cross-origin responses listed by their
+/// `access-control-expose-headers`, so cross-origin servers must expose `grpc-status` and
+/// `grpc-message`, like the default CORS configuration of [`GrpcWebLayer`] does, for the statuses
+/// of trailers-only responses to be read.
+///
+/// [`GrpcWebLayer`]: crate::GrpcWebLayer
+#[derive(Debug, Clone)]
+pub struct FetchChannel {
+    base_url: String,
+}
+
+impl FetchChannel {
+    /// Create a new `FetchChannel` sending the requests to `base_url`, e.g.
+    /// `https://example.com`, prefixed to the paths of the methods.
+    ///
+    /// An empty `base_url` sends the requests to the origin of the page.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let mut base_url = base_url.into();
+        if base_url.ends_with('/') {
+            base_url.pop();
+        }
+        Self { base_url }
+    }
+}
+
+impl Service<Request<Body>> for FetchChannel {
+    type Response = Response<Body>;
+    type Error = FetchError;
+    type Future = ResponseFuture;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let path = req.uri().path_and_query().map_or("/", |path| path.as_str());
+        let url = format!("{}{path}", self.base_url);
+        let req = client_request(req);
+
+        // The JavaScript values of the call aren't `Send`, so the call runs on its own task,
+        // handing back its response.
+        let (tx, rx) = oneshot::channel();
+        spawn_local(async move {
+            let _ = tx.send(fetch(url, req).await);
+        });
+
+        ResponseFuture { inner: rx }
+    }
+}
+
+async fn fetch(url: String, req: Request<GrpcWebCall<Body>>) -> Result<Response<Body>, FetchError> {
+    let (parts, body) = req.into_parts();
+    let body = body
+        .collect()
+        .await
+        .map_err(|status| FetchError::new(status.to_string()))?
+        .to_bytes();
+
+    let headers = web_sys::Headers::new().map_err(FetchError::js)?;
+    for (name, value) in &parts.headers {
+        // Browsers negotiate the transfer of responses themselves.
+        if name == TE {
+            continue;
+        }
+        let value = value
+            .to_str()
+            .map_err(|_| FetchError::new(format!("invalid value of header {name}")))?;
+        headers
+            .append(name.as_str(), value)
+            .map_err(FetchError::js)?;
+    }
+
+    let init = web_sys::RequestInit::new();
+    init.set_method(parts.method.as_str());
+    init.set_headers(&headers);
+    init.set_body(&Uint8Array::from(body.as_ref()));
+    let request = web_sys::Request::new_with_str_and_init(&url, &init).map_err(FetchError::js)?;
+    let response: web_sys::Response = JsFuture::from(fetch_with_request(&request))
+        .await
+        .map_err(FetchError::js)?
+        .unchecked_into();
+
+    let mut res = Response::builder().status(response.status());
+    let entries = js_sys::try_iter(&response.headers())
+        .map_err(FetchError::js)?
+        .ok_or_else(|| FetchError::new("response headers aren't iterable"))?;
+    for entry in entries {
+        let entry: Array = entry.map_err(FetchError::js)?.unchecked_into();
+        if let (Some(name), Some(value)) = (entry.get(0).as_string(), entry.get(1).as_string()) {
+            res = res.header(name, value);
+        }
+    }
+
+    let (tx, rx) = mpsc::channel(1);
+    if let Some(stream) = response.body() {
+        let reader: web_sys::ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+        spawn_local(read_body(reader, tx));
+    }
+    res.body(Body::new(GrpcWebCall::client_response(FetchBody { rx })))
+        .map_err(|e| FetchError::new(e.to_string()))
+}
+
+// Forwards the chunks of the body of a response, until its end or until it's dropped.
+async fn read_body(
+    reader: web_sys::ReadableStreamDefaultReader,
+    tx: mpsc::Sender<Result<Bytes, FetchError>>,
+) {
+    loop {
+        let chunk = match JsFuture::from(reader.read()).await {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let _ = tx.send(Err(FetchError::js(e))).await;
+                return;
+            }
+        };
+        let done =
+            Reflect::get(&chunk, &JsValue::from_str("done")).map_or(true, |done| done.is_truthy());
+        if done {
+            return;
+        }
+        let value = Reflect::get(&chunk, &JsValue::from_str("value")).unwrap_or(JsValue::UNDEFINED);
+        let chunk = Bytes::from(Uint8Array::new(&value).to_vec());
+        if tx.send(Ok(chunk)).await.is_err() {
+            let _ = reader.cancel();
+            return;
+        }
+    }
+}
+
+// The body of a response, received from its reader.
+struct FetchBody {
+    rx: mpsc::Receiver<Result<Bytes, FetchError>>,
+}
+
+impl http_body::Body for FetchBody {
+    type Data = Bytes;
+    type Error = FetchError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.rx
+            .poll_recv(cx)
+            .map(|chunk| chunk.map(|chunk| chunk.map(Frame::data)))
+    }
+}
+
+/// Response future for the [`FetchChannel`].
+#[pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture {
+    #[pin]
+    inner: oneshot::Receiver<Result<Response<Body>, FetchError>>,
+}
+
+impl Future for ResponseFuture {
+    type Output = Result<Response<Body>, FetchError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match ready!(self.project().inner.poll(cx)) {
+            Ok(res) => Poll::Ready(res),
+            Err(_) => Poll::Ready(Err(FetchError::new("fetch task dropped"))),
+        }
+    }
+}
+
+impl fmt::Debug for ResponseFuture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+/// Error of the calls of a [`FetchChannel`], e.g. failing to reach the server.
+#[derive(Debug)]
+pub struct FetchError {
+    message: String,
+}
+
+impl FetchError {
+    fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    fn js(value: JsValue) -> Self {
+        let message = match value.dyn_ref::<js_sys::Error>() {
+            Some(error) => String::from(error.message()),
+            None => value.as_string().unwrap_or_else(|| format!("{value:?}")),
+        };
+        Self::new(message)
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fetch failed: {}", self.message)
+    }
+}
+
+impl std::error::Error for FetchError {}
@@ -52,6 +52,11 @@
 //! }
 //! ```
 //!
+//! ## Browser clients
+//!
+//! With the `wasm` feature, the generated clients compiled to `wasm32-unknown-unknown` run in
+//! browsers over a `FetchChannel`, calling the servers with grpc-web through the `fetch` API.
+//!
 //! ## Limitations
 //!
 //! * `tonic_web` is designed to work with grpc-web-compliant clients only. It is not expected to
@@ -61,7 +66,8 @@
 //! * Currently, grpc-web clients can only perform `unary` and `server-streaming` calls. These
 //!   are the only requests this crate is designed to handle. Support for client and bi-directional
 //!   streaming will be officially supported when clients do.
-//! * There is no support for web socket transports.
+//! * The only web socket transport supported is the `grpc-websockets` protocol of the
+//!   improbable-eng clients, with the `GrpcWebSocketLayer` of the `websocket` feature.
 //!
 //!
 //! [`tonic`]: https://github.com/hyperium/tonic
@@ -71,14 +77,34 @@
 #![doc(issue_tracker_base_url = "https://github.com/hyperium/tonic/issues/")]
 
 pub use call::GrpcWebCall;
+#[cfg(feature = "channel")]
+pub use channel::{FallbackChannel, GrpcWebChannel};
 pub use client::{GrpcWebClientLayer, GrpcWebClientService};
+#[cfg(feature = "connect")]
+pub use connect::{ConnectClientLayer, ConnectClientService, ConnectLayer, ConnectService};
+#[cfg(feature = "wasm")]
+pub use fetch::FetchChannel;
 pub use layer::GrpcWebLayer;
 pub use service::{GrpcWebService, ResponseFuture};
+#[cfg(feature = "transcoding")]
+pub use transcoding::{TranscodingLayer, TranscodingService};
+#[cfg(feature = "websocket")]
+pub use websocket::{GrpcWebSocketLayer, GrpcWebSocketService};
 
 mod call;
+#[cfg(feature = "channel")]
+pub mod channel;
 mod client;
+#[cfg(feature = "connect")]
+pub mod connect;
+#[cfg(feature = "wasm")]
+pub mod fetch;
 mod layer;
 mod service;
+#[cfg(feature = "transcoding")]
+pub mod transcoding;
+#[cfg(feature = "websocket")]
+pub mod websocket;
 
 type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
@@ -98,5 +124,69 @@ pub(crate) mod util {
                 .with_encode_padding(true)
                 .with_decode_padding_mode(DecodePaddingMode::Indifferent),
         );
+
+        #[cfg(feature = "connect")]
+        pub(crate) const STANDARD_NO_PAD: GeneralPurpose = GeneralPurpose::new(
+            &alphabet::STANDARD,
+            GeneralPurposeConfig::new()
+                .with_encode_padding(false)
+                .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        );
+
+        #[cfg(any(feature = "connect", feature = "transcoding"))]
+        pub(crate) const URL_SAFE: GeneralPurpose = GeneralPurpose::new(
+            &alphabet::URL_SAFE,
+            GeneralPurposeConfig::new()
+                .with_encode_padding(false)
+                .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+        );
+    }
+
+    #[cfg(any(feature = "connect", feature = "transcoding"))]
+    pub(crate) mod status {
+        use base64::Engine as _;
+        use http::{HeaderMap, HeaderName, StatusCode};
+        use tonic::{Code, Status};
+
+        use super::base64::STANDARD;
+
+        const GRPC_STATUS_DETAILS: HeaderName = HeaderName::from_static("grpc-status-details-bin");
+
+        /// The HTTP status of responses failing with `code`.
+        pub(crate) fn http_status(code: Code) -> StatusCode {
+            match code {
+                Code::Ok => StatusCode::OK,
+                Code::Cancelled => StatusCode::from_u16(499).unwrap(),
+                Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => {
+                    StatusCode::BAD_REQUEST
+                }
+                Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+                Code::NotFound => StatusCode::NOT_FOUND,
+                Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+                Code::PermissionDenied => StatusCode::FORBIDDEN,
+                Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+                Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+                Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+                Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+                Code::Unknown | Code::Internal | Code::DataLoss => {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                }
+            }
+        }
+
+        /// The status of a gRPC response, from its `headers` or trailers.
+        ///
+        /// Unlike [`Status::from_header_map`], invalid `grpc-status-details-bin` headers are dropped
+        /// rather than panicking.
+        pub(crate) fn status_from_headers(headers: &HeaderMap) -> Option<Status> {
+            let details = headers.get(GRPC_STATUS_DETAILS);
+            if details.is_some_and(|details| STANDARD.decode(details.as_bytes()).is_err()) {
+                let mut headers = headers.clone();
+                headers.remove(GRPC_STATUS_DETAILS);
+                return Status::from_header_map(&headers);
+            }
+
+            Status::from_header_map(headers)
+        }
     }
 }
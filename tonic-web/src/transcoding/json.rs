@@ -0,0 +1,751 @@
+//! The [JSON mapping] of protobuf messages, over their descriptors.
+//!
+//! [JSON mapping]: https://protobuf.dev/programming-guides/json/
+
+use std::collections::HashMap;
+
+use base64::Engine as _;
+use prost::Message as _;
+use prost_reflect::{
+    DynamicMessage, FieldDescriptor, Kind, MapKey, MessageDescriptor, ReflectMessage,
+    Value as ProtoValue,
+};
+use serde_json::{Map, Number, Value};
+
+use crate::util::base64::{STANDARD, URL_SAFE};
+
+// The range of `google.protobuf.Timestamp`, from 0001-01-01T00:00:00Z to 9999-12-31T23:59:59Z.
+const MIN_TIMESTAMP: i64 = -62_135_596_800;
+const MAX_TIMESTAMP: i64 = 253_402_300_799;
+// The range of `google.protobuf.Duration`, about 10,000 years.
+const MAX_DURATION: i64 = 315_576_000_000;
+
+/// Convert `message` to JSON.
+pub(crate) fn to_json(message: &DynamicMessage) -> Result<Value, String> {
+    if let Some(value) = well_known_to_json(message)? {
+        return Ok(value);
+    }
+
+    let mut object = Map::new();
+    for (field, value) in message.fields() {
+        object.insert(field.json_name().to_owned(), field_to_json(&field, value)?);
+    }
+    Ok(Value::Object(object))
+}
+
+/// Convert the `value` of `field` to JSON.
+pub(crate) fn field_to_json(field: &FieldDescriptor, value: &ProtoValue) -> Result<Value, String> {
+    match value {
+        ProtoValue::List(values) => values
+            .iter()
+            .map(|value| value_to_json(&field.kind(), value))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        ProtoValue::Map(entries) => {
+            let kind = match field.kind() {
+                Kind::Message(entry) => entry.map_entry_value_field().kind(),
+                kind => kind,
+            };
+            let mut object = Map::new();
+            for (key, value) in entries {
+                object.insert(map_key_to_string(key), value_to_json(&kind, value)?);
+            }
+            Ok(Value::Object(object))
+        }
+        value => value_to_json(&field.kind(), value),
+    }
+}
+
+fn value_to_json(kind: &Kind, value: &ProtoValue) -> Result<Value, String> {
+    let value = match value {
+        ProtoValue::Bool(value) => Value::Bool(*value),
+        ProtoValue::I32(value) => Value::from(*value),
+        ProtoValue::U32(value) => Value::from(*value),
+        // 64-bit integers are strings, as JavaScript numbers cannot represent all of them.
+        ProtoValue::I64(value) => Value::String(value.to_string()),
+        ProtoValue::U64(value) => Value::String(value.to_string()),
+        // Go through the shortest representation of `f32` values, to keep 0.1 as 0.1.
+        ProtoValue::F32(value) => float_to_json(value.to_string().parse().unwrap_or(f64::NAN)),
+        ProtoValue::F64(value) => float_to_json(*value),
+        ProtoValue::String(value) => Value::String(value.clone()),
+        ProtoValue::Bytes(value) => Value::String(STANDARD.encode(value)),
+        ProtoValue::EnumNumber(number) => match kind {
+            Kind::Enum(desc) if desc.full_name() == "google.protobuf.NullValue" => Value::Null,
+            Kind::Enum(desc) => desc
+                .get_value(*number)
+                .map_or_else(|| Value::from(*number), |value| value.name().into()),
+            _ => Value::from(*number),
+        },
+        ProtoValue::Message(message) => to_json(message)?,
+        ProtoValue::List(_) | ProtoValue::Map(_) => {
+            return Err("unexpected nested repeated value".into())
+        }
+    };
+    Ok(value)
+}
+
+fn float_to_json(value: f64) -> Value {
+    if value.is_nan() {
+        "NaN".into()
+    } else if value == f64::INFINITY {
+        "Infinity".into()
+    } else if value == f64::NEG_INFINITY {
+        "-Infinity".into()
+    } else {
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+fn map_key_to_string(key: &MapKey) -> String {
+    match key {
+        MapKey::Bool(key) => key.to_string(),
+        MapKey::I32(key) => key.to_string(),
+        MapKey::I64(key) => key.to_string(),
+        MapKey::U32(key) => key.to_string(),
+        MapKey::U64(key) => key.to_string(),
+        MapKey::String(key) => key.clone(),
+    }
+}
+
+// The well-known types with a special JSON representation.
+fn well_known_to_json(message: &DynamicMessage) -> Result<Option<Value>, String> {
+    let desc = message.descriptor();
+    let field = |number| message.get_field_by_number(number);
+
+    let value = match desc.full_name() {
+        "google.protobuf.Timestamp" => {
+            let seconds = field(1).and_then(|v| v.as_i64()).unwrap_or_default();
+            let nanos = field(2).and_then(|v| v.as_i32()).unwrap_or_default();
+            format_timestamp(seconds, nanos)?.into()
+        }
+        "google.protobuf.Duration" => {
+            let seconds = field(1).and_then(|v| v.as_i64()).unwrap_or_default();
+            let nanos = field(2).and_then(|v| v.as_i32()).unwrap_or_default();
+            format_duration(seconds, nanos)?.into()
+        }
+        _ if is_wrapper(&desc) => {
+            let value = desc.get_field(1).ok_or("missing wrapper value")?;
+            value_to_json(&value.kind(), &message.get_field(&value))?
+        }
+        "google.protobuf.Struct" => {
+            let fields = desc.get_field(1).ok_or("missing struct fields")?;
+            field_to_json(&fields, &message.get_field(&fields))?
+        }
+        "google.protobuf.ListValue" => {
+            let values = desc.get_field(1).ok_or("missing list values")?;
+            field_to_json(&values, &message.get_field(&values))?
+        }
+        "google.protobuf.Value" => match message.fields().next() {
+            Some((field, value)) => field_to_json(&field, value)?,
+            None => Value::Null,
+        },
+        "google.protobuf.FieldMask" => {
+            let paths = field(1).map_or_else(Vec::new, |paths| {
+                paths
+                    .as_list()
+                    .unwrap_or_default()
+                    .iter()
+                    .filter_map(|path| path.as_str())
+                    .map(snake_to_camel)
+                    .collect()
+            });
+            paths.join(",").into()
+        }
+        "google.protobuf.Any" => {
+            let type_url = field(1)
+                .and_then(|v| v.as_str().map(str::to_owned))
+                .unwrap_or_default();
+            let value = field(2)
+                .and_then(|v| v.as_bytes().cloned())
+                .unwrap_or_default();
+            let desc = any_type(&desc, &type_url)?;
+            let message = DynamicMessage::decode(desc, value)
+                .map_err(|e| format!("invalid Any of type '{type_url}': {e}"))?;
+
+            let mut object = Map::new();
+            object.insert("@type".into(), type_url.into());
+            match well_known_to_json(&message)? {
+                Some(value) => {
+                    object.insert("value".into(), value);
+                }
+                None => {
+                    if let Value::Object(fields) = to_json(&message)? {
+                        object.extend(fields);
+                    }
+                }
+            }
+            Value::Object(object)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+/// Convert `value` to a message of type `desc`.
+pub(crate) fn from_json(desc: MessageDescriptor, value: &Value) -> Result<DynamicMessage, String> {
+    let mut message = DynamicMessage::new(desc.clone());
+    if well_known_from_json(&mut message, value)? {
+        return Ok(message);
+    }
+
+    let Value::Object(object) = value else {
+        return Err(format!("expected an object for '{}'", desc.full_name()));
+    };
+    for (name, value) in object {
+        let field = desc
+            .get_field_by_json_name(name)
+            .or_else(|| desc.get_field_by_name(name))
+            .ok_or_else(|| format!("unknown field '{name}' of '{}'", desc.full_name()))?;
+        // Null values are the default values of fields, except for `google.protobuf.Value`.
+        if value.is_null() && !accepts_null(&field.kind()) {
+            continue;
+        }
+        let value = field_from_json(&field, value)
+            .map_err(|e| format!("invalid field '{name}' of '{}': {e}", desc.full_name()))?;
+        message.set_field(&field, value);
+    }
+    Ok(message)
+}
+
+/// Convert `value` to a value of `field`.
+pub(crate) fn field_from_json(
+    field: &FieldDescriptor,
+    value: &Value,
+) -> Result<ProtoValue, String> {
+    if field.is_map() {
+        let Kind::Message(entry) = field.kind() else {
+            return Err("invalid map field".into());
+        };
+        let Value::Object(object) = value else {
+            return Err("expected an object".into());
+        };
+        let key_kind = entry.map_entry_key_field().kind();
+        let value_kind = entry.map_entry_value_field().kind();
+
+        let mut entries = HashMap::with_capacity(object.len());
+        for (key, value) in object {
+            let key = value_from_str(&key_kind, key)?
+                .into_map_key()
+                .ok_or("invalid map key")?;
+            entries.insert(key, value_from_json(&value_kind, value)?);
+        }
+        Ok(ProtoValue::Map(entries))
+    } else if field.is_list() {
+        let Value::Array(values) = value else {
+            return Err("expected an array".into());
+        };
+        values
+            .iter()
+            .map(|value| value_from_json(&field.kind(), value))
+            .collect::<Result<_, _>>()
+            .map(ProtoValue::List)
+    } else {
+        value_from_json(&field.kind(), value)
+    }
+}
+
+/// Convert `value`, a path or query parameter, to a value of kind `kind`.
+///
+/// Parameters hold the JSON representation of values, without quotes for strings.
+pub(crate) fn value_from_str(kind: &Kind, value: &str) -> Result<ProtoValue, String> {
+    match kind {
+        Kind::Bool => match value {
+            "true" => Ok(ProtoValue::Bool(true)),
+            "false" => Ok(ProtoValue::Bool(false)),
+            _ => Err(format!("invalid bool '{value}'")),
+        },
+        Kind::Message(desc) if is_wrapper(desc) => {
+            let field = desc.get_field(1).ok_or("missing wrapper value")?;
+            let mut message = DynamicMessage::new(desc.clone());
+            message.set_field(&field, value_from_str(&field.kind(), value)?);
+            Ok(ProtoValue::Message(message))
+        }
+        _ => value_from_json(kind, &Value::String(value.to_owned())),
+    }
+}
+
+fn value_from_json(kind: &Kind, value: &Value) -> Result<ProtoValue, String> {
+    let value = match kind {
+        Kind::Double => ProtoValue::F64(float_from_json(value)?),
+        Kind::Float => ProtoValue::F32(float_from_json(value)? as f32),
+        Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => ProtoValue::I32(integer(value)?),
+        Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => ProtoValue::I64(integer(value)?),
+        Kind::Uint32 | Kind::Fixed32 => ProtoValue::U32(integer(value)?),
+        Kind::Uint64 | Kind::Fixed64 => ProtoValue::U64(integer(value)?),
+        Kind::Bool => ProtoValue::Bool(value.as_bool().ok_or("expected a bool")?),
+        Kind::String => ProtoValue::String(value.as_str().ok_or("expected a string")?.to_owned()),
+        Kind::Bytes => {
+            let value = value.as_str().ok_or("expected a base64 string")?;
+            let bytes = STANDARD
+                .decode(value)
+                .or_else(|_| URL_SAFE.decode(value))
+                .map_err(|e| format!("invalid base64: {e}"))?;
+            ProtoValue::Bytes(bytes.into())
+        }
+        Kind::Enum(desc) => match value {
+            Value::Null if desc.full_name() == "google.protobuf.NullValue" => {
+                ProtoValue::EnumNumber(0)
+            }
+            Value::String(name) => desc
+                .get_value_by_name(name)
+                .map(|value| ProtoValue::EnumNumber(value.number()))
+                .or_else(|| name.parse().ok().map(ProtoValue::EnumNumber))
+                .ok_or_else(|| format!("unknown value '{name}' of '{}'", desc.full_name()))?,
+            value => ProtoValue::EnumNumber(integer(value)?),
+        },
+        Kind::Message(desc) => ProtoValue::Message(from_json(desc.clone(), value)?),
+    };
+    Ok(value)
+}
+
+fn float_from_json(value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(value) => match value.as_str() {
+            "NaN" => Some(f64::NAN),
+            "Infinity" => Some(f64::INFINITY),
+            "-Infinity" => Some(f64::NEG_INFINITY),
+            value => value.parse().ok().filter(|value: &f64| value.is_finite()),
+        },
+        _ => None,
+    }
+    .ok_or_else(|| format!("invalid number {value}"))
+}
+
+fn integer<T: TryFrom<i128>>(value: &Value) -> Result<T, String> {
+    let integer = match value {
+        Value::Number(number) => number
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| number.as_u64().map(i128::from))
+            .or_else(|| {
+                // Integers may be written with exponents or fractions, such as `1e3`.
+                number
+                    .as_f64()
+                    .filter(|value| value.fract() == 0.0 && value.abs() < 1e20)
+                    .map(|value| value as i128)
+            }),
+        Value::String(value) => value.parse().ok(),
+        _ => None,
+    };
+    integer
+        .and_then(|integer| T::try_from(integer).ok())
+        .ok_or_else(|| format!("invalid integer {value}"))
+}
+
+fn accepts_null(kind: &Kind) -> bool {
+    match kind {
+        Kind::Message(desc) => desc.full_name() == "google.protobuf.Value",
+        Kind::Enum(desc) => desc.full_name() == "google.protobuf.NullValue",
+        _ => false,
+    }
+}
+
+fn is_wrapper(desc: &MessageDescriptor) -> bool {
+    desc.package_name() == "google.protobuf"
+        && desc.name().ends_with("Value")
+        && !matches!(desc.name(), "Value" | "ListValue")
+}
+
+// Reads `value` into `message` if it is a well-known type, returning whether it is one.
+fn well_known_from_json(message: &mut DynamicMessage, value: &Value) -> Result<bool, String> {
+    let desc = message.descriptor();
+    let field = |number| {
+        desc.get_field(number)
+            .ok_or_else(|| format!("invalid '{}' descriptor", desc.full_name()))
+    };
+
+    match desc.full_name() {
+        "google.protobuf.Timestamp" => {
+            let value = value.as_str().ok_or("expected an RFC 3339 timestamp")?;
+            let (seconds, nanos) = parse_timestamp(value)?;
+            message.set_field(&field(1)?, ProtoValue::I64(seconds));
+            message.set_field(&field(2)?, ProtoValue::I32(nanos));
+        }
+        "google.protobuf.Duration" => {
+            let value = value.as_str().ok_or("expected a duration such as '1.5s'")?;
+            let (seconds, nanos) = parse_duration(value)?;
+            message.set_field(&field(1)?, ProtoValue::I64(seconds));
+            message.set_field(&field(2)?, ProtoValue::I32(nanos));
+        }
+        _ if is_wrapper(&desc) => {
+            let field = field(1)?;
+            message.set_field(&field, value_from_json(&field.kind(), value)?);
+        }
+        "google.protobuf.Struct" => {
+            let field = field(1)?;
+            message.set_field(&field, field_from_json(&field, value)?);
+        }
+        "google.protobuf.ListValue" => {
+            let field = field(1)?;
+            message.set_field(&field, field_from_json(&field, value)?);
+        }
+        "google.protobuf.Value" => {
+            let (number, value) = match value {
+                Value::Null => (1, ProtoValue::EnumNumber(0)),
+                Value::Number(number) => (2, ProtoValue::F64(number.as_f64().unwrap_or_default())),
+                Value::String(value) => (3, ProtoValue::String(value.clone())),
+                Value::Bool(value) => (4, ProtoValue::Bool(*value)),
+                Value::Object(_) | Value::Array(_) => {
+                    let field = field(if value.is_object() { 5 } else { 6 })?;
+                    (field.number(), value_from_json(&field.kind(), value)?)
+                }
+            };
+            message.set_field(&field(number)?, value);
+        }
+        "google.protobuf.FieldMask" => {
+            let value = value
+                .as_str()
+                .ok_or("expected a comma-separated field mask")?;
+            let paths = value
+                .split(',')
+                .filter(|path| !path.is_empty())
+                .map(|path| ProtoValue::String(camel_to_snake(path)))
+                .collect();
+            message.set_field(&field(1)?, ProtoValue::List(paths));
+        }
+        "google.protobuf.Any" => {
+            let Value::Object(object) = value else {
+                return Err("expected an object for 'google.protobuf.Any'".into());
+            };
+            let type_url = object
+                .get("@type")
+                .and_then(Value::as_str)
+                .ok_or("missing '@type' of 'google.protobuf.Any'")?;
+            let any_desc = any_type(&desc, type_url)?;
+
+            let mut fields = object.clone();
+            fields.remove("@type");
+            let mut any = DynamicMessage::new(any_desc.clone());
+            let value = match fields.remove("value") {
+                Some(value) if well_known_from_json(&mut any, &value)? => any,
+                value => {
+                    if let Some(value) = value {
+                        fields.insert("value".into(), value);
+                    }
+                    from_json(any_desc, &Value::Object(fields))?
+                }
+            };
+
+            message.set_field(&field(1)?, ProtoValue::String(type_url.to_owned()));
+            message.set_field(&field(2)?, ProtoValue::Bytes(value.encode_to_vec().into()));
+        }
+        _ => return Ok(false),
+    }
+    Ok(true)
+}
+
+// The type of the message packed in an `Any`, by its type URL.
+fn any_type(desc: &MessageDescriptor, type_url: &str) -> Result<MessageDescriptor, String> {
+    let name = type_url.rsplit('/').next().unwrap_or_default();
+    desc.parent_pool()
+        .get_message_by_name(name)
+        .ok_or_else(|| format!("unknown Any type '{type_url}'"))
+}
+
+fn snake_to_camel(path: &str) -> String {
+    let mut camel = String::with_capacity(path.len());
+    let mut upper = false;
+    for c in path.chars() {
+        match c {
+            '_' => upper = true,
+            c if upper => {
+                camel.push(c.to_ascii_uppercase());
+                upper = false;
+            }
+            c => camel.push(c),
+        }
+    }
+    camel
+}
+
+fn camel_to_snake(path: &str) -> String {
+    let mut snake = String::with_capacity(path.len() + 4);
+    for c in path.chars() {
+        if c.is_ascii_uppercase() {
+            snake.push('_');
+            snake.push(c.to_ascii_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+// Formats the fractional seconds of timestamps and durations, with 0, 3, 6 or 9 digits.
+fn format_nanos(nanos: i32) -> String {
+    if nanos == 0 {
+        String::new()
+    } else if nanos % 1_000_000 == 0 {
+        format!(".{:03}", nanos / 1_000_000)
+    } else if nanos % 1_000 == 0 {
+        format!(".{:06}", nanos / 1_000)
+    } else {
+        format!(".{nanos:09}")
+    }
+}
+
+fn parse_nanos(fraction: &str) -> Result<i32, String> {
+    if fraction.is_empty() || fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("invalid fractional seconds '{fraction}'"));
+    }
+    let nanos: i32 = fraction.parse().map_err(|_| "invalid fractional seconds")?;
+    Ok(nanos * 10_i32.pow(9 - fraction.len() as u32))
+}
+
+fn format_timestamp(seconds: i64, nanos: i32) -> Result<String, String> {
+    if !(MIN_TIMESTAMP..=MAX_TIMESTAMP).contains(&seconds) || !(0..1_000_000_000).contains(&nanos) {
+        return Err(format!("timestamp out of range: {seconds}s {nanos}ns"));
+    }
+
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    Ok(format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}{}Z",
+        time / 3600,
+        time / 60 % 60,
+        time % 60,
+        format_nanos(nanos),
+    ))
+}
+
+// Parses RFC 3339 timestamps such as `1972-01-01T10:00:20.021-05:00`.
+fn parse_timestamp(value: &str) -> Result<(i64, i32), String> {
+    let invalid = || format!("invalid timestamp '{value}'");
+    let number = |range: std::ops::Range<usize>| -> Result<i64, String> {
+        let digits = value.get(range).ok_or_else(invalid)?;
+        if !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        digits.parse().map_err(|_| invalid())
+    };
+
+    let bytes = value.as_bytes();
+    if bytes.len() < 20
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b't')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return Err(invalid());
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return Err(invalid());
+    }
+
+    let mut rest = value.get(19..).ok_or_else(invalid)?;
+    let mut nanos = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let end = fraction
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(fraction.len());
+        nanos = parse_nanos(&fraction[..end])?;
+        rest = &fraction[end..];
+    }
+
+    let offset = match rest {
+        "Z" | "z" => 0,
+        offset if offset.len() == 6 && offset.as_bytes()[3] == b':' => {
+            let sign = match offset.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return Err(invalid()),
+            };
+            let part = |range| {
+                offset
+                    .get(range)
+                    .and_then(|part: &str| part.parse::<i64>().ok())
+            };
+            let hours = part(1..3).ok_or_else(invalid)?;
+            let minutes = part(4..6).ok_or_else(invalid)?;
+            sign * (hours * 3600 + minutes * 60)
+        }
+        _ => return Err(invalid()),
+    };
+
+    let days = days_from_civil(year, month as u32, day as u32);
+    let seconds = days * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    if !(MIN_TIMESTAMP..=MAX_TIMESTAMP).contains(&seconds) {
+        return Err(format!("timestamp out of range '{value}'"));
+    }
+    Ok((seconds, nanos))
+}
+
+fn format_duration(seconds: i64, nanos: i32) -> Result<String, String> {
+    if seconds.abs() > MAX_DURATION
+        || nanos.abs() >= 1_000_000_000
+        || (seconds > 0 && nanos < 0)
+        || (seconds < 0 && nanos > 0)
+    {
+        return Err(format!("duration out of range: {seconds}s {nanos}ns"));
+    }
+
+    let sign = if seconds < 0 || nanos < 0 { "-" } else { "" };
+    Ok(format!(
+        "{sign}{}{}s",
+        seconds.abs(),
+        format_nanos(nanos.abs())
+    ))
+}
+
+// Parses durations such as `-1.5s`.
+fn parse_duration(value: &str) -> Result<(i64, i32), String> {
+    let invalid = || format!("invalid duration '{value}'");
+
+    let rest = value.strip_suffix('s').ok_or_else(invalid)?;
+    let (negative, rest) = match rest.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    let (seconds, nanos) = match rest.split_once('.') {
+        Some((seconds, fraction)) => (seconds, parse_nanos(fraction)?),
+        None => (rest, 0),
+    };
+    if seconds.is_empty() || !seconds.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let seconds: i64 = seconds.parse().map_err(|_| invalid())?;
+    if seconds > MAX_DURATION {
+        return Err(format!("duration out of range '{value}'"));
+    }
+
+    Ok(if negative {
+        (-seconds, -nanos)
+    } else {
+        (seconds, nanos)
+    })
+}
+
+// The days since 1970-01-01 of a date of the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * i64::from((month + 9) % 12) + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+// The date of the proleptic Gregorian calendar `days` after 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_timestamp(0, 0).unwrap(), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(63_108_020, 21_000_000).unwrap(),
+            "1972-01-01T10:00:20.021Z"
+        );
+        assert_eq!(
+            format_timestamp(MIN_TIMESTAMP, 1).unwrap(),
+            "0001-01-01T00:00:00.000000001Z"
+        );
+        assert_eq!(
+            format_timestamp(MAX_TIMESTAMP, 0).unwrap(),
+            "9999-12-31T23:59:59Z"
+        );
+        assert!(format_timestamp(MAX_TIMESTAMP + 1, 0).is_err());
+
+        assert_eq!(
+            parse_timestamp("1972-01-01T10:00:20.021Z").unwrap(),
+            (63_108_020, 21_000_000)
+        );
+        assert_eq!(
+            parse_timestamp("1972-01-01T05:00:20.021-05:00").unwrap(),
+            (63_108_020, 21_000_000)
+        );
+        assert_eq!(
+            parse_timestamp("2000-02-29T00:00:00Z").unwrap(),
+            (951_782_400, 0)
+        );
+        for invalid in ["1972-01-01", "1972-01-01T10:00:20", "1972-13-01T10:00:20Z"] {
+            assert!(parse_timestamp(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(1, 500_000_000).unwrap(), "1.500s");
+        assert_eq!(format_duration(-1, -1_000).unwrap(), "-1.000001s");
+        assert_eq!(format_duration(0, -5).unwrap(), "-0.000000005s");
+        assert!(format_duration(1, -1).is_err());
+
+        assert_eq!(parse_duration("1.5s").unwrap(), (1, 500_000_000));
+        assert_eq!(parse_duration("-0.000000005s").unwrap(), (0, -5));
+        assert_eq!(parse_duration("3s").unwrap(), (3, 0));
+        for invalid in ["1.5", "s", "1.s", "-s", "1.0000000001s"] {
+            assert!(parse_duration(invalid).is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn well_known_types() {
+        let pool = prost_reflect::DescriptorPool::global();
+        let round_trip = |name: &str, value: Value| {
+            let desc = pool.get_message_by_name(name).unwrap();
+            let message = from_json(desc, &value).unwrap();
+            assert_eq!(to_json(&message).unwrap(), value, "{name}");
+        };
+
+        round_trip(
+            "google.protobuf.Struct",
+            serde_json::json!({ "a": [1.5, "b", true, null, { "c": {} }] }),
+        );
+        round_trip("google.protobuf.Int64Value", Value::from("-12"));
+        round_trip("google.protobuf.FieldMask", Value::from("a.bC,d"));
+        round_trip(
+            "google.protobuf.Any",
+            serde_json::json!({
+                "@type": "type.googleapis.com/google.protobuf.Duration",
+                "value": "1.500s",
+            }),
+        );
+    }
+
+    #[test]
+    fn field_masks() {
+        assert_eq!(snake_to_camel("user.display_name"), "user.displayName");
+        assert_eq!(camel_to_snake("user.displayName"), "user.display_name");
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(integer::<i32>(&Value::from(7)).unwrap(), 7);
+        assert_eq!(integer::<i64>(&Value::from("-7")).unwrap(), -7);
+        assert_eq!(integer::<u32>(&serde_json::json!(1e3)).unwrap(), 1000);
+        assert!(integer::<u32>(&Value::from(-1)).is_err());
+        assert!(integer::<i32>(&Value::from(1.5)).is_err());
+
+        assert_eq!(float_to_json(f64::INFINITY), "Infinity");
+        assert!(float_from_json(&Value::from("NaN")).unwrap().is_nan());
+        assert_eq!(float_from_json(&Value::from("2.5")).unwrap(), 2.5);
+    }
+}
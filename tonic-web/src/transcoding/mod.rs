@@ -0,0 +1,258 @@
+//! HTTP/JSON to gRPC transcoding.
+//!
+//! The [`TranscodingLayer`] maps HTTP/JSON requests to the methods of the inner gRPC service,
+//! following the [`google.api.http`] annotations of the methods, like [grpc-gateway] does in
+//! front of gRPC servers. gRPC clients and HTTP/JSON clients may then be served by the same
+//! tonic server:
+//!
+//! ```ignore
+//! let transcoding = tonic_web::transcoding::Builder::configure()
+//!     .register_encoded_file_descriptor_set(pb::FILE_DESCRIPTOR_SET)
+//!     .add_routes(pb::shelves_methods::HTTP_ROUTES)
+//!     .build()?;
+//!
+//! Server::builder()
+//!    .accept_http1(true)
+//!    .layer(transcoding)
+//!    .add_service(ShelvesServer::new(shelves))
+//!    .serve(addr)
+//!    .await?;
+//! ```
+//!
+//! Routes are the `HTTP_ROUTES` tables generated by `tonic-build` with
+//! `build_http_routes(true)`, and messages are translated from and to their [JSON mapping]
+//! with the descriptors of the methods, so the descriptor set of the services must be
+//! registered too.
+//!
+//! Requests are mapped to request messages from:
+//!
+//! * the variables of the path template, e.g. `name` in `/v1/{name=shelves/*}`,
+//! * the JSON body, as the whole message with `body: "*"`, or as the field named by `body`,
+//! * the query parameters, for the fields bound by neither the path nor the body, e.g.
+//!   `?page_size=10&filter.author=me`.
+//!
+//! Responses are the JSON mapping of the response message, or of the field named by
+//! `response_body`. Server streaming methods respond with newline-delimited JSON objects,
+//! `{"result": ...}` for each message, and `{"error": ...}` if the call fails after the first
+//! message. Client streaming methods read newline-delimited request messages from the body,
+//! which must map to the whole message. Failed calls respond with `{"code": ..., "message":
+//! ...}` and the HTTP status of their gRPC code.
+//!
+//! [`google.api.http`]: https://github.com/googleapis/googleapis/blob/master/google/api/http.proto
+//! [grpc-gateway]: https://github.com/grpc-ecosystem/grpc-gateway
+//! [JSON mapping]: https://protobuf.dev/programming-guides/json/
+
+use std::fmt::{self, Display, Formatter};
+use std::sync::Arc;
+
+use prost_reflect::{DescriptorError, DescriptorPool, FieldDescriptor, MessageDescriptor};
+use tonic::HttpRoute;
+
+mod json;
+mod service;
+mod template;
+
+pub use service::{ResponseFuture, TranscodingLayer, TranscodingService};
+
+use template::PathTemplate;
+
+/// A builder for a [`TranscodingLayer`].
+#[derive(Debug, Default)]
+pub struct Builder<'b> {
+    pool: Option<DescriptorPool>,
+    encoded_file_descriptor_sets: Vec<&'b [u8]>,
+    routes: Vec<HttpRoute>,
+}
+
+impl<'b> Builder<'b> {
+    /// Create a new builder that can configure a [`TranscodingLayer`].
+    pub fn configure() -> Self {
+        Self::default()
+    }
+
+    /// Registers an encoded `prost_types::FileDescriptorSet` with the layer, holding the
+    /// descriptors of the methods of the routes and of their messages.
+    pub fn register_encoded_file_descriptor_set(
+        mut self,
+        encoded_file_descriptor_set: &'b [u8],
+    ) -> Self {
+        self.encoded_file_descriptor_sets
+            .push(encoded_file_descriptor_set);
+        self
+    }
+
+    /// Use `pool` for the descriptors of the methods of the routes, along with the registered
+    /// file descriptor sets.
+    pub fn with_descriptor_pool(mut self, pool: DescriptorPool) -> Self {
+        self.pool = Some(pool);
+        self
+    }
+
+    /// Adds `routes`, the `HTTP_ROUTES` table of a service, to the layer.
+    ///
+    /// Requests matching several routes are mapped to the first one added.
+    pub fn add_routes(mut self, routes: &[HttpRoute]) -> Self {
+        self.routes.extend_from_slice(routes);
+        self
+    }
+
+    /// Build a [`TranscodingLayer`] from the configured routes.
+    pub fn build(self) -> Result<TranscodingLayer, Error> {
+        let mut pool = self.pool.unwrap_or_default();
+        for encoded in self.encoded_file_descriptor_sets {
+            pool.decode_file_descriptor_set(encoded)?;
+        }
+
+        let routes = self
+            .routes
+            .iter()
+            .map(|route| Route::new(&pool, route).map(Arc::new))
+            .collect::<Result<_, _>>()?;
+        Ok(TranscodingLayer::new(routes))
+    }
+}
+
+/// Represents an error in the construction of a [`TranscodingLayer`].
+#[derive(Debug)]
+pub enum Error {
+    /// An invalid encoded `prost_types::FileDescriptorSet` was registered.
+    InvalidFileDescriptorSet(DescriptorError),
+    /// A route does not match the descriptors of its method, or has an invalid path template.
+    InvalidRoute(String),
+}
+
+impl From<DescriptorError> for Error {
+    fn from(e: DescriptorError) -> Self {
+        Error::InvalidFileDescriptorSet(e)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidFileDescriptorSet(e) => write!(f, "invalid FileDescriptorSet - {e}"),
+            Error::InvalidRoute(s) => write!(f, "invalid route - {s}"),
+        }
+    }
+}
+
+// The field of the request message mapped to the request body.
+#[derive(Debug)]
+enum BodyField {
+    Whole,
+    Field(FieldDescriptor),
+}
+
+// A route, resolved against the descriptors of its method.
+#[derive(Debug)]
+struct Route {
+    http_method: http::Method,
+    template: PathTemplate,
+    grpc_path: &'static str,
+    client_streaming: bool,
+    server_streaming: bool,
+    input: MessageDescriptor,
+    output: MessageDescriptor,
+    // The fields of the variables of `template`, in their order.
+    variables: Vec<Vec<FieldDescriptor>>,
+    body: Option<BodyField>,
+    response_body: Option<FieldDescriptor>,
+}
+
+impl Route {
+    fn new(pool: &DescriptorPool, route: &HttpRoute) -> Result<Self, Error> {
+        let method = route.method();
+        let invalid = |reason: String| {
+            Error::InvalidRoute(format!(
+                "{} {} of '{}': {reason}",
+                route.http_method(),
+                route.path_template(),
+                method.path(),
+            ))
+        };
+
+        let desc = pool
+            .get_service_by_name(method.service())
+            .and_then(|service| service.methods().find(|m| m.name() == method.method()))
+            .ok_or_else(|| invalid("method not found in the descriptor pool".into()))?;
+        let (input, output) = (desc.input(), desc.output());
+
+        let http_method = http::Method::from_bytes(route.http_method().as_bytes())
+            .map_err(|_| invalid("invalid HTTP method".into()))?;
+        let template = PathTemplate::parse(route.path_template()).map_err(invalid)?;
+        let variables = template
+            .field_paths()
+            .map(|path| resolve(&input, path))
+            .collect::<Result<_, _>>()
+            .map_err(invalid)?;
+
+        let body = match route.body() {
+            None => None,
+            Some("*") => Some(BodyField::Whole),
+            Some(name) => {
+                Some(BodyField::Field(input.get_field_by_name(name).ok_or_else(
+                    || invalid(format!("unknown body field '{name}'")),
+                )?))
+            }
+        };
+        if method.client_streaming() && !matches!(body, Some(BodyField::Whole)) {
+            return Err(invalid(
+                "client streaming methods must map the body to the whole message".into(),
+            ));
+        }
+        let response_body = route
+            .response_body()
+            .map(|name| {
+                output
+                    .get_field_by_name(name)
+                    .ok_or_else(|| invalid(format!("unknown response body field '{name}'")))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            http_method,
+            template,
+            grpc_path: method.path(),
+            client_streaming: method.client_streaming(),
+            server_streaming: method.server_streaming(),
+            input,
+            output,
+            variables,
+            body,
+            response_body,
+        })
+    }
+}
+
+// Resolves the fields of a field path, by their proto or JSON names.
+fn resolve<S: AsRef<str>>(
+    desc: &MessageDescriptor,
+    path: &[S],
+) -> Result<Vec<FieldDescriptor>, String> {
+    let mut fields = Vec::with_capacity(path.len());
+    let mut desc = desc.clone();
+
+    for (i, name) in path.iter().enumerate() {
+        let name = name.as_ref();
+        let field = desc
+            .get_field_by_name(name)
+            .or_else(|| desc.get_field_by_json_name(name))
+            .ok_or_else(|| format!("unknown field '{name}' of '{}'", desc.full_name()))?;
+
+        if i + 1 < path.len() {
+            desc = match field.kind() {
+                prost_reflect::Kind::Message(nested) if !field.is_list() && !field.is_map() => {
+                    nested
+                }
+                _ => return Err(format!("field '{name}' is not a singular message")),
+            };
+        } else if field.is_map() {
+            return Err(format!("map field '{name}' cannot be bound to a parameter"));
+        }
+        fields.push(field);
+    }
+
+    Ok(fields)
+}
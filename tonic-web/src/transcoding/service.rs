@@ -0,0 +1,604 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::{header, HeaderMap, HeaderValue, Method, Request, Response, Uri, Version};
+use http_body::Frame;
+use http_body_util::{BodyExt, Full};
+use pin_project::pin_project;
+use prost::Message as _;
+use prost_reflect::{DynamicMessage, FieldDescriptor, Value as ProtoValue};
+use serde_json::{json, Value};
+use tonic::body::Body;
+use tonic::Status;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::trace;
+
+use super::{json, resolve, BodyField, Route};
+use crate::util::status::{http_status, status_from_headers};
+use crate::BoxError;
+
+const GRPC_HEADER_SIZE: usize = 5;
+
+/// Layer mapping HTTP/JSON requests to gRPC requests, per the `google.api.http` annotations
+/// of the methods.
+///
+/// Built with a [`Builder`](super::Builder). Requests matching none of the routes, and gRPC
+/// requests, are passed through.
+#[derive(Debug, Clone)]
+pub struct TranscodingLayer {
+    routes: Arc<[Arc<Route>]>,
+}
+
+impl TranscodingLayer {
+    pub(super) fn new(routes: Vec<Arc<Route>>) -> Self {
+        Self {
+            routes: routes.into(),
+        }
+    }
+}
+
+impl<S> Layer<S> for TranscodingLayer {
+    type Service = TranscodingService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TranscodingService {
+            inner,
+            routes: self.routes.clone(),
+        }
+    }
+}
+
+/// Service mapping HTTP/JSON requests to gRPC requests.
+#[derive(Debug, Clone)]
+pub struct TranscodingService<S> {
+    inner: S,
+    routes: Arc<[Arc<Route>]>,
+}
+
+impl<S> TranscodingService<S> {
+    // The route matching `req`, and the values of the variables of its path.
+    fn route<B>(&self, req: &Request<B>) -> Option<(Arc<Route>, Vec<String>)> {
+        let grpc = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/grpc"));
+        if grpc {
+            return None;
+        }
+
+        self.routes.iter().find_map(|route| {
+            if route.http_method != req.method() {
+                return None;
+            }
+            let values = route.template.matches(req.uri().path())?;
+            Some((route.clone(), values))
+        })
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for TranscodingService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    ReqBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ReqBody::Error: Into<BoxError>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        let Some((route, values)) = self.route(&req) else {
+            return ResponseFuture {
+                case: Case::Other {
+                    future: self.inner.call(req.map(Body::new)),
+                },
+            };
+        };
+        trace!(kind = "transcoding", path = ?req.uri().path(), method = route.grpc_path);
+
+        let (mut parts, body) = req.into_parts();
+        let body = RequestBody {
+            inner: body,
+            route: route.clone(),
+            values,
+            query: parts.uri.query().map(str::to_owned),
+            body: BytesMut::new(),
+            done: false,
+        };
+
+        parts.method = Method::POST;
+        parts.uri = Uri::from_static(route.grpc_path);
+        parts.version = Version::HTTP_2;
+        for name in [
+            header::CONTENT_LENGTH,
+            header::ACCEPT,
+            header::ACCEPT_ENCODING,
+            header::CONTENT_ENCODING,
+        ] {
+            parts.headers.remove(name);
+        }
+        // Messages are neither sent nor accepted compressed.
+        parts.headers.remove("grpc-encoding");
+        parts.headers.remove("grpc-accept-encoding");
+        parts.headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/grpc"),
+        );
+        parts
+            .headers
+            .insert(header::TE, HeaderValue::from_static("trailers"));
+
+        let future = self.inner.call(Request::from_parts(parts, Body::new(body)));
+        let case = if route.server_streaming {
+            Case::Streaming { future, route }
+        } else {
+            Case::Unary { future, route }
+        };
+        ResponseFuture { case }
+    }
+}
+
+/// Response future for the [`TranscodingService`].
+#[pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture<F> {
+    #[pin]
+    case: Case<F>,
+}
+
+#[pin_project(project = CaseProj)]
+enum Case<F> {
+    Unary {
+        #[pin]
+        future: F,
+        route: Arc<Route>,
+    },
+    // The response of a unary call, buffered to learn its status.
+    Buffering {
+        future: Pin<Box<dyn Future<Output = Response<Body>> + Send>>,
+    },
+    Streaming {
+        #[pin]
+        future: F,
+        route: Arc<Route>,
+    },
+    Other {
+        #[pin]
+        future: F,
+    },
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            match self.as_mut().project().case.project() {
+                CaseProj::Unary { future, route } => {
+                    let res = ready!(future.poll(cx))?;
+                    let future = Box::pin(unary_response(res, route.clone()));
+                    self.as_mut().project().case.set(Case::Buffering { future });
+                }
+                CaseProj::Buffering { future } => return future.as_mut().poll(cx).map(Ok),
+                CaseProj::Streaming { future, route } => {
+                    let res = ready!(future.poll(cx))?;
+                    return Poll::Ready(Ok(streaming_response(res, route.clone())));
+                }
+                CaseProj::Other { future } => {
+                    let res = ready!(future.poll(cx))?;
+                    return Poll::Ready(Ok(res.map(Body::new)));
+                }
+            }
+        }
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+// The request messages of a call, from its path variables, query and body.
+fn request_messages(
+    route: &Route,
+    values: &[String],
+    query: Option<&str>,
+    body: &[u8],
+) -> Result<Vec<DynamicMessage>, Status> {
+    let invalid = |e: String| Status::invalid_argument(e);
+
+    let mut messages = if route.client_streaming {
+        serde_json::Deserializer::from_slice(body)
+            .into_iter::<Value>()
+            .map(|value| {
+                let value = value.map_err(|e| invalid(format!("invalid JSON body: {e}")))?;
+                json::from_json(route.input.clone(), &value).map_err(invalid)
+            })
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        let value = match body.iter().all(u8::is_ascii_whitespace) {
+            true => None,
+            false => Some(
+                serde_json::from_slice::<Value>(body)
+                    .map_err(|e| invalid(format!("invalid JSON body: {e}")))?,
+            ),
+        };
+
+        let message = match (&route.body, value) {
+            (Some(BodyField::Whole), value) => {
+                let value = value.unwrap_or_else(|| json!({}));
+                json::from_json(route.input.clone(), &value).map_err(invalid)?
+            }
+            (Some(BodyField::Field(field)), Some(value)) => {
+                let mut message = DynamicMessage::new(route.input.clone());
+                let value = json::field_from_json(field, &value)
+                    .map_err(|e| invalid(format!("invalid body field '{}': {e}", field.name())))?;
+                message.set_field(field, value);
+                message
+            }
+            _ => DynamicMessage::new(route.input.clone()),
+        };
+        vec![message]
+    };
+
+    for message in &mut messages {
+        for (fields, value) in route.variables.iter().zip(values) {
+            bind(message, fields, value).map_err(invalid)?;
+        }
+    }
+
+    // Query parameters bind the fields bound by neither the path nor the body.
+    if let (Some(query), [message]) = (query, &mut messages[..]) {
+        if matches!(route.body, Some(BodyField::Whole)) {
+            return Ok(messages);
+        }
+
+        for (name, value) in form_urlencoded::parse(query.as_bytes()) {
+            let path = name.split('.').collect::<Vec<_>>();
+            let fields = resolve(&route.input, &path)
+                .map_err(|e| invalid(format!("invalid query parameter '{name}': {e}")))?;
+            let bound = route
+                .variables
+                .iter()
+                .map(Vec::as_slice)
+                .chain(match &route.body {
+                    Some(BodyField::Field(field)) => Some(std::slice::from_ref(field)),
+                    _ => None,
+                })
+                .any(|bound| bound.starts_with(&fields) || fields.starts_with(bound));
+            if !bound {
+                bind(message, &fields, &value)
+                    .map_err(|e| invalid(format!("invalid query parameter '{name}': {e}")))?;
+            }
+        }
+    }
+
+    Ok(messages)
+}
+
+// Sets the field at the end of `fields` to `value`, or appends `value` to it if it is repeated.
+fn bind(
+    message: &mut DynamicMessage,
+    fields: &[FieldDescriptor],
+    value: &str,
+) -> Result<(), String> {
+    let Some((field, rest)) = fields.split_first() else {
+        return Ok(());
+    };
+
+    if !rest.is_empty() {
+        return match message.get_field_mut(field) {
+            ProtoValue::Message(nested) => bind(nested, rest, value),
+            _ => Err(format!("field '{}' is not a message", field.name())),
+        };
+    }
+
+    let value = json::value_from_str(&field.kind(), value)
+        .map_err(|e| format!("invalid value of field '{}': {e}", field.name()))?;
+    match message.get_field_mut(field) {
+        ProtoValue::List(values) => values.push(value),
+        _ => message.set_field(field, value),
+    }
+    Ok(())
+}
+
+// The body of a transcoded request, buffering the HTTP body to map it to the request messages.
+#[pin_project]
+struct RequestBody<B> {
+    #[pin]
+    inner: B,
+    route: Arc<Route>,
+    values: Vec<String>,
+    query: Option<String>,
+    body: BytesMut,
+    done: bool,
+}
+
+impl<B> http_body::Body for RequestBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            match ready!(this.inner.as_mut().poll_frame(cx)) {
+                Some(Ok(frame)) => {
+                    if let Ok(data) = frame.into_data() {
+                        this.body.put(data);
+                    }
+                }
+                Some(Err(e)) => {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(Status::from_error(e.into()))));
+                }
+                None => {
+                    *this.done = true;
+                    let messages = request_messages(
+                        this.route,
+                        this.values,
+                        this.query.as_deref(),
+                        this.body,
+                    )?;
+
+                    let mut buf = BytesMut::new();
+                    for message in messages {
+                        buf.put_u8(0);
+                        buf.put_u32(message.encoded_len() as u32);
+                        message
+                            .encode(&mut buf)
+                            .expect("buffers have enough capacity");
+                    }
+                    return Poll::Ready(Some(Ok(Frame::data(buf.freeze()))));
+                }
+            }
+        }
+    }
+}
+
+// Splits the next message of a gRPC response from `buf`, if it is complete.
+fn next_message(buf: &mut BytesMut) -> Option<Result<Bytes, Status>> {
+    if buf.len() < GRPC_HEADER_SIZE {
+        return None;
+    }
+    let compressed = buf[0] != 0;
+    let len = u32::from_be_bytes([buf[1], buf[2], buf[3], buf[4]]) as usize;
+    if buf.len() < GRPC_HEADER_SIZE + len {
+        return None;
+    }
+    if compressed {
+        return Some(Err(Status::internal("unexpected compressed message")));
+    }
+
+    buf.advance(GRPC_HEADER_SIZE);
+    Some(Ok(buf.split_to(len).freeze()))
+}
+
+// The JSON of a response message, or of its field mapped to the response body.
+fn response_json(route: &Route, message: Bytes) -> Result<Value, Status> {
+    let message = DynamicMessage::decode(route.output.clone(), message)
+        .map_err(|e| Status::internal(format!("invalid response message: {e}")))?;
+
+    match &route.response_body {
+        Some(field) => json::field_to_json(field, &message.get_field(field)),
+        None => json::to_json(&message),
+    }
+    .map_err(Status::internal)
+}
+
+fn error_json(status: &Status) -> Value {
+    json!({
+        "code": status.code() as i32,
+        "message": status.message(),
+    })
+}
+
+fn json_response(value: &Value, headers: HeaderMap) -> Response<Body> {
+    let body = serde_json::to_vec(value).expect("JSON values are serializable");
+
+    let mut res = Response::new(Body::new(Full::new(Bytes::from(body))));
+    *res.headers_mut() = headers;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+fn error_response(status: &Status, headers: HeaderMap) -> Response<Body> {
+    let mut res = json_response(&error_json(status), headers);
+    *res.status_mut() = http_status(status.code());
+    res
+}
+
+// The headers of a gRPC response forwarded to the HTTP response.
+fn response_headers(mut headers: HeaderMap) -> HeaderMap {
+    let grpc = headers
+        .keys()
+        .filter(|name| name.as_str().starts_with("grpc-"))
+        .cloned()
+        .collect::<Vec<_>>();
+    for name in grpc {
+        headers.remove(name);
+    }
+    headers.remove(header::CONTENT_TYPE);
+    headers.remove(header::CONTENT_LENGTH);
+    headers
+}
+
+// Buffers the response of a unary call, to learn its status before translating its message.
+async fn unary_response<B>(res: Response<B>, route: Arc<Route>) -> Response<Body>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    let (parts, body) = res.into_parts();
+
+    // A trailers-only response.
+    if let Some(status) = status_from_headers(&parts.headers) {
+        if status.code() != tonic::Code::Ok {
+            return error_response(&status, response_headers(parts.headers));
+        }
+    }
+
+    let collected = match body.collect().await {
+        Ok(collected) => collected,
+        Err(e) => return error_response(&Status::from_error(e.into()), HeaderMap::new()),
+    };
+    let trailers = collected.trailers().cloned().unwrap_or_default();
+    let mut buf = BytesMut::from(&collected.to_bytes()[..]);
+
+    let status = status_from_headers(&trailers)
+        .or_else(|| status_from_headers(&parts.headers))
+        .unwrap_or_else(|| Status::internal("missing grpc-status"));
+    let headers = response_headers(parts.headers);
+    if status.code() != tonic::Code::Ok {
+        return error_response(&status, headers);
+    }
+
+    let message = match next_message(&mut buf) {
+        Some(Ok(message)) if buf.is_empty() => message,
+        Some(Err(status)) => return error_response(&status, headers),
+        _ => {
+            let status = Status::internal("expected a single response message");
+            return error_response(&status, headers);
+        }
+    };
+    match response_json(&route, message) {
+        Ok(value) => json_response(&value, headers),
+        Err(status) => error_response(&status, headers),
+    }
+}
+
+fn streaming_response<B>(res: Response<B>, route: Arc<Route>) -> Response<Body>
+where
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    let (parts, body) = res.into_parts();
+
+    // Errors before the first message are reported with their HTTP status.
+    if let Some(status) = status_from_headers(&parts.headers) {
+        if status.code() != tonic::Code::Ok {
+            return error_response(&status, response_headers(parts.headers));
+        }
+    }
+
+    let trailers_only = status_from_headers(&parts.headers);
+    let headers = response_headers(parts.headers);
+    let body = StreamingResponseBody {
+        inner: body,
+        route,
+        trailers_only,
+        buf: BytesMut::new(),
+        done: false,
+    };
+    let mut res = Response::new(Body::new(body));
+    *res.headers_mut() = headers;
+    res.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    res
+}
+
+fn json_line(value: &Value) -> Bytes {
+    let mut line = serde_json::to_vec(value).expect("JSON values are serializable");
+    line.push(b'\n');
+    line.into()
+}
+
+// The body of a streaming response, with a JSON line for each message and for a failed status.
+#[pin_project]
+struct StreamingResponseBody<B> {
+    #[pin]
+    inner: B,
+    route: Arc<Route>,
+    trailers_only: Option<Status>,
+    buf: BytesMut,
+    done: bool,
+}
+
+impl<B> http_body::Body for StreamingResponseBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<BoxError>,
+{
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            let status = match next_message(this.buf) {
+                Some(Ok(message)) => match response_json(this.route, message) {
+                    Ok(value) => {
+                        let line = json_line(&json!({ "result": value }));
+                        return Poll::Ready(Some(Ok(Frame::data(line))));
+                    }
+                    Err(status) => status,
+                },
+                Some(Err(status)) => status,
+                None => match ready!(this.inner.as_mut().poll_frame(cx)) {
+                    Some(Ok(frame)) => match frame.into_data() {
+                        Ok(data) => {
+                            this.buf.put(data);
+                            continue;
+                        }
+                        Err(frame) => match frame.into_trailers() {
+                            Ok(trailers) => status_from_headers(&trailers)
+                                .unwrap_or_else(|| Status::internal("missing grpc-status")),
+                            Err(_) => continue,
+                        },
+                    },
+                    Some(Err(e)) => Status::from_error(e.into()),
+                    None => this
+                        .trailers_only
+                        .take()
+                        .unwrap_or_else(|| Status::internal("missing trailers")),
+                },
+            };
+
+            *this.done = true;
+            if status.code() == tonic::Code::Ok {
+                return Poll::Ready(None);
+            }
+            let line = json_line(&json!({ "error": error_json(&status) }));
+            return Poll::Ready(Some(Ok(Frame::data(line))));
+        }
+    }
+}
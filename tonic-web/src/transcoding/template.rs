@@ -0,0 +1,287 @@
+//! Path templates of `google.api.http` annotations.
+//!
+//! ```text
+//! Template = "/" Segments [ Verb ] ;
+//! Segments = Segment { "/" Segment } ;
+//! Segment  = "*" | "**" | LITERAL | Variable ;
+//! Variable = "{" FieldPath [ "=" Segments ] "}" ;
+//! FieldPath = IDENT { "." IDENT } ;
+//! Verb     = ":" LITERAL ;
+//! ```
+
+use percent_encoding::percent_decode_str;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    // `*`, matching a single segment.
+    Wildcard,
+    // `**`, matching any number of segments.
+    DeepWildcard,
+}
+
+// A variable, capturing the segments `start..end` of the template.
+#[derive(Debug, Clone, PartialEq)]
+struct Variable {
+    field_path: Vec<String>,
+    start: usize,
+    end: usize,
+}
+
+/// A parsed path template.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PathTemplate {
+    segments: Vec<Segment>,
+    variables: Vec<Variable>,
+    verb: Option<String>,
+}
+
+impl PathTemplate {
+    /// Parse `template`, e.g. `/v1/{name=shelves/*}:publish`.
+    pub(crate) fn parse(template: &str) -> Result<Self, String> {
+        let rest = template
+            .strip_prefix('/')
+            .ok_or_else(|| format!("path template '{template}' does not start with '/'"))?;
+
+        // The verb follows the last `:` outside of variables.
+        let (rest, verb) = match rest.rfind(':') {
+            Some(i) if !rest[i..].contains('}') => (&rest[..i], Some(rest[i + 1..].to_owned())),
+            _ => (rest, None),
+        };
+
+        let mut parsed = Self {
+            segments: Vec::new(),
+            variables: Vec::new(),
+            verb,
+        };
+        let invalid = |reason: &str| format!("invalid path template '{template}': {reason}");
+
+        let mut chars = rest;
+        while !chars.is_empty() {
+            if let Some(variable) = chars.strip_prefix('{') {
+                let end = variable
+                    .find('}')
+                    .ok_or_else(|| invalid("unclosed variable"))?;
+                let (field_path, segments) = match variable[..end].split_once('=') {
+                    Some((field_path, segments)) => (field_path, segments),
+                    None => (&variable[..end], "*"),
+                };
+                if field_path.is_empty() || field_path.split('.').any(str::is_empty) {
+                    return Err(invalid("empty field path"));
+                }
+
+                let start = parsed.segments.len();
+                for segment in segments.split('/') {
+                    if segment.contains(['{', '}']) {
+                        return Err(invalid("nested variable"));
+                    }
+                    parsed
+                        .segments
+                        .push(Self::segment(segment).ok_or_else(|| invalid("empty segment"))?);
+                }
+                parsed.variables.push(Variable {
+                    field_path: field_path.split('.').map(str::to_owned).collect(),
+                    start,
+                    end: parsed.segments.len(),
+                });
+                chars = &variable[end + 1..];
+            } else {
+                let end = chars.find('/').unwrap_or(chars.len());
+                let segment = &chars[..end];
+                if segment.contains(['{', '}']) {
+                    return Err(invalid("misplaced variable"));
+                }
+                parsed
+                    .segments
+                    .push(Self::segment(segment).ok_or_else(|| invalid("empty segment"))?);
+                chars = &chars[end..];
+            }
+
+            chars = match chars.strip_prefix('/') {
+                Some("") => return Err(invalid("trailing '/'")),
+                Some(rest) => rest,
+                None if chars.is_empty() => chars,
+                None => return Err(invalid("expected '/'")),
+            };
+        }
+
+        let deep = parsed
+            .segments
+            .iter()
+            .filter(|segment| **segment == Segment::DeepWildcard)
+            .count();
+        if deep > 1 {
+            return Err(invalid("more than one '**'"));
+        }
+        Ok(parsed)
+    }
+
+    fn segment(segment: &str) -> Option<Segment> {
+        match segment {
+            "" => None,
+            "*" => Some(Segment::Wildcard),
+            "**" => Some(Segment::DeepWildcard),
+            literal => Some(Segment::Literal(literal.to_owned())),
+        }
+    }
+
+    /// Match `path` against the template, returning the values of its variables in the order of
+    /// [`field_paths`](Self::field_paths).
+    pub(crate) fn matches(&self, path: &str) -> Option<Vec<String>> {
+        let path = path.strip_prefix('/')?;
+        let path = match &self.verb {
+            Some(verb) => path.strip_suffix(verb.as_str())?.strip_suffix(':')?,
+            None => path,
+        };
+        let parts = if path.is_empty() {
+            Vec::new()
+        } else {
+            path.split('/').collect::<Vec<_>>()
+        };
+
+        // The `**` segment, if any, matches the parts left by the other segments.
+        let deep = self
+            .segments
+            .iter()
+            .position(|segment| *segment == Segment::DeepWildcard);
+        let fixed = self.segments.len() - usize::from(deep.is_some());
+        if parts.len() < fixed || (deep.is_none() && parts.len() != fixed) {
+            return None;
+        }
+
+        // The range of `parts` matched by each template segment.
+        let mut ranges = Vec::with_capacity(self.segments.len());
+        let mut next = 0;
+        for (i, segment) in self.segments.iter().enumerate() {
+            let len = if Some(i) == deep {
+                parts.len() - fixed
+            } else {
+                1
+            };
+            if let Segment::Literal(literal) = segment {
+                if decode(parts[next])? != *literal {
+                    return None;
+                }
+            }
+            ranges.push(next..next + len);
+            next += len;
+        }
+
+        self.variables
+            .iter()
+            .map(|variable| {
+                let start = ranges[variable.start].start;
+                let end = ranges[variable.end - 1].end;
+                let value = parts[start..end]
+                    .iter()
+                    .map(|part| decode(part))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(value.join("/"))
+            })
+            .collect()
+    }
+
+    /// The field paths bound by the variables of the template.
+    pub(crate) fn field_paths(&self) -> impl Iterator<Item = &[String]> {
+        self.variables
+            .iter()
+            .map(|variable| &variable.field_path[..])
+    }
+}
+
+fn decode(part: &str) -> Option<String> {
+    percent_decode_str(part)
+        .decode_utf8()
+        .ok()
+        .map(|part| part.into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn captures(template: &str, path: &str) -> Option<Vec<(String, String)>> {
+        let template = PathTemplate::parse(template).unwrap();
+        template.matches(path).map(|values| {
+            template
+                .field_paths()
+                .map(|field_path| field_path.join("."))
+                .zip(values)
+                .collect()
+        })
+    }
+
+    fn capture(field_path: &str, value: &str) -> (String, String) {
+        (field_path.to_owned(), value.to_owned())
+    }
+
+    #[test]
+    fn literals() {
+        assert_eq!(captures("/v1/shelves", "/v1/shelves"), Some(vec![]));
+        assert_eq!(captures("/v1/shelves", "/v1/shelves/1"), None);
+        assert_eq!(captures("/v1/shelves", "/v1"), None);
+    }
+
+    #[test]
+    fn variables() {
+        assert_eq!(
+            captures("/v1/{name=shelves/*}", "/v1/shelves/1"),
+            Some(vec![capture("name", "shelves/1")])
+        );
+        assert_eq!(captures("/v1/{name=shelves/*}", "/v1/books/1"), None);
+        assert_eq!(
+            captures(
+                "/v1/shelves/{shelf}/books/{book.id}",
+                "/v1/shelves/a%20b/books/2"
+            ),
+            Some(vec![capture("shelf", "a b"), capture("book.id", "2")])
+        );
+    }
+
+    #[test]
+    fn deep_wildcards() {
+        assert_eq!(
+            captures("/v1/{name=files/**}", "/v1/files/a/b/c"),
+            Some(vec![capture("name", "files/a/b/c")])
+        );
+        assert_eq!(
+            captures("/v1/{name=files/**}/meta", "/v1/files/a/b/meta"),
+            Some(vec![capture("name", "files/a/b")])
+        );
+        assert_eq!(
+            captures("/v1/{name=files/**}", "/v1/files"),
+            Some(vec![capture("name", "files")])
+        );
+    }
+
+    #[test]
+    fn verbs() {
+        assert_eq!(
+            captures("/v1/{name=shelves/*}:publish", "/v1/shelves/1:publish"),
+            Some(vec![capture("name", "shelves/1")])
+        );
+        assert_eq!(
+            captures("/v1/{name=shelves/*}:publish", "/v1/shelves/1"),
+            None
+        );
+        assert_eq!(
+            captures("/v1/shelves:batchGet", "/v1/shelves:batchGet"),
+            Some(vec![])
+        );
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for template in [
+            "v1/shelves",
+            "/v1/shelves/",
+            "/v1/{name",
+            "/v1/{=shelves/*}",
+            "/v1/{name={id}}",
+            "/v1/**/{name=**}",
+            "/v1//shelves",
+        ] {
+            assert!(PathTemplate::parse(template).is_err(), "{template}");
+        }
+    }
+}
@@ -0,0 +1,620 @@
+//! grpc-web over WebSocket, as spoken by the [improbable-eng] clients.
+//!
+//! Browsers can't stream request bodies over `fetch`, so the improbable-eng clients carry
+//! client-streaming and bi-directional streaming calls over a WebSocket negotiating the
+//! `grpc-websockets` sub-protocol:
+//!
+//!  * the first client message holds the request headers, as an HTTP/1 headers block;
+//!  * each following client message starts with a flag byte, `0x00` followed by grpc frames of
+//!    the request body, or `0x01` when the client is done sending;
+//!  * the server sends a grpc-web headers frame, then the grpc-web frames of the response body,
+//!    trailers included, and closes the WebSocket.
+//!
+//! The [`tunnel`] module of the `tunnel` feature carries whole gRPC connections over
+//! WebSockets instead.
+//!
+//! [improbable-eng]: https://github.com/improbable-eng/grpc-web
+
+use std::fmt;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use futures_util::{stream, SinkExt as _, StreamExt as _};
+use http::{header, HeaderMap, HeaderName, HeaderValue, Method, Request, Response, StatusCode};
+use http::{Uri, Version};
+use http_body::Frame;
+use hyper_util::rt::TokioIo;
+use pin_project::pin_project;
+use sha1::{Digest as _, Sha1};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::{CloseFrame, Role, WebSocketConfig};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::WebSocketStream;
+use tonic::body::Body;
+use tonic::metadata::GRPC_CONTENT_TYPE;
+use tonic::Status;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::{debug, trace};
+
+use crate::call::content_types::GRPC_WEB_PROTO;
+use crate::call::{make_trailers_frame, Encoding, GrpcWebCall};
+use crate::BoxError;
+
+#[cfg(feature = "tunnel")]
+pub mod tunnel;
+
+const PROTOCOL: &str = "grpc-websockets";
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+// The largest WebSocket message accepted from a client.
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+const FLAG_DATA: u8 = 0x00;
+const FLAG_FINISH_SEND: u8 = 0x01;
+
+type SplitSink<IO> = stream::SplitSink<WebSocketStream<IO>, Message>;
+type SplitStream<IO> = stream::SplitStream<WebSocketStream<IO>>;
+
+/// Layer serving grpc-web calls over WebSocket.
+///
+/// WebSocket upgrade requests negotiating the `grpc-websockets` sub-protocol are answered by the
+/// layer, which then calls the inner service with plain grpc requests. All other requests are
+/// passed through, so the layer is usually stacked on top of the [`GrpcWebLayer`]:
+///
+/// ```ignore
+/// Server::builder()
+///    .accept_http1(true)
+///    .accept_http1_upgrades(true)
+///    .layer(GrpcWebSocketLayer::new())
+///    .layer(GrpcWebLayer::new())
+///    .add_service(greeter)
+///    .serve(addr)
+///    .await?;
+/// ```
+///
+/// [`GrpcWebLayer`]: crate::GrpcWebLayer
+#[derive(Debug, Default, Clone)]
+pub struct GrpcWebSocketLayer {
+    _priv: (),
+}
+
+impl GrpcWebSocketLayer {
+    /// Create a new grpc-web WebSocket layer.
+    pub fn new() -> GrpcWebSocketLayer {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for GrpcWebSocketLayer {
+    type Service = GrpcWebSocketService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcWebSocketService { inner }
+    }
+}
+
+/// Service serving grpc-web calls over WebSocket.
+#[derive(Debug, Clone)]
+pub struct GrpcWebSocketService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for GrpcWebSocketService<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Into<BoxError>,
+    ReqBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ReqBody::Error: Into<BoxError>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError> + fmt::Display,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        if !is_upgrade(&req, PROTOCOL) {
+            return ResponseFuture {
+                case: Case::Other {
+                    future: self.inner.call(req.map(Body::new)),
+                },
+            };
+        }
+
+        let res = handshake(&req, PROTOCOL);
+        if res.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return ResponseFuture::immediate(res);
+        }
+
+        trace!(kind = "websocket", path = ?req.uri().path());
+
+        let uri = req.uri().clone();
+        let on_upgrade = hyper::upgrade::on(&mut req);
+        let inner = self.inner.clone();
+        tokio::spawn(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => serve(TokioIo::new(upgraded), inner, uri).await,
+                Err(e) => debug!("failed upgrading to websocket: {}", e),
+            }
+        });
+
+        ResponseFuture::immediate(res)
+    }
+}
+
+/// Response future for the [`GrpcWebSocketService`].
+#[pin_project]
+#[must_use = "futures do nothing unless polled"]
+pub struct ResponseFuture<F> {
+    #[pin]
+    case: Case<F>,
+}
+
+#[pin_project(project = CaseProj)]
+enum Case<F> {
+    Other {
+        #[pin]
+        future: F,
+    },
+    Immediate {
+        res: Option<Response<Body>>,
+    },
+}
+
+impl<F> ResponseFuture<F> {
+    fn immediate(res: Response<Body>) -> Self {
+        Self {
+            case: Case::Immediate { res: Some(res) },
+        }
+    }
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    type Output = Result<Response<Body>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().case.project() {
+            CaseProj::Other { future } => {
+                let res = ready!(future.poll(cx))?;
+                Poll::Ready(Ok(res.map(Body::new)))
+            }
+            CaseProj::Immediate { res } => {
+                Poll::Ready(Ok(res.take().expect("polled after completion")))
+            }
+        }
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+fn immediate(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+// Whether `req` is a WebSocket upgrade request negotiating the `protocol` sub-protocol.
+fn is_upgrade<B>(req: &Request<B>, protocol: &str) -> bool {
+    req.method() == Method::GET
+        && has_token(req.headers(), header::CONNECTION, "upgrade")
+        && has_token(req.headers(), header::UPGRADE, "websocket")
+        && has_token(req.headers(), header::SEC_WEBSOCKET_PROTOCOL, protocol)
+}
+
+// The response to the upgrade request `req`, switching protocols unless the request is invalid.
+fn handshake<B>(req: &Request<B>, protocol: &'static str) -> Response<Body> {
+    let headers = req.headers();
+    if !headers
+        .get(header::SEC_WEBSOCKET_VERSION)
+        .is_some_and(|version| version == "13")
+    {
+        debug!(error = "unsupported websocket version", version = ?headers.get(header::SEC_WEBSOCKET_VERSION));
+        let mut res = immediate(StatusCode::UPGRADE_REQUIRED);
+        res.headers_mut().insert(
+            header::SEC_WEBSOCKET_VERSION,
+            HeaderValue::from_static("13"),
+        );
+        return res;
+    }
+
+    let Some(accept) = headers
+        .get(header::SEC_WEBSOCKET_KEY)
+        .map(|key| accept_key(key.as_bytes()))
+    else {
+        debug!(error = "missing websocket key");
+        return immediate(StatusCode::BAD_REQUEST);
+    };
+
+    let mut res = immediate(StatusCode::SWITCHING_PROTOCOLS);
+    let headers = res.headers_mut();
+    headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+    headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+    headers.insert(
+        header::SEC_WEBSOCKET_ACCEPT,
+        HeaderValue::from_str(&accept).expect("base64 is a valid header value"),
+    );
+    headers.insert(
+        header::SEC_WEBSOCKET_PROTOCOL,
+        HeaderValue::from_static(protocol),
+    );
+    res
+}
+
+// Whether the comma-separated values of the `name` headers include `token`.
+fn has_token(headers: &HeaderMap, name: HeaderName, token: &str) -> bool {
+    headers
+        .get_all(name)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|value| value.trim().eq_ignore_ascii_case(token))
+}
+
+// Serves a single call over the WebSocket `io`.
+async fn serve<S, ResBody, IO>(io: IO, mut inner: S, uri: Uri)
+where
+    S: Service<Request<Body>, Response = Response<ResBody>>,
+    S::Error: Into<BoxError>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<BoxError> + fmt::Display,
+    IO: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let ws = WebSocketStream::from_raw_socket(io, Role::Server, Some(config())).await;
+    let (sink, mut stream) = ws.split();
+    let sink = Arc::new(Mutex::new(sink));
+
+    let headers = match read_message(&mut stream).await {
+        Ok(Some(message)) => decode_headers(&message),
+        Ok(None) => return,
+        Err(code) => {
+            close(&mut *sink.lock().await, code).await;
+            return;
+        }
+    };
+
+    let (tx, rx) = mpsc::channel(16);
+    let pump = tokio::spawn(pump(stream, sink.clone(), tx));
+
+    let mut req = Request::new(Body::new(ClientStream { rx }));
+    *req.method_mut() = Method::POST;
+    *req.uri_mut() = uri;
+    *req.version_mut() = Version::HTTP_2;
+    *req.headers_mut() = headers;
+    req.headers_mut()
+        .insert(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+    req.headers_mut()
+        .insert(header::TE, HeaderValue::from_static("trailers"));
+    req.headers_mut().remove(header::CONTENT_LENGTH);
+
+    let ready: Result<(), BoxError> = poll_fn(|cx| inner.poll_ready(cx)).await.map_err(Into::into);
+    let res = match ready {
+        Ok(()) => inner.call(req).await.map_err(Into::into),
+        Err(e) => Err(e),
+    };
+
+    let code = match res {
+        Ok(res) => send_response(res, &sink).await,
+        Err(e) => {
+            debug!("websocket call failed: {}", e);
+            CloseCode::Error
+        }
+    };
+
+    pump.abort();
+    close(&mut *sink.lock().await, code).await;
+}
+
+// The configuration of the WebSockets, limiting the size of the messages of the clients.
+fn config() -> WebSocketConfig {
+    WebSocketConfig::default()
+        .max_message_size(Some(MAX_MESSAGE_SIZE))
+        .max_frame_size(Some(MAX_MESSAGE_SIZE))
+}
+
+// Reads the next data message. The control frames read meanwhile are answered by the WebSocket.
+//
+// Returns `None` once the WebSocket is closed, and the close code with which to fail the
+// WebSocket on protocol errors.
+async fn read_message<IO>(stream: &mut SplitStream<IO>) -> Result<Option<Bytes>, CloseCode>
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    loop {
+        match stream.next().await {
+            Some(Ok(Message::Binary(data))) => return Ok(Some(data)),
+            Some(Ok(Message::Text(text))) => return Ok(Some(text.into())),
+            Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {}
+            Some(Ok(Message::Close(_))) | None => return Ok(None),
+            Some(Err(WsError::Capacity(e))) => {
+                debug!("websocket message too large: {}", e);
+                return Err(CloseCode::Size);
+            }
+            Some(Err(WsError::Protocol(e))) => {
+                debug!("invalid websocket frame: {}", e);
+                return Err(CloseCode::Protocol);
+            }
+            Some(Err(e)) => {
+                debug!("failed reading websocket: {}", e);
+                return Ok(None);
+            }
+        }
+    }
+}
+
+// Forwards the request messages of the client to `tx`, until the client is done sending.
+async fn pump<IO>(
+    mut stream: SplitStream<IO>,
+    sink: Arc<Mutex<SplitSink<IO>>>,
+    tx: mpsc::Sender<Result<Bytes, Status>>,
+) where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut tx = Some(tx);
+
+    loop {
+        let message = match read_message(&mut stream).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(code) => {
+                if let Some(tx) = tx.take() {
+                    let _ = tx
+                        .send(Err(Status::internal(
+                            "tonic-web: invalid websocket message",
+                        )))
+                        .await;
+                }
+                close(&mut *sink.lock().await, code).await;
+                break;
+            }
+        };
+
+        match message.first() {
+            Some(&FLAG_DATA) => {
+                let sent = match &tx {
+                    Some(sender) => sender.send(Ok(message.slice(1..))).await.is_ok(),
+                    None => true,
+                };
+                // The request body was dropped.
+                if !sent {
+                    tx = None;
+                }
+            }
+            Some(&FLAG_FINISH_SEND) => tx = None,
+            _ => {}
+        }
+    }
+}
+
+async fn send_response<B, IO>(res: Response<B>, sink: &Mutex<SplitSink<IO>>) -> CloseCode
+where
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: fmt::Display,
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let (mut parts, body) = res.into_parts();
+    parts.headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(GRPC_WEB_PROTO),
+    );
+
+    let headers = make_trailers_frame(parts.headers);
+    if sink
+        .lock()
+        .await
+        .send(Message::Binary(headers))
+        .await
+        .is_err()
+    {
+        return CloseCode::Error;
+    }
+
+    let mut body = Box::pin(GrpcWebCall::response(body, Encoding::None));
+    loop {
+        let frame = match poll_fn(|cx| http_body::Body::poll_frame(body.as_mut(), cx)).await {
+            Some(Ok(frame)) => frame,
+            Some(Err(status)) => {
+                debug!("failed sending websocket response: {}", status);
+                return CloseCode::Error;
+            }
+            None => return CloseCode::Normal,
+        };
+
+        if let Ok(data) = frame.into_data() {
+            if sink.lock().await.send(Message::Binary(data)).await.is_err() {
+                return CloseCode::Error;
+            }
+        }
+    }
+}
+
+// Closes the WebSocket with `code`, unless it is closed already.
+async fn close<IO>(sink: &mut SplitSink<IO>, code: CloseCode)
+where
+    IO: AsyncRead + AsyncWrite + Unpin,
+{
+    let frame = CloseFrame {
+        code,
+        reason: Default::default(),
+    };
+    let _ = sink.send(Message::Close(Some(frame))).await;
+    let _ = sink.close().await;
+}
+
+// Headers encoded as an HTTP/1 headers block. Invalid lines are dropped.
+fn decode_headers(block: &[u8]) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+
+    for line in block.split(|&b| b == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let Some(colon) = line.iter().position(|&b| b == b':') else {
+            continue;
+        };
+        let (name, value) = (trim(&line[..colon]), trim(&line[colon + 1..]));
+
+        if let (Ok(name), Ok(value)) =
+            (HeaderName::from_bytes(name), HeaderValue::from_bytes(value))
+        {
+            headers.append(name, value);
+        }
+    }
+
+    headers
+}
+
+fn trim(mut bytes: &[u8]) -> &[u8] {
+    while let [b, rest @ ..] = bytes {
+        if !b.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    while let [rest @ .., b] = bytes {
+        if !b.is_ascii_whitespace() {
+            break;
+        }
+        bytes = rest;
+    }
+    bytes
+}
+
+// The request body, fed by `pump`.
+struct ClientStream {
+    rx: mpsc::Receiver<Result<Bytes, Status>>,
+}
+
+impl http_body::Body for ClientStream {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        self.rx
+            .poll_recv(cx)
+            .map(|message| message.map(|message| message.map(Frame::data)))
+    }
+}
+
+// The `Sec-WebSocket-Accept` value answering the `Sec-WebSocket-Key` `key`.
+fn accept_key(key: &[u8]) -> String {
+    use base64::Engine as _;
+
+    let digest = Sha1::new()
+        .chain_update(key)
+        .chain_update(WEBSOCKET_GUID)
+        .finalize();
+    base64::engine::general_purpose::STANDARD.encode(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handshake_accept_key() {
+        // The example of RFC 6455, section 1.3.
+        assert_eq!(
+            accept_key(b"dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn decode_headers_block() {
+        let headers = decode_headers(b"x-grpc-web: 1\r\nAuthorization: Bearer a:b\r\ninvalid\r\n");
+
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["x-grpc-web"], "1");
+        assert_eq!(headers["authorization"], "Bearer a:b");
+    }
+
+    #[test]
+    fn upgrade_requests() {
+        let req = Request::builder()
+            .method(Method::GET)
+            .header(header::CONNECTION, "keep-alive, Upgrade")
+            .header(header::UPGRADE, "websocket")
+            .header(header::SEC_WEBSOCKET_PROTOCOL, "grpc-websockets")
+            .body(())
+            .unwrap();
+        assert!(is_upgrade(&req, PROTOCOL));
+
+        let (mut parts, ()) = req.into_parts();
+        parts.headers.remove(header::SEC_WEBSOCKET_PROTOCOL);
+        assert!(!is_upgrade(&Request::from_parts(parts, ()), PROTOCOL));
+    }
+
+    // A client frame, masked with a zero mask.
+    fn client_frame(head: u8, payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![head];
+        match payload.len() {
+            len if len < 126 => frame.push(0x80 | len as u8),
+            len => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+        }
+        frame.extend_from_slice(&[0; 4]);
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    async fn read_frames(frames: &[u8]) -> Result<Option<Bytes>, CloseCode> {
+        use tokio::io::AsyncWriteExt as _;
+
+        let (mut client, server) = tokio::io::duplex(1024);
+        client.write_all(frames).await.unwrap();
+
+        let ws = WebSocketStream::from_raw_socket(server, Role::Server, Some(config())).await;
+        let (_sink, mut stream) = ws.split();
+        read_message(&mut stream).await
+    }
+
+    #[tokio::test]
+    async fn read_fragmented_message() {
+        let mut frames = client_frame(0x02, b"hel");
+        frames.extend(client_frame(0x89, b"ping"));
+        frames.extend(client_frame(0x80, b"lo"));
+
+        assert_eq!(read_frames(&frames).await, Ok(Some(Bytes::from("hello"))));
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_control_frames() {
+        // A ping longer than 125 bytes.
+        let frames = client_frame(0x89, &[0; 126]);
+        assert_eq!(read_frames(&frames).await, Err(CloseCode::Protocol));
+
+        // A fragmented ping.
+        let frames = client_frame(0x09, b"ping");
+        assert_eq!(read_frames(&frames).await, Err(CloseCode::Protocol));
+    }
+
+    #[tokio::test]
+    async fn reject_unmasked_frames() {
+        let frames = [0x82, 0x02, b'h', b'i'];
+        assert_eq!(read_frames(&frames).await, Err(CloseCode::Protocol));
+    }
+}
@@ -0,0 +1,495 @@
+//! gRPC over a WebSocket tunnel.
+//!
+//! Some networks only let WebSocket traffic out, e.g. through proxies allowing `ws` and `wss`
+//! egress only. The tunnel carries the whole HTTP/2 connection of a channel over a single
+//! WebSocket negotiating the `grpc-tunnel` sub-protocol, as the payload of its binary messages,
+//! so calls, streaming, flow control and keep-alives work as over TCP:
+//!
+//! * on the client, the [`TunnelConnector`] opens the WebSocket, in place of the TCP connector
+//!   given to [`Endpoint::connect_with_connector`];
+//! * on the server, the [`TunnelAcceptor`] answers the WebSocket upgrade requests, and the
+//!   [`TunnelIncoming`] stream yields the tunnelled connections to a gRPC server.
+//!
+//! ```ignore
+//! // The server answering the upgrade requests, and the one serving the tunnelled connections.
+//! let (acceptor, incoming) = tonic_web::websocket::tunnel::acceptor();
+//! tokio::spawn(
+//!     Server::builder()
+//!         .accept_http1(true)
+//!         .accept_http1_upgrades(true)
+//!         .add_routes(Routes::default().http_service(acceptor))
+//!         .serve(addr),
+//! );
+//! Server::builder()
+//!     .add_service(GreeterServer::new(greeter))
+//!     .serve_with_incoming(incoming)
+//!     .await?;
+//!
+//! // The client, whose calls go through the WebSocket.
+//! let connector = TunnelConnector::new("ws://gateway.example.com/tunnel".parse()?);
+//! let channel = Endpoint::from_static("http://greeter")
+//!     .connect_with_connector(connector)
+//!     .await?;
+//! ```
+//!
+//! [`Endpoint::connect_with_connector`]: https://docs.rs/tonic/latest/tonic/transport/struct.Endpoint.html#method.connect_with_connector
+
+use std::collections::hash_map::RandomState;
+use std::convert::Infallible;
+use std::future::{self, Future, Ready};
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use base64::Engine as _;
+use bytes::Bytes;
+use futures_util::Sink;
+use http::uri::{Parts, Scheme};
+use http::{header, HeaderValue, Request, Response, StatusCode, Uri};
+use hyper::upgrade::Upgraded;
+use hyper_util::rt::TokioIo;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_stream::Stream;
+use tokio_tungstenite::tungstenite::protocol::{Role, WebSocketConfig};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::WebSocketStream;
+use tonic::body::Body;
+use tonic::transport::server::{Connected, TcpConnectInfo};
+use tower_service::Service;
+use tracing::{debug, trace};
+
+use super::{accept_key, handshake, immediate, is_upgrade, MAX_MESSAGE_SIZE};
+use crate::util::base64::STANDARD;
+use crate::BoxError;
+
+const PROTOCOL: &str = "grpc-tunnel";
+
+// The largest payload of the binary messages sent.
+const MAX_FRAME_SIZE: usize = 64 * 1024;
+
+/// Creates a [`TunnelAcceptor`] answering the WebSocket upgrade requests, and the
+/// [`TunnelIncoming`] stream of their tunnelled connections.
+///
+/// The stream ends once every clone of the acceptor is dropped.
+pub fn acceptor() -> (TunnelAcceptor, TunnelIncoming) {
+    let (tx, rx) = mpsc::channel(64);
+    (TunnelAcceptor { tx }, TunnelIncoming { rx })
+}
+
+/// Service answering the WebSocket upgrade requests of the [`TunnelConnector`].
+///
+/// The service must be served by a server supporting HTTP/1 upgrades, e.g. by a tonic server
+/// accepting HTTP/1 and its upgrades with [`Routes::http_service`], and it answers requests that
+/// aren't `grpc-tunnel` upgrade requests with `426 Upgrade Required`.
+///
+/// [`Routes::http_service`]: https://docs.rs/tonic/latest/tonic/service/struct.Routes.html#method.http_service
+#[derive(Debug, Clone)]
+pub struct TunnelAcceptor {
+    tx: mpsc::Sender<TunnelStream>,
+}
+
+impl<B> Service<Request<B>> for TunnelAcceptor {
+    type Response = Response<Body>;
+    type Error = Infallible;
+    type Future = Ready<Result<Response<Body>, Infallible>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, mut req: Request<B>) -> Self::Future {
+        if !is_upgrade(&req, PROTOCOL) {
+            let mut res = immediate(StatusCode::UPGRADE_REQUIRED);
+            res.headers_mut()
+                .insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+            return future::ready(Ok(res));
+        }
+        if self.tx.is_closed() {
+            return future::ready(Ok(immediate(StatusCode::SERVICE_UNAVAILABLE)));
+        }
+
+        let res = handshake(&req, PROTOCOL);
+        if res.status() != StatusCode::SWITCHING_PROTOCOLS {
+            return future::ready(Ok(res));
+        }
+
+        trace!(kind = "websocket-tunnel", path = ?req.uri().path());
+
+        // The tunnelled connection has the address of the upgraded one, when known.
+        let connect_info =
+            req.extensions()
+                .get::<TcpConnectInfo>()
+                .cloned()
+                .unwrap_or(TcpConnectInfo {
+                    local_addr: None,
+                    remote_addr: None,
+                });
+        let on_upgrade = hyper::upgrade::on(&mut req);
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            match on_upgrade.await {
+                Ok(upgraded) => {
+                    let stream = TunnelStream::new(upgraded, Role::Server, connect_info).await;
+                    let _ = tx.send(stream).await;
+                }
+                Err(e) => debug!("failed upgrading to websocket: {}", e),
+            }
+        });
+
+        future::ready(Ok(res))
+    }
+}
+
+/// The stream of the connections tunnelled through a [`TunnelAcceptor`], to serve with
+/// `Server::serve_with_incoming`.
+#[derive(Debug)]
+pub struct TunnelIncoming {
+    rx: mpsc::Receiver<TunnelStream>,
+}
+
+impl Stream for TunnelIncoming {
+    type Item = Result<TunnelStream, Infallible>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx).map(|stream| stream.map(Ok))
+    }
+}
+
+/// Connector opening a WebSocket tunnel to a [`TunnelAcceptor`].
+///
+/// Every connection of the channel is tunnelled through a new WebSocket to the URI of the
+/// connector, whatever the URI of the endpoint, which is still the `:authority` of the calls.
+/// The WebSocket is opened over the stream of `C`, called with the URI of the connector with the
+/// `http` or `https` scheme for `ws` or `wss` respectively; set a TLS connector with
+/// [`with_connector`](Self::with_connector) for `wss` URIs.
+#[derive(Debug, Clone)]
+pub struct TunnelConnector<C = TcpConnector> {
+    uri: Uri,
+    connector: C,
+}
+
+impl TunnelConnector {
+    /// Create a connector opening WebSockets to `uri`, e.g. `ws://gateway.example.com/tunnel`,
+    /// over TCP.
+    pub fn new(uri: Uri) -> Self {
+        Self::with_connector(uri, TcpConnector::default())
+    }
+}
+
+impl<C> TunnelConnector<C> {
+    /// Create a connector opening WebSockets to `uri` over the streams of `connector`.
+    pub fn with_connector(uri: Uri, connector: C) -> Self {
+        Self { uri, connector }
+    }
+}
+
+impl<C> Service<Uri> for TunnelConnector<C>
+where
+    C: Service<Uri>,
+    C::Response: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    C::Future: Send + 'static,
+    C::Error: Into<BoxError>,
+{
+    type Response = TunnelStream;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<TunnelStream>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.connector.poll_ready(cx).map_err(io::Error::other)
+    }
+
+    fn call(&mut self, _endpoint: Uri) -> Self::Future {
+        let uri = self.uri.clone();
+        let scheme = match uri.scheme_str() {
+            Some("ws") => Scheme::HTTP,
+            Some("wss") => Scheme::HTTPS,
+            _ => {
+                let e = io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid websocket uri '{uri}'"),
+                );
+                return Box::pin(async move { Err(e) });
+            }
+        };
+
+        let mut parts = Parts::from(uri.clone());
+        parts.scheme = Some(scheme);
+        let connecting = match Uri::from_parts(parts) {
+            Ok(http_uri) => self.connector.call(http_uri),
+            Err(e) => return Box::pin(async move { Err(io::Error::other(e)) }),
+        };
+
+        Box::pin(async move {
+            let io = connecting.await.map_err(io::Error::other)?;
+            connect(io, uri).await
+        })
+    }
+}
+
+// Opens the WebSocket to `uri` over `io`.
+async fn connect<IO>(io: IO, uri: Uri) -> io::Result<TunnelStream>
+where
+    IO: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+{
+    let (mut sender, conn) = hyper::client::conn::http1::handshake(TokioIo::new(io))
+        .await
+        .map_err(io::Error::other)?;
+    tokio::spawn(async move {
+        if let Err(e) = conn.with_upgrades().await {
+            debug!("websocket tunnel connection failed: {}", e);
+        }
+    });
+
+    let key = STANDARD.encode(random::<16>());
+    let authority = uri.authority().map_or("", |authority| authority.as_str());
+    let path = uri.path_and_query().map_or("/", |path| path.as_str());
+    let req = Request::get(path)
+        .header(header::HOST, authority)
+        .header(header::CONNECTION, "upgrade")
+        .header(header::UPGRADE, "websocket")
+        .header(header::SEC_WEBSOCKET_VERSION, "13")
+        .header(header::SEC_WEBSOCKET_KEY, &key)
+        .header(header::SEC_WEBSOCKET_PROTOCOL, PROTOCOL)
+        .body(Body::empty())
+        .map_err(io::Error::other)?;
+
+    let mut res = sender.send_request(req).await.map_err(io::Error::other)?;
+    let accepted = res
+        .headers()
+        .get(header::SEC_WEBSOCKET_ACCEPT)
+        .is_some_and(|accept| accept.as_bytes() == accept_key(key.as_bytes()).as_bytes());
+    if res.status() != StatusCode::SWITCHING_PROTOCOLS || !accepted {
+        return Err(io::Error::new(
+            io::ErrorKind::ConnectionRefused,
+            format!("websocket handshake failed with status {}", res.status()),
+        ));
+    }
+
+    let upgraded = hyper::upgrade::on(&mut res)
+        .await
+        .map_err(io::Error::other)?;
+    let connect_info = TcpConnectInfo {
+        local_addr: None,
+        remote_addr: None,
+    };
+    Ok(TunnelStream::new(upgraded, Role::Client, connect_info).await)
+}
+
+/// The default connector of the [`TunnelConnector`], opening TCP connections.
+#[derive(Debug, Default, Clone)]
+pub struct TcpConnector {
+    _priv: (),
+}
+
+impl Service<Uri> for TcpConnector {
+    type Response = TcpStream;
+    type Error = io::Error;
+    type Future = Pin<Box<dyn Future<Output = io::Result<TcpStream>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        Box::pin(async move {
+            let host = uri
+                .host()
+                .map(|host| host.trim_start_matches('[').trim_end_matches(']'))
+                .ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, format!("no host in '{uri}'"))
+                })?;
+            let port = uri.port_u16().unwrap_or(match uri.scheme() {
+                Some(scheme) if *scheme == Scheme::HTTPS => 443,
+                _ => 80,
+            });
+
+            let stream = TcpStream::connect((host, port)).await?;
+            stream.set_nodelay(true)?;
+            Ok(stream)
+        })
+    }
+}
+
+/// A connection tunnelled through a WebSocket.
+///
+/// The bytes written to the stream are sent as binary messages, and the payloads of the binary
+/// messages received are read from it.
+pub struct TunnelStream {
+    ws: WebSocketStream<TokioIo<Upgraded>>,
+    connect_info: TcpConnectInfo,
+    // The payload of the last binary message, not read yet.
+    payload: Bytes,
+    read_closed: bool,
+}
+
+impl TunnelStream {
+    async fn new(upgraded: Upgraded, role: Role, connect_info: TcpConnectInfo) -> Self {
+        let config = WebSocketConfig::default()
+            .max_message_size(Some(MAX_MESSAGE_SIZE))
+            .max_frame_size(Some(MAX_MESSAGE_SIZE));
+        let ws = WebSocketStream::from_raw_socket(TokioIo::new(upgraded), role, Some(config)).await;
+
+        Self {
+            ws,
+            connect_info,
+            payload: Bytes::new(),
+            read_closed: false,
+        }
+    }
+
+    // Reads the payload of the binary messages, giving at most `remaining` bytes to `put`.
+    fn poll_read_payload(
+        &mut self,
+        cx: &mut Context<'_>,
+        remaining: usize,
+        put: impl FnOnce(&[u8]),
+    ) -> Poll<io::Result<()>> {
+        loop {
+            if !self.payload.is_empty() {
+                let n = remaining.min(self.payload.len());
+                put(&self.payload.split_to(n));
+                return Poll::Ready(Ok(()));
+            }
+            if self.read_closed {
+                return Poll::Ready(Ok(()));
+            }
+
+            // The WebSocket answers the control frames itself.
+            match ready!(Pin::new(&mut self.ws).poll_next(cx)) {
+                Some(Ok(Message::Binary(data))) => self.payload = data,
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_))) => {}
+                Some(Ok(Message::Close(_))) | None => self.read_closed = true,
+                Some(Ok(Message::Text(_))) => {
+                    return Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "unexpected websocket text message",
+                    )))
+                }
+                Some(Err(e)) => return Poll::Ready(Err(io_error(e))),
+            }
+        }
+    }
+
+    fn poll_write_payload(&mut self, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+        ready!(Pin::new(&mut self.ws).poll_ready(cx)).map_err(io_error)?;
+
+        let n = buf.len().min(MAX_FRAME_SIZE);
+        Pin::new(&mut self.ws)
+            .start_send(Message::Binary(Bytes::copy_from_slice(&buf[..n])))
+            .map_err(io_error)?;
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush_io(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.ws).poll_flush(cx).map_err(io_error)
+    }
+
+    fn poll_shutdown_io(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match ready!(Pin::new(&mut self.ws).poll_close(cx)) {
+            Ok(()) | Err(WsError::AlreadyClosed) => {}
+            Err(e) => return Poll::Ready(Err(io_error(e))),
+        }
+        Pin::new(self.ws.get_mut()).poll_shutdown(cx)
+    }
+}
+
+impl AsyncRead for TunnelStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let remaining = buf.remaining();
+        self.get_mut()
+            .poll_read_payload(cx, remaining, |data| buf.put_slice(data))
+    }
+}
+
+impl AsyncWrite for TunnelStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_write_payload(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_flush_io(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_shutdown_io(cx)
+    }
+}
+
+impl hyper::rt::Read for TunnelStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        mut buf: hyper::rt::ReadBufCursor<'_>,
+    ) -> Poll<io::Result<()>> {
+        let remaining = buf.remaining();
+        self.get_mut()
+            .poll_read_payload(cx, remaining, |data| buf.put_slice(data))
+    }
+}
+
+impl hyper::rt::Write for TunnelStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_write_payload(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_flush_io(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_shutdown_io(cx)
+    }
+}
+
+impl Connected for TunnelStream {
+    type ConnectInfo = TcpConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.connect_info.clone()
+    }
+}
+
+impl std::fmt::Debug for TunnelStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TunnelStream")
+            .field("connect_info", &self.connect_info)
+            .finish()
+    }
+}
+
+fn io_error(e: WsError) -> io::Error {
+    match e {
+        WsError::Io(e) => e,
+        WsError::ConnectionClosed | WsError::AlreadyClosed => io::ErrorKind::BrokenPipe.into(),
+        e => io::Error::new(io::ErrorKind::InvalidData, e),
+    }
+}
+
+// Random bytes for the handshake keys, which must be unpredictable to intermediaries but need no
+// cryptographic strength.
+fn random<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    for chunk in bytes.chunks_mut(8) {
+        let random = RandomState::new().build_hasher().finish().to_ne_bytes();
+        chunk.copy_from_slice(&random[..chunk.len()]);
+    }
+    bytes
+}
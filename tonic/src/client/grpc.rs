@@ -6,7 +6,7 @@ use crate::{
     client::GrpcService,
     codec::{Codec, Decoder, Streaming},
     request::SanitizeHeaders,
-    Code, Request, Response, Status,
+    Code, GrpcMethodKind, Request, Response, Status,
 };
 use http::{
     header::{HeaderValue, CONTENT_TYPE, TE},
@@ -218,7 +218,7 @@ impl<T> Grpc<T> {
         M1: Send + Sync + 'static,
         M2: Send + Sync + 'static,
     {
-        let request = request.map(|m| tokio_stream::once(m));
+        let request = with_kind(request, GrpcMethodKind::Unary).map(|m| tokio_stream::once(m));
         self.client_streaming(request, path, codec).await
     }
 
@@ -238,6 +238,7 @@ impl<T> Grpc<T> {
         M1: Send + Sync + 'static,
         M2: Send + Sync + 'static,
     {
+        let request = with_kind(request, GrpcMethodKind::ClientStreaming);
         let (mut parts, body, extensions) =
             self.streaming(request, path, codec).await?.into_parts();
 
@@ -247,13 +248,13 @@ impl<T> Grpc<T> {
             .try_next()
             .await
             .map_err(|mut status| {
-                status.metadata_mut().merge(parts.clone());
+                status.metadata_mut().extend(parts.clone());
                 status
             })?
             .ok_or_else(|| Status::internal("Missing response message."))?;
 
         if let Some(trailers) = body.trailers().await? {
-            parts.merge(trailers);
+            parts.extend(trailers);
         }
 
         Ok(Response::from_parts(parts, message, extensions))
@@ -274,7 +275,8 @@ impl<T> Grpc<T> {
         M1: Send + Sync + 'static,
         M2: Send + Sync + 'static,
     {
-        let request = request.map(|m| tokio_stream::once(m));
+        let request =
+            with_kind(request, GrpcMethodKind::ServerStreaming).map(|m| tokio_stream::once(m));
         self.streaming(request, path, codec).await
     }
 
@@ -294,7 +296,7 @@ impl<T> Grpc<T> {
         M1: Send + Sync + 'static,
         M2: Send + Sync + 'static,
     {
-        let request = request
+        let request = with_kind(request, GrpcMethodKind::Streaming)
             .map(|s| {
                 EncodeBody::new_client(
                     codec.encoder(),
@@ -368,6 +370,15 @@ impl<T> Grpc<T> {
     }
 }
 
+// Marks `request` as a request of a `kind` method, unless it already is marked by the method
+// delegating to the caller.
+fn with_kind<T>(mut request: Request<T>, kind: GrpcMethodKind) -> Request<T> {
+    if request.extensions().get::<GrpcMethodKind>().is_none() {
+        request.extensions_mut().insert(kind);
+    }
+    request
+}
+
 impl GrpcConfig {
     fn prepare_request(&self, request: Request<Body>, path: PathAndQuery) -> http::Request<Body> {
         let mut parts = self.origin.clone().into_parts();
@@ -0,0 +1,154 @@
+//! Adapter using any HTTP client as the transport of the gRPC clients.
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use http::{
+    uri::{Authority, Scheme},
+    Uri, Version,
+};
+use pin_project::pin_project;
+use tower_service::Service;
+
+use crate::body::Body;
+
+/// Adapter using any HTTP client as the transport of [`Grpc`] and the generated clients.
+///
+/// The generated clients send requests with the path of their method only, e.g.
+/// `/helloworld.Greeter/SayHello`. `HttpTransport` sets the scheme and authority of each request
+/// from its origin, marks it as HTTP/2, and converts the responses of the client into
+/// [`Body`]s, so that any `Service<http::Request<Body>, Response = http::Response<B>>`, e.g. a
+/// `hyper-util` client pool, `reqwest` or a test double, backs a client:
+///
+/// ```ignore
+/// use hyper_util::{client::legacy::Client, rt::TokioExecutor};
+/// use tonic::{body::Body, client::HttpTransport};
+///
+/// let client = Client::builder(TokioExecutor::new())
+///     .http2_only(true)
+///     .build_http::<Body>();
+/// let transport = HttpTransport::new(client, "http://[::1]:50051".parse().unwrap());
+/// let mut greeter = GreeterClient::new(transport);
+/// ```
+///
+/// # Requirements
+///
+/// gRPC runs over HTTP/2, so the client must speak it, e.g. with prior knowledge for `http`
+/// origins, or negotiated with ALPN for `https` ones. The client must stream the bodies of the
+/// requests as they are produced, instead of buffering them, for client streaming calls. The
+/// body of each response must yield its trailers, which carry the final `grpc-status` of the
+/// call, except for trailers-only responses carrying it in their headers: responses ending
+/// without them are taken as successful, losing the statuses of failed calls. HTTP/1.1 clients
+/// usually drop trailers.
+///
+/// [`Grpc`]: super::Grpc
+#[derive(Clone)]
+pub struct HttpTransport<S> {
+    inner: S,
+    scheme: Scheme,
+    authority: Authority,
+}
+
+impl<S> HttpTransport<S> {
+    /// Create a new `HttpTransport` sending the requests to `origin` through `inner`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `origin` lacks a scheme or an authority.
+    pub fn new(inner: S, origin: Uri) -> Self {
+        let http::uri::Parts {
+            scheme, authority, ..
+        } = origin.into_parts();
+        Self {
+            inner,
+            scheme: scheme.expect("origin lacks a scheme"),
+            authority: authority.expect("origin lacks an authority"),
+        }
+    }
+
+    /// Get a reference to the inner client.
+    pub fn get_ref(&self) -> &S {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner client.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.inner
+    }
+
+    /// Consume `self`, returning the inner client.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for HttpTransport<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpTransport")
+            .field("inner", &self.inner)
+            .field("scheme", &self.scheme)
+            .field("authority", &self.authority)
+            .finish()
+    }
+}
+
+impl<S, B> Service<http::Request<Body>> for HttpTransport<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<B>>,
+    S::Error: Into<crate::BoxError>,
+    B: http_body::Body<Data = bytes::Bytes> + Send + 'static,
+    B::Error: Into<crate::BoxError>,
+{
+    type Response = http::Response<Body>;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let (mut head, body) = req.into_parts();
+        let mut uri = http::uri::Parts::from(head.uri);
+        uri.scheme = Some(self.scheme.clone());
+        uri.authority = Some(self.authority.clone());
+        head.uri = Uri::from_parts(uri).expect("valid uri");
+        head.version = Version::HTTP_2;
+
+        ResponseFuture {
+            inner: self.inner.call(http::Request::from_parts(head, body)),
+        }
+    }
+}
+
+/// Response future for [`HttpTransport`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+}
+
+impl<F, E, B> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<B>, E>>,
+    E: Into<crate::BoxError>,
+    B: http_body::Body<Data = bytes::Bytes> + Send + 'static,
+    B::Error: Into<crate::BoxError>,
+{
+    type Output = Result<http::Response<Body>, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let res = ready!(self.project().inner.poll(cx)).map_err(Into::into)?;
+        Poll::Ready(Ok(res.map(Body::new)))
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
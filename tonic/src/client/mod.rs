@@ -17,7 +17,14 @@
 //! [transport::Channel](../transport/struct.Channel.html#multiplexing-requests).
 
 mod grpc;
+pub mod http_transport;
+#[cfg(feature = "channel")]
+mod resume;
 mod service;
 
 pub use self::grpc::Grpc;
+#[doc(inline)]
+pub use self::http_transport::HttpTransport;
+#[cfg(feature = "channel")]
+pub use self::resume::ResumableStream;
 pub use self::service::GrpcService;
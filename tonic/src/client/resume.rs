@@ -0,0 +1,195 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+
+use tokio::time::Sleep;
+use tokio_stream::Stream;
+
+use crate::{Code, Status};
+
+/// A server-streaming call resumed where it stopped when it fails, e.g. when its connection is
+/// lost.
+///
+/// The call is issued by `call`, given the resume token of the last message received, or `None`
+/// the first time. The token of each message is extracted by `cursor`, and its meaning is up to the
+/// application, e.g. an offset or the key of the last item, the server resuming after it:
+///
+/// ```ignore
+/// let stream = ResumableStream::new(
+///     move |token: Option<String>| {
+///         let mut client = client.clone();
+///         async move {
+///             let req = ListRequest { after: token.unwrap_or_default() };
+///             client.list(req).await.map(Response::into_inner)
+///         }
+///     },
+///     |item: &Item| Some(item.key.clone()),
+/// )
+/// .backoff(Duration::from_millis(100), Duration::from_secs(5));
+/// ```
+///
+/// Failures with one of the [resume codes](ResumableStream::resume_codes) are retried after an
+/// exponential backoff, up to the [maximum attempts](ResumableStream::max_attempts) in a row
+/// without a message received. Other failures, and the failure of the last attempt, end the
+/// stream with their status.
+pub struct ResumableStream<F, Fut, S, C, K> {
+    call: F,
+    cursor: C,
+    token: Option<K>,
+    state: State<Fut, S>,
+    failures: u32,
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    resume_codes: Vec<Code>,
+}
+
+enum State<Fut, S> {
+    Idle,
+    Calling(Pin<Box<Fut>>),
+    Streaming(Pin<Box<S>>),
+    Sleeping(Pin<Box<Sleep>>),
+    Done,
+}
+
+impl<F, Fut, S, C, K> ResumableStream<F, Fut, S, C, K> {
+    /// Create a new `ResumableStream` issuing the call with `call`, resuming it with the tokens
+    /// extracted by `cursor`.
+    ///
+    /// The call is first issued once the stream is polled.
+    pub fn new(call: F, cursor: C) -> Self {
+        Self {
+            call,
+            cursor,
+            token: None,
+            state: State::Idle,
+            failures: 0,
+            max_attempts: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            resume_codes: vec![Code::Unavailable],
+        }
+    }
+
+    /// Set the delay before resuming after a first failure, doubled by each further failure in a
+    /// row up to `max`.
+    ///
+    /// Default is 100 milliseconds, up to 10 seconds.
+    pub fn backoff(mut self, base: Duration, max: Duration) -> Self {
+        self.base_delay = base;
+        self.max_delay = max;
+        self
+    }
+
+    /// Set the number of times the call is issued in a row without a message received before
+    /// giving up, including its first attempt.
+    ///
+    /// Default is 5.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Set the codes of the failures after which the call is resumed.
+    ///
+    /// Default is [`Code::Unavailable`], the code of transport failures, such as lost
+    /// connections.
+    pub fn resume_codes(mut self, codes: impl IntoIterator<Item = Code>) -> Self {
+        self.resume_codes = codes.into_iter().collect();
+        self
+    }
+
+    /// The resume token of the last message received with one.
+    pub fn token(&self) -> Option<&K> {
+        self.token.as_ref()
+    }
+
+    // Schedules the next attempt after `status`, or returns it if the call shouldn't be resumed.
+    fn fail(&mut self, status: Status) -> Option<Status> {
+        self.failures += 1;
+        if self.failures >= self.max_attempts || !self.resume_codes.contains(&status.code()) {
+            self.state = State::Done;
+            return Some(status);
+        }
+
+        let factor = 2u32.saturating_pow(self.failures - 1);
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        tracing::debug!(code = ?status.code(), ?delay, "resuming server-streaming call");
+        self.state = State::Sleeping(Box::pin(tokio::time::sleep(delay)));
+        None
+    }
+}
+
+// The futures and streams are boxed, and nothing else is pinned.
+impl<F, Fut, S, C, K> Unpin for ResumableStream<F, Fut, S, C, K> {}
+
+impl<F, Fut, S, C, K, T> Stream for ResumableStream<F, Fut, S, C, K>
+where
+    F: FnMut(Option<K>) -> Fut,
+    Fut: Future<Output = Result<S, Status>>,
+    S: Stream<Item = Result<T, Status>>,
+    C: FnMut(&T) -> Option<K>,
+    K: Clone,
+{
+    type Item = Result<T, Status>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match &mut this.state {
+                State::Idle => {
+                    let call = (this.call)(this.token.clone());
+                    this.state = State::Calling(Box::pin(call));
+                }
+                State::Calling(call) => match ready!(call.as_mut().poll(cx)) {
+                    Ok(stream) => this.state = State::Streaming(Box::pin(stream)),
+                    Err(status) => {
+                        if let Some(status) = this.fail(status) {
+                            return Poll::Ready(Some(Err(status)));
+                        }
+                    }
+                },
+                State::Streaming(stream) => match ready!(stream.as_mut().poll_next(cx)) {
+                    Some(Ok(message)) => {
+                        if let Some(token) = (this.cursor)(&message) {
+                            this.token = Some(token);
+                        }
+                        this.failures = 0;
+                        return Poll::Ready(Some(Ok(message)));
+                    }
+                    Some(Err(status)) => {
+                        if let Some(status) = this.fail(status) {
+                            return Poll::Ready(Some(Err(status)));
+                        }
+                    }
+                    None => {
+                        this.state = State::Done;
+                        return Poll::Ready(None);
+                    }
+                },
+                State::Sleeping(sleep) => {
+                    ready!(sleep.as_mut().poll(cx));
+                    this.state = State::Idle;
+                }
+                State::Done => return Poll::Ready(None),
+            }
+        }
+    }
+}
+
+impl<F, Fut, S, C, K: fmt::Debug> fmt::Debug for ResumableStream<F, Fut, S, C, K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResumableStream")
+            .field("token", &self.token)
+            .field("failures", &self.failures)
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("resume_codes", &self.resume_codes)
+            .finish()
+    }
+}
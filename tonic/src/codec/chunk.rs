@@ -0,0 +1,314 @@
+//! Transferring payloads larger than the max message size as chunks.
+//!
+//! Rather than raising the max message sizes of the client and the server to the size of the
+//! largest payload, a client can [`split`] a payload into [`Chunk`]s and send them as the
+//! request stream of a client streaming method, which the server [`reassemble`]s. Each chunk
+//! carries the checksum of its data, so a corrupted chunk fails the reassembly rather than
+//! corrupting the payload.
+//!
+//! Chunks are encoded by [`ChunkCodec`], both by clients:
+//!
+//! ```ignore
+//! let chunks = tonic::codec::chunk::split(payload, 1024 * 1024);
+//! let path = http::uri::PathAndQuery::from_static("/files.Files/Upload");
+//! grpc.ready().await?;
+//! let response = grpc
+//!     .client_streaming(tonic::Request::new(tokio_stream::iter(chunks)), path, ChunkCodec)
+//!     .await?;
+//! ```
+//!
+//! and by servers, e.g. by serving a
+//! [`ClientStreamingMethod`](crate::service::method::ClientStreamingMethod):
+//!
+//! ```ignore
+//! let upload = ClientStreamingMethod::new(
+//!     ChunkCodec,
+//!     tower::service_fn(|request: Request<Streaming<Chunk>>| async move {
+//!         let payload = reassemble(request.into_inner(), 64 * 1024 * 1024).await?;
+//!         store(payload).await?;
+//!         Ok(Response::new(Chunk::empty()))
+//!     }),
+//! );
+//!
+//! let routes = Routes::default().add_method("/files.Files/Upload", upload);
+//! ```
+//!
+//! Each encoded chunk is [`CHUNK_HEADER_SIZE`] bytes larger than its data, so the chunk size
+//! should leave room for the header under the max message size.
+
+use super::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use crate::{util::crc32c, Status};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use std::{future::poll_fn, pin::pin};
+use tokio_stream::Stream;
+
+/// The size of the header of an encoded chunk: its offset, the length of the payload and the
+/// checksum of its data.
+pub const CHUNK_HEADER_SIZE: usize = 8 + 8 + 4;
+
+/// A chunk of a payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    offset: u64,
+    total_len: u64,
+    checksum: u32,
+    data: Bytes,
+}
+
+impl Chunk {
+    /// Create a new chunk of the `data` at `offset` in a payload of `total_len` bytes.
+    pub fn new(offset: u64, total_len: u64, data: Bytes) -> Self {
+        Self {
+            offset,
+            total_len,
+            checksum: crc32c::checksum(&data),
+            data,
+        }
+    }
+
+    /// The only chunk of an empty payload, e.g. the response of a method receiving chunks.
+    pub fn empty() -> Self {
+        Self::new(0, 0, Bytes::new())
+    }
+
+    /// The offset of the data of the chunk in the payload.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// The length of the payload.
+    pub fn total_len(&self) -> u64 {
+        self.total_len
+    }
+
+    /// The CRC-32C checksum of the data of the chunk, as sent.
+    pub fn checksum(&self) -> u32 {
+        self.checksum
+    }
+
+    /// Whether the data of the chunk matches its checksum.
+    pub fn verify(&self) -> bool {
+        crc32c::checksum(&self.data) == self.checksum
+    }
+
+    /// The data of the chunk.
+    pub fn data(&self) -> &Bytes {
+        &self.data
+    }
+
+    /// Consumes the chunk, returning its data.
+    pub fn into_data(self) -> Bytes {
+        self.data
+    }
+}
+
+/// Split `payload` into chunks of at most `chunk_size` bytes.
+///
+/// An empty payload is split into a single empty chunk.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is zero.
+pub fn split(payload: Bytes, chunk_size: usize) -> Split {
+    assert!(chunk_size > 0, "chunk size must be greater than zero");
+    Split {
+        total_len: payload.len() as u64,
+        offset: 0,
+        payload: Some(payload),
+        chunk_size,
+    }
+}
+
+/// An iterator over the chunks of a payload, see [`split`].
+#[derive(Debug)]
+pub struct Split {
+    payload: Option<Bytes>,
+    total_len: u64,
+    offset: u64,
+    chunk_size: usize,
+}
+
+impl Iterator for Split {
+    type Item = Chunk;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let payload = self.payload.as_mut()?;
+        let data = payload.split_to(payload.len().min(self.chunk_size));
+        let chunk = Chunk::new(self.offset, self.total_len, data);
+        self.offset += chunk.data.len() as u64;
+        if payload.is_empty() {
+            self.payload = None;
+        }
+        Some(chunk)
+    }
+}
+
+/// Reassemble the payload of the chunks of `chunks`, split by [`split`].
+///
+/// Fails with `DataLoss` if the data of a chunk doesn't match its checksum, with
+/// `InvalidArgument` if the chunks are out of order or the payload is incomplete, and with
+/// `ResourceExhausted` if the payload is larger than `max_len` bytes. Errors of `chunks` are
+/// returned as is.
+pub async fn reassemble<S>(chunks: S, max_len: usize) -> Result<Bytes, Status>
+where
+    S: Stream<Item = Result<Chunk, Status>>,
+{
+    let mut chunks = pin!(chunks);
+    let mut payload: Option<(BytesMut, u64)> = None;
+
+    while let Some(chunk) = poll_fn(|cx| chunks.as_mut().poll_next(cx)).await {
+        let chunk = chunk?;
+        if !chunk.verify() {
+            return Err(Status::data_loss(format!(
+                "checksum mismatch of the chunk at offset {}",
+                chunk.offset
+            )));
+        }
+
+        let (buf, total_len) = match &mut payload {
+            Some(payload) => payload,
+            None => {
+                if chunk.total_len > max_len as u64 {
+                    return Err(Status::resource_exhausted(format!(
+                        "payload of {} bytes is larger than the max of {max_len} bytes",
+                        chunk.total_len
+                    )));
+                }
+                let buf = BytesMut::with_capacity(chunk.total_len as usize);
+                payload.insert((buf, chunk.total_len))
+            }
+        };
+
+        if chunk.total_len != *total_len {
+            return Err(Status::invalid_argument(
+                "chunks have different payload lengths",
+            ));
+        }
+        if chunk.offset != buf.len() as u64 {
+            return Err(Status::invalid_argument(format!(
+                "expected the chunk at offset {}, got the chunk at offset {}",
+                buf.len(),
+                chunk.offset
+            )));
+        }
+        if buf.len() as u64 + chunk.data.len() as u64 > *total_len {
+            return Err(Status::invalid_argument(
+                "chunks are longer than their payload",
+            ));
+        }
+        buf.extend_from_slice(&chunk.data);
+    }
+
+    match payload {
+        Some((buf, total_len)) if buf.len() as u64 == total_len => Ok(buf.freeze()),
+        Some((buf, total_len)) => Err(Status::invalid_argument(format!(
+            "incomplete payload, got {} of {total_len} bytes",
+            buf.len()
+        ))),
+        None => Err(Status::invalid_argument("no chunk received")),
+    }
+}
+
+/// A [`Codec`] encoding [`Chunk`]s.
+///
+/// A chunk is encoded as its offset and the length of its payload, as big endian 64 bit
+/// integers, then the CRC-32C checksum of its data, as a big endian 32 bit integer, then its
+/// data.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChunkCodec;
+
+impl Codec for ChunkCodec {
+    type Encode = Chunk;
+    type Decode = Chunk;
+
+    type Encoder = ChunkCodec;
+    type Decoder = ChunkCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        ChunkCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        ChunkCodec
+    }
+}
+
+impl Encoder for ChunkCodec {
+    type Item = Chunk;
+    type Error = Status;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        dst.reserve(CHUNK_HEADER_SIZE + item.data.len());
+        dst.put_u64(item.offset);
+        dst.put_u64(item.total_len);
+        dst.put_u32(item.checksum);
+        dst.put_slice(&item.data);
+        Ok(())
+    }
+}
+
+impl Decoder for ChunkCodec {
+    type Item = Chunk;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        if src.remaining() < CHUNK_HEADER_SIZE {
+            return Err(Status::internal("chunk is shorter than its header"));
+        }
+        let offset = src.get_u64();
+        let total_len = src.get_u64();
+        let checksum = src.get_u32();
+        let data = src.copy_to_bytes(src.remaining());
+        Ok(Some(Chunk {
+            offset,
+            total_len,
+            checksum,
+            data,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn reassemble_chunks(chunks: Vec<Chunk>) -> Result<Bytes, Status> {
+        reassemble(tokio_stream::iter(chunks.into_iter().map(Ok)), 1024).await
+    }
+
+    #[tokio::test]
+    async fn split_and_reassemble() {
+        let payload = Bytes::from((0..=255).cycle().take(1000).collect::<Vec<u8>>());
+        let chunks = split(payload.clone(), 300).collect::<Vec<_>>();
+        let offsets = chunks.iter().map(Chunk::offset).collect::<Vec<_>>();
+        assert_eq!(offsets, [0, 300, 600, 900]);
+        assert_eq!(reassemble_chunks(chunks).await.unwrap(), payload);
+
+        let chunks = split(Bytes::new(), 300).collect::<Vec<_>>();
+        assert_eq!(chunks, [Chunk::empty()]);
+        assert_eq!(reassemble_chunks(chunks).await.unwrap(), Bytes::new());
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_chunks() {
+        let payload = Bytes::from_static(b"hello world");
+        let chunks = split(payload.clone(), 4).collect::<Vec<_>>();
+
+        let mut corrupted = chunks.clone();
+        corrupted[1].data = Bytes::from_static(b"O WO");
+        let status = reassemble_chunks(corrupted).await.unwrap_err();
+        assert_eq!(status.code(), crate::Code::DataLoss);
+
+        let mut reordered = chunks.clone();
+        reordered.swap(0, 1);
+        let status = reassemble_chunks(reordered).await.unwrap_err();
+        assert_eq!(status.code(), crate::Code::InvalidArgument);
+
+        let status = reassemble_chunks(chunks[..2].to_vec()).await.unwrap_err();
+        assert_eq!(status.code(), crate::Code::InvalidArgument);
+
+        let large = split(Bytes::from(vec![0; 2048]), 512).collect();
+        let status = reassemble_chunks(large).await.unwrap_err();
+        assert_eq!(status.code(), crate::Code::ResourceExhausted);
+    }
+}
@@ -4,7 +4,7 @@ use bytes::{Buf, BufMut, BytesMut};
 use flate2::read::{GzDecoder, GzEncoder};
 #[cfg(feature = "deflate")]
 use flate2::read::{ZlibDecoder, ZlibEncoder};
-use std::fmt;
+use std::{fmt, sync::Arc};
 #[cfg(feature = "zstd")]
 use zstd::stream::read::{Decoder, Encoder};
 
@@ -202,6 +202,115 @@ impl fmt::Display for CompressionEncoding {
     }
 }
 
+/// Decides whether and how the responses of a server are compressed, in place of the encodings
+/// enabled with `send_compressed`.
+///
+/// The policy is consulted once per response, when its headers are sent, to pick the encoding of
+/// the response among those the peer accepts, and then for each message of the response, to
+/// decide whether it is worth compressing. Messages left uncompressed are sent as is, which the
+/// peer supports regardless of the encoding of the response.
+///
+/// ```
+/// # #[cfg(feature = "gzip")] {
+/// use tonic::codec::{CompressionEncoding, CompressionPolicy};
+///
+/// /// Compresses the messages of at least 1KiB, except for the already compressed images.
+/// #[derive(Debug)]
+/// struct LargeMessages;
+///
+/// impl CompressionPolicy for LargeMessages {
+///     fn encoding(
+///         &self,
+///         method: &str,
+///         accept_encodings: &[CompressionEncoding],
+///     ) -> Option<CompressionEncoding> {
+///         if method.ends_with("/GetImage") {
+///             return None;
+///         }
+///         accept_encodings
+///             .contains(&CompressionEncoding::Gzip)
+///             .then_some(CompressionEncoding::Gzip)
+///     }
+///
+///     fn compress_message(
+///         &self,
+///         _method: &str,
+///         _encoding: CompressionEncoding,
+///         message_size: usize,
+///     ) -> bool {
+///         message_size >= 1024
+///     }
+/// }
+/// # }
+/// ```
+pub trait CompressionPolicy: fmt::Debug + Send + Sync + 'static {
+    /// Picks the encoding of the responses of `method`, the path of the method, among
+    /// `accept_encodings`, the encodings the peer accepts in the order of its preference. Returning
+    /// `None`, or an encoding the peer doesn't accept, leaves the response uncompressed.
+    ///
+    /// Defaults to the encoding the peer prefers.
+    fn encoding(
+        &self,
+        method: &str,
+        accept_encodings: &[CompressionEncoding],
+    ) -> Option<CompressionEncoding> {
+        let _ = method;
+        accept_encodings.first().copied()
+    }
+
+    /// Decides whether a message of `message_size` bytes, before compression, of a response of
+    /// `method` with `encoding` is compressed.
+    ///
+    /// Defaults to compressing every message.
+    fn compress_message(
+        &self,
+        method: &str,
+        encoding: CompressionEncoding,
+        message_size: usize,
+    ) -> bool {
+        let _ = (method, encoding, message_size);
+        true
+    }
+}
+
+/// A [`CompressionPolicy`] applied to the messages of a response of a method.
+#[derive(Clone, Debug)]
+pub(crate) struct MessageCompression {
+    pub(crate) policy: Arc<dyn CompressionPolicy>,
+    pub(crate) method: String,
+}
+
+impl MessageCompression {
+    pub(crate) fn compress(&self, encoding: CompressionEncoding, message_size: usize) -> bool {
+        self.policy
+            .compress_message(&self.method, encoding, message_size)
+    }
+}
+
+/// The encodings, among those supported, accepted in the `grpc-accept-encoding` header of `map`,
+/// in the order of the header.
+pub(crate) fn accept_encodings(map: &http::HeaderMap) -> Vec<CompressionEncoding> {
+    let Some(header_value) = map
+        .get(ACCEPT_ENCODING_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return Vec::new();
+    };
+
+    let mut encodings = Vec::new();
+    for encoding in split_by_comma(header_value).filter_map(|value| {
+        CompressionEncoding::ENCODINGS
+            .iter()
+            .copied()
+            .find(|encoding| encoding.as_str() == value)
+    }) {
+        if !encodings.contains(&encoding) {
+            encodings.push(encoding);
+        }
+    }
+    encodings
+}
+
 fn split_by_comma(s: &str) -> impl Iterator<Item = &str> {
     s.split(',').map(|s| s.trim())
 }
@@ -388,4 +497,24 @@ mod tests {
             HeaderValue::from_static("zstd,deflate,gzip,identity"),
         );
     }
+
+    #[test]
+    fn accept_encodings_without_header() {
+        assert!(accept_encodings(&http::HeaderMap::new()).is_empty());
+    }
+
+    #[test]
+    #[cfg(all(feature = "gzip", feature = "zstd"))]
+    fn accept_encodings_in_header_order() {
+        let mut map = http::HeaderMap::new();
+        map.insert(
+            ACCEPT_ENCODING_HEADER,
+            HeaderValue::from_static("zstd, br,gzip,zstd,identity"),
+        );
+
+        assert_eq!(
+            accept_encodings(&map),
+            [CompressionEncoding::Zstd, CompressionEncoding::Gzip]
+        );
+    }
 }
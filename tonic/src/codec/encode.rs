@@ -1,5 +1,6 @@
 use super::compression::{
-    compress, CompressionEncoding, CompressionSettings, SingleMessageCompressionOverride,
+    compress, CompressionEncoding, CompressionSettings, MessageCompression,
+    SingleMessageCompressionOverride,
 };
 use super::{BufferSettings, EncodeBuf, Encoder, DEFAULT_MAX_SEND_MESSAGE_SIZE, HEADER_SIZE};
 use crate::Status;
@@ -25,6 +26,7 @@ struct EncodedBytes<T, U> {
     source: Fuse<U>,
     encoder: T,
     compression_encoding: Option<CompressionEncoding>,
+    message_compression: Option<MessageCompression>,
     max_message_size: Option<usize>,
     buf: BytesMut,
     uncompression_buf: BytesMut,
@@ -59,6 +61,7 @@ impl<T: Encoder, U: Stream> EncodedBytes<T, U> {
             source: source.fuse(),
             encoder,
             compression_encoding,
+            message_compression: None,
             max_message_size,
             buf,
             uncompression_buf,
@@ -79,6 +82,7 @@ where
             mut source,
             encoder,
             compression_encoding,
+            message_compression,
             max_message_size,
             buf,
             uncompression_buf,
@@ -106,7 +110,10 @@ where
                         encoder,
                         buf,
                         uncompression_buf,
-                        *compression_encoding,
+                        ItemCompression {
+                            encoding: *compression_encoding,
+                            messages: message_compression.as_ref(),
+                        },
                         *max_message_size,
                         buffer_settings,
                         item,
@@ -130,11 +137,19 @@ where
     }
 }
 
+// The compression of an encoded item: the encoding of the body, if compressed, and the policy
+// deciding which of its messages are compressed.
+#[derive(Clone, Copy)]
+struct ItemCompression<'a> {
+    encoding: Option<CompressionEncoding>,
+    messages: Option<&'a MessageCompression>,
+}
+
 fn encode_item<T>(
     encoder: &mut T,
     buf: &mut BytesMut,
     uncompression_buf: &mut BytesMut,
-    compression_encoding: Option<CompressionEncoding>,
+    compression: ItemCompression<'_>,
     max_message_size: Option<usize>,
     buffer_settings: BufferSettings,
     item: T::Item,
@@ -149,6 +164,7 @@ where
         buf.advance_mut(HEADER_SIZE);
     }
 
+    let mut compression_encoding = compression.encoding;
     if let Some(encoding) = compression_encoding {
         uncompression_buf.clear();
 
@@ -158,16 +174,28 @@ where
 
         let uncompressed_len = uncompression_buf.len();
 
-        compress(
-            CompressionSettings {
-                encoding,
-                buffer_growth_interval: buffer_settings.buffer_size,
-            },
-            uncompression_buf,
-            buf,
-            uncompressed_len,
-        )
-        .map_err(|err| Status::internal(format!("Error compressing: {err}")))?;
+        let compress_message = match compression.messages {
+            Some(message_compression) => message_compression.compress(encoding, uncompressed_len),
+            None => true,
+        };
+
+        if compress_message {
+            compress(
+                CompressionSettings {
+                    encoding,
+                    buffer_growth_interval: buffer_settings.buffer_size,
+                },
+                uncompression_buf,
+                buf,
+                uncompressed_len,
+            )
+            .map_err(|err| Status::internal(format!("Error compressing: {err}")))?;
+        } else {
+            // the message is sent as is, with the compressed flag unset
+            buf.extend_from_slice(&uncompression_buf[..]);
+            uncompression_buf.clear();
+            compression_encoding = None;
+        }
     } else {
         encoder
             .encode(item, &mut EncodeBuf::new(buf))
@@ -276,6 +304,16 @@ impl<T: Encoder, U: Stream> EncodeBody<T, U> {
             },
         }
     }
+
+    /// Decides which messages of a compressed response are compressed with `message_compression`,
+    /// instead of compressing all of them.
+    pub(crate) fn with_message_compression(
+        mut self,
+        message_compression: Option<MessageCompression>,
+    ) -> Self {
+        self.inner.message_compression = message_compression;
+        self
+    }
 }
 
 impl EncodeState {
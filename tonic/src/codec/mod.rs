@@ -4,6 +4,7 @@
 //! and a protobuf codec based on prost.
 
 mod buffer;
+pub mod chunk;
 pub(crate) mod compression;
 mod decode;
 mod encode;
@@ -14,7 +15,7 @@ use crate::Status;
 use std::io;
 
 pub use self::buffer::{DecodeBuf, EncodeBuf};
-pub use self::compression::{CompressionEncoding, EnabledCompressionEncodings};
+pub use self::compression::{CompressionEncoding, CompressionPolicy, EnabledCompressionEncodings};
 pub use self::decode::Streaming;
 pub use self::encode::EncodeBody;
 #[cfg(feature = "prost")]
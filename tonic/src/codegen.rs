@@ -9,13 +9,21 @@ pub use std::sync::Arc;
 pub use std::task::{Context, Poll};
 pub use tower_service::Service;
 pub type StdError = Box<dyn std::error::Error + Send + Sync + 'static>;
-pub use crate::codec::{CompressionEncoding, EnabledCompressionEncodings};
+pub use crate::codec::{CompressionEncoding, CompressionPolicy, EnabledCompressionEncodings};
 pub use crate::extensions::GrpcMethod;
 pub use crate::service::interceptor::InterceptedService;
 pub use bytes::Bytes;
 pub use http;
 pub use http_body::Body;
 
+pub mod field_mask;
+mod response_channel;
+#[cfg(feature = "serde")]
+pub mod serde;
+
+pub use self::response_channel::{response_channel, Disconnected, ResponseSender, ResponseStream};
+
 pub type BoxFuture<T, E> = self::Pin<Box<dyn self::Future<Output = Result<T, E>> + Send + 'static>>;
+pub type LocalBoxFuture<T, E> = self::Pin<Box<dyn self::Future<Output = Result<T, E>> + 'static>>;
 pub type BoxStream<T> =
     self::Pin<Box<dyn tokio_stream::Stream<Item = Result<T, crate::Status>> + Send + 'static>>;
@@ -0,0 +1,80 @@
+//! Helpers used by the `tonic::FieldMaskMessage` implementations generated
+//! with `tonic-build`'s `Builder::field_mask_message`.
+//!
+//! The paths of field masks are sequences of field names separated by dots,
+//! e.g. `author.name`. A path selects the field it names, and each of its
+//! prefixes selects the fields of its message field.
+
+/// What the paths of a field mask select of a message field.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Selection<'a> {
+    /// The whole field is selected.
+    Field,
+    /// Only these fields of the field are selected, e.g. `name` for the
+    /// `author` field selected by `author.name`.
+    Fields(Vec<&'a str>),
+    /// The field is not selected.
+    None,
+}
+
+/// Whether a mask keeps all the fields of its message, i.e. it is empty or
+/// holds the `*` path.
+pub fn keeps_all<S: AsRef<str>>(paths: &[S]) -> bool {
+    paths.is_empty() || paths.iter().any(|path| path.as_ref() == "*")
+}
+
+/// What `paths` select of `field`.
+pub fn select<'a, S: AsRef<str>>(paths: &'a [S], field: &str) -> Selection<'a> {
+    let mut fields = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        if path == field {
+            return Selection::Field;
+        }
+        if let Some(subpath) = path
+            .strip_prefix(field)
+            .and_then(|rest| rest.strip_prefix('.'))
+        {
+            fields.push(subpath);
+        }
+    }
+
+    if fields.is_empty() {
+        Selection::None
+    } else {
+        Selection::Fields(fields)
+    }
+}
+
+/// Whether `paths` select `field` or any of its fields.
+pub fn selects<S: AsRef<str>>(paths: &[S], field: &str) -> bool {
+    select(paths, field) != Selection::None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_fields_by_path() {
+        let paths = ["name", "author.name", "author.address.city", "authored"];
+
+        assert!(!keeps_all(&paths));
+        assert!(keeps_all::<&str>(&[]));
+        assert!(keeps_all(&["name", "*"]));
+
+        assert_eq!(select(&paths, "name"), Selection::Field);
+        assert_eq!(
+            select(&paths, "author"),
+            Selection::Fields(vec!["name", "address.city"])
+        );
+        assert_eq!(select(&paths, "auth"), Selection::None);
+        assert_eq!(
+            select(&["author.name", "author"], "author"),
+            Selection::Field
+        );
+
+        assert!(selects(&paths, "authored"));
+        assert!(!selects(&paths, "title"));
+    }
+}
@@ -0,0 +1,130 @@
+//! A channel backing streaming responses, paced by the client's reading.
+
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::sync::mpsc;
+use tokio_stream::Stream;
+
+use crate::Status;
+
+/// Create a channel streaming the messages of a response, buffering at most `capacity` of them.
+///
+/// The [`ResponseStream`] is returned as the stream of a streaming method, and the messages are
+/// sent from the [`ResponseSender`], e.g. from a spawned task:
+///
+/// ```
+/// # use tonic::{codegen::{response_channel, ResponseStream}, Response, Status};
+/// # struct Feature;
+/// # async fn features() -> Vec<Feature> { Vec::new() }
+/// async fn list_features() -> Result<Response<ResponseStream<Feature>>, Status> {
+///     let (tx, rx) = response_channel(4);
+///     tokio::spawn(async move {
+///         for feature in features().await {
+///             // Stops once the client is gone.
+///             if tx.send(Ok(feature)).await.is_err() {
+///                 break;
+///             }
+///         }
+///     });
+///     Ok(Response::new(rx))
+/// }
+/// ```
+///
+/// The stream is read as the encoded messages are written to the connection, which HTTP/2 flow
+/// control only lets happen as fast as the client reads them. Once the buffer of the channel is
+/// full, [`ResponseSender::send`] waits, so producers go at the pace of their client and the
+/// messages of a slow client don't pile up in memory, as they do in an unbounded channel. Beyond
+/// the channel, the messages in flight are bounded by the windows of the connection and the send
+/// buffer of the server.
+///
+/// Sending an `Err` ends the response with its status. Dropping all the senders ends the response
+/// with an `OK` status.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn response_channel<T>(capacity: usize) -> (ResponseSender<T>, ResponseStream<T>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    (ResponseSender { tx }, ResponseStream { rx })
+}
+
+/// The sending half of a [`response_channel`].
+pub struct ResponseSender<T> {
+    tx: mpsc::Sender<Result<T, Status>>,
+}
+
+impl<T> ResponseSender<T> {
+    /// Send a message, or end the response with a status, waiting for room in the channel.
+    ///
+    /// Fails, handing back the item, once the response stream is dropped: the client cancelled the
+    /// call or went away, or the response ended.
+    pub async fn send(&self, item: Result<T, Status>) -> Result<(), Disconnected<T>> {
+        self.tx
+            .send(item)
+            .await
+            .map_err(|mpsc::error::SendError(item)| Disconnected(item))
+    }
+
+    /// Wait until the response stream is dropped, e.g. to stop producing messages once the client
+    /// went away, while not sending.
+    pub async fn closed(&self) {
+        self.tx.closed().await;
+    }
+
+    /// Whether the response stream is dropped.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+impl<T> Clone for ResponseSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for ResponseSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseSender")
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// The stream of a response, receiving half of a [`response_channel`].
+pub struct ResponseStream<T> {
+    rx: mpsc::Receiver<Result<T, Status>>,
+}
+
+impl<T> Stream for ResponseStream<T> {
+    type Item = Result<T, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl<T> fmt::Debug for ResponseStream<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseStream").finish()
+    }
+}
+
+/// Error of [`ResponseSender::send`] once the response stream is dropped, holding the item which
+/// could not be sent.
+#[derive(Debug)]
+pub struct Disconnected<T>(pub Result<T, Status>);
+
+impl<T> fmt::Display for Disconnected<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("response stream disconnected")
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for Disconnected<T> {}
@@ -0,0 +1,156 @@
+//! `serde` helpers used by the code generated with `tonic-build`'s `serde`
+//! option.
+//!
+//! Protobuf enumeration fields are generated as `i32`. These helpers map them
+//! through their enumeration type so that they are serialized as the names of
+//! the values, as in the protobuf JSON mapping. Values missing from the
+//! enumeration are serialized as numbers, and both names and numbers are
+//! accepted when deserializing.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+    de::{self, IntoDeserializer, Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+struct EnumValue<E> {
+    value: i32,
+    _marker: PhantomData<E>,
+}
+
+impl<E> EnumValue<E> {
+    fn new(value: i32) -> Self {
+        EnumValue {
+            value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E> Serialize for EnumValue<E>
+where
+    E: TryFrom<i32> + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match E::try_from(self.value) {
+            Ok(value) => value.serialize(serializer),
+            Err(_) => serializer.serialize_i32(self.value),
+        }
+    }
+}
+
+impl<'de, E> Deserialize<'de> for EnumValue<E>
+where
+    E: Deserialize<'de> + Into<i32>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(EnumVisitor::<E>(PhantomData))
+    }
+}
+
+struct EnumVisitor<E>(PhantomData<E>);
+
+impl<'de, E> Visitor<'de> for EnumVisitor<E>
+where
+    E: Deserialize<'de> + Into<i32>,
+{
+    type Value = EnumValue<E>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("an enumeration value name or number")
+    }
+
+    fn visit_str<Err: de::Error>(self, value: &str) -> Result<Self::Value, Err> {
+        E::deserialize(value.into_deserializer()).map(|value| EnumValue::new(value.into()))
+    }
+
+    fn visit_i64<Err: de::Error>(self, value: i64) -> Result<Self::Value, Err> {
+        i32::try_from(value)
+            .map(EnumValue::new)
+            .map_err(|_| Err::invalid_value(Unexpected::Signed(value), &self))
+    }
+
+    fn visit_u64<Err: de::Error>(self, value: u64) -> Result<Self::Value, Err> {
+        i32::try_from(value)
+            .map(EnumValue::new)
+            .map_err(|_| Err::invalid_value(Unexpected::Unsigned(value), &self))
+    }
+}
+
+/// Helpers for singular enumeration fields, generated as `i32`.
+pub mod enumeration {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::EnumValue;
+
+    /// Serializes `value` as the name of the matching `E` value.
+    pub fn serialize<E, S>(value: &i32, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        E: TryFrom<i32> + Serialize,
+        S: Serializer,
+    {
+        EnumValue::<E>::new(*value).serialize(serializer)
+    }
+
+    /// Deserializes an `E` value from its name or number.
+    pub fn deserialize<'de, E, D>(deserializer: D) -> Result<i32, D::Error>
+    where
+        E: Deserialize<'de> + Into<i32>,
+        D: Deserializer<'de>,
+    {
+        EnumValue::<E>::deserialize(deserializer).map(|value| value.value)
+    }
+}
+
+/// Helpers for optional enumeration fields, generated as `Option<i32>`.
+pub mod enumeration_option {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::EnumValue;
+
+    /// Serializes `value` as the name of the matching `E` value, if any.
+    pub fn serialize<E, S>(value: &Option<i32>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        E: TryFrom<i32> + Serialize,
+        S: Serializer,
+    {
+        value.map(EnumValue::<E>::new).serialize(serializer)
+    }
+
+    /// Deserializes an optional `E` value from its name or number.
+    pub fn deserialize<'de, E, D>(deserializer: D) -> Result<Option<i32>, D::Error>
+    where
+        E: Deserialize<'de> + Into<i32>,
+        D: Deserializer<'de>,
+    {
+        Option::<EnumValue<E>>::deserialize(deserializer)
+            .map(|value| value.map(|value| value.value))
+    }
+}
+
+/// Helpers for repeated enumeration fields, generated as `Vec<i32>`.
+pub mod enumeration_repeated {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::EnumValue;
+
+    /// Serializes `values` as the names of the matching `E` values.
+    pub fn serialize<E, S>(values: &[i32], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        E: TryFrom<i32> + Serialize,
+        S: Serializer,
+    {
+        serializer.collect_seq(values.iter().map(|value| EnumValue::<E>::new(*value)))
+    }
+
+    /// Deserializes `E` values from their names or numbers.
+    pub fn deserialize<'de, E, D>(deserializer: D) -> Result<Vec<i32>, D::Error>
+    where
+        E: Deserialize<'de> + Into<i32>,
+        D: Deserializer<'de>,
+    {
+        Vec::<EnumValue<E>>::deserialize(deserializer)
+            .map(|values| values.into_iter().map(|value| value.value).collect())
+    }
+}
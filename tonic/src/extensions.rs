@@ -21,3 +21,21 @@ impl<'a> GrpcMethod<'a> {
         self.method
     }
 }
+
+/// The kind of a gRPC method, an extension of the requests sent by [`Grpc`].
+///
+/// This lets transports handling the kinds of methods differently, such as the Connect
+/// protocol, learn the kind of the method of a request.
+///
+/// [`Grpc`]: crate::client::Grpc
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GrpcMethodKind {
+    /// A single request message, and a single response message.
+    Unary,
+    /// A stream of request messages, and a single response message.
+    ClientStreaming,
+    /// A single request message, and a stream of response messages.
+    ServerStreaming,
+    /// Streams of request and response messages.
+    Streaming,
+}
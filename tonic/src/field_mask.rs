@@ -0,0 +1,91 @@
+use crate::Status;
+
+/// Applies the paths of `google.protobuf.FieldMask`s to a message, e.g. the `read_mask` of
+/// AIP-157 partial responses, and checks them, e.g. the `update_mask` of AIP-134 updates.
+///
+/// Paths are sequences of field names separated by dots, e.g. `author.name`. `tonic-build`
+/// implements it for the generated messages matched by `Builder::field_mask_message`, and for the
+/// messages of their fields, so that masks can select the fields of these. It may also be
+/// implemented by hand:
+///
+/// ```
+/// # use tonic::FieldMaskMessage;
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Book {
+///     title: String,
+///     summary: String,
+/// }
+///
+/// impl FieldMaskMessage for Book {
+///     fn is_field_path(path: &str) -> bool {
+///         matches!(path, "title" | "summary")
+///     }
+///
+///     fn apply_field_mask<S: AsRef<str>>(&mut self, paths: &[S]) {
+///         let selects = |field| paths.iter().any(|path| path.as_ref() == field);
+///         if paths.is_empty() || selects("*") {
+///             return;
+///         }
+///         if !selects("title") {
+///             self.title.clear();
+///         }
+///         if !selects("summary") {
+///             self.summary.clear();
+///         }
+///     }
+/// }
+///
+/// let mut book = Book {
+///     title: "Dune".to_string(),
+///     summary: "Spice".to_string(),
+/// };
+/// assert!(Book::check_field_mask(&["title"]).is_ok());
+/// assert!(Book::check_field_mask(&["author"]).is_err());
+///
+/// book.apply_field_mask(&["title"]);
+/// assert_eq!(book.summary, "");
+/// ```
+pub trait FieldMaskMessage {
+    /// Whether `path` names a field of the message.
+    fn is_field_path(path: &str) -> bool;
+
+    /// Clear the fields not selected by `paths`, keeping the fields they name and the fields
+    /// of these. An empty mask, or a mask holding the `*` path, keeps all the fields.
+    fn apply_field_mask<S: AsRef<str>>(&mut self, paths: &[S]);
+
+    /// Check that `paths` name fields of the message, or hold the `*` path, failing with
+    /// `InvalidArgument` otherwise.
+    fn check_field_mask<S: AsRef<str>>(paths: &[S]) -> Result<(), Status> {
+        for path in paths {
+            let path = path.as_ref();
+            if path != "*" && !Self::is_field_path(path) {
+                return Err(Status::invalid_argument(format!(
+                    "invalid field mask path `{path}`"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: FieldMaskMessage + ?Sized> FieldMaskMessage for Box<T> {
+    fn is_field_path(path: &str) -> bool {
+        T::is_field_path(path)
+    }
+
+    fn apply_field_mask<S: AsRef<str>>(&mut self, paths: &[S]) {
+        (**self).apply_field_mask(paths);
+    }
+}
+
+impl<T: FieldMaskMessage> FieldMaskMessage for Option<T> {
+    fn is_field_path(path: &str) -> bool {
+        T::is_field_path(path)
+    }
+
+    fn apply_field_mask<S: AsRef<str>>(&mut self, paths: &[S]) {
+        if let Some(message) = self {
+            message.apply_field_mask(paths);
+        }
+    }
+}
@@ -22,6 +22,8 @@
 //! - `server`: Enables just the full featured server portion of the `transport` feature.
 //! - `channel`: Enables just the full featured channel portion of the `transport` feature.
 //! - `router`: Enables the [`axum`] based service router. Enabled by default.
+//! - `io-uring`: Enables the experimental [`io_uring`] based connection backend of the server,
+//!   on Linux only. Depends on [`tokio-uring`]. Not enabled by default.
 //! - `codegen`: Enables all the required exports and optional dependencies required
 //!   for [`tonic-build`]. Enabled by default.
 //! - `tls-ring`: Enables the [`rustls`] based TLS options for the `transport` feature using
@@ -33,6 +35,10 @@
 //! - `tls-webpki-roots`: Add the standard trust roots from the [`webpki-roots`] crate to
 //!   `rustls`-based gRPC clients. Not enabled by default.
 //! - `prost`: Enables the [`prost`] based gRPC [`Codec`] implementation. Enabled by default.
+//! - `request-id`: Enables the [`request_id`] middleware, generating request IDs with the
+//!   [`uuid`] crate. Not enabled by default.
+//! - `serde`: Enables the [`serde`] helpers used by code generated with the `serde` option
+//!   of [`tonic-build`]. Not enabled by default.
 //! - `gzip`: Enables compressing requests, responses, and streams. Depends on [`flate2`].
 //!   Not enabled by default.
 //! - `deflate`: Enables compressing requests, responses, and streams. Depends on [`flate2`].
@@ -71,6 +77,7 @@
 //! [`tonic`]: https://github.com/hyperium/tonic
 //! [`tokio`]: https://docs.rs/tokio
 //! [`prost`]: https://docs.rs/prost
+//! [`serde`]: https://docs.rs/serde
 //! [`hyper`]: https://docs.rs/hyper
 //! [`tower`]: https://docs.rs/tower
 //! [`tonic-build`]: https://docs.rs/tonic-build
@@ -86,6 +93,10 @@
 //! [`webpki-roots`]: https://docs.rs/webpki-roots
 //! [`flate2`]: https://docs.rs/flate2
 //! [`zstd`]: https://docs.rs/zstd
+//! [`io_uring`]: https://man7.org/linux/man-pages/man7/io_uring.7.html
+//! [`tokio-uring`]: https://docs.rs/tokio-uring
+//! [`request_id`]: service::request_id
+//! [`uuid`]: https://docs.rs/uuid
 
 #![recursion_limit = "256"]
 #![doc(
@@ -101,12 +112,16 @@ pub mod codec;
 pub mod metadata;
 pub mod server;
 pub mod service;
+pub mod test;
 
 #[cfg(any(feature = "server", feature = "channel"))]
 pub mod transport;
 
 mod extensions;
+mod field_mask;
 mod macros;
+mod method;
+mod redact;
 mod request;
 mod response;
 mod status;
@@ -118,11 +133,16 @@ pub use async_trait::async_trait;
 
 #[doc(inline)]
 pub use codec::Streaming;
-pub use extensions::GrpcMethod;
+pub use extensions::{GrpcMethod, GrpcMethodKind};
+pub use field_mask::FieldMaskMessage;
 pub use http::Extensions;
+pub use method::{HttpRoute, IdempotencyLevel, MethodDescriptor};
+pub use redact::{RedactMessage, Redacted};
 pub use request::{IntoRequest, IntoStreamingRequest, Request};
 pub use response::Response;
-pub use status::{Code, ConnectError, Status, TimeoutExpired};
+#[cfg(any(feature = "server", feature = "channel"))]
+pub use status::Http2Error;
+pub use status::{Code, ConnectError, Status, StatusKeys, TimeoutExpired};
 
 pub(crate) type BoxError = Box<dyn std::error::Error + Send + Sync>;
 
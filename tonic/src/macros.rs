@@ -74,3 +74,34 @@ macro_rules! include_file_descriptor_set {
         include_bytes!(concat!(env!("OUT_DIR"), concat!("/", $package, ".bin")))
     };
 }
+
+/// Assert that a call failed with a code, and that its status matches [`StatusMatcher`]s.
+///
+/// Takes the `Result` of the call, or its `Status`, the expected [`Code`], and any number of
+/// matchers. Panics with the code, message, metadata and details of the status, along with its
+/// mismatches, when it doesn't match:
+///
+/// ```
+/// use tonic::{assert_status, test::message_eq, Code, Status};
+///
+/// let result: Result<(), Status> = Err(Status::invalid_argument("empty name"));
+/// assert_status!(result, Code::InvalidArgument);
+/// assert_status!(result, Code::InvalidArgument, message_eq("empty name"));
+/// ```
+///
+/// See the [`test`](crate::test) module for the matchers.
+///
+/// [`StatusMatcher`]: crate::test::StatusMatcher
+/// [`Code`]: crate::Code
+#[macro_export]
+macro_rules! assert_status {
+    ($outcome:expr, $code:expr $(, $matcher:expr)* $(,)?) => {
+        if let ::std::result::Result::Err(report) = $crate::test::check_status(
+            &$outcome,
+            $code,
+            &[$(&$matcher as &dyn $crate::test::StatusMatcher),*],
+        ) {
+            ::std::panic!("{}", report);
+        }
+    };
+}
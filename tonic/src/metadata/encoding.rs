@@ -182,7 +182,9 @@ impl self::value_encoding::Sealed for Binary {
 
 impl ValueEncoding for Binary {
     fn is_valid_key(key: &str) -> bool {
-        key.ends_with("-bin")
+        // Keys looked up with strings are not lowercase yet.
+        let key = key.as_bytes();
+        key.len() >= 4 && key[key.len() - 4..].eq_ignore_ascii_case(b"-bin")
     }
 }
 
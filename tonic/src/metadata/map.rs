@@ -50,6 +50,18 @@ impl AsMut<http::HeaderMap> for MetadataMap {
     }
 }
 
+impl From<http::HeaderMap> for MetadataMap {
+    fn from(headers: http::HeaderMap) -> Self {
+        MetadataMap::from_headers(headers)
+    }
+}
+
+impl From<MetadataMap> for http::HeaderMap {
+    fn from(map: MetadataMap) -> Self {
+        map.into_headers()
+    }
+}
+
 /// `MetadataMap` entry iterator.
 ///
 /// Yields `KeyAndValueRef` values. The same header name may be yielded
@@ -210,6 +222,14 @@ pub struct OccupiedEntry<'a, VE: ValueEncoding> {
 
 pub(crate) const GRPC_TIMEOUT_HEADER: &str = "grpc-timeout";
 
+/// The size of `headers` as accounted by the `SETTINGS_MAX_HEADER_LIST_SIZE` limit of HTTP/2.
+pub(crate) fn header_list_size(headers: &http::HeaderMap) -> usize {
+    headers
+        .iter()
+        .map(|(name, value)| name.as_str().len() + value.len() + 32)
+        .sum()
+}
+
 // ===== impl MetadataMap =====
 
 impl MetadataMap {
@@ -241,6 +261,8 @@ impl MetadataMap {
     }
 
     /// Convert an HTTP HeaderMap to a MetadataMap
+    ///
+    /// The headers are moved, not copied.
     pub fn from_headers(headers: http::HeaderMap) -> Self {
         MetadataMap { headers }
     }
@@ -347,6 +369,25 @@ impl MetadataMap {
         self.headers.keys_len()
     }
 
+    /// Returns the size of the metadata as accounted by the `SETTINGS_MAX_HEADER_LIST_SIZE`
+    /// limit of HTTP/2: the lengths of the keys and values, plus 32 bytes per value.
+    ///
+    /// Peers reject the metadata over their limit, with `Code::ResourceExhausted`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tonic::metadata::*;
+    /// let mut map = MetadataMap::new();
+    ///
+    /// map.insert("x-host-ip", "127.0.0.1".parse().unwrap());
+    ///
+    /// assert_eq!(9 + 9 + 32, map.header_list_size());
+    /// ```
+    pub fn header_list_size(&self) -> usize {
+        header_list_size(&self.headers)
+    }
+
     /// Returns true if the map contains no elements.
     ///
     /// # Examples
@@ -583,7 +624,8 @@ impl MetadataMap {
     ///
     /// The returned view does not incur any allocations and allows iterating
     /// the values associated with the key.  See [`GetAll`] for more details.
-    /// Returns `None` if there are no values associated with the key.
+    /// Returns `None` if there are no values associated with the key. Keys
+    /// are matched ignoring their case.
     ///
     /// [`GetAll`]: struct.GetAll.html
     ///
@@ -1224,9 +1266,82 @@ impl MetadataMap {
         key.remove(self)
     }
 
-    pub(crate) fn merge(&mut self, other: MetadataMap) {
+    /// Moves all the entries of `other` into this map.
+    ///
+    /// The values of the keys of `other` replace the values of these keys in
+    /// this map. The entries are moved without being copied or validated
+    /// again.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tonic::metadata::*;
+    /// let mut map = MetadataMap::new();
+    /// map.insert("x-host", "hello".parse().unwrap());
+    /// map.insert("x-number", "123".parse().unwrap());
+    ///
+    /// let mut other = MetadataMap::new();
+    /// other.insert("x-host", "world".parse().unwrap());
+    /// other.append("x-host", "again".parse().unwrap());
+    /// map.extend(other);
+    ///
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.get_all("x-host").iter().collect::<Vec<_>>(), ["world", "again"]);
+    /// assert_eq!(map.get("x-number").unwrap(), "123");
+    /// ```
+    pub fn extend(&mut self, other: MetadataMap) {
         self.headers.extend(other.headers);
     }
+
+    /// Retains only the entries (ascii and binary) for which `f` returns
+    /// `true`, removing the others.
+    ///
+    /// `f` is called once for each value, in the order of [`MetadataMap::iter`].
+    /// The map is only rebuilt when an entry is removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use tonic::metadata::*;
+    /// let mut map = MetadataMap::new();
+    /// map.insert("x-host", "example.com".parse().unwrap());
+    /// map.insert("x-internal", "1".parse().unwrap());
+    /// map.append("x-internal", "2".parse().unwrap());
+    /// map.insert_bin("x-internal-bin", MetadataValue::from_bytes(b"[binary data]"));
+    ///
+    /// map.retain(|entry| match entry {
+    ///     KeyAndValueRef::Ascii(key, value) => key != "x-internal" || value == "2",
+    ///     KeyAndValueRef::Binary(key, _) => !key.as_str().starts_with("x-internal"),
+    /// });
+    ///
+    /// assert_eq!(map.len(), 2);
+    /// assert_eq!(map.get_all("x-internal").iter().collect::<Vec<_>>(), ["2"]);
+    /// assert!(!map.contains_key("x-internal-bin"));
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(KeyAndValueRef<'_>) -> bool,
+    {
+        let keep: Vec<bool> = self.iter().map(&mut f).collect();
+        if keep.iter().all(|keep| *keep) {
+            return;
+        }
+
+        let headers = std::mem::take(&mut self.headers);
+        let len = keep.iter().filter(|keep| **keep).count();
+        self.headers.reserve(len);
+        let mut name = None;
+        // The headers are moved in the same order as they are iterated.
+        for ((next, value), keep) in headers.into_iter().zip(keep) {
+            if next.is_some() {
+                name = next;
+            }
+            if keep {
+                let name = name.clone().expect("first value has a name");
+                self.headers.append(name, value);
+            }
+        }
+    }
 }
 
 // ===== impl Iter =====
@@ -2729,6 +2844,55 @@ mod tests {
         assert!(found_x_word_bin);
     }
 
+    #[test]
+    fn test_get_all_ignores_the_case_of_keys() {
+        let mut map = MetadataMap::new();
+
+        map.append("x-word", "hello".parse().unwrap());
+        map.append("x-word", "goodbye".parse().unwrap());
+        map.append_bin("x-word-bin", MetadataValue::from_bytes(b"binary"));
+
+        let words: Vec<_> = map.get_all("X-Word").iter().collect();
+        assert_eq!(words, ["hello", "goodbye"]);
+        assert_eq!(map.get_all_bin("X-Word-BIN").iter().count(), 1);
+        assert!(map.get("X-Word-BIN").is_none());
+        assert!(map.get_all("X-Word-Bin").iter().next().is_none());
+    }
+
+    #[test]
+    fn test_retain_keeps_the_order_of_values() {
+        let mut map = MetadataMap::new();
+
+        map.append("x-a", "1".parse().unwrap());
+        map.append("x-b", "2".parse().unwrap());
+        map.append("x-a", "3".parse().unwrap());
+        map.append("x-a", "4".parse().unwrap());
+
+        let mut seen = 0;
+        map.retain(|entry| {
+            seen += 1;
+            match entry {
+                KeyAndValueRef::Ascii(_, value) => value != "3",
+                KeyAndValueRef::Binary(..) => true,
+            }
+        });
+
+        assert_eq!(seen, 4);
+        let values: Vec<_> = map.get_all("x-a").iter().collect();
+        assert_eq!(values, ["1", "4"]);
+        assert_eq!(map.get("x-b").unwrap(), "2");
+    }
+
+    #[test]
+    fn test_converts_to_and_from_header_maps() {
+        let mut headers = http::HeaderMap::new();
+        headers.insert("x-word", "hello".parse().unwrap());
+
+        let map = MetadataMap::from(headers.clone());
+        assert_eq!(map.get("x-word").unwrap(), "hello");
+        assert_eq!(http::HeaderMap::from(map), headers);
+    }
+
     #[allow(dead_code)]
     fn value_drain_is_send_sync() {
         fn is_send_sync<T: Send + Sync>() {}
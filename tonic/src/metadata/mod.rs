@@ -32,6 +32,10 @@ pub use self::value::BinaryMetadataValue;
 pub use self::value::MetadataValue;
 use http::HeaderValue;
 
+#[cfg(feature = "channel")]
+pub(crate) use self::encoding::ValueEncoding;
+#[cfg(any(feature = "server", feature = "channel"))]
+pub(crate) use self::map::header_list_size;
 pub(crate) use self::map::GRPC_TIMEOUT_HEADER;
 
 /// HTTP Header `content-type` value for gRPC calls.
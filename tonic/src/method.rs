@@ -0,0 +1,183 @@
+use crate::GrpcMethod;
+
+/// Describes a method of a gRPC service.
+///
+/// Descriptors are generated by `tonic-build` for each method, along with a
+/// `METHODS` table listing the descriptors of each service, and can be used
+/// by metrics, authorization policies or routers to refer to methods without
+/// duplicating their names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MethodDescriptor {
+    service: &'static str,
+    method: &'static str,
+    path: &'static str,
+    client_streaming: bool,
+    server_streaming: bool,
+    idempotency_level: IdempotencyLevel,
+}
+
+impl MethodDescriptor {
+    /// Create a new `MethodDescriptor`.
+    #[doc(hidden)]
+    pub const fn new(
+        service: &'static str,
+        method: &'static str,
+        path: &'static str,
+        client_streaming: bool,
+        server_streaming: bool,
+    ) -> Self {
+        Self {
+            service,
+            method,
+            path,
+            client_streaming,
+            server_streaming,
+            idempotency_level: IdempotencyLevel::Unknown,
+        }
+    }
+
+    /// Set the idempotency level of the method.
+    #[doc(hidden)]
+    pub const fn with_idempotency_level(self, idempotency_level: IdempotencyLevel) -> Self {
+        Self {
+            idempotency_level,
+            ..self
+        }
+    }
+
+    /// The fully qualified name of the service, e.g. `helloworld.Greeter`.
+    pub const fn service(&self) -> &'static str {
+        self.service
+    }
+
+    /// The name of the method, e.g. `SayHello`.
+    pub const fn method(&self) -> &'static str {
+        self.method
+    }
+
+    /// The path of the method in requests, e.g. `/helloworld.Greeter/SayHello`.
+    pub const fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Whether the client sends a stream of messages.
+    pub const fn client_streaming(&self) -> bool {
+        self.client_streaming
+    }
+
+    /// Whether the server sends a stream of messages.
+    pub const fn server_streaming(&self) -> bool {
+        self.server_streaming
+    }
+
+    /// The idempotency level of the method, as declared by its
+    /// `idempotency_level` option.
+    pub const fn idempotency_level(&self) -> IdempotencyLevel {
+        self.idempotency_level
+    }
+
+    /// The [`GrpcMethod`] extension of the requests to the method.
+    pub fn grpc_method(&self) -> GrpcMethod<'static> {
+        GrpcMethod::new(self.service, self.method)
+    }
+}
+
+/// Whether calling a method more than once has side effects, as declared by
+/// the `idempotency_level` option of the method.
+///
+/// Besides the [`MethodDescriptor`] of each method, the clients generated by
+/// `tonic-build` insert the level of the methods declaring one into the
+/// extensions of their requests, so retry and hedging layers can only retry
+/// the calls of idempotent methods:
+///
+/// ```
+/// # use tonic::IdempotencyLevel;
+/// fn is_retryable<B>(request: &http::Request<B>) -> bool {
+///     request
+///         .extensions()
+///         .get::<IdempotencyLevel>()
+///         .is_some_and(|level| level.is_idempotent())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IdempotencyLevel {
+    /// The method may have side effects, the default.
+    #[default]
+    Unknown,
+    /// The method has no side effects, e.g. it only reads state.
+    NoSideEffects,
+    /// The method may have side effects, but calling it more than once has
+    /// the same effects as calling it once.
+    Idempotent,
+}
+
+impl IdempotencyLevel {
+    /// Whether calling the method more than once has the same effects as
+    /// calling it once, so its calls may be retried or hedged.
+    pub const fn is_idempotent(&self) -> bool {
+        matches!(self, Self::NoSideEffects | Self::Idempotent)
+    }
+}
+
+/// An HTTP route of a method, as declared by a `google.api.http` annotation
+/// of the method.
+///
+/// Routes are generated by `tonic-build` in the `HTTP_ROUTES` table of each
+/// service when enabled, so that a transcoding layer mapping HTTP/JSON
+/// requests to the methods can be set up without a hand-written route table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HttpRoute {
+    method: MethodDescriptor,
+    http_method: &'static str,
+    path_template: &'static str,
+    body: Option<&'static str>,
+    response_body: Option<&'static str>,
+}
+
+impl HttpRoute {
+    /// Create a new `HttpRoute`.
+    #[doc(hidden)]
+    pub const fn new(
+        method: MethodDescriptor,
+        http_method: &'static str,
+        path_template: &'static str,
+        body: Option<&'static str>,
+        response_body: Option<&'static str>,
+    ) -> Self {
+        Self {
+            method,
+            http_method,
+            path_template,
+            body,
+            response_body,
+        }
+    }
+
+    /// The method the route maps to.
+    pub const fn method(&self) -> &MethodDescriptor {
+        &self.method
+    }
+
+    /// The HTTP method of the route, e.g. `GET`, or the kind of a custom
+    /// pattern.
+    pub const fn http_method(&self) -> &'static str {
+        self.http_method
+    }
+
+    /// The path template of the route, e.g. `/v1/{name=shelves/*}`.
+    pub const fn path_template(&self) -> &'static str {
+        self.path_template
+    }
+
+    /// The field of the request message mapped to the request body, `*` for
+    /// the whole message, or `None` if the request has no body.
+    pub const fn body(&self) -> Option<&'static str> {
+        self.body
+    }
+
+    /// The field of the response message mapped to the response body, or
+    /// `None` for the whole message.
+    pub const fn response_body(&self) -> Option<&'static str> {
+        self.response_body
+    }
+}
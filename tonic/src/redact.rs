@@ -0,0 +1,83 @@
+use std::fmt;
+
+/// Clears the sensitive fields of a message, e.g. personal data, before it is logged or recorded.
+///
+/// `tonic-build` implements it for the generated messages with fields redacted by
+/// `Builder::redact_field`, and for the messages with fields of these messages, so that the
+/// redaction of a message also redacts the messages it holds. It may also be implemented by hand:
+///
+/// ```
+/// # use tonic::RedactMessage;
+/// #[derive(Debug, Clone)]
+/// struct SignUp {
+///     name: String,
+///     password: String,
+/// }
+///
+/// impl RedactMessage for SignUp {
+///     fn redact(&mut self) {
+///         self.password.clear();
+///     }
+/// }
+/// ```
+///
+/// Redacted messages are logged with [`Redacted`], and recorded with
+/// [`RecordLayer::redact_requests`](crate::service::RecordLayer::redact_requests).
+pub trait RedactMessage {
+    /// Clear the sensitive fields of the message.
+    fn redact(&mut self);
+}
+
+impl<T: RedactMessage + ?Sized> RedactMessage for Box<T> {
+    fn redact(&mut self) {
+        (**self).redact();
+    }
+}
+
+impl<T: RedactMessage> RedactMessage for Vec<T> {
+    fn redact(&mut self) {
+        for message in self {
+            message.redact();
+        }
+    }
+}
+
+impl<T: RedactMessage> RedactMessage for Option<T> {
+    fn redact(&mut self) {
+        if let Some(message) = self {
+            message.redact();
+        }
+    }
+}
+
+/// Formats a message with [`fmt::Debug`] once redacted, leaving the message itself as is.
+///
+/// ```
+/// # use tonic::{RedactMessage, Redacted};
+/// # #[derive(Debug, Clone)]
+/// # struct SignUp { name: String, password: String }
+/// # impl RedactMessage for SignUp {
+/// #     fn redact(&mut self) { self.password.clear(); }
+/// # }
+/// let request = tonic::Request::new(SignUp {
+///     name: "alice".to_string(),
+///     password: "hunter2".to_string(),
+/// });
+/// let logged = format!("{:?}", Redacted(request.get_ref()));
+/// assert_eq!(logged, r#"SignUp { name: "alice", password: "" }"#);
+/// ```
+///
+/// The message is cloned to be redacted each time it is formatted.
+#[derive(Clone, Copy)]
+pub struct Redacted<'a, T>(pub &'a T);
+
+impl<T> fmt::Debug for Redacted<'_, T>
+where
+    T: RedactMessage + Clone + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut message = self.0.clone();
+        message.redact();
+        fmt::Debug::fmt(&message, f)
+    }
+}
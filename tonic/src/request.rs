@@ -3,12 +3,18 @@ use crate::metadata::{MetadataMap, MetadataValue};
 use crate::transport::server::TcpConnectInfo;
 #[cfg(all(feature = "server", feature = "_tls-any"))]
 use crate::transport::server::TlsConnectInfo;
+#[cfg(feature = "server")]
+use crate::Status;
 use http::Extensions;
 #[cfg(feature = "server")]
+use std::future::Future;
+#[cfg(feature = "server")]
 use std::net::SocketAddr;
 #[cfg(all(feature = "server", feature = "_tls-any"))]
 use std::sync::Arc;
-use std::time::Duration;
+#[cfg(feature = "server")]
+use std::task::Poll;
+use std::time::{Duration, Instant};
 #[cfg(all(feature = "server", feature = "_tls-any"))]
 use tokio_rustls::rustls::pki_types::CertificateDer;
 use tokio_stream::Stream;
@@ -296,6 +302,76 @@ impl<T> Request<T> {
             .insert(crate::metadata::GRPC_TIMEOUT_HEADER, value);
     }
 
+    /// Get the deadline of the request on the server.
+    ///
+    /// The deadline is set by the server of `transport` from the
+    /// `grpc-timeout` header of the request, after applying the default and
+    /// max timeouts of the server, see
+    /// [`Server::default_grpc_timeout`](crate::transport::Server::default_grpc_timeout)
+    /// and [`Server::max_grpc_timeout`](crate::transport::Server::max_grpc_timeout),
+    /// along with its [`Server::timeout`](crate::transport::Server::timeout).
+    /// The server cancels the request once its deadline passes.
+    ///
+    /// Returns `None` when the request has no deadline.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.extensions()
+            .get::<GrpcDeadline>()
+            .map(|deadline| deadline.deadline)
+    }
+
+    /// Get the time left before the deadline of the request on the server.
+    ///
+    /// Returns `None` when the request has no deadline, see
+    /// [`Request::deadline`], and zero once the deadline passed.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.extensions()
+            .get::<GrpcDeadline>()
+            .map(GrpcDeadline::remaining)
+    }
+
+    /// Run `fut` until the deadline of the request on the server.
+    ///
+    /// `fut` is dropped, cancelling its work, once the deadline passes, and
+    /// the returned future then fails with `DeadlineExceeded`, so handlers
+    /// stop working on requests the client gave up on. Without a deadline,
+    /// see [`Request::deadline`], `fut` runs to completion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::{Request, Response, Status};
+    /// # async fn lookup(name: String) -> Result<String, Status> { Ok(name) }
+    /// async fn greet(request: Request<String>) -> Result<Response<String>, Status> {
+    ///     let name = request.get_ref().clone();
+    ///     let greeting = request.with_deadline(lookup(name)).await?;
+    ///     Ok(Response::new(greeting))
+    /// }
+    /// ```
+    #[cfg(feature = "server")]
+    pub fn with_deadline<F, U>(&self, fut: F) -> impl Future<Output = Result<U, Status>>
+    where
+        F: Future<Output = Result<U, Status>>,
+    {
+        use hyper::rt::Timer;
+
+        let deadline = self.extensions().get::<GrpcDeadline>().cloned();
+        async move {
+            let Some(GrpcDeadline { deadline, timer }) = deadline else {
+                return fut.await;
+            };
+            let mut fut = std::pin::pin!(fut);
+            let mut sleep = timer.sleep_until(deadline);
+            std::future::poll_fn(|cx| {
+                if let Poll::Ready(result) = fut.as_mut().poll(cx) {
+                    return Poll::Ready(result);
+                }
+                std::task::ready!(sleep.as_mut().poll(cx));
+                Poll::Ready(Err(Status::deadline_exceeded("deadline exceeded")))
+            })
+            .await
+        }
+    }
+
     /// Returns a reference to the associated extensions.
     pub fn extensions(&self) -> &Extensions {
         &self.extensions
@@ -394,6 +470,25 @@ mod sealed {
     pub trait Sealed {}
 }
 
+/// The deadline of a request on the server, see [`Request::deadline`].
+#[derive(Debug, Clone)]
+pub(crate) struct GrpcDeadline {
+    pub(crate) deadline: Instant,
+    // The timer of the server, telling the time left before the deadline.
+    #[cfg(any(feature = "server", feature = "channel"))]
+    pub(crate) timer: crate::transport::service::SharedTimer,
+}
+
+impl GrpcDeadline {
+    fn remaining(&self) -> Duration {
+        #[cfg(any(feature = "server", feature = "channel"))]
+        let now = hyper::rt::Timer::now(&self.timer);
+        #[cfg(not(any(feature = "server", feature = "channel")))]
+        let now = Instant::now();
+        self.deadline.saturating_duration_since(now)
+    }
+}
+
 fn duration_to_grpc_timeout(duration: Duration) -> String {
     fn try_format<T: Into<u128>>(
         duration: Duration,
@@ -0,0 +1,186 @@
+use std::{ops::Deref, sync::Arc};
+
+use crate::{metadata::MetadataMap, service::Interceptor, Request, Status};
+
+/// Extracts a value from the metadata and extensions of a request.
+///
+/// The handlers of the methods configured with `tonic-build`'s `Builder::handler_extractor` take
+/// the extracted values as arguments, ahead of their request, so that each handler doesn't repeat
+/// the extraction. A request failing to be extracted is answered with the status returned,
+/// without calling its handler:
+///
+/// ```
+/// use tonic::{server::FromRequest, Request, Status};
+///
+/// struct User(String);
+///
+/// impl FromRequest for User {
+///     fn from_request<T>(request: &Request<T>) -> Result<Self, Status> {
+///         request
+///             .metadata()
+///             .get("x-user")
+///             .and_then(|user| user.to_str().ok())
+///             .map(|user| User(user.to_string()))
+///             .ok_or_else(|| Status::unauthenticated("missing user"))
+///     }
+/// }
+/// ```
+///
+/// With `.handler_extractor("helloworld.Greeter.SayHello", "crate::User")`, the handler of the
+/// method is then generated as:
+///
+/// ```ignore
+/// async fn say_hello(
+///     &self,
+///     user: crate::User,
+///     request: Request<HelloRequest>,
+/// ) -> Result<Response<HelloReply>, Status>;
+/// ```
+pub trait FromRequest: Sized {
+    /// Extract the value from `request`.
+    fn from_request<T>(request: &Request<T>) -> Result<Self, Status>;
+}
+
+impl FromRequest for MetadataMap {
+    fn from_request<T>(request: &Request<T>) -> Result<Self, Status> {
+        Ok(request.metadata().clone())
+    }
+}
+
+impl<E: FromRequest> FromRequest for Option<E> {
+    fn from_request<T>(request: &Request<T>) -> Result<Self, Status> {
+        Ok(E::from_request(request).ok())
+    }
+}
+
+/// Extracts a clone of the extension of type `T` of a request, e.g. inserted by an interceptor.
+///
+/// Requests without the extension fail with `Internal`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Extension<T>(pub T);
+
+impl<E> FromRequest for Extension<E>
+where
+    E: Clone + Send + Sync + 'static,
+{
+    fn from_request<T>(request: &Request<T>) -> Result<Self, Status> {
+        match request.extensions().get::<E>() {
+            Some(extension) => Ok(Extension(extension.clone())),
+            None => Err(Status::internal(format!(
+                "missing request extension {}",
+                std::any::type_name::<E>()
+            ))),
+        }
+    }
+}
+
+/// Shared state of a server, e.g. its database pool and configuration.
+///
+/// The generated `with_state` constructors of the servers serve their services with a state,
+/// inserting it in the extensions of each request as an [`Interceptor`], so that the
+/// implementations of the services don't each hold and clone their own `Arc`s. Handlers take the
+/// state as an extractor argument, configured with `tonic-build`'s `Builder::handler_extractor`,
+/// e.g. `tonic::server::State<crate::AppState>`, or extract it themselves:
+///
+/// ```
+/// # use std::sync::Arc;
+/// # use tonic::{server::{FromRequest, State}, Request, Status};
+/// struct AppState {
+///     greeting: String,
+/// }
+///
+/// fn greet(request: &Request<String>) -> Result<String, Status> {
+///     let state = State::<AppState>::from_request(request)?;
+///     Ok(format!("{} {}", state.greeting, request.get_ref()))
+/// }
+/// # let mut request = Request::new("world".to_string());
+/// # let state = Arc::new(AppState { greeting: "Hello".to_string() });
+/// # request.extensions_mut().insert(State(state));
+/// # assert_eq!(greet(&request).unwrap(), "Hello world");
+/// ```
+///
+/// Requests without the state fail with `Internal`.
+#[derive(Debug)]
+pub struct State<S>(pub Arc<S>);
+
+impl<S> Clone for State<S> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<S> Deref for State<S> {
+    type Target = S;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<S> FromRequest for State<S>
+where
+    S: Send + Sync + 'static,
+{
+    fn from_request<T>(request: &Request<T>) -> Result<Self, Status> {
+        match request.extensions().get::<State<S>>() {
+            Some(state) => Ok(state.clone()),
+            None => Err(Status::internal(format!(
+                "missing server state {}",
+                std::any::type_name::<S>()
+            ))),
+        }
+    }
+}
+
+impl<S> Interceptor for State<S>
+where
+    S: Send + Sync + 'static,
+{
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request.extensions_mut().insert(self.clone());
+        Ok(request)
+    }
+}
+
+/// Extracts the remote address of a request, see [`Request::remote_addr`].
+///
+/// Requests without it fail with `Internal`.
+#[cfg(feature = "server")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteAddr(pub std::net::SocketAddr);
+
+#[cfg(feature = "server")]
+impl FromRequest for RemoteAddr {
+    fn from_request<T>(request: &Request<T>) -> Result<Self, Status> {
+        request
+            .remote_addr()
+            .map(RemoteAddr)
+            .ok_or_else(|| Status::internal("missing remote address"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Tenant(&'static str);
+
+    #[test]
+    fn extracts_extensions() {
+        let mut request = Request::new(());
+        assert_eq!(
+            Extension::<Tenant>::from_request(&request)
+                .unwrap_err()
+                .code(),
+            crate::Code::Internal
+        );
+        assert!(Option::<Extension<Tenant>>::from_request(&request)
+            .unwrap()
+            .is_none());
+
+        request.extensions_mut().insert(Tenant("a"));
+        let Extension(tenant) = Extension::<Tenant>::from_request(&request).unwrap();
+        assert_eq!(tenant, Tenant("a"));
+    }
+}
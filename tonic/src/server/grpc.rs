@@ -1,7 +1,8 @@
 use crate::codec::compression::{
-    CompressionEncoding, EnabledCompressionEncodings, SingleMessageCompressionOverride,
+    accept_encodings, CompressionEncoding, EnabledCompressionEncodings, MessageCompression,
+    SingleMessageCompressionOverride,
 };
-use crate::codec::EncodeBody;
+use crate::codec::{CompressionPolicy, EncodeBody};
 use crate::metadata::GRPC_CONTENT_TYPE;
 use crate::{
     body::Body,
@@ -10,7 +11,7 @@ use crate::{
     Request, Status,
 };
 use http_body::Body as HttpBody;
-use std::{fmt, pin::pin};
+use std::{fmt, pin::pin, sync::Arc};
 use tokio_stream::{Stream, StreamExt};
 
 macro_rules! t {
@@ -37,6 +38,8 @@ pub struct Grpc<T> {
     accept_compression_encodings: EnabledCompressionEncodings,
     /// Which compression encodings might the server use for responses.
     send_compression_encodings: EnabledCompressionEncodings,
+    /// Decides how responses are compressed, in place of `send_compression_encodings`.
+    send_compression_policy: Option<Arc<dyn CompressionPolicy>>,
     /// Limits the maximum size of a decoded message.
     max_decoding_message_size: Option<usize>,
     /// Limits the maximum size of an encoded message.
@@ -53,6 +56,7 @@ where
             codec,
             accept_compression_encodings: EnabledCompressionEncodings::default(),
             send_compression_encodings: EnabledCompressionEncodings::default(),
+            send_compression_policy: None,
             max_decoding_message_size: None,
             max_encoding_message_size: None,
         }
@@ -121,6 +125,53 @@ where
         self
     }
 
+    /// Decide whether and how responses are compressed with a [`CompressionPolicy`], in place of
+    /// the encodings enabled with [`Grpc::send_compressed`].
+    ///
+    /// Requires the client to also support receiving compressed responses.
+    ///
+    /// # Example
+    ///
+    /// The most common way of using this is through a server generated by tonic-build:
+    ///
+    /// ```rust
+    /// # use tonic::codec::CompressionPolicy;
+    /// # struct Svc;
+    /// # struct ExampleServer<T>(T);
+    /// # impl<T> ExampleServer<T> {
+    /// #     fn new(svc: T) -> Self { Self(svc) }
+    /// #     fn send_compression_policy(self, _: impl CompressionPolicy) -> Self { self }
+    /// # }
+    /// # #[tonic::async_trait]
+    /// # trait Example {}
+    ///
+    /// #[tonic::async_trait]
+    /// impl Example for Svc {
+    ///     // ...
+    /// }
+    ///
+    /// /// Compresses the messages of at least 1KiB with the encoding the client prefers.
+    /// #[derive(Debug)]
+    /// struct LargeMessages;
+    ///
+    /// impl CompressionPolicy for LargeMessages {
+    ///     fn compress_message(
+    ///         &self,
+    ///         _method: &str,
+    ///         _encoding: tonic::codec::CompressionEncoding,
+    ///         message_size: usize,
+    ///     ) -> bool {
+    ///         message_size >= 1024
+    ///     }
+    /// }
+    ///
+    /// let service = ExampleServer::new(Svc).send_compression_policy(LargeMessages);
+    /// ```
+    pub fn send_compression_policy(mut self, policy: Arc<dyn CompressionPolicy>) -> Self {
+        self.send_compression_policy = Some(policy);
+        self
+    }
+
     /// Limits the maximum size of a decoded message.
     ///
     /// # Example
@@ -199,6 +250,15 @@ where
         self
     }
 
+    #[doc(hidden)]
+    pub fn apply_compression_policy(mut self, policy: Option<Arc<dyn CompressionPolicy>>) -> Self {
+        if let Some(policy) = policy {
+            self = self.send_compression_policy(policy);
+        }
+
+        self
+    }
+
     #[doc(hidden)]
     pub fn apply_max_message_size_config(
         mut self,
@@ -226,17 +286,14 @@ where
         B: HttpBody + Send + 'static,
         B::Error: Into<crate::BoxError> + Send,
     {
-        let accept_encoding = CompressionEncoding::from_accept_encoding_header(
-            req.headers(),
-            self.send_compression_encodings,
-        );
+        let compression = self.response_compression(&req);
 
         let request = match self.map_request_unary(req).await {
             Ok(r) => r,
             Err(status) => {
                 return self.map_response::<tokio_stream::Once<Result<T::Encode, Status>>>(
                     Err(status),
-                    accept_encoding,
+                    compression,
                     SingleMessageCompressionOverride::default(),
                     self.max_encoding_message_size,
                 );
@@ -252,7 +309,7 @@ where
 
         self.map_response(
             response,
-            accept_encoding,
+            compression,
             compression_override,
             self.max_encoding_message_size,
         )
@@ -270,17 +327,14 @@ where
         B: HttpBody + Send + 'static,
         B::Error: Into<crate::BoxError> + Send,
     {
-        let accept_encoding = CompressionEncoding::from_accept_encoding_header(
-            req.headers(),
-            self.send_compression_encodings,
-        );
+        let compression = self.response_compression(&req);
 
         let request = match self.map_request_unary(req).await {
             Ok(r) => r,
             Err(status) => {
                 return self.map_response::<S::ResponseStream>(
                     Err(status),
-                    accept_encoding,
+                    compression,
                     SingleMessageCompressionOverride::default(),
                     self.max_encoding_message_size,
                 );
@@ -291,7 +345,7 @@ where
 
         self.map_response(
             response,
-            accept_encoding,
+            compression,
             // disabling compression of individual stream items must be done on
             // the items themselves
             SingleMessageCompressionOverride::default(),
@@ -310,10 +364,7 @@ where
         B: HttpBody + Send + 'static,
         B::Error: Into<crate::BoxError> + Send + 'static,
     {
-        let accept_encoding = CompressionEncoding::from_accept_encoding_header(
-            req.headers(),
-            self.send_compression_encodings,
-        );
+        let compression = self.response_compression(&req);
 
         let request = t!(self.map_request_streaming(req));
 
@@ -326,7 +377,7 @@ where
 
         self.map_response(
             response,
-            accept_encoding,
+            compression,
             compression_override,
             self.max_encoding_message_size,
         )
@@ -339,15 +390,12 @@ where
         req: http::Request<B>,
     ) -> http::Response<Body>
     where
-        S: StreamingService<T::Decode, Response = T::Encode> + Send,
+        S: StreamingService<T::Decode, Response = T::Encode>,
         S::ResponseStream: Send + 'static,
         B: HttpBody + Send + 'static,
         B::Error: Into<crate::BoxError> + Send,
     {
-        let accept_encoding = CompressionEncoding::from_accept_encoding_header(
-            req.headers(),
-            self.send_compression_encodings,
-        );
+        let compression = self.response_compression(&req);
 
         let request = t!(self.map_request_streaming(req));
 
@@ -355,7 +403,7 @@ where
 
         self.map_response(
             response,
-            accept_encoding,
+            compression,
             SingleMessageCompressionOverride::default(),
             self.max_encoding_message_size,
         )
@@ -388,7 +436,7 @@ where
         let mut req = Request::from_http_parts(parts, message);
 
         if let Some(trailers) = stream.trailers().await? {
-            req.metadata_mut().merge(trailers);
+            req.metadata_mut().extend(trailers);
         }
 
         Ok(req)
@@ -416,10 +464,36 @@ where
         Ok(Request::from_http(request))
     }
 
+    fn response_compression<B>(&self, request: &http::Request<B>) -> ResponseCompression {
+        let Some(policy) = &self.send_compression_policy else {
+            return ResponseCompression {
+                encoding: CompressionEncoding::from_accept_encoding_header(
+                    request.headers(),
+                    self.send_compression_encodings,
+                ),
+                messages: None,
+            };
+        };
+
+        let method = request.uri().path();
+        let accept_encodings = accept_encodings(request.headers());
+        let encoding = policy
+            .encoding(method, &accept_encodings)
+            .filter(|encoding| accept_encodings.contains(encoding));
+
+        ResponseCompression {
+            encoding,
+            messages: encoding.map(|_| MessageCompression {
+                policy: policy.clone(),
+                method: method.to_owned(),
+            }),
+        }
+    }
+
     fn map_response<B>(
         &mut self,
         response: Result<crate::Response<B>, Status>,
-        accept_encoding: Option<CompressionEncoding>,
+        compression: ResponseCompression,
         compression_override: SingleMessageCompressionOverride,
         max_message_size: Option<usize>,
     ) -> http::Response<Body>
@@ -436,7 +510,7 @@ where
             .insert(http::header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
 
         #[cfg(any(feature = "gzip", feature = "deflate", feature = "zstd"))]
-        if let Some(encoding) = accept_encoding {
+        if let Some(encoding) = compression.encoding {
             // Set the content encoding
             parts.headers.insert(
                 crate::codec::compression::ENCODING_HEADER,
@@ -447,10 +521,11 @@ where
         let body = EncodeBody::new_server(
             self.codec.encoder(),
             body,
-            accept_encoding,
+            compression.encoding,
             compression_override,
             max_message_size,
-        );
+        )
+        .with_message_compression(compression.messages);
 
         http::Response::from_parts(parts, Body::new(body))
     }
@@ -478,10 +553,19 @@ impl<T: fmt::Debug> fmt::Debug for Grpc<T> {
                 "send_compression_encodings",
                 &self.send_compression_encodings,
             )
+            .field("send_compression_policy", &self.send_compression_policy)
             .finish()
     }
 }
 
+/// How a response is compressed.
+struct ResponseCompression {
+    /// The encoding of the response, if it is compressed.
+    encoding: Option<CompressionEncoding>,
+    /// Decides which messages of the response are compressed, all of them if unset.
+    messages: Option<MessageCompression>,
+}
+
 fn compression_override_from_response<B, E>(
     res: &Result<crate::Response<B>, E>,
 ) -> SingleMessageCompressionOverride {
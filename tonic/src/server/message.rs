@@ -0,0 +1,198 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    fmt,
+    sync::Arc,
+};
+
+use crate::{service::Interceptor, Extensions, MethodDescriptor, Request, Status};
+
+type Hook<M> =
+    Box<dyn Fn(&MethodDescriptor, &M, &mut Extensions) -> Result<(), Status> + Send + Sync>;
+
+/// Middleware running on the decoded request messages of a server, before its handlers.
+///
+/// Interceptors run before the request messages are decoded, so middleware reading the messages,
+/// e.g. to authorize a request on the resource it names or to record the resource in the
+/// extensions of the request, would otherwise be repeated in each handler. Hooks are registered
+/// by message type, and called with the descriptor of the method and the message, along with the
+/// extensions of the request to enrich. A hook failing answers the request with its status,
+/// without calling the handler.
+///
+/// Hooks run on the methods configured with `tonic-build`'s `Builder::intercept_messages`, for
+/// requests served with the `MessageInterceptors` as an [`Interceptor`], e.g. with the generated
+/// `with_interceptor` constructors of the servers:
+///
+/// ```
+/// # use tonic::{server::MessageInterceptors, Request, Status};
+/// # #[derive(Clone)]
+/// # struct GetBookRequest { name: String }
+/// #[derive(Clone)]
+/// struct Shelf(String);
+///
+/// let interceptors = MessageInterceptors::new().on(
+///     |_method, request: &GetBookRequest, extensions| match request.name.split_once("/books/") {
+///         Some((shelf, _)) => {
+///             extensions.insert(Shelf(shelf.to_string()));
+///             Ok(())
+///         }
+///         None => Err(Status::invalid_argument("name must be a book")),
+///     },
+/// );
+/// # let mut request = Request::new(GetBookRequest { name: "shelves/1/books/2".to_string() });
+/// # request.extensions_mut().insert(interceptors);
+/// # const DESCRIPTOR: tonic::MethodDescriptor = tonic::MethodDescriptor::new(
+/// #     "library.Library",
+/// #     "GetBook",
+/// #     "/library.Library/GetBook",
+/// #     false,
+/// #     false,
+/// # );
+/// # let request = tonic::server::intercept_message(&DESCRIPTOR, request).unwrap();
+/// # assert_eq!(request.extensions().get::<Shelf>().unwrap().0, "shelves/1");
+/// ```
+///
+/// Only the messages of unary and server streaming methods are intercepted, the messages of
+/// request streams being decoded by the handlers.
+#[derive(Clone, Default)]
+pub struct MessageInterceptors {
+    hooks: Arc<HashMap<TypeId, Vec<Arc<dyn Any + Send + Sync>>>>,
+}
+
+impl MessageInterceptors {
+    /// Creates `MessageInterceptors` without any hook.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a hook running on the request messages of type `M`, after the hooks registered
+    /// before it.
+    #[must_use]
+    pub fn on<M, F>(mut self, hook: F) -> Self
+    where
+        M: 'static,
+        F: Fn(&MethodDescriptor, &M, &mut Extensions) -> Result<(), Status> + Send + Sync + 'static,
+    {
+        let hook: Hook<M> = Box::new(hook);
+        Arc::make_mut(&mut self.hooks)
+            .entry(TypeId::of::<M>())
+            .or_default()
+            .push(Arc::new(hook));
+        self
+    }
+
+    /// Runs the hooks registered for the request messages of type `M` on `request`, stopping at
+    /// the first failing.
+    pub fn intercept<M: 'static>(
+        &self,
+        method: &MethodDescriptor,
+        request: Request<M>,
+    ) -> Result<Request<M>, Status> {
+        let Some(hooks) = self.hooks.get(&TypeId::of::<M>()) else {
+            return Ok(request);
+        };
+
+        let (metadata, mut extensions, message) = request.into_parts();
+        for hook in hooks {
+            if let Some(hook) = hook.downcast_ref::<Hook<M>>() {
+                hook(method, &message, &mut extensions)?;
+            }
+        }
+        Ok(Request::from_parts(metadata, extensions, message))
+    }
+}
+
+impl fmt::Debug for MessageInterceptors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageInterceptors")
+            .field("hooks", &self.hooks.values().map(Vec::len).sum::<usize>())
+            .finish()
+    }
+}
+
+impl Interceptor for MessageInterceptors {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        request.extensions_mut().insert(self.clone());
+        Ok(request)
+    }
+}
+
+/// Runs the [`MessageInterceptors`] inserted in the extensions of `request` on its message,
+/// passing requests without any through.
+///
+/// This is called by the servers generated by `tonic-build` for the methods configured with
+/// `Builder::intercept_messages`, after decoding their requests.
+pub fn intercept_message<M: 'static>(
+    method: &MethodDescriptor,
+    request: Request<M>,
+) -> Result<Request<M>, Status> {
+    match request.extensions().get::<MessageInterceptors>().cloned() {
+        Some(interceptors) => interceptors.intercept(method, request),
+        None => Ok(request),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Code;
+
+    const DESCRIPTOR: MethodDescriptor =
+        MethodDescriptor::new("test.Test", "Get", "/test.Test/Get", false, false);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Seen(Vec<&'static str>);
+
+    fn record(
+        name: &'static str,
+    ) -> impl Fn(&MethodDescriptor, &u32, &mut Extensions) -> Result<(), Status> {
+        move |_, _, extensions| {
+            let mut seen = extensions.remove::<Seen>().unwrap_or(Seen(Vec::new()));
+            seen.0.push(name);
+            extensions.insert(seen);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn runs_hooks_of_message_type_in_order() {
+        let interceptors = MessageInterceptors::new()
+            .on(record("first"))
+            .on(|_, _: &String, _| Err(Status::internal("not a u32")))
+            .on(record("second"));
+
+        let request = interceptors
+            .intercept(&DESCRIPTOR, Request::new(1u32))
+            .unwrap();
+        assert_eq!(
+            request.extensions().get::<Seen>(),
+            Some(&Seen(vec!["first", "second"]))
+        );
+    }
+
+    #[test]
+    fn failing_hook_stops_the_request() {
+        let interceptors = MessageInterceptors::new()
+            .on(
+                |method: &MethodDescriptor, message: &u32, _: &mut Extensions| {
+                    if *message == 0 {
+                        return Err(Status::invalid_argument(format!(
+                            "{} of 0",
+                            method.method()
+                        )));
+                    }
+                    Ok(())
+                },
+            )
+            .on(record("after"));
+
+        let mut request = Request::new(0u32);
+        request.extensions_mut().insert(interceptors);
+        let err = intercept_message(&DESCRIPTOR, request).unwrap_err();
+        assert_eq!(err.code(), Code::InvalidArgument);
+        assert_eq!(err.message(), "Get of 0");
+
+        let request = intercept_message(&DESCRIPTOR, Request::new(0u32)).unwrap();
+        assert!(request.extensions().get::<Seen>().is_none());
+    }
+}
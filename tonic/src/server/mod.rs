@@ -8,10 +8,16 @@
 //! will implement the proper gRPC service. Thusly, they are a bit hard to use
 //! by hand.
 
+mod extract;
 mod grpc;
+mod message;
 mod service;
 
+#[cfg(feature = "server")]
+pub use self::extract::RemoteAddr;
+pub use self::extract::{Extension, FromRequest, State};
 pub use self::grpc::Grpc;
+pub use self::message::{intercept_message, MessageInterceptors};
 pub use self::service::{
     ClientStreamingService, ServerStreamingService, StreamingService, UnaryService,
 };
@@ -0,0 +1,361 @@
+//! Middleware isolating the calls of services and methods in their own concurrency pools.
+//!
+//! The [`BulkheadLayer`] bounds the number of calls in flight of each method or service given a
+//! pool, so a slow method exhausts its own pool rather than the concurrency of the connection or
+//! of the server, and the other methods keep being served. The calls beyond the bound of their pool
+//! fail at once with [`Code::Unavailable`], without waiting and holding the shared limits:
+//!
+//! ```
+//! # use tonic::service::bulkhead::BulkheadLayer;
+//! let bulkheads = BulkheadLayer::new()
+//!     // At most 4 reports generated at once.
+//!     .method_limit("/reports.Reports/Generate", 4)
+//!     // At most 32 calls of the other methods of the service.
+//!     .service_limit("reports.Reports", 32)
+//!     // At most 128 calls of all the other services together.
+//!     .default_limit(128);
+//!
+//! # tonic::transport::Server::builder().layer(bulkheads);
+//! ```
+//!
+//! A call is in flight until its response body ends or is dropped, so streaming calls hold their
+//! pool for as long as they stream. Every service wrapped by a layer and its clones shares the same
+//! pools, so a layer set with [`Server::layer`] bounds the calls of the whole server.
+//!
+//! [`Code::Unavailable`]: crate::Code::Unavailable
+//! [`Server::layer`]: crate::transport::Server::layer
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+};
+
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::debug;
+
+use crate::Status;
+
+/// Layer which applies the [`Bulkhead`] middleware.
+#[derive(Debug, Clone, Default)]
+pub struct BulkheadLayer {
+    pools: Arc<Pools>,
+}
+
+#[derive(Debug, Default)]
+struct Pools {
+    methods: HashMap<String, Arc<Pool>>,
+    services: HashMap<String, Arc<Pool>>,
+    default: Option<Arc<Pool>>,
+}
+
+impl Pools {
+    // The pool of the method of `path`, e.g. `/helloworld.Greeter/SayHello`.
+    fn pool(&self, path: &str) -> Option<&Arc<Pool>> {
+        let service = path
+            .strip_prefix('/')
+            .and_then(|path| path.split_once('/'))
+            .map(|(service, _)| service);
+        self.methods
+            .get(path)
+            .or_else(|| service.and_then(|service| self.services.get(service)))
+            .or(self.default.as_ref())
+    }
+}
+
+#[derive(Debug)]
+struct Pool {
+    limit: usize,
+    in_flight: AtomicUsize,
+}
+
+impl Pool {
+    fn new(limit: usize) -> Arc<Self> {
+        Arc::new(Self {
+            limit,
+            in_flight: AtomicUsize::new(0),
+        })
+    }
+
+    fn acquire(self: &Arc<Self>) -> Option<Permit> {
+        self.in_flight
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |in_flight| {
+                (in_flight < self.limit).then_some(in_flight + 1)
+            })
+            .ok()
+            .map(|_| Permit(self.clone()))
+    }
+}
+
+// A call in flight in a pool, leaving it once dropped.
+#[derive(Debug)]
+struct Permit(Arc<Pool>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl BulkheadLayer {
+    /// Create a new `BulkheadLayer`, without pools.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn pools(&mut self) -> &mut Pools {
+        Arc::get_mut(&mut self.pools).expect("configured before the layer is cloned")
+    }
+
+    /// Give the method of `path`, e.g. `/helloworld.Greeter/SayHello`, its own pool of `limit`
+    /// calls in flight.
+    ///
+    /// # Panics
+    ///
+    /// The setters panic if the layer was already cloned.
+    pub fn method_limit(mut self, path: impl Into<String>, limit: usize) -> Self {
+        self.pools().methods.insert(path.into(), Pool::new(limit));
+        self
+    }
+
+    /// Give the methods of the service `name`, e.g. `helloworld.Greeter`, without a pool of their
+    /// own a pool of `limit` calls in flight, shared by them.
+    pub fn service_limit(mut self, name: impl Into<String>, limit: usize) -> Self {
+        self.pools().services.insert(name.into(), Pool::new(limit));
+        self
+    }
+
+    /// Give the calls of all the methods without a pool of their own or of their service a pool of
+    /// `limit` calls in flight, shared by them.
+    ///
+    /// By default, these calls aren't bounded.
+    pub fn default_limit(mut self, limit: usize) -> Self {
+        self.pools().default = Some(Pool::new(limit));
+        self
+    }
+
+    /// The number of calls in flight in the pool of the method of `path`, or `None` if it has no
+    /// pool.
+    pub fn in_flight(&self, path: &str) -> Option<usize> {
+        self.pools
+            .pool(path)
+            .map(|pool| pool.in_flight.load(Ordering::Acquire))
+    }
+}
+
+impl<S> Layer<S> for BulkheadLayer {
+    type Service = Bulkhead<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Bulkhead {
+            inner,
+            pools: self.pools.clone(),
+        }
+    }
+}
+
+/// Middleware bounding the calls in flight of the methods of a service in the pools of a
+/// [`BulkheadLayer`].
+#[derive(Debug, Clone)]
+pub struct Bulkhead<S> {
+    inner: S,
+    pools: Arc<Pools>,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for Bulkhead<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    ResBody: Default,
+{
+    type Response = http::Response<ResponseBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let permit = match self.pools.pool(req.uri().path()) {
+            Some(pool) => match pool.acquire() {
+                Some(permit) => Some(permit),
+                None => {
+                    debug!(path = req.uri().path(), "bulkhead full, rejecting call");
+                    return ResponseFuture {
+                        kind: Kind::Rejected,
+                    };
+                }
+            },
+            None => None,
+        };
+        ResponseFuture {
+            kind: Kind::Called {
+                future: self.inner.call(req),
+                permit,
+            },
+        }
+    }
+}
+
+/// Response future for [`Bulkhead`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    kind: Kind<F>,
+}
+
+#[pin_project(project = KindProj)]
+enum Kind<F> {
+    Called {
+        #[pin]
+        future: F,
+        permit: Option<Permit>,
+    },
+    Rejected,
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    ResBody: Default,
+{
+    type Output = Result<http::Response<ResponseBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().kind.project() {
+            KindProj::Called { future, permit } => {
+                let res = ready!(future.poll(cx))?;
+                let permit = permit.take();
+                Poll::Ready(Ok(res.map(|inner| ResponseBody { inner, permit })))
+            }
+            KindProj::Rejected => {
+                let status = Status::unavailable("bulkhead full, call rejected");
+                Poll::Ready(Ok(status.into_http()))
+            }
+        }
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+/// Response body for [`Bulkhead`], holding the call in its pool until it ends.
+#[pin_project]
+#[derive(Debug, Default)]
+pub struct ResponseBody<B> {
+    #[pin]
+    inner: B,
+    permit: Option<Permit>,
+}
+
+impl<B: http_body::Body> http_body::Body for ResponseBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = ready!(this.inner.poll_frame(cx));
+        if frame.is_none() {
+            *this.permit = None;
+        }
+        Poll::Ready(frame)
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tower::ServiceExt;
+
+    fn service<B: Default + Send + 'static>(
+        layer: &BulkheadLayer,
+    ) -> impl Service<
+        http::Request<()>,
+        Response = http::Response<ResponseBody<B>>,
+        Error = Infallible,
+    > + Clone {
+        layer.layer(tower::service_fn(|_: http::Request<()>| async {
+            Ok(http::Response::new(B::default()))
+        }))
+    }
+
+    fn request(path: &str) -> http::Request<()> {
+        http::Request::builder().uri(path).body(()).unwrap()
+    }
+
+    fn grpc_status<B>(res: &http::Response<B>) -> Option<&str> {
+        res.headers()
+            .get("grpc-status")
+            .map(|status| status.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn full_pools_reject_their_calls_only() {
+        let layer = BulkheadLayer::new()
+            .method_limit("/test.Slow/Call", 1)
+            .service_limit("test.Slow", 2);
+        let svc = service::<()>(&layer);
+
+        // The response of a call holds its pool until dropped.
+        let slow = svc
+            .clone()
+            .oneshot(request("/test.Slow/Call"))
+            .await
+            .unwrap();
+        assert_eq!(grpc_status(&slow), None);
+        assert_eq!(layer.in_flight("/test.Slow/Call"), Some(1));
+
+        let rejected = svc.clone().oneshot(request("/test.Slow/Call")).await;
+        assert_eq!(grpc_status(&rejected.unwrap()), Some("14"));
+
+        // The other methods keep their pools, or aren't bounded.
+        let other = svc.clone().oneshot(request("/test.Slow/Other")).await;
+        assert_eq!(grpc_status(&other.unwrap()), None);
+        let unbounded = svc.clone().oneshot(request("/test.Fast/Call")).await;
+        assert_eq!(grpc_status(&unbounded.unwrap()), None);
+        assert_eq!(layer.in_flight("/test.Fast/Call"), None);
+
+        drop(slow);
+        assert_eq!(layer.in_flight("/test.Slow/Call"), Some(0));
+        let res = svc.oneshot(request("/test.Slow/Call")).await;
+        assert_eq!(grpc_status(&res.unwrap()), None);
+    }
+
+    #[tokio::test]
+    async fn default_pool_is_shared() {
+        let layer = BulkheadLayer::new().default_limit(1);
+        let svc = service::<http_body_util::Empty<bytes::Bytes>>(&layer);
+
+        let first = svc.clone().oneshot(request("/test.A/Call")).await.unwrap();
+        let second = svc.clone().oneshot(request("/test.B/Call")).await;
+        assert_eq!(grpc_status(&second.unwrap()), Some("14"));
+
+        // Pools are left once the bodies end.
+        let mut body = first.into_body();
+        assert!(http_body_util::BodyExt::frame(&mut body).await.is_none());
+        assert_eq!(layer.in_flight("/test.B/Call"), Some(0));
+    }
+}
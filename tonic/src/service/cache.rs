@@ -0,0 +1,463 @@
+//! Middleware caching the responses of unary calls.
+//!
+//! The [`ResponseCacheLayer`] answers repeated identical unary calls of a channel from a cache,
+//! without sending them, e.g. to avoid calling a configuration service for every lookup. Calls
+//! are identical when they call the same method with the same encoded request message; their
+//! metadata is not taken into account, so only cache methods whose responses are the same for all
+//! the clients of the channel.
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use tonic::{service::cache::ResponseCacheLayer, transport::Endpoint};
+//! # async fn run() -> Result<(), tonic::transport::Error> {
+//! let channel = Endpoint::from_static("http://config").connect().await?;
+//!
+//! let cache = ResponseCacheLayer::new(Duration::from_secs(30), 1000)
+//!     .method("/config.Config/GetValue");
+//! let channel = tower::ServiceBuilder::new()
+//!     .layer(cache.clone())
+//!     .service(channel);
+//!
+//! // ...
+//! let stats = cache.stats();
+//! println!("{} hits, {} misses", stats.hits, stats.misses);
+//! # drop(channel);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Only the successful responses are cached. The kind of a call is learned from the
+//! [`GrpcMethodKind`] extension of its request, so calls of other kinds and requests not sent by
+//! [`Grpc`] are never cached.
+//!
+//! [`Grpc`]: crate::client::Grpc
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use http::{HeaderMap, StatusCode};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{body::Body, metadata::GRPC_CONTENT_TYPE, GrpcMethodKind};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+/// Layer which applies the [`ResponseCache`] middleware.
+///
+/// The clones of a layer share their cache.
+#[derive(Debug, Clone)]
+pub struct ResponseCacheLayer {
+    cache: Arc<Cache>,
+}
+
+impl ResponseCacheLayer {
+    /// Create a new `ResponseCacheLayer`, caching the responses for `ttl`, and at most
+    /// `max_entries` responses at a time.
+    ///
+    /// The responses of all the unary methods are cached, unless some are set with
+    /// [`method`](Self::method).
+    pub fn new(ttl: Duration, max_entries: usize) -> Self {
+        Self {
+            cache: Arc::new(Cache {
+                ttl,
+                max_entries,
+                methods: HashSet::new(),
+                entries: Mutex::new(HashMap::new()),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Cache the responses of the unary method of `path`, e.g. `/config.Config/GetValue`, and of
+    /// the other methods set, only.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layer was already cloned.
+    pub fn method(mut self, path: impl Into<String>) -> Self {
+        Arc::get_mut(&mut self.cache)
+            .expect("methods set before the layer is cloned")
+            .methods
+            .insert(path.into());
+        self
+    }
+
+    /// Statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.cache.hits.load(Ordering::Relaxed),
+            misses: self.cache.misses.load(Ordering::Relaxed),
+            entries: self.cache.entries.lock().unwrap().len(),
+        }
+    }
+
+    /// Remove all the responses from the cache.
+    pub fn clear(&self) {
+        self.cache.entries.lock().unwrap().clear();
+    }
+}
+
+impl<S> Layer<S> for ResponseCacheLayer {
+    type Service = ResponseCache<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ResponseCache {
+            inner,
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+/// Statistics of a [`ResponseCacheLayer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of calls answered from the cache.
+    pub hits: u64,
+    /// The number of cacheable calls sent, as their response wasn't cached.
+    pub misses: u64,
+    /// The number of responses in the cache, including the expired ones not removed yet.
+    pub entries: usize,
+}
+
+#[derive(Debug)]
+struct Cache {
+    ttl: Duration,
+    max_entries: usize,
+    methods: HashSet<String>,
+    entries: Mutex<HashMap<Key, Entry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+// The method path, and the request body.
+type Key = (String, Bytes);
+
+#[derive(Debug, Clone)]
+struct Entry {
+    headers: HeaderMap,
+    body: Bytes,
+    trailers: Option<HeaderMap>,
+    expires: Instant,
+}
+
+impl Cache {
+    fn get(&self, key: &Key) -> Option<Entry> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.expires > Instant::now() => Some(entry.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: Key, entry: Entry) {
+        if self.max_entries == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let now = Instant::now();
+            entries.retain(|_, entry| entry.expires > now);
+        }
+        // Evict the oldest response, the first to expire.
+        if entries.len() >= self.max_entries && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.expires)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, entry);
+    }
+}
+
+/// Middleware answering repeated unary calls of a service from a cache.
+#[derive(Debug, Clone)]
+pub struct ResponseCache<S> {
+    inner: S,
+    cache: Arc<Cache>,
+}
+
+impl<S, ResBody> Service<http::Request<Body>> for ResponseCache<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Error: Into<crate::BoxError>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<crate::BoxError>,
+{
+    type Response = http::Response<Body>;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let unary = req.extensions().get::<GrpcMethodKind>() == Some(&GrpcMethodKind::Unary);
+        let cached = self.cache.methods.is_empty() || self.cache.methods.contains(req.uri().path());
+        if !unary || !cached {
+            return ResponseFuture {
+                kind: Kind::Future(self.inner.call(req)),
+            };
+        }
+
+        // The inner service is called once the request is read, so take the service driven to
+        // readiness and leave a clone in its place.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let cache = self.cache.clone();
+
+        let future = async move {
+            let (parts, body) = req.into_parts();
+            let message = body.collect().await?.to_bytes();
+            let key = (parts.uri.path().to_owned(), message.clone());
+
+            if let Some(entry) = cache.get(&key) {
+                cache.hits.fetch_add(1, Ordering::Relaxed);
+                return Ok(response(entry.headers, entry.body, entry.trailers));
+            }
+            cache.misses.fetch_add(1, Ordering::Relaxed);
+
+            let req =
+                http::Request::from_parts(parts, Body::new(http_body_util::Full::new(message)));
+            let (parts, body) = inner.call(req).await.map_err(Into::into)?.into_parts();
+            let collected = body.collect().await.map_err(Into::into)?;
+            let trailers = collected.trailers().cloned();
+            let body = collected.to_bytes();
+
+            if parts.status == StatusCode::OK && is_ok(&parts.headers, trailers.as_ref()) {
+                cache.insert(
+                    key,
+                    Entry {
+                        headers: parts.headers.clone(),
+                        body: body.clone(),
+                        trailers: trailers.clone(),
+                        expires: Instant::now() + cache.ttl,
+                    },
+                );
+            }
+
+            let mut res = response(parts.headers, body, trailers);
+            *res.status_mut() = parts.status;
+            *res.extensions_mut() = parts.extensions;
+            Ok(res)
+        };
+
+        ResponseFuture {
+            kind: Kind::Cached(Box::pin(future)),
+        }
+    }
+}
+
+// Whether the response of `headers` and `trailers` is a successful gRPC response.
+fn is_ok(headers: &HeaderMap, trailers: Option<&HeaderMap>) -> bool {
+    headers.get(http::header::CONTENT_TYPE) == Some(&GRPC_CONTENT_TYPE)
+        && trailers
+            .unwrap_or(headers)
+            .get("grpc-status")
+            .is_some_and(|status| status.as_bytes() == b"0")
+}
+
+fn response(headers: HeaderMap, body: Bytes, trailers: Option<HeaderMap>) -> http::Response<Body> {
+    let frames = [Some(Frame::data(body)), trailers.map(Frame::trailers)]
+        .into_iter()
+        .flatten()
+        .map(Ok::<_, std::convert::Infallible>);
+    let mut res = http::Response::new(Body::new(StreamBody::new(tokio_stream::iter(frames))));
+    *res.headers_mut() = headers;
+    res
+}
+
+/// Response future for [`ResponseCache`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    kind: Kind<F>,
+}
+
+#[pin_project(project = KindProj)]
+enum Kind<F> {
+    Future(#[pin] F),
+    Cached(BoxFuture<Result<http::Response<Body>, crate::BoxError>>),
+}
+
+impl<F, E, ResBody> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    E: Into<crate::BoxError>,
+    ResBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody::Error: Into<crate::BoxError>,
+{
+    type Output = Result<http::Response<Body>, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().kind.project() {
+            KindProj::Future(future) => future
+                .poll(cx)
+                .map(|res| res.map(|res| res.map(Body::new)).map_err(Into::into)),
+            KindProj::Cached(future) => future.as_mut().poll(cx),
+        }
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Status;
+    use std::sync::atomic::AtomicUsize;
+    use tower::ServiceExt;
+
+    fn request(path: &str, message: &'static [u8]) -> http::Request<Body> {
+        let mut req = http::Request::builder()
+            .uri(path)
+            .body(Body::new(http_body_util::Full::new(Bytes::from_static(
+                message,
+            ))))
+            .unwrap();
+        req.extensions_mut().insert(GrpcMethodKind::Unary);
+        req
+    }
+
+    // A service echoing the request messages, failing the calls to `/test.Test/Fail`.
+    fn echo(
+        calls: Arc<AtomicUsize>,
+    ) -> impl Service<
+        http::Request<Body>,
+        Response = http::Response<Body>,
+        Error = Status,
+        Future = impl Send,
+    > + Clone
+           + Send
+           + 'static {
+        tower::service_fn(move |req: http::Request<Body>| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if req.uri().path() == "/test.Test/Fail" {
+                    let (parts, ()) = Status::unavailable("").into_http::<()>().into_parts();
+                    return Ok(http::Response::from_parts(parts, Body::empty()));
+                }
+
+                let message = req.into_body().collect().await?.to_bytes();
+                let mut trailers = HeaderMap::new();
+                trailers.insert("grpc-status", http::HeaderValue::from_static("0"));
+                let mut res = response(HeaderMap::new(), message, Some(trailers));
+                res.headers_mut()
+                    .insert(http::header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+                Ok(res)
+            }
+        })
+    }
+
+    async fn call(
+        svc: &ResponseCache<
+            impl Service<
+                    http::Request<Body>,
+                    Response = http::Response<Body>,
+                    Error = Status,
+                    Future = impl Send,
+                > + Clone
+                + Send
+                + 'static,
+        >,
+        path: &str,
+        message: &'static [u8],
+    ) -> (Bytes, Option<HeaderMap>) {
+        let res = svc.clone().oneshot(request(path, message)).await.unwrap();
+        let collected = res.into_body().collect().await.unwrap();
+        let trailers = collected.trailers().cloned();
+        (collected.to_bytes(), trailers)
+    }
+
+    #[tokio::test]
+    async fn caches_responses() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let layer = ResponseCacheLayer::new(Duration::from_secs(60), 10);
+        let svc = layer.layer(echo(calls.clone()));
+
+        let (body, trailers) = call(&svc, "/test.Test/Call", b"a").await;
+        assert_eq!(body, "a");
+        assert_eq!(trailers.unwrap()["grpc-status"], "0");
+
+        let (body, trailers) = call(&svc, "/test.Test/Call", b"a").await;
+        assert_eq!(body, "a");
+        assert_eq!(trailers.unwrap()["grpc-status"], "0");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        call(&svc, "/test.Test/Call", b"b").await;
+        call(&svc, "/test.Test/Other", b"a").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            layer.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 3,
+                entries: 3,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_failed_and_other_calls() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let layer = ResponseCacheLayer::new(Duration::from_secs(60), 10).method("/test.Test/Fail");
+        let svc = layer.layer(echo(calls.clone()));
+
+        call(&svc, "/test.Test/Fail", b"a").await;
+        call(&svc, "/test.Test/Fail", b"a").await;
+        // Not set with `method`.
+        call(&svc, "/test.Test/Call", b"a").await;
+        call(&svc, "/test.Test/Call", b"a").await;
+
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(layer.stats().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn expires_and_evicts_responses() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let layer = ResponseCacheLayer::new(Duration::from_millis(50), 2);
+        let svc = layer.layer(echo(calls.clone()));
+
+        call(&svc, "/test.Test/Call", b"a").await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        call(&svc, "/test.Test/Call", b"a").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        call(&svc, "/test.Test/Call", b"b").await;
+        call(&svc, "/test.Test/Call", b"c").await;
+        assert_eq!(layer.stats().entries, 2);
+        // `a` was evicted.
+        call(&svc, "/test.Test/Call", b"a").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+    }
+}
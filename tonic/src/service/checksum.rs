@@ -0,0 +1,240 @@
+//! Checksums of response bodies, sent as trailers.
+//!
+//! A server with [`ChecksumLayer::new`] computes the CRC-32C checksum of the body of each
+//! response, i.e. of its encoded messages, and sends it in the [`CHECKSUM_TRAILER`] trailer. A
+//! client with [`ChecksumLayer::verify`] computes the checksum of the bodies it receives, and
+//! fails the calls whose checksum doesn't match the trailer with `DataLoss`, detecting
+//! corruption introduced between them, e.g. by a buggy proxy:
+//!
+//! ```
+//! # #[cfg(feature = "transport")]
+//! # fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! # use tonic::{service::ChecksumLayer, transport::{Endpoint, Server}};
+//! let server = Server::builder().layer(ChecksumLayer::new());
+//!
+//! let channel = Endpoint::from_static("http://[::1]:50051").connect_lazy();
+//! let channel = tower::ServiceBuilder::new()
+//!     .layer(ChecksumLayer::verify())
+//!     .service(channel);
+//! # drop((server, channel));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The messages of a response are yielded as they are received, so a mismatch fails the call
+//! once the whole body was received: unary calls fail rather than returning their response,
+//! and streams end with an error after their last message. Responses without the trailer,
+//! e.g. from servers without the layer, aren't verified.
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use base64::Engine as _;
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue};
+use http_body::Frame;
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{
+    util::{
+        base64::{STANDARD, STANDARD_NO_PAD},
+        crc32c::Crc32c,
+    },
+    Status,
+};
+
+/// The trailer holding the CRC-32C checksum of the body of a response, as a big endian 32 bit
+/// integer.
+pub const CHECKSUM_TRAILER: &str = "checksum-crc32c-bin";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Send,
+    Verify,
+}
+
+impl Mode {
+    // Send or verify the checksum `crc` of a body in its `trailers`.
+    fn finish(self, crc: Crc32c, trailers: &mut HeaderMap) -> Result<(), Status> {
+        let checksum = crc.finish().to_be_bytes();
+        match self {
+            Mode::Send => {
+                let value = HeaderValue::try_from(STANDARD_NO_PAD.encode(checksum))
+                    .expect("base64 is a valid header value");
+                trailers.insert(CHECKSUM_TRAILER, value);
+                Ok(())
+            }
+            Mode::Verify => match trailers.get(CHECKSUM_TRAILER) {
+                Some(value) if STANDARD.decode(value).ok().as_deref() != Some(&checksum[..]) => {
+                    Err(Status::data_loss("response checksum mismatch"))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+}
+
+/// Layer computing the checksums of response bodies, to send them or verify them.
+///
+/// See the [module docs](crate::service::checksum) for more details.
+#[derive(Debug, Clone)]
+pub struct ChecksumLayer {
+    mode: Mode,
+}
+
+impl ChecksumLayer {
+    /// Create a new `ChecksumLayer` for servers, sending the checksum of each response in its
+    /// trailers.
+    pub fn new() -> Self {
+        Self { mode: Mode::Send }
+    }
+
+    /// Create a new `ChecksumLayer` for clients, verifying the checksum of each response
+    /// against its trailers.
+    pub fn verify() -> Self {
+        Self { mode: Mode::Verify }
+    }
+}
+
+impl Default for ChecksumLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for ChecksumLayer {
+    type Service = Checksum<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Checksum {
+            inner,
+            mode: self.mode,
+        }
+    }
+}
+
+/// Middleware computing the checksums of response bodies.
+///
+/// See [`ChecksumLayer`] for more details.
+#[derive(Debug, Clone)]
+pub struct Checksum<S> {
+    inner: S,
+    mode: Mode,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for Checksum<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Response = http::Response<ChecksumBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        ResponseFuture {
+            inner: self.inner.call(req),
+            mode: self.mode,
+        }
+    }
+}
+
+/// Response future for [`Checksum`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    mode: Mode,
+}
+
+impl<F, ResBody, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+{
+    type Output = Result<http::Response<ChecksumBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let response = ready!(this.inner.poll(cx))?;
+        let mode = *this.mode;
+        Poll::Ready(Ok(response.map(|inner| ChecksumBody {
+            inner,
+            mode,
+            crc: Crc32c::new(),
+        })))
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+/// Response body of [`Checksum`].
+#[pin_project]
+pub struct ChecksumBody<B> {
+    #[pin]
+    inner: B,
+    mode: Mode,
+    crc: Crc32c,
+}
+
+impl<B> http_body::Body for ChecksumBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+    B::Error: Into<crate::BoxError>,
+{
+    type Data = Bytes;
+    type Error = crate::BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => frame,
+            Some(Err(err)) => return Poll::Ready(Some(Err(err.into()))),
+            None => return Poll::Ready(None),
+        };
+
+        let frame = match frame.into_data() {
+            Ok(data) => {
+                this.crc.update(&data);
+                Frame::data(data)
+            }
+            Err(frame) => match frame.into_trailers() {
+                Ok(mut trailers) => {
+                    this.mode.finish(*this.crc, &mut trailers)?;
+                    Frame::trailers(trailers)
+                }
+                Err(frame) => frame,
+            },
+        };
+        Poll::Ready(Some(Ok(frame)))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<B> fmt::Debug for ChecksumBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChecksumBody").finish()
+    }
+}
@@ -0,0 +1,647 @@
+//! Middleware failing calls fast while a service fails.
+//!
+//! The [`CircuitBreakerLayer`] counts the failures of the last calls of a service. When their
+//! rate reaches a threshold, the breaker opens, and calls fail with [`Code::Unavailable`] without
+//! being sent. Once open for a while, the breaker is half-open, sending a few probe calls: it
+//! closes if they all succeed, and opens again if one fails.
+//!
+//! Calls fail with an error of the service, with a non-OK HTTP status, or with one of the
+//! [failure codes](CircuitBreakerLayer::failure_codes).
+//!
+//! A breaker wraps a [`Channel`]:
+//!
+//! ```no_run
+//! # use std::time::Duration;
+//! # use tonic::{service::circuit_breaker::CircuitBreakerLayer, transport::Endpoint};
+//! # async fn run() -> Result<(), tonic::transport::Error> {
+//! let channel = Endpoint::from_static("http://backend").connect().await?;
+//! let channel = tower::ServiceBuilder::new()
+//!     .layer(
+//!         CircuitBreakerLayer::new()
+//!             .failure_rate(50.0)
+//!             .open_duration(Duration::from_secs(10)),
+//!     )
+//!     .service(channel);
+//! # drop(channel);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! or each endpoint of a balanced channel, set with [`Endpoint::circuit_breaker`], where endpoints
+//! with an open breaker are avoided by the balancer.
+//!
+//! [`Channel`]: crate::transport::Channel
+//! [`Endpoint::circuit_breaker`]: crate::transport::Endpoint::circuit_breaker
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use http::StatusCode;
+use http_body::Frame;
+use pin_project::pin_project;
+use tokio::time::Instant;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{Code, Status};
+
+/// Layer which applies the [`CircuitBreaker`] middleware.
+///
+/// Each service wrapped by the layer has its own breaker, shared by its clones.
+#[derive(Debug, Clone)]
+pub struct CircuitBreakerLayer {
+    config: Arc<Config>,
+}
+
+#[derive(Debug)]
+struct Config {
+    failure_rate: f64,
+    window_size: usize,
+    minimum_calls: usize,
+    open_duration: Duration,
+    half_open_calls: usize,
+    failure_codes: Vec<Code>,
+}
+
+impl Default for CircuitBreakerLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitBreakerLayer {
+    /// Create a new `CircuitBreakerLayer`.
+    pub fn new() -> Self {
+        Self {
+            config: Arc::new(Config {
+                failure_rate: 50.0,
+                window_size: 20,
+                minimum_calls: 10,
+                open_duration: Duration::from_secs(30),
+                half_open_calls: 1,
+                failure_codes: vec![
+                    Code::Unknown,
+                    Code::DeadlineExceeded,
+                    Code::Internal,
+                    Code::Unavailable,
+                ],
+            }),
+        }
+    }
+
+    fn config(&mut self) -> &mut Config {
+        Arc::get_mut(&mut self.config).expect("configured before the layer is cloned")
+    }
+
+    /// Set the percentage of failed calls opening the breaker.
+    ///
+    /// Default is 50.
+    ///
+    /// # Panics
+    ///
+    /// The setters panic if the layer was already cloned.
+    pub fn failure_rate(mut self, percentage: f64) -> Self {
+        self.config().failure_rate = percentage;
+        self
+    }
+
+    /// Set the number of last calls of which the failure rate is computed.
+    ///
+    /// Default is 20.
+    pub fn window_size(mut self, calls: usize) -> Self {
+        self.config().window_size = calls.max(1);
+        self
+    }
+
+    /// Set the number of calls needed to compute the failure rate, the breaker staying closed
+    /// before.
+    ///
+    /// Default is 10.
+    pub fn minimum_calls(mut self, calls: usize) -> Self {
+        self.config().minimum_calls = calls.max(1);
+        self
+    }
+
+    /// Set how long the breaker stays open before sending probe calls.
+    ///
+    /// Default is 30 seconds.
+    pub fn open_duration(mut self, duration: Duration) -> Self {
+        self.config().open_duration = duration;
+        self
+    }
+
+    /// Set the number of probe calls which must succeed for the half-open breaker to close.
+    ///
+    /// Default is 1.
+    pub fn half_open_calls(mut self, calls: usize) -> Self {
+        self.config().half_open_calls = calls.max(1);
+        self
+    }
+
+    /// Set the codes of the statuses counted as failures.
+    ///
+    /// Default is `Unknown`, `DeadlineExceeded`, `Internal` and `Unavailable`.
+    pub fn failure_codes(mut self, codes: impl IntoIterator<Item = Code>) -> Self {
+        self.config().failure_codes = codes.into_iter().collect();
+        self
+    }
+}
+
+impl<S> Layer<S> for CircuitBreakerLayer {
+    type Service = CircuitBreaker<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CircuitBreaker {
+            inner,
+            breaker: Arc::new(Breaker {
+                config: self.config.clone(),
+                state: Mutex::new(BreakerState {
+                    epoch: 0,
+                    state: State::Closed {
+                        outcomes: VecDeque::new(),
+                    },
+                }),
+            }),
+        }
+    }
+}
+
+/// The state of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls are sent.
+    Closed,
+    /// Calls fail fast.
+    Open,
+    /// Probe calls are sent.
+    HalfOpen,
+}
+
+#[derive(Debug)]
+pub(crate) struct Breaker {
+    config: Arc<Config>,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    // Incremented on each transition, so outcomes of calls of a previous state are ignored.
+    epoch: u64,
+    state: State,
+}
+
+#[derive(Debug)]
+enum State {
+    // Whether each of the last calls failed.
+    Closed { outcomes: VecDeque<bool> },
+    Open { until: Instant },
+    HalfOpen { in_flight: usize, successes: usize },
+}
+
+impl Breaker {
+    pub(crate) fn state(&self) -> CircuitState {
+        match self.state.lock().unwrap().state {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if until > Instant::now() => CircuitState::Open,
+            State::Open { .. } | State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    // Permit a call, unless the breaker is open.
+    fn acquire(self: &Arc<Self>) -> Option<Permit> {
+        let mut state = self.state.lock().unwrap();
+        if let State::Open { until } = state.state {
+            if until > Instant::now() {
+                return None;
+            }
+            state.transition(State::HalfOpen {
+                in_flight: 0,
+                successes: 0,
+            });
+        }
+        let epoch = state.epoch;
+        if let State::HalfOpen {
+            in_flight,
+            successes,
+        } = &mut state.state
+        {
+            if *in_flight + *successes >= self.config.half_open_calls {
+                return None;
+            }
+            *in_flight += 1;
+        }
+        Some(Permit {
+            breaker: Some(self.clone()),
+            epoch,
+        })
+    }
+
+    // Record the outcome of a permitted call, or release its permit if `failed` is `None`.
+    fn record(&self, epoch: u64, failed: Option<bool>) {
+        let config = &self.config;
+        let mut state = self.state.lock().unwrap();
+        if state.epoch != epoch {
+            return;
+        }
+
+        let next = match (&mut state.state, failed) {
+            (State::Closed { outcomes }, Some(failed)) => {
+                outcomes.push_back(failed);
+                if outcomes.len() > config.window_size {
+                    outcomes.pop_front();
+                }
+                let failures = outcomes.iter().filter(|failed| **failed).count();
+                let rate = failures as f64 * 100.0 / outcomes.len() as f64;
+                (outcomes.len() >= config.minimum_calls && rate >= config.failure_rate).then(|| {
+                    State::Open {
+                        until: Instant::now() + config.open_duration,
+                    }
+                })
+            }
+            (State::HalfOpen { in_flight, .. }, None) => {
+                *in_flight -= 1;
+                None
+            }
+            (State::HalfOpen { .. }, Some(true)) => Some(State::Open {
+                until: Instant::now() + config.open_duration,
+            }),
+            (
+                State::HalfOpen {
+                    in_flight,
+                    successes,
+                },
+                Some(false),
+            ) => {
+                *in_flight -= 1;
+                *successes += 1;
+                (*successes >= config.half_open_calls).then(|| State::Closed {
+                    outcomes: VecDeque::new(),
+                })
+            }
+            _ => None,
+        };
+        if let Some(next) = next {
+            state.transition(next);
+        }
+    }
+
+    fn is_failure(&self, status: StatusCode, code: Option<Code>) -> bool {
+        status != StatusCode::OK
+            || code.is_some_and(|code| self.config.failure_codes.contains(&code))
+    }
+}
+
+impl BreakerState {
+    fn transition(&mut self, state: State) {
+        self.epoch += 1;
+        self.state = state;
+    }
+}
+
+// A permitted call, released if dropped before its outcome is recorded.
+struct Permit {
+    breaker: Option<Arc<Breaker>>,
+    epoch: u64,
+}
+
+impl Permit {
+    fn record(&mut self, failed: bool) {
+        if let Some(breaker) = self.breaker.take() {
+            breaker.record(self.epoch, Some(failed));
+        }
+    }
+
+    // Record the outcome of a call answered with `status`, and a gRPC status of `code`.
+    fn record_status(&mut self, status: StatusCode, code: Option<Code>) {
+        let failed = self
+            .breaker
+            .as_ref()
+            .is_some_and(|breaker| breaker.is_failure(status, code));
+        self.record(failed);
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        if let Some(breaker) = self.breaker.take() {
+            breaker.record(self.epoch, None);
+        }
+    }
+}
+
+/// Middleware failing the calls of a service fast while it fails.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker<S> {
+    inner: S,
+    breaker: Arc<Breaker>,
+}
+
+impl<S> CircuitBreaker<S> {
+    /// The state of the breaker.
+    pub fn state(&self) -> CircuitState {
+        self.breaker.state()
+    }
+
+    pub(crate) fn breaker(&self) -> Arc<Breaker> {
+        self.breaker.clone()
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for CircuitBreaker<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Response = http::Response<ResponseBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        match self.breaker.acquire() {
+            Some(permit) => ResponseFuture {
+                kind: Kind::Future {
+                    future: self.inner.call(req),
+                    permit: Some(permit),
+                },
+            },
+            None => ResponseFuture { kind: Kind::Open },
+        }
+    }
+}
+
+/// Response future for [`CircuitBreaker`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    kind: Kind<F>,
+}
+
+#[pin_project(project = KindProj)]
+enum Kind<F> {
+    Future {
+        #[pin]
+        future: F,
+        permit: Option<Permit>,
+    },
+    Open,
+}
+
+impl<F, E, ResBody> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+{
+    type Output = Result<http::Response<ResponseBody<ResBody>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project().kind.project() {
+            KindProj::Future { future, permit } => {
+                let result = std::task::ready!(future.poll(cx));
+                let mut permit = permit.take().expect("polled after completion");
+                let res = match result {
+                    Ok(res) => res,
+                    Err(e) => {
+                        permit.record(true);
+                        return Poll::Ready(Err(e));
+                    }
+                };
+
+                // Trailers-only responses, and responses failing at the HTTP level, are
+                // classified from their headers.
+                let code = Status::from_header_map(res.headers()).map(|status| status.code());
+                if code.is_some() || res.status() != StatusCode::OK {
+                    permit.record_status(res.status(), code);
+                }
+                Poll::Ready(Ok(res.map(|body| ResponseBody::wrap(body, permit))))
+            }
+            KindProj::Open => {
+                let (parts, ()) = Status::unavailable("circuit breaker is open")
+                    .into_http()
+                    .into_parts();
+                Poll::Ready(Ok(http::Response::from_parts(parts, ResponseBody::empty())))
+            }
+        }
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+/// Response body for [`CircuitBreaker`].
+#[pin_project]
+pub struct ResponseBody<B> {
+    #[pin]
+    kind: ResponseBodyKind<B>,
+}
+
+#[pin_project(project = ResponseBodyKindProj)]
+enum ResponseBodyKind<B> {
+    Empty,
+    Wrap {
+        #[pin]
+        body: B,
+        permit: Permit,
+    },
+}
+
+impl<B> ResponseBody<B> {
+    fn empty() -> Self {
+        Self {
+            kind: ResponseBodyKind::Empty,
+        }
+    }
+
+    fn wrap(body: B, permit: Permit) -> Self {
+        Self {
+            kind: ResponseBodyKind::Wrap { body, permit },
+        }
+    }
+}
+
+impl<B: http_body::Body> http_body::Body for ResponseBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        match self.project().kind.project() {
+            ResponseBodyKindProj::Empty => Poll::Ready(None),
+            ResponseBodyKindProj::Wrap { body, permit } => {
+                let frame = std::task::ready!(body.poll_frame(cx));
+                match &frame {
+                    Some(Ok(frame)) => {
+                        if let Some(trailers) = frame.trailers_ref() {
+                            let code = Status::from_header_map(trailers).map(|s| s.code());
+                            permit.record_status(StatusCode::OK, code);
+                        }
+                    }
+                    Some(Err(_)) => permit.record(true),
+                    // Responses ending without trailers are successful.
+                    None => permit.record(false),
+                }
+                Poll::Ready(frame)
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.kind {
+            ResponseBodyKind::Empty => true,
+            ResponseBodyKind::Wrap { body, .. } => body.is_end_stream(),
+        }
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        match &self.kind {
+            ResponseBodyKind::Empty => http_body::SizeHint::with_exact(0),
+            ResponseBodyKind::Wrap { body, .. } => body.size_hint(),
+        }
+    }
+}
+
+impl<B> fmt::Debug for ResponseBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseBody").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_body_util::BodyExt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    // A service answering with the status codes of `codes`, in turn.
+    fn service(
+        codes: Arc<Mutex<Vec<Code>>>,
+        calls: Arc<AtomicUsize>,
+    ) -> impl Service<
+        http::Request<()>,
+        Response = http::Response<crate::body::Body>,
+        Error = Status,
+        Future = impl Send,
+    > + Clone {
+        tower::service_fn(move |_: http::Request<()>| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            let code = codes.lock().unwrap().remove(0);
+            async move {
+                let mut trailers = http::HeaderMap::new();
+                Status::new(code, "").add_header(&mut trailers).unwrap();
+                let frames = [Ok::<_, Status>(Frame::trailers(trailers))];
+                Ok(http::Response::new(crate::body::Body::new(
+                    http_body_util::StreamBody::new(tokio_stream::iter(frames)),
+                )))
+            }
+        })
+    }
+
+    async fn call<S>(svc: &CircuitBreaker<S>) -> Code
+    where
+        S: Service<http::Request<()>, Response = http::Response<crate::body::Body>, Error = Status>
+            + Clone,
+    {
+        let res = svc.clone().oneshot(http::Request::new(())).await.unwrap();
+        if let Some(status) = Status::from_header_map(res.headers()) {
+            return status.code();
+        }
+        let trailers = res.into_body().collect().await.unwrap().trailers().cloned();
+        Status::from_header_map(&trailers.unwrap()).unwrap().code()
+    }
+
+    fn layer() -> CircuitBreakerLayer {
+        CircuitBreakerLayer::new()
+            .window_size(4)
+            .minimum_calls(4)
+            .open_duration(Duration::from_secs(10))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn opens_on_failure_rate() {
+        let codes = Arc::new(Mutex::new(vec![
+            Code::Ok,
+            Code::Unavailable,
+            Code::NotFound,
+            Code::Unavailable,
+        ]));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = layer().layer(service(codes, calls.clone()));
+
+        for code in [Code::Ok, Code::Unavailable, Code::NotFound] {
+            assert_eq!(call(&svc).await, code);
+            assert_eq!(svc.state(), CircuitState::Closed);
+        }
+        assert_eq!(call(&svc).await, Code::Unavailable);
+        assert_eq!(svc.state(), CircuitState::Open);
+
+        assert_eq!(call(&svc).await, Code::Unavailable);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closes_after_probe_calls() {
+        let codes = vec![Code::Internal; 4]
+            .into_iter()
+            .chain([Code::Internal, Code::Ok, Code::Ok])
+            .collect();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = layer()
+            .half_open_calls(2)
+            .layer(service(Arc::new(Mutex::new(codes)), calls.clone()));
+
+        for _ in 0..4 {
+            call(&svc).await;
+        }
+        assert_eq!(svc.state(), CircuitState::Open);
+
+        // A failed probe call opens the breaker again.
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(svc.state(), CircuitState::HalfOpen);
+        assert_eq!(call(&svc).await, Code::Internal);
+        assert_eq!(svc.state(), CircuitState::Open);
+
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(call(&svc).await, Code::Ok);
+        assert_eq!(svc.state(), CircuitState::HalfOpen);
+        assert_eq!(call(&svc).await, Code::Ok);
+        assert_eq!(svc.state(), CircuitState::Closed);
+        assert_eq!(calls.load(Ordering::SeqCst), 7);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_probe_calls() {
+        let codes = vec![Code::Unavailable; 4]
+            .into_iter()
+            .chain([Code::Ok])
+            .collect();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let svc = layer().layer(service(Arc::new(Mutex::new(codes)), calls.clone()));
+
+        for _ in 0..4 {
+            call(&svc).await;
+        }
+        tokio::time::advance(Duration::from_secs(10)).await;
+
+        // The probe call is in flight until its response ends.
+        let res = svc.clone().oneshot(http::Request::new(())).await.unwrap();
+        assert_eq!(call(&svc).await, Code::Unavailable);
+        assert_eq!(calls.load(Ordering::SeqCst), 5);
+
+        res.into_body().collect().await.unwrap();
+        assert_eq!(svc.state(), CircuitState::Closed);
+    }
+}
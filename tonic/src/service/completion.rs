@@ -0,0 +1,294 @@
+//! Completion hooks of client calls, receiving the state stashed by interceptors.
+//!
+//! An [`Interceptor`](crate::service::Interceptor) stashes typed state in the extensions of the
+//! requests it intercepts, e.g. the time a call started. The [`CompletionLayer`], set between
+//! the interceptor and the channel, takes the state out of the requests, and hands it back to its
+//! hook along with the final status of each call, once the call completes:
+//!
+//! ```
+//! # #[cfg(feature = "transport")]
+//! # fn run() {
+//! use std::time::Instant;
+//! use tonic::{service::CompletionLayer, transport::Endpoint, Request, Status};
+//! use tower::Layer;
+//!
+//! #[derive(Clone)]
+//! struct Started(Instant);
+//!
+//! let channel = Endpoint::from_static("http://[::1]:50051").connect_lazy();
+//! let channel = CompletionLayer::new(|started: Started, status: &Status| {
+//!     println!("call ended with {:?} in {:?}", status.code(), started.0.elapsed());
+//! })
+//! .layer(channel);
+//!
+//! let intercept = |mut request: Request<()>| {
+//!     request.extensions_mut().insert(Started(Instant::now()));
+//!     Ok::<_, Status>(request)
+//! };
+//! // The generated clients are then created with
+//! // `GreeterClient::with_interceptor(channel, intercept)`.
+//! # drop((channel, intercept));
+//! # }
+//! ```
+//!
+//! Calls complete once their response ends, including the response streams, or once they fail.
+//! Calls dropped before, e.g. by a client dropping a response stream, complete with `Cancelled`.
+//! Requests without state, like the ones cancelled by the interceptor, don't call the hook.
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use http::{HeaderMap, StatusCode};
+use http_body::Frame;
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{status::infer_grpc_status, Status};
+
+/// Layer handing the state of `T` stashed in requests back to a hook, once their call completes.
+///
+/// See the [module docs](crate::service::completion) for more details.
+pub struct CompletionLayer<T, H> {
+    hook: Arc<H>,
+    _state: std::marker::PhantomData<fn(T)>,
+}
+
+impl<T, H> CompletionLayer<T, H>
+where
+    H: Fn(T, &Status),
+{
+    /// Create a new `CompletionLayer` calling `hook` with the state of each call and its final
+    /// status.
+    pub fn new(hook: H) -> Self {
+        Self {
+            hook: Arc::new(hook),
+            _state: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, H> Clone for CompletionLayer<T, H> {
+    fn clone(&self) -> Self {
+        Self {
+            hook: self.hook.clone(),
+            _state: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T, H> fmt::Debug for CompletionLayer<T, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompletionLayer")
+            .field("state", &std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<S, T, H> Layer<S> for CompletionLayer<T, H> {
+    type Service = Completion<S, T, H>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Completion {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+/// Middleware calling a hook once calls complete.
+///
+/// See [`CompletionLayer`] for more details.
+pub struct Completion<S, T, H> {
+    inner: S,
+    layer: CompletionLayer<T, H>,
+}
+
+impl<S: Clone, T, H> Clone for Completion<S, T, H> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            layer: self.layer.clone(),
+        }
+    }
+}
+
+impl<S: fmt::Debug, T, H> fmt::Debug for Completion<S, T, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Completion")
+            .field("inner", &self.inner)
+            .field("layer", &self.layer)
+            .finish()
+    }
+}
+
+impl<S, T, H, ReqBody, ResBody> Service<http::Request<ReqBody>> for Completion<S, T, H>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Into<crate::BoxError>,
+    T: Send + Sync + 'static,
+    H: Fn(T, &Status),
+{
+    type Response = http::Response<ResponseBody<ResBody, T, H>>;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<S::Future, T, H>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let pending = Pending(
+            req.extensions_mut()
+                .remove::<T>()
+                .map(|state| (state, self.layer.hook.clone())),
+        );
+        ResponseFuture {
+            inner: self.inner.call(req),
+            pending: Some(pending),
+        }
+    }
+}
+
+// The state of a call and the hook to call once it completes, or with `Cancelled` if dropped
+// before.
+struct Pending<T, H: Fn(T, &Status)>(Option<(T, Arc<H>)>);
+
+impl<T, H: Fn(T, &Status)> Pending<T, H> {
+    fn complete(&mut self, status: &Status) {
+        if let Some((state, hook)) = self.0.take() {
+            hook(state, status);
+        }
+    }
+}
+
+impl<T, H: Fn(T, &Status)> Drop for Pending<T, H> {
+    fn drop(&mut self) {
+        self.complete(&Status::cancelled("call dropped before completion"));
+    }
+}
+
+// The final status of a call, from the trailers of its response, or from its HTTP status.
+fn status_of(trailers: Option<&HeaderMap>, status: StatusCode) -> Status {
+    match infer_grpc_status(trailers, status) {
+        Ok(()) | Err(None) => Status::ok(""),
+        Err(Some(status)) => status,
+    }
+}
+
+/// Response future for [`Completion`].
+#[pin_project]
+pub struct ResponseFuture<F, T, H: Fn(T, &Status)> {
+    #[pin]
+    inner: F,
+    pending: Option<Pending<T, H>>,
+}
+
+impl<F, E, ResBody, T, H> Future for ResponseFuture<F, T, H>
+where
+    F: Future<Output = Result<http::Response<ResBody>, E>>,
+    E: Into<crate::BoxError>,
+    H: Fn(T, &Status),
+{
+    type Output = Result<http::Response<ResponseBody<ResBody, T, H>>, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = ready!(this.inner.poll(cx));
+        let mut pending = this.pending.take().expect("polled after completion");
+        let res = match result {
+            Ok(res) => res,
+            Err(e) => {
+                let status = Status::from_error(e.into());
+                pending.complete(&status);
+                return Poll::Ready(Err(status.into()));
+            }
+        };
+
+        // Trailers-only responses, and responses failing at the HTTP level, complete with their
+        // headers.
+        if let Some(status) = Status::from_header_map(res.headers()) {
+            pending.complete(&status);
+        } else if res.status() != StatusCode::OK {
+            pending.complete(&status_of(None, res.status()));
+        }
+        Poll::Ready(Ok(res.map(|inner| ResponseBody { inner, pending })))
+    }
+}
+
+impl<F, T, H: Fn(T, &Status)> fmt::Debug for ResponseFuture<F, T, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+/// Response body of [`Completion`].
+#[pin_project]
+pub struct ResponseBody<B, T, H: Fn(T, &Status)> {
+    #[pin]
+    inner: B,
+    pending: Pending<T, H>,
+}
+
+impl<B: Default, T, H: Fn(T, &Status)> Default for ResponseBody<B, T, H> {
+    fn default() -> Self {
+        Self {
+            inner: B::default(),
+            pending: Pending(None),
+        }
+    }
+}
+
+impl<B, T, H> http_body::Body for ResponseBody<B, T, H>
+where
+    B: http_body::Body,
+    B::Error: Into<crate::BoxError>,
+    H: Fn(T, &Status),
+{
+    type Data = B::Data;
+    type Error = crate::BoxError;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        match ready!(this.inner.poll_frame(cx)) {
+            Some(Ok(frame)) => {
+                if let Some(trailers) = frame.trailers_ref() {
+                    this.pending
+                        .complete(&status_of(Some(trailers), StatusCode::OK));
+                }
+                Poll::Ready(Some(Ok(frame)))
+            }
+            Some(Err(e)) => {
+                let status = Status::from_error(e.into());
+                this.pending.complete(&status);
+                Poll::Ready(Some(Err(status.into())))
+            }
+            None => {
+                this.pending.complete(&Status::ok(""));
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<B, T, H: Fn(T, &Status)> fmt::Debug for ResponseBody<B, T, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseBody").finish()
+    }
+}
@@ -0,0 +1,438 @@
+//! Middleware injecting faults, to test the resilience of clients and servers.
+//!
+//! The [`FaultInjectionLayer`] delays calls and fails them with a given [`Status`], for a
+//! percentage of the calls of each method, so retries, hedging, deadlines and fallbacks can be
+//! exercised without an external fault-injecting proxy. It may wrap the services of a server:
+//!
+//! ```
+//! # use std::time::Duration;
+//! # use tonic::{service::fault::{Fault, FaultInjectionLayer}, Status};
+//! let faults = FaultInjectionLayer::new()
+//!     // Fail a tenth of all calls.
+//!     .fault(Fault::new().abort(Status::unavailable("injected fault"), 10.0))
+//!     // Delay half of the calls of `SayHello` by 100 to 150ms.
+//!     .method_fault(
+//!         "/helloworld.Greeter/SayHello",
+//!         Fault::new().jittered_delay(Duration::from_millis(100), Duration::from_millis(50), 50.0),
+//!     );
+//!
+//! # tonic::transport::Server::builder().layer(faults);
+//! ```
+//!
+//! or a channel, with `tower::ServiceBuilder::new().layer(faults).service(channel)`, the delays
+//! then being applied before the requests are sent.
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use pin_project::pin_project;
+use tokio::time::Sleep;
+use tower::util::rng::{HasherRng, Rng};
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::debug;
+
+use crate::Status;
+
+/// The faults injected into the calls of a method.
+///
+/// The calls are first delayed, then aborted, each fault being injected into its own percentage of
+/// calls.
+#[derive(Debug, Clone, Default)]
+pub struct Fault {
+    abort: Option<(Status, f64)>,
+    delay: Option<(Duration, Duration, f64)>,
+}
+
+impl Fault {
+    /// Create a new `Fault`, injecting nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fail `percentage` percent of the calls with `status`, without calling the inner service.
+    pub fn abort(mut self, status: Status, percentage: f64) -> Self {
+        self.abort = Some((status, percentage));
+        self
+    }
+
+    /// Delay `percentage` percent of the calls by `delay`.
+    pub fn delay(self, delay: Duration, percentage: f64) -> Self {
+        self.jittered_delay(delay, Duration::ZERO, percentage)
+    }
+
+    /// Delay `percentage` percent of the calls by `delay`, plus a random duration up to `jitter`.
+    pub fn jittered_delay(mut self, delay: Duration, jitter: Duration, percentage: f64) -> Self {
+        self.delay = Some((delay, jitter, percentage));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Faults {
+    default: Fault,
+    methods: HashMap<String, Fault>,
+}
+
+/// Layer which applies the [`FaultInjection`] middleware.
+#[derive(Debug, Default, Clone)]
+pub struct FaultInjectionLayer {
+    faults: Arc<Faults>,
+}
+
+impl FaultInjectionLayer {
+    /// Create a new `FaultInjectionLayer`, injecting no faults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inject `fault` into the calls of the methods without a fault of their own.
+    pub fn fault(mut self, fault: Fault) -> Self {
+        Arc::make_mut(&mut self.faults).default = fault;
+        self
+    }
+
+    /// Inject `fault` into the calls of the method of `path`, e.g.
+    /// `/helloworld.Greeter/SayHello`, instead of the default fault.
+    pub fn method_fault(mut self, path: impl Into<String>, fault: Fault) -> Self {
+        Arc::make_mut(&mut self.faults)
+            .methods
+            .insert(path.into(), fault);
+        self
+    }
+}
+
+impl<S> Layer<S> for FaultInjectionLayer {
+    type Service = FaultInjection<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        FaultInjection {
+            inner,
+            faults: self.faults.clone(),
+            rng: HasherRng::new(),
+        }
+    }
+}
+
+/// Middleware injecting the faults of a [`FaultInjectionLayer`] into the calls of a service.
+#[derive(Debug)]
+pub struct FaultInjection<S> {
+    inner: S,
+    faults: Arc<Faults>,
+    rng: HasherRng,
+}
+
+// Clones sample their faults with their own random numbers, as servers and channels call a clone
+// of the service for each request.
+impl<S: Clone> Clone for FaultInjection<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            faults: self.faults.clone(),
+            rng: HasherRng::new(),
+        }
+    }
+}
+
+impl<S> FaultInjection<S> {
+    // Whether to inject a fault into `percentage` percent of the calls.
+    fn sample(&mut self, percentage: f64) -> bool {
+        percentage > 0.0 && self.rng.next_f64() * 100.0 < percentage
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for FaultInjection<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone,
+{
+    type Response = http::Response<ResponseBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S, ReqBody>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let faults = self.faults.clone();
+        let fault = faults
+            .methods
+            .get(req.uri().path())
+            .unwrap_or(&faults.default);
+
+        let delay = match fault.delay {
+            Some((delay, jitter, percentage)) if self.sample(percentage) => {
+                Some(delay + jitter.mul_f64(self.rng.next_f64()))
+            }
+            _ => None,
+        };
+        let abort = match &fault.abort {
+            Some((status, percentage)) if self.sample(*percentage) => Some(status.clone()),
+            _ => None,
+        };
+        if delay.is_some() || abort.is_some() {
+            debug!(path = req.uri().path(), ?delay, abort = ?abort.as_ref().map(Status::code), "injecting fault");
+        }
+
+        let then = match abort {
+            Some(status) => Then::Abort(status),
+            None if delay.is_some() => {
+                // The inner service is called once the delay elapsed, so take the service driven
+                // to readiness and leave a clone in its place.
+                let clone = self.inner.clone();
+                Then::Call(Box::new((std::mem::replace(&mut self.inner, clone), req)))
+            }
+            None => return ResponseFuture::future(self.inner.call(req)),
+        };
+
+        match delay {
+            Some(delay) => ResponseFuture {
+                kind: Kind::Delay {
+                    sleep: Box::pin(tokio::time::sleep(delay)),
+                    then: Some(then),
+                },
+            },
+            None => ResponseFuture::then(then),
+        }
+    }
+}
+
+enum Then<S, ReqBody> {
+    Call(Box<(S, http::Request<ReqBody>)>),
+    Abort(Status),
+}
+
+/// Response future for [`FaultInjection`].
+#[pin_project]
+pub struct ResponseFuture<S, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    #[pin]
+    kind: Kind<S, ReqBody, S::Future>,
+}
+
+impl<S, ReqBody> ResponseFuture<S, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    fn future(future: S::Future) -> Self {
+        Self {
+            kind: Kind::Future(future),
+        }
+    }
+
+    fn then(then: Then<S, ReqBody>) -> Self {
+        match then {
+            Then::Call(call) => {
+                let (mut inner, req) = *call;
+                Self::future(inner.call(req))
+            }
+            Then::Abort(status) => Self {
+                kind: Kind::Status(Some(status)),
+            },
+        }
+    }
+}
+
+#[pin_project(project = KindProj)]
+enum Kind<S, ReqBody, F> {
+    Delay {
+        sleep: Pin<Box<Sleep>>,
+        then: Option<Then<S, ReqBody>>,
+    },
+    Future(#[pin] F),
+    Status(Option<Status>),
+}
+
+impl<S, ReqBody, ResBody> Future for ResponseFuture<S, ReqBody>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Output = Result<http::Response<ResponseBody<ResBody>>, S::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            match self.as_mut().project().kind.project() {
+                KindProj::Delay { sleep, then } => {
+                    std::task::ready!(sleep.as_mut().poll(cx));
+                    let then = then.take().expect("polled after completion");
+                    self.set(Self::then(then));
+                }
+                KindProj::Future(future) => {
+                    return future.poll(cx).map_ok(|res| res.map(ResponseBody::wrap))
+                }
+                KindProj::Status(status) => {
+                    let status = status.take().expect("polled after completion");
+                    let (parts, ()) = status.into_http::<()>().into_parts();
+                    let response = http::Response::from_parts(parts, ResponseBody::empty());
+                    return Poll::Ready(Ok(response));
+                }
+            }
+        }
+    }
+}
+
+impl<S, ReqBody> fmt::Debug for ResponseFuture<S, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+/// Response body for [`FaultInjection`].
+#[pin_project]
+#[derive(Debug)]
+pub struct ResponseBody<B> {
+    #[pin]
+    kind: ResponseBodyKind<B>,
+}
+
+#[pin_project(project = ResponseBodyKindProj)]
+#[derive(Debug)]
+enum ResponseBodyKind<B> {
+    Empty,
+    Wrap(#[pin] B),
+}
+
+impl<B> ResponseBody<B> {
+    fn empty() -> Self {
+        Self {
+            kind: ResponseBodyKind::Empty,
+        }
+    }
+
+    fn wrap(body: B) -> Self {
+        Self {
+            kind: ResponseBodyKind::Wrap(body),
+        }
+    }
+}
+
+impl<B: http_body::Body> http_body::Body for ResponseBody<B> {
+    type Data = B::Data;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<http_body::Frame<Self::Data>, Self::Error>>> {
+        match self.project().kind.project() {
+            ResponseBodyKindProj::Empty => Poll::Ready(None),
+            ResponseBodyKindProj::Wrap(body) => body.poll_frame(cx),
+        }
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        match &self.kind {
+            ResponseBodyKind::Empty => http_body::SizeHint::with_exact(0),
+            ResponseBodyKind::Wrap(body) => body.size_hint(),
+        }
+    }
+
+    fn is_end_stream(&self) -> bool {
+        match &self.kind {
+            ResponseBodyKind::Empty => true,
+            ResponseBodyKind::Wrap(body) => body.is_end_stream(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+    use tower::ServiceExt;
+
+    fn service(
+        calls: Arc<AtomicUsize>,
+        faults: FaultInjectionLayer,
+    ) -> impl Service<http::Request<()>, Response = http::Response<ResponseBody<()>>, Error = Status>
+           + Clone {
+        faults.layer(tower::service_fn(move |_: http::Request<()>| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async { Ok::<_, Status>(http::Response::new(())) }
+        }))
+    }
+
+    fn request(path: &str) -> http::Request<()> {
+        http::Request::builder().uri(path).body(()).unwrap()
+    }
+
+    fn grpc_status(res: &http::Response<ResponseBody<()>>) -> Option<&str> {
+        res.headers()
+            .get("grpc-status")
+            .map(|status| status.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn aborts_method_calls() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let faults = FaultInjectionLayer::new().method_fault(
+            "/test.Test/Fail",
+            Fault::new().abort(Status::unavailable("injected"), 100.0),
+        );
+        let svc = service(calls.clone(), faults);
+
+        let res = svc
+            .clone()
+            .oneshot(request("/test.Test/Fail"))
+            .await
+            .unwrap();
+        assert_eq!(grpc_status(&res), Some("14"));
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+        let res = svc.oneshot(request("/test.Test/Other")).await.unwrap();
+        assert_eq!(grpc_status(&res), None);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn aborts_percentage_of_calls() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let faults = FaultInjectionLayer::new()
+            .fault(Fault::new().abort(Status::unavailable("injected"), 50.0));
+        let svc = service(calls.clone(), faults);
+
+        for _ in 0..1000 {
+            svc.clone()
+                .oneshot(request("/test.Test/Call"))
+                .await
+                .unwrap();
+        }
+        let calls = calls.load(Ordering::SeqCst);
+        assert!((350..650).contains(&calls), "{calls}");
+    }
+
+    #[tokio::test]
+    async fn delays_calls() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let faults = FaultInjectionLayer::new().fault(Fault::new().jittered_delay(
+            Duration::from_millis(50),
+            Duration::from_millis(20),
+            100.0,
+        ));
+        let svc = service(calls.clone(), faults);
+
+        let start = Instant::now();
+        let (res, ()) = tokio::join!(svc.oneshot(request("/test.Test/Call")), async {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            // The inner service is only called once the delay elapsed.
+            assert_eq!(calls.load(Ordering::SeqCst), 0);
+        });
+        let res = res.unwrap();
+        assert_eq!(grpc_status(&res), None);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -52,6 +52,78 @@ where
     }
 }
 
+/// Extension trait adding combinators to [`Interceptor`]s.
+///
+/// Interceptors are composed into a single interceptor, running them in order, so that they are
+/// used the same way by clients, with the generated `with_interceptor` constructors, and by
+/// servers:
+///
+/// ```
+/// use tonic::{service::InterceptorExt, Request, Status};
+///
+/// fn authenticate(request: Request<()>) -> Result<Request<()>, Status> {
+///     match request.metadata().get("authorization") {
+///         Some(_) => Ok(request),
+///         None => Err(Status::unauthenticated("missing authorization")),
+///     }
+/// }
+///
+/// fn extract_tenant(mut request: Request<()>) -> Result<Request<()>, Status> {
+///     let tenant = request
+///         .metadata()
+///         .get("x-tenant")
+///         .and_then(|tenant| tenant.to_str().ok())
+///         .unwrap_or("default")
+///         .to_string();
+///     request.extensions_mut().insert(tenant);
+///     Ok(request)
+/// }
+///
+/// // Tenants are only extracted from authenticated requests.
+/// let layer = authenticate.and_then(extract_tenant).layer();
+/// # drop(layer);
+/// ```
+pub trait InterceptorExt: Interceptor + Sized {
+    /// Compose this interceptor with `next`, which intercepts the requests returned by this
+    /// interceptor.
+    ///
+    /// A request cancelled by this interceptor isn't passed to `next`.
+    fn and_then<I>(self, next: I) -> AndThen<Self, I>
+    where
+        I: Interceptor,
+    {
+        AndThen { first: self, next }
+    }
+
+    /// Convert this interceptor into a [`Layer`], e.g. to add it to a server or to a
+    /// [`tower::ServiceBuilder`](https://docs.rs/tower/latest/tower/struct.ServiceBuilder.html).
+    fn layer(self) -> InterceptorLayer<Self> {
+        InterceptorLayer::new(self)
+    }
+}
+
+impl<I: Interceptor> InterceptorExt for I {}
+
+/// An interceptor running two interceptors in order.
+///
+/// See [`InterceptorExt::and_then`] for more details.
+#[derive(Debug, Clone, Copy)]
+pub struct AndThen<A, B> {
+    first: A,
+    next: B,
+}
+
+impl<A, B> Interceptor for AndThen<A, B>
+where
+    A: Interceptor,
+    B: Interceptor,
+{
+    fn call(&mut self, request: crate::Request<()>) -> Result<crate::Request<()>, Status> {
+        let request = self.first.call(request)?;
+        self.next.call(request)
+    }
+}
+
 /// A gRPC interceptor that can be used as a [`Layer`],
 ///
 /// See [`Interceptor`] for more details.
@@ -326,6 +398,50 @@ mod tests {
         assert_eq!(expected.headers(), response.headers());
     }
 
+    #[tokio::test]
+    async fn runs_composed_interceptors_in_order() {
+        let svc = tower::service_fn(|request: http::Request<()>| async move {
+            assert_eq!(request.headers()["x-order"], "first,next");
+            Ok::<_, Status>(http::Response::new(()))
+        });
+
+        let first = |mut request: crate::Request<()>| {
+            request
+                .metadata_mut()
+                .insert("x-order", "first".parse().unwrap());
+            Ok(request)
+        };
+        let next = |mut request: crate::Request<()>| {
+            let order = request.metadata().get("x-order").unwrap().to_str().unwrap();
+            let order = format!("{order},next");
+            request
+                .metadata_mut()
+                .insert("x-order", order.parse().unwrap());
+            Ok(request)
+        };
+        let svc = first.and_then(next).layer().layer(svc);
+
+        let request = http::Request::builder().body(()).unwrap();
+        svc.oneshot(request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn skips_next_interceptors_of_cancelled_requests() {
+        let svc = tower::service_fn(|_: http::Request<()>| async {
+            Ok::<_, Status>(http::Response::new(()))
+        });
+
+        let deny = |_: crate::Request<()>| Err(Status::permission_denied("denied"));
+        let unreachable = |_: crate::Request<()>| -> Result<crate::Request<()>, Status> {
+            panic!("intercepted a cancelled request")
+        };
+        let svc = InterceptedService::new(svc, deny.and_then(unreachable));
+
+        let request = http::Request::builder().body(()).unwrap();
+        let response = svc.oneshot(request).await.unwrap();
+        assert_eq!(response.headers()["grpc-status"], "7");
+    }
+
     #[tokio::test]
     async fn doesnt_change_http_method() {
         let svc = tower::service_fn(|request: http::Request<()>| async move {
@@ -0,0 +1,159 @@
+//! Middleware routing calls by the value of a metadata key.
+//!
+//! The [`MetadataRouterLayer`] sends the calls whose metadata has a given value for a key to the
+//! service of that value, and the others to the wrapped service, e.g. to send the calls of some
+//! tenants to dedicated backends, or the calls asking for a version of an API to the backends
+//! serving it:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), tonic::transport::Error> {
+//! # use tonic::{metadata::AsciiMetadataKey, service::metadata_router::MetadataRouterLayer, transport::Endpoint};
+//! let default = Endpoint::from_static("http://backend").connect_lazy();
+//! let beta = Endpoint::from_static("http://backend-beta").connect_lazy();
+//!
+//! let channel = tower::ServiceBuilder::new()
+//!     .layer(MetadataRouterLayer::new(AsciiMetadataKey::from_static("x-version")).route("beta", beta))
+//!     .service(default);
+//! # drop(channel);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! All the routes are services of the same type, which may be boxed, e.g. with
+//! `tower::util::BoxCloneService`, to mix services of different types. The router may wrap the
+//! services of a server as well as channels.
+
+use std::{
+    collections::HashMap,
+    task::{Context, Poll},
+};
+
+use tower::{util::Oneshot, ServiceExt};
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::metadata::AsciiMetadataKey;
+
+/// Layer which applies the [`MetadataRouter`] middleware.
+#[derive(Debug, Clone)]
+pub struct MetadataRouterLayer<S> {
+    key: AsciiMetadataKey,
+    routes: HashMap<String, S>,
+}
+
+impl<S> MetadataRouterLayer<S> {
+    /// Create a new `MetadataRouterLayer` routing calls by the value of `key`, without routes.
+    pub fn new(key: AsciiMetadataKey) -> Self {
+        Self {
+            key,
+            routes: HashMap::new(),
+        }
+    }
+
+    /// Route the calls whose value of the key is `value` to `service`.
+    pub fn route(mut self, value: impl Into<String>, service: S) -> Self {
+        self.routes.insert(value.into(), service);
+        self
+    }
+}
+
+impl<S: Clone> Layer<S> for MetadataRouterLayer<S> {
+    type Service = MetadataRouter<S>;
+
+    fn layer(&self, default: S) -> Self::Service {
+        MetadataRouter {
+            key: self.key.clone(),
+            routes: self.routes.clone(),
+            default,
+        }
+    }
+}
+
+/// Middleware routing calls to services by the value of a metadata key.
+///
+/// The calls without a route for their value are sent to the default service.
+#[derive(Debug, Clone)]
+pub struct MetadataRouter<S> {
+    key: AsciiMetadataKey,
+    routes: HashMap<String, S>,
+    default: S,
+}
+
+impl<S> MetadataRouter<S> {
+    /// Create a new `MetadataRouter` routing calls by the value of `key`, sending them all to
+    /// `default` until routes are added.
+    pub fn new(key: AsciiMetadataKey, default: S) -> Self {
+        Self {
+            key,
+            routes: HashMap::new(),
+            default,
+        }
+    }
+
+    /// Route the calls whose value of the key is `value` to `service`.
+    pub fn route(mut self, value: impl Into<String>, service: S) -> Self {
+        self.routes.insert(value.into(), service);
+        self
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for MetadataRouter<S>
+where
+    S: Service<http::Request<B>> + Clone,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Oneshot<S, http::Request<B>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // The route of a call is only known once called, so the service of each call is readied
+        // by the call.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let service = req
+            .headers()
+            .get(self.key.as_str())
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| self.routes.get(value))
+            .unwrap_or(&self.default);
+        service.clone().oneshot(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(version: Option<&str>) -> http::Request<()> {
+        let mut req = http::Request::builder();
+        if let Some(version) = version {
+            req = req.header("x-version", version);
+        }
+        req.body(()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn routes_by_value() {
+        let backend = |name: &'static str| {
+            tower::service_fn(move |_: http::Request<()>| async move {
+                Ok::<_, std::convert::Infallible>(name)
+            })
+        };
+        let mut router = MetadataRouterLayer::new(AsciiMetadataKey::from_static("x-version"))
+            .route("beta", backend("beta"))
+            .route("v2", backend("v2"))
+            .layer(backend("default"));
+
+        for (version, expected) in [
+            (Some("beta"), "beta"),
+            (Some("v2"), "v2"),
+            (Some("v3"), "default"),
+            (None, "default"),
+        ] {
+            let name = router.ready().await.unwrap().call(request(version)).await;
+            assert_eq!(name.unwrap(), expected);
+        }
+    }
+}
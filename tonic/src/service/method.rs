@@ -0,0 +1,112 @@
+//! Services serving a single gRPC method, without codegen.
+//!
+//! Each service decodes the requests and encodes the responses of a method with a [`Codec`],
+//! and calls an inner service with the messages, like the services generated by `tonic-build`
+//! do. Together with [`Routes::add_method`], this allows serving methods at arbitrary paths, for
+//! example to proxy some methods of a service, or to override a method of a generated one:
+//!
+//! ```ignore
+//! let say_hello = UnaryMethod::new(
+//!     ProstCodec::<HelloReply, HelloRequest>::default(),
+//!     tower::service_fn(|request: Request<HelloRequest>| async move {
+//!         Ok(Response::new(HelloReply {
+//!             message: format!("Hello {}!", request.into_inner().name),
+//!         }))
+//!     }),
+//! );
+//!
+//! let routes = Routes::new(GreeterServer::new(greeter))
+//!     .add_method("/helloworld.Greeter/SayHello", say_hello);
+//! ```
+//!
+//! [`Routes::add_method`]: crate::service::Routes::add_method
+
+use crate::{
+    body::Body,
+    codec::Codec,
+    server::{
+        ClientStreamingService, Grpc, ServerStreamingService, StreamingService, UnaryService,
+    },
+};
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = Result<T, Infallible>> + Send + 'static>>;
+
+macro_rules! method {
+    ($(#[$attr:meta])* $name:ident, $service:ident, $handle:ident $(, $stream:ident)?) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $name<C, S> {
+            codec: C,
+            service: S,
+        }
+
+        impl<C, S> $name<C, S> {
+            /// Create a new service decoding and encoding messages with `codec`, and calling
+            /// `service` with them.
+            pub fn new(codec: C, service: S) -> Self {
+                Self { codec, service }
+            }
+        }
+
+        impl<C, S, B> Service<http::Request<B>> for $name<C, S>
+        where
+            C: Codec + Clone + Send + 'static,
+            S: $service<C::Decode, Response = C::Encode> + Clone + Send + 'static,
+            S::Future: Send,
+            $(S::$stream: Send + 'static,)?
+            B: http_body::Body<Data = bytes::Bytes> + Send + 'static,
+            B::Error: Into<crate::BoxError> + Send + 'static,
+        {
+            type Response = http::Response<Body>;
+            type Error = Infallible;
+            type Future = BoxFuture<Self::Response>;
+
+            fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+                Poll::Ready(Ok(()))
+            }
+
+            fn call(&mut self, req: http::Request<B>) -> Self::Future {
+                let mut grpc = Grpc::new(self.codec.clone());
+                let service = self.service.clone();
+                Box::pin(async move { Ok(grpc.$handle(service, req).await) })
+            }
+        }
+    };
+}
+
+method!(
+    /// Serves a unary method.
+    UnaryMethod,
+    UnaryService,
+    unary
+);
+
+method!(
+    /// Serves a server streaming method.
+    ServerStreamingMethod,
+    ServerStreamingService,
+    server_streaming,
+    ResponseStream
+);
+
+method!(
+    /// Serves a client streaming method.
+    ClientStreamingMethod,
+    ClientStreamingService,
+    client_streaming
+);
+
+method!(
+    /// Serves a bidirectional streaming method.
+    StreamingMethod,
+    StreamingService,
+    streaming,
+    ResponseStream
+);
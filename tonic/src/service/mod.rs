@@ -1,18 +1,74 @@
 //! Utilities for using Tower services with Tonic.
 
+#[cfg(any(feature = "server", feature = "channel"))]
+pub mod bulkhead;
+#[cfg(feature = "channel")]
+pub mod cache;
+pub mod checksum;
+#[cfg(feature = "channel")]
+pub mod circuit_breaker;
+pub mod completion;
+#[cfg(any(feature = "server", feature = "channel"))]
+pub mod fault;
 pub mod interceptor;
 pub(crate) mod layered;
+#[cfg(any(feature = "server", feature = "channel"))]
+pub mod metadata_router;
+pub mod method;
+#[cfg(feature = "request-id")]
+pub mod request_id;
 #[cfg(feature = "router")]
 pub(crate) mod router;
+#[cfg(any(feature = "server", feature = "channel"))]
+pub mod scheduler;
+pub mod trace_context;
 
 #[doc(inline)]
-pub use self::interceptor::{Interceptor, InterceptorLayer};
+#[cfg(any(feature = "server", feature = "channel"))]
+pub use self::bulkhead::BulkheadLayer;
+#[doc(inline)]
+#[cfg(feature = "channel")]
+pub use self::cache::ResponseCacheLayer;
+#[doc(inline)]
+pub use self::checksum::ChecksumLayer;
+#[doc(inline)]
+#[cfg(feature = "channel")]
+pub use self::circuit_breaker::CircuitBreakerLayer;
+#[doc(inline)]
+pub use self::completion::CompletionLayer;
+#[doc(inline)]
+#[cfg(any(feature = "server", feature = "channel"))]
+pub use self::fault::{Fault, FaultInjectionLayer};
+#[doc(inline)]
+pub use self::interceptor::{Interceptor, InterceptorExt, InterceptorLayer};
 pub use self::layered::{LayerExt, Layered};
 #[doc(inline)]
+#[cfg(any(feature = "server", feature = "channel"))]
+pub use self::metadata_router::MetadataRouterLayer;
+#[doc(inline)]
+#[cfg(feature = "request-id")]
+pub use self::request_id::{ClientRequestIdLayer, RequestId, ServerRequestIdLayer};
+#[doc(inline)]
 #[cfg(feature = "router")]
 pub use self::router::{Routes, RoutesBuilder};
+#[doc(inline)]
+#[cfg(any(feature = "server", feature = "channel"))]
+pub use self::scheduler::{Priority, SchedulerLayer};
+#[doc(inline)]
+pub use self::trace_context::{ClientTraceContextLayer, ServerTraceContextLayer, TraceContext};
 #[cfg(feature = "router")]
 pub use axum::{body::Body as AxumBody, Router as AxumRouter};
 
+pub mod proxy;
 pub mod recover_error;
+pub mod replay;
+#[cfg(feature = "channel")]
+pub mod shadow;
+#[doc(inline)]
+pub use self::proxy::GrpcProxy;
 pub use self::recover_error::{RecoverError, RecoverErrorLayer};
+#[doc(inline)]
+pub use self::replay::{RecordLayer, Recording};
+#[doc(inline)]
+#[cfg(feature = "channel")]
+pub use self::shadow::ShadowLayer;
@@ -0,0 +1,110 @@
+//! A service forwarding gRPC calls to an upstream service.
+//!
+//! [`GrpcProxy`] forwards every call it receives to an upstream service, usually a [`Channel`],
+//! without decoding the messages, so it proxies the calls of any method, unary or streaming,
+//! including methods whose schema is unknown. The metadata, messages and trailers of the calls
+//! are forwarded as they are, and the bodies are streamed, so the flow control of the upstream
+//! connection applies to the downstream one.
+//!
+//! Serving a `GrpcProxy` as the fallback of a server makes a gateway, forwarding the calls of
+//! the services not served locally:
+//!
+//! ```no_run
+//! # use tonic::{service::{proxy::GrpcProxy, AxumRouter, Routes}, transport::{Channel, Server}};
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! let upstream = Channel::from_static("http://upstream").connect().await?;
+//! let proxy = GrpcProxy::new(upstream);
+//!
+//! Server::builder()
+//!     .add_routes(Routes::from(AxumRouter::new().fallback_service(proxy)))
+//!     .serve("[::1]:50051".parse()?)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! A proxy may also serve a few methods only, see [`Routes::add_method`].
+//!
+//! [`Channel`]: crate::transport::Channel
+//! [`Routes::add_method`]: crate::service::Routes::add_method
+
+use std::{
+    convert::Infallible,
+    future::{poll_fn, Future},
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use http::header::{HeaderName, CONNECTION, HOST, TRANSFER_ENCODING, UPGRADE};
+use tower_service::Service;
+
+use crate::{body::Body, client::GrpcService, Status};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = Result<T, Infallible>> + Send + 'static>>;
+
+// The connection-specific headers, which HTTP/2 forbids.
+const CONNECTION_HEADERS: [HeaderName; 4] = [CONNECTION, HOST, TRANSFER_ENCODING, UPGRADE];
+
+/// A service forwarding gRPC calls to an upstream service.
+///
+/// See the [module documentation](self) for more details.
+#[derive(Debug, Clone)]
+pub struct GrpcProxy<S> {
+    upstream: S,
+}
+
+impl<S> GrpcProxy<S> {
+    /// Create a new `GrpcProxy` forwarding calls to `upstream`.
+    pub fn new(upstream: S) -> Self {
+        Self { upstream }
+    }
+
+    /// Get a reference to the upstream service.
+    pub fn get_ref(&self) -> &S {
+        &self.upstream
+    }
+}
+
+impl<S, B> Service<http::Request<B>> for GrpcProxy<S>
+where
+    S: GrpcService<Body> + Clone + Send + 'static,
+    S::Future: Send,
+    S::ResponseBody: http_body::Body<Data = Bytes> + Send + 'static,
+    <S::ResponseBody as http_body::Body>::Error: Into<crate::BoxError>,
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<crate::BoxError>,
+{
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // The clone of the upstream service of each call is readied by the call.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let mut upstream = self.upstream.clone();
+
+        Box::pin(async move {
+            let mut req = req.map(Body::new);
+            for name in &CONNECTION_HEADERS {
+                req.headers_mut().remove(name);
+            }
+
+            let response = async {
+                poll_fn(|cx| upstream.poll_ready(cx)).await?;
+                upstream.call(req).await
+            };
+            Ok(match response.await {
+                Ok(response) => response.map(Body::new),
+                Err(err) => {
+                    let status = Status::from_error(err.into());
+                    tracing::debug!("proxied call failed: {status}");
+                    status.into_http()
+                }
+            })
+        })
+    }
+}
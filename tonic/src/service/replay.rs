@@ -0,0 +1,913 @@
+//! Recording calls, and replaying them.
+//!
+//! The [`RecordLayer`] records the calls of a channel or of a server into a [`Recording`]: their
+//! method, request metadata, request and response messages, and status. Recordings are saved to
+//! text files, e.g. checked in as golden files, and replayed:
+//!
+//! - as a mock server, answering the recorded calls with their recorded responses, with
+//!   [`Recording::into_service`];
+//! - as client load, sending the recorded calls to a service and recording its responses, to
+//!   compare them with the recorded ones, with [`Recording::replay`].
+//!
+//! ```no_run
+//! # use tonic::{service::replay::{RecordLayer, Recording}, transport::Endpoint};
+//! # async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+//! let channel = Endpoint::from_static("http://backend").connect().await?;
+//!
+//! let recorder = RecordLayer::new();
+//! let channel = tower::ServiceBuilder::new()
+//!     .layer(recorder.clone())
+//!     .service(channel);
+//! // Call the backend with `channel`...
+//! # drop(channel);
+//! recorder.recording().save("tests/golden/backend.rec")?;
+//!
+//! // Later, check the backend still answers the same.
+//! let recording = Recording::load("tests/golden/backend.rec")?;
+//! let channel = Endpoint::from_static("http://backend").connect().await?;
+//! assert_eq!(recording.replay(channel).await?, recording);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Messages are recorded encoded, as sent, so record calls without compression. Messages with
+//! sensitive fields are redacted before being recorded with [`RecordLayer::redact_requests`] and
+//! [`RecordLayer::redact_responses`].
+
+use std::{
+    collections::HashMap,
+    fmt,
+    future::Future,
+    io,
+    path::Path,
+    pin::Pin,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+use base64::Engine as _;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use http::{header, HeaderMap, StatusCode};
+use http_body::Frame;
+use http_body_util::{BodyExt, StreamBody};
+use percent_encoding::{percent_decode_str, percent_encode, AsciiSet, CONTROLS};
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::{body::Body, metadata::GRPC_CONTENT_TYPE, util::base64::STANDARD, Code, Status};
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+// Redacts an encoded message.
+type Redactor = fn(Bytes) -> Bytes;
+
+// The redactors of the requests and responses of methods, by path.
+#[derive(Debug, Clone, Default)]
+struct Redactors {
+    requests: HashMap<String, Redactor>,
+    responses: HashMap<String, Redactor>,
+}
+
+// The request headers not recorded as metadata, set by the gRPC protocol.
+const PROTOCOL_HEADERS: [&str; 6] = [
+    "te",
+    "content-type",
+    "user-agent",
+    "grpc-timeout",
+    "grpc-encoding",
+    "grpc-accept-encoding",
+];
+
+const MESSAGE_ENCODING_SET: &AsciiSet = &CONTROLS.add(b'%');
+
+/// A recorded call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Call {
+    method: String,
+    metadata: Vec<(String, String)>,
+    requests: Vec<Bytes>,
+    responses: Vec<Bytes>,
+    code: Code,
+    message: String,
+}
+
+impl Call {
+    /// The path of the method called, e.g. `/helloworld.Greeter/SayHello`.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// The names and values of the request metadata.
+    pub fn metadata(&self) -> &[(String, String)] {
+        &self.metadata
+    }
+
+    /// The encoded request messages.
+    pub fn requests(&self) -> &[Bytes] {
+        &self.requests
+    }
+
+    /// The encoded response messages.
+    pub fn responses(&self) -> &[Bytes] {
+        &self.responses
+    }
+
+    /// The code of the call status.
+    pub fn code(&self) -> Code {
+        self.code
+    }
+
+    /// The message of the call status.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Recorded calls.
+///
+/// Recordings are saved as text, one line per item, each call starting with its method:
+///
+/// ```text
+/// # Comments start with `#`.
+/// call /helloworld.Greeter/SayHello
+/// metadata x-request-id 42
+/// request CgV3b3JsZA==
+/// response Cg1IZWxsbyB3b3JsZCE=
+/// status 0
+///
+/// call /helloworld.Greeter/SayHello
+/// request
+/// status 3 invalid%20name
+/// ```
+///
+/// Messages are encoded in base64, and status messages are percent-encoded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recording {
+    calls: Vec<Call>,
+}
+
+impl Recording {
+    /// Create an empty `Recording`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The recorded calls, in the order they ended.
+    pub fn calls(&self) -> &[Call] {
+        &self.calls
+    }
+
+    /// Load a recording from the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        std::fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Save the recording to the file at `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.to_string())
+    }
+
+    /// Create a service answering the recorded calls, e.g. to use as a mock server.
+    ///
+    /// See [`ReplayService`].
+    pub fn into_service(self) -> ReplayService {
+        ReplayService {
+            calls: self.calls.into(),
+        }
+    }
+
+    /// Send the recorded calls to `service`, one after the other, and return the recording of its
+    /// answers.
+    ///
+    /// The calls are sent with their recorded metadata and request messages, so the recording
+    /// returned is equal to this one if `service` answers as recorded.
+    pub async fn replay<S, B>(&self, mut service: S) -> Result<Recording, crate::BoxError>
+    where
+        S: Service<http::Request<Body>, Response = http::Response<B>>,
+        S::Error: Into<crate::BoxError>,
+        B: http_body::Body<Data = Bytes>,
+        B::Error: Into<crate::BoxError>,
+    {
+        let mut calls = Vec::with_capacity(self.calls.len());
+        for call in &self.calls {
+            let mut req = http::Request::builder()
+                .method(http::Method::POST)
+                .uri(&call.method)
+                .version(http::Version::HTTP_2)
+                .header(header::TE, "trailers")
+                .header(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+            for (name, value) in &call.metadata {
+                req = req.header(name, value);
+            }
+            let body = encode_messages(&call.requests);
+            let req = req.body(Body::new(http_body_util::Full::new(body)))?;
+
+            std::future::poll_fn(|cx| service.poll_ready(cx))
+                .await
+                .map_err(Into::into)?;
+            let (parts, body) = service.call(req).await.map_err(Into::into)?.into_parts();
+            let collected = body.collect().await.map_err(Into::into)?;
+            let trailers = collected.trailers().cloned();
+            let (code, message) = status_of(parts.status, &parts.headers, trailers.as_ref());
+
+            calls.push(Call {
+                responses: decode_messages(collected.to_bytes()),
+                code,
+                message,
+                ..call.clone()
+            });
+        }
+        Ok(Recording { calls })
+    }
+}
+
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, call) in self.calls.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "call {}", call.method)?;
+            for (name, value) in &call.metadata {
+                writeln!(f, "metadata {name} {value}")?;
+            }
+            for (kind, messages) in [("request", &call.requests), ("response", &call.responses)] {
+                for message in messages {
+                    if message.is_empty() {
+                        writeln!(f, "{kind}")?;
+                    } else {
+                        writeln!(f, "{kind} {}", STANDARD.encode(message))?;
+                    }
+                }
+            }
+            if call.message.is_empty() {
+                writeln!(f, "status {}", call.code as i32)?;
+            } else {
+                let message = percent_encode(call.message.as_bytes(), MESSAGE_ENCODING_SET);
+                writeln!(f, "status {} {message}", call.code as i32)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recording {
+    type Err = ParseRecordingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut calls = Vec::new();
+        // The call being parsed, and whether its status was parsed.
+        let mut call: Option<(Call, bool)> = None;
+
+        for (i, line) in s.lines().enumerate() {
+            let error = |reason| ParseRecordingError {
+                line: i + 1,
+                reason,
+            };
+
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (item, value) = line.split_once(' ').unwrap_or((line, ""));
+
+            if item == "call" {
+                match call.take() {
+                    Some((_, false)) => return Err(error("previous call has no status")),
+                    Some((previous, true)) => calls.push(previous),
+                    None => {}
+                }
+                if !value.starts_with('/') {
+                    return Err(error("invalid method path"));
+                }
+                let new = Call {
+                    method: value.to_owned(),
+                    metadata: Vec::new(),
+                    requests: Vec::new(),
+                    responses: Vec::new(),
+                    code: Code::Ok,
+                    message: String::new(),
+                };
+                call = Some((new, false));
+                continue;
+            }
+
+            let (call, status_parsed) = match &mut call {
+                Some((_, true)) => return Err(error("item after the call status")),
+                Some((call, status_parsed)) => (call, status_parsed),
+                None => return Err(error("item before the first call")),
+            };
+            match item {
+                "metadata" => {
+                    let (name, value) = value.split_once(' ').unwrap_or((value, ""));
+                    if name.is_empty() {
+                        return Err(error("metadata without name"));
+                    }
+                    call.metadata.push((name.to_owned(), value.to_owned()));
+                }
+                "request" | "response" => {
+                    let message = STANDARD
+                        .decode(value)
+                        .map_err(|_| error("invalid base64 message"))?;
+                    if item == "request" {
+                        call.requests.push(message.into());
+                    } else {
+                        call.responses.push(message.into());
+                    }
+                }
+                "status" => {
+                    let (code, message) = value.split_once(' ').unwrap_or((value, ""));
+                    call.code = code
+                        .parse::<i32>()
+                        .map(Code::from)
+                        .map_err(|_| error("invalid status code"))?;
+                    call.message = percent_decode_str(message)
+                        .decode_utf8()
+                        .map_err(|_| error("invalid status message"))?
+                        .into_owned();
+                }
+                _ => return Err(error("unknown item")),
+            }
+            if item == "status" {
+                *status_parsed = true;
+            }
+        }
+
+        match call {
+            Some((_, false)) => {
+                return Err(ParseRecordingError {
+                    line: s.lines().count(),
+                    reason: "last call has no status",
+                })
+            }
+            Some((last, true)) => calls.push(last),
+            None => {}
+        }
+        Ok(Recording { calls })
+    }
+}
+
+/// Error returned when parsing an invalid [`Recording`].
+#[derive(Debug)]
+pub struct ParseRecordingError {
+    line: usize,
+    reason: &'static str,
+}
+
+impl fmt::Display for ParseRecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid recording, line {}: {}", self.line, self.reason)
+    }
+}
+
+impl std::error::Error for ParseRecordingError {}
+
+/// Layer which applies the [`Record`] middleware.
+///
+/// The clones of a layer record into the same recording.
+#[derive(Debug, Clone, Default)]
+pub struct RecordLayer {
+    calls: Arc<Mutex<Vec<Call>>>,
+    redactors: Arc<Redactors>,
+}
+
+impl RecordLayer {
+    /// Create a new `RecordLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Redact the request messages of the method at `path`, e.g. `/helloworld.Greeter/SayHello`,
+    /// before recording them.
+    ///
+    /// The requests are decoded as `M`, redacted with [`RedactMessage`](crate::RedactMessage),
+    /// and encoded again, so their replays send the redacted messages. Requests failing to be
+    /// decoded are recorded empty, rather than leaking their fields.
+    #[cfg(feature = "prost")]
+    pub fn redact_requests<M>(mut self, path: impl Into<String>) -> Self
+    where
+        M: prost::Message + Default + crate::RedactMessage,
+    {
+        Arc::make_mut(&mut self.redactors)
+            .requests
+            .insert(path.into(), redact_encoded::<M>);
+        self
+    }
+
+    /// Redact the response messages of the method at `path` before recording them.
+    ///
+    /// See [`RecordLayer::redact_requests`].
+    #[cfg(feature = "prost")]
+    pub fn redact_responses<M>(mut self, path: impl Into<String>) -> Self
+    where
+        M: prost::Message + Default + crate::RedactMessage,
+    {
+        Arc::make_mut(&mut self.redactors)
+            .responses
+            .insert(path.into(), redact_encoded::<M>);
+        self
+    }
+
+    /// The calls recorded so far.
+    pub fn recording(&self) -> Recording {
+        Recording {
+            calls: self.calls.lock().unwrap().clone(),
+        }
+    }
+}
+
+impl<S> Layer<S> for RecordLayer {
+    type Service = Record<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Record {
+            inner,
+            calls: self.calls.clone(),
+            redactors: self.redactors.clone(),
+        }
+    }
+}
+
+/// Middleware recording the calls of a service.
+///
+/// Calls are recorded once their response ends. Calls failing with an error of the service, not
+/// answered with a status, aren't recorded.
+#[derive(Debug, Clone)]
+pub struct Record<S> {
+    inner: S,
+    calls: Arc<Mutex<Vec<Call>>>,
+    redactors: Arc<Redactors>,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for Record<S>
+where
+    S: Service<http::Request<Body>, Response = http::Response<ResBody>>,
+    ReqBody: http_body::Body<Data = Bytes> + Send + 'static,
+    ResBody: http_body::Body,
+    ReqBody::Error: Into<crate::BoxError>,
+{
+    type Response = http::Response<ResponseBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let metadata = req
+            .headers()
+            .iter()
+            .filter(|(name, _)| !PROTOCOL_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect();
+        let path = req.uri().path();
+        let redactors = [&self.redactors.requests, &self.redactors.responses]
+            .map(|redactors| redactors.get(path).copied());
+        let pending = Arc::new(Mutex::new(Pending {
+            call: Some(Call {
+                method: req.uri().path().to_owned(),
+                metadata,
+                requests: Vec::new(),
+                responses: Vec::new(),
+                code: Code::Unknown,
+                message: String::new(),
+            }),
+            requests: BytesMut::new(),
+            responses: BytesMut::new(),
+            status: None,
+            redactors,
+            calls: self.calls.clone(),
+        }));
+
+        let tapped = pending.clone();
+        let req = req.map(|body| {
+            Body::new(RequestBody {
+                inner: body,
+                pending: tapped,
+            })
+        });
+
+        ResponseFuture {
+            inner: self.inner.call(req),
+            pending: Some(pending),
+        }
+    }
+}
+
+// A call being recorded.
+#[derive(Debug)]
+struct Pending {
+    call: Option<Call>,
+    requests: BytesMut,
+    responses: BytesMut,
+    // The HTTP status and headers of the response.
+    status: Option<(StatusCode, HeaderMap)>,
+    // The redactors of the requests and responses of the method.
+    redactors: [Option<Redactor>; 2],
+    calls: Arc<Mutex<Vec<Call>>>,
+}
+
+impl Pending {
+    // Record the call, once its response ends with `trailers`.
+    fn finish(&mut self, trailers: Option<&HeaderMap>) {
+        let Some(mut call) = self.call.take() else {
+            return;
+        };
+        let (status, headers) = self.status.take().unwrap_or_default();
+        (call.code, call.message) = status_of(status, &headers, trailers);
+        call.requests = decode_messages(self.requests.split().freeze());
+        call.responses = decode_messages(self.responses.split().freeze());
+        let [redact_requests, redact_responses] = self.redactors;
+        if let Some(redact) = redact_requests {
+            call.requests = call.requests.into_iter().map(redact).collect();
+        }
+        if let Some(redact) = redact_responses {
+            call.responses = call.responses.into_iter().map(redact).collect();
+        }
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+// A request body copying its data into the call being recorded.
+#[pin_project]
+struct RequestBody<B> {
+    #[pin]
+    inner: B,
+    pending: Arc<Mutex<Pending>>,
+}
+
+impl<B> http_body::Body for RequestBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let this = self.project();
+        let frame = std::task::ready!(this.inner.poll_frame(cx));
+        if let Some(data) = frame.as_ref().and_then(|f| f.as_ref().ok()?.data_ref()) {
+            this.pending.lock().unwrap().requests.put_slice(data);
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<B> fmt::Debug for RequestBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestBody").finish()
+    }
+}
+
+/// Response future for [`Record`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    pending: Option<Arc<Mutex<Pending>>>,
+}
+
+impl<F, E, B> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<B>, E>>,
+    B: http_body::Body,
+{
+    type Output = Result<http::Response<ResponseBody<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = std::task::ready!(this.inner.poll(cx))?;
+        let pending = this.pending.take().expect("polled after completion");
+        {
+            let mut pending = pending.lock().unwrap();
+            pending.status = Some((res.status(), res.headers().clone()));
+            // Clients may not poll the body of trailers-only responses.
+            if res.body().is_end_stream() {
+                pending.finish(None);
+            }
+        }
+        Poll::Ready(Ok(res.map(|body| ResponseBody {
+            inner: body,
+            pending,
+        })))
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+/// Response body for [`Record`].
+#[pin_project]
+pub struct ResponseBody<B> {
+    #[pin]
+    inner: B,
+    pending: Arc<Mutex<Pending>>,
+}
+
+impl<B> http_body::Body for ResponseBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let frame = std::task::ready!(this.inner.as_mut().poll_frame(cx));
+        let mut pending = this.pending.lock().unwrap();
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    pending.responses.put_slice(data);
+                }
+                if let Some(trailers) = frame.trailers_ref() {
+                    pending.finish(Some(trailers));
+                } else if this.inner.is_end_stream() {
+                    // The body may not be polled again once it reports its end.
+                    pending.finish(None);
+                }
+            }
+            Some(Err(_)) => pending.call = None,
+            None => pending.finish(None),
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl<B> fmt::Debug for ResponseBody<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseBody").finish()
+    }
+}
+
+/// Service answering recorded calls with their recorded responses.
+///
+/// Calls are answered once their request ends, by the first recorded call of their method with
+/// the same request messages, regardless of their metadata. Calls not recorded fail with
+/// [`Code::Unimplemented`].
+///
+/// As a mock server, `ReplayService` is used directly as the channel of clients, or served with
+/// [`Routes`]:
+///
+/// ```no_run
+/// # use tonic::service::{replay::Recording, Routes};
+/// # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+/// let replay = Recording::load("tests/golden/backend.rec")?.into_service();
+/// tonic::transport::Server::builder()
+///     .add_routes(Routes::from(axum::Router::new().fallback_service(replay)))
+///     .serve("127.0.0.1:50051".parse()?)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// [`Routes`]: crate::service::Routes
+#[derive(Debug, Clone)]
+pub struct ReplayService {
+    calls: Arc<[Call]>,
+}
+
+impl<B> Service<http::Request<B>> for ReplayService
+where
+    B: http_body::Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<crate::BoxError>,
+{
+    type Response = http::Response<Body>;
+    type Error = std::convert::Infallible;
+    type Future = BoxFuture<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<B>) -> Self::Future {
+        let calls = self.calls.clone();
+        Box::pin(async move {
+            let (parts, body) = req.into_parts();
+            let body = match body.collect().await {
+                Ok(body) => body.to_bytes(),
+                Err(e) => return Ok(Status::from_error(e.into()).into_http()),
+            };
+            let requests = decode_messages(body);
+
+            let path = parts.uri.path();
+            let Some(call) = calls
+                .iter()
+                .find(|call| call.method == path && call.requests == requests)
+            else {
+                return Ok(Status::unimplemented(format!("no recorded call to {path}")).into_http());
+            };
+
+            let mut trailers = HeaderMap::new();
+            if let Err(status) =
+                Status::new(call.code, call.message.clone()).add_header(&mut trailers)
+            {
+                return Ok(status.into_http());
+            }
+            let frames = [
+                Frame::data(encode_messages(&call.responses)),
+                Frame::trailers(trailers),
+            ]
+            .map(Ok::<_, std::convert::Infallible>);
+            let mut res =
+                http::Response::new(Body::new(StreamBody::new(tokio_stream::iter(frames))));
+            res.headers_mut()
+                .insert(header::CONTENT_TYPE, GRPC_CONTENT_TYPE);
+            Ok(res)
+        })
+    }
+}
+
+// The status code and message of a response.
+fn status_of(
+    status: StatusCode,
+    headers: &HeaderMap,
+    trailers: Option<&HeaderMap>,
+) -> (Code, String) {
+    let status = trailers
+        .and_then(Status::from_header_map)
+        .or_else(|| Status::from_header_map(headers))
+        .or_else(|| {
+            crate::status::infer_grpc_status(None, status)
+                .err()
+                .flatten()
+        });
+    match status {
+        Some(status) => (status.code(), status.message().to_owned()),
+        None => (Code::Unknown, "missing grpc-status".to_owned()),
+    }
+}
+
+// Split the length-prefixed messages of a body.
+fn decode_messages(mut body: Bytes) -> Vec<Bytes> {
+    let mut messages = Vec::new();
+    while body.len() >= 5 {
+        let len = u32::from_be_bytes([body[1], body[2], body[3], body[4]]) as usize;
+        body.advance(5);
+        messages.push(body.split_to(len.min(body.len())));
+    }
+    messages
+}
+
+#[cfg(feature = "prost")]
+fn redact_encoded<M>(message: Bytes) -> Bytes
+where
+    M: prost::Message + Default + crate::RedactMessage,
+{
+    match M::decode(message) {
+        Ok(mut message) => {
+            message.redact();
+            message.encode_to_vec().into()
+        }
+        Err(_) => Bytes::new(),
+    }
+}
+
+fn encode_messages(messages: &[Bytes]) -> Bytes {
+    let mut body = BytesMut::new();
+    for message in messages {
+        body.put_u8(0);
+        body.put_u32(message.len() as u32);
+        body.put_slice(message);
+    }
+    body.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECORDING: &str = "\
+call /test.Test/Call
+metadata x-user alice
+request aGVsbG8=
+request
+response d29ybGQ=
+status 0
+
+call /test.Test/Fail
+metadata x-user alice
+request aGVsbG8=
+status 3 invalid%0Aname 100%25
+";
+
+    fn request(path: &str, messages: &[&'static [u8]]) -> http::Request<Body> {
+        let messages: Vec<_> = messages.iter().map(|m| Bytes::from_static(m)).collect();
+        http::Request::builder()
+            .uri(path)
+            .header("x-user", "alice")
+            .header(header::TE, "trailers")
+            .body(Body::new(http_body_util::Full::new(encode_messages(
+                &messages,
+            ))))
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_and_formats_recordings() {
+        let recording = RECORDING.parse::<Recording>().unwrap();
+        let calls = recording.calls();
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].method(), "/test.Test/Call");
+        assert_eq!(
+            calls[0].metadata(),
+            [("x-user".to_owned(), "alice".to_owned())]
+        );
+        assert_eq!(calls[0].requests(), ["hello", ""]);
+        assert_eq!(calls[0].responses(), ["world"]);
+        assert_eq!(calls[0].code(), Code::Ok);
+        assert_eq!(calls[1].code(), Code::InvalidArgument);
+        assert_eq!(calls[1].message(), "invalid\nname 100%");
+
+        assert_eq!(recording.to_string(), RECORDING);
+    }
+
+    #[test]
+    fn rejects_invalid_recordings() {
+        for (recording, error) in [
+            ("request aGVsbG8=\n", "line 1: item before the first call"),
+            ("call /a/b\nrequest !\n", "line 2: invalid base64 message"),
+            (
+                "call /a/b\n\ncall /a/c\n",
+                "line 3: previous call has no status",
+            ),
+            (
+                "call /a/b\nstatus 0\nrequest\n",
+                "line 3: item after the call status",
+            ),
+            ("call /a/b\nrequest\n", "line 2: last call has no status"),
+        ] {
+            let err = recording.parse::<Recording>().unwrap_err();
+            assert_eq!(err.to_string(), format!("invalid recording, {error}"));
+        }
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_calls() {
+        let mut svc = RECORDING.parse::<Recording>().unwrap().into_service();
+
+        let res = svc
+            .call(request("/test.Test/Call", &[b"hello", b""]))
+            .await
+            .unwrap();
+        let collected = res.into_body().collect().await.unwrap();
+        assert_eq!(collected.trailers().unwrap()["grpc-status"], "0");
+        assert_eq!(decode_messages(collected.to_bytes()), ["world"]);
+
+        let res = svc
+            .call(request("/test.Test/Call", &[b"other"]))
+            .await
+            .unwrap();
+        assert_eq!(res.headers()["grpc-status"], "12");
+    }
+
+    #[tokio::test]
+    async fn records_calls() {
+        let recording = RECORDING.parse::<Recording>().unwrap();
+        let layer = RecordLayer::new();
+        let mut svc = layer.layer(recording.clone().into_service());
+
+        for (path, messages) in [
+            ("/test.Test/Call", &[&b"hello"[..], b""][..]),
+            ("/test.Test/Fail", &[b"hello"]),
+        ] {
+            let res = svc.call(request(path, messages)).await.unwrap();
+            res.into_body().collect().await.unwrap();
+        }
+        assert_eq!(layer.recording(), recording);
+
+        // Replaying the recording to its own replay records the same calls.
+        let replayed = recording.replay(recording.clone().into_service());
+        assert_eq!(replayed.await.unwrap(), recording);
+    }
+}
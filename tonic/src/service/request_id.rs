@@ -0,0 +1,326 @@
+//! Middleware propagating and generating request IDs.
+//!
+//! The [`ServerRequestIdLayer`] reads the `x-request-id` metadata of the requests of a server,
+//! generating a random UUID when absent, and serves the requests within a tracing span recording
+//! it. The ID is set in the [`RequestId`] extension for application logging, and sent back in the
+//! metadata of the response:
+//!
+//! ```
+//! # use tonic::{service::request_id::RequestId, Request};
+//! # fn handle(req: Request<()>) {
+//! let id = req.extensions().get::<RequestId>().unwrap();
+//! tracing::info!(request_id = %id, "handling the request");
+//! # }
+//! ```
+//!
+//! The [`ClientRequestIdLayer`] sets the `x-request-id` metadata of the requests of a channel
+//! without it, to the [`RequestId`] extension of the request, or to a random UUID. Handlers
+//! calling other servers propagate the ID of their own request by copying its extension:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), tonic::transport::Error> {
+//! # use tonic::{
+//! #     service::request_id::{ClientRequestIdLayer, RequestId},
+//! #     transport::Endpoint,
+//! #     Request,
+//! # };
+//! # let incoming = Request::new(());
+//! let channel = Endpoint::from_static("http://backend").connect().await?;
+//! let channel = tower::ServiceBuilder::new()
+//!     .layer(ClientRequestIdLayer::new())
+//!     .service(channel);
+//!
+//! let mut req = Request::new(());
+//! if let Some(id) = incoming.extensions().get::<RequestId>() {
+//!     req.extensions_mut().insert(id.clone());
+//! }
+//! # drop((channel, req));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The ID of a call is also set in the [`RequestId`] extension of its response.
+
+use std::{
+    fmt,
+    future::Future,
+    pin::Pin,
+    str::FromStr,
+    task::{ready, Context, Poll},
+};
+
+use http::{HeaderName, HeaderValue};
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::{instrument::Instrumented, Instrument};
+use uuid::Uuid;
+
+use crate::metadata::{errors::InvalidMetadataValue, AsciiMetadataKey};
+
+const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// The ID of a request, in the extensions of the requests and responses passing through a
+/// [`ServerRequestIdLayer`] or a [`ClientRequestIdLayer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(HeaderValue);
+
+impl RequestId {
+    /// A random version 4 UUID.
+    pub fn generate() -> Self {
+        let id = Uuid::new_v4().hyphenated().to_string();
+        Self(HeaderValue::try_from(id).expect("UUIDs are valid metadata"))
+    }
+
+    // Request IDs must be visible ASCII, to be shown as strings.
+    fn from_header(value: &HeaderValue) -> Option<Self> {
+        let valid = !value.is_empty() && value.to_str().is_ok();
+        valid.then(|| Self(value.clone()))
+    }
+
+    /// The ID as a string.
+    pub fn as_str(&self) -> &str {
+        self.0.to_str().expect("request IDs are visible ASCII")
+    }
+}
+
+impl FromStr for RequestId {
+    type Err = InvalidMetadataValue;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        HeaderValue::from_str(s)
+            .ok()
+            .as_ref()
+            .and_then(Self::from_header)
+            .ok_or_else(InvalidMetadataValue::new)
+    }
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Layer which applies the [`ServerRequestId`] middleware.
+#[derive(Debug, Clone)]
+pub struct ServerRequestIdLayer {
+    key: HeaderName,
+}
+
+impl ServerRequestIdLayer {
+    /// Create a new `ServerRequestIdLayer` reading the `x-request-id` metadata.
+    pub fn new() -> Self {
+        Self {
+            key: REQUEST_ID_HEADER,
+        }
+    }
+
+    /// Read the request IDs from the `key` metadata instead.
+    pub fn metadata_key(self, key: AsciiMetadataKey) -> Self {
+        Self { key: key.inner }
+    }
+}
+
+impl Default for ServerRequestIdLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for ServerRequestIdLayer {
+    type Service = ServerRequestId<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ServerRequestId {
+            inner,
+            key: self.key.clone(),
+        }
+    }
+}
+
+/// Middleware setting the request IDs of the requests of a server, and serving them within a
+/// tracing span recording their ID.
+#[derive(Debug, Clone)]
+pub struct ServerRequestId<S> {
+    inner: S,
+    key: HeaderName,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for ServerRequestId<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<Instrumented<S::Future>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let id = match req
+            .headers()
+            .get(&self.key)
+            .and_then(RequestId::from_header)
+        {
+            Some(id) => id,
+            None => {
+                let id = RequestId::generate();
+                req.headers_mut().insert(self.key.clone(), id.0.clone());
+                id
+            }
+        };
+        req.extensions_mut().insert(id.clone());
+
+        let span = tracing::info_span!("request", request_id = %id);
+        ResponseFuture {
+            inner: self.inner.call(req).instrument(span),
+            id: Some(id),
+            key: Some(self.key.clone()),
+        }
+    }
+}
+
+/// Layer which applies the [`ClientRequestId`] middleware.
+#[derive(Debug, Clone)]
+pub struct ClientRequestIdLayer {
+    key: HeaderName,
+}
+
+impl ClientRequestIdLayer {
+    /// Create a new `ClientRequestIdLayer` setting the `x-request-id` metadata.
+    pub fn new() -> Self {
+        Self {
+            key: REQUEST_ID_HEADER,
+        }
+    }
+
+    /// Set the request IDs in the `key` metadata instead.
+    pub fn metadata_key(self, key: AsciiMetadataKey) -> Self {
+        Self { key: key.inner }
+    }
+}
+
+impl Default for ClientRequestIdLayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> Layer<S> for ClientRequestIdLayer {
+    type Service = ClientRequestId<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientRequestId {
+            inner,
+            key: self.key.clone(),
+        }
+    }
+}
+
+/// Middleware setting the request IDs of the requests of a client.
+#[derive(Debug, Clone)]
+pub struct ClientRequestId<S> {
+    inner: S,
+    key: HeaderName,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for ClientRequestId<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let metadata = req
+            .headers()
+            .get(&self.key)
+            .and_then(RequestId::from_header);
+        let id = match metadata {
+            Some(id) => id,
+            None => {
+                let id = req
+                    .extensions()
+                    .get::<RequestId>()
+                    .cloned()
+                    .unwrap_or_else(RequestId::generate);
+                req.headers_mut().insert(self.key.clone(), id.0.clone());
+                id
+            }
+        };
+
+        ResponseFuture {
+            inner: self.inner.call(req),
+            id: Some(id),
+            key: None,
+        }
+    }
+}
+
+/// Response future for [`ServerRequestId`] and [`ClientRequestId`].
+#[pin_project]
+pub struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    id: Option<RequestId>,
+    // The metadata the ID is sent back in, by servers.
+    key: Option<HeaderName>,
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<http::Response<B>, E>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let mut res = ready!(this.inner.poll(cx))?;
+        let id = this.id.take().expect("polled after completion");
+        if let Some(key) = this.key.take() {
+            res.headers_mut().entry(key).or_insert_with(|| id.0.clone());
+        }
+        res.extensions_mut().insert(id);
+        Poll::Ready(Ok(res))
+    }
+}
+
+impl<F> fmt::Debug for ResponseFuture<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture")
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_version_4_uuids() {
+        let id = RequestId::generate();
+        let id = id.as_str();
+        assert_eq!(id.len(), 36);
+        let groups: Vec<_> = id.split('-').map(str::len).collect();
+        assert_eq!(groups, [8, 4, 4, 4, 12]);
+        assert_eq!(&id[14..15], "4");
+        assert!(matches!(&id[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(RequestId::generate(), RequestId::generate());
+    }
+
+    #[test]
+    fn parses_visible_ascii() {
+        assert_eq!("abc-123".parse::<RequestId>().unwrap().as_str(), "abc-123");
+        assert!("".parse::<RequestId>().is_err());
+        assert!("a\nb".parse::<RequestId>().is_err());
+    }
+}
@@ -10,9 +10,14 @@ use std::{
 use tower::{Service, ServiceExt};
 
 /// A [`Service`] router.
+///
+/// Requests are routed to the added gRPC services by their path. An HTTP service may also be set
+/// with [`Routes::http_service`] to serve the requests that are not gRPC requests, e.g. to serve
+/// a REST API next to the gRPC services on the same listener.
 #[derive(Debug, Clone)]
 pub struct Routes {
     router: axum::Router,
+    http: Option<axum::Router>,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -39,6 +44,34 @@ impl RoutesBuilder {
         self
     }
 
+    /// Add a service serving the requests of a single method.
+    ///
+    /// See [`Routes::add_method`].
+    pub fn add_method<S>(&mut self, path: &str, svc: S) -> &mut Self
+    where
+        S: Service<Request<Body>, Error = Infallible> + Clone + Send + Sync + 'static,
+        S::Response: axum::response::IntoResponse,
+        S::Future: Send + 'static,
+    {
+        let routes = self.routes.take().unwrap_or_default();
+        self.routes.replace(routes.add_method(path, svc));
+        self
+    }
+
+    /// Set the service of the requests that are not gRPC requests.
+    ///
+    /// See [`Routes::http_service`].
+    pub fn http_service<S>(&mut self, svc: S) -> &mut Self
+    where
+        S: Service<Request<Body>, Error = Infallible> + Clone + Send + Sync + 'static,
+        S::Response: axum::response::IntoResponse,
+        S::Future: Send + 'static,
+    {
+        let routes = self.routes.take().unwrap_or_default();
+        self.routes.replace(routes.http_service(svc));
+        self
+    }
+
     /// Returns the routes with added services or empty [`Routes`] if no service was added
     pub fn routes(self) -> Routes {
         self.routes.unwrap_or_default()
@@ -49,6 +82,7 @@ impl Default for Routes {
     fn default() -> Self {
         Self {
             router: axum::Router::new().fallback(unimplemented),
+            http: None,
         }
     }
 }
@@ -93,21 +127,82 @@ impl Routes {
         self
     }
 
+    /// Add a service serving the requests of a single method, at `path`.
+    ///
+    /// `path` is the path of the method, like `/helloworld.Greeter/SayHello`. The requests of the
+    /// method are routed to `svc` even when the service of the method was added, so a method of a
+    /// generated service can be overridden. `svc` may be any service, e.g. one proxying the
+    /// requests, or one of the typed services of [`method`](crate::service::method).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a service was already added at `path`.
+    pub fn add_method<S>(mut self, path: &str, svc: S) -> Self
+    where
+        S: Service<Request<Body>, Error = Infallible> + Clone + Send + Sync + 'static,
+        S::Response: axum::response::IntoResponse,
+        S::Future: Send + 'static,
+    {
+        self.router = self.router.route_service(
+            path,
+            svc.map_request(|req: Request<axum::body::Body>| req.map(Body::new)),
+        );
+        self
+    }
+
+    /// Set the service of the requests that are not gRPC requests, replacing any previously set
+    /// one.
+    ///
+    /// Requests whose `content-type` starts with `application/grpc` are routed to the gRPC
+    /// services, and the others to `svc`, for example an [`axum::Router`] serving a REST API:
+    ///
+    /// ```ignore
+    /// let rest = axum::Router::new().route("/health", axum::routing::get(|| async { "ok" }));
+    ///
+    /// Server::builder()
+    ///     .accept_http1(true)
+    ///     .add_service(GreeterServer::new(greeter))
+    ///     .http_service(rest)
+    ///     .serve(addr)
+    ///     .await?;
+    /// ```
+    ///
+    /// Both are then served by the same server, sharing its listener, TLS configuration and
+    /// graceful shutdown. Layers of the server, like `tonic_web::GrpcWebLayer`, run before the
+    /// requests are routed, so the requests they translate to gRPC reach the gRPC services.
+    pub fn http_service<S>(mut self, svc: S) -> Self
+    where
+        S: Service<Request<Body>, Error = Infallible> + Clone + Send + Sync + 'static,
+        S::Response: axum::response::IntoResponse,
+        S::Future: Send + 'static,
+    {
+        self.http = Some(axum::Router::new().fallback_service(
+            svc.map_request(|req: Request<axum::body::Body>| req.map(Body::new)),
+        ));
+        self
+    }
+
     /// This makes axum perform update some internals of the router that improves perf.
     ///
     /// See <https://docs.rs/axum/latest/axum/routing/struct.Router.html#a-note-about-performance>
     pub fn prepare(self) -> Self {
         Self {
             router: self.router.with_state(()),
+            http: self.http.map(|http| http.with_state(())),
         }
     }
 
     /// Convert this `Routes` into an [`axum::Router`].
+    ///
+    /// When an HTTP service is set, the returned router dispatches to it like `Routes` does.
     pub fn into_axum_router(self) -> axum::Router {
-        self.router
+        if self.http.is_none() {
+            return self.router;
+        }
+        axum::Router::new().fallback_service(self)
     }
 
-    /// Get a mutable reference to the [`axum::Router`].
+    /// Get a mutable reference to the [`axum::Router`] of the gRPC services.
     pub fn axum_router_mut(&mut self) -> &mut axum::Router {
         &mut self.router
     }
@@ -131,10 +226,16 @@ impl From<axum::Router> for RoutesBuilder {
 
 impl From<axum::Router> for Routes {
     fn from(router: axum::Router) -> Self {
-        Self { router }
+        Self { router, http: None }
     }
 }
 
+fn is_grpc<B>(req: &Request<B>) -> bool {
+    req.headers()
+        .get(http::header::CONTENT_TYPE)
+        .is_some_and(|content_type| content_type.as_bytes().starts_with(b"application/grpc"))
+}
+
 async fn unimplemented() -> Response<Body> {
     let (parts, ()) = Status::unimplemented("").into_http::<()>().into_parts();
     Response::from_parts(parts, Body::empty())
@@ -155,7 +256,10 @@ where
     }
 
     fn call(&mut self, req: Request<B>) -> Self::Future {
-        RoutesFuture(self.router.call(req))
+        match &mut self.http {
+            Some(http) if !is_grpc(&req) => RoutesFuture(http.call(req)),
+            _ => RoutesFuture(self.router.call(req)),
+        }
     }
 }
 
@@ -0,0 +1,556 @@
+//! Middleware scheduling calls by priority.
+//!
+//! The [`SchedulerLayer`] bounds the number of calls in flight of the services it wraps. The calls
+//! beyond the bound wait in a queue, from which the calls of the highest [`Priority`] are taken
+//! first. Once the queue is full, the calls of lower priorities are shed first: a call evicts the
+//! last queued call of the lowest priority below its own, and is shed itself when there is none.
+//! Shed calls fail with [`Code::Unavailable`].
+//!
+//! Calls get the priority of their method, or of the value of a metadata key, e.g. set by clients
+//! for their background calls:
+//!
+//! ```
+//! # use tonic::{metadata::AsciiMetadataKey, service::scheduler::{Priority, SchedulerLayer}};
+//! let scheduler = SchedulerLayer::new(64)
+//!     .queue_capacity(256)
+//!     .method("/helloworld.Greeter/SayHello", Priority::High)
+//!     .metadata_key(AsciiMetadataKey::from_static("x-priority"));
+//!
+//! // A clone of the layer keeps reading the depths of its queues.
+//! let metrics = scheduler.clone();
+//! # tonic::transport::Server::builder().layer(scheduler);
+//! # let _ = metrics.queue_depth(Priority::Low);
+//! ```
+//!
+//! Every service wrapped by a layer and its clones is scheduled against the same bound, so a layer
+//! set with [`Server::layer`] bounds the calls of the whole server.
+//!
+//! [`Code::Unavailable`]: crate::Code::Unavailable
+//! [`Server::layer`]: crate::transport::Server::layer
+
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::debug;
+
+use crate::{metadata::AsciiMetadataKey, Status};
+
+/// The priority class of a call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Calls shed first, e.g. background or batch calls.
+    Low,
+    /// The priority of the calls without another one.
+    #[default]
+    Normal,
+    /// Calls shed last, e.g. health checks or interactive calls.
+    High,
+}
+
+impl Priority {
+    // From the highest priority to the lowest.
+    const ALL: [Priority; 3] = [Priority::High, Priority::Normal, Priority::Low];
+
+    /// The metadata value of the priority: `low`, `normal` or `high`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+
+    fn from_value(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|priority| value.eq_ignore_ascii_case(priority.as_str()))
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Layer which applies the [`Scheduler`] middleware.
+#[derive(Debug, Clone)]
+pub struct SchedulerLayer {
+    shared: Arc<Shared>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    max_concurrency: usize,
+    queue_capacity: usize,
+    methods: HashMap<String, Priority>,
+    key: Option<AsciiMetadataKey>,
+    default: Priority,
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    in_flight: usize,
+    // The calls waiting for their turn, indexed by priority.
+    queues: [VecDeque<Waiter>; 3],
+}
+
+type Waiter = Arc<Mutex<Turn>>;
+
+#[derive(Debug)]
+enum Turn {
+    Waiting(Option<Waker>),
+    Admitted,
+    Shed,
+}
+
+impl SchedulerLayer {
+    /// Create a new `SchedulerLayer` letting `max_concurrency` calls in flight at once.
+    pub fn new(max_concurrency: usize) -> Self {
+        let max_concurrency = max_concurrency.max(1);
+        Self {
+            shared: Arc::new(Shared {
+                max_concurrency,
+                queue_capacity: max_concurrency,
+                methods: HashMap::new(),
+                key: None,
+                default: Priority::Normal,
+                state: Mutex::default(),
+            }),
+        }
+    }
+
+    fn shared(&mut self) -> &mut Shared {
+        Arc::get_mut(&mut self.shared).expect("configured before the layer is cloned")
+    }
+
+    /// Set the number of calls waiting for their turn, over all the priorities.
+    ///
+    /// Default is the maximum number of calls in flight.
+    ///
+    /// # Panics
+    ///
+    /// The setters panic if the layer was already cloned.
+    pub fn queue_capacity(mut self, capacity: usize) -> Self {
+        self.shared().queue_capacity = capacity;
+        self
+    }
+
+    /// Give the calls of the method of `path`, e.g. `/helloworld.Greeter/SayHello`, the priority
+    /// `priority`.
+    pub fn method(mut self, path: impl Into<String>, priority: Priority) -> Self {
+        self.shared().methods.insert(path.into(), priority);
+        self
+    }
+
+    /// Give the calls whose metadata has a [priority value](Priority::as_str) for `key` that
+    /// priority, over the priority of their method.
+    ///
+    /// Clients may then raise the priorities of their own calls, so the key should be stripped
+    /// from the calls of untrusted clients.
+    pub fn metadata_key(mut self, key: AsciiMetadataKey) -> Self {
+        self.shared().key = Some(key);
+        self
+    }
+
+    /// Set the priority of the calls without another one.
+    ///
+    /// Default is [`Priority::Normal`].
+    pub fn default_priority(mut self, priority: Priority) -> Self {
+        self.shared().default = priority;
+        self
+    }
+
+    /// The number of calls of priority `priority` waiting for their turn.
+    pub fn queue_depth(&self, priority: Priority) -> usize {
+        self.shared.state.lock().unwrap().queues[priority.index()].len()
+    }
+
+    /// The number of calls in flight.
+    pub fn in_flight(&self) -> usize {
+        self.shared.state.lock().unwrap().in_flight
+    }
+}
+
+impl<S> Layer<S> for SchedulerLayer {
+    type Service = Scheduler<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Scheduler {
+            inner,
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// Middleware scheduling the calls of a service by priority, as configured by a
+/// [`SchedulerLayer`].
+#[derive(Debug, Clone)]
+pub struct Scheduler<S> {
+    inner: S,
+    shared: Arc<Shared>,
+}
+
+impl Shared {
+    fn priority<B>(&self, req: &http::Request<B>) -> Priority {
+        let tagged = self
+            .key
+            .as_ref()
+            .and_then(|key| req.headers().get(key.as_str()))
+            .and_then(|value| value.to_str().ok())
+            .and_then(Priority::from_value);
+        tagged
+            .or_else(|| self.methods.get(req.uri().path()).copied())
+            .unwrap_or(self.default)
+    }
+
+    fn schedule(self: &Arc<Self>, priority: Priority) -> Admission {
+        let mut state = self.state.lock().unwrap();
+        let queued: usize = state.queues.iter().map(VecDeque::len).sum();
+        if state.in_flight < self.max_concurrency && queued == 0 {
+            state.in_flight += 1;
+            return Admission::Admitted(Permit(self.clone()));
+        }
+
+        if queued >= self.queue_capacity {
+            let evicted = Priority::ALL
+                .into_iter()
+                .rev()
+                .take_while(|lower| *lower < priority)
+                .find_map(|lower| state.queues[lower.index()].pop_back());
+            match evicted {
+                Some(waiter) => {
+                    debug!(%priority, "shedding a queued call of a lower priority");
+                    wake(&waiter, Turn::Shed);
+                }
+                None => return Admission::Shed,
+            }
+        }
+
+        let waiter = Arc::new(Mutex::new(Turn::Waiting(None)));
+        state.queues[priority.index()].push_back(waiter.clone());
+        Admission::Queued(waiter)
+    }
+}
+
+impl State {
+    // Hands the turn of a call done to the next queued call.
+    fn release(&mut self) {
+        let next = Priority::ALL
+            .into_iter()
+            .find_map(|priority| self.queues[priority.index()].pop_front());
+        match next {
+            Some(waiter) => wake(&waiter, Turn::Admitted),
+            None => self.in_flight -= 1,
+        }
+    }
+}
+
+fn wake(waiter: &Waiter, turn: Turn) {
+    if let Turn::Waiting(Some(waker)) = std::mem::replace(&mut *waiter.lock().unwrap(), turn) {
+        waker.wake();
+    }
+}
+
+enum Admission {
+    Admitted(Permit),
+    Queued(Waiter),
+    Shed,
+}
+
+// The turn of a call in flight, handed to the next call once dropped.
+#[derive(Debug)]
+struct Permit(Arc<Shared>);
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().release();
+    }
+}
+
+// A queued call, leaving the queue once dropped.
+struct Ticket {
+    shared: Arc<Shared>,
+    waiter: Option<Waiter>,
+}
+
+impl Ticket {
+    fn poll_turn(&mut self, cx: &mut Context<'_>) -> Poll<Option<Permit>> {
+        let waiter = self.waiter.as_ref().expect("polled after completion");
+        let admitted = match &mut *waiter.lock().unwrap() {
+            Turn::Waiting(waker) => {
+                *waker = Some(cx.waker().clone());
+                return Poll::Pending;
+            }
+            Turn::Admitted => true,
+            Turn::Shed => false,
+        };
+        self.waiter = None;
+        Poll::Ready(admitted.then(|| Permit(self.shared.clone())))
+    }
+}
+
+impl Drop for Ticket {
+    fn drop(&mut self) {
+        let Some(waiter) = self.waiter.take() else {
+            return;
+        };
+        let mut state = self.shared.state.lock().unwrap();
+        let turn = std::mem::replace(&mut *waiter.lock().unwrap(), Turn::Shed);
+        match turn {
+            Turn::Waiting(_) => {
+                for queue in &mut state.queues {
+                    queue.retain(|queued| !Arc::ptr_eq(queued, &waiter));
+                }
+            }
+            Turn::Admitted => state.release(),
+            Turn::Shed => {}
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for Scheduler<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>> + Clone,
+    ResBody: Default,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S, ReqBody>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let priority = self.shared.priority(&req);
+        let kind = match self.shared.schedule(priority) {
+            Admission::Admitted(permit) => Kind::Called {
+                future: self.inner.call(req),
+                permit,
+            },
+            Admission::Queued(waiter) => {
+                // The inner service is called once the call's turn comes, so take the service
+                // driven to readiness and leave a clone in its place.
+                let clone = self.inner.clone();
+                Kind::Queued {
+                    ticket: Ticket {
+                        shared: self.shared.clone(),
+                        waiter: Some(waiter),
+                    },
+                    call: Some(Box::new((std::mem::replace(&mut self.inner, clone), req))),
+                }
+            }
+            Admission::Shed => {
+                debug!(%priority, path = req.uri().path(), "shedding call");
+                Kind::Shed
+            }
+        };
+        ResponseFuture { kind }
+    }
+}
+
+/// Response future for [`Scheduler`].
+#[pin_project]
+pub struct ResponseFuture<S, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    #[pin]
+    kind: Kind<S, ReqBody, S::Future>,
+}
+
+#[pin_project(project = KindProj)]
+enum Kind<S, ReqBody, F> {
+    Queued {
+        ticket: Ticket,
+        call: Option<Box<(S, http::Request<ReqBody>)>>,
+    },
+    Called {
+        #[pin]
+        future: F,
+        permit: Permit,
+    },
+    Shed,
+}
+
+impl<S, ReqBody, ResBody> Future for ResponseFuture<S, ReqBody>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    ResBody: Default,
+{
+    type Output = Result<S::Response, S::Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        loop {
+            match self.as_mut().project().kind.project() {
+                KindProj::Queued { ticket, call } => {
+                    match std::task::ready!(ticket.poll_turn(cx)) {
+                        Some(permit) => {
+                            let (mut inner, req) = *call.take().expect("polled after completion");
+                            let future = inner.call(req);
+                            self.as_mut()
+                                .project()
+                                .kind
+                                .set(Kind::Called { future, permit });
+                        }
+                        None => self.as_mut().project().kind.set(Kind::Shed),
+                    }
+                }
+                KindProj::Called { future, .. } => return future.poll(cx),
+                KindProj::Shed => {
+                    let status = Status::unavailable("server overloaded, call shed");
+                    return Poll::Ready(Ok(status.into_http()));
+                }
+            }
+        }
+    }
+}
+
+impl<S, ReqBody> fmt::Debug for ResponseFuture<S, ReqBody>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseFuture").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::Infallible;
+    use tokio::sync::Notify;
+    use tower::ServiceExt;
+
+    // A service recording the calls it receives, the calls of `/block` waiting to be notified.
+    fn service(
+        calls: Arc<Mutex<Vec<String>>>,
+        unblock: Arc<Notify>,
+    ) -> impl Service<http::Request<()>, Response = http::Response<()>, Error = Infallible> + Clone
+    {
+        tower::service_fn(move |req: http::Request<()>| {
+            let calls = calls.clone();
+            let unblock = unblock.clone();
+            async move {
+                let path = req.uri().path().to_owned();
+                if path == "/block" {
+                    unblock.notified().await;
+                }
+                calls.lock().unwrap().push(path);
+                Ok(http::Response::new(()))
+            }
+        })
+    }
+
+    fn request(path: &str, priority: Option<&str>) -> http::Request<()> {
+        let mut req = http::Request::builder().uri(path);
+        if let Some(priority) = priority {
+            req = req.header("x-priority", priority);
+        }
+        req.body(()).unwrap()
+    }
+
+    fn grpc_status(res: &http::Response<()>) -> Option<&str> {
+        res.headers()
+            .get("grpc-status")
+            .map(|status| status.to_str().unwrap())
+    }
+
+    #[tokio::test]
+    async fn queued_calls_are_taken_by_priority() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let unblock = Arc::new(Notify::new());
+        let layer = SchedulerLayer::new(1)
+            .queue_capacity(8)
+            .method("/critical", Priority::High)
+            .metadata_key(AsciiMetadataKey::from_static("x-priority"));
+        let metrics = layer.clone();
+        let mut svc = layer.layer(service(calls.clone(), unblock.clone()));
+
+        let block = svc.ready().await.unwrap().call(request("/block", None));
+        let low = svc
+            .ready()
+            .await
+            .unwrap()
+            .call(request("/batch", Some("low")));
+        let normal = svc.ready().await.unwrap().call(request("/call", None));
+        let high = svc.ready().await.unwrap().call(request("/critical", None));
+        assert_eq!(metrics.in_flight(), 1);
+        assert_eq!(metrics.queue_depth(Priority::Low), 1);
+        assert_eq!(metrics.queue_depth(Priority::Normal), 1);
+        assert_eq!(metrics.queue_depth(Priority::High), 1);
+
+        unblock.notify_one();
+        let (block, low, normal, high) = tokio::join!(block, low, normal, high);
+        for res in [block, low, normal, high] {
+            assert_eq!(grpc_status(&res.unwrap()), None);
+        }
+        assert_eq!(
+            *calls.lock().unwrap(),
+            ["/block", "/critical", "/call", "/batch"]
+        );
+        assert_eq!(metrics.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn lower_priorities_are_shed_first() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let unblock = Arc::new(Notify::new());
+        let layer = SchedulerLayer::new(1)
+            .queue_capacity(1)
+            .method("/critical", Priority::High)
+            .default_priority(Priority::Low);
+        let metrics = layer.clone();
+        let mut svc = layer.layer(service(calls.clone(), unblock.clone()));
+
+        let block = svc.ready().await.unwrap().call(request("/block", None));
+        let evicted = svc.ready().await.unwrap().call(request("/batch", None));
+        let high = svc.ready().await.unwrap().call(request("/critical", None));
+        let rejected = svc.ready().await.unwrap().call(request("/batch", None));
+        assert_eq!(metrics.queue_depth(Priority::Low), 0);
+        assert_eq!(metrics.queue_depth(Priority::High), 1);
+
+        assert_eq!(grpc_status(&evicted.await.unwrap()), Some("14"));
+        assert_eq!(grpc_status(&rejected.await.unwrap()), Some("14"));
+
+        unblock.notify_one();
+        let (block, high) = tokio::join!(block, high);
+        assert_eq!(grpc_status(&block.unwrap()), None);
+        assert_eq!(grpc_status(&high.unwrap()), None);
+        assert_eq!(*calls.lock().unwrap(), ["/block", "/critical"]);
+    }
+
+    #[tokio::test]
+    async fn dropped_calls_leave_the_queue() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let unblock = Arc::new(Notify::new());
+        let layer = SchedulerLayer::new(1);
+        let metrics = layer.clone();
+        let mut svc = layer.layer(service(calls.clone(), unblock.clone()));
+
+        let block = svc.ready().await.unwrap().call(request("/block", None));
+        drop(svc.ready().await.unwrap().call(request("/dropped", None)));
+        assert_eq!(metrics.queue_depth(Priority::Normal), 0);
+
+        drop(block);
+        assert_eq!(metrics.in_flight(), 0);
+        let res = svc.oneshot(request("/call", None)).await.unwrap();
+        assert_eq!(grpc_status(&res), None);
+        assert_eq!(*calls.lock().unwrap(), ["/call"]);
+    }
+}
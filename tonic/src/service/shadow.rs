@@ -0,0 +1,312 @@
+//! Middleware mirroring calls to a shadow service.
+//!
+//! The [`ShadowLayer`] sends a copy of a percentage of the unary calls of a channel to a shadow
+//! channel, e.g. a new version of a backend, to validate it with production traffic. The calls
+//! are answered by the wrapped channel only: the shadow calls are sent in the background once the
+//! request message is sent, and their responses and errors are ignored.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), tonic::transport::Error> {
+//! # use tonic::{service::shadow::ShadowLayer, transport::Endpoint};
+//! let channel = Endpoint::from_static("http://backend").connect().await?;
+//! let shadow = Endpoint::from_static("http://backend-canary").connect_lazy();
+//!
+//! // Mirror a fifth of the unary calls.
+//! let channel = tower::ServiceBuilder::new()
+//!     .layer(ShadowLayer::new(shadow, 20.0))
+//!     .service(channel);
+//! # drop(channel);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The kind of a call is learned from the [`GrpcMethodKind`] extension of its request, so calls
+//! of other kinds and requests not sent by [`Grpc`] are never mirrored.
+//!
+//! [`Grpc`]: crate::client::Grpc
+
+use std::{
+    fmt,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::{Bytes, BytesMut};
+use http_body::Frame;
+use http_body_util::{BodyExt, Full};
+use tokio::sync::oneshot;
+use tower::util::rng::{HasherRng, Rng};
+use tower::ServiceExt;
+use tower_layer::Layer;
+use tower_service::Service;
+use tracing::debug;
+
+use crate::{body::Body, GrpcMethodKind, Status};
+
+/// Layer which applies the [`Shadow`] middleware.
+#[derive(Debug, Clone)]
+pub struct ShadowLayer<T> {
+    shadow: T,
+    percentage: f64,
+}
+
+impl<T> ShadowLayer<T> {
+    /// Create a new `ShadowLayer` mirroring `percentage` percent of the unary calls to `shadow`.
+    pub fn new(shadow: T, percentage: f64) -> Self {
+        Self { shadow, percentage }
+    }
+}
+
+impl<S, T: Clone> Layer<S> for ShadowLayer<T> {
+    type Service = Shadow<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Shadow {
+            inner,
+            shadow: self.shadow.clone(),
+            percentage: self.percentage,
+            rng: HasherRng::new(),
+        }
+    }
+}
+
+/// Middleware mirroring the unary calls of a service to a shadow service.
+#[derive(Debug)]
+pub struct Shadow<S, T> {
+    inner: S,
+    shadow: T,
+    percentage: f64,
+    rng: HasherRng,
+}
+
+// Clones sample the calls with their own random numbers, as clients may call a clone of the
+// service for each request.
+impl<S: Clone, T: Clone> Clone for Shadow<S, T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            shadow: self.shadow.clone(),
+            percentage: self.percentage,
+            rng: HasherRng::new(),
+        }
+    }
+}
+
+impl<S, T, ShadowBody> Service<http::Request<Body>> for Shadow<S, T>
+where
+    S: Service<http::Request<Body>>,
+    T: Service<http::Request<Body>, Response = http::Response<ShadowBody>> + Clone + Send + 'static,
+    T::Future: Send,
+    T::Error: Into<crate::BoxError>,
+    ShadowBody: http_body::Body + Send + 'static,
+    ShadowBody::Data: Send,
+    ShadowBody::Error: Into<crate::BoxError>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let unary = req.extensions().get::<GrpcMethodKind>() == Some(&GrpcMethodKind::Unary);
+        if !unary || self.percentage <= 0.0 || self.rng.next_f64() * 100.0 >= self.percentage {
+            return self.inner.call(req);
+        }
+
+        let mut shadow_req = http::Request::new(());
+        *shadow_req.method_mut() = req.method().clone();
+        *shadow_req.uri_mut() = req.uri().clone();
+        *shadow_req.version_mut() = req.version();
+        *shadow_req.headers_mut() = req.headers().clone();
+        *shadow_req.extensions_mut() = req.extensions().clone();
+
+        // The shadow call is sent with the request message, once read from the request body.
+        let (tx, rx) = oneshot::channel();
+        let req = req.map(|body| {
+            Body::new(TeeBody {
+                inner: body,
+                buf: BytesMut::new(),
+                tx: Some(tx),
+            })
+        });
+
+        let shadow = self.shadow.clone();
+        tokio::spawn(async move {
+            let Ok(message) = rx.await else {
+                return;
+            };
+            let path = shadow_req.uri().path().to_owned();
+            let shadow_req = shadow_req.map(|()| Body::new(Full::new(message)));
+
+            let res: Result<_, crate::BoxError> =
+                shadow.oneshot(shadow_req).await.map_err(Into::into);
+            match res {
+                // Read the response to its end, so the shadow call isn't cancelled.
+                Ok(res) => {
+                    if let Err(e) = res.into_body().collect().await {
+                        debug!(path, "shadow response failed: {}", e.into());
+                    }
+                }
+                Err(e) => debug!(path, "shadow call failed: {}", e),
+            }
+        });
+
+        self.inner.call(req)
+    }
+}
+
+// A request body keeping a copy of its data, sent to `tx` once the body is read to its end.
+struct TeeBody {
+    inner: Body,
+    buf: BytesMut,
+    tx: Option<oneshot::Sender<Bytes>>,
+}
+
+impl TeeBody {
+    fn send(&mut self) {
+        if let Some(tx) = self.tx.take() {
+            let _ = tx.send(std::mem::take(&mut self.buf).freeze());
+        }
+    }
+}
+
+impl http_body::Body for TeeBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let frame = std::task::ready!(Pin::new(&mut self.inner).poll_frame(cx));
+        match &frame {
+            Some(Ok(frame)) => {
+                if let Some(data) = frame.data_ref() {
+                    if self.tx.is_some() {
+                        self.buf.extend_from_slice(data);
+                    }
+                }
+                // The body may not be polled again once it reports its end.
+                if self.inner.is_end_stream() {
+                    self.send();
+                }
+            }
+            Some(Err(_)) => self.tx = None,
+            None => self.send(),
+        }
+        Poll::Ready(frame)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+impl fmt::Debug for TeeBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeeBody").finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc;
+
+    fn request(kind: Option<GrpcMethodKind>, message: &'static [u8]) -> http::Request<Body> {
+        let mut req = http::Request::builder()
+            .uri("http://backend/test.Test/Call")
+            .header("x-test", "1")
+            .body(Body::new(Full::new(Bytes::from_static(message))))
+            .unwrap();
+        if let Some(kind) = kind {
+            req.extensions_mut().insert(kind);
+        }
+        req
+    }
+
+    // A service reading its requests, sending their paths, headers and messages to `tx`.
+    fn recorder(
+        tx: mpsc::UnboundedSender<(String, String, Bytes)>,
+    ) -> impl Service<
+        http::Request<Body>,
+        Response = http::Response<Body>,
+        Error = Status,
+        Future = impl Send,
+    > + Clone
+           + Send
+           + 'static {
+        tower::service_fn(move |req: http::Request<Body>| {
+            let tx = tx.clone();
+            async move {
+                let path = req.uri().path().to_owned();
+                let header = req.headers()["x-test"].to_str().unwrap().to_owned();
+                let message = req.into_body().collect().await?.to_bytes();
+                tx.send((path, header, message)).unwrap();
+                Ok(http::Response::new(Body::empty()))
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn mirrors_unary_calls() {
+        let (primary_tx, mut primary_rx) = mpsc::unbounded_channel();
+        let (shadow_tx, mut shadow_rx) = mpsc::unbounded_channel();
+        let svc = ShadowLayer::new(recorder(shadow_tx), 100.0).layer(recorder(primary_tx));
+
+        svc.oneshot(request(Some(GrpcMethodKind::Unary), b"hello"))
+            .await
+            .unwrap();
+
+        let expected = (
+            "/test.Test/Call".to_owned(),
+            "1".to_owned(),
+            Bytes::from("hello"),
+        );
+        assert_eq!(primary_rx.recv().await.unwrap(), expected);
+        assert_eq!(shadow_rx.recv().await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn ignores_other_calls() {
+        let (primary_tx, mut primary_rx) = mpsc::unbounded_channel();
+        let (shadow_tx, mut shadow_rx) = mpsc::unbounded_channel();
+        let svc = ShadowLayer::new(recorder(shadow_tx), 100.0).layer(recorder(primary_tx));
+
+        for kind in [None, Some(GrpcMethodKind::ClientStreaming)] {
+            svc.clone().oneshot(request(kind, b"hello")).await.unwrap();
+            assert_eq!(primary_rx.recv().await.unwrap().2, "hello");
+        }
+        drop(svc);
+        assert!(shadow_rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn ignores_shadow_errors() {
+        let calls = Arc::new(Mutex::new(0));
+        let shadow = {
+            let calls = calls.clone();
+            tower::service_fn(move |_: http::Request<Body>| {
+                *calls.lock().unwrap() += 1;
+                async { Err::<http::Response<Body>, _>(Status::internal("shadow")) }
+            })
+        };
+        let (primary_tx, mut primary_rx) = mpsc::unbounded_channel();
+        let svc = ShadowLayer::new(shadow, 100.0).layer(recorder(primary_tx));
+
+        svc.oneshot(request(Some(GrpcMethodKind::Unary), b"hello"))
+            .await
+            .unwrap();
+        assert_eq!(primary_rx.recv().await.unwrap().2, "hello");
+        tokio::task::yield_now().await;
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+}
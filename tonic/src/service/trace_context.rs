@@ -0,0 +1,589 @@
+//! Middleware propagating the W3C trace context and baggage of calls.
+//!
+//! The [`ServerTraceContextLayer`] reads the `traceparent`, `tracestate` and `baggage` metadata
+//! of the requests of a server into a [`TraceContext`], set in the extensions of the requests and
+//! made [current](TraceContext::current) while their handlers run. The
+//! [`ClientTraceContextLayer`] sets this metadata on the requests of a channel to the current
+//! trace context, so the calls made by handlers continue the trace of their own request without
+//! a tracing integration:
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), Box<dyn std::error::Error>> {
+//! # use tonic::{
+//! #     service::trace_context::{ClientTraceContextLayer, ServerTraceContextLayer},
+//! #     transport::{Endpoint, Server},
+//! # };
+//! let channel = Endpoint::from_static("http://backend").connect().await?;
+//! let channel = tower::ServiceBuilder::new()
+//!     .layer(ClientTraceContextLayer::new())
+//!     .service(channel);
+//!
+//! let server = Server::builder().layer(ServerTraceContextLayer::new());
+//! # drop((channel, server));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The trace context is current in the task of the handler only; tasks spawned by handlers take
+//! it along with [`TraceContext::scope`].
+//!
+//! Servers also read the trace context of the requests with the binary `grpc-trace-bin` metadata
+//! of OpenCensus only, and clients send it along with the W3C metadata when
+//! [asked to](ClientTraceContextLayer::grpc_trace_bin), to interoperate with the services only
+//! propagating it. This format carries no `tracestate`.
+
+use std::{
+    cell::RefCell,
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use http::{HeaderMap, HeaderName};
+use pin_project::pin_project;
+use tower_layer::Layer;
+use tower_service::Service;
+
+use crate::metadata::{Binary, MetadataMap, MetadataValue};
+
+const TRACEPARENT: HeaderName = HeaderName::from_static("traceparent");
+const TRACESTATE: HeaderName = HeaderName::from_static("tracestate");
+const BAGGAGE: HeaderName = HeaderName::from_static("baggage");
+const GRPC_TRACE_BIN: HeaderName = HeaderName::from_static("grpc-trace-bin");
+
+thread_local! {
+    static CURRENT: RefCell<Option<TraceContext>> = const { RefCell::new(None) };
+}
+
+/// The W3C trace context and baggage of a call: its `traceparent`, `tracestate` and `baggage`
+/// metadata.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TraceContext {
+    headers: HeaderMap,
+}
+
+impl TraceContext {
+    /// Read the trace context of `metadata`.
+    ///
+    /// A `traceparent` not following the W3C format is ignored, along with the `tracestate`.
+    /// Without a valid `traceparent`, the `grpc-trace-bin` metadata is read instead.
+    pub fn extract(metadata: &MetadataMap) -> Self {
+        Self::from_headers(metadata.as_ref())
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let mut context = HeaderMap::new();
+        let traceparent = headers.get(TRACEPARENT);
+        if let Some(traceparent) = traceparent.filter(|value| is_traceparent(value.as_bytes())) {
+            context.insert(TRACEPARENT, traceparent.clone());
+            for value in headers.get_all(TRACESTATE) {
+                context.append(TRACESTATE, value.clone());
+            }
+        } else if let Some(traceparent) = headers
+            .get(GRPC_TRACE_BIN)
+            .and_then(|value| {
+                MetadataValue::<Binary>::unchecked_from_header_value_ref(value)
+                    .to_bytes()
+                    .ok()
+            })
+            .and_then(|bytes| Self::from_grpc_trace_bin(&bytes))
+            .and_then(|context| context.headers.get(TRACEPARENT).cloned())
+        {
+            context.insert(TRACEPARENT, traceparent);
+        }
+        for value in headers.get_all(BAGGAGE) {
+            context.append(BAGGAGE, value.clone());
+        }
+        Self { headers: context }
+    }
+
+    /// Set the trace context in `metadata`, keeping the trace context already there.
+    ///
+    /// Only the W3C metadata is set; the `grpc-trace-bin` metadata is set with
+    /// [`TraceContext::grpc_trace_bin`].
+    pub fn inject(&self, metadata: &mut MetadataMap) {
+        self.inject_headers(metadata.as_mut(), false);
+    }
+
+    fn inject_headers(&self, headers: &mut HeaderMap, grpc_trace_bin: bool) {
+        if self.headers.is_empty() || is_traced(headers) {
+            return;
+        }
+        for (key, value) in &self.headers {
+            headers.append(key, value.clone());
+        }
+        if let Some(bytes) = self.grpc_trace_bin().filter(|_| grpc_trace_bin) {
+            headers.insert(
+                GRPC_TRACE_BIN,
+                MetadataValue::<Binary>::from_bytes(&bytes).inner,
+            );
+        }
+    }
+
+    /// The trace context of the `grpc-trace-bin` metadata `bytes`, if valid.
+    ///
+    /// ```
+    /// # use tonic::service::trace_context::TraceContext;
+    /// let mut bytes = vec![0, 0];
+    /// bytes.extend([0x0a; 16]);
+    /// bytes.push(1);
+    /// bytes.extend([0x0b; 8]);
+    /// bytes.extend([2, 1]);
+    ///
+    /// let context = TraceContext::from_grpc_trace_bin(&bytes).unwrap();
+    /// assert_eq!(
+    ///     context.traceparent(),
+    ///     Some("00-0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a-0b0b0b0b0b0b0b0b-01")
+    /// );
+    /// assert_eq!(context.grpc_trace_bin(), Some(bytes));
+    /// ```
+    pub fn from_grpc_trace_bin(bytes: &[u8]) -> Option<Self> {
+        // The version, then fields of an ID byte followed by their value.
+        let (&0, mut fields) = bytes.split_first()? else {
+            return None;
+        };
+        let (mut trace_id, mut span_id, mut options) = (None, None, 0);
+        while let Some((&id, rest)) = fields.split_first() {
+            // Unknown fields end the fields known to this version.
+            let len = match id {
+                0 => 16,
+                1 => 8,
+                2 => 1,
+                _ => break,
+            };
+            let value = rest.get(..len)?;
+            match id {
+                0 => trace_id = Some(value),
+                1 => span_id = Some(value),
+                _ => options = value[0],
+            }
+            fields = &rest[len..];
+        }
+
+        let traceparent = format!(
+            "00-{}-{}-{:02x}",
+            to_hex(trace_id?),
+            to_hex(span_id?),
+            options & 1
+        );
+        if !is_traceparent(traceparent.as_bytes()) {
+            return None;
+        }
+        let mut headers = HeaderMap::new();
+        let value = traceparent
+            .try_into()
+            .expect("traceparents are valid metadata");
+        headers.insert(TRACEPARENT, value);
+        Some(Self { headers })
+    }
+
+    /// The `grpc-trace-bin` metadata of the trace context, if it has a `traceparent`.
+    pub fn grpc_trace_bin(&self) -> Option<Vec<u8>> {
+        let traceparent = self.headers.get(TRACEPARENT)?.as_bytes();
+        let mut parts = traceparent.split(|b| *b == b'-').skip(1);
+        let (trace_id, span_id, flags) = (parts.next()?, parts.next()?, parts.next()?);
+
+        let mut bytes = vec![0, 0];
+        bytes.extend(from_hex(trace_id)?);
+        bytes.push(1);
+        bytes.extend(from_hex(span_id)?);
+        bytes.push(2);
+        bytes.push(from_hex(flags)?.first()? & 1);
+        Some(bytes)
+    }
+
+    /// The `traceparent` of the call, identifying the trace and the calling span.
+    pub fn traceparent(&self) -> Option<&str> {
+        self.headers.get(TRACEPARENT)?.to_str().ok()
+    }
+
+    /// The `tracestate` of the call, with the values of several `tracestate` metadata joined.
+    pub fn tracestate(&self) -> Option<String> {
+        join(self.headers.get_all(TRACESTATE))
+    }
+
+    /// The `baggage` of the call, with the values of several `baggage` metadata joined.
+    pub fn baggage(&self) -> Option<String> {
+        join(self.headers.get_all(BAGGAGE))
+    }
+
+    /// Whether the call has no trace context.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    /// The trace context current in this task, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT.with(|current| current.borrow().clone())
+    }
+
+    /// Make this trace context current while `fut` runs.
+    pub fn scope<F: Future>(self, fut: F) -> Scoped<F> {
+        Scoped {
+            inner: fut,
+            context: Some(self),
+        }
+    }
+
+    /// Make this trace context current while `f` runs.
+    pub fn sync_scope<R>(self, f: impl FnOnce() -> R) -> R {
+        enter(&mut Some(self), f)
+    }
+}
+
+// Whether the request has trace context metadata of its own.
+fn is_traced(headers: &HeaderMap) -> bool {
+    [TRACEPARENT, TRACESTATE, BAGGAGE, GRPC_TRACE_BIN]
+        .iter()
+        .any(|key| headers.contains_key(key))
+}
+
+// A `traceparent` of version `00`, or of a later version with at least its fields.
+fn is_traceparent(value: &[u8]) -> bool {
+    let parts: Vec<&[u8]> = value.split(|b| *b == b'-').collect();
+    let [version, trace_id, parent_id, flags, rest @ ..] = parts.as_slice() else {
+        return false;
+    };
+    let hex = |part: &[u8], len: usize| {
+        part.len() == len && part.iter().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+    };
+    // The trace and parent IDs must not be all zeroes.
+    let non_zero = |part: &[u8]| part.iter().any(|b| *b != b'0');
+
+    hex(version, 2)
+        && *version != b"ff"
+        && (rest.is_empty() || *version != b"00")
+        && hex(trace_id, 32)
+        && non_zero(trace_id)
+        && hex(parent_id, 16)
+        && non_zero(parent_id)
+        && hex(flags, 2)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(hex: &[u8]) -> Option<Vec<u8>> {
+    let digit = |b: &u8| (*b as char).to_digit(16).map(|digit| digit as u8);
+    hex.chunks(2)
+        .map(|pair| match pair {
+            [high, low] => Some((digit(high)? << 4) | digit(low)?),
+            _ => None,
+        })
+        .collect()
+}
+
+fn join<'a>(values: impl IntoIterator<Item = &'a http::HeaderValue>) -> Option<String> {
+    let values: Vec<_> = values
+        .into_iter()
+        .filter_map(|value| value.to_str().ok())
+        .collect();
+    (!values.is_empty()).then(|| values.join(","))
+}
+
+// Runs `f` with the trace context of `slot` current, storing it back afterwards.
+fn enter<R>(slot: &mut Option<TraceContext>, f: impl FnOnce() -> R) -> R {
+    struct Reset<'a> {
+        slot: &'a mut Option<TraceContext>,
+        previous: Option<TraceContext>,
+    }
+
+    impl Drop for Reset<'_> {
+        fn drop(&mut self) {
+            let previous = self.previous.take();
+            *self.slot = CURRENT.with(|current| current.replace(previous));
+        }
+    }
+
+    let previous = CURRENT.with(|current| current.replace(slot.take()));
+    let _reset = Reset { slot, previous };
+    f()
+}
+
+/// Future with a trace context current while it runs, returned by [`TraceContext::scope`].
+#[pin_project]
+#[derive(Debug)]
+pub struct Scoped<F> {
+    #[pin]
+    inner: F,
+    context: Option<TraceContext>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let inner = this.inner;
+        enter(this.context, || inner.poll(cx))
+    }
+}
+
+/// Layer which applies the [`ServerTraceContext`] middleware.
+#[derive(Debug, Clone, Default)]
+pub struct ServerTraceContextLayer {
+    _priv: (),
+}
+
+impl ServerTraceContextLayer {
+    /// Create a new `ServerTraceContextLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for ServerTraceContextLayer {
+    type Service = ServerTraceContext<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ServerTraceContext { inner }
+    }
+}
+
+/// Middleware making the trace context of the requests of a server current while they are
+/// served.
+#[derive(Debug, Clone)]
+pub struct ServerTraceContext<S> {
+    inner: S,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for ServerTraceContext<S>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Scoped<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        let context = TraceContext::from_headers(req.headers());
+        req.extensions_mut().insert(context.clone());
+        // The handler may be called by the inner service already.
+        let mut slot = Some(context);
+        let fut = enter(&mut slot, || self.inner.call(req));
+        Scoped {
+            inner: fut,
+            context: slot,
+        }
+    }
+}
+
+/// Layer which applies the [`ClientTraceContext`] middleware.
+#[derive(Debug, Clone, Default)]
+pub struct ClientTraceContextLayer {
+    grpc_trace_bin: bool,
+}
+
+impl ClientTraceContextLayer {
+    /// Create a new `ClientTraceContextLayer`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also set the binary `grpc-trace-bin` metadata of the requests, for servers only reading
+    /// it. Defaults to `false`.
+    pub fn grpc_trace_bin(self, enabled: bool) -> Self {
+        Self {
+            grpc_trace_bin: enabled,
+        }
+    }
+}
+
+impl<S> Layer<S> for ClientTraceContextLayer {
+    type Service = ClientTraceContext<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ClientTraceContext {
+            inner,
+            grpc_trace_bin: self.grpc_trace_bin,
+        }
+    }
+}
+
+/// Middleware setting the trace context metadata of the requests of a client.
+///
+/// Requests with trace context metadata of their own are sent unchanged. The others get the
+/// [`TraceContext`] of their extensions, or else the current one.
+#[derive(Debug, Clone)]
+pub struct ClientTraceContext<S> {
+    inner: S,
+    grpc_trace_bin: bool,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for ClientTraceContext<S>
+where
+    S: Service<http::Request<ReqBody>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: http::Request<ReqBody>) -> Self::Future {
+        if !is_traced(req.headers()) {
+            let context = req
+                .extensions()
+                .get::<TraceContext>()
+                .cloned()
+                .or_else(TraceContext::current);
+            if let Some(context) = context {
+                context.inject_headers(req.headers_mut(), self.grpc_trace_bin);
+            }
+        }
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+    fn metadata(entries: &[(&'static str, &'static str)]) -> MetadataMap {
+        let mut metadata = MetadataMap::new();
+        for (key, value) in entries {
+            metadata.append(*key, value.parse().unwrap());
+        }
+        metadata
+    }
+
+    #[test]
+    fn extracts_valid_trace_contexts() {
+        let context = TraceContext::extract(&metadata(&[
+            ("traceparent", PARENT),
+            ("tracestate", "a=1"),
+            ("tracestate", "b=2"),
+            ("baggage", "user=alice"),
+        ]));
+        assert_eq!(context.traceparent(), Some(PARENT));
+        assert_eq!(context.tracestate().as_deref(), Some("a=1,b=2"));
+        assert_eq!(context.baggage().as_deref(), Some("user=alice"));
+    }
+
+    #[test]
+    fn ignores_invalid_traceparents() {
+        let invalid = [
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331",
+            "00-0AF7651916CD43DD8448EB211C80319C-b7ad6b7169203331-01",
+            "00-00000000000000000000000000000000-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-0000000000000000-01",
+            "ff-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01",
+            "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-extra",
+        ];
+        for traceparent in invalid {
+            let mut metadata = metadata(&[("tracestate", "a=1"), ("baggage", "user=alice")]);
+            metadata.insert("traceparent", traceparent.parse().unwrap());
+            let context = TraceContext::extract(&metadata);
+            assert_eq!(context.traceparent(), None, "{traceparent}");
+            assert_eq!(context.tracestate(), None);
+            assert_eq!(context.baggage().as_deref(), Some("user=alice"));
+        }
+
+        let later = "01-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01-extra";
+        let context = TraceContext::extract(&metadata(&[("traceparent", later)]));
+        assert_eq!(context.traceparent(), Some(later));
+    }
+
+    #[test]
+    fn keeps_the_trace_context_of_requests() {
+        let context = TraceContext::extract(&metadata(&[("traceparent", PARENT)]));
+        let mut traced = metadata(&[("baggage", "user=bob")]);
+        context.inject(&mut traced);
+        assert_eq!(traced.len(), 1);
+
+        let mut untraced = metadata(&[("x-other", "1")]);
+        context.inject(&mut untraced);
+        assert_eq!(untraced.get("traceparent").unwrap(), PARENT);
+    }
+
+    #[test]
+    fn converts_grpc_trace_bin() {
+        let context = TraceContext::extract(&metadata(&[("traceparent", PARENT)]));
+        let bytes = context.grpc_trace_bin().unwrap();
+        assert_eq!(bytes.len(), 29);
+
+        let mut binary = MetadataMap::new();
+        binary.insert_bin("grpc-trace-bin", MetadataValue::from_bytes(&bytes));
+        assert_eq!(TraceContext::extract(&binary).traceparent(), Some(PARENT));
+
+        // Unknown fields are ignored, and the other trace options are not sampling.
+        let mut extended = bytes.clone();
+        extended[28] = 0xff;
+        extended.extend([3, 1, 2, 3]);
+        assert_eq!(
+            TraceContext::from_grpc_trace_bin(&extended)
+                .unwrap()
+                .traceparent(),
+            Some(PARENT)
+        );
+
+        let mut unsampled = bytes.clone();
+        unsampled[28] = 0;
+        let traceparent = TraceContext::from_grpc_trace_bin(&unsampled).unwrap();
+        assert!(traceparent.traceparent().unwrap().ends_with("-00"));
+
+        for invalid in [&bytes[..20], &[1][..], &[0, 1, 1, 1, 1, 1, 1, 1, 1, 1][..]] {
+            assert_eq!(TraceContext::from_grpc_trace_bin(invalid), None);
+        }
+    }
+
+    #[test]
+    fn injects_grpc_trace_bin_when_asked() {
+        let context = TraceContext::extract(&metadata(&[("traceparent", PARENT)]));
+        let mut headers = HeaderMap::new();
+        context.inject_headers(&mut headers, false);
+        assert!(!headers.contains_key(GRPC_TRACE_BIN));
+
+        let mut headers = HeaderMap::new();
+        context.inject_headers(&mut headers, true);
+        let binary = MetadataMap::from_headers(headers);
+        let bytes = binary
+            .get_bin("grpc-trace-bin")
+            .unwrap()
+            .to_bytes()
+            .unwrap();
+        assert_eq!(bytes, context.grpc_trace_bin().unwrap());
+    }
+
+    #[test]
+    fn prefers_the_w3c_trace_context() {
+        let mut bytes = vec![0, 0];
+        bytes.extend([1; 16]);
+        bytes.push(1);
+        bytes.extend([2; 8]);
+        let mut both = metadata(&[("traceparent", PARENT), ("tracestate", "a=1")]);
+        both.insert_bin("grpc-trace-bin", MetadataValue::from_bytes(&bytes));
+        let context = TraceContext::extract(&both);
+        assert_eq!(context.traceparent(), Some(PARENT));
+        assert_eq!(context.tracestate().as_deref(), Some("a=1"));
+    }
+
+    #[tokio::test]
+    async fn scopes_make_contexts_current() {
+        let context = TraceContext::extract(&metadata(&[("traceparent", PARENT)]));
+        assert_eq!(TraceContext::current(), None);
+
+        let current = context
+            .clone()
+            .scope(async {
+                tokio::task::yield_now().await;
+                TraceContext::current()
+            })
+            .await;
+        assert_eq!(current.as_ref(), Some(&context));
+        assert_eq!(TraceContext::current(), None);
+
+        let nested =
+            context.sync_scope(|| TraceContext::default().sync_scope(TraceContext::current));
+        assert_eq!(nested, Some(TraceContext::default()));
+    }
+}
@@ -462,31 +462,20 @@ impl Status {
 
     /// Extract a `Status` from a hyper `HeaderMap`.
     pub fn from_header_map(header_map: &HeaderMap) -> Option<Status> {
-        let code = Code::from_bytes(header_map.get(Self::GRPC_STATUS)?.as_ref());
+        let keys = &StatusKeys::GRPC;
+        let code = Code::from_bytes(header_map.get(&keys.code)?.as_ref());
 
-        let error_message = match header_map.get(Self::GRPC_MESSAGE) {
-            Some(header) => percent_decode(header.as_bytes())
-                .decode_utf8()
-                .map(|cow| cow.to_string()),
+        let error_message = match header_map.get(&keys.message) {
+            Some(header) => decode_message(header),
             None => Ok(String::new()),
         };
 
-        let details = match header_map.get(Self::GRPC_STATUS_DETAILS) {
-            Some(header) => crate::util::base64::STANDARD
-                .decode(header.as_bytes())
-                .expect("Invalid status header, expected base64 encoded value")
-                .into(),
+        let details = match header_map.get(&keys.details) {
+            Some(header) => decode_details(header)
+                .expect("Invalid status header, expected base64 encoded value"),
             None => Bytes::new(),
         };
 
-        let other_headers = {
-            let mut header_map = header_map.clone();
-            header_map.remove(Self::GRPC_STATUS);
-            header_map.remove(Self::GRPC_MESSAGE);
-            header_map.remove(Self::GRPC_STATUS_DETAILS);
-            header_map
-        };
-
         let (code, message) = match error_message {
             Ok(message) => (code, message),
             Err(e) => {
@@ -496,16 +485,83 @@ impl Status {
             }
         };
 
-        Some(
-            StatusInner {
-                code,
-                message,
-                details,
-                metadata: MetadataMap::from_headers(other_headers),
-                source: None,
-            }
-            .into_status(),
-        )
+        Some(Self::from_parts(code, message, details, header_map, keys))
+    }
+
+    /// Parse a `Status` from the headers of `header_map` named by `keys`, the other headers
+    /// becoming its metadata, as proxies and test tools handling the trailers of calls do.
+    ///
+    /// Returns `Ok(None)` without a code header, and fails with `Code::Internal` when the message
+    /// isn't percent-encoded UTF-8 or the details aren't base64 encoded.
+    ///
+    /// ```
+    /// # use tonic::{Code, Status, StatusKeys};
+    /// let keys = StatusKeys::new(
+    ///     "x-status".parse().unwrap(),
+    ///     "x-status-message".parse().unwrap(),
+    ///     "x-status-details-bin".parse().unwrap(),
+    /// );
+    ///
+    /// let trailers = Status::not_found("no user alice")
+    ///     .to_header_map_with_keys(&keys)
+    ///     .unwrap();
+    /// assert_eq!(trailers["x-status"], "5");
+    ///
+    /// let status = Status::parse_header_map(&trailers, &keys).unwrap().unwrap();
+    /// assert_eq!(status.code(), Code::NotFound);
+    /// assert_eq!(status.message(), "no user alice");
+    /// ```
+    pub fn parse_header_map(
+        header_map: &HeaderMap,
+        keys: &StatusKeys,
+    ) -> Result<Option<Status>, Status> {
+        let Some(code) = header_map.get(&keys.code) else {
+            return Ok(None);
+        };
+        let code = Code::from_bytes(code.as_ref());
+
+        let message = match header_map.get(&keys.message) {
+            Some(header) => decode_message(header).map_err(|err| {
+                Status::internal(format!("invalid {} header: {err}", keys.message))
+            })?,
+            None => String::new(),
+        };
+
+        let details = match header_map.get(&keys.details) {
+            Some(header) => decode_details(header).map_err(|err| {
+                Status::internal(format!("invalid {} header: {err}", keys.details))
+            })?,
+            None => Bytes::new(),
+        };
+
+        Ok(Some(Self::from_parts(
+            code, message, details, header_map, keys,
+        )))
+    }
+
+    fn from_parts(
+        code: Code,
+        message: String,
+        details: Bytes,
+        header_map: &HeaderMap,
+        keys: &StatusKeys,
+    ) -> Status {
+        let other_headers = {
+            let mut header_map = header_map.clone();
+            header_map.remove(&keys.code);
+            header_map.remove(&keys.message);
+            header_map.remove(&keys.details);
+            header_map
+        };
+
+        StatusInner {
+            code,
+            message,
+            details,
+            metadata: MetadataMap::from_headers(other_headers),
+            source: None,
+        }
+        .into_status()
     }
 
     /// Get the gRPC `Code` of this `Status`.
@@ -533,17 +589,48 @@ impl Status {
         &mut self.0.metadata
     }
 
-    pub(crate) fn to_header_map(&self) -> Result<HeaderMap, Self> {
+    /// Get the HTTP/2 error this `Status` was inferred from, when the stream of its call was
+    /// reset with a `RST_STREAM`, or its connection closed with a `GOAWAY`.
+    #[cfg(any(feature = "server", feature = "channel"))]
+    pub fn http2_error(&self) -> Option<Http2Error> {
+        let mut source = Error::source(self);
+        while let Some(err) = source {
+            if let Some(h2) = err.downcast_ref::<h2::Error>() {
+                return Http2Error::from_h2(h2);
+            }
+            source = err.source();
+        }
+        None
+    }
+
+    /// Serialize this `Status` into a new `HeaderMap`, as the trailers of a gRPC response.
+    pub fn to_header_map(&self) -> Result<HeaderMap, Self> {
+        self.to_header_map_with_keys(&StatusKeys::GRPC)
+    }
+
+    /// Serialize this `Status` into a new `HeaderMap`, with its fields in the headers named by
+    /// `keys`.
+    pub fn to_header_map_with_keys(&self, keys: &StatusKeys) -> Result<HeaderMap, Self> {
         let mut header_map = HeaderMap::with_capacity(3 + self.0.metadata.len());
-        self.add_header(&mut header_map)?;
+        self.add_header_with_keys(&mut header_map, keys)?;
         Ok(header_map)
     }
 
     /// Add headers from this `Status` into `header_map`.
     pub fn add_header(&self, header_map: &mut HeaderMap) -> Result<(), Self> {
+        self.add_header_with_keys(header_map, &StatusKeys::GRPC)
+    }
+
+    /// Add headers from this `Status` into `header_map`, with its fields in the headers named by
+    /// `keys`.
+    pub fn add_header_with_keys(
+        &self,
+        header_map: &mut HeaderMap,
+        keys: &StatusKeys,
+    ) -> Result<(), Self> {
         header_map.extend(self.0.metadata.clone().into_sanitized_headers());
 
-        header_map.insert(Self::GRPC_STATUS, self.0.code.to_header_value());
+        header_map.insert(keys.code.clone(), self.0.code.to_header_value());
 
         if !self.0.message.is_empty() {
             let to_write = Bytes::copy_from_slice(
@@ -551,7 +638,7 @@ impl Status {
             );
 
             header_map.insert(
-                Self::GRPC_MESSAGE,
+                keys.message.clone(),
                 HeaderValue::from_maybe_shared(to_write).map_err(invalid_header_value_byte)?,
             );
         }
@@ -560,7 +647,7 @@ impl Status {
             let details = crate::util::base64::STANDARD_NO_PAD.encode(&self.0.details[..]);
 
             header_map.insert(
-                Self::GRPC_STATUS_DETAILS,
+                keys.details.clone(),
                 HeaderValue::from_maybe_shared(details).map_err(invalid_header_value_byte)?,
             );
         }
@@ -620,6 +707,69 @@ impl Status {
     pub const GRPC_STATUS_DETAILS: HeaderName = HeaderName::from_static("grpc-status-details-bin");
 }
 
+/// The names of the headers carrying the code, message and details of a [`Status`] on the wire.
+///
+/// gRPC uses `grpc-status`, `grpc-message` and `grpc-status-details-bin`, but proxies and test
+/// tools relaying statuses through other protocols may carry them in other headers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatusKeys {
+    code: HeaderName,
+    message: HeaderName,
+    details: HeaderName,
+}
+
+impl StatusKeys {
+    /// The headers of gRPC.
+    pub const GRPC: StatusKeys = StatusKeys {
+        code: Status::GRPC_STATUS,
+        message: Status::GRPC_MESSAGE,
+        details: Status::GRPC_STATUS_DETAILS,
+    };
+
+    /// Create `StatusKeys` carrying the code, message and base64 encoded details of statuses in
+    /// the given headers.
+    pub const fn new(code: HeaderName, message: HeaderName, details: HeaderName) -> Self {
+        Self {
+            code,
+            message,
+            details,
+        }
+    }
+
+    /// The name of the header carrying the code.
+    pub fn code(&self) -> &HeaderName {
+        &self.code
+    }
+
+    /// The name of the header carrying the percent-encoded message.
+    pub fn message(&self) -> &HeaderName {
+        &self.message
+    }
+
+    /// The name of the header carrying the base64 encoded details.
+    pub fn details(&self) -> &HeaderName {
+        &self.details
+    }
+}
+
+impl Default for StatusKeys {
+    fn default() -> Self {
+        Self::GRPC
+    }
+}
+
+fn decode_message(header: &HeaderValue) -> Result<String, std::str::Utf8Error> {
+    percent_decode(header.as_bytes())
+        .decode_utf8()
+        .map(|cow| cow.to_string())
+}
+
+fn decode_details(header: &HeaderValue) -> Result<Bytes, base64::DecodeError> {
+    crate::util::base64::STANDARD
+        .decode(header.as_bytes())
+        .map(Bytes::from)
+}
+
 fn find_status_in_source_chain(err: &(dyn Error + 'static)) -> Option<Status> {
     let mut source = Some(err);
 
@@ -788,6 +938,9 @@ pub(crate) fn infer_grpc_status(
         http::StatusCode::UNAUTHORIZED => Code::Unauthenticated,
         http::StatusCode::FORBIDDEN => Code::PermissionDenied,
         http::StatusCode::NOT_FOUND => Code::Unimplemented,
+        // Not part of the mapping, sent by servers rejecting request headers over their max
+        // header list size.
+        http::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE => Code::ResourceExhausted,
         http::StatusCode::TOO_MANY_REQUESTS
         | http::StatusCode::BAD_GATEWAY
         | http::StatusCode::SERVICE_UNAVAILABLE
@@ -977,6 +1130,15 @@ mod tests {
         assert_eq!(source.reason(), Some(h2::Reason::CANCEL));
     }
 
+    #[test]
+    fn infers_resource_exhausted_from_request_headers_too_large() {
+        let status = infer_grpc_status(None, http::StatusCode::REQUEST_HEADER_FIELDS_TOO_LARGE)
+            .unwrap_err()
+            .unwrap();
+
+        assert_eq!(status.code(), Code::ResourceExhausted);
+    }
+
     #[test]
     #[cfg(feature = "server")]
     fn to_h2_error() {
@@ -1047,6 +1209,63 @@ mod tests {
 
         assert_eq!(status.details(), DETAILS);
     }
+
+    #[test]
+    fn custom_keys() {
+        let keys = StatusKeys::new(
+            HeaderName::from_static("x-status"),
+            HeaderName::from_static("x-status-message"),
+            HeaderName::from_static("x-status-details-bin"),
+        );
+
+        let mut metadata = MetadataMap::new();
+        metadata.insert("x-request-id", "42".parse().unwrap());
+        let status = Status::with_details_and_metadata(
+            Code::Aborted,
+            "retry 100% later",
+            Bytes::from_static(&[1, 2]),
+            metadata,
+        );
+
+        let header_map = status.to_header_map_with_keys(&keys).unwrap();
+        assert!(header_map.get(Status::GRPC_STATUS).is_none());
+        assert_eq!(header_map["x-status"], "10");
+        assert_eq!(header_map["x-status-message"], "retry%20100%25%20later");
+
+        let parsed = Status::parse_header_map(&header_map, &keys)
+            .unwrap()
+            .unwrap();
+        assert_eq!(parsed.code(), Code::Aborted);
+        assert_eq!(parsed.message(), "retry 100% later");
+        assert_eq!(parsed.details(), &[1, 2]);
+        assert_eq!(parsed.metadata().len(), 1);
+        assert_eq!(parsed.metadata().get("x-request-id").unwrap(), "42");
+
+        assert!(Status::parse_header_map(&header_map, &StatusKeys::GRPC)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn parse_invalid_header_map() {
+        let mut header_map = HeaderMap::new();
+        header_map.insert(Status::GRPC_STATUS, HeaderValue::from_static("5"));
+        header_map.insert(
+            Status::GRPC_STATUS_DETAILS,
+            HeaderValue::from_static("not base64!"),
+        );
+
+        let err = Status::parse_header_map(&header_map, &StatusKeys::default()).unwrap_err();
+        assert_eq!(err.code(), Code::Internal);
+        assert!(err
+            .message()
+            .starts_with("invalid grpc-status-details-bin header"));
+
+        header_map.remove(Status::GRPC_STATUS_DETAILS);
+        header_map.insert(Status::GRPC_MESSAGE, HeaderValue::from_static("%FF"));
+        let err = Status::parse_header_map(&header_map, &StatusKeys::default()).unwrap_err();
+        assert!(err.message().starts_with("invalid grpc-message header"));
+    }
 }
 
 /// Error returned if a request didn't complete within the configured timeout.
@@ -1085,3 +1304,57 @@ impl std::error::Error for ConnectError {
         Some(self.0.as_ref())
     }
 }
+
+/// An HTTP/2 error closing a call: a `RST_STREAM` resetting its stream, or a `GOAWAY` closing its
+/// connection.
+///
+/// Available from the statuses of the calls, see [`Status::http2_error`], and from the
+/// [`ConnectionEvent::GoAway`] events of channels.
+///
+/// [`ConnectionEvent::GoAway`]: crate::transport::channel::ConnectionEvent::GoAway
+#[cfg(any(feature = "server", feature = "channel"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Http2Error {
+    code: u32,
+    go_away: bool,
+    remote: bool,
+}
+
+#[cfg(any(feature = "server", feature = "channel"))]
+impl Http2Error {
+    pub(crate) fn from_h2(err: &h2::Error) -> Option<Self> {
+        if !err.is_go_away() && !err.is_reset() {
+            return None;
+        }
+        Some(Self {
+            code: err.reason()?.into(),
+            go_away: err.is_go_away(),
+            remote: err.is_remote(),
+        })
+    }
+
+    /// The HTTP/2 error code, e.g. `0x2` for `INTERNAL_ERROR`.
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// Whether the error is a `GOAWAY` closing the connection, rather than a `RST_STREAM`
+    /// resetting a stream.
+    pub fn is_go_away(&self) -> bool {
+        self.go_away
+    }
+
+    /// Whether the error was sent by the peer, rather than by this end of the connection.
+    pub fn is_remote(&self) -> bool {
+        self.remote
+    }
+}
+
+#[cfg(any(feature = "server", feature = "channel"))]
+impl fmt::Display for Http2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frame = if self.go_away { "GOAWAY" } else { "RST_STREAM" };
+        let origin = if self.remote { "received" } else { "sent" };
+        write!(f, "{frame} {origin} with {:?}", h2::Reason::from(self.code))
+    }
+}
@@ -0,0 +1,209 @@
+//! Assertions on the statuses of calls, for tests.
+//!
+//! [`assert_status!`] asserts that a call failed with a code, and that its status matches
+//! [`StatusMatcher`]s, e.g. on its message or metadata. Failed assertions print the code, message,
+//! metadata and details of the status, along with every mismatch:
+//!
+//! ```should_panic
+//! use tonic::{assert_status, test::{has_metadata, message_contains}, Code, Status};
+//!
+//! let result: Result<(), Status> = Err(Status::not_found("no user alice"));
+//! assert_status!(result, Code::NotFound, message_contains("alice"));
+//!
+//! // Panics with:
+//! //
+//! // status mismatch:
+//! //   code: NotFound
+//! //   message: "no user alice"
+//! //   metadata: {}
+//! //   mismatches:
+//! //     - metadata "x-retry" is missing, expected "false"
+//! assert_status!(result, Code::NotFound, has_metadata("x-retry", "false"));
+//! ```
+//!
+//! Other crates provide their own matchers, such as the matchers of the details of statuses of
+//! `tonic-types`.
+
+use std::fmt::{self, Write as _};
+
+use crate::{Code, Status};
+
+pub use crate::assert_status;
+
+/// Checks that a status matches an expectation.
+///
+/// Implemented for the closures returning the description of the mismatch of a status, if any.
+pub trait StatusMatcher {
+    /// Check `status`, returning the description of its mismatch when it doesn't match.
+    fn check(&self, status: &Status) -> Result<(), String>;
+}
+
+impl<F> StatusMatcher for F
+where
+    F: Fn(&Status) -> Result<(), String>,
+{
+    fn check(&self, status: &Status) -> Result<(), String> {
+        self(status)
+    }
+}
+
+/// The outcome of a call checked by [`assert_status!`], failed with a status or not.
+pub trait Outcome {
+    /// The status the call failed with, or the description of the outcome of the call when it
+    /// didn't fail.
+    fn status(&self) -> Result<&Status, String>;
+}
+
+impl Outcome for Status {
+    fn status(&self) -> Result<&Status, String> {
+        Ok(self)
+    }
+}
+
+impl<T: fmt::Debug> Outcome for Result<T, Status> {
+    fn status(&self) -> Result<&Status, String> {
+        match self {
+            Ok(value) => Err(format!("expected an error status, got Ok({value:?})")),
+            Err(status) => Ok(status),
+        }
+    }
+}
+
+impl<O: Outcome + ?Sized> Outcome for &O {
+    fn status(&self) -> Result<&Status, String> {
+        (**self).status()
+    }
+}
+
+/// Matches the statuses with a message containing `text`.
+pub fn message_contains(text: impl Into<String>) -> impl StatusMatcher {
+    let text = text.into();
+    move |status: &Status| {
+        if status.message().contains(&text) {
+            Ok(())
+        } else {
+            Err(format!("message doesn't contain {text:?}"))
+        }
+    }
+}
+
+/// Matches the statuses with the message `message`.
+pub fn message_eq(message: impl Into<String>) -> impl StatusMatcher {
+    let message = message.into();
+    move |status: &Status| {
+        if status.message() == message {
+            Ok(())
+        } else {
+            Err(format!("message isn't {message:?}"))
+        }
+    }
+}
+
+/// Matches the statuses with the ASCII metadata `value` for `key`.
+pub fn has_metadata(key: impl Into<String>, value: impl Into<String>) -> impl StatusMatcher {
+    let (key, value) = (key.into(), value.into());
+    move |status: &Status| match status.metadata().get(key.as_str()) {
+        Some(actual) if actual.to_str().ok() == Some(value.as_str()) => Ok(()),
+        Some(actual) => Err(format!(
+            "metadata {key:?} is {actual:?}, expected {value:?}"
+        )),
+        None => Err(format!("metadata {key:?} is missing, expected {value:?}")),
+    }
+}
+
+/// Check that `outcome` failed with `code`, and that its status matches `matchers`.
+///
+/// Returns the report of the mismatches otherwise, see [`assert_status!`].
+pub fn check_status(
+    outcome: &impl Outcome,
+    code: Code,
+    matchers: &[&dyn StatusMatcher],
+) -> Result<(), String> {
+    let status = outcome
+        .status()
+        .map_err(|outcome| format!("status mismatch:\n  {outcome}"))?;
+
+    let mut mismatches = Vec::new();
+    if status.code() != code {
+        mismatches.push(format!("code is {:?}, expected {code:?}", status.code()));
+    }
+    mismatches.extend(
+        matchers
+            .iter()
+            .filter_map(|matcher| matcher.check(status).err()),
+    );
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+
+    let mut report = String::from("status mismatch:\n");
+    let _ = writeln!(report, "  code: {:?}", status.code());
+    let _ = writeln!(report, "  message: {:?}", status.message());
+    let _ = writeln!(
+        report,
+        "  metadata: {:?}",
+        status.metadata().clone().into_headers()
+    );
+    if !status.details().is_empty() {
+        let _ = writeln!(report, "  details: {} bytes", status.details().len());
+    }
+    report.push_str("  mismatches:");
+    for mismatch in mismatches {
+        // Indent the lines of multi-line mismatches under their item.
+        let _ = write!(report, "\n    - {}", mismatch.replace('\n', "\n      "));
+    }
+    Err(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failed() -> Result<(), Status> {
+        let mut status = Status::not_found("no user alice");
+        status
+            .metadata_mut()
+            .insert("x-retry", "false".parse().unwrap());
+        Err(status)
+    }
+
+    #[test]
+    fn matches() {
+        assert_status!(failed(), Code::NotFound);
+        assert_status!(
+            failed(),
+            Code::NotFound,
+            message_contains("alice"),
+            message_eq("no user alice"),
+            has_metadata("x-retry", "false"),
+        );
+        assert_status!(failed().unwrap_err(), Code::NotFound);
+    }
+
+    #[test]
+    fn reports_mismatches() {
+        let report = check_status(
+            &failed(),
+            Code::Internal,
+            &[&message_contains("bob"), &has_metadata("x-retry", "true")],
+        )
+        .unwrap_err();
+        assert_eq!(
+            report,
+            "status mismatch:\n  \
+             code: NotFound\n  \
+             message: \"no user alice\"\n  \
+             metadata: {\"x-retry\": \"false\"}\n  \
+             mismatches:\n    \
+             - code is NotFound, expected Internal\n    \
+             - message doesn't contain \"bob\"\n    \
+             - metadata \"x-retry\" is \"false\", expected \"true\""
+        );
+
+        let report = check_status(&Ok::<_, Status>(1), Code::NotFound, &[]).unwrap_err();
+        assert_eq!(
+            report,
+            "status mismatch:\n  expected an error status, got Ok(1)"
+        );
+    }
+}
@@ -1,9 +1,9 @@
 use std::{fmt, future::Future, net::IpAddr, pin::Pin, str, str::FromStr, time::Duration};
 
 use bytes::Bytes;
-use http::uri::Uri;
 #[cfg(feature = "user-agent")]
 use http::HeaderValue;
+use http::{uri::Uri, HeaderMap};
 use hyper::rt;
 use hyper_util::client::legacy::connect::HttpConnector;
 use tower_service::Service;
@@ -13,13 +13,20 @@ use super::service::TlsConnector;
 #[cfg(feature = "_tls-any")]
 use super::ClientTlsConfig;
 use super::{
-    service::{self, Executor, SharedExec},
+    service::{
+        self, ConnectionEvent, ConnectionEvents, Executor, Locality, Metrics, MetricsSink,
+        SharedExec,
+    },
     uds_connector::UdsConnector,
     Channel,
 };
 #[cfg(feature = "_tls-any")]
 use crate::transport::error;
-use crate::transport::Error;
+use crate::{
+    metadata::{MetadataKey, MetadataValue, ValueEncoding},
+    service::CircuitBreakerLayer,
+    transport::{service::SharedTimer, Error},
+};
 
 #[derive(Clone, PartialEq, Eq, Hash)]
 pub(crate) enum EndpointType {
@@ -37,9 +44,15 @@ pub struct Endpoint {
     pub(crate) origin: Option<Uri>,
     #[cfg(feature = "user-agent")]
     pub(crate) user_agent: Option<HeaderValue>,
+    pub(crate) static_metadata: HeaderMap,
     pub(crate) timeout: Option<Duration>,
     pub(crate) concurrency_limit: Option<usize>,
     pub(crate) rate_limit: Option<(u64, Duration)>,
+    pub(crate) circuit_breaker: Option<CircuitBreakerLayer>,
+    pub(crate) locality: Option<Locality>,
+    pub(crate) group: Option<String>,
+    pub(crate) connection_events: Option<ConnectionEvents>,
+    pub(crate) metrics: Option<Metrics>,
     #[cfg(feature = "_tls-any")]
     pub(crate) tls: Option<TlsConnector>,
     pub(crate) buffer_size: Option<usize>,
@@ -53,10 +66,14 @@ pub struct Endpoint {
     pub(crate) http2_keep_alive_timeout: Option<Duration>,
     pub(crate) http2_keep_alive_while_idle: Option<bool>,
     pub(crate) http2_max_header_list_size: Option<u32>,
+    pub(crate) http2_max_send_buf_size: Option<usize>,
+    pub(crate) max_frame_size: Option<u32>,
     pub(crate) connect_timeout: Option<Duration>,
     pub(crate) http2_adaptive_window: Option<bool>,
+    pub(crate) http1_only: bool,
     pub(crate) local_address: Option<IpAddr>,
     pub(crate) executor: SharedExec,
+    pub(crate) timer: SharedTimer,
 }
 
 impl Endpoint {
@@ -85,8 +102,14 @@ impl Endpoint {
             origin: None,
             #[cfg(feature = "user-agent")]
             user_agent: None,
+            static_metadata: HeaderMap::new(),
             concurrency_limit: None,
             rate_limit: None,
+            circuit_breaker: None,
+            locality: None,
+            group: None,
+            connection_events: None,
+            metrics: None,
             timeout: None,
             #[cfg(feature = "_tls-any")]
             tls: None,
@@ -101,9 +124,13 @@ impl Endpoint {
             http2_keep_alive_timeout: None,
             http2_keep_alive_while_idle: None,
             http2_max_header_list_size: None,
+            http2_max_send_buf_size: None,
+            max_frame_size: None,
             connect_timeout: None,
             http2_adaptive_window: None,
+            http1_only: false,
             executor: SharedExec::tokio(),
+            timer: SharedTimer::tokio(),
             local_address: None,
         }
     }
@@ -115,8 +142,14 @@ impl Endpoint {
             origin: None,
             #[cfg(feature = "user-agent")]
             user_agent: None,
+            static_metadata: HeaderMap::new(),
             concurrency_limit: None,
             rate_limit: None,
+            circuit_breaker: None,
+            locality: None,
+            group: None,
+            connection_events: None,
+            metrics: None,
             timeout: None,
             #[cfg(feature = "_tls-any")]
             tls: None,
@@ -131,9 +164,13 @@ impl Endpoint {
             http2_keep_alive_timeout: None,
             http2_keep_alive_while_idle: None,
             http2_max_header_list_size: None,
+            http2_max_send_buf_size: None,
+            max_frame_size: None,
             connect_timeout: None,
             http2_adaptive_window: None,
+            http1_only: false,
             executor: SharedExec::tokio(),
+            timer: SharedTimer::tokio(),
             local_address: None,
         }
     }
@@ -208,6 +245,36 @@ impl Endpoint {
             .map_err(|_| Error::new_invalid_user_agent())
     }
 
+    /// Set metadata on every request of the channels connected to this
+    /// endpoint, such as an API key.
+    ///
+    /// The metadata is validated once here instead of on each request, and
+    /// its values are shared by the requests. Sent with every request of a
+    /// connection, it is compressed by HTTP/2 into references to the earlier
+    /// requests, unless marked [sensitive](MetadataValue::set_sensitive).
+    /// Metadata set on a request replaces the static metadata of its key;
+    /// calling this again with the same key adds a value.
+    ///
+    /// ```
+    /// # use tonic::{
+    /// #     metadata::{AsciiMetadataKey, MetadataValue},
+    /// #     transport::Endpoint,
+    /// # };
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.static_metadata(
+    ///     AsciiMetadataKey::from_static("x-api-key"),
+    ///     MetadataValue::from_static("secret"),
+    /// );
+    /// ```
+    pub fn static_metadata<VE: ValueEncoding>(
+        mut self,
+        key: MetadataKey<VE>,
+        value: MetadataValue<VE>,
+    ) -> Self {
+        self.static_metadata.append(key.inner, value.inner);
+        self
+    }
+
     /// Set a custom origin.
     ///
     /// Override the `origin`, mainly useful when you are reaching a Server/LoadBalancer
@@ -335,9 +402,103 @@ impl Endpoint {
         }
     }
 
+    /// Apply a circuit breaker to the requests of each channel connected to
+    /// this endpoint.
+    ///
+    /// Requests fail fast with `Unavailable` while the breaker is open. When
+    /// the endpoint is balanced with others, the balancer avoids it while its
+    /// breaker is open.
+    ///
+    /// ```
+    /// # use tonic::{service::CircuitBreakerLayer, transport::Endpoint};
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.circuit_breaker(CircuitBreakerLayer::new().failure_rate(25.0));
+    /// ```
+    pub fn circuit_breaker(self, circuit_breaker: CircuitBreakerLayer) -> Self {
+        Endpoint {
+            circuit_breaker: Some(circuit_breaker),
+            ..self
+        }
+    }
+
+    /// Set the locality of the endpoint.
+    ///
+    /// Channels balancing endpoints with
+    /// [`Channel::balance_list_with_locality`] prefer the endpoints of their
+    /// zone, then of their region. Endpoints without a locality are used last.
+    ///
+    /// ```
+    /// # use tonic::transport::{channel::Locality, Endpoint};
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.locality(Locality::new("us-east1", "us-east1-b"));
+    /// ```
+    pub fn locality(self, locality: Locality) -> Self {
+        Endpoint {
+            locality: Some(locality),
+            ..self
+        }
+    }
+
+    /// Set the group of the endpoint.
+    ///
+    /// Channels created with [`Channel::balance_channel_with_groups`] split
+    /// their requests across the groups of their endpoints. Endpoints without
+    /// a group belong to the group named `default`.
+    ///
+    /// ```
+    /// # use tonic::transport::Endpoint;
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.group("canary");
+    /// ```
+    pub fn group(self, name: impl Into<String>) -> Self {
+        Endpoint {
+            group: Some(name.into()),
+            ..self
+        }
+    }
+
+    /// Call `f` with the events of the connections of each channel connected
+    /// to this endpoint.
+    ///
+    /// `f` is called when a connection is established or ends, and when the
+    /// channel will reconnect, which lets applications log and alert on
+    /// flapping backends.
+    ///
+    /// ```
+    /// # use tonic::transport::{channel::ConnectionEvent, Endpoint};
+    /// # let mut builder = Endpoint::from_static("https://example.com");
+    /// builder.connection_events(|event| {
+    ///     if let ConnectionEvent::Disconnected { reason } = event {
+    ///         eprintln!("disconnected: {reason:?}");
+    ///     }
+    /// });
+    /// ```
+    pub fn connection_events(self, f: impl Fn(ConnectionEvent) + Send + Sync + 'static) -> Self {
+        Endpoint {
+            connection_events: Some(ConnectionEvents::new(f)),
+            ..self
+        }
+    }
+
+    /// Report the metrics of the requests and connections of each channel
+    /// connected to this endpoint to `sink`.
+    ///
+    /// See [`MetricsSink`] for the metrics reported.
+    ///
+    /// [`MetricsSink`]: crate::transport::channel::MetricsSink
+    pub fn metrics_sink(self, sink: impl MetricsSink) -> Self {
+        Endpoint {
+            metrics: Some(Metrics::new(sink)),
+            ..self
+        }
+    }
+
     /// Sets the [`SETTINGS_INITIAL_WINDOW_SIZE`][spec] option for HTTP2
     /// stream-level flow control.
     ///
+    /// This window bounds the data the server sends on each stream before the channel reads it,
+    /// see [`Endpoint::http2_max_send_buf_size`] for the data the channel sends.
+    ///
     /// Default is 65,535
     ///
     /// [spec]: https://httpwg.org/specs/rfc9113.html#InitialWindowSize
@@ -350,6 +511,9 @@ impl Endpoint {
 
     /// Sets the max connection-level flow control for HTTP2
     ///
+    /// Like [`Endpoint::initial_stream_window_size`], this window bounds the data received from
+    /// the server, over all the streams of the connection.
+    ///
     /// Default is 65,535
     pub fn initial_connection_window_size(self, sz: impl Into<Option<u32>>) -> Self {
         Endpoint {
@@ -358,6 +522,30 @@ impl Endpoint {
         }
     }
 
+    /// Sets the max size of the data buffered by each stream to send to the server.
+    ///
+    /// The data sent is bounded by the flow control windows granted by the server, so this only
+    /// bounds the data waiting for them, e.g. to fill links with a high bandwidth-delay product
+    /// without buffering whole messages.
+    ///
+    /// This will default to whatever the default in hyper is. As of v1.12.0, it is 1 MiB.
+    pub fn http2_max_send_buf_size(self, max: usize) -> Self {
+        Endpoint {
+            http2_max_send_buf_size: Some(max),
+            ..self
+        }
+    }
+
+    /// Sets the maximum frame size to use for HTTP2.
+    ///
+    /// If not set, will default from underlying transport.
+    pub fn max_frame_size(self, frame_size: impl Into<Option<u32>>) -> Self {
+        Endpoint {
+            max_frame_size: frame_size.into(),
+            ..self
+        }
+    }
+
     /// Sets the tower service default internal buffer size
     ///
     /// Default is 1024
@@ -408,7 +596,8 @@ impl Endpoint {
         }
     }
 
-    /// Set http2 KEEP_ALIVE_WHILE_IDLE. Uses `hyper`'s default otherwise.
+    /// Set http2 KEEP_ALIVE_WHILE_IDLE. Uses `hyper`'s default otherwise, which only pings
+    /// while streams are open.
     pub fn keep_alive_while_idle(self, enabled: bool) -> Self {
         Endpoint {
             http2_keep_alive_while_idle: Some(enabled),
@@ -434,6 +623,20 @@ impl Endpoint {
         }
     }
 
+    /// Sets whether to connect with HTTP/1.1 rather than HTTP/2. Disabled by default.
+    ///
+    /// gRPC requires HTTP/2, so this is only useful along with protocols carried over HTTP/1.1,
+    /// such as the grpc-web protocol implemented by `tonic-web`. With TLS, `http/1.1` is
+    /// negotiated through ALPN instead of `h2`.
+    ///
+    /// A connection carries one request at a time over HTTP/1.1.
+    pub fn http1_only(self, enabled: bool) -> Self {
+        Endpoint {
+            http1_only: enabled,
+            ..self
+        }
+    }
+
     /// Sets the executor used to spawn async tasks.
     ///
     /// Uses `tokio::spawn` by default.
@@ -445,11 +648,26 @@ impl Endpoint {
         self
     }
 
+    /// Sets the timer used for the timeouts of the requests, see [`Endpoint::timeout`], and for the
+    /// HTTP2 keep alive pings.
+    ///
+    /// Uses a timer of Tokio by default, so that tests pausing its time with `tokio::time::pause`
+    /// control the timeouts of the channel.
+    pub fn timer<T>(mut self, timer: T) -> Self
+    where
+        T: rt::Timer + Send + Sync + 'static,
+    {
+        self.timer = SharedTimer::new(timer);
+        self
+    }
+
     pub(crate) fn connector<C>(&self, c: C) -> service::Connector<C> {
         service::Connector::new(
             c,
             #[cfg(feature = "_tls-any")]
-            self.tls.clone(),
+            self.tls
+                .clone()
+                .map(|tls| if self.http1_only { tls.http1() } else { tls }),
         )
     }
 
@@ -6,12 +6,17 @@ pub(crate) mod service;
 mod tls;
 mod uds_connector;
 
-pub use self::service::Change;
+pub use self::service::{
+    Change, ConnectionEvent, ConnectionTasks, Locality, MetricsSink, TrafficSplit,
+};
 pub use endpoint::Endpoint;
 #[cfg(feature = "_tls-any")]
 pub use tls::ClientTlsConfig;
 
-use self::service::{Connection, DynamicServiceStream, Executor, SharedExec};
+use self::service::{
+    CallTimes, Connection, DynamicServiceStream, Executor, GroupBalance, LocalityBalance,
+    PickTimer, SharedExec,
+};
 use crate::body::Body;
 use bytes::Bytes;
 use http::{
@@ -63,9 +68,15 @@ const DEFAULT_BUFFER_SIZE: usize = 1024;
 /// the channel is backed by a `tower_buffer::Buffer` which runs the connection
 /// in a background task and provides a `mpsc` channel interface. Due to this
 /// cloning the `Channel` type is cheap and encouraged.
+///
+/// # Shutdown
+///
+/// The connections of a channel are driven by background tasks, which complete once all the
+/// clones of the channel are dropped. [`Channel::shutdown`] drops a channel and waits for them.
 #[derive(Clone)]
 pub struct Channel {
     svc: Buffer<Request<Body>, BoxFuture<'static, Result<Response<Body>, crate::BoxError>>>,
+    tasks: ConnectionTasks,
 }
 
 /// A future that resolves to an HTTP response.
@@ -141,8 +152,96 @@ impl Channel {
         E: Executor<Pin<Box<dyn Future<Output = ()> + Send>>> + Send + Sync + 'static,
     {
         let (tx, rx) = channel(capacity);
-        let list = DynamicServiceStream::new(rx);
-        (Self::balance(list, DEFAULT_BUFFER_SIZE, executor), tx)
+        let tasks = ConnectionTasks::default();
+        let list = DynamicServiceStream::new(rx, tasks.clone());
+        let channel = Self::balance(list, tasks, DEFAULT_BUFFER_SIZE, executor);
+
+        (channel, tx)
+    }
+
+    /// Balance a list of [`Endpoint`]'s, preferring the endpoints closest to
+    /// the `local` locality.
+    ///
+    /// Requests are balanced across the endpoints of the `local` zone. They
+    /// spill over to the other endpoints of the `local` region, then to the
+    /// other endpoints, only while no endpoint closer is ready, or all of them
+    /// have an open [circuit breaker](Endpoint::circuit_breaker).
+    ///
+    /// The locality of endpoints is set with [`Endpoint::locality`].
+    pub fn balance_list_with_locality(
+        list: impl Iterator<Item = Endpoint>,
+        local: Locality,
+    ) -> Self {
+        let (channel, tx) = Self::balance_channel_with_locality(DEFAULT_BUFFER_SIZE, local);
+        list.for_each(|endpoint| {
+            tx.try_send(Change::Insert(endpoint.uri.clone(), endpoint))
+                .unwrap();
+        });
+
+        channel
+    }
+
+    /// Balance a list of [`Endpoint`]'s, preferring the endpoints closest to
+    /// the `local` locality.
+    ///
+    /// This creates a [`Channel`] that will listen to a stream of change
+    /// events and will add or remove provided endpoints, balanced as with
+    /// [`Channel::balance_list_with_locality`].
+    pub fn balance_channel_with_locality<K>(
+        capacity: usize,
+        local: Locality,
+    ) -> (Self, Sender<Change<K, Endpoint>>)
+    where
+        K: Hash + Eq + Send + Clone + 'static,
+    {
+        let (tx, rx) = channel(capacity);
+        let tasks = ConnectionTasks::default();
+        let svc = BoxService::new(LocalityBalance::new(local, rx, tasks.clone()));
+        let (svc, worker) = Buffer::pair(PickTimer::new(svc), DEFAULT_BUFFER_SIZE);
+        SharedExec::tokio().execute(Box::pin(worker));
+
+        (Channel { svc, tasks }, tx)
+    }
+
+    /// Balance a list of [`Endpoint`]'s, splitting requests across groups of
+    /// endpoints.
+    ///
+    /// This creates a [`Channel`] that will listen to a stream of change
+    /// events and will add or remove provided endpoints. The
+    /// [`Change::Split`] events set the percentage of the requests sent to each
+    /// group, whose endpoints are set with [`Endpoint::group`]. Until the first
+    /// split is set, the groups get equal shares.
+    ///
+    /// While a group has no ready endpoint, its requests are sent to the other
+    /// groups.
+    ///
+    /// ```no_run
+    /// # use tonic::transport::{channel::{Change, TrafficSplit}, Channel, Endpoint};
+    /// # async fn run() {
+    /// let (channel, tx) = Channel::balance_channel_with_groups(16);
+    /// tx.send(Change::Insert("a", Endpoint::from_static("http://stable-a").group("stable")))
+    ///     .await
+    ///     .unwrap();
+    /// tx.send(Change::Insert("b", Endpoint::from_static("http://canary-b").group("canary")))
+    ///     .await
+    ///     .unwrap();
+    /// tx.send(Change::Split(TrafficSplit::new().group("stable", 95.0).group("canary", 5.0)))
+    ///     .await
+    ///     .unwrap();
+    /// # drop(channel);
+    /// # }
+    /// ```
+    pub fn balance_channel_with_groups<K>(capacity: usize) -> (Self, Sender<Change<K, Endpoint>>)
+    where
+        K: Hash + Eq + Send + Clone + 'static,
+    {
+        let (tx, rx) = channel(capacity);
+        let tasks = ConnectionTasks::default();
+        let svc = BoxService::new(GroupBalance::new(rx, tasks.clone()));
+        let (svc, worker) = Buffer::pair(PickTimer::new(svc), DEFAULT_BUFFER_SIZE);
+        SharedExec::tokio().execute(Box::pin(worker));
+
+        (Channel { svc, tasks }, tx)
     }
 
     /// Create a new [`Channel`] using a custom connector to the provided [Endpoint].
@@ -158,12 +257,13 @@ impl Channel {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
         let executor = endpoint.executor.clone();
 
-        let svc = Connection::lazy(connector, endpoint);
-        let (svc, worker) = Buffer::pair(svc, buffer_size);
+        let tasks = ConnectionTasks::default();
+        let svc = Connection::lazy(connector, endpoint, tasks.clone());
+        let (svc, worker) = Buffer::pair(PickTimer::new(svc), buffer_size);
 
         executor.execute(worker);
 
-        Channel { svc }
+        Channel { svc, tasks }
     }
 
     /// Connect to the provided [`Endpoint`] using the provided connector, and return a new [`Channel`].
@@ -179,16 +279,41 @@ impl Channel {
         let buffer_size = endpoint.buffer_size.unwrap_or(DEFAULT_BUFFER_SIZE);
         let executor = endpoint.executor.clone();
 
-        let svc = Connection::connect(connector, endpoint)
+        let tasks = ConnectionTasks::default();
+        let svc = Connection::connect(connector, endpoint, tasks.clone())
             .await
             .map_err(super::Error::from_source)?;
-        let (svc, worker) = Buffer::pair(svc, buffer_size);
+        let (svc, worker) = Buffer::pair(PickTimer::new(svc), buffer_size);
         executor.execute(worker);
 
-        Ok(Channel { svc })
+        Ok(Channel { svc, tasks })
+    }
+
+    /// Returns a handle on the background tasks driving the connections of this channel, shared
+    /// by its clones.
+    ///
+    /// The handle stays valid once the channel is dropped, e.g. for tests to check that its
+    /// connection tasks completed.
+    pub fn connection_tasks(&self) -> ConnectionTasks {
+        self.tasks.clone()
     }
 
-    pub(crate) fn balance<D, E>(discover: D, buffer_size: usize, executor: E) -> Self
+    /// Drops this channel and waits for the background tasks driving its connections to complete.
+    ///
+    /// The connections are closed once all the clones of the channel are dropped, so this waits
+    /// for the other clones to be dropped too.
+    pub async fn shutdown(self) {
+        let tasks = self.connection_tasks();
+        drop(self);
+        tasks.wait().await;
+    }
+
+    pub(crate) fn balance<D, E>(
+        discover: D,
+        tasks: ConnectionTasks,
+        buffer_size: usize,
+        executor: E,
+    ) -> Self
     where
         D: Discover<Service = Connection> + Unpin + Send + 'static,
         D::Error: Into<crate::BoxError>,
@@ -198,10 +323,10 @@ impl Channel {
         let svc = Balance::new(discover);
 
         let svc = BoxService::new(svc);
-        let (svc, worker) = Buffer::pair(svc, buffer_size);
+        let (svc, worker) = Buffer::pair(PickTimer::new(svc), buffer_size);
         executor.execute(Box::pin(worker));
 
-        Channel { svc }
+        Channel { svc, tasks }
     }
 }
 
@@ -214,7 +339,8 @@ impl Service<http::Request<Body>> for Channel {
         Service::poll_ready(&mut self.svc, cx).map_err(super::Error::from_source)
     }
 
-    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+    fn call(&mut self, mut request: http::Request<Body>) -> Self::Future {
+        request.extensions_mut().insert(CallTimes::now());
         let inner = Service::call(&mut self.svc, request);
 
         ResponseFuture { inner }
@@ -1,11 +1,18 @@
 use std::{
+    any::Any,
     fmt,
+    sync::Arc,
     task::{Context, Poll},
+    time::Instant,
 };
 
-use http::{Request, Response, Uri};
-use hyper::{client::conn::http2::Builder, rt, rt::Executor};
-use hyper_util::rt::TokioTimer;
+use http::{header::HOST, HeaderValue, Request, Response, Uri, Version};
+use hyper::{
+    client::conn::{http1, http2::Builder},
+    rt,
+    rt::Executor,
+};
+use hyper_util::rt::TokioIo;
 use tower::{
     layer::Layer,
     limit::{concurrency::ConcurrencyLimitLayer, rate::RateLimitLayer},
@@ -17,18 +24,30 @@ use tower_service::Service;
 
 #[cfg(feature = "user-agent")]
 use super::UserAgent;
-use super::{AddOrigin, Reconnect, SharedExec};
+use super::{
+    AddOrigin, BoxedIo, ConnectionEvent, ConnectionEvents, ConnectionTasks, EndpointMetrics,
+    InFlightBody, Reconnect, SharedExec, StaticMetadata,
+};
 use crate::{
     body::Body,
-    transport::{channel::BoxFuture, service::GrpcTimeout, Endpoint},
+    service::circuit_breaker::{Breaker, CircuitState},
+    transport::{
+        channel::BoxFuture,
+        peer_settings::{PeerSettingsIo, SharedPeerSettings},
+        service::GrpcTimeout,
+        ConnectionId, Endpoint,
+    },
+    ConnectError, Http2Error, Status,
 };
 
 pub(crate) struct Connection {
     inner: BoxService<Request<Body>, Response<Body>, crate::BoxError>,
+    breaker: Option<Arc<Breaker>>,
+    metrics: Option<Arc<EndpointMetrics>>,
 }
 
 impl Connection {
-    fn new<C>(connector: C, endpoint: Endpoint, is_lazy: bool) -> Self
+    fn new<C>(connector: C, endpoint: Endpoint, tasks: ConnectionTasks, is_lazy: bool) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::BoxError> + Send,
@@ -39,7 +58,8 @@ impl Connection {
             .initial_stream_window_size(endpoint.init_stream_window_size)
             .initial_connection_window_size(endpoint.init_connection_window_size)
             .keep_alive_interval(endpoint.http2_keep_alive_interval)
-            .timer(TokioTimer::new())
+            .max_frame_size(endpoint.max_frame_size)
+            .timer(endpoint.timer.clone())
             .clone();
 
         if let Some(val) = endpoint.http2_keep_alive_timeout {
@@ -58,34 +78,77 @@ impl Connection {
             settings.max_header_list_size(val);
         }
 
+        if let Some(val) = endpoint.http2_max_send_buf_size {
+            settings.max_send_buf_size(val);
+        }
+
         let stack = ServiceBuilder::new().layer_fn(|s| {
             let origin = endpoint.origin.as_ref().unwrap_or(endpoint.uri()).clone();
 
             AddOrigin::new(s, origin)
         });
 
+        let stack = stack.layer_fn(|s| StaticMetadata::new(s, endpoint.static_metadata.clone()));
+
         #[cfg(feature = "user-agent")]
         let stack = stack.layer_fn(|s| UserAgent::new(s, endpoint.user_agent.clone()));
 
         let stack = stack
-            .layer_fn(|s| GrpcTimeout::new(s, endpoint.timeout))
+            .layer_fn(|s| GrpcTimeout::new(s, endpoint.timeout, endpoint.timer.clone()))
             .option_layer(endpoint.concurrency_limit.map(ConcurrencyLimitLayer::new))
             .option_layer(endpoint.rate_limit.map(|(l, d)| RateLimitLayer::new(l, d)))
             .into_inner();
 
-        let make_service =
-            MakeSendRequestService::new(connector, endpoint.executor.clone(), settings);
+        let settings = if endpoint.http1_only {
+            Settings::Http1(http1::Builder::new())
+        } else {
+            Settings::Http2(settings)
+        };
 
-        let conn = Reconnect::new(make_service, endpoint.uri().clone(), is_lazy);
+        let metrics = endpoint
+            .metrics
+            .clone()
+            .map(|sink| EndpointMetrics::new(sink, endpoint.uri().clone()));
 
-        Self {
-            inner: BoxService::new(stack.layer(conn)),
+        let make_service = MakeSendRequestService::new(
+            connector,
+            endpoint.executor.clone(),
+            tasks,
+            settings,
+            endpoint.connection_events.clone(),
+            metrics.clone(),
+        );
+
+        let conn = Reconnect::new(
+            make_service,
+            endpoint.uri().clone(),
+            is_lazy,
+            endpoint.connection_events.clone(),
+        );
+        let svc = stack.layer(conn);
+
+        match &endpoint.circuit_breaker {
+            Some(circuit_breaker) => {
+                let svc = circuit_breaker.layer(svc);
+                let breaker = svc.breaker();
+                Self {
+                    inner: BoxService::new(svc.map_response(|res| res.map(Body::new))),
+                    breaker: Some(breaker),
+                    metrics,
+                }
+            }
+            None => Self {
+                inner: BoxService::new(svc),
+                breaker: None,
+                metrics,
+            },
         }
     }
 
     pub(crate) async fn connect<C>(
         connector: C,
         endpoint: Endpoint,
+        tasks: ConnectionTasks,
     ) -> Result<Self, crate::BoxError>
     where
         C: Service<Uri> + Send + 'static,
@@ -93,17 +156,23 @@ impl Connection {
         C::Future: Unpin + Send,
         C::Response: rt::Read + rt::Write + Unpin + Send + 'static,
     {
-        Self::new(connector, endpoint, false).ready_oneshot().await
+        Self::new(connector, endpoint, tasks, false)
+            .ready_oneshot()
+            .await
     }
 
-    pub(crate) fn lazy<C>(connector: C, endpoint: Endpoint) -> Self
+    pub(crate) fn breaker(&self) -> Option<Arc<Breaker>> {
+        self.breaker.clone()
+    }
+
+    pub(crate) fn lazy<C>(connector: C, endpoint: Endpoint, tasks: ConnectionTasks) -> Self
     where
         C: Service<Uri> + Send + 'static,
         C::Error: Into<crate::BoxError> + Send,
         C::Future: Send,
         C::Response: rt::Read + rt::Write + Unpin + Send + 'static,
     {
-        Self::new(connector, endpoint, true)
+        Self::new(connector, endpoint, tasks, true)
     }
 }
 
@@ -117,15 +186,28 @@ impl Service<Request<Body>> for Connection {
     }
 
     fn call(&mut self, req: Request<Body>) -> Self::Future {
-        self.inner.call(req)
+        let Some(metrics) = &self.metrics else {
+            return self.inner.call(req);
+        };
+
+        let in_flight = metrics.start(&req);
+        let fut = self.inner.call(req);
+        Box::pin(async move {
+            let res = fut.await?;
+            Ok(res.map(|body| Body::new(InFlightBody::new(body, in_flight))))
+        })
     }
 }
 
 impl Load for Connection {
     type Metric = usize;
 
+    // Connections with an open circuit breaker are avoided by the balancer.
     fn load(&self) -> Self::Metric {
-        0
+        match &self.breaker {
+            Some(breaker) if breaker.state() == CircuitState::Open => usize::MAX,
+            _ => 0,
+        }
     }
 }
 
@@ -136,13 +218,14 @@ impl fmt::Debug for Connection {
 }
 
 struct SendRequest {
-    inner: hyper::client::conn::http2::SendRequest<Body>,
+    sender: Sender,
+    connection_id: ConnectionId,
+    peer_settings: SharedPeerSettings,
 }
 
-impl From<hyper::client::conn::http2::SendRequest<Body>> for SendRequest {
-    fn from(inner: hyper::client::conn::http2::SendRequest<Body>) -> Self {
-        Self { inner }
-    }
+enum Sender {
+    Http1(http1::SendRequest<Body>),
+    Http2(hyper::client::conn::http2::SendRequest<Body>),
 }
 
 impl tower::Service<Request<Body>> for SendRequest {
@@ -151,28 +234,117 @@ impl tower::Service<Request<Body>> for SendRequest {
     type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
 
     fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
-        self.inner.poll_ready(cx).map_err(Into::into)
+        match &mut self.sender {
+            Sender::Http1(inner) => inner.poll_ready(cx).map_err(Into::into),
+            Sender::Http2(inner) => inner.poll_ready(cx).map_err(Into::into),
+        }
     }
 
     fn call(&mut self, req: Request<Body>) -> Self::Future {
-        let fut = self.inner.send_request(req);
+        let connection_id = self.connection_id;
+        let peer_settings = self.peer_settings.clone();
+        let with_id = move |res: Response<hyper::body::Incoming>| {
+            let mut res = res.map(Body::new);
+            res.extensions_mut().insert(connection_id);
+            if let Some(settings) = peer_settings.get() {
+                res.extensions_mut().insert(settings);
+            }
+            res
+        };
+
+        match &mut self.sender {
+            Sender::Http1(inner) => {
+                let fut = inner.send_request(http1_request(req));
+
+                Box::pin(async move { fut.await.map_err(Into::into).map(with_id) })
+            }
+            Sender::Http2(inner) => {
+                // Fail the requests the server would reset the stream of, rather than sending them.
+                let exceeded = self
+                    .peer_settings
+                    .get()
+                    .and_then(|settings| settings.header_list_size_exceeded(req.headers()));
+                if let Some((size, max)) = exceeded {
+                    let status = Status::resource_exhausted(format!(
+                        "request headers of {size} bytes exceed the max header list size of the \
+                         server ({max} bytes)"
+                    ));
+                    return Box::pin(async move { Err(status.into()) });
+                }
+
+                let fut = inner.send_request(req);
+
+                Box::pin(async move { fut.await.map_err(Into::into).map(with_id) })
+            }
+        }
+    }
+}
+
+// Turns the request into an HTTP/1.1 request in origin form, with the authority of the URI moved
+// to the `Host` header.
+fn http1_request(mut req: Request<Body>) -> Request<Body> {
+    *req.version_mut() = Version::HTTP_11;
+
+    if let Some(authority) = req.uri().authority() {
+        if !req.headers().contains_key(HOST) {
+            if let Ok(host) = HeaderValue::from_str(authority.as_str()) {
+                req.headers_mut().insert(HOST, host);
+            }
+        }
+    }
+
+    if let Some(path_and_query) = req.uri().path_and_query().cloned() {
+        *req.uri_mut() = Uri::from(path_and_query);
+    }
 
-        Box::pin(async move { fut.await.map_err(Into::into).map(|res| res.map(Body::new)) })
+    req
+}
+
+// Handshake errors happen while connecting, before any request is sent on the connection.
+fn handshake_error(err: hyper::Error) -> ConnectError {
+    ConnectError(err.into())
+}
+
+// The `GOAWAY` error a connection was closed with, if any.
+fn go_away(err: &hyper::Error) -> Option<Http2Error> {
+    let err = std::error::Error::source(err)?.downcast_ref::<h2::Error>()?;
+    if !err.is_go_away() {
+        return None;
     }
+    Http2Error::from_h2(err)
+}
+
+#[derive(Clone)]
+enum Settings {
+    Http1(http1::Builder),
+    Http2(Builder<SharedExec>),
 }
 
 struct MakeSendRequestService<C> {
     connector: C,
     executor: SharedExec,
-    settings: Builder<SharedExec>,
+    tasks: ConnectionTasks,
+    settings: Settings,
+    events: Option<ConnectionEvents>,
+    metrics: Option<Arc<EndpointMetrics>>,
 }
 
 impl<C> MakeSendRequestService<C> {
-    fn new(connector: C, executor: SharedExec, settings: Builder<SharedExec>) -> Self {
+    fn new(
+        connector: C,
+        executor: SharedExec,
+        tasks: ConnectionTasks,
+        settings: Settings,
+        events: Option<ConnectionEvents>,
+        metrics: Option<Arc<EndpointMetrics>>,
+    ) -> Self {
         Self {
             connector,
             executor,
+            tasks,
             settings,
+            events,
+            metrics,
         }
     }
 }
@@ -182,7 +354,7 @@ where
     C: Service<Uri> + Send + 'static,
     C::Error: Into<crate::BoxError> + Send,
     C::Future: Send,
-    C::Response: rt::Read + rt::Write + Unpin + Send,
+    C::Response: rt::Read + rt::Write + Unpin + Send + 'static,
 {
     type Response = SendRequest;
     type Error = crate::BoxError;
@@ -193,24 +365,70 @@ where
     }
 
     fn call(&mut self, req: Uri) -> Self::Future {
+        let start = Instant::now();
         let fut = self.connector.call(req);
         let builder = self.settings.clone();
         let executor = self.executor.clone();
+        let tasks = self.tasks.clone();
+        let events = self.events.clone();
+        let metrics = self.metrics.clone();
 
         Box::pin(async move {
             let io = fut.await.map_err(Into::into)?;
-            let (send_request, conn) = builder.handshake(io).await?;
+            // Only tonic's own connectors know about their connections.
+            let info = (&io as &dyn Any)
+                .downcast_ref::<BoxedIo>()
+                .map(|io| io.info().clone())
+                .unwrap_or_default();
+            let peer_settings = SharedPeerSettings::default();
+            let (sender, conn) = match builder {
+                Settings::Http1(builder) => {
+                    let (send_request, conn) =
+                        builder.handshake(io).await.map_err(handshake_error)?;
+                    let conn: BoxFuture<'static, _> = Box::pin(conn);
+                    (Sender::Http1(send_request), conn)
+                }
+                Settings::Http2(builder) => {
+                    let io = PeerSettingsIo::client(TokioIo::new(io), peer_settings.clone());
+                    let (send_request, conn) = builder
+                        .handshake(TokioIo::new(io))
+                        .await
+                        .map_err(handshake_error)?;
+                    let conn: BoxFuture<'static, _> = Box::pin(conn);
+                    (Sender::Http2(send_request), conn)
+                }
+            };
+
+            if let Some(metrics) = &metrics {
+                metrics.connected(start.elapsed());
+            }
+            if let Some(events) = &events {
+                events.emit(info.into_event());
+            }
 
             Executor::<BoxFuture<'static, ()>>::execute(
                 &executor,
-                Box::pin(async move {
-                    if let Err(e) = conn.await {
+                Box::pin(tasks.track(async move {
+                    let result = conn.await;
+                    if let Err(e) = &result {
                         tracing::debug!("connection task error: {:?}", e);
                     }
-                }) as _,
+                    if let Some(events) = events {
+                        if let Some(error) = result.as_ref().err().and_then(go_away) {
+                            events.emit(ConnectionEvent::GoAway { error });
+                        }
+                        events.emit(ConnectionEvent::Disconnected {
+                            reason: result.err().map(|e| e.to_string()),
+                        });
+                    }
+                })) as _,
             );
 
-            Ok(SendRequest::from(send_request))
+            Ok(SendRequest {
+                sender,
+                connection_id: ConnectionId::next(),
+                peer_settings,
+            })
         })
     }
 }
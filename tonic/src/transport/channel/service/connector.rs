@@ -1,17 +1,20 @@
-use super::BoxedIo;
 #[cfg(feature = "_tls-any")]
 use super::TlsConnector;
+use super::{BoxedIo, ConnectInfo};
 use crate::transport::channel::BoxFuture;
 use crate::ConnectError;
 use http::Uri;
 #[cfg(feature = "_tls-any")]
 use std::fmt;
-use std::task::{Context, Poll};
+use std::{
+    any::Any,
+    net::SocketAddr,
+    task::{Context, Poll},
+};
 
 use hyper::rt;
-
-#[cfg(feature = "_tls-any")]
 use hyper_util::rt::TokioIo;
+use tokio::net::TcpStream;
 use tower_service::Service;
 
 pub(crate) struct Connector<C> {
@@ -58,18 +61,27 @@ where
         Box::pin(async move {
             async {
                 let io = connect.await?;
+                let addr = peer_addr(&io);
 
                 #[cfg(feature = "_tls-any")]
                 if is_https {
                     return if let Some(tls) = tls {
                         let io = tls.connect(TokioIo::new(io)).await?;
-                        Ok(io)
+                        let info = ConnectInfo {
+                            addr,
+                            ..io.info().clone()
+                        };
+                        Ok(io.with_info(info))
                     } else {
                         Err(HttpsUriWithoutTlsSupport(()).into())
                     };
                 }
 
-                Ok::<_, crate::BoxError>(BoxedIo::new(io))
+                let info = ConnectInfo {
+                    addr,
+                    ..ConnectInfo::default()
+                };
+                Ok::<_, crate::BoxError>(BoxedIo::new(io).with_info(info))
             }
             .await
             .map_err(ConnectError)
@@ -77,6 +89,12 @@ where
     }
 }
 
+// The address of the peer of the connections of tonic's own TCP connector.
+fn peer_addr<I: Any>(io: &I) -> Option<SocketAddr> {
+    let io = (io as &dyn Any).downcast_ref::<TokioIo<TcpStream>>()?;
+    io.inner().peer_addr().ok()
+}
+
 /// Error returned when trying to connect to an HTTPS endpoint without TLS enabled.
 #[cfg(feature = "_tls-any")]
 #[derive(Debug)]
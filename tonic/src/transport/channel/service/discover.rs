@@ -1,11 +1,12 @@
 use super::super::{Connection, Endpoint};
+use super::{ConnectionTasks, TrafficSplit};
 
 use std::{
     hash::Hash,
     pin::Pin,
     task::{Context, Poll},
 };
-use tokio::sync::mpsc::Receiver;
+use tokio::sync::mpsc::{Receiver, UnboundedReceiver};
 use tokio_stream::Stream;
 use tower::discover::Change as TowerChange;
 
@@ -16,15 +17,23 @@ pub enum Change<K, V> {
     Insert(K, V),
     /// The service identified by key `K` disappeared.
     Remove(K),
+    /// The traffic split across the endpoint groups changed.
+    ///
+    /// Only channels created with [`Channel::balance_channel_with_groups`]
+    /// split traffic, other channels ignore this change.
+    ///
+    /// [`Channel::balance_channel_with_groups`]: crate::transport::Channel::balance_channel_with_groups
+    Split(TrafficSplit),
 }
 
 pub(crate) struct DynamicServiceStream<K: Hash + Eq + Clone> {
     changes: Receiver<Change<K, Endpoint>>,
+    tasks: ConnectionTasks,
 }
 
 impl<K: Hash + Eq + Clone> DynamicServiceStream<K> {
-    pub(crate) fn new(changes: Receiver<Change<K, Endpoint>>) -> Self {
-        Self { changes }
+    pub(crate) fn new(changes: Receiver<Change<K, Endpoint>>, tasks: ConnectionTasks) -> Self {
+        Self { changes, tasks }
     }
 }
 
@@ -32,17 +41,48 @@ impl<K: Hash + Eq + Clone> Stream for DynamicServiceStream<K> {
     type Item = Result<TowerChange<K, Connection>, crate::BoxError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match Pin::new(&mut self.changes).poll_recv(cx) {
-            Poll::Pending | Poll::Ready(None) => Poll::Pending,
-            Poll::Ready(Some(change)) => match change {
-                Change::Insert(k, endpoint) => {
-                    let connection = Connection::lazy(endpoint.http_connector(), endpoint);
-                    Poll::Ready(Some(Ok(TowerChange::Insert(k, connection))))
-                }
-                Change::Remove(k) => Poll::Ready(Some(Ok(TowerChange::Remove(k)))),
-            },
+        loop {
+            return match Pin::new(&mut self.changes).poll_recv(cx) {
+                Poll::Pending | Poll::Ready(None) => Poll::Pending,
+                Poll::Ready(Some(change)) => match change {
+                    Change::Insert(k, endpoint) => {
+                        let connection = Connection::lazy(
+                            endpoint.http_connector(),
+                            endpoint,
+                            self.tasks.clone(),
+                        );
+                        Poll::Ready(Some(Ok(TowerChange::Insert(k, connection))))
+                    }
+                    Change::Remove(k) => Poll::Ready(Some(Ok(TowerChange::Remove(k)))),
+                    Change::Split(_) => continue,
+                },
+            };
         }
     }
 }
 
 impl<K: Hash + Eq + Clone> Unpin for DynamicServiceStream<K> {}
+
+/// The connection changes of a subset of the endpoints of a channel, dispatched by its balancer.
+pub(crate) struct SubsetDiscover<K> {
+    changes: UnboundedReceiver<TowerChange<K, Connection>>,
+}
+
+impl<K> SubsetDiscover<K> {
+    pub(crate) fn new(changes: UnboundedReceiver<TowerChange<K, Connection>>) -> Self {
+        Self { changes }
+    }
+}
+
+impl<K> Stream for SubsetDiscover<K> {
+    type Item = Result<TowerChange<K, Connection>, crate::BoxError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.changes.poll_recv(cx) {
+            Poll::Pending | Poll::Ready(None) => Poll::Pending,
+            Poll::Ready(Some(change)) => Poll::Ready(Some(Ok(change))),
+        }
+    }
+}
+
+impl<K> Unpin for SubsetDiscover<K> {}
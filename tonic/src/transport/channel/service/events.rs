@@ -0,0 +1,80 @@
+use std::{fmt, net::SocketAddr, sync::Arc, time::Duration};
+
+use crate::Http2Error;
+
+/// An event of the connection of a channel to an endpoint.
+///
+/// See [`Endpoint::connection_events`].
+///
+/// [`Endpoint::connection_events`]: crate::transport::Endpoint::connection_events
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionEvent {
+    /// The channel connected to the endpoint.
+    Connected {
+        /// The address of the endpoint, when connected over TCP by tonic's own connector.
+        addr: Option<SocketAddr>,
+        /// The protocol negotiated with ALPN, when connected over TLS.
+        alpn: Option<Vec<u8>>,
+        /// The version of TLS, such as `TLSv1_3`, when connected over TLS.
+        tls_version: Option<String>,
+    },
+    /// The connection to the endpoint was closed with a `GOAWAY` error, sent by the endpoint or by
+    /// the channel, e.g. on a protocol error.
+    ///
+    /// Emitted before the [`ConnectionEvent::Disconnected`] event of the connection.
+    GoAway {
+        /// The error code of the `GOAWAY`, and whether the endpoint sent it.
+        error: Http2Error,
+    },
+    /// The connection to the endpoint ended.
+    Disconnected {
+        /// The error that ended the connection, or `None` when closed gracefully.
+        reason: Option<String>,
+    },
+    /// The channel will connect to the endpoint again.
+    ReconnectScheduled {
+        /// The delay before connecting again.
+        ///
+        /// Channels reconnect on their next request without delay, so this is
+        /// currently always zero.
+        backoff: Duration,
+    },
+}
+
+/// The callback receiving the connection events of an endpoint.
+#[derive(Clone)]
+pub(crate) struct ConnectionEvents(Arc<dyn Fn(ConnectionEvent) + Send + Sync>);
+
+impl ConnectionEvents {
+    pub(crate) fn new(f: impl Fn(ConnectionEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn emit(&self, event: ConnectionEvent) {
+        (self.0)(event)
+    }
+}
+
+impl fmt::Debug for ConnectionEvents {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionEvents").finish()
+    }
+}
+
+/// What is known about a connection once established.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConnectInfo {
+    pub(crate) addr: Option<SocketAddr>,
+    pub(crate) alpn: Option<Vec<u8>>,
+    pub(crate) tls_version: Option<String>,
+}
+
+impl ConnectInfo {
+    pub(crate) fn into_event(self) -> ConnectionEvent {
+        ConnectionEvent::Connected {
+            addr: self.addr,
+            alpn: self.alpn,
+            tls_version: self.tls_version,
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use super::super::{Connection, Endpoint};
+use super::{Change, ConnectionTasks, SubsetDiscover};
+use crate::body::Body;
+
+use http::{Request, Response};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::{self, Receiver, UnboundedSender};
+use tower::{
+    balance::p2c::Balance,
+    discover::Change as TowerChange,
+    util::rng::{HasherRng, Rng},
+};
+use tower_service::Service;
+
+/// The group of the endpoints without a group.
+const DEFAULT_GROUP: &str = "default";
+
+/// The percentages of the requests of a channel sent to each group of endpoints.
+///
+/// See [`Channel::balance_channel_with_groups`].
+///
+/// [`Channel::balance_channel_with_groups`]: crate::transport::Channel::balance_channel_with_groups
+#[derive(Debug, Clone, Default)]
+pub struct TrafficSplit {
+    percentages: HashMap<String, f64>,
+}
+
+impl TrafficSplit {
+    /// Create a new `TrafficSplit`, sending no request to any group.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send `percentage` percent of the requests to the endpoints of the group `name`.
+    pub fn group(mut self, name: impl Into<String>, percentage: f64) -> Self {
+        self.percentages.insert(name.into(), percentage.max(0.0));
+        self
+    }
+
+    /// The percentage of the requests sent to the endpoints of the group `name`.
+    pub fn percentage(&self, name: &str) -> f64 {
+        self.percentages.get(name).copied().unwrap_or(0.0)
+    }
+}
+
+type SubsetBalance<K> = Balance<SubsetDiscover<K>, Request<Body>>;
+
+struct Group<K: Hash + Eq + Clone> {
+    sender: UnboundedSender<TowerChange<K, Connection>>,
+    balance: SubsetBalance<K>,
+    endpoints: usize,
+}
+
+/// Splits requests across groups of endpoints, balancing them over the endpoints of each group.
+///
+/// The group of each request is picked among the groups with a ready endpoint, so the requests of
+/// a group without ready endpoints are sent to the other groups.
+pub(crate) struct GroupBalance<K: Hash + Eq + Clone> {
+    changes: Receiver<Change<K, Endpoint>>,
+    tasks: ConnectionTasks,
+    // The group of each endpoint.
+    endpoints: HashMap<K, String>,
+    groups: HashMap<String, Group<K>>,
+    // Until a split is set, the groups have equal shares.
+    split: Option<TrafficSplit>,
+    selected: Option<String>,
+    rng: HasherRng,
+}
+
+impl<K: Hash + Eq + Send + Clone + 'static> GroupBalance<K> {
+    pub(crate) fn new(changes: Receiver<Change<K, Endpoint>>, tasks: ConnectionTasks) -> Self {
+        Self {
+            changes,
+            tasks,
+            endpoints: HashMap::new(),
+            groups: HashMap::new(),
+            split: None,
+            selected: None,
+            rng: HasherRng::new(),
+        }
+    }
+
+    fn remove(&mut self, k: K) {
+        let Some(name) = self.endpoints.remove(&k) else {
+            return;
+        };
+        let Some(group) = self.groups.get_mut(&name) else {
+            return;
+        };
+        group.endpoints -= 1;
+        if group.endpoints == 0 {
+            self.groups.remove(&name);
+            if self.selected.as_ref() == Some(&name) {
+                self.selected = None;
+            }
+        } else {
+            let _ = group.sender.send(TowerChange::Remove(k));
+        }
+    }
+
+    // Dispatch the endpoint changes to the balancers of their groups.
+    fn poll_changes(&mut self, cx: &mut Context<'_>) {
+        while let Poll::Ready(Some(change)) = self.changes.poll_recv(cx) {
+            match change {
+                Change::Insert(k, endpoint) => {
+                    self.remove(k.clone());
+                    let name = endpoint
+                        .group
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_GROUP.to_owned());
+                    let group = self.groups.entry(name.clone()).or_insert_with(|| {
+                        let (sender, rx) = mpsc::unbounded_channel();
+                        Group {
+                            sender,
+                            balance: Balance::new(SubsetDiscover::new(rx)),
+                            endpoints: 0,
+                        }
+                    });
+                    let connection =
+                        Connection::lazy(endpoint.http_connector(), endpoint, self.tasks.clone());
+                    let _ = group
+                        .sender
+                        .send(TowerChange::Insert(k.clone(), connection));
+                    group.endpoints += 1;
+                    self.endpoints.insert(k, name);
+                }
+                Change::Remove(k) => self.remove(k),
+                Change::Split(split) => self.split = Some(split),
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Send + Clone + 'static> Service<Request<Body>> for GroupBalance<K> {
+    type Response = Response<Body>;
+    type Error = crate::BoxError;
+    type Future = <SubsetBalance<K> as Service<Request<Body>>>::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_changes(cx);
+
+        // Poll the balancers of all the groups, so they process their endpoint changes.
+        let mut ready = Vec::with_capacity(self.groups.len());
+        for (name, group) in &mut self.groups {
+            if group.balance.poll_ready(cx)?.is_ready() {
+                let share = self
+                    .split
+                    .as_ref()
+                    .map_or(1.0, |split| split.percentage(name));
+                if share > 0.0 {
+                    ready.push((name, share));
+                }
+            }
+        }
+
+        if ready.is_empty() {
+            return Poll::Pending;
+        }
+        if ready
+            .iter()
+            .any(|(name, _)| self.selected.as_ref() == Some(*name))
+        {
+            return Poll::Ready(Ok(()));
+        }
+
+        let total: f64 = ready.iter().map(|(_, share)| share).sum();
+        let mut pick = self.rng.next_f64() * total;
+        let mut selected = ready[ready.len() - 1].0;
+        for (name, share) in ready {
+            if pick < share {
+                selected = name;
+                break;
+            }
+            pick -= share;
+        }
+        self.selected = Some(selected.clone());
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let name = self.selected.take().expect("called before ready");
+        self.groups
+            .get_mut(&name)
+            .expect("selected group exists")
+            .balance
+            .call(req)
+    }
+}
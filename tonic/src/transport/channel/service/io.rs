@@ -5,6 +5,8 @@ use std::task::{Context, Poll};
 use hyper::rt;
 use hyper_util::client::legacy::connect::{Connected as HyperConnected, Connection};
 
+use super::ConnectInfo;
+
 pub(in crate::transport) trait Io:
     rt::Read + rt::Write + Send + 'static
 {
@@ -12,11 +14,19 @@ pub(in crate::transport) trait Io:
 
 impl<T> Io for T where T: rt::Read + rt::Write + Send + 'static {}
 
-pub(crate) struct BoxedIo(Pin<Box<dyn Io>>);
+pub(crate) struct BoxedIo(Pin<Box<dyn Io>>, ConnectInfo);
 
 impl BoxedIo {
     pub(in crate::transport) fn new<I: Io>(io: I) -> Self {
-        BoxedIo(Box::pin(io))
+        BoxedIo(Box::pin(io), ConnectInfo::default())
+    }
+
+    pub(in crate::transport) fn with_info(self, info: ConnectInfo) -> Self {
+        BoxedIo(self.0, info)
+    }
+
+    pub(in crate::transport) fn info(&self) -> &ConnectInfo {
+        &self.1
     }
 }
 
@@ -0,0 +1,172 @@
+use super::super::{Connection, Endpoint};
+use super::{Change, ConnectionTasks, SubsetDiscover};
+use crate::{
+    body::Body,
+    service::circuit_breaker::{Breaker, CircuitState},
+};
+
+use http::{Request, Response};
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc::{self, Receiver, UnboundedSender};
+use tower::{balance::p2c::Balance, discover::Change as TowerChange};
+use tower_service::Service;
+
+/// The locality of an endpoint, its zone and region.
+///
+/// See [`Endpoint::locality`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Locality {
+    region: String,
+    zone: String,
+}
+
+impl Locality {
+    /// Create a new `Locality` of the `zone` of `region`.
+    pub fn new(region: impl Into<String>, zone: impl Into<String>) -> Self {
+        Self {
+            region: region.into(),
+            zone: zone.into(),
+        }
+    }
+
+    /// The region of the locality.
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    /// The zone of the locality.
+    pub fn zone(&self) -> &str {
+        &self.zone
+    }
+}
+
+// The endpoints of the local zone, of the local region, and the others.
+const TIERS: usize = 3;
+
+type TierBalance<K> = Balance<SubsetDiscover<K>, Request<Body>>;
+
+/// Balances requests over the endpoints of the closest tier of localities with available
+/// endpoints.
+///
+/// The endpoints of a tier are available when one of them is ready, and doesn't have an open
+/// circuit breaker.
+pub(crate) struct LocalityBalance<K: Hash + Eq + Clone> {
+    local: Locality,
+    changes: Receiver<Change<K, Endpoint>>,
+    tasks: ConnectionTasks,
+    // The tier and circuit breaker of each endpoint.
+    endpoints: HashMap<K, (usize, Option<Arc<Breaker>>)>,
+    senders: [UnboundedSender<TowerChange<K, Connection>>; TIERS],
+    tiers: [TierBalance<K>; TIERS],
+    ready: usize,
+}
+
+impl<K: Hash + Eq + Send + Clone + 'static> LocalityBalance<K> {
+    pub(crate) fn new(
+        local: Locality,
+        changes: Receiver<Change<K, Endpoint>>,
+        tasks: ConnectionTasks,
+    ) -> Self {
+        let mut receivers = Vec::with_capacity(TIERS);
+        let senders = [(); TIERS].map(|()| {
+            let (tx, rx) = mpsc::unbounded_channel();
+            receivers.push(rx);
+            tx
+        });
+        let mut receivers = receivers.into_iter();
+        let tiers = [(); TIERS].map(|()| {
+            Balance::new(SubsetDiscover::new(
+                receivers.next().expect("a receiver per tier"),
+            ))
+        });
+
+        Self {
+            local,
+            changes,
+            tasks,
+            endpoints: HashMap::new(),
+            senders,
+            tiers,
+            ready: 0,
+        }
+    }
+
+    fn tier(&self, endpoint: &Endpoint) -> usize {
+        match &endpoint.locality {
+            Some(locality) if *locality == self.local => 0,
+            Some(locality) if locality.region == self.local.region => 1,
+            _ => 2,
+        }
+    }
+
+    fn is_available(&self, tier: usize) -> bool {
+        self.endpoints
+            .values()
+            .filter(|(t, _)| *t == tier)
+            .any(|(_, breaker)| {
+                breaker
+                    .as_ref()
+                    .map_or(true, |breaker| breaker.state() != CircuitState::Open)
+            })
+    }
+
+    // Dispatch the endpoint changes to the balancers of their tiers.
+    fn poll_changes(&mut self, cx: &mut Context<'_>) {
+        while let Poll::Ready(Some(change)) = self.changes.poll_recv(cx) {
+            match change {
+                Change::Insert(k, endpoint) => {
+                    let tier = self.tier(&endpoint);
+                    let connection =
+                        Connection::lazy(endpoint.http_connector(), endpoint, self.tasks.clone());
+                    let breaker = connection.breaker();
+                    if let Some((previous, _)) = self.endpoints.insert(k.clone(), (tier, breaker)) {
+                        if previous != tier {
+                            let _ = self.senders[previous].send(TowerChange::Remove(k.clone()));
+                        }
+                    }
+                    let _ = self.senders[tier].send(TowerChange::Insert(k, connection));
+                }
+                Change::Remove(k) => {
+                    if let Some((tier, _)) = self.endpoints.remove(&k) {
+                        let _ = self.senders[tier].send(TowerChange::Remove(k));
+                    }
+                }
+                Change::Split(_) => {}
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Send + Clone + 'static> Service<Request<Body>> for LocalityBalance<K> {
+    type Response = Response<Body>;
+    type Error = crate::BoxError;
+    type Future = <TierBalance<K> as Service<Request<Body>>>::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.poll_changes(cx);
+
+        let mut ready = false;
+        for tier in 0..TIERS {
+            // Poll the balancers of all the tiers, so they process their endpoint changes.
+            if self.tiers[tier].poll_ready(cx)?.is_ready() && !ready && self.is_available(tier) {
+                self.ready = tier;
+                ready = true;
+            }
+        }
+
+        if ready {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        self.tiers[self.ready].call(req)
+    }
+}
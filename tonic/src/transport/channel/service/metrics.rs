@@ -0,0 +1,236 @@
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use http::{Request, Uri};
+use http_body::Frame;
+use pin_project::pin_project;
+use tower_service::Service;
+
+use crate::body::Body;
+
+/// A receiver of the metrics of the requests and connections of a channel.
+///
+/// The metrics are reported for each endpoint set with [`Endpoint::metrics_sink`]; balanced
+/// channels report the metrics of each of their endpoints, so the same sink is usually set on all
+/// of them. The methods are called on the request path and should not block. Each method does
+/// nothing by default.
+///
+/// ```
+/// # use std::time::Duration;
+/// # use http::Uri;
+/// # use tonic::transport::{channel::MetricsSink, Endpoint};
+/// struct Log;
+///
+/// impl MetricsSink for Log {
+///     fn connect_latency(&self, endpoint: &Uri, latency: Duration) {
+///         eprintln!("connected to {endpoint} in {latency:?}");
+///     }
+/// }
+///
+/// # let mut builder = Endpoint::from_static("https://example.com");
+/// builder.metrics_sink(Log);
+/// ```
+///
+/// [`Endpoint::metrics_sink`]: crate::transport::Endpoint::metrics_sink
+pub trait MetricsSink: Send + Sync + 'static {
+    /// The time a request spent in the buffer of the channel, from the call of the channel until
+    /// the channel starts picking an endpoint for it.
+    fn queue_time(&self, endpoint: &Uri, time: Duration) {
+        let _ = (endpoint, time);
+    }
+
+    /// The time taken to pick the endpoint of a request, waiting for an endpoint to be ready, once
+    /// taken out of the buffer.
+    fn pick_latency(&self, endpoint: &Uri, latency: Duration) {
+        let _ = (endpoint, latency);
+    }
+
+    /// The time taken to establish a connection to the endpoint, including the HTTP handshake.
+    fn connect_latency(&self, endpoint: &Uri, latency: Duration) {
+        let _ = (endpoint, latency);
+    }
+
+    /// The number of requests in flight on the endpoint, reported each time it changes.
+    ///
+    /// A request is in flight until its response body ends or is dropped.
+    fn in_flight(&self, endpoint: &Uri, in_flight: usize) {
+        let _ = (endpoint, in_flight);
+    }
+}
+
+/// The sink of the metrics of an endpoint.
+#[derive(Clone)]
+pub(crate) struct Metrics(Arc<dyn MetricsSink>);
+
+impl Metrics {
+    pub(crate) fn new(sink: impl MetricsSink) -> Self {
+        Self(Arc::new(sink))
+    }
+}
+
+impl fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Metrics").finish()
+    }
+}
+
+/// The metrics of the connection of a channel to an endpoint.
+pub(crate) struct EndpointMetrics {
+    sink: Metrics,
+    endpoint: Uri,
+    in_flight: AtomicUsize,
+}
+
+impl EndpointMetrics {
+    pub(crate) fn new(sink: Metrics, endpoint: Uri) -> Arc<Self> {
+        Arc::new(Self {
+            sink,
+            endpoint,
+            in_flight: AtomicUsize::new(0),
+        })
+    }
+
+    pub(crate) fn connected(&self, latency: Duration) {
+        self.sink.0.connect_latency(&self.endpoint, latency);
+    }
+
+    // Reports the times of a request once sent to the endpoint, and counts it in flight until the
+    // returned guard is dropped.
+    pub(crate) fn start(self: &Arc<Self>, req: &Request<Body>) -> InFlight {
+        if let Some(times) = req.extensions().get::<CallTimes>() {
+            let now = Instant::now();
+            let picking = times.picking.unwrap_or(now);
+            let queued = picking.saturating_duration_since(times.called);
+            self.sink.0.queue_time(&self.endpoint, queued);
+            let picked = now.saturating_duration_since(picking);
+            self.sink.0.pick_latency(&self.endpoint, picked);
+        }
+
+        let in_flight = self.in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+        self.sink.0.in_flight(&self.endpoint, in_flight);
+        InFlight(self.clone())
+    }
+}
+
+/// Counts a request in flight until dropped.
+pub(crate) struct InFlight(Arc<EndpointMetrics>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let in_flight = self.0.in_flight.fetch_sub(1, Ordering::Relaxed) - 1;
+        self.0.sink.0.in_flight(&self.0.endpoint, in_flight);
+    }
+}
+
+/// A response body, keeping its request in flight until it ends or is dropped.
+#[pin_project]
+pub(crate) struct InFlightBody {
+    #[pin]
+    inner: Body,
+    in_flight: Option<InFlight>,
+}
+
+impl InFlightBody {
+    pub(crate) fn new(inner: Body, in_flight: InFlight) -> Self {
+        Self {
+            inner,
+            in_flight: Some(in_flight),
+        }
+    }
+}
+
+impl http_body::Body for InFlightBody {
+    type Data = Bytes;
+    type Error = crate::Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let frame = this.inner.as_mut().poll_frame(cx);
+        let ended = match &frame {
+            Poll::Ready(Some(Ok(frame))) => frame.is_trailers() || this.inner.is_end_stream(),
+            Poll::Ready(_) => true,
+            Poll::Pending => false,
+        };
+        if ended {
+            this.in_flight.take();
+        }
+        frame
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+/// When a request was given to the channel, and when the channel started picking its endpoint.
+#[derive(Debug, Clone)]
+pub(crate) struct CallTimes {
+    called: Instant,
+    picking: Option<Instant>,
+}
+
+impl CallTimes {
+    pub(crate) fn now() -> Self {
+        Self {
+            called: Instant::now(),
+            picking: None,
+        }
+    }
+}
+
+/// Records in the [`CallTimes`] of the requests when the service started getting ready for them.
+///
+/// The service is in the worker of the buffer of the channel, which only polls it for readiness
+/// once it has taken a request out of the buffer.
+#[derive(Debug)]
+pub(crate) struct PickTimer<S> {
+    inner: S,
+    picking: Option<Instant>,
+}
+
+impl<S> PickTimer<S> {
+    pub(crate) fn new(inner: S) -> Self {
+        Self {
+            inner,
+            picking: None,
+        }
+    }
+}
+
+impl<S> Service<Request<Body>> for PickTimer<S>
+where
+    S: Service<Request<Body>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.picking.get_or_insert_with(Instant::now);
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        let picking = self.picking.take();
+        if let Some(times) = req.extensions_mut().get_mut::<CallTimes>() {
+            times.picking = picking;
+        }
+        self.inner.call(req)
+    }
+}
@@ -1,6 +1,9 @@
 mod add_origin;
 use self::add_origin::AddOrigin;
 
+mod static_metadata;
+use self::static_metadata::StaticMetadata;
+
 #[cfg(feature = "user-agent")]
 mod user_agent;
 #[cfg(feature = "user-agent")]
@@ -12,9 +15,28 @@ use self::reconnect::Reconnect;
 mod connection;
 pub(super) use self::connection::Connection;
 
+mod tasks;
+pub use self::tasks::ConnectionTasks;
+
 mod discover;
 pub use self::discover::Change;
-pub(super) use self::discover::DynamicServiceStream;
+pub(super) use self::discover::{DynamicServiceStream, SubsetDiscover};
+
+mod groups;
+pub(super) use self::groups::GroupBalance;
+pub use self::groups::TrafficSplit;
+
+mod locality;
+pub use self::locality::Locality;
+pub(super) use self::locality::LocalityBalance;
+
+mod events;
+pub use self::events::ConnectionEvent;
+pub(crate) use self::events::{ConnectInfo, ConnectionEvents};
+
+mod metrics;
+pub use self::metrics::MetricsSink;
+pub(crate) use self::metrics::{CallTimes, EndpointMetrics, InFlightBody, Metrics, PickTimer};
 
 mod io;
 use self::io::BoxedIo;
@@ -22,8 +44,7 @@ use self::io::BoxedIo;
 mod connector;
 pub(crate) use self::connector::Connector;
 
-mod executor;
-pub(super) use self::executor::{Executor, SharedExec};
+pub(super) use crate::transport::service::{Executor, SharedExec};
 
 #[cfg(feature = "_tls-any")]
 mod tls;
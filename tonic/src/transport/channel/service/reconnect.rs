@@ -4,11 +4,14 @@ use std::{
     future::Future,
     pin::Pin,
     task::{Context, Poll},
+    time::Duration,
 };
 use tower::make::MakeService;
 use tower_service::Service;
 use tracing::trace;
 
+use super::{ConnectionEvent, ConnectionEvents};
+
 pub(crate) struct Reconnect<M, Target>
 where
     M: Service<Target>,
@@ -20,6 +23,7 @@ where
     error: Option<crate::BoxError>,
     has_been_connected: bool,
     is_lazy: bool,
+    events: Option<ConnectionEvents>,
 }
 
 #[derive(Debug)]
@@ -34,7 +38,12 @@ where
     M: Service<Target>,
     M::Error: Into<crate::BoxError>,
 {
-    pub(crate) fn new(mk_service: M, target: Target, is_lazy: bool) -> Self {
+    pub(crate) fn new(
+        mk_service: M,
+        target: Target,
+        is_lazy: bool,
+        events: Option<ConnectionEvents>,
+    ) -> Self {
         Reconnect {
             mk_service,
             state: State::Idle,
@@ -42,6 +51,16 @@ where
             error: None,
             has_been_connected: false,
             is_lazy,
+            events,
+        }
+    }
+
+    // The service reconnects on the next call to `poll_ready`.
+    fn schedule_reconnect(&self) {
+        if let Some(events) = &self.events {
+            events.emit(ConnectionEvent::ReconnectScheduled {
+                backoff: Duration::ZERO,
+            });
         }
     }
 }
@@ -103,6 +122,7 @@ where
                                 let error = e.into();
                                 tracing::debug!("reconnect::poll_ready: {:?}", error);
                                 self.error = Some(error);
+                                self.schedule_reconnect();
                                 break;
                             }
                         }
@@ -125,6 +145,7 @@ where
                         Poll::Ready(Err(_)) => {
                             trace!("poll_ready; error");
                             state = State::Idle;
+                            self.schedule_reconnect();
                         }
                     }
                 }
@@ -0,0 +1,106 @@
+use http::{HeaderMap, Request};
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower_service::Service;
+
+/// Sets the metadata registered with [`Endpoint::static_metadata`] on the requests without it.
+///
+/// The metadata is validated once when registered, and its values are shared, so setting it on a
+/// request only clones references to them.
+///
+/// [`Endpoint::static_metadata`]: crate::transport::Endpoint::static_metadata
+#[derive(Debug)]
+pub(crate) struct StaticMetadata<T> {
+    inner: T,
+    metadata: Arc<HeaderMap>,
+}
+
+impl<T> StaticMetadata<T> {
+    pub(crate) fn new(inner: T, metadata: HeaderMap) -> Self {
+        Self {
+            inner,
+            metadata: Arc::new(metadata),
+        }
+    }
+}
+
+impl<T, ReqBody> Service<Request<ReqBody>> for StaticMetadata<T>
+where
+    T: Service<Request<ReqBody>>,
+{
+    type Response = T::Response;
+    type Error = T::Error;
+    type Future = T::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let headers = req.headers_mut();
+        headers.reserve(self.metadata.len());
+        for key in self.metadata.keys() {
+            // The metadata set on the request takes precedence.
+            if headers.contains_key(key) {
+                continue;
+            }
+            for value in self.metadata.get_all(key) {
+                headers.append(key.clone(), value.clone());
+            }
+        }
+
+        self.inner.call(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::HeaderValue;
+
+    #[derive(Debug, Clone, Copy)]
+    struct Svc;
+
+    impl Service<Request<()>> for Svc {
+        type Response = HeaderMap;
+        type Error = ();
+        type Future = std::future::Ready<Result<HeaderMap, ()>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, req: Request<()>) -> Self::Future {
+            std::future::ready(Ok(req.into_parts().0.headers))
+        }
+    }
+
+    fn metadata() -> HeaderMap {
+        let mut metadata = HeaderMap::new();
+        metadata.insert("x-api-key", HeaderValue::from_static("secret"));
+        metadata.append("x-tag", HeaderValue::from_static("a"));
+        metadata.append("x-tag", HeaderValue::from_static("b"));
+        metadata
+    }
+
+    #[tokio::test]
+    async fn sets_all_the_values() {
+        let mut svc = StaticMetadata::new(Svc, metadata());
+        let headers = svc.call(Request::new(())).await.unwrap();
+        assert_eq!(headers, metadata());
+    }
+
+    #[tokio::test]
+    async fn keeps_the_metadata_of_the_request() {
+        let mut svc = StaticMetadata::new(Svc, metadata());
+        let mut req = Request::new(());
+        req.headers_mut()
+            .insert("x-tag", HeaderValue::from_static("c"));
+        let headers = svc.call(req).await.unwrap();
+        assert_eq!(headers["x-api-key"], "secret");
+        let tags: Vec<_> = headers.get_all("x-tag").iter().collect();
+        assert_eq!(tags, ["c"]);
+    }
+}
@@ -0,0 +1,104 @@
+use std::{
+    fmt,
+    future::Future,
+    pin::pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use tokio::sync::Notify;
+
+/// The background tasks driving the connections of a [`Channel`].
+///
+/// Each connection of a channel is driven by a task spawned on the executor of its endpoint, see
+/// [`Endpoint::executor`]. A connection, and its task, completes once the channel stops using it:
+/// when it is replaced after a failure, when its endpoint is removed from a balanced channel, or
+/// when all the clones of the channel are dropped.
+///
+/// This handle stays valid after the channel is dropped, so tests can check that no connection
+/// task outlives it:
+///
+/// ```no_run
+/// # use tonic::transport::Channel;
+/// # async fn run(channel: Channel) {
+/// let tasks = channel.connection_tasks();
+/// drop(channel);
+/// tasks.wait().await;
+/// assert!(tasks.is_empty());
+/// # }
+/// ```
+///
+/// [`Channel`]: crate::transport::Channel
+/// [`Endpoint::executor`]: crate::transport::Endpoint::executor
+#[derive(Clone, Default)]
+pub struct ConnectionTasks {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    running: AtomicUsize,
+    // Notified when the last running task completes.
+    idle: Notify,
+}
+
+impl ConnectionTasks {
+    /// Returns the number of connection tasks still running.
+    pub fn len(&self) -> usize {
+        self.inner.running.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if no connection task is running.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Waits until all the connection tasks have completed.
+    ///
+    /// Connection tasks spawned while waiting are waited for too.
+    pub async fn wait(&self) {
+        loop {
+            let mut idle = pin!(self.inner.idle.notified());
+            idle.as_mut().enable();
+            if self.is_empty() {
+                return;
+            }
+            idle.await;
+        }
+    }
+
+    // Counts the task driving a connection as running until it completes, or the executor drops
+    // it.
+    pub(crate) fn track<F>(&self, task: F) -> impl Future<Output = ()> + Send + 'static
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.inner.running.fetch_add(1, Ordering::AcqRel);
+        let guard = Running(self.inner.clone());
+
+        async move {
+            task.await;
+            drop(guard);
+        }
+    }
+}
+
+impl fmt::Debug for ConnectionTasks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectionTasks")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+struct Running(Arc<Inner>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        if self.0.running.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
@@ -13,10 +13,12 @@ use tokio_rustls::{
     TlsConnector as RustlsConnector,
 };
 
-use super::io::BoxedIo;
+use super::{io::BoxedIo, ConnectInfo};
 use crate::transport::service::tls::{
     convert_certificate_to_pki_types, convert_identity_to_pki_types, TlsError, ALPN_H2,
 };
+
+const ALPN_HTTP1: &[u8] = b"http/1.1";
 use crate::transport::tls::{Certificate, Identity};
 
 #[derive(Clone)]
@@ -24,6 +26,7 @@ pub(crate) struct TlsConnector {
     config: Arc<ClientConfig>,
     domain: Arc<ServerName<'static>>,
     assume_http2: bool,
+    http1: bool,
     timeout: Option<Duration>,
 }
 
@@ -101,10 +104,22 @@ impl TlsConnector {
             config: Arc::new(config),
             domain: Arc::new(ServerName::try_from(domain)?.to_owned()),
             assume_http2,
+            http1: false,
             timeout,
         })
     }
 
+    /// Negotiates HTTP/1.1 rather than HTTP/2.
+    pub(crate) fn http1(self) -> Self {
+        let mut config = ClientConfig::clone(&self.config);
+        config.alpn_protocols = vec![ALPN_HTTP1.into()];
+        Self {
+            config: Arc::new(config),
+            http1: true,
+            ..self
+        }
+    }
+
     pub(crate) async fn connect<I>(&self, io: I) -> Result<BoxedIo, crate::BoxError>
     where
         I: AsyncRead + AsyncWrite + Send + Unpin + 'static,
@@ -120,12 +135,25 @@ impl TlsConnector {
 
         // Generally we require ALPN to be negotiated, but if the user has
         // explicitly set `assume_http2` to true, we'll allow it to be missing.
+        // Servers may not negotiate HTTP/1.1, which is then assumed.
         let (_, session) = io.get_ref();
         let alpn_protocol = session.alpn_protocol();
-        if !(alpn_protocol == Some(ALPN_H2) || self.assume_http2) {
+        if self.http1 {
+            if alpn_protocol.is_some_and(|protocol| protocol != ALPN_HTTP1) {
+                return Err(TlsError::Http1NotNegotiated.into());
+            }
+        } else if !(alpn_protocol == Some(ALPN_H2) || self.assume_http2) {
             return Err(TlsError::H2NotNegotiated.into());
         }
-        Ok(BoxedIo::new(TokioIo::new(io)))
+        let info = ConnectInfo {
+            addr: None,
+            alpn: alpn_protocol.map(<[u8]>::to_vec),
+            tls_version: session
+                .protocol_version()
+                .and_then(|version| version.as_str())
+                .map(str::to_owned),
+        };
+        Ok(BoxedIo::new(TokioIo::new(io)).with_info(info))
     }
 }
 
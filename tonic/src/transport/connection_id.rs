@@ -0,0 +1,44 @@
+use std::{
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// The id of the HTTP connection of a request or response.
+///
+/// The server inserts it into the extensions of each request, and channels into the extensions of
+/// each response, so the logs of the requests of a connection can be correlated with each other
+/// and with packet captures. Ids are unique within a process.
+///
+/// The SETTINGS of HTTP/2 peers are inserted along with it, as [`PeerSettings`]. hyper doesn't
+/// expose the ids of HTTP/2 streams, so streams aren't identified.
+///
+/// ```
+/// # use tonic::{transport::ConnectionId, Request};
+/// # fn handle(request: Request<()>) {
+/// if let Some(id) = request.extensions().get::<ConnectionId>() {
+///     println!("handling a request of connection {id}");
+/// }
+/// # }
+/// ```
+///
+/// [`PeerSettings`]: crate::transport::PeerSettings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConnectionId(u64);
+
+impl ConnectionId {
+    pub(crate) fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// The id as a number.
+    pub fn get(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ConnectionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
@@ -93,15 +93,19 @@ pub mod channel;
 #[cfg(feature = "server")]
 pub mod server;
 
+mod connection_id;
 mod error;
-mod service;
+mod peer_settings;
+pub(crate) mod service;
 #[cfg(feature = "_tls-any")]
 mod tls;
 
 #[doc(inline)]
 #[cfg(feature = "channel")]
 pub use self::channel::{Channel, Endpoint};
+pub use self::connection_id::ConnectionId;
 pub use self::error::Error;
+pub use self::peer_settings::PeerSettings;
 #[doc(inline)]
 #[cfg(feature = "server")]
 pub use self::server::Server;
@@ -0,0 +1,333 @@
+use std::{
+    io,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{ready, Context, Poll},
+};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::metadata::header_list_size;
+
+/// The SETTINGS sent by the HTTP/2 peer of the connection of a request or response.
+///
+/// The server inserts them into the extensions of each HTTP/2 request, and channels into the
+/// extensions of each response, as of the time the request or response was received. Along with
+/// the [`ConnectionId`], they help correlate application logs with packet captures.
+///
+/// hyper doesn't expose the ids of HTTP/2 streams, so streams aren't identified.
+///
+/// ```
+/// # use tonic::{transport::PeerSettings, Request};
+/// # fn handle(request: Request<()>) {
+/// if let Some(settings) = request.extensions().get::<PeerSettings>() {
+///     println!(
+///         "the client accepts {:?} concurrent streams",
+///         settings.max_concurrent_streams()
+///     );
+/// }
+/// # }
+/// ```
+///
+/// [`ConnectionId`]: crate::transport::ConnectionId
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PeerSettings {
+    max_concurrent_streams: Option<u32>,
+    initial_window_size: Option<u32>,
+    max_header_list_size: Option<u32>,
+}
+
+impl PeerSettings {
+    /// The `SETTINGS_MAX_CONCURRENT_STREAMS` of the peer, or `None` if it didn't send one, in
+    /// which case the number of streams is unlimited.
+    pub fn max_concurrent_streams(&self) -> Option<u32> {
+        self.max_concurrent_streams
+    }
+
+    /// The `SETTINGS_INITIAL_WINDOW_SIZE` of the peer, or `None` if it didn't send one, in which
+    /// case the window of its streams is 65,535 bytes.
+    pub fn initial_window_size(&self) -> Option<u32> {
+        self.initial_window_size
+    }
+
+    /// The `SETTINGS_MAX_HEADER_LIST_SIZE` of the peer, or `None` if it didn't send one, in which
+    /// case the size of the header lists it accepts is unlimited.
+    pub fn max_header_list_size(&self) -> Option<u32> {
+        self.max_header_list_size
+    }
+
+    // The size of `headers`, with the max header list size of the peer, if it exceeds it. The
+    // peer resets the streams of such header lists, without telling why.
+    pub(crate) fn header_list_size_exceeded(
+        &self,
+        headers: &http::HeaderMap,
+    ) -> Option<(usize, u32)> {
+        let max = self.max_header_list_size?;
+        let size = header_list_size(headers);
+        (size > max as usize).then_some((size, max))
+    }
+}
+
+/// The SETTINGS of the peer of a connection, updated by its [`PeerSettingsIo`].
+#[derive(Clone, Default)]
+pub(crate) struct SharedPeerSettings(Arc<Mutex<Option<PeerSettings>>>);
+
+impl SharedPeerSettings {
+    /// The last SETTINGS of the peer, or `None` until the peer sends its first SETTINGS frame,
+    /// e.g. when the connection is not an HTTP/2 one.
+    pub(crate) fn get(&self) -> Option<PeerSettings> {
+        *self.0.lock().unwrap()
+    }
+
+    fn apply(&self, entries: &[(u16, u32)]) {
+        let mut settings = self.0.lock().unwrap();
+        let settings = settings.get_or_insert_with(PeerSettings::default);
+        for &(id, value) in entries {
+            match id {
+                SETTINGS_MAX_CONCURRENT_STREAMS => settings.max_concurrent_streams = Some(value),
+                SETTINGS_INITIAL_WINDOW_SIZE => settings.initial_window_size = Some(value),
+                SETTINGS_MAX_HEADER_LIST_SIZE => settings.max_header_list_size = Some(value),
+                _ => {}
+            }
+        }
+    }
+}
+
+const PREFACE: &[u8] = b"PRI * HTTP/2.0\r\n\r\nSM\r\n\r\n";
+const FRAME_HEADER_LEN: usize = 9;
+const FRAME_TYPE_SETTINGS: u8 = 0x4;
+const FLAG_ACK: u8 = 0x1;
+const SETTINGS_MAX_CONCURRENT_STREAMS: u16 = 0x3;
+const SETTINGS_INITIAL_WINDOW_SIZE: u16 = 0x4;
+const SETTINGS_MAX_HEADER_LIST_SIZE: u16 = 0x6;
+
+/// An IO recording the SETTINGS frames read from the HTTP/2 peer (RFC 9113, section 6.5).
+///
+/// Only the frame headers, and the payload of SETTINGS frames, are decoded; the other frames are
+/// skipped by their length. On the server side, connections not starting with the HTTP/2 client
+/// preface, e.g. HTTP/1 ones, are not decoded.
+pub(crate) struct PeerSettingsIo<T> {
+    inner: T,
+    settings: SharedPeerSettings,
+    frames: FrameReader,
+}
+
+impl<T> PeerSettingsIo<T> {
+    /// Wraps the IO of a server connection, which starts with the client preface.
+    #[cfg(feature = "server")]
+    pub(crate) fn server(inner: T, settings: SharedPeerSettings) -> Self {
+        Self::new(inner, settings, 0)
+    }
+
+    /// Wraps the IO of a client connection.
+    #[cfg(feature = "channel")]
+    pub(crate) fn client(inner: T, settings: SharedPeerSettings) -> Self {
+        Self::new(inner, settings, PREFACE.len())
+    }
+
+    fn new(inner: T, settings: SharedPeerSettings, preface_read: usize) -> Self {
+        Self {
+            inner,
+            settings,
+            frames: FrameReader {
+                preface_read,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for PeerSettingsIo<T> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let filled = buf.filled().len();
+        ready!(Pin::new(&mut self.inner).poll_read(cx, buf))?;
+
+        let this = &mut *self;
+        let entries = this.frames.read(&buf.filled()[filled..]);
+        if !entries.is_empty() {
+            this.settings.apply(&entries);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for PeerSettingsIo<T> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.inner.is_write_vectored()
+    }
+}
+
+// Decodes the frames read from a connection, as they are read.
+#[derive(Default)]
+struct FrameReader {
+    // The number of bytes of the client preface read so far.
+    preface_read: usize,
+    // The header of the current frame, complete once `header_len` reaches `FRAME_HEADER_LEN`.
+    header: [u8; FRAME_HEADER_LEN],
+    header_len: usize,
+    // The number of bytes of the payload of the current frame not read yet.
+    payload_left: usize,
+    // Whether the current frame is a SETTINGS frame, other than an acknowledgement.
+    settings: bool,
+    // The current setting of a SETTINGS frame, complete once `entry_len` reaches 6.
+    entry: [u8; 6],
+    entry_len: usize,
+    // Whether the connection is not an HTTP/2 one.
+    done: bool,
+}
+
+impl FrameReader {
+    // Reads the next bytes of the connection, returning the settings they complete.
+    fn read(&mut self, mut data: &[u8]) -> Vec<(u16, u32)> {
+        let mut entries = Vec::new();
+
+        while !data.is_empty() && !self.done {
+            if self.preface_read < PREFACE.len() {
+                let n = data.len().min(PREFACE.len() - self.preface_read);
+                if data[..n] != PREFACE[self.preface_read..self.preface_read + n] {
+                    self.done = true;
+                    break;
+                }
+                self.preface_read += n;
+                data = &data[n..];
+                continue;
+            }
+
+            if self.header_len < FRAME_HEADER_LEN {
+                let n = data.len().min(FRAME_HEADER_LEN - self.header_len);
+                self.header[self.header_len..self.header_len + n].copy_from_slice(&data[..n]);
+                self.header_len += n;
+                data = &data[n..];
+
+                if self.header_len == FRAME_HEADER_LEN {
+                    let [l0, l1, l2, kind, flags, ..] = self.header;
+                    self.payload_left = u32::from_be_bytes([0, l0, l1, l2]) as usize;
+                    self.settings = kind == FRAME_TYPE_SETTINGS && flags & FLAG_ACK == 0;
+                    self.entry_len = 0;
+                    if self.payload_left == 0 {
+                        self.header_len = 0;
+                    }
+                }
+                continue;
+            }
+
+            let n = data.len().min(self.payload_left);
+            if self.settings {
+                for &b in &data[..n] {
+                    self.entry[self.entry_len] = b;
+                    self.entry_len += 1;
+                    if self.entry_len == self.entry.len() {
+                        let [i0, i1, v0, v1, v2, v3] = self.entry;
+                        entries.push((
+                            u16::from_be_bytes([i0, i1]),
+                            u32::from_be_bytes([v0, v1, v2, v3]),
+                        ));
+                        self.entry_len = 0;
+                    }
+                }
+            }
+            self.payload_left -= n;
+            data = &data[n..];
+            if self.payload_left == 0 {
+                self.header_len = 0;
+            }
+        }
+
+        entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(kind: u8, flags: u8, payload: &[u8]) -> Vec<u8> {
+        let len = (payload.len() as u32).to_be_bytes();
+        let mut frame = vec![len[1], len[2], len[3], kind, flags, 0, 0, 0, 0];
+        frame.extend_from_slice(payload);
+        frame
+    }
+
+    fn settings(entries: &[(u16, u32)]) -> Vec<u8> {
+        let mut payload = Vec::new();
+        for (id, value) in entries {
+            payload.extend_from_slice(&id.to_be_bytes());
+            payload.extend_from_slice(&value.to_be_bytes());
+        }
+        frame(FRAME_TYPE_SETTINGS, 0, &payload)
+    }
+
+    #[test]
+    fn reads_settings_split_across_reads() {
+        let mut data = PREFACE.to_vec();
+        data.extend(settings(&[(0x1, 4096), (0x3, 100), (0x4, 1 << 20)]));
+        // A WINDOW_UPDATE, a settings acknowledgement and an empty SETTINGS frame.
+        data.extend(frame(0x8, 0, &[0, 0, 1, 0]));
+        data.extend(frame(FRAME_TYPE_SETTINGS, FLAG_ACK, &[]));
+        data.extend(settings(&[]));
+        data.extend(settings(&[(0x3, 10)]));
+
+        let mut reader = FrameReader::default();
+        let mut entries = Vec::new();
+        for chunk in data.chunks(5) {
+            entries.extend(reader.read(chunk));
+        }
+
+        assert_eq!(
+            entries,
+            [(0x1, 4096), (0x3, 100), (0x4, 1 << 20), (0x3, 10)]
+        );
+    }
+
+    #[test]
+    fn skips_connections_without_preface() {
+        let mut reader = FrameReader::default();
+
+        assert!(reader
+            .read(b"POST /test.Test/UnaryCall HTTP/1.1\r\n")
+            .is_empty());
+        assert!(reader.read(&settings(&[(0x3, 100)])).is_empty());
+    }
+
+    #[test]
+    fn applies_settings() {
+        let shared = SharedPeerSettings::default();
+        assert_eq!(shared.get(), None);
+
+        shared.apply(&[(0x3, 100)]);
+        shared.apply(&[(0x4, 1 << 20), (0x3, 10), (0x6, 8192)]);
+
+        let settings = shared.get().unwrap();
+        assert_eq!(settings.max_concurrent_streams(), Some(10));
+        assert_eq!(settings.initial_window_size(), Some(1 << 20));
+        assert_eq!(settings.max_header_list_size(), Some(8192));
+    }
+}
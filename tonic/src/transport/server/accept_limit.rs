@@ -0,0 +1,137 @@
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+    time::Duration,
+};
+
+use tokio::time::Instant;
+
+/// Limits the rate of the connections accepted from each IP address.
+///
+/// Each IP address has a token bucket holding as many tokens as the connections per second it
+/// may open, refilled at that rate. Connections from an IP address without tokens are closed as
+/// soon as they are accepted, which protects the server against the reconnect storms of
+/// misbehaving clients.
+///
+/// See [`Server::accept_rate_limit`] and [`TcpIncoming::with_accept_rate_limit`].
+///
+/// [`Server::accept_rate_limit`]: super::Server::accept_rate_limit
+/// [`TcpIncoming::with_accept_rate_limit`]: super::TcpIncoming::with_accept_rate_limit
+///
+/// ```
+/// # use tonic::transport::server::AcceptRateLimit;
+/// AcceptRateLimit::per_ip(10).exempt("10.0.0.1".parse().unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct AcceptRateLimit {
+    per_second: u32,
+    exempt: HashSet<IpAddr>,
+}
+
+impl AcceptRateLimit {
+    /// Accept at most `per_second` connections per second from each IP address.
+    pub fn per_ip(per_second: u32) -> Self {
+        Self {
+            per_second,
+            exempt: HashSet::new(),
+        }
+    }
+
+    /// Accept the connections from `ip` without limit.
+    pub fn exempt(mut self, ip: IpAddr) -> Self {
+        self.exempt.insert(ip.to_canonical());
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// The token buckets of the IP addresses connecting to a listener.
+#[derive(Debug)]
+pub(crate) struct AcceptLimiter {
+    limit: AcceptRateLimit,
+    buckets: HashMap<IpAddr, Bucket>,
+    pruned: Instant,
+}
+
+impl AcceptLimiter {
+    pub(crate) fn new(limit: AcceptRateLimit) -> Self {
+        Self {
+            limit,
+            buckets: HashMap::new(),
+            pruned: Instant::now(),
+        }
+    }
+
+    /// Whether a connection from `ip` is accepted, taking one of its tokens if so.
+    pub(crate) fn accept(&mut self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        if self.limit.exempt.contains(&ip) {
+            return true;
+        }
+
+        let now = Instant::now();
+        let capacity = f64::from(self.limit.per_second);
+        // Full buckets are the same as missing ones.
+        if now.duration_since(self.pruned) >= Duration::from_secs(1) {
+            self.buckets
+                .retain(|_, bucket| now.duration_since(bucket.refilled) < Duration::from_secs(1));
+            self.pruned = now;
+        }
+
+        let bucket = self.buckets.entry(ip).or_insert(Bucket {
+            tokens: capacity,
+            refilled: now,
+        });
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * capacity).min(capacity);
+        bucket.refilled = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn limits_each_ip() {
+        let exempt: IpAddr = "10.0.0.3".parse().unwrap();
+        let mut limiter = AcceptLimiter::new(AcceptRateLimit::per_ip(2).exempt(exempt));
+        let [a, b] = ["10.0.0.1", "10.0.0.2"].map(|ip| ip.parse::<IpAddr>().unwrap());
+
+        assert!(limiter.accept(a));
+        assert!(limiter.accept(a));
+        assert!(!limiter.accept(a));
+        assert!(limiter.accept(b));
+        for _ in 0..10 {
+            assert!(limiter.accept(exempt));
+        }
+
+        tokio::time::advance(Duration::from_millis(500)).await;
+        assert!(limiter.accept(a));
+        assert!(!limiter.accept(a));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn matches_ipv4_mapped_addresses() {
+        let mut limiter = AcceptLimiter::new(
+            AcceptRateLimit::per_ip(1).exempt("::ffff:10.0.0.1".parse().unwrap()),
+        );
+        for _ in 0..2 {
+            assert!(limiter.accept("10.0.0.1".parse().unwrap()));
+        }
+        assert!(limiter.accept("::ffff:10.0.0.2".parse().unwrap()));
+        assert!(!limiter.accept("10.0.0.2".parse().unwrap()));
+    }
+}
@@ -0,0 +1,72 @@
+use std::time::Duration;
+
+use tokio::sync::watch;
+
+/// Notice of the graceful shutdown of the connection of a request, found in the extensions of
+/// every request served by a [`Server`].
+///
+/// The connections are shut down gracefully when the signal of [`Server::serve_with_shutdown`]
+/// completes, or when they reach their [maximum age](super::Server::max_connection_age). They then stop
+/// accepting streams, but wait for the streams open to end, up to the
+/// [shutdown timeout](super::Server::graceful_shutdown_timeout). Long-lived streaming handlers wait for
+/// the notice to send their final messages and end their streams before being reset:
+///
+/// ```
+/// # use tonic::{transport::server::Drain, Request};
+/// # async fn send_update() {}
+/// # async fn send_final_message() {}
+/// # async fn handle(req: Request<()>) {
+/// let drain = req.extensions().get::<Drain>().cloned().unwrap();
+/// loop {
+///     tokio::select! {
+///         _ = send_update() => {}
+///         _ = drain.draining() => break,
+///     }
+/// }
+/// send_final_message().await;
+/// # }
+/// ```
+///
+/// [`Server`]: super::Server
+/// [`Server::serve_with_shutdown`]: super::Server::serve_with_shutdown
+#[derive(Debug, Clone)]
+pub struct Drain {
+    rx: watch::Receiver<bool>,
+}
+
+impl Drain {
+    /// Whether the graceful shutdown of the connection started, or the connection is closed.
+    pub fn is_draining(&self) -> bool {
+        *self.rx.borrow() || self.rx.has_changed().is_err()
+    }
+
+    /// Wait until the graceful shutdown of the connection starts, or the connection is closed.
+    pub async fn draining(&self) {
+        let mut rx = self.rx.clone();
+        let _ = rx.wait_for(|draining| *draining).await;
+    }
+}
+
+// How a connection is shut down: by the signal of the server, and within its timeout.
+pub(crate) struct ConnectionShutdown {
+    pub(crate) signal: Option<watch::Receiver<()>>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) drain: watch::Sender<bool>,
+}
+
+impl ConnectionShutdown {
+    pub(crate) fn new(
+        signal: Option<watch::Receiver<()>>,
+        timeout: Option<Duration>,
+    ) -> (Self, Drain) {
+        let (drain, rx) = watch::channel(false);
+        (
+            Self {
+                signal,
+                timeout,
+                drain,
+            },
+            Drain { rx },
+        )
+    }
+}
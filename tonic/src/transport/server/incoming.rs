@@ -8,7 +8,9 @@ use std::{
 use socket2::TcpKeepalive;
 use tokio::net::{TcpListener, TcpStream};
 use tokio_stream::{wrappers::TcpListenerStream, Stream};
-use tracing::warn;
+use tracing::{debug, warn};
+
+use super::accept_limit::{AcceptLimiter, AcceptRateLimit};
 
 /// Binds a socket address for a [Router](super::Router)
 ///
@@ -22,6 +24,7 @@ pub struct TcpIncoming {
     keepalive_time: Option<Duration>,
     keepalive_interval: Option<Duration>,
     keepalive_retries: Option<u32>,
+    accept_limit: Option<AcceptLimiter>,
 }
 
 impl TcpIncoming {
@@ -107,6 +110,14 @@ impl TcpIncoming {
         }
     }
 
+    /// Limits the rate of the connections accepted from each IP address.
+    pub fn with_accept_rate_limit(self, limit: Option<AcceptRateLimit>) -> Self {
+        Self {
+            accept_limit: limit.map(AcceptLimiter::new),
+            ..self
+        }
+    }
+
     /// Returns the local address that this tcp incoming is bound to.
     pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
         self.inner.as_ref().local_addr()
@@ -122,6 +133,7 @@ impl From<TcpListener> for TcpIncoming {
             keepalive_time: None,
             keepalive_interval: None,
             keepalive_retries: None,
+            accept_limit: None,
         }
     }
 }
@@ -130,13 +142,21 @@ impl Stream for TcpIncoming {
     type Item = std::io::Result<TcpStream>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let polled = Pin::new(&mut self.inner).poll_next(cx);
+        loop {
+            let polled = Pin::new(&mut self.inner).poll_next(cx);
 
-        if let Poll::Ready(Some(Ok(stream))) = &polled {
-            set_accepted_socket_options(stream, self.nodelay, &self.keepalive);
-        }
+            if let Poll::Ready(Some(Ok(stream))) = &polled {
+                if let (Some(limiter), Ok(addr)) = (&mut self.accept_limit, stream.peer_addr()) {
+                    if !limiter.accept(addr.ip()) {
+                        debug!("closing connection from {addr}: accept rate limit exceeded");
+                        continue;
+                    }
+                }
+                set_accepted_socket_options(stream, self.nodelay, &self.keepalive);
+            }
 
-        polled
+            return polled;
+        }
     }
 }
 
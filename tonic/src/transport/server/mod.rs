@@ -1,6 +1,8 @@
 //! Server implementation and builder.
 
+mod accept_limit;
 mod conn;
+mod drain;
 mod incoming;
 mod io_stream;
 mod service;
@@ -8,6 +10,8 @@ mod service;
 mod tls;
 #[cfg(unix)]
 mod unix;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+mod uring;
 
 use tokio_stream::StreamExt as _;
 use tracing::{debug, trace};
@@ -20,8 +24,11 @@ use std::convert::Infallible;
 
 pub use conn::{Connected, TcpConnectInfo};
 use hyper_util::{
-    rt::{TokioExecutor, TokioIo, TokioTimer},
-    server::conn::auto::{Builder as ConnectionBuilder, HttpServerConnExec},
+    rt::TokioIo,
+    server::{
+        conn::auto::{Builder as ConnectionBuilder, HttpServerConnExec},
+        graceful::GracefulConnection,
+    },
     service::TowerToHyperService,
 };
 #[cfg(feature = "_tls-any")]
@@ -36,19 +43,32 @@ use self::service::TlsAcceptor;
 #[cfg(unix)]
 pub use unix::UdsConnectInfo;
 
+pub use accept_limit::AcceptRateLimit;
+pub use drain::Drain;
 pub use incoming::TcpIncoming;
+#[cfg(all(feature = "io-uring", target_os = "linux"))]
+pub use uring::{UringIncoming, UringStream};
 
 #[cfg(feature = "_tls-any")]
 use crate::transport::Error;
 
-use self::service::{ConnectInfoLayer, ServerIo};
-use super::service::GrpcTimeout;
-use crate::body::Body;
+use self::drain::ConnectionShutdown;
+use self::service::{
+    ConnectInfoLayer, InitialMessageTimeout, Load, Pushback, ServerIo, StreamKeepalive,
+};
+use super::{
+    peer_settings::{PeerSettingsIo, SharedPeerSettings},
+    service::{Executor, GrpcTimeout, SharedExec, SharedTimer},
+    ConnectionId,
+};
 use crate::service::RecoverErrorLayer;
+use crate::{body::Body, Status};
 use bytes::Bytes;
 use http::{Request, Response};
 use http_body_util::BodyExt;
-use hyper::{body::Incoming, service::Service as HyperService};
+use hyper::{
+    body::Incoming, server::conn::http2::Builder as Http2Builder, service::Service as HyperService,
+};
 use pin_project::pin_project;
 use std::{
     fmt,
@@ -64,7 +84,7 @@ use tokio::io::{AsyncRead, AsyncWrite};
 use tokio_stream::Stream;
 use tower::{
     layer::util::{Identity, Stack},
-    layer::Layer,
+    layer::{layer_fn, Layer},
     limit::concurrency::ConcurrencyLimitLayer,
     load_shed::LoadShedLayer,
     util::BoxCloneService,
@@ -89,7 +109,13 @@ pub struct Server<L = Identity> {
     trace_interceptor: Option<TraceInterceptor>,
     concurrency_limit: Option<usize>,
     load_shed: bool,
+    overload_pushback: Option<Duration>,
     timeout: Option<Duration>,
+    default_grpc_timeout: Option<Duration>,
+    max_grpc_timeout: Option<Duration>,
+    request_header_timeout: Option<Duration>,
+    initial_message_timeout: Option<Duration>,
+    stream_keepalive_interval: Option<Duration>,
     #[cfg(feature = "_tls-any")]
     tls: Option<TlsAcceptor>,
     init_stream_window_size: Option<u32>,
@@ -97,15 +123,24 @@ pub struct Server<L = Identity> {
     max_concurrent_streams: Option<u32>,
     tcp_keepalive: Option<Duration>,
     tcp_nodelay: bool,
+    accept_rate_limit: Option<AcceptRateLimit>,
     http2_keepalive_interval: Option<Duration>,
     http2_keepalive_timeout: Duration,
     http2_adaptive_window: Option<bool>,
     http2_max_pending_accept_reset_streams: Option<usize>,
     http2_max_header_list_size: Option<u32>,
+    http2_max_send_buf_size: Option<usize>,
+    http2_header_table_size: Option<u32>,
     max_frame_size: Option<u32>,
     accept_http1: bool,
+    accept_http1_upgrades: bool,
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    io_uring: bool,
     service_builder: ServiceBuilder<L>,
     max_connection_age: Option<Duration>,
+    graceful_shutdown_timeout: Option<Duration>,
+    timer: SharedTimer,
+    executor: SharedExec,
 }
 
 impl Default for Server<Identity> {
@@ -114,7 +149,13 @@ impl Default for Server<Identity> {
             trace_interceptor: None,
             concurrency_limit: None,
             load_shed: false,
+            overload_pushback: None,
             timeout: None,
+            default_grpc_timeout: None,
+            max_grpc_timeout: None,
+            request_header_timeout: None,
+            initial_message_timeout: None,
+            stream_keepalive_interval: None,
             #[cfg(feature = "_tls-any")]
             tls: None,
             init_stream_window_size: None,
@@ -122,15 +163,24 @@ impl Default for Server<Identity> {
             max_concurrent_streams: None,
             tcp_keepalive: None,
             tcp_nodelay: false,
+            accept_rate_limit: None,
             http2_keepalive_interval: None,
             http2_keepalive_timeout: DEFAULT_HTTP2_KEEPALIVE_TIMEOUT,
             http2_adaptive_window: None,
             http2_max_pending_accept_reset_streams: None,
             http2_max_header_list_size: None,
+            http2_max_send_buf_size: None,
+            http2_header_table_size: None,
             max_frame_size: None,
             accept_http1: false,
+            accept_http1_upgrades: false,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            io_uring: false,
             service_builder: Default::default(),
             max_connection_age: None,
+            graceful_shutdown_timeout: None,
+            timer: SharedTimer::tokio(),
+            executor: SharedExec::tokio(),
         }
     }
 }
@@ -203,6 +253,36 @@ impl<L> Server<L> {
         Server { load_shed, ..self }
     }
 
+    /// Answer the requests rejected by load shedding with `UNAVAILABLE` and a [`RetryInfo`]
+    /// detail, instead of `RESOURCE_EXHAUSTED`. Disabled by default.
+    ///
+    /// The retry delay recommended is `base_delay`, multiplied by the number of connections'
+    /// worth of requests in flight over the whole server, up to 16 times, so that clients back off
+    /// for longer, and more coherently, as the load of the server grows. Only applies along with
+    /// [`Server::load_shed`] and [`Server::concurrency_limit_per_connection`]. The `RetryInfo`
+    /// detail requires the `prost` feature.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::time::Duration;
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder
+    ///     .concurrency_limit_per_connection(32)
+    ///     .load_shed(true)
+    ///     .overload_pushback(Duration::from_millis(100));
+    /// ```
+    ///
+    /// [`RetryInfo`]: https://docs.rs/tonic-types/latest/tonic_types/struct.RetryInfo.html
+    #[must_use]
+    pub fn overload_pushback(self, base_delay: Duration) -> Self {
+        Server {
+            overload_pushback: Some(base_delay),
+            ..self
+        }
+    }
+
     /// Set a timeout on for all request handlers.
     ///
     /// # Example
@@ -222,9 +302,137 @@ impl<L> Server<L> {
         }
     }
 
+    /// Set the timeout of the requests without a `grpc-timeout` header.
+    ///
+    /// The timeout applies as if the client had sent it, so it is clamped
+    /// by [`Server::max_grpc_timeout`] and is exposed to handlers by
+    /// [`Request::deadline`](crate::Request::deadline).
+    ///
+    /// Default is no timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.default_grpc_timeout(Duration::from_secs(10));
+    /// ```
+    #[must_use]
+    pub fn default_grpc_timeout(self, timeout: Duration) -> Self {
+        Server {
+            default_grpc_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set the max timeout of requests, clamping the `grpc-timeout` header.
+    ///
+    /// Requests with a longer `grpc-timeout`, or without one and without a
+    /// [`Server::default_grpc_timeout`], time out after `timeout`, and
+    /// [`Request::deadline`](crate::Request::deadline) returns the clamped
+    /// deadline.
+    ///
+    /// Default is no max.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.max_grpc_timeout(Duration::from_secs(60));
+    /// ```
+    #[must_use]
+    pub fn max_grpc_timeout(self, timeout: Duration) -> Self {
+        Server {
+            max_grpc_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set a timeout for reading the headers of requests.
+    ///
+    /// Connections are closed when the headers of a request aren't received
+    /// within the timeout. This applies to HTTP/1 requests, see
+    /// [`Server::accept_http1`]; HTTP/2 requests are dispatched once their
+    /// headers are received.
+    ///
+    /// Default is no timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.request_header_timeout(Duration::from_secs(10));
+    /// ```
+    #[must_use]
+    pub fn request_header_timeout(self, timeout: Duration) -> Self {
+        Server {
+            request_header_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set a timeout between the opening of a stream and its first message.
+    ///
+    /// The request stream fails with `DeadlineExceeded` when the client
+    /// doesn't send a message or close the stream within the timeout, so
+    /// handlers waiting on it end, rather than holding their state
+    /// indefinitely.
+    ///
+    /// Default is no timeout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.initial_message_timeout(Duration::from_secs(10));
+    /// ```
+    #[must_use]
+    pub fn initial_message_timeout(self, timeout: Duration) -> Self {
+        Server {
+            initial_message_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set the interval after which an empty `DATA` frame is sent on response streams without
+    /// traffic.
+    ///
+    /// HTTP/2 pings, set with [`Server::http2_keepalive_interval`], keep connections alive for the
+    /// peers, but some middleboxes ignore them and close the connections whose streams carry no
+    /// data for a while. The empty frames keep long-lived streams, such as subscriptions, busy for
+    /// them. They carry no message, so clients don't see them, and need no flow control capacity.
+    ///
+    /// Default is no empty frames.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.stream_keepalive_interval(Duration::from_secs(30));
+    /// ```
+    #[must_use]
+    pub fn stream_keepalive_interval(self, interval: Duration) -> Self {
+        Server {
+            stream_keepalive_interval: Some(interval),
+            ..self
+        }
+    }
+
     /// Sets the [`SETTINGS_INITIAL_WINDOW_SIZE`][spec] option for HTTP2
     /// stream-level flow control.
     ///
+    /// This window bounds the data the clients send on each stream before the server reads it,
+    /// see [`Server::http2_max_send_buf_size`] for the data the server sends.
+    ///
     /// Default is 65,535
     ///
     /// [spec]: https://httpwg.org/specs/rfc9113.html#InitialWindowSize
@@ -238,6 +446,9 @@ impl<L> Server<L> {
 
     /// Sets the max connection-level flow control for HTTP2
     ///
+    /// Like [`Server::initial_stream_window_size`], this window bounds the data received from the
+    /// clients, over all the streams of each connection.
+    ///
     /// Default is 65,535
     #[must_use]
     pub fn initial_connection_window_size(self, sz: impl Into<Option<u32>>) -> Self {
@@ -282,6 +493,80 @@ impl<L> Server<L> {
         }
     }
 
+    /// Set how long the streams open on a connection are waited for once its graceful shutdown
+    /// started, with the signal of [`Server::serve_with_shutdown`] or at its
+    /// [maximum age](Server::max_connection_age), before the connection is closed, resetting them.
+    ///
+    /// The handlers of the streams are notified that the shutdown started by the [`Drain`] in the
+    /// extensions of their requests, to end their streams in time.
+    ///
+    /// Default is no timeout, waiting for all the streams to end.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # use std::time::Duration;
+    /// # let builder = Server::builder();
+    /// builder.graceful_shutdown_timeout(Duration::from_secs(30));
+    /// ```
+    #[must_use]
+    pub fn graceful_shutdown_timeout(self, timeout: Duration) -> Self {
+        Server {
+            graceful_shutdown_timeout: Some(timeout),
+            ..self
+        }
+    }
+
+    /// Set the timer of the server, used for the timeouts of its requests, see
+    /// [`Server::timeout`], for its HTTP2 keep alive pings, and for its request header timeout.
+    ///
+    /// Default is a timer of Tokio, so that tests pausing its time with `tokio::time::pause`
+    /// control the timeouts of the server. Other runtimes supply their own timers.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.timer(hyper_util::rt::TokioTimer::new());
+    /// ```
+    #[must_use]
+    pub fn timer<T>(self, timer: T) -> Self
+    where
+        T: hyper::rt::Timer + Send + Sync + 'static,
+    {
+        Server {
+            timer: SharedTimer::new(timer),
+            ..self
+        }
+    }
+
+    /// Set the executor spawning the connections of the server, and the tasks of their HTTP2
+    /// streams.
+    ///
+    /// Default is `tokio::spawn`. Along with [`Server::timer`], and the connections of another
+    /// runtime served with [`Router::serve_with_incoming`], e.g. through a compatibility wrapper
+    /// implementing Tokio's `AsyncRead` and `AsyncWrite`, it runs the server on other runtimes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::Server;
+    /// # let builder = Server::builder();
+    /// builder.executor(hyper_util::rt::TokioExecutor::new());
+    /// ```
+    #[must_use]
+    pub fn executor<E>(self, executor: E) -> Self
+    where
+        E: Executor<Pin<Box<dyn Future<Output = ()> + Send>>> + Send + Sync + 'static,
+    {
+        Server {
+            executor: SharedExec::new(executor),
+            ..self
+        }
+    }
+
     /// Set whether HTTP2 Ping frames are enabled on accepted connections.
     ///
     /// If `None` is specified, HTTP2 keepalive is disabled, otherwise the duration
@@ -356,6 +641,30 @@ impl<L> Server<L> {
         }
     }
 
+    /// Limit the rate of the connections accepted from each IP address.
+    ///
+    /// Connections beyond the limit are closed as soon as they are accepted.
+    /// This applies to the servers bound by [`Router::serve`] and
+    /// [`Router::serve_with_shutdown`]; see
+    /// [`TcpIncoming::with_accept_rate_limit`] for the others.
+    ///
+    /// Default is no limit.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use tonic::transport::{server::AcceptRateLimit, Server};
+    /// # let builder = Server::builder();
+    /// builder.accept_rate_limit(AcceptRateLimit::per_ip(10));
+    /// ```
+    #[must_use]
+    pub fn accept_rate_limit(self, limit: AcceptRateLimit) -> Self {
+        Server {
+            accept_rate_limit: Some(limit),
+            ..self
+        }
+    }
+
     /// Set the value of `TCP_NODELAY` option for accepted connections. Enabled by default.
     #[must_use]
     pub fn tcp_nodelay(self, enabled: bool) -> Self {
@@ -376,6 +685,37 @@ impl<L> Server<L> {
         }
     }
 
+    /// Sets the max size of the data buffered by each stream to send to the clients.
+    ///
+    /// The data sent is bounded by the flow control windows granted by the clients, so this only
+    /// bounds the data waiting for them, e.g. to fill links with a high bandwidth-delay product
+    /// without buffering whole messages.
+    ///
+    /// This will default to whatever the default in hyper is. As of v1.12.0, it is 400 KiB.
+    #[must_use]
+    pub fn http2_max_send_buf_size(self, max: impl Into<Option<usize>>) -> Self {
+        Server {
+            http2_max_send_buf_size: max.into(),
+            ..self
+        }
+    }
+
+    /// Sets the size of the HPACK dynamic table of the headers received from the clients, i.e.
+    /// the `SETTINGS_HEADER_TABLE_SIZE` sent to them.
+    ///
+    /// This is not applied to the servers accepting http1 requests, see
+    /// [`Server::accept_http1`], whose connections are served by hyper-util, which doesn't
+    /// support it.
+    ///
+    /// This will default to whatever the default in hyper is. As of v1.12.0, it is 4 KiB.
+    #[must_use]
+    pub fn http2_header_table_size(self, size: impl Into<Option<u32>>) -> Self {
+        Server {
+            http2_header_table_size: size.into(),
+            ..self
+        }
+    }
+
     /// Sets the maximum frame size to use for HTTP2.
     ///
     /// Passing `None` will do nothing.
@@ -405,6 +745,40 @@ impl<L> Server<L> {
         }
     }
 
+    /// Allow the http1 connections of this server to be upgraded to other
+    /// protocols, e.g. to WebSockets.
+    ///
+    /// Services take over upgraded connections with `hyper::upgrade::on`,
+    /// as the WebSocket layers of `tonic-web` do. Without this setting,
+    /// upgrade requests are served as plain http1 requests, and connections
+    /// are never handed over. Only useful along with [`Server::accept_http1`].
+    ///
+    /// Default is `false`.
+    #[must_use]
+    pub fn accept_http1_upgrades(self, accept_http1_upgrades: bool) -> Self {
+        Server {
+            accept_http1_upgrades,
+            ..self
+        }
+    }
+
+    /// Accept and serve the connections of [`Server::serve`] and [`Server::serve_with_shutdown`]
+    /// with `io_uring` rather than epoll, as an [`UringIncoming`] does.
+    ///
+    /// This is experimental, and only available on Linux with the `io-uring` feature. The server
+    /// must then run within [`tokio_uring::start`]. The TCP keepalive and accept rate limit
+    /// settings are not supported, and are ignored.
+    ///
+    /// Default is `false`.
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    #[must_use]
+    pub fn io_uring(self, enabled: bool) -> Self {
+        Server {
+            io_uring: enabled,
+            ..self
+        }
+    }
+
     /// Intercept inbound headers and add a [`tracing::Span`] to each response future.
     #[must_use]
     pub fn trace_fn<F>(self, f: F) -> Self
@@ -541,7 +915,13 @@ impl<L> Server<L> {
             trace_interceptor: self.trace_interceptor,
             concurrency_limit: self.concurrency_limit,
             load_shed: self.load_shed,
+            overload_pushback: self.overload_pushback,
             timeout: self.timeout,
+            default_grpc_timeout: self.default_grpc_timeout,
+            max_grpc_timeout: self.max_grpc_timeout,
+            request_header_timeout: self.request_header_timeout,
+            initial_message_timeout: self.initial_message_timeout,
+            stream_keepalive_interval: self.stream_keepalive_interval,
             #[cfg(feature = "_tls-any")]
             tls: self.tls,
             init_stream_window_size: self.init_stream_window_size,
@@ -549,14 +929,23 @@ impl<L> Server<L> {
             max_concurrent_streams: self.max_concurrent_streams,
             tcp_keepalive: self.tcp_keepalive,
             tcp_nodelay: self.tcp_nodelay,
+            accept_rate_limit: self.accept_rate_limit,
             http2_keepalive_interval: self.http2_keepalive_interval,
             http2_keepalive_timeout: self.http2_keepalive_timeout,
             http2_adaptive_window: self.http2_adaptive_window,
             http2_max_pending_accept_reset_streams: self.http2_max_pending_accept_reset_streams,
             http2_max_header_list_size: self.http2_max_header_list_size,
+            http2_max_send_buf_size: self.http2_max_send_buf_size,
+            http2_header_table_size: self.http2_header_table_size,
             max_frame_size: self.max_frame_size,
             accept_http1: self.accept_http1,
+            accept_http1_upgrades: self.accept_http1_upgrades,
+            #[cfg(all(feature = "io-uring", target_os = "linux"))]
+            io_uring: self.io_uring,
             max_connection_age: self.max_connection_age,
+            graceful_shutdown_timeout: self.graceful_shutdown_timeout,
+            timer: self.timer,
+            executor: self.executor,
         }
     }
 
@@ -564,7 +953,15 @@ impl<L> Server<L> {
         Ok(TcpIncoming::bind(addr)
             .map_err(super::Error::from_source)?
             .with_nodelay(Some(self.tcp_nodelay))
-            .with_keepalive(self.tcp_keepalive))
+            .with_keepalive(self.tcp_keepalive)
+            .with_accept_rate_limit(self.accept_rate_limit.clone()))
+    }
+
+    #[cfg(all(feature = "io-uring", target_os = "linux"))]
+    fn bind_uring_incoming(&self, addr: SocketAddr) -> Result<UringIncoming, super::Error> {
+        Ok(UringIncoming::bind(addr)
+            .map_err(super::Error::from_source)?
+            .with_nodelay(Some(self.tcp_nodelay)))
     }
 
     /// Serve the service.
@@ -578,6 +975,12 @@ impl<L> Server<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::BoxError>,
     {
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        if self.io_uring {
+            let incoming = self.bind_uring_incoming(addr)?;
+            return self.serve_with_incoming(svc, incoming).await;
+        }
+
         let incoming = self.bind_incoming(addr)?;
         self.serve_with_incoming(svc, incoming).await
     }
@@ -599,6 +1002,14 @@ impl<L> Server<L> {
         ResBody: http_body::Body<Data = Bytes> + Send + 'static,
         ResBody::Error: Into<crate::BoxError>,
     {
+        #[cfg(all(feature = "io-uring", target_os = "linux"))]
+        if self.io_uring {
+            let incoming = self.bind_uring_incoming(addr)?;
+            return self
+                .serve_with_incoming_shutdown(svc, incoming, signal)
+                .await;
+        }
+
         let incoming = self.bind_incoming(addr)?;
         self.serve_with_incoming_shutdown(svc, incoming, signal)
             .await
@@ -671,19 +1082,35 @@ impl<L> Server<L> {
         let trace_interceptor = self.trace_interceptor.clone();
         let concurrency_limit = self.concurrency_limit;
         let load_shed = self.load_shed;
+        let pushback = self
+            .overload_pushback
+            .filter(|_| load_shed)
+            .zip(concurrency_limit)
+            .map(|(base_delay, limit)| Load::new(base_delay, limit));
         let init_connection_window_size = self.init_connection_window_size;
         let init_stream_window_size = self.init_stream_window_size;
         let max_concurrent_streams = self.max_concurrent_streams;
         let timeout = self.timeout;
+        let default_grpc_timeout = self.default_grpc_timeout;
+        let max_grpc_timeout = self.max_grpc_timeout;
+        let request_header_timeout = self.request_header_timeout;
+        let initial_message_timeout = self.initial_message_timeout;
+        let stream_keepalive_interval = self.stream_keepalive_interval;
         let max_header_list_size = self.http2_max_header_list_size;
+        let max_send_buf_size = self.http2_max_send_buf_size;
+        let header_table_size = self.http2_header_table_size;
         let max_frame_size = self.max_frame_size;
         let http2_only = !self.accept_http1;
+        let accept_http1_upgrades = self.accept_http1_upgrades;
 
         let http2_keepalive_interval = self.http2_keepalive_interval;
         let http2_keepalive_timeout = self.http2_keepalive_timeout;
         let http2_adaptive_window = self.http2_adaptive_window;
         let http2_max_pending_accept_reset_streams = self.http2_max_pending_accept_reset_streams;
         let max_connection_age = self.max_connection_age;
+        let graceful_shutdown_timeout = self.graceful_shutdown_timeout;
+        let timer = self.timer;
+        let executor = self.executor;
 
         let svc = self.service_builder.service(svc);
 
@@ -696,35 +1123,61 @@ impl<L> Server<L> {
             inner: svc,
             concurrency_limit,
             load_shed,
+            pushback,
             timeout,
+            default_grpc_timeout,
+            max_grpc_timeout,
+            initial_message_timeout,
+            stream_keepalive_interval,
             trace_interceptor,
+            timer: timer.clone(),
             _io: PhantomData,
         };
 
-        let server = {
-            let mut builder = ConnectionBuilder::new(TokioExecutor::new());
+        // The connections of http2 only servers are served by hyper directly, as the builder of
+        // hyper-util doesn't support every HTTP/2 setting.
+        macro_rules! configure_http2 {
+            ($builder:expr) => {{
+                let builder = &mut $builder;
+                builder
+                    .timer(timer.clone())
+                    .initial_connection_window_size(init_connection_window_size)
+                    .initial_stream_window_size(init_stream_window_size)
+                    .max_concurrent_streams(max_concurrent_streams)
+                    .keep_alive_interval(http2_keepalive_interval)
+                    .keep_alive_timeout(http2_keepalive_timeout)
+                    .adaptive_window(http2_adaptive_window.unwrap_or_default())
+                    .max_pending_accept_reset_streams(http2_max_pending_accept_reset_streams)
+                    .max_frame_size(max_frame_size);
+
+                if let Some(max_header_list_size) = max_header_list_size {
+                    builder.max_header_list_size(max_header_list_size);
+                }
 
-            if http2_only {
-                builder = builder.http2_only();
-            }
+                if let Some(max_send_buf_size) = max_send_buf_size {
+                    builder.max_send_buf_size(max_send_buf_size);
+                }
+            }};
+        }
+
+        let server = if http2_only {
+            let mut builder = Http2Builder::new(executor.clone());
+            configure_http2!(builder);
+            builder.header_table_size(header_table_size);
 
-            builder
-                .http2()
-                .timer(TokioTimer::new())
-                .initial_connection_window_size(init_connection_window_size)
-                .initial_stream_window_size(init_stream_window_size)
-                .max_concurrent_streams(max_concurrent_streams)
-                .keep_alive_interval(http2_keepalive_interval)
-                .keep_alive_timeout(http2_keepalive_timeout)
-                .adaptive_window(http2_adaptive_window.unwrap_or_default())
-                .max_pending_accept_reset_streams(http2_max_pending_accept_reset_streams)
-                .max_frame_size(max_frame_size);
-
-            if let Some(max_header_list_size) = max_header_list_size {
-                builder.http2().max_header_list_size(max_header_list_size);
+            ServerBuilder::Http2(builder)
+        } else {
+            let mut builder = ConnectionBuilder::new(executor.clone());
+            configure_http2!(builder.http2());
+
+            if let Some(request_header_timeout) = request_header_timeout {
+                builder
+                    .http1()
+                    .timer(timer.clone())
+                    .header_read_timeout(request_header_timeout);
             }
 
-            builder
+            ServerBuilder::Auto(builder)
         };
 
         let (signal_tx, signal_rx) = tokio::sync::watch::channel(());
@@ -759,10 +1212,21 @@ impl<L> Server<L> {
                         .await
                         .map_err(super::Error::from_source)?;
 
-                    let hyper_io = TokioIo::new(io);
-                    let hyper_svc = TowerToHyperService::new(req_svc.map_request(|req: Request<Incoming>| req.map(Body::new)));
+                    let (shutdown, drain) = ConnectionShutdown::new(graceful.then(|| signal_rx.clone()), graceful_shutdown_timeout);
+
+                    let peer_settings = SharedPeerSettings::default();
+                    let hyper_io = TokioIo::new(PeerSettingsIo::server(io, peer_settings.clone()));
+                    let res_settings = peer_settings.clone();
+                    let hyper_svc = TowerToHyperService::new(req_svc.map_request(move |req: Request<Incoming>| {
+                        let mut req = req.map(Body::new);
+                        req.extensions_mut().insert(drain.clone());
+                        if let Some(settings) = peer_settings.get() {
+                            req.extensions_mut().insert(settings);
+                        }
+                        req
+                    }).map_response(move |res| check_header_list_size(res, &res_settings)));
 
-                    serve_connection(hyper_io, hyper_svc, server.clone(), graceful.then(|| signal_rx.clone()), max_connection_age);
+                    serve_connection(hyper_io, hyper_svc, server.clone(), accept_http1_upgrades, shutdown, max_connection_age, &executor, &timer);
                 }
             }
         }
@@ -783,14 +1247,34 @@ impl<L> Server<L> {
     }
 }
 
+// Replaces the responses whose headers exceed the max header list size of the client, which would
+// reset their stream, with a status saying so.
+fn check_header_list_size(res: Response<Body>, settings: &SharedPeerSettings) -> Response<Body> {
+    let exceeded = settings
+        .get()
+        .and_then(|settings| settings.header_list_size_exceeded(res.headers()));
+    match exceeded {
+        Some((size, max)) => Status::resource_exhausted(format!(
+            "response headers of {size} bytes exceed the max header list size of the client \
+             ({max} bytes)"
+        ))
+        .into_http(),
+        None => res,
+    }
+}
+
 // This is moved to its own function as a way to get around
 // https://github.com/rust-lang/rust/issues/102211
+#[allow(clippy::too_many_arguments)]
 fn serve_connection<B, IO, S, E>(
     hyper_io: IO,
     hyper_svc: S,
-    builder: ConnectionBuilder<E>,
-    mut watcher: Option<tokio::sync::watch::Receiver<()>>,
+    builder: ServerBuilder<E>,
+    accept_http1_upgrades: bool,
+    shutdown: ConnectionShutdown,
     max_connection_age: Option<Duration>,
+    executor: &SharedExec,
+    timer: &SharedTimer,
 ) where
     B: http_body::Body + Send + 'static,
     B::Data: Send,
@@ -801,15 +1285,34 @@ fn serve_connection<B, IO, S, E>(
     S::Error: Into<Box<dyn std::error::Error + Send + Sync>> + Send,
     E: HttpServerConnExec<S::Future, B> + Send + Sync + 'static,
 {
-    tokio::spawn(async move {
+    let timer = timer.clone();
+    let ConnectionShutdown {
+        signal: mut watcher,
+        timeout,
+        drain,
+    } = shutdown;
+    executor.execute(async move {
         {
             let mut sig = pin!(Fuse {
                 inner: watcher.as_mut().map(|w| w.changed()),
             });
 
-            let mut conn = pin!(builder.serve_connection(hyper_io, hyper_svc));
+            let mut conn = pin!(match &builder {
+                ServerBuilder::Http2(builder) => {
+                    ServingConnection::Http2(builder.serve_connection(hyper_io, hyper_svc))
+                }
+                ServerBuilder::Auto(builder) if accept_http1_upgrades => {
+                    ServingConnection::WithUpgrades(
+                        builder.serve_connection_with_upgrades(hyper_io, hyper_svc),
+                    )
+                }
+                ServerBuilder::Auto(builder) => {
+                    ServingConnection::Plain(builder.serve_connection(hyper_io, hyper_svc))
+                }
+            });
 
-            let mut sleep = pin!(sleep_or_pending(max_connection_age));
+            let mut sleep = pin!(sleep_or_pending(&timer, max_connection_age));
+            let mut deadline = pin!(sleep_or_pending(&timer, None));
 
             loop {
                 tokio::select! {
@@ -821,10 +1324,20 @@ fn serve_connection<B, IO, S, E>(
                     },
                     _ = &mut sleep  => {
                         conn.as_mut().graceful_shutdown();
-                        sleep.set(sleep_or_pending(None));
+                        sleep.set(sleep_or_pending(&timer, None));
+                        if !drain.send_replace(true) {
+                            deadline.set(sleep_or_pending(&timer, timeout));
+                        }
                     },
                     _ = &mut sig => {
                         conn.as_mut().graceful_shutdown();
+                        if !drain.send_replace(true) {
+                            deadline.set(sleep_or_pending(&timer, timeout));
+                        }
+                    },
+                    _ = &mut deadline => {
+                        debug!("graceful shutdown timed out, closing connection");
+                        break;
                     }
                 }
             }
@@ -835,9 +1348,9 @@ fn serve_connection<B, IO, S, E>(
     });
 }
 
-async fn sleep_or_pending(wait_for: Option<Duration>) {
+async fn sleep_or_pending(timer: &SharedTimer, wait_for: Option<Duration>) {
     match wait_for {
-        Some(wait) => tokio::time::sleep(wait).await,
+        Some(wait) => hyper::rt::Timer::sleep(timer, wait).await,
         None => future::pending().await,
     };
 }
@@ -889,6 +1402,21 @@ impl<L> Router<L> {
         self
     }
 
+    /// Set the service of the requests that are not gRPC requests, e.g. an [`axum::Router`]
+    /// serving a REST API on the same listener as the gRPC services.
+    ///
+    /// See [`Routes::http_service`] for details. Enable [`Server::accept_http1`] to serve
+    /// HTTP/1 clients.
+    pub fn http_service<S>(mut self, svc: S) -> Self
+    where
+        S: Service<Request<Body>, Error = Infallible> + Clone + Send + Sync + 'static,
+        S::Response: axum::response::IntoResponse,
+        S::Future: Send + 'static,
+    {
+        self.routes = self.routes.http_service(svc);
+        self
+    }
+
     /// Consume this [`Server`] creating a future that will execute the server
     /// on [tokio]'s default executor.
     ///
@@ -1002,6 +1530,7 @@ impl<L> fmt::Debug for Server<L> {
 struct Svc<S> {
     inner: S,
     trace_interceptor: Option<TraceInterceptor>,
+    connection_id: ConnectionId,
 }
 
 impl<S, ResBody> Service<Request<Body>> for Svc<S>
@@ -1020,6 +1549,8 @@ where
     }
 
     fn call(&mut self, mut req: Request<Body>) -> Self::Future {
+        req.extensions_mut().insert(self.connection_id);
+
         let span = if let Some(trace_interceptor) = &self.trace_interceptor {
             let (parts, body) = req.into_parts();
             let bodyless_request = Request::from_parts(parts, ());
@@ -1077,9 +1608,15 @@ impl<S> fmt::Debug for Svc<S> {
 struct MakeSvc<S, IO> {
     concurrency_limit: Option<usize>,
     load_shed: bool,
+    pushback: Option<Load>,
     timeout: Option<Duration>,
+    default_grpc_timeout: Option<Duration>,
+    max_grpc_timeout: Option<Duration>,
+    initial_message_timeout: Option<Duration>,
+    stream_keepalive_interval: Option<Duration>,
     inner: S,
     trace_interceptor: Option<TraceInterceptor>,
+    timer: SharedTimer,
     _io: PhantomData<fn() -> IO>,
 }
 
@@ -1106,13 +1643,27 @@ where
         let svc = self.inner.clone();
         let concurrency_limit = self.concurrency_limit;
         let timeout = self.timeout;
+        let default_grpc_timeout = self.default_grpc_timeout;
+        let max_grpc_timeout = self.max_grpc_timeout;
+        let initial_message_timeout = self.initial_message_timeout;
+        let stream_keepalive_interval = self.stream_keepalive_interval;
         let trace_interceptor = self.trace_interceptor.clone();
 
         let svc = ServiceBuilder::new()
+            .layer_fn(|s| StreamKeepalive::new(s, stream_keepalive_interval))
+            .layer_fn(|s| InitialMessageTimeout::new(s, initial_message_timeout))
             .layer(RecoverErrorLayer::new())
+            .option_layer(
+                self.pushback
+                    .clone()
+                    .map(|load| layer_fn(move |s| Pushback::new(s, load.clone()))),
+            )
             .option_layer(self.load_shed.then_some(LoadShedLayer::new()))
             .option_layer(concurrency_limit.map(ConcurrencyLimitLayer::new))
-            .layer_fn(|s| GrpcTimeout::new(s, timeout))
+            .layer_fn(|s| {
+                GrpcTimeout::new(s, timeout, self.timer.clone())
+                    .with_client_timeout_limits(default_grpc_timeout, max_grpc_timeout)
+            })
             .service(svc);
 
         let svc = ServiceBuilder::new()
@@ -1121,12 +1672,64 @@ where
             .service(Svc {
                 inner: svc,
                 trace_interceptor,
+                connection_id: ConnectionId::next(),
             });
 
         future::ready(Ok(svc))
     }
 }
 
+// The builder of the connections of a server, served by hyper for http2 only servers, and by
+// hyper-util otherwise.
+#[derive(Clone)]
+enum ServerBuilder<E> {
+    Http2(Http2Builder<E>),
+    Auto(ConnectionBuilder<E>),
+}
+
+// A connection served by hyper, or by hyper-util with or without support for http1 upgrades.
+#[pin_project(project = ServingConnectionProj)]
+enum ServingConnection<H, C, U> {
+    Http2(#[pin] H),
+    Plain(#[pin] C),
+    WithUpgrades(#[pin] U),
+}
+
+impl<H, C, U> ServingConnection<H, C, U>
+where
+    H: GracefulConnection,
+    C: GracefulConnection,
+    U: GracefulConnection,
+{
+    fn graceful_shutdown(self: Pin<&mut Self>) {
+        match self.project() {
+            ServingConnectionProj::Http2(conn) => conn.graceful_shutdown(),
+            ServingConnectionProj::Plain(conn) => conn.graceful_shutdown(),
+            ServingConnectionProj::WithUpgrades(conn) => conn.graceful_shutdown(),
+        }
+    }
+}
+
+impl<H, C, U> Future for ServingConnection<H, C, U>
+where
+    H: GracefulConnection,
+    H::Error: Into<crate::BoxError>,
+    C: GracefulConnection,
+    C::Error: Into<crate::BoxError>,
+    U: GracefulConnection,
+    U::Error: Into<crate::BoxError>,
+{
+    type Output = Result<(), crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.project() {
+            ServingConnectionProj::Http2(conn) => conn.poll(cx).map_err(Into::into),
+            ServingConnectionProj::Plain(conn) => conn.poll(cx).map_err(Into::into),
+            ServingConnectionProj::WithUpgrades(conn) => conn.poll(cx).map_err(Into::into),
+        }
+    }
+}
+
 // From `futures-util` crate, borrowed since this is the only dependency tonic requires.
 // LICENSE: MIT or Apache-2.0
 // A future which only yields `Poll::Ready` once, and thereafter yields `Poll::Pending`.
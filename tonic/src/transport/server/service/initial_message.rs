@@ -0,0 +1,95 @@
+use crate::{body::Body, Status};
+use bytes::Bytes;
+use http::Request;
+use http_body::Frame;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::time::Sleep;
+use tower_service::Service;
+
+/// Fails the request bodies without a frame within the timeout after the stream was opened, so
+/// the handlers waiting on them end.
+#[derive(Debug, Clone)]
+pub(crate) struct InitialMessageTimeout<S> {
+    inner: S,
+    timeout: Option<Duration>,
+}
+
+impl<S> InitialMessageTimeout<S> {
+    pub(crate) fn new(inner: S, timeout: Option<Duration>) -> Self {
+        Self { inner, timeout }
+    }
+}
+
+impl<S> Service<Request<Body>> for InitialMessageTimeout<S>
+where
+    S: Service<Request<Body>>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        match self.timeout {
+            Some(timeout) => self.inner.call(req.map(|inner| {
+                Body::new(TimeoutBody {
+                    inner,
+                    sleep: Some(tokio::time::sleep(timeout)),
+                })
+            })),
+            None => self.inner.call(req),
+        }
+    }
+}
+
+#[pin_project]
+struct TimeoutBody {
+    #[pin]
+    inner: Body,
+    // Cleared once the first frame is received.
+    #[pin]
+    sleep: Option<Sleep>,
+}
+
+impl http_body::Body for TimeoutBody {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+
+        if let ready @ Poll::Ready(_) = this.inner.poll_frame(cx) {
+            this.sleep.set(None);
+            return ready;
+        }
+
+        if let Some(sleep) = this.sleep.as_pin_mut() {
+            ready!(sleep.poll(cx));
+            return Poll::Ready(Some(Err(Status::deadline_exceeded(
+                "timed out waiting for the first request message",
+            ))));
+        }
+
+        Poll::Pending
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -1,6 +1,15 @@
+mod initial_message;
+pub(crate) use self::initial_message::InitialMessageTimeout;
+
 mod io;
 pub(crate) use self::io::{ConnectInfoLayer, ServerIo};
 
+mod pushback;
+pub(crate) use self::pushback::{Load, Pushback};
+
+mod stream_keepalive;
+pub(crate) use self::stream_keepalive::StreamKeepalive;
+
 #[cfg(feature = "_tls-any")]
 mod tls;
 #[cfg(feature = "_tls-any")]
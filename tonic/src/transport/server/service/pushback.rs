@@ -0,0 +1,226 @@
+use crate::{Code, Status};
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tower::load_shed::error::Overloaded;
+use tower_service::Service;
+
+// The largest multiple of the base delay recommended to clients.
+const MAX_FACTOR: u32 = 16;
+
+/// The load of a server, shared by the connections it serves.
+#[derive(Debug, Clone)]
+pub(crate) struct Load {
+    base_delay: Duration,
+    concurrency_limit: usize,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Load {
+    pub(crate) fn new(base_delay: Duration, concurrency_limit: usize) -> Self {
+        Self {
+            base_delay,
+            concurrency_limit: concurrency_limit.max(1),
+            in_flight: Arc::default(),
+        }
+    }
+
+    // The delay recommended to the clients of rejected requests: the base delay, multiplied by the
+    // number of connections' worth of requests in flight over the whole server.
+    fn retry_delay(&self) -> Duration {
+        let in_flight = self.in_flight.load(Ordering::Relaxed);
+        let factor = in_flight.div_ceil(self.concurrency_limit).max(1);
+        let factor = u32::try_from(factor).unwrap_or(MAX_FACTOR).min(MAX_FACTOR);
+        self.base_delay.saturating_mul(factor)
+    }
+}
+
+/// Answers the requests rejected by load shedding with `UNAVAILABLE` and a `RetryInfo` detail,
+/// so that clients back off for longer as the load of the server grows.
+#[derive(Debug, Clone)]
+pub(crate) struct Pushback<S> {
+    inner: S,
+    load: Load,
+}
+
+impl<S> Pushback<S> {
+    pub(crate) fn new(inner: S, load: Load) -> Self {
+        Self { inner, load }
+    }
+}
+
+impl<S, Req> Service<Req> for Pushback<S>
+where
+    S: Service<Req>,
+    S::Error: Into<crate::BoxError>,
+{
+    type Response = S::Response;
+    type Error = crate::BoxError;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        self.load.in_flight.fetch_add(1, Ordering::Relaxed);
+        ResponseFuture {
+            inner: self.inner.call(req),
+            guard: InFlight(self.load.clone()),
+        }
+    }
+}
+
+// Counts a request in flight until its response future is dropped.
+#[derive(Debug)]
+struct InFlight(Load);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[pin_project]
+pub(crate) struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    guard: InFlight,
+}
+
+impl<F, T, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<T, E>>,
+    E: Into<crate::BoxError>,
+{
+    type Output = Result<T, crate::BoxError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        match ready!(this.inner.poll(cx)).map_err(Into::into) {
+            Err(e) if e.is::<Overloaded>() => {
+                let delay = this.guard.0.retry_delay();
+                Poll::Ready(Err(overloaded(delay).into()))
+            }
+            result => Poll::Ready(result),
+        }
+    }
+}
+
+const MESSAGE: &str = "server overloaded, retry later";
+
+// An `UNAVAILABLE` status with the `google.rpc.Status` details of a `RetryInfo` recommending
+// `delay`.
+#[cfg(feature = "prost")]
+fn overloaded(delay: Duration) -> Status {
+    use prost::Message;
+
+    let retry_info = details::RetryInfo {
+        retry_delay: Some(details::Duration {
+            seconds: i64::try_from(delay.as_secs()).unwrap_or(i64::MAX),
+            nanos: delay.subsec_nanos() as i32,
+        }),
+    };
+    let status = details::Status {
+        code: Code::Unavailable as i32,
+        message: MESSAGE.to_owned(),
+        details: vec![details::Any {
+            type_url: details::RETRY_INFO.to_owned(),
+            value: retry_info.encode_to_vec(),
+        }],
+    };
+
+    Status::with_details(Code::Unavailable, MESSAGE, status.encode_to_vec().into())
+}
+
+// Without prost, the `RetryInfo` detail can't be encoded.
+#[cfg(not(feature = "prost"))]
+fn overloaded(_delay: Duration) -> Status {
+    Status::new(Code::Unavailable, MESSAGE)
+}
+
+// The standard messages of the details, which tonic doesn't depend on.
+#[cfg(feature = "prost")]
+mod details {
+    pub(super) const RETRY_INFO: &str = "type.googleapis.com/google.rpc.RetryInfo";
+
+    /// `google.rpc.Status`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct Status {
+        #[prost(int32, tag = "1")]
+        pub(super) code: i32,
+        #[prost(string, tag = "2")]
+        pub(super) message: String,
+        #[prost(message, repeated, tag = "3")]
+        pub(super) details: Vec<Any>,
+    }
+
+    /// `google.protobuf.Any`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct Any {
+        #[prost(string, tag = "1")]
+        pub(super) type_url: String,
+        #[prost(bytes = "vec", tag = "2")]
+        pub(super) value: Vec<u8>,
+    }
+
+    /// `google.rpc.RetryInfo`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct RetryInfo {
+        #[prost(message, optional, tag = "1")]
+        pub(super) retry_delay: Option<Duration>,
+    }
+
+    /// `google.protobuf.Duration`
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(super) struct Duration {
+        #[prost(int64, tag = "1")]
+        pub(super) seconds: i64,
+        #[prost(int32, tag = "2")]
+        pub(super) nanos: i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delays_grow_with_load() {
+        let load = Load::new(Duration::from_millis(100), 10);
+        assert_eq!(load.retry_delay(), Duration::from_millis(100));
+
+        load.in_flight.store(25, Ordering::Relaxed);
+        assert_eq!(load.retry_delay(), Duration::from_millis(300));
+
+        load.in_flight.store(10_000, Ordering::Relaxed);
+        assert_eq!(load.retry_delay(), Duration::from_millis(1_600));
+    }
+
+    #[test]
+    #[cfg(feature = "prost")]
+    fn overloaded_details_carry_retry_info() {
+        use prost::Message;
+
+        let status = overloaded(Duration::from_millis(1_500));
+        assert_eq!(status.code(), Code::Unavailable);
+
+        let details = details::Status::decode(status.details()).unwrap();
+        assert_eq!(details.code, Code::Unavailable as i32);
+        assert_eq!(details.message, MESSAGE);
+        assert_eq!(details.details.len(), 1);
+        assert_eq!(details.details[0].type_url, details::RETRY_INFO);
+
+        let retry_info = details::RetryInfo::decode(&*details.details[0].value).unwrap();
+        let delay = retry_info.retry_delay.unwrap();
+        assert_eq!((delay.seconds, delay.nanos), (1, 500_000_000));
+    }
+}
@@ -0,0 +1,119 @@
+use bytes::Bytes;
+use http::{Request, Response};
+use http_body::Frame;
+use pin_project::pin_project;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{ready, Context, Poll},
+    time::Duration,
+};
+use tokio::time::{Instant, Sleep};
+use tower_service::Service;
+
+/// Sends an empty `DATA` frame on the response streams without a frame within the interval, so
+/// middleboxes closing idle connections see traffic on long-lived streams.
+///
+/// The empty frames need no flow control capacity and carry no message, so clients don't see them.
+#[derive(Debug, Clone)]
+pub(crate) struct StreamKeepalive<S> {
+    inner: S,
+    interval: Option<Duration>,
+}
+
+impl<S> StreamKeepalive<S> {
+    pub(crate) fn new(inner: S, interval: Option<Duration>) -> Self {
+        Self { inner, interval }
+    }
+}
+
+impl<S, ReqBody, ResBody> Service<Request<ReqBody>> for StreamKeepalive<S>
+where
+    S: Service<Request<ReqBody>, Response = Response<ResBody>>,
+{
+    type Response = Response<KeepaliveBody<ResBody>>;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Future>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+        ResponseFuture {
+            inner: self.inner.call(req),
+            interval: self.interval,
+        }
+    }
+}
+
+#[pin_project]
+pub(crate) struct ResponseFuture<F> {
+    #[pin]
+    inner: F,
+    interval: Option<Duration>,
+}
+
+impl<F, B, E> Future for ResponseFuture<F>
+where
+    F: Future<Output = Result<Response<B>, E>>,
+{
+    type Output = Result<Response<KeepaliveBody<B>>, E>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let res = ready!(this.inner.poll(cx))?;
+        let interval = *this.interval;
+        Poll::Ready(Ok(res.map(|inner| KeepaliveBody {
+            inner,
+            interval,
+            sleep: interval.map(tokio::time::sleep),
+        })))
+    }
+}
+
+#[pin_project]
+pub(crate) struct KeepaliveBody<B> {
+    #[pin]
+    inner: B,
+    interval: Option<Duration>,
+    // Reset each time a frame is sent.
+    #[pin]
+    sleep: Option<Sleep>,
+}
+
+impl<B> http_body::Body for KeepaliveBody<B>
+where
+    B: http_body::Body<Data = Bytes>,
+{
+    type Data = Bytes;
+    type Error = B::Error;
+
+    fn poll_frame(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        let mut this = self.project();
+        let (Some(interval), Some(mut sleep)) = (*this.interval, this.sleep.as_mut().as_pin_mut())
+        else {
+            return this.inner.poll_frame(cx);
+        };
+
+        if let ready @ Poll::Ready(_) = this.inner.poll_frame(cx) {
+            sleep.reset(Instant::now() + interval);
+            return ready;
+        }
+
+        ready!(sleep.as_mut().poll(cx));
+        sleep.reset(Instant::now() + interval);
+        Poll::Ready(Some(Ok(Frame::data(Bytes::new()))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> http_body::SizeHint {
+        self.inner.size_hint()
+    }
+}
@@ -0,0 +1,235 @@
+use std::{
+    io,
+    net::{Shutdown, SocketAddr, TcpListener as StdTcpListener},
+    pin::{pin, Pin},
+    task::{Context, Poll},
+};
+
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadBuf},
+    sync::mpsc,
+};
+use tokio_stream::Stream;
+use tokio_uring::{
+    buf::BoundedBuf,
+    net::{TcpListener, TcpStream},
+};
+use tracing::debug;
+
+use super::{Connected, TcpConnectInfo};
+
+// The size of the buffers of each connection, in each direction.
+const BUF_SIZE: usize = 64 * 1024;
+
+/// Binds a socket address for a [Router](super::Router), accepting and serving its connections
+/// with [`io_uring`] rather than epoll.
+///
+/// This backend is experimental. The connections are accepted, read and written by tasks of the
+/// [`tokio-uring`] runtime, which copy the data from and to the [`UringStream`]s served by the
+/// server, so the server must run within [`tokio_uring::start`]:
+///
+/// ```no_run
+/// # use tonic::transport::{server::UringIncoming, Server};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let router = Server::builder().add_routes(Default::default());
+/// tokio_uring::start(async {
+///     let incoming = UringIncoming::bind("127.0.0.1:50051".parse()?)?;
+///     router.serve_with_incoming(incoming).await?;
+///     Ok(())
+/// })
+/// # }
+/// ```
+///
+/// [`io_uring`]: https://man7.org/linux/man-pages/man7/io_uring.7.html
+/// [`tokio-uring`]: https://docs.rs/tokio-uring
+#[derive(Debug)]
+pub struct UringIncoming {
+    listener: Option<StdTcpListener>,
+    local_addr: SocketAddr,
+    nodelay: Option<bool>,
+    connections: Option<mpsc::Receiver<io::Result<UringStream>>>,
+}
+
+impl UringIncoming {
+    /// Creates an instance by binding (opening) the specified socket address.
+    ///
+    /// The connections are only accepted once the instance is polled, which must happen within
+    /// [`tokio_uring::start`].
+    pub fn bind(addr: SocketAddr) -> io::Result<Self> {
+        let listener = StdTcpListener::bind(addr)?;
+
+        Ok(Self {
+            local_addr: listener.local_addr()?,
+            listener: Some(listener),
+            nodelay: None,
+            connections: None,
+        })
+    }
+
+    /// Sets the `TCP_NODELAY` option on the accepted connection.
+    pub fn with_nodelay(self, nodelay: Option<bool>) -> Self {
+        Self { nodelay, ..self }
+    }
+
+    /// Returns the local address that this instance is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Stream for UringIncoming {
+    type Item = io::Result<UringStream>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(listener) = self.listener.take() {
+            let (tx, rx) = mpsc::channel(1);
+            tokio_uring::spawn(accept(
+                TcpListener::from_std(listener),
+                self.local_addr,
+                self.nodelay,
+                tx,
+            ));
+            self.connections = Some(rx);
+        }
+
+        match &mut self.connections {
+            Some(connections) => connections.poll_recv(cx),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+// Accepts the connections of the listener, as long as the incoming stream is alive.
+async fn accept(
+    listener: TcpListener,
+    local_addr: SocketAddr,
+    nodelay: Option<bool>,
+    tx: mpsc::Sender<io::Result<UringStream>>,
+) {
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            () = tx.closed() => break,
+        };
+
+        let stream = accepted.map(|(stream, remote_addr)| {
+            if let Some(nodelay) = nodelay {
+                if let Err(e) = stream.set_nodelay(nodelay) {
+                    debug!("error trying to set TCP_NODELAY: {e}");
+                }
+            }
+
+            let (io, peer) = tokio::io::duplex(BUF_SIZE);
+            tokio_uring::spawn(pump(stream, peer));
+
+            UringStream {
+                io,
+                connect_info: TcpConnectInfo {
+                    local_addr: Some(local_addr),
+                    remote_addr: Some(remote_addr),
+                },
+            }
+        });
+
+        if tx.send(stream).await.is_err() {
+            break;
+        }
+    }
+}
+
+// Copies the data read from the socket to the server, and the data written by the server to the
+// socket, until the server is done with the connection.
+async fn pump(stream: TcpStream, io: DuplexStream) {
+    let (mut reader, mut writer) = tokio::io::split(io);
+
+    let incoming = async {
+        let mut buf = Vec::with_capacity(BUF_SIZE);
+        loop {
+            buf.clear();
+            let (res, b) = stream.read(buf).await;
+            buf = b;
+            match res {
+                Ok(0) => break,
+                Ok(_) => {
+                    if writer.write_all(&buf).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    debug!("error reading from the connection: {e}");
+                    break;
+                }
+            }
+        }
+        let _ = writer.shutdown().await;
+    };
+
+    let outgoing = async {
+        let mut buf = vec![0; BUF_SIZE];
+        loop {
+            let n = match reader.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            };
+            let (res, b) = stream.write_all(buf.slice(..n)).await;
+            buf = b.into_inner();
+            if let Err(e) = res {
+                debug!("error writing to the connection: {e}");
+                break;
+            }
+        }
+        let _ = stream.shutdown(Shutdown::Write);
+    };
+
+    // The peer closing its side doesn't end the connection until the server is done writing,
+    // while the server being done ends it regardless of the peer.
+    let mut incoming = pin!(incoming);
+    let mut outgoing = pin!(outgoing);
+    tokio::select! {
+        () = &mut outgoing => {}
+        () = &mut incoming => outgoing.await,
+    }
+}
+
+/// A connection accepted by an [`UringIncoming`].
+#[derive(Debug)]
+pub struct UringStream {
+    io: DuplexStream,
+    connect_info: TcpConnectInfo,
+}
+
+impl Connected for UringStream {
+    type ConnectInfo = TcpConnectInfo;
+
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.connect_info.clone()
+    }
+}
+
+impl AsyncRead for UringStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for UringStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
@@ -1,9 +1,11 @@
-use crate::transport::channel::BoxFuture;
 use hyper_util::rt::TokioExecutor;
-use std::{future::Future, sync::Arc};
+use std::{future::Future, pin::Pin, sync::Arc};
 
 pub(crate) use hyper::rt::Executor;
 
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+// The executor of a channel or of a server, spawning their background tasks and connections.
 #[derive(Clone)]
 pub(crate) struct SharedExec {
     inner: Arc<dyn Executor<BoxFuture<'static, ()>> + Send + Sync + 'static>,
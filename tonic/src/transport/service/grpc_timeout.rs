@@ -1,5 +1,7 @@
-use crate::{metadata::GRPC_TIMEOUT_HEADER, TimeoutExpired};
+use super::SharedTimer;
+use crate::{metadata::GRPC_TIMEOUT_HEADER, request::GrpcDeadline, TimeoutExpired};
 use http::{HeaderMap, HeaderValue, Request};
+use hyper::rt::{Sleep, Timer};
 use pin_project::pin_project;
 use std::{
     future::Future,
@@ -7,20 +9,45 @@ use std::{
     task::{ready, Context, Poll},
     time::Duration,
 };
-use tokio::time::Sleep;
 use tower_service::Service;
 
 #[derive(Debug, Clone)]
 pub(crate) struct GrpcTimeout<S> {
     inner: S,
     server_timeout: Option<Duration>,
+    limits: Option<ClientTimeoutLimits>,
+    timer: SharedTimer,
+}
+
+// Limits of the `grpc-timeout` header of the requests received by a server.
+#[derive(Debug, Clone, Copy)]
+struct ClientTimeoutLimits {
+    default: Option<Duration>,
+    max: Option<Duration>,
 }
 
 impl<S> GrpcTimeout<S> {
-    pub(crate) fn new(inner: S, server_timeout: Option<Duration>) -> Self {
+    pub(crate) fn new(inner: S, server_timeout: Option<Duration>, timer: SharedTimer) -> Self {
         Self {
             inner,
             server_timeout,
+            limits: None,
+            timer,
+        }
+    }
+
+    /// Apply `default` to the requests without a `grpc-timeout` header and
+    /// clamp the header to `max`, inserting the resulting deadline into the
+    /// extensions of the requests.
+    #[cfg(feature = "server")]
+    pub(crate) fn with_client_timeout_limits(
+        self,
+        default: Option<Duration>,
+        max: Option<Duration>,
+    ) -> Self {
+        Self {
+            limits: Some(ClientTimeoutLimits { default, max }),
+            ..self
         }
     }
 }
@@ -38,12 +65,19 @@ where
         self.inner.poll_ready(cx).map_err(Into::into)
     }
 
-    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
-        let client_timeout = try_parse_grpc_timeout(req.headers()).unwrap_or_else(|e| {
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let mut client_timeout = try_parse_grpc_timeout(req.headers()).unwrap_or_else(|e| {
             tracing::trace!("Error parsing `grpc-timeout` header {:?}", e);
             None
         });
 
+        if let Some(limits) = self.limits {
+            client_timeout = match (client_timeout.or(limits.default), limits.max) {
+                (Some(timeout), Some(max)) => Some(timeout.min(max)),
+                (timeout, max) => timeout.or(max),
+            };
+        }
+
         // Use the shorter of the two durations, if either are set
         let timeout_duration = match (client_timeout, self.server_timeout) {
             (None, None) => None,
@@ -55,9 +89,16 @@ where
             }
         };
 
+        if let (Some(_), Some(timeout)) = (self.limits, timeout_duration) {
+            req.extensions_mut().insert(GrpcDeadline {
+                deadline: self.timer.now() + timeout,
+                timer: self.timer.clone(),
+            });
+        }
+
         ResponseFuture {
             inner: self.inner.call(req),
-            sleep: timeout_duration.map(tokio::time::sleep),
+            sleep: timeout_duration.map(|timeout| self.timer.sleep(timeout)),
         }
     }
 }
@@ -66,8 +107,7 @@ where
 pub(crate) struct ResponseFuture<F> {
     #[pin]
     inner: F,
-    #[pin]
-    sleep: Option<Sleep>,
+    sleep: Option<Pin<Box<dyn Sleep>>>,
 }
 
 impl<F, Res, E> Future for ResponseFuture<F>
@@ -84,8 +124,8 @@ where
             return ready.map_err(Into::into);
         }
 
-        if let Some(sleep) = this.sleep.as_pin_mut() {
-            ready!(sleep.poll(cx));
+        if let Some(sleep) = this.sleep {
+            ready!(sleep.as_mut().poll(cx));
             return Poll::Ready(Err(TimeoutExpired(()).into()));
         }
 
@@ -1,5 +1,9 @@
+pub(crate) mod executor;
 pub(crate) mod grpc_timeout;
+pub(crate) mod timer;
 #[cfg(feature = "_tls-any")]
 pub(crate) mod tls;
 
+pub(crate) use self::executor::{Executor, SharedExec};
 pub(crate) use self::grpc_timeout::GrpcTimeout;
+pub(crate) use self::timer::SharedTimer;
@@ -0,0 +1,54 @@
+use std::{
+    fmt,
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use hyper::rt::{Sleep, Timer};
+use hyper_util::rt::TokioTimer;
+
+// The timer of a channel or of a server, shared by its timeouts and HTTP/2 keep alive pings.
+#[derive(Clone)]
+pub(crate) struct SharedTimer {
+    inner: Arc<dyn Timer + Send + Sync + 'static>,
+}
+
+impl SharedTimer {
+    pub(crate) fn new<T>(timer: T) -> Self
+    where
+        T: Timer + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(timer),
+        }
+    }
+
+    pub(crate) fn tokio() -> Self {
+        Self::new(TokioTimer::new())
+    }
+}
+
+impl Timer for SharedTimer {
+    fn sleep(&self, duration: Duration) -> Pin<Box<dyn Sleep>> {
+        self.inner.sleep(duration)
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Sleep>> {
+        self.inner.sleep_until(deadline)
+    }
+
+    fn now(&self) -> Instant {
+        self.inner.now()
+    }
+
+    fn reset(&self, sleep: &mut Pin<Box<dyn Sleep>>, new_deadline: Instant) {
+        self.inner.reset(sleep, new_deadline)
+    }
+}
+
+impl fmt::Debug for SharedTimer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedTimer").finish()
+    }
+}
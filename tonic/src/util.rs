@@ -26,3 +26,64 @@ pub(crate) mod base64 {
             .with_decode_padding_mode(DecodePaddingMode::Indifferent),
     );
 }
+
+pub(crate) mod crc32c {
+    // The CRC-32C (Castagnoli) polynomial, reversed.
+    const POLYNOMIAL: u32 = 0x82f6_3b78;
+
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ POLYNOMIAL
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+
+    /// An incremental CRC-32C checksum.
+    #[derive(Debug, Clone, Copy)]
+    pub(crate) struct Crc32c(u32);
+
+    impl Crc32c {
+        pub(crate) fn new() -> Self {
+            Self(!0)
+        }
+
+        pub(crate) fn update(&mut self, data: &[u8]) {
+            for &byte in data {
+                self.0 = TABLE[((self.0 ^ u32::from(byte)) & 0xff) as usize] ^ (self.0 >> 8);
+            }
+        }
+
+        pub(crate) fn finish(self) -> u32 {
+            !self.0
+        }
+    }
+
+    /// The CRC-32C checksum of `data`.
+    pub(crate) fn checksum(data: &[u8]) -> u32 {
+        let mut crc = Crc32c::new();
+        crc.update(data);
+        crc.finish()
+    }
+
+    #[cfg(test)]
+    mod tests {
+        #[test]
+        fn check_value() {
+            assert_eq!(super::checksum(b"123456789"), 0xe306_9283);
+            assert_eq!(super::checksum(b""), 0);
+        }
+    }
+}